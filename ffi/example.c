@@ -0,0 +1,64 @@
+/*
+ * Steps a glider loaded from RLE and prints it, e.g. after `make ffi`:
+ *
+ *     cc -I ffi ffi/example.c target/release/libgame_of_life.a -lpthread -ldl -lm -o example
+ */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "game_of_life.h"
+
+static ConwayStatus print_board(const ConwayGrid *grid) {
+    size_t width = conway_grid_width(grid), height = conway_grid_height(grid);
+    uint8_t *cells = malloc(width * height);
+    ConwayStatus status = cells == NULL ? CONWAY_STATUS_NULL_POINTER
+                                        : conway_grid_read_cells(grid, cells, width * height);
+    if (status == CONWAY_STATUS_OK) {
+        for (size_t y = 0; y < height; y++) {
+            for (size_t x = 0; x < width; x++) {
+                putchar(cells[y * width + x] ? 'O' : '.');
+            }
+            putchar('\n');
+        }
+    }
+    free(cells);
+    return status;
+}
+
+int main(void) {
+    const char *glider = "x = 3, y = 3\nbo$2bo$3o!";
+    ConwayGrid *pattern = conway_grid_load_rle((const uint8_t *)glider, strlen(glider));
+    ConwayGrid *grid = conway_grid_new(8, 8);
+    if (pattern == NULL || grid == NULL) {
+        fprintf(stderr, "couldn't make the boards\n");
+        return 1;
+    }
+    for (size_t y = 0; y < conway_grid_height(pattern); y++) {
+        for (size_t x = 0; x < conway_grid_width(pattern); x++) {
+            conway_grid_set(grid, x, y, conway_grid_get(pattern, x, y) == 1);
+        }
+    }
+    conway_grid_free(pattern);
+
+    ConwayStatus status = conway_grid_step(grid, 4);
+    if (status == CONWAY_STATUS_OK) {
+        status = print_board(grid);
+    }
+    ConwayLifespan lifespan;
+    if (status == CONWAY_STATUS_OK) {
+        status = conway_grid_lifespan(grid, 100, &lifespan);
+    }
+    if (status != CONWAY_STATUS_OK) {
+        fprintf(stderr, "error %d\n", (int)status);
+        conway_grid_free(grid);
+        return 1;
+    }
+    printf("population %zu at generation %llu\n", conway_grid_population(grid),
+           (unsigned long long)conway_grid_generation(grid));
+    /* The glider crashes into the corner and leaves a block */
+    printf("settles with period %llu at generation %llu\n", (unsigned long long)lifespan.period,
+           (unsigned long long)lifespan.settled_at);
+    conway_grid_free(grid);
+    return 0;
+}
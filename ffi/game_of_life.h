@@ -0,0 +1,257 @@
+#ifndef GAME_OF_LIFE_H
+#define GAME_OF_LIFE_H
+
+/* Generated by cbindgen from src/ffi.rs, don't edit by hand */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * Most generations `conway_grid_step` and `conway_grid_lifespan` run in one
+ * call
+ *
+ * A generation takes time in proportion to the board's area, some tens of
+ * milliseconds for a million cells, so a call on a big board can take
+ * minutes. Step a board further with several calls.
+ */
+#define CONWAY_GRID_MAX_STEP 10000
+
+/**
+ * Longest period of a cycle `conway_grid_lifespan` counts a board settling
+ * into, the same as the game's headless report
+ */
+#define CONWAY_LIFESPAN_MAX_PERIOD 30
+
+/**
+ * What a call came to, `CONWAY_STATUS_OK` when it did what was asked; every
+ * other status but `CONWAY_STATUS_PANIC` leaves the board as it was
+ */
+typedef enum ConwayStatus {
+  CONWAY_STATUS_OK = 0,
+  /**
+   * A board or buffer was null
+   */
+  CONWAY_STATUS_NULL_POINTER = 1,
+  /**
+   * A cell was off the board
+   */
+  CONWAY_STATUS_OUT_OF_BOUNDS = 2,
+  /**
+   * A buffer wasn't the size the call needs
+   */
+  CONWAY_STATUS_BUFFER_SIZE = 3,
+  /**
+   * A rulestring that isn't `B<digits>/S<digits>`, or neighbor counts above 8
+   */
+  CONWAY_STATUS_INVALID_RULE = 4,
+  /**
+   * More generations than one call runs
+   */
+  CONWAY_STATUS_TOO_LARGE = 5,
+  /**
+   * The library hit a bug, which may have left the board half changed
+   */
+  CONWAY_STATUS_PANIC = 6,
+} ConwayStatus;
+
+typedef struct ConwayGrid ConwayGrid;
+
+/**
+ * How a board fared over a run of `conway_grid_lifespan`, in generations
+ * counted like `conway_grid_generation`
+ */
+typedef struct ConwayLifespan {
+  /**
+   * Where the run stopped: the generation the board was seen to settle at,
+   * or the last one run
+   */
+  uint64_t generation;
+  /**
+   * Live cells at `generation`
+   */
+  size_t population;
+  /**
+   * Most live cells of any generation run, and the first one that had them
+   */
+  size_t peak_population;
+  uint64_t peak_generation;
+  /**
+   * Whether the board came back to a state it was in at most
+   * `CONWAY_LIFESPAN_MAX_PERIOD` generations before
+   */
+  bool settled;
+  /**
+   * With `settled`, the first generation of the cycle it settled into, and
+   * its period, 1 for a still life
+   */
+  uint64_t settled_at;
+  uint64_t period;
+} ConwayLifespan;
+
+/**
+ * A new board of dead cells, or null when it would have more than
+ * `MAX_PATTERN_CELLS` cells
+ */
+ConwayGrid *conway_grid_new(size_t width, size_t height);
+
+/**
+ * Free a board, doing nothing for null
+ *
+ * # Safety
+ * `grid` is null or a board from this library not freed yet, and isn't used
+ * again afterwards
+ */
+void conway_grid_free(ConwayGrid *grid);
+
+/**
+ * Make the cell at `(x, y)` alive or dead
+ *
+ * # Safety
+ * `grid` is null or a live board from this library
+ */
+ConwayStatus conway_grid_set(ConwayGrid *grid, size_t x, size_t y, bool alive);
+
+/**
+ * 1 when the cell at `(x, y)` is alive, 0 when it is dead and -1 when the
+ * board is null or the cell outside it
+ *
+ * # Safety
+ * `grid` is null or a live board from this library
+ */
+int32_t conway_grid_get(const ConwayGrid *grid, size_t x, size_t y);
+
+/**
+ * Copy every cell into the `len` bytes at `cells`, row by row, 1 for a live
+ * cell and 0 for a dead one; `len` must be the board's width times height
+ *
+ * # Safety
+ * `grid` is null or a live board from this library, and `cells` is null or
+ * points to `len` writable bytes
+ */
+ConwayStatus conway_grid_read_cells(const ConwayGrid *grid, uint8_t *cells, size_t len);
+
+/**
+ * Set every cell from the `len` bytes at `cells`, laid out like
+ * `conway_grid_read_cells` writes them, any byte but 0 a live cell
+ *
+ * # Safety
+ * `grid` is null or a live board from this library, and `cells` is null or
+ * points to `len` readable bytes
+ */
+ConwayStatus conway_grid_write_cells(ConwayGrid *grid, const uint8_t *cells, size_t len);
+
+/**
+ * Advance the board `generations` generations, `CONWAY_STATUS_TOO_LARGE`
+ * above `CONWAY_GRID_MAX_STEP`
+ *
+ * # Safety
+ * `grid` is null or a live board from this library
+ */
+ConwayStatus conway_grid_step(ConwayGrid *grid, uint64_t generations);
+
+/**
+ * Number of live cells, 0 for null
+ *
+ * # Safety
+ * `grid` is null or a live board from this library
+ */
+size_t conway_grid_population(const ConwayGrid *grid);
+
+/**
+ * Number of columns, 0 for null
+ *
+ * # Safety
+ * `grid` is null or a live board from this library
+ */
+size_t conway_grid_width(const ConwayGrid *grid);
+
+/**
+ * Number of rows, 0 for null
+ *
+ * # Safety
+ * `grid` is null or a live board from this library
+ */
+size_t conway_grid_height(const ConwayGrid *grid);
+
+/**
+ * Number of generations the board was stepped, 0 for null
+ *
+ * # Safety
+ * `grid` is null or a live board from this library
+ */
+uint64_t conway_grid_generation(const ConwayGrid *grid);
+
+/**
+ * Write the rule the board steps by to `birth` and `survival`, as masks of
+ * neighbor counts: bit `n` is set when `n` neighbors bring a dead cell to
+ * life, or keep a live one alive
+ *
+ * # Safety
+ * `grid` is null or a live board from this library, and `birth` and
+ * `survival` are null or writable
+ */
+ConwayStatus conway_grid_rule(const ConwayGrid *grid, uint16_t *birth, uint16_t *survival);
+
+/**
+ * Step the board by the rule of the masks `conway_grid_rule` writes,
+ * `CONWAY_STATUS_INVALID_RULE` with a bit above 8 set
+ *
+ * # Safety
+ * `grid` is null or a live board from this library
+ */
+ConwayStatus conway_grid_set_rule(ConwayGrid *grid, uint16_t birth, uint16_t survival);
+
+/**
+ * Read the `len` bytes of UTF-8 at `text`, a rulestring such as `B36/S23`,
+ * into the masks `conway_grid_set_rule` takes
+ *
+ * # Safety
+ * `text` is null or points to `len` readable bytes, and `birth` and
+ * `survival` are null or writable
+ */
+ConwayStatus conway_rule_parse(const uint8_t *text,
+                               size_t len,
+                               uint16_t *birth,
+                               uint16_t *survival);
+
+/**
+ * Run a copy of the board for up to `generations` generations, stopping
+ * once it settles, and write how it fared to `lifespan`; the board is left
+ * as it was, and more than `CONWAY_GRID_MAX_STEP` generations are
+ * `CONWAY_STATUS_TOO_LARGE`
+ *
+ * # Safety
+ * `grid` is null or a live board from this library, and `lifespan` is null
+ * or writable
+ */
+ConwayStatus conway_grid_lifespan(const ConwayGrid *grid,
+                                  uint64_t generations,
+                                  ConwayLifespan *lifespan);
+
+/**
+ * Write the objects on the board and how many of each as `len` bytes of
+ * UTF-8 at `text`, ending with a nul, and the length of the text without it
+ * to `written`
+ *
+ * Every line is a count and a name, e.g. `2 block`, the most common object
+ * first, with objects `patterns::identify` doesn't know as `unidentified`.
+ * When the text doesn't fit, which `len` 0 with a null `text` always does,
+ * `written` still gets its length, and the call is `CONWAY_STATUS_BUFFER_SIZE`.
+ *
+ * # Safety
+ * `grid` is null or a live board from this library, `text` is null or
+ * points to `len` writable bytes, and `written` is null or writable
+ */
+ConwayStatus conway_grid_census(const ConwayGrid *grid, uint8_t *text, size_t len, size_t *written);
+
+/**
+ * A board decoded from `len` bytes of UTF-8 RLE text at `text`, which needn't
+ * end with a nul, or null when the text is null, isn't UTF-8 or isn't valid RLE
+ *
+ * # Safety
+ * `text` is null or points to `len` readable bytes
+ */
+ConwayGrid *conway_grid_load_rle(const uint8_t *text, size_t len);
+
+#endif /* GAME_OF_LIFE_H */
@@ -0,0 +1,406 @@
+"""The engine from Python, through the C interface of the `ffi` feature
+
+A stand-in for pyo3 bindings until pyo3 is available to the build. Build the
+library with `make ffi` first. It's looked for in `target/release`, or in the
+file `GAME_OF_LIFE_LIB` names.
+
+    >>> from game_of_life import Grid, Rule
+    >>> grid = Grid.from_rle("x = 3, y = 1\\n3o!")
+    >>> grid.step()
+    >>> grid.population, sorted(grid.live_cells())
+    (1, [(1, 0)])
+    >>> grid.rule = Rule("B36/S23")
+    >>> Grid.from_lists([[0, 1], [1, 1]])
+    Grid 2x2, generation 0, 3 alive, B3/S23
+    .O
+    OO
+
+Every status a C function returns but `CONWAY_STATUS_OK` raises its own
+subclass of `ConwayError`, which also subclasses the built-in exception
+Python code would expect, e.g. `IndexError` for a cell off the board.
+"""
+
+import ctypes
+import os
+import sys
+from pathlib import Path
+
+__all__ = [
+    "Grid",
+    "Rule",
+    "Lifespan",
+    "ConwayError",
+    "NullPointerError",
+    "OutOfBoundsError",
+    "BufferSizeError",
+    "InvalidRuleError",
+    "TooLargeError",
+    "PanicError",
+    "PatternError",
+]
+
+# CONWAY_GRID_MAX_STEP in ffi/game_of_life.h
+_MAX_STEP = 10_000
+
+
+class ConwayError(Exception):
+    """A call into the library failed, with the `ConwayStatus` it returned"""
+
+    status = None
+
+
+class NullPointerError(ConwayError, ValueError):
+    """CONWAY_STATUS_NULL_POINTER: a board or buffer was null"""
+
+    status = 1
+
+
+class OutOfBoundsError(ConwayError, IndexError):
+    """CONWAY_STATUS_OUT_OF_BOUNDS: a cell was off the board"""
+
+    status = 2
+
+
+class BufferSizeError(ConwayError, ValueError):
+    """CONWAY_STATUS_BUFFER_SIZE: cells that don't fill the board exactly"""
+
+    status = 3
+
+
+class InvalidRuleError(ConwayError, ValueError):
+    """CONWAY_STATUS_INVALID_RULE: not a `B<digits>/S<digits>` rulestring"""
+
+    status = 4
+
+
+class TooLargeError(ConwayError, ValueError):
+    """CONWAY_STATUS_TOO_LARGE: more generations than one call runs, or a
+    board with too many cells"""
+
+    status = 5
+
+
+class PanicError(ConwayError, RuntimeError):
+    """CONWAY_STATUS_PANIC: the library hit a bug"""
+
+    status = 6
+
+
+class PatternError(ConwayError, ValueError):
+    """RLE text the library couldn't decode"""
+
+
+_ERRORS = {
+    error.status: error
+    for error in [
+        NullPointerError,
+        OutOfBoundsError,
+        BufferSizeError,
+        InvalidRuleError,
+        TooLargeError,
+        PanicError,
+    ]
+}
+
+
+def _check(status, message):
+    if status != 0:
+        raise _ERRORS.get(status, ConwayError)(message)
+
+
+def _library_path():
+    if "GAME_OF_LIFE_LIB" in os.environ:
+        return os.environ["GAME_OF_LIFE_LIB"]
+    name = {"darwin": "libgame_of_life.dylib", "win32": "game_of_life.dll"}.get(
+        sys.platform, "libgame_of_life.so"
+    )
+    return str(Path(__file__).resolve().parent.parent / "target" / "release" / name)
+
+
+class _Lifespan(ctypes.Structure):
+    _fields_ = [
+        ("generation", ctypes.c_uint64),
+        ("population", ctypes.c_size_t),
+        ("peak_population", ctypes.c_size_t),
+        ("peak_generation", ctypes.c_uint64),
+        ("settled", ctypes.c_bool),
+        ("settled_at", ctypes.c_uint64),
+        ("period", ctypes.c_uint64),
+    ]
+
+
+_lib = ctypes.CDLL(_library_path())
+_grid = ctypes.c_void_p
+_status = ctypes.c_int
+_bytes = ctypes.c_char_p
+_mask = ctypes.POINTER(ctypes.c_uint16)
+for _name, _result, _args in [
+    ("conway_grid_new", _grid, [ctypes.c_size_t, ctypes.c_size_t]),
+    ("conway_grid_free", None, [_grid]),
+    ("conway_grid_set", _status, [_grid, ctypes.c_size_t, ctypes.c_size_t, ctypes.c_bool]),
+    ("conway_grid_get", ctypes.c_int32, [_grid, ctypes.c_size_t, ctypes.c_size_t]),
+    ("conway_grid_read_cells", _status, [_grid, ctypes.c_void_p, ctypes.c_size_t]),
+    ("conway_grid_write_cells", _status, [_grid, _bytes, ctypes.c_size_t]),
+    ("conway_grid_step", _status, [_grid, ctypes.c_uint64]),
+    ("conway_grid_population", ctypes.c_size_t, [_grid]),
+    ("conway_grid_width", ctypes.c_size_t, [_grid]),
+    ("conway_grid_height", ctypes.c_size_t, [_grid]),
+    ("conway_grid_generation", ctypes.c_uint64, [_grid]),
+    ("conway_grid_rule", _status, [_grid, _mask, _mask]),
+    ("conway_grid_set_rule", _status, [_grid, ctypes.c_uint16, ctypes.c_uint16]),
+    ("conway_rule_parse", _status, [_bytes, ctypes.c_size_t, _mask, _mask]),
+    ("conway_grid_lifespan", _status, [_grid, ctypes.c_uint64, ctypes.POINTER(_Lifespan)]),
+    (
+        "conway_grid_census",
+        _status,
+        [_grid, ctypes.c_void_p, ctypes.c_size_t, ctypes.POINTER(ctypes.c_size_t)],
+    ),
+    ("conway_grid_load_rle", _grid, [_bytes, ctypes.c_size_t]),
+]:
+    _function = getattr(_lib, _name)
+    _function.restype = _result
+    _function.argtypes = _args
+
+
+class Rule:
+    """A life-like rule in B/S notation, e.g. `Rule("B36/S23")`
+
+    `birth` and `survival` are the neighbor counts that bring a dead cell to
+    life and keep a live one alive. Rules compare equal however they were
+    written.
+    """
+
+    def __init__(self, text):
+        data = text.encode()
+        birth, survival = ctypes.c_uint16(), ctypes.c_uint16()
+        _check(
+            _lib.conway_rule_parse(data, len(data), birth, survival),
+            f"'{text}' isn't a B<digits>/S<digits> rulestring",
+        )
+        self._masks = (birth.value, survival.value)
+
+    @classmethod
+    def _from_masks(cls, birth, survival):
+        rule = cls.__new__(cls)
+        rule._masks = (birth, survival)
+        return rule
+
+    @property
+    def birth(self):
+        return frozenset(n for n in range(9) if self._masks[0] >> n & 1)
+
+    @property
+    def survival(self):
+        return frozenset(n for n in range(9) if self._masks[1] >> n & 1)
+
+    def __str__(self):
+        digits = lambda counts: "".join(str(n) for n in sorted(counts))
+        return f"B{digits(self.birth)}/S{digits(self.survival)}"
+
+    def __repr__(self):
+        return f"Rule('{self}')"
+
+    def __eq__(self, other):
+        return isinstance(other, Rule) and self._masks == other._masks
+
+    def __hash__(self):
+        return hash(self._masks)
+
+
+Rule.CONWAY = Rule("B3/S23")
+
+
+class Lifespan:
+    """How a board fared over `Grid.lifespan`, with the fields of
+    `ConwayLifespan` in ffi/game_of_life.h; `settled_at` and `period` are
+    `None` unless it settled"""
+
+    def __init__(self, run):
+        self.generation = run.generation
+        self.population = run.population
+        self.peak_population = run.peak_population
+        self.peak_generation = run.peak_generation
+        self.settled = run.settled
+        self.settled_at = run.settled_at if run.settled else None
+        self.period = run.period if run.settled else None
+
+    def __repr__(self):
+        fields = ", ".join(f"{name}={value!r}" for name, value in vars(self).items())
+        return f"Lifespan({fields})"
+
+
+class Grid:
+    """A board with dead edges, stepping by Conway's rule unless given
+    another, freed with the object
+
+    Cells off the board raise `OutOfBoundsError`, an `IndexError` like a
+    list's.
+    """
+
+    def __init__(self, width, height):
+        self._ptr = _lib.conway_grid_new(width, height)
+        if not self._ptr:
+            raise TooLargeError(f"a {width}x{height} board is too big")
+
+    @classmethod
+    def _adopt(cls, ptr):
+        grid = cls.__new__(cls)
+        grid._ptr = ptr
+        return grid
+
+    @classmethod
+    def from_rle(cls, text):
+        """A board decoded from RLE text, sized from its header"""
+        data = text.encode()
+        ptr = _lib.conway_grid_load_rle(data, len(data))
+        if not ptr:
+            raise PatternError("not a valid RLE pattern")
+        return cls._adopt(ptr)
+
+    @classmethod
+    def from_bytes(cls, width, height, data):
+        """A board from `width * height` bytes, row by row, as `to_bytes`
+        gives them, any byte but 0 a live cell"""
+        grid = cls(width, height)
+        grid.load_bytes(data)
+        return grid
+
+    @classmethod
+    def from_lists(cls, rows):
+        """A board from a list of rows of the same length, each a list of
+        cells, live where truthy"""
+        width = len(rows[0]) if rows else 0
+        if any(len(row) != width for row in rows):
+            raise BufferSizeError("rows of different lengths")
+        data = bytes(1 if cell else 0 for row in rows for cell in row)
+        return cls.from_bytes(width, len(rows), data)
+
+    def __del__(self):
+        if getattr(self, "_ptr", None):
+            _lib.conway_grid_free(self._ptr)
+            self._ptr = None
+
+    @property
+    def width(self):
+        return _lib.conway_grid_width(self._ptr)
+
+    @property
+    def height(self):
+        return _lib.conway_grid_height(self._ptr)
+
+    @property
+    def population(self):
+        """Number of live cells"""
+        return _lib.conway_grid_population(self._ptr)
+
+    @property
+    def generation(self):
+        """Number of generations the board was stepped"""
+        return _lib.conway_grid_generation(self._ptr)
+
+    @property
+    def rule(self):
+        """The rule the board steps by, set from a `Rule` or a rulestring"""
+        birth, survival = ctypes.c_uint16(), ctypes.c_uint16()
+        _check(_lib.conway_grid_rule(self._ptr, birth, survival), "no rule")
+        return Rule._from_masks(birth.value, survival.value)
+
+    @rule.setter
+    def rule(self, rule):
+        if not isinstance(rule, Rule):
+            rule = Rule(rule)
+        _check(_lib.conway_grid_set_rule(self._ptr, *rule._masks), f"can't step by {rule}")
+
+    def _off_board(self, x, y):
+        return f"({x}, {y}) is off the {self.width}x{self.height} board"
+
+    def get(self, x, y):
+        """Whether the cell at `(x, y)` is alive"""
+        if x < 0 or y < 0:
+            raise OutOfBoundsError(self._off_board(x, y))
+        state = _lib.conway_grid_get(self._ptr, x, y)
+        if state < 0:
+            raise OutOfBoundsError(self._off_board(x, y))
+        return state == 1
+
+    def set(self, x, y, alive=True):
+        if x < 0 or y < 0:
+            raise OutOfBoundsError(self._off_board(x, y))
+        _check(_lib.conway_grid_set(self._ptr, x, y, alive), self._off_board(x, y))
+
+    def to_bytes(self):
+        """Every cell, row by row, 1 for a live cell and 0 for a dead one"""
+        size = self.width * self.height
+        cells = ctypes.create_string_buffer(size)
+        _check(_lib.conway_grid_read_cells(self._ptr, cells, size), "cells not read")
+        return cells.raw
+
+    def load_bytes(self, data):
+        """Set every cell from `width * height` bytes, as `from_bytes` takes them"""
+        data = bytes(data)
+        size = self.width * self.height
+        _check(
+            _lib.conway_grid_write_cells(self._ptr, data, len(data)),
+            f"{len(data)} cells for a board of {size}",
+        )
+
+    def to_lists(self):
+        """The board as a list of rows, each a list of `True` for live cells
+        and `False` for dead ones"""
+        data, width = self.to_bytes(), self.width
+        return [[cell == 1 for cell in data[y * width : (y + 1) * width]] for y in range(self.height)]
+
+    def step(self, generations=1):
+        """Advance the board `generations` generations
+
+        Runs them as calls of at most `CONWAY_GRID_MAX_STEP` generations, so a
+        long run can be interrupted between two.
+        """
+        if generations < 0:
+            raise ValueError("can't step backwards")
+        while generations > 0:
+            chunk = min(generations, _MAX_STEP)
+            _check(_lib.conway_grid_step(self._ptr, chunk), "the board couldn't be stepped")
+            generations -= chunk
+
+    def lifespan(self, generations=_MAX_STEP):
+        """Run a copy of the board up to `generations` generations, at most
+        `CONWAY_GRID_MAX_STEP`, until it settles, as a `Lifespan`"""
+        run = _Lifespan()
+        _check(
+            _lib.conway_grid_lifespan(self._ptr, generations, ctypes.byref(run)),
+            f"a run of {generations} generations",
+        )
+        return Lifespan(run)
+
+    def census(self):
+        """How many of each object are on the board, by name, the most
+        common first; objects no name is known for are `"unidentified"`"""
+        written = ctypes.c_size_t()
+        status = _lib.conway_grid_census(self._ptr, None, 0, ctypes.byref(written))
+        if status != 3:
+            _check(status, "no census")
+        text = ctypes.create_string_buffer(written.value + 1)
+        _check(
+            _lib.conway_grid_census(self._ptr, text, len(text), ctypes.byref(written)),
+            "no census",
+        )
+        census = {}
+        for line in text.value.decode().splitlines():
+            count, name = line.split(" ", 1)
+            census[name] = int(count)
+        return census
+
+    def live_cells(self):
+        """The live cells as `(x, y)`, row by row"""
+        data, width = self.to_bytes(), self.width
+        return [(i % width, i // width) for i, cell in enumerate(data) if cell]
+
+    def __repr__(self):
+        rows = "".join(
+            "\n" + "".join("O" if cell else "." for cell in row) for row in self.to_lists()
+        )
+        return (
+            f"Grid {self.width}x{self.height}, generation {self.generation}, "
+            f"{self.population} alive, {self.rule}{rows}"
+        )
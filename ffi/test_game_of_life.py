@@ -0,0 +1,149 @@
+"""The Python wrapper over the C interface, run by `make python`"""
+
+import doctest
+import unittest
+
+import game_of_life
+from game_of_life import (
+    BufferSizeError,
+    Grid,
+    InvalidRuleError,
+    OutOfBoundsError,
+    PatternError,
+    Rule,
+    TooLargeError,
+)
+
+
+class GridTest(unittest.TestCase):
+    def test_a_blinker_turns(self):
+        grid = Grid(5, 5)
+        for x in range(1, 4):
+            grid.set(x, 2)
+        self.assertEqual(grid.population, 3)
+        grid.step()
+        self.assertEqual(grid.live_cells(), [(2, 1), (2, 2), (2, 3)])
+        # Back where it started after a full period
+        grid.step(3)
+        self.assertEqual(grid.live_cells(), [(1, 2), (2, 2), (3, 2)])
+        self.assertEqual((grid.width, grid.height), (5, 5))
+        # More than the C interface steps in one call
+        grid.step(25_001)
+        self.assertEqual(grid.live_cells(), [(2, 1), (2, 2), (2, 3)])
+
+    def test_a_glider_loaded_from_rle(self):
+        grid = Grid.from_rle("x = 3, y = 3\nbo$2bo$3o!")
+        self.assertEqual((grid.width, grid.height, grid.population), (3, 3, 5))
+        self.assertTrue(grid.get(1, 0))
+        self.assertFalse(grid.get(0, 0))
+        grid.set(1, 0, False)
+        self.assertEqual(grid.population, 4)
+
+    def test_bad_arguments_raise(self):
+        grid = Grid(3, 3)
+        with self.assertRaises(IndexError):
+            grid.get(3, 0)
+        with self.assertRaises(IndexError):
+            grid.set(0, -1)
+        with self.assertRaises(ValueError):
+            grid.step(-1)
+        with self.assertRaises(ValueError):
+            Grid.from_rle("x = 2, y = 2\n3o!")
+        with self.assertRaises(ValueError):
+            Grid(2**64 - 1, 2)
+
+    def test_every_error_has_its_own_exception(self):
+        grid = Grid(3, 3)
+        with self.assertRaises(OutOfBoundsError):
+            grid.set(3, 0)
+        with self.assertRaises(BufferSizeError):
+            grid.load_bytes(b"\x01" * 8)
+        with self.assertRaises(BufferSizeError):
+            Grid.from_lists([[1, 0], [1]])
+        with self.assertRaises(InvalidRuleError):
+            grid.rule = "B9/S23"
+        with self.assertRaises(TooLargeError):
+            grid.lifespan(10_001)
+        with self.assertRaises(TooLargeError):
+            Grid(2**64 - 1, 2)
+        with self.assertRaises(PatternError):
+            Grid.from_rle("x = 2, y = 2\n3o!")
+        self.assertEqual(grid.population, 0)
+
+    def test_cells_as_bytes_and_lists(self):
+        grid = Grid.from_bytes(3, 2, b"\x00\x01\x00\xff\x00\x00")
+        self.assertEqual(grid.live_cells(), [(1, 0), (0, 1)])
+        self.assertEqual(grid.to_bytes(), b"\x00\x01\x00\x01\x00\x00")
+        self.assertEqual(grid.to_lists(), [[False, True, False], [True, False, False]])
+        copy = Grid.from_lists(grid.to_lists())
+        self.assertEqual((copy.width, copy.height), (3, 2))
+        self.assertEqual(copy.to_bytes(), grid.to_bytes())
+        self.assertEqual(Grid.from_lists([]).to_lists(), [])
+
+    def test_rules(self):
+        rule = Rule("b36/s23")
+        self.assertEqual(rule.birth, {3, 6})
+        self.assertEqual(rule.survival, {2, 3})
+        self.assertEqual(str(rule), "B36/S23")
+        self.assertEqual(repr(rule), "Rule('B36/S23')")
+        self.assertEqual(rule, Rule("B63/S32"))
+        self.assertEqual(len({rule, Rule("B36/S23"), Rule.CONWAY}), 2)
+        with self.assertRaises(InvalidRuleError):
+            Rule("B3")
+
+        grid = Grid(5, 5)
+        self.assertEqual(grid.rule, Rule.CONWAY)
+        grid.rule = rule
+        self.assertEqual(grid.rule, rule)
+        # Two cells apart are born between under B2/S and die themselves
+        grid.rule = "B2/S"
+        for x, y in [(1, 1), (3, 1)]:
+            grid.set(x, y)
+        grid.step()
+        self.assertEqual(grid.live_cells(), [(2, 0), (2, 1), (2, 2)])
+        self.assertEqual(grid.generation, 1)
+
+    def test_the_lifespan_of_a_board(self):
+        grid = Grid.from_rle("x = 3, y = 3\n$3o!")
+        lifespan = grid.lifespan(100)
+        self.assertTrue(lifespan.settled)
+        self.assertEqual((lifespan.settled_at, lifespan.period), (0, 2))
+        self.assertEqual(grid.generation, 0)
+
+        grid = Grid(3, 3)
+        grid.set(1, 1)
+        lifespan = grid.lifespan(100)
+        self.assertEqual((lifespan.population, lifespan.peak_population), (0, 1))
+        self.assertEqual(lifespan.peak_generation, 0)
+        self.assertEqual(grid.population, 1)
+
+        # A glider far from the corner is still flying after 10 generations
+        glider = Grid.from_rle("x = 40, y = 40\nbo$2bo$3o!")
+        lifespan = glider.lifespan(10)
+        self.assertFalse(lifespan.settled)
+        self.assertEqual(lifespan.generation, 10)
+        self.assertIsNone(lifespan.period)
+
+    def test_a_census_of_the_board(self):
+        grid = Grid.from_rle("x = 12, y = 3\n2o3b2o3bo$2o3b2o3bo$10bo!")
+        self.assertEqual(grid.census(), {"block": 2, "blinker": 1})
+        grid.set(0, 2)
+        self.assertEqual(grid.census(), {"unidentified": 1, "blinker": 1, "block": 1})
+        self.assertEqual(Grid(2, 2).census(), {})
+
+    def test_repr_draws_the_board(self):
+        grid = Grid.from_rle("x = 3, y = 3\nbo$2bo$3o!")
+        grid.rule = "B36/S23"
+        self.assertEqual(
+            repr(grid),
+            "Grid 3x3, generation 0, 5 alive, B36/S23\n.O.\n..O\nOOO",
+        )
+
+
+def load_tests(loader, tests, ignore):
+    tests.addTests(doctest.DocTestSuite(game_of_life))
+    return tests
+
+
+if __name__ == "__main__":
+    unittest.main()
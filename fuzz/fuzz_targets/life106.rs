@@ -0,0 +1,9 @@
+#![no_main]
+
+use game_of_life::formats::life106;
+use libfuzzer_sys::fuzz_target;
+
+// Any text may be refused, but never with a panic
+fuzz_target!(|text: &str| {
+    let _ = life106::decode(text);
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use game_of_life::formats::plaintext;
+use libfuzzer_sys::fuzz_target;
+
+// Any text may be refused, but never with a panic
+fuzz_target!(|text: &str| {
+    let _ = plaintext::decode(text);
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use game_of_life::formats::rle;
+use libfuzzer_sys::fuzz_target;
+
+// Any text may be refused, but never with a panic
+fuzz_target!(|text: &str| {
+    let _ = rle::decode(text);
+});
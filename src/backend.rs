@@ -0,0 +1,473 @@
+//! Ways of storing a board that the code reading and stepping it doesn't
+//! have to tell apart
+//!
+//! - [`Grid`]: a cell at a time, with everything the game keeps about a
+//!   board, e.g. wrapping edges, custom rulesets and an observer
+//! - [`bits::BitGrid`]: 64 cells to a word, stepped 64 at a time
+//! - [`sparse::SparseGrid`]: only the live cells, for huge and mostly empty
+//!   boards
+//!
+//! The pattern decoders build any of them and the encoders, cycle detection
+//! and the headless run's `simulate` take any of them. The game keeps a
+//! `Grid` for its edits, history and drawing, and steps runs of many
+//! generations through [`Backend::run`], in whichever backend `--backend`
+//! picked. [`convert`] moves a board from one backend to another.
+//!
+//! ```
+//! use game_of_life::backend::{self, bits::BitGrid, GridBackend};
+//! use game_of_life::conways::Grid;
+//! use game_of_life::formats::rle;
+//!
+//! let mut bits: BitGrid = rle::decode("x = 100, y = 100\n49$50bo$51bo$49b3o!").unwrap();
+//! let mut dense: Grid = backend::convert(&bits);
+//! for _ in 0..40 {
+//!     bits.step();
+//! }
+//! dense.step_n(40);
+//! assert_eq!(backend::convert::<Grid>(&bits), dense);
+//! ```
+
+use crate::conways::{BoundaryCondition, CellState, Grid};
+use crate::error::ConwayError;
+use crate::rule::Rule;
+use alloc::boxed::Box;
+use alloc::string::ToString;
+
+pub mod bits;
+pub mod sparse;
+
+/// A board of `width` x `height` cells and how it steps
+///
+/// Cells are addressed by `x` and `y` within the board, and `get` and `set`
+/// panic outside of it like `Grid`'s do. The bit-packed and sparse grids
+/// have dead edges; a `Grid` steps with whatever edges it was given.
+pub trait GridBackend {
+    /// An empty board of `width` x `height` cells at generation 0, stepping by
+    /// Conway's rule
+    fn with_size(width: usize, height: usize) -> Self
+    where
+        Self: Sized;
+
+    fn width(&self) -> usize;
+
+    fn height(&self) -> usize;
+
+    fn get(&self, x: usize, y: usize) -> CellState;
+
+    fn set(&mut self, x: usize, y: usize, state: CellState);
+
+    /// The live cells, row by row and left to right in a row
+    fn live_cells(&self) -> Box<dyn Iterator<Item = (usize, usize)> + '_>;
+
+    /// Number of live cells
+    fn population(&self) -> usize {
+        self.live_cells().count()
+    }
+
+    /// Advance the board a generation
+    fn step(&mut self);
+
+    /// Number of times the board was stepped
+    fn generation(&self) -> u64;
+
+    fn set_generation(&mut self, generation: u64);
+
+    /// The rule `step` follows, unless `has_custom_ruleset`: then only the
+    /// one it last followed, which no other backend would step the same by
+    fn rule(&self) -> Rule;
+
+    fn set_rule(&mut self, rule: Rule);
+
+    /// `set_rule`, or an `InvalidArgument` error, leaving the rule as it was,
+    /// for a rule the board can't be stepped by in reasonable time
+    fn try_set_rule(&mut self, rule: Rule) -> Result<(), ConwayError> {
+        self.set_rule(rule);
+        Ok(())
+    }
+
+    /// Whether `step` follows a ruleset other than `rule`, which only a `Grid`
+    /// can be given
+    fn has_custom_ruleset(&self) -> bool {
+        false
+    }
+
+    /// The same hash `Grid::content_hash` gives a grid with these cells
+    fn content_hash(&self) -> u64 {
+        let mut hash = ContentHash::new(self.width(), self.height());
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                hash.feed((self.get(x, y) == CellState::Alive) as u8);
+            }
+        }
+        hash.0
+    }
+}
+
+/// The FNV-1a hash `Grid::content_hash` takes, for backends to feed cells to
+struct ContentHash(u64);
+
+impl ContentHash {
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    /// A hash fed the size of a `width` x `height` board
+    fn new(width: usize, height: usize) -> Self {
+        let mut hash = Self(0xcbf2_9ce4_8422_2325);
+        let size = (width as u64).to_le_bytes();
+        size.into_iter()
+            .chain((height as u64).to_le_bytes())
+            .for_each(|byte| hash.feed(byte));
+        hash
+    }
+
+    fn feed(&mut self, byte: u8) {
+        self.0 ^= byte as u64;
+        self.0 = self.0.wrapping_mul(Self::PRIME);
+    }
+
+    /// Feed `count` dead cells at once: a 0 only multiplies by the prime, so
+    /// they take `PRIME.pow(count)`, squared up in `log2(count)` steps
+    fn skip(&mut self, mut count: u128) {
+        let mut power = Self::PRIME;
+        while count > 0 {
+            if count & 1 == 1 {
+                self.0 = self.0.wrapping_mul(power);
+            }
+            power = power.wrapping_mul(power);
+            count >>= 1;
+        }
+    }
+}
+
+/// A copy of `from` in another backend: its size, cells, generation and rule
+///
+/// A `Grid`'s edges, which the other backends don't have, are left behind, as
+/// is anything but its cells when converting to a `Grid`.
+///
+/// # Panics
+/// When `from` has a custom ruleset or a rule the copy refuses, see
+/// [`try_convert`]
+pub fn convert<B: GridBackend>(from: &(impl GridBackend + ?Sized)) -> B {
+    try_convert(from).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// [`convert`], or an `InvalidArgument` error when `from` has a custom
+/// ruleset, which the copy couldn't step by, or a rule the copy refuses, see
+/// `GridBackend::try_set_rule`
+pub fn try_convert<B: GridBackend>(from: &(impl GridBackend + ?Sized)) -> Result<B, ConwayError> {
+    if from.has_custom_ruleset() {
+        return Err(ConwayError::InvalidArgument(
+            "a board with a custom ruleset can't change backends".to_string(),
+        ));
+    }
+    let mut to = B::with_size(from.width(), from.height());
+    for (x, y) in from.live_cells() {
+        to.set(x, y, CellState::Alive);
+    }
+    to.set_generation(from.generation());
+    to.try_set_rule(from.rule())?;
+    Ok(to)
+}
+
+/// The backend a board is stepped by, picked with `--backend`
+/// - `Grid`: the board itself
+/// - `Bits`: a `BitGrid` copy of it
+/// - `Sparse`: a `SparseGrid` copy of it
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Backend {
+    #[default]
+    Grid,
+    Bits,
+    Sparse,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 3] = [Backend::Grid, Backend::Bits, Backend::Sparse];
+
+    /// Name used by `--backend`
+    pub fn label(self) -> &'static str {
+        match self {
+            Backend::Grid => "grid",
+            Backend::Bits => "bits",
+            Backend::Sparse => "sparse",
+        }
+    }
+
+    /// Run `steps` on `grid` in this backend
+    ///
+    /// Other backends step a copy of the board, whose cells and generation
+    /// `grid` takes once `steps` returns, see `Grid::take_cells`. A board that
+    /// wraps, has a custom ruleset or has an observer, which only a `Grid`
+    /// steps, is given to `steps` as it is by every backend.
+    pub fn run<R>(self, grid: &mut Grid, steps: impl FnOnce(&mut dyn GridBackend) -> R) -> R {
+        let own = grid.boundary() != BoundaryCondition::Dead
+            || grid.has_custom_ruleset()
+            || grid.has_observer();
+        let copy: Option<Box<dyn GridBackend>> = match self {
+            _ if own => None,
+            Backend::Grid => None,
+            Backend::Bits => Some(Box::new(convert::<bits::BitGrid>(grid))),
+            Backend::Sparse => try_convert::<sparse::SparseGrid>(grid)
+                .ok()
+                .map(|board| Box::new(board) as Box<dyn GridBackend>),
+        };
+        let Some(mut board) = copy else {
+            return steps(grid);
+        };
+        let result = steps(&mut *board);
+        grid.take_cells(&*board);
+        result
+    }
+}
+
+impl GridBackend for Grid {
+    fn with_size(width: usize, height: usize) -> Self {
+        Grid::new(width, height)
+    }
+
+    fn width(&self) -> usize {
+        Grid::width(self)
+    }
+
+    fn height(&self) -> usize {
+        Grid::height(self)
+    }
+
+    fn get(&self, x: usize, y: usize) -> CellState {
+        Grid::get(self, x, y)
+    }
+
+    fn set(&mut self, x: usize, y: usize, state: CellState) {
+        Grid::set(self, x, y, state);
+    }
+
+    fn live_cells(&self) -> Box<dyn Iterator<Item = (usize, usize)> + '_> {
+        let width = Grid::width(self);
+        Box::new(
+            (0..Grid::height(self))
+                .flat_map(move |y| (0..width).map(move |x| (x, y)))
+                .filter(|&(x, y)| Grid::get(self, x, y) == CellState::Alive),
+        )
+    }
+
+    fn population(&self) -> usize {
+        Grid::population(self)
+    }
+
+    fn step(&mut self) {
+        self.next_cell_generation();
+    }
+
+    fn generation(&self) -> u64 {
+        Grid::generation(self)
+    }
+
+    fn set_generation(&mut self, generation: u64) {
+        Grid::set_generation(self, generation);
+    }
+
+    fn rule(&self) -> Rule {
+        Grid::rule(self)
+    }
+
+    fn set_rule(&mut self, rule: Rule) {
+        Grid::set_rule(self, rule);
+    }
+
+    fn has_custom_ruleset(&self) -> bool {
+        Grid::has_custom_ruleset(self)
+    }
+
+    fn content_hash(&self) -> u64 {
+        Grid::content_hash(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::bits::BitGrid;
+    use super::sparse::SparseGrid;
+    use super::*;
+    use crate::rule::Ruleset;
+    use alloc::vec::Vec;
+
+    /// Soups of varying size and density stepped by every preset rule
+    fn soups() -> impl Iterator<Item = Grid> {
+        Rule::presets().into_iter().enumerate().map(|(i, rule)| {
+            // Widths either side of a word, and one spanning several
+            let width = [7, 63, 64, 65, 130][i % 5];
+            let mut grid = Grid::new(width, 24 + i);
+            grid.randomize(0.2 + 0.1 * (i % 5) as f64, i as u64);
+            grid.set_rule(rule);
+            grid
+        })
+    }
+
+    /// Step `grid` in `B` as well as in itself, returning where the two parted
+    fn diverges<B: GridBackend>(grid: &Grid, generations: u64) -> Option<u64> {
+        let (mut dense, mut other) = (grid.clone(), convert::<B>(grid));
+        for _ in 0..generations {
+            dense.next_cell_generation();
+            other.step();
+            if convert::<Grid>(&other) != dense || other.generation() != dense.generation() {
+                return Some(dense.generation());
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn test_every_backend_steps_like_the_dense_grid() {
+        for grid in soups() {
+            let rule = grid.rule();
+            assert_eq!(diverges::<BitGrid>(&grid, 30), None, "bit-packed, {rule}");
+            assert_eq!(diverges::<SparseGrid>(&grid, 30), None, "sparse, {rule}");
+        }
+    }
+
+    #[test]
+    fn test_conversions_keep_the_board() {
+        for mut grid in soups() {
+            grid.set_generation(17);
+            let bits: BitGrid = convert(&grid);
+            let sparse: SparseGrid = convert(&bits);
+            let back: Grid = convert(&sparse);
+            assert_eq!(back, grid);
+            assert_eq!((back.generation(), back.rule()), (17, grid.rule()));
+            let hashes = [
+                GridBackend::content_hash(&grid),
+                bits.content_hash(),
+                sparse.content_hash(),
+            ];
+            assert_eq!(hashes, [grid.content_hash(); 3]);
+            assert_eq!(bits.population(), grid.population());
+            assert_eq!(
+                sparse.live_cells().collect::<Vec<_>>(),
+                GridBackend::live_cells(&grid).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    struct Conway;
+
+    impl Ruleset for Conway {
+        fn next_state(&self, current: CellState, neighbors: u8, _: usize, _: usize) -> CellState {
+            match (current, neighbors) {
+                (_, 3) | (CellState::Alive, 2) => CellState::Alive,
+                _ => CellState::Dead,
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_rulesets_dont_change_backends() {
+        let mut grid = Grid::new(8, 8);
+        grid.set(3, 3, CellState::Alive);
+        grid.set_ruleset(Conway);
+        assert!(GridBackend::has_custom_ruleset(&grid));
+        assert!(try_convert::<BitGrid>(&grid).is_err());
+        assert!(try_convert::<Grid>(&grid).is_err());
+        // Back to a B/S rule, the board converts again
+        grid.set_rule(Rule::conway());
+        let sparse: SparseGrid = try_convert(&grid).unwrap();
+        assert_eq!(sparse.population(), 1);
+    }
+
+    /// Run `generations` on `grid` in `backend`, checking the board it's given
+    fn run(backend: Backend, grid: &mut Grid, generations: u64) -> (Vec<(usize, usize)>, u64) {
+        backend.run(grid, |board| {
+            for _ in 0..generations {
+                board.step();
+            }
+            (board.live_cells().collect(), board.generation())
+        })
+    }
+
+    #[test]
+    fn test_every_backend_runs_a_grid_the_same() {
+        for grid in soups() {
+            let mut stepped = grid.clone();
+            stepped.step_n(30);
+            for backend in Backend::ALL {
+                let mut run_grid = grid.clone();
+                let (cells, generation) = run(backend, &mut run_grid, 30);
+                assert_eq!(run_grid, stepped, "{}, {}", backend.label(), grid.rule());
+                assert_eq!((run_grid.generation(), generation), (30, 30));
+                let live: Vec<_> = GridBackend::live_cells(&stepped).collect();
+                assert_eq!(cells, live);
+            }
+        }
+    }
+
+    #[test]
+    fn test_what_only_a_grid_steps_stays_on_the_grid() {
+        use crate::observer::GridObserver;
+
+        struct Silent;
+        impl GridObserver for Silent {}
+
+        // A glider about to cross the wrapping edge, which dead edges would stop
+        let mut glider = Grid::new(6, 6);
+        for (x, y) in [(4, 3), (5, 4), (3, 5), (4, 5), (5, 5)] {
+            glider.set(x, y, CellState::Alive);
+        }
+        let mut wrapping = glider.clone();
+        wrapping.set_boundary(BoundaryCondition::Wrap);
+        let mut custom = glider.clone();
+        custom.set_ruleset(Conway);
+        let mut observed = glider.clone();
+        observed.set_observer(Box::new(Silent));
+        for (mut grid, name) in [
+            (wrapping, "wrapping"),
+            (custom, "custom"),
+            (observed, "observed"),
+        ] {
+            let mut stepped = grid.clone();
+            stepped.step_n(8);
+            // Handed the grid itself, which steps by its own `step`
+            let cells = run(Backend::Bits, &mut grid, 8).0;
+            assert_eq!(grid, stepped, "{name}");
+            assert_eq!(cells, GridBackend::live_cells(&stepped).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "custom ruleset")]
+    fn test_converting_a_custom_ruleset_panics() {
+        let mut grid = Grid::new(8, 8);
+        grid.set_ruleset(Conway);
+        let _: BitGrid = convert(&grid);
+    }
+
+    #[test]
+    fn test_births_from_nothing_stay_on_the_board() {
+        // Under B0 every empty cell comes to life, right up to the edges
+        let rule: Rule = "B0/S".parse().unwrap();
+        for width in [1, 64, 70] {
+            let mut grid = Grid::new(width, 3);
+            grid.set_rule(rule);
+            grid.set(0, 1, CellState::Alive);
+            assert_eq!(diverges::<BitGrid>(&grid, 4), None, "{width} wide");
+            assert_eq!(diverges::<SparseGrid>(&grid, 4), None, "{width} wide");
+        }
+    }
+
+    #[test]
+    fn test_backends_are_interchangeable_behind_dyn() {
+        let mut boards: Vec<Box<dyn GridBackend>> = Vec::from([
+            Box::new(Grid::with_size(6, 6)) as Box<dyn GridBackend>,
+            Box::new(BitGrid::with_size(6, 6)),
+            Box::new(SparseGrid::with_size(6, 6)),
+        ]);
+        for board in &mut boards {
+            for x in 1..4 {
+                board.set(x, 2, CellState::Alive);
+            }
+            board.step();
+        }
+        for board in &boards {
+            let cells: Vec<_> = board.live_cells().collect();
+            assert_eq!(cells, [(2, 1), (2, 2), (2, 3)]);
+            assert_eq!((board.generation(), board.population()), (1, 3));
+        }
+    }
+}
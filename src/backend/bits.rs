@@ -0,0 +1,232 @@
+//! A board stored 64 cells to a `u64`, row by row, and stepped a word at a
+//! time: the neighbor counts of 64 cells are added up at once as four
+//! bit-planes, which the rule's birth and survival counts are then picked
+//! out of
+
+use super::GridBackend;
+use crate::conways::CellState;
+use crate::rule::Rule;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A bit-packed board with dead edges, see the [module docs](self)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    /// Words in a row; bit `x % 64` of word `x / 64` is the cell at `x`
+    words: usize,
+    /// Every row's words, one row after another; bits past `width` stay 0
+    cells: Vec<u64>,
+    generation: u64,
+    rule: Rule,
+}
+
+impl BitGrid {
+    fn index(&self, x: usize, y: usize) -> (usize, u64) {
+        assert!(
+            x < self.width && y < self.height,
+            "({x}, {y}) is off the {}x{} board",
+            self.width,
+            self.height
+        );
+        (y * self.words + x / 64, 1 << (x % 64))
+    }
+
+    /// The bits of the last word of a row that are on the board
+    fn last_word_mask(&self) -> u64 {
+        match self.width % 64 {
+            0 => !0,
+            bits => (1 << bits) - 1,
+        }
+    }
+
+    /// Word `i` of row `y`, 0 off the board
+    fn word(&self, y: isize, i: isize) -> u64 {
+        if y < 0 || y as usize >= self.height || i < 0 || i as usize >= self.words {
+            return 0;
+        }
+        self.cells[y as usize * self.words + i as usize]
+    }
+}
+
+/// Add a bit to each of the 64 4-bit counters spread over `planes`
+fn add(planes: &mut [u64; 4], mut carry: u64) {
+    for plane in planes {
+        let next = *plane & carry;
+        *plane ^= carry;
+        carry = next;
+    }
+}
+
+impl GridBackend for BitGrid {
+    fn with_size(width: usize, height: usize) -> Self {
+        let words = width.div_ceil(64);
+        Self {
+            width,
+            height,
+            words,
+            cells: vec![0; words * height],
+            generation: 0,
+            rule: Rule::default(),
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn get(&self, x: usize, y: usize) -> CellState {
+        let (word, bit) = self.index(x, y);
+        match self.cells[word] & bit {
+            0 => CellState::Dead,
+            _ => CellState::Alive,
+        }
+    }
+
+    fn set(&mut self, x: usize, y: usize, state: CellState) {
+        let (word, bit) = self.index(x, y);
+        match state {
+            CellState::Alive => self.cells[word] |= bit,
+            CellState::Dead => self.cells[word] &= !bit,
+        }
+    }
+
+    fn live_cells(&self) -> Box<dyn Iterator<Item = (usize, usize)> + '_> {
+        Box::new(self.cells.iter().enumerate().flat_map(move |(i, &word)| {
+            let (y, first) = (i / self.words, i % self.words * 64);
+            let mut rest = word;
+            core::iter::from_fn(move || {
+                let bit = rest.trailing_zeros() as usize;
+                (rest != 0).then(|| {
+                    rest &= rest - 1;
+                    (first + bit, y)
+                })
+            })
+        }))
+    }
+
+    fn population(&self) -> usize {
+        self.cells
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    fn step(&mut self) {
+        // Whether a dead and a live cell with so many neighbors is alive next
+        let outcomes: [(bool, bool); 9] = core::array::from_fn(|neighbors| {
+            let neighbors = neighbors as u8;
+            (
+                self.rule.next_state(false, neighbors),
+                self.rule.next_state(true, neighbors),
+            )
+        });
+        let last = self.last_word_mask();
+        let mut next = vec![0; self.cells.len()];
+        for y in 0..self.height as isize {
+            for i in 0..self.words as isize {
+                let mut planes = [0; 4];
+                for dy in -1..=1 {
+                    let (before, word, after) = (
+                        self.word(y + dy, i - 1),
+                        self.word(y + dy, i),
+                        self.word(y + dy, i + 1),
+                    );
+                    // The neighbor to the left of every cell, then to the right
+                    add(&mut planes, word << 1 | before >> 63);
+                    add(&mut planes, word >> 1 | after << 63);
+                    if dy != 0 {
+                        add(&mut planes, word);
+                    }
+                }
+                let alive = self.word(y, i);
+                let mut word = 0;
+                for (count, &(born, survives)) in outcomes.iter().enumerate() {
+                    let cells = match (born, survives) {
+                        (false, false) => continue,
+                        (true, false) => !alive,
+                        (false, true) => alive,
+                        (true, true) => !0,
+                    };
+                    let with_count = planes.iter().enumerate().fold(!0, |with, (bit, plane)| {
+                        with & if count >> bit & 1 == 1 {
+                            *plane
+                        } else {
+                            !plane
+                        }
+                    });
+                    word |= cells & with_count;
+                }
+                if i as usize == self.words - 1 {
+                    word &= last;
+                }
+                next[y as usize * self.words + i as usize] = word;
+            }
+        }
+        self.cells = next;
+        self.generation += 1;
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn set_generation(&mut self, generation: u64) {
+        self.generation = generation;
+    }
+
+    fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighbors_are_counted_across_words() {
+        // A blinker standing on the boundary between the first two words
+        let mut grid = BitGrid::with_size(100, 5);
+        for y in 1..4 {
+            grid.set(64, y, CellState::Alive);
+        }
+        grid.step();
+        let cells: Vec<_> = grid.live_cells().collect();
+        assert_eq!(cells, [(63, 2), (64, 2), (65, 2)]);
+        assert_eq!(grid.cells[2 * grid.words], 1 << 63);
+        grid.step();
+        assert_eq!(
+            grid.live_cells().collect::<Vec<_>>(),
+            [(64, 1), (64, 2), (64, 3)]
+        );
+    }
+
+    #[test]
+    fn test_nothing_is_born_past_the_right_edge() {
+        // A blinker against the right edge would grow past it on an unbounded board
+        let mut grid = BitGrid::with_size(70, 5);
+        for y in 1..4 {
+            grid.set(69, y, CellState::Alive);
+        }
+        grid.step();
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), [(68, 2), (69, 2)]);
+        assert_eq!(grid.cells[2 * grid.words + 1] & !grid.last_word_mask(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "(70, 0) is off the 70x5 board")]
+    fn test_cells_off_the_board_panic() {
+        BitGrid::with_size(70, 5).get(70, 0);
+    }
+}
@@ -0,0 +1,271 @@
+//! A board that only keeps its live cells, so its size costs nothing and a
+//! generation takes time in proportion to the population, not the area
+
+use super::{ContentHash, GridBackend};
+use crate::conways::CellState;
+use crate::error::ConwayError;
+use crate::formats::MAX_PATTERN_CELLS;
+use crate::rule::Rule;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+
+/// A sparse board with dead edges, see the [module docs](self)
+///
+/// Under a rule that brings cells to life with no neighbors, e.g. `B0/S`,
+/// every cell is looked at, as every empty one can come to life, and the
+/// dead edges keep changes coming in from all around the board. Such rules
+/// are refused on boards of more than `MAX_PATTERN_CELLS` cells.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseGrid {
+    width: usize,
+    height: usize,
+    /// The live cells as `(y, x)`, so that they're in row order
+    live: BTreeSet<(usize, usize)>,
+    generation: u64,
+    rule: Rule,
+}
+
+impl SparseGrid {
+    fn check(&self, x: usize, y: usize) {
+        assert!(
+            x < self.width && y < self.height,
+            "({x}, {y}) is off the {}x{} board",
+            self.width,
+            self.height
+        );
+    }
+
+    /// The neighbors of `(x, y)` that are on the board, as `(y, x)`
+    fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width, self.height);
+        (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .filter(|&offset| offset != (0, 0))
+            .filter_map(move |(dx, dy)| {
+                let x = x.checked_add_signed(dx).filter(|&x| x < width)?;
+                let y = y.checked_add_signed(dy).filter(|&y| y < height)?;
+                Some((y, x))
+            })
+    }
+}
+
+impl GridBackend for SparseGrid {
+    fn with_size(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            live: BTreeSet::new(),
+            generation: 0,
+            rule: Rule::default(),
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn get(&self, x: usize, y: usize) -> CellState {
+        self.check(x, y);
+        match self.live.contains(&(y, x)) {
+            true => CellState::Alive,
+            false => CellState::Dead,
+        }
+    }
+
+    fn set(&mut self, x: usize, y: usize, state: CellState) {
+        self.check(x, y);
+        match state {
+            CellState::Alive => self.live.insert((y, x)),
+            CellState::Dead => self.live.remove(&(y, x)),
+        };
+    }
+
+    fn live_cells(&self) -> Box<dyn Iterator<Item = (usize, usize)> + '_> {
+        Box::new(self.live.iter().map(|&(y, x)| (x, y)))
+    }
+
+    fn population(&self) -> usize {
+        self.live.len()
+    }
+
+    fn step(&mut self) {
+        let next = if self.rule.next_state(false, 0) {
+            (0..self.height)
+                .flat_map(|y| (0..self.width).map(move |x| (y, x)))
+                .filter(|&(y, x)| {
+                    let neighbors = self
+                        .neighbors(x, y)
+                        .filter(|cell| self.live.contains(cell))
+                        .count();
+                    self.rule
+                        .next_state(self.live.contains(&(y, x)), neighbors as u8)
+                })
+                .collect()
+        } else {
+            // Only live cells and their neighbors can be alive next
+            let mut counts: BTreeMap<(usize, usize), u8> =
+                self.live.iter().map(|&cell| (cell, 0)).collect();
+            for &(y, x) in &self.live {
+                for neighbor in self.neighbors(x, y) {
+                    *counts.entry(neighbor).or_insert(0) += 1;
+                }
+            }
+            counts
+                .into_iter()
+                .filter(|(cell, neighbors)| {
+                    self.rule.next_state(self.live.contains(cell), *neighbors)
+                })
+                .map(|(cell, _)| cell)
+                .collect()
+        };
+        self.live = next;
+        self.generation += 1;
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn set_generation(&mut self, generation: u64) {
+        self.generation = generation;
+    }
+
+    fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// # Panics
+    /// For a B0 rule on a board of more than `MAX_PATTERN_CELLS` cells, see
+    /// `try_set_rule`
+    fn set_rule(&mut self, rule: Rule) {
+        self.try_set_rule(rule)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    fn try_set_rule(&mut self, rule: Rule) -> Result<(), ConwayError> {
+        let area = self.width as u128 * self.height as u128;
+        if rule.next_state(false, 0) && area > MAX_PATTERN_CELLS as u128 {
+            return Err(ConwayError::InvalidArgument(format!(
+                "{rule} steps every cell, too many on a {}x{} board",
+                self.width, self.height
+            )));
+        }
+        self.rule = rule;
+        Ok(())
+    }
+
+    /// The dense `content_hash`, in time with the population: the dead cells
+    /// between two live ones are skipped over all at once
+    fn content_hash(&self) -> u64 {
+        let mut hash = ContentHash::new(self.width, self.height);
+        // Index of the next cell to feed, row by row; `u128` as the area of
+        // a board can be past `usize`
+        let mut next = 0;
+        for &(y, x) in &self.live {
+            let index = y as u128 * self.width as u128 + x as u128;
+            hash.skip(index - next);
+            hash.feed(1);
+            next = index + 1;
+        }
+        hash.skip(self.width as u128 * self.height as u128 - next);
+        hash.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::convert;
+    use crate::conways::Grid;
+    use crate::formats::rle;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_a_glider_crosses_a_board_too_big_to_lay_out() {
+        let side = 1 << 40;
+        let mut grid = SparseGrid::with_size(side, side);
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            grid.set(side / 2 + x, side / 2 + y, CellState::Alive);
+        }
+        for _ in 0..400 {
+            grid.step();
+        }
+        // 100 cells down and to the right, in the same phase
+        let cells: Vec<_> = grid
+            .live_cells()
+            .map(|(x, y)| (x - side / 2 - 100, y - side / 2 - 100))
+            .collect();
+        assert_eq!(cells, [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        assert_eq!(grid.generation(), 400);
+    }
+
+    #[test]
+    fn test_the_hash_is_the_dense_one() {
+        let glider = rle::decode("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+        for (width, height) in [(3, 3), (8, 8), (70, 5), (3, 70)] {
+            let dense = Grid::centered(&glider, width, height);
+            let sparse: SparseGrid = convert(&dense);
+            assert_eq!(
+                sparse.content_hash(),
+                dense.content_hash(),
+                "{width}x{height}"
+            );
+        }
+        // Pinned like `Grid::content_hash`, and as empty as can be
+        let sparse: SparseGrid = convert(&Grid::centered(&glider, 8, 8));
+        assert_eq!(sparse.content_hash(), 0xce46_1b1b_d363_642c);
+        assert_eq!(
+            SparseGrid::with_size(0, 0).content_hash(),
+            0x8820_1fb9_60ff_6465
+        );
+    }
+
+    #[test]
+    fn test_a_huge_board_hashes_by_its_population() {
+        let side = 1 << 40;
+        let mut grid = SparseGrid::with_size(side, side);
+        let empty = grid.content_hash();
+        grid.set(side - 1, side - 1, CellState::Alive);
+        assert_ne!(grid.content_hash(), empty);
+        grid.set(side - 1, side - 1, CellState::Dead);
+        assert_eq!(grid.content_hash(), empty);
+    }
+
+    #[test]
+    fn test_births_from_nothing_are_refused_on_huge_boards() {
+        let b0: Rule = "B03/S23".parse().unwrap();
+        let mut huge = SparseGrid::with_size(1 << 40, 1 << 40);
+        assert_eq!(
+            huge.try_set_rule(b0).unwrap_err().to_string(),
+            "B03/S23 steps every cell, too many on a 1099511627776x1099511627776 board"
+        );
+        assert_eq!(huge.rule(), Rule::conway());
+        huge.set_rule("B36/S23".parse().unwrap());
+        // Up to the largest board the game opens, they step as any rule does
+        let mut grid = SparseGrid::with_size(4096, 4096);
+        grid.try_set_rule(b0).unwrap();
+        let mut wide = crate::backend::bits::BitGrid::with_size(1 << 25, 1);
+        wide.set_rule(b0);
+        assert!(crate::backend::try_convert::<SparseGrid>(&wide).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "steps every cell")]
+    fn test_setting_a_refused_rule_panics() {
+        SparseGrid::with_size(1 << 40, 1 << 40).set_rule("B0/S".parse().unwrap());
+    }
+
+    #[test]
+    fn test_a_lone_cell_survives_by_a_rule_that_keeps_it() {
+        let mut grid = SparseGrid::with_size(3, 3);
+        grid.set_rule("B3/S0".parse().unwrap());
+        grid.set(1, 1, CellState::Alive);
+        grid.step();
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), [(1, 1)]);
+    }
+}
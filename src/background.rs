@@ -0,0 +1,189 @@
+use crate::camera::Camera;
+use crate::input::Point;
+
+/// Smallest cell size dots are drawn at, in pixels; below it they would blur
+/// into a solid fill
+pub const MIN_DOT_CELL_SIZE: f32 = 4.0;
+
+/// Faint marks on dead cells, to help judge distances on an empty board
+/// - `Off`: A plain background, the classic look
+/// - `Dots`: A dot in the middle of every cell
+/// - `Checkerboard`: Every other cell shaded
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BackgroundStyle {
+    #[default]
+    Off,
+    Dots,
+    Checkerboard,
+}
+
+impl BackgroundStyle {
+    pub const ALL: [BackgroundStyle; 3] = [
+        BackgroundStyle::Off,
+        BackgroundStyle::Dots,
+        BackgroundStyle::Checkerboard,
+    ];
+
+    /// The style after this one, wrapping around
+    pub fn next(self) -> Self {
+        match self {
+            BackgroundStyle::Off => BackgroundStyle::Dots,
+            BackgroundStyle::Dots => BackgroundStyle::Checkerboard,
+            BackgroundStyle::Checkerboard => BackgroundStyle::Off,
+        }
+    }
+
+    /// Name used by the HUD and `conways.toml`
+    pub fn label(self) -> &'static str {
+        match self {
+            BackgroundStyle::Off => "off",
+            BackgroundStyle::Dots => "dots",
+            BackgroundStyle::Checkerboard => "checkerboard",
+        }
+    }
+}
+
+/// Whether the screen pixel at `pixel` is part of the background marks
+/// Marks only cover the board, and follow the camera so they line up with the cells.
+pub fn is_marked(
+    style: BackgroundStyle,
+    camera: &Camera,
+    grid: (usize, usize),
+    pixel: Point,
+) -> bool {
+    // Sample the middle of the pixel, so marks don't shift by one at cell edges
+    let (x, y) = camera.screen_to_cell((pixel.0 + 0.5, pixel.1 + 0.5));
+    if x < 0.0 || y < 0.0 || x >= grid.0 as f32 || y >= grid.1 as f32 {
+        return false;
+    }
+    match style {
+        BackgroundStyle::Off => false,
+        BackgroundStyle::Dots => {
+            if camera.cell_size < MIN_DOT_CELL_SIZE {
+                return false;
+            }
+            let radius = (camera.cell_size * 0.08).max(0.75);
+            let dx = (x.fract() - 0.5) * camera.cell_size;
+            let dy = (y.fract() - 0.5) * camera.cell_size;
+            dx * dx + dy * dy <= radius * radius
+        }
+        BackgroundStyle::Checkerboard => (x.floor() as i64 + y.floor() as i64) % 2 == 1,
+    }
+}
+
+/// The background of the whole screen as RGBA pixels, row by row, marks in
+/// `color` and everything else transparent
+/// Rendered once into a texture whenever the camera, screen or board changes,
+/// instead of drawing every mark each frame.
+pub fn render(
+    style: BackgroundStyle,
+    camera: &Camera,
+    screen: (usize, usize),
+    grid: (usize, usize),
+    color: [u8; 4],
+) -> Vec<u8> {
+    let mut bytes = vec![0; screen.0 * screen.1 * 4];
+    if style == BackgroundStyle::Off {
+        return bytes;
+    }
+    for (i, pixel) in bytes.chunks_exact_mut(4).enumerate() {
+        let at = ((i % screen.0) as f32, (i / screen.0) as f32);
+        if is_marked(style, camera, grid, at) {
+            pixel.copy_from_slice(&color);
+        }
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRID: (usize, usize) = (10, 10);
+
+    /// The screen position of the middle of a cell
+    fn center(camera: &Camera, cell: (f32, f32)) -> Point {
+        let (x, y) = camera.cell_to_screen(cell);
+        (x + camera.cell_size / 2.0, y + camera.cell_size / 2.0)
+    }
+
+    #[test]
+    fn test_checkerboard_follows_camera() {
+        let mut camera = Camera::new(10.0);
+        let check = BackgroundStyle::Checkerboard;
+        let shaded = |camera: &Camera, cell| is_marked(check, camera, GRID, center(camera, cell));
+        assert!(!shaded(&camera, (0.0, 0.0)));
+        assert!(shaded(&camera, (1.0, 0.0)));
+        assert!(shaded(&camera, (0.0, 1.0)));
+
+        // Panning and zooming keep the pattern on the same cells
+        camera.pan_by((-37.0, 12.0));
+        camera.zoom_at((100.0, 80.0), 2.5);
+        assert!(!shaded(&camera, (4.0, 4.0)));
+        assert!(shaded(&camera, (4.0, 5.0)));
+    }
+
+    #[test]
+    fn test_dots_at_cell_centers() {
+        let camera = Camera::new(20.0);
+        let dots = BackgroundStyle::Dots;
+        assert!(is_marked(dots, &camera, GRID, center(&camera, (3.0, 2.0))));
+        // Corners of a cell stay clear
+        assert!(!is_marked(dots, &camera, GRID, (60.0, 40.0)));
+
+        // Too small to tell dots apart
+        let zoomed_out = Camera::new(2.0);
+        assert!(!is_marked(
+            dots,
+            &zoomed_out,
+            GRID,
+            center(&zoomed_out, (3.0, 2.0))
+        ));
+    }
+
+    #[test]
+    fn test_only_on_board() {
+        let mut camera = Camera::new(10.0);
+        camera.origin = (-2.0, -2.0);
+        let check = BackgroundStyle::Checkerboard;
+        assert!(!is_marked(
+            check,
+            &camera,
+            GRID,
+            center(&camera, (-1.0, 0.0))
+        ));
+        assert!(!is_marked(
+            check,
+            &camera,
+            GRID,
+            center(&camera, (11.0, 0.0))
+        ));
+        assert!(is_marked(check, &camera, GRID, center(&camera, (1.0, 0.0))));
+        assert!(!is_marked(
+            BackgroundStyle::Off,
+            &camera,
+            GRID,
+            center(&camera, (1.0, 0.0))
+        ));
+    }
+
+    #[test]
+    fn test_render_size_and_colors() {
+        let camera = Camera::new(10.0);
+        let color = [1, 2, 3, 4];
+        let bytes = render(
+            BackgroundStyle::Checkerboard,
+            &camera,
+            (30, 20),
+            GRID,
+            color,
+        );
+        assert_eq!(bytes.len(), 30 * 20 * 4);
+        // First cell clear, second one shaded
+        assert_eq!(&bytes[..4], &[0, 0, 0, 0]);
+        assert_eq!(&bytes[10 * 4..11 * 4], &color);
+        assert!(render(BackgroundStyle::Off, &camera, (30, 20), GRID, color)
+            .iter()
+            .all(|&byte| byte == 0));
+    }
+}
@@ -0,0 +1,1099 @@
+use crate::bookmarks;
+use crate::conways::Edge;
+use crate::formats::FormatError;
+use crate::palette;
+use crate::slots;
+use crate::speed_presets;
+use crate::tool::Tool;
+use macroquad::input::KeyCode;
+
+/// File the key bindings are loaded from, in the working directory
+pub const KEYBINDINGS_FILE: &str = "keybindings.toml";
+
+/// Everything the player can trigger from the keyboard
+/// `Game::apply` carries these out, regardless of which key produced them.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputCommand {
+    TogglePause,
+    SlowDown,
+    SpeedUp,
+    /// Run as many generations per frame as the machine allows
+    ToggleTurbo,
+    /// Jump to speed preset `n` (1 to `speed_presets::PRESET_SLOTS`), or back
+    /// to the speed before it when it is already running
+    SpeedPreset(usize),
+    /// Show the previous generation kept by the timeline
+    StepBack,
+    /// Replay the next generation after stepping back, or run a single new one
+    StepForward,
+    /// Turn pausing once the board is stable or oscillating on or off
+    ToggleAutoPause,
+    CycleTheme,
+    /// Switch between square, circular and rounded cells
+    CycleCellShape,
+    ToggleGridLines,
+    /// Number the columns along the top of the window and the rows along the left
+    ToggleRulers,
+    /// Switch between a plain, dotted and checkered background
+    CycleBackground,
+    ToggleTrails,
+    /// Color live cells by how many live neighbors they have
+    ToggleNeighborColors,
+    /// Mark the cells the next generation brings to life or kills, while paused
+    TogglePreview,
+    /// Outline the cells of the previous generation under the current one
+    ToggleOnionSkin,
+    /// Tint the live cells that were placed apart from the ones the rule made
+    ToggleCellOrigins,
+    CycleSymmetry,
+    /// Make the left mouse button use a tool; the paste tool comes with pasting instead
+    SelectTool(Tool),
+    /// Make pasting rapid-fire: every click stamps a copy without pausing,
+    /// and drags lay down copies spaced by the pattern's size
+    ToggleStampBrush,
+    /// Switch between dead and wrapping edges
+    CycleBoundary,
+    /// Switch between tiling a wrapping board endlessly and showing strips of
+    /// it across the edges
+    ToggleSeamlessWrap,
+    /// Switch to the next rule of the list, keeping the board
+    CycleRule,
+    /// Type a rulestring to switch to
+    EnterRule,
+    /// Type a generation to fast-forward or rewind to
+    GoToGeneration,
+    /// Add `RESIZE_STEP` rows or columns to one side of the board
+    Grow(Edge),
+    /// Remove `RESIZE_STEP` rows or columns from one side, confirming first
+    /// when that would remove live cells
+    Shrink(Edge),
+    ToggleMinimap,
+    ToggleGraph,
+    /// Show the coordinates, state and live neighbors of the cell under the cursor
+    ToggleInspector,
+    /// Show or hide the keyboard cursor, which edits the board while paused
+    ToggleCursor,
+    /// Enter or leave the measure mode, where clicks on two cells pin the
+    /// distance between them
+    Measure,
+    /// Zoom and pan until every live cell is in view
+    FitView,
+    ToggleHelp,
+    Save,
+    LoadNextSave,
+    /// Close the game, asking first when the board has unsaved changes
+    Quit,
+    Randomize,
+    /// Kill every cell on the board
+    Clear,
+    /// Start a two-player Immigration match, end the current setup turn or
+    /// close the results
+    PlayMatch,
+    /// Fill the selection with random cells
+    FillNoise,
+    #[cfg(feature = "gif")]
+    ToggleRecording,
+    /// Switch between silence, ticks per generation and clicks on die-offs
+    #[cfg(feature = "audio")]
+    CycleSound,
+    /// Clear the board and run the `--script` again
+    #[cfg(feature = "scripting")]
+    RerunScript,
+    Screenshot,
+    /// Save the board as an SVG in the screenshots directory, in the theme's colors
+    ExportSvg,
+    Undo,
+    Redo,
+    Copy,
+    Cut,
+    ClearSelection,
+    Paste,
+    /// Put the selection, or every live cell, on the system clipboard as RLE
+    CopyRle,
+    /// Put the selection, or every live cell, on the system clipboard as a
+    /// share string
+    CopyShareString,
+    /// Paste RLE text or a share string from the system clipboard
+    PasteRle,
+    /// Show the `--reference` layer above the board, below it, or not at all
+    CycleReference,
+    /// Start or stop moving the reference layer with the keyboard cursor's keys
+    MoveReference,
+    /// Stamp the reference layer into the board where it sits
+    CommitReference,
+    NextPalettePage,
+    /// Stamp pattern `n` (1 to `palette::PAGE_SIZE`) of the current palette page
+    SelectPattern(usize),
+    /// Keep a copy of the board in bookmark `n` (1 to `bookmarks::BOOKMARK_SLOTS`)
+    SaveBookmark(usize),
+    /// Go back to the board in bookmark `n`, pausing
+    RestoreBookmark(usize),
+    /// Quick-save the whole session into slot `n` (1 to `slots::SLOT_COUNT`)
+    SaveSlot(usize),
+    /// Load the session kept in slot `n`
+    LoadSlot(usize),
+    RotatePaste,
+    FlipPasteHorizontal,
+    /// Mirror the pending paste top to bottom, or start moving the selection
+    /// when there is no paste
+    FlipPasteVertical,
+    RestoreSession,
+    /// Dismiss whatever is in progress: prompts, the help overlay, selections and pastes
+    Cancel,
+}
+
+/// The tools that have a key of their own, in the order the help overlay lists them
+pub const SELECTABLE_TOOLS: [Tool; 6] = [
+    Tool::Toggle,
+    Tool::Paint,
+    Tool::Erase,
+    Tool::FloodFill,
+    Tool::Select,
+    Tool::Tile,
+];
+
+impl InputCommand {
+    /// Every command, in the order the help overlay and the default file list them
+    pub fn all() -> Vec<InputCommand> {
+        use InputCommand::*;
+
+        let mut commands = vec![ToggleHelp, TogglePause, SlowDown, SpeedUp, ToggleTurbo];
+        commands.extend((1..=speed_presets::PRESET_SLOTS).map(SpeedPreset));
+        commands.extend([
+            StepBack,
+            StepForward,
+            ToggleAutoPause,
+            Randomize,
+            Clear,
+            PlayMatch,
+        ]);
+        commands.extend(SELECTABLE_TOOLS.map(SelectTool));
+        commands.extend([
+            ToggleStampBrush,
+            CycleSymmetry,
+            CycleBoundary,
+            ToggleSeamlessWrap,
+            CycleRule,
+            EnterRule,
+            GoToGeneration,
+            CycleTheme,
+            CycleCellShape,
+            ToggleGridLines,
+            ToggleRulers,
+            CycleBackground,
+            ToggleTrails,
+            ToggleNeighborColors,
+            TogglePreview,
+            ToggleOnionSkin,
+            ToggleCellOrigins,
+            ToggleMinimap,
+            ToggleGraph,
+            ToggleInspector,
+            ToggleCursor,
+            Measure,
+            FitView,
+            Save,
+            LoadNextSave,
+            Quit,
+            Screenshot,
+            ExportSvg,
+        ]);
+        #[cfg(feature = "gif")]
+        commands.push(ToggleRecording);
+        #[cfg(feature = "audio")]
+        commands.push(CycleSound);
+        #[cfg(feature = "scripting")]
+        commands.push(RerunScript);
+        commands.extend([
+            Undo,
+            Redo,
+            Copy,
+            Cut,
+            ClearSelection,
+            FillNoise,
+            Paste,
+            CopyRle,
+            CopyShareString,
+            PasteRle,
+            CycleReference,
+            MoveReference,
+            CommitReference,
+            RotatePaste,
+            FlipPasteHorizontal,
+            FlipPasteVertical,
+            NextPalettePage,
+        ]);
+        commands.extend((1..=palette::PAGE_SIZE).map(SelectPattern));
+        commands.extend(
+            (1..=bookmarks::BOOKMARK_SLOTS).flat_map(|n| [SaveBookmark(n), RestoreBookmark(n)]),
+        );
+        commands.extend((1..=slots::SLOT_COUNT).flat_map(|n| [SaveSlot(n), LoadSlot(n)]));
+        for edge in [Edge::Left, Edge::Right, Edge::Top, Edge::Bottom] {
+            commands.extend([Grow(edge), Shrink(edge)]);
+        }
+        commands.extend([RestoreSession, Cancel]);
+        commands
+    }
+
+    /// Name of the action in `keybindings.toml`
+    pub fn name(self) -> String {
+        let name = match self {
+            InputCommand::TogglePause => "toggle_pause",
+            InputCommand::SlowDown => "slow_down",
+            InputCommand::SpeedUp => "speed_up",
+            InputCommand::ToggleTurbo => "toggle_turbo",
+            InputCommand::StepBack => "step_back",
+            InputCommand::StepForward => "step_forward",
+            InputCommand::ToggleAutoPause => "toggle_auto_pause",
+            InputCommand::CycleTheme => "cycle_theme",
+            InputCommand::CycleCellShape => "cycle_cell_shape",
+            InputCommand::ToggleGridLines => "toggle_grid_lines",
+            InputCommand::ToggleRulers => "toggle_rulers",
+            InputCommand::CycleBackground => "cycle_background",
+            InputCommand::ToggleTrails => "toggle_trails",
+            InputCommand::ToggleNeighborColors => "toggle_neighbor_colors",
+            InputCommand::TogglePreview => "toggle_preview",
+            InputCommand::ToggleOnionSkin => "toggle_onion_skin",
+            InputCommand::ToggleCellOrigins => "toggle_cell_origins",
+            InputCommand::CycleSymmetry => "cycle_symmetry",
+            InputCommand::ToggleStampBrush => "toggle_stamp_brush",
+            InputCommand::CycleBoundary => "cycle_boundary",
+            InputCommand::ToggleSeamlessWrap => "toggle_seamless_wrap",
+            InputCommand::CycleRule => "cycle_rule",
+            InputCommand::EnterRule => "enter_rule",
+            InputCommand::GoToGeneration => "goto_generation",
+            InputCommand::ToggleMinimap => "toggle_minimap",
+            InputCommand::ToggleGraph => "toggle_graph",
+            InputCommand::ToggleInspector => "toggle_inspector",
+            InputCommand::ToggleCursor => "toggle_cursor",
+            InputCommand::Measure => "measure",
+            InputCommand::FitView => "fit_view",
+            InputCommand::ToggleHelp => "toggle_help",
+            InputCommand::Save => "save",
+            InputCommand::LoadNextSave => "load_next_save",
+            InputCommand::Quit => "quit",
+            InputCommand::Randomize => "randomize",
+            InputCommand::Clear => "clear",
+            InputCommand::PlayMatch => "play_match",
+            InputCommand::FillNoise => "fill_noise",
+            #[cfg(feature = "gif")]
+            InputCommand::ToggleRecording => "toggle_recording",
+            #[cfg(feature = "audio")]
+            InputCommand::CycleSound => "cycle_sound",
+            #[cfg(feature = "scripting")]
+            InputCommand::RerunScript => "rerun_script",
+            InputCommand::Screenshot => "screenshot",
+            InputCommand::ExportSvg => "export_svg",
+            InputCommand::Undo => "undo",
+            InputCommand::Redo => "redo",
+            InputCommand::Copy => "copy",
+            InputCommand::Cut => "cut",
+            InputCommand::ClearSelection => "clear_selection",
+            InputCommand::Paste => "paste",
+            InputCommand::CopyRle => "copy_rle",
+            InputCommand::CopyShareString => "copy_share_string",
+            InputCommand::PasteRle => "paste_rle",
+            InputCommand::CycleReference => "cycle_reference",
+            InputCommand::MoveReference => "move_reference",
+            InputCommand::CommitReference => "commit_reference",
+            InputCommand::NextPalettePage => "next_palette_page",
+            InputCommand::SpeedPreset(n) => return format!("speed_preset_{n}"),
+            InputCommand::SelectPattern(n) => return format!("select_pattern_{n}"),
+            InputCommand::SaveBookmark(n) => return format!("save_bookmark_{n}"),
+            InputCommand::RestoreBookmark(n) => return format!("restore_bookmark_{n}"),
+            InputCommand::SaveSlot(n) => return format!("save_slot_{n}"),
+            InputCommand::LoadSlot(n) => return format!("load_slot_{n}"),
+            InputCommand::SelectTool(tool) => return format!("tool_{}", tool.label()),
+            InputCommand::Grow(edge) => return format!("grow_{}", edge_name(edge)),
+            InputCommand::Shrink(edge) => return format!("shrink_{}", edge_name(edge)),
+            InputCommand::RotatePaste => "rotate_paste",
+            InputCommand::FlipPasteHorizontal => "flip_paste_horizontal",
+            InputCommand::FlipPasteVertical => "flip_paste_vertical",
+            InputCommand::RestoreSession => "restore_session",
+            InputCommand::Cancel => "cancel",
+        };
+        name.to_string()
+    }
+
+    /// The command with the given `keybindings.toml` name
+    pub fn from_name(name: &str) -> Option<InputCommand> {
+        Self::all()
+            .into_iter()
+            .find(|command| command.name() == name)
+    }
+
+    /// One line description shown by the help overlay
+    pub fn description(self) -> String {
+        let text = match self {
+            InputCommand::TogglePause => "Pause / resume",
+            InputCommand::SlowDown => "Slow down",
+            InputCommand::SpeedUp => "Speed up",
+            InputCommand::ToggleTurbo => "Turbo: run as fast as possible",
+            InputCommand::StepBack => "Step back a generation",
+            InputCommand::StepForward => "Step forward a generation",
+            InputCommand::ToggleAutoPause => "Toggle pausing when stable",
+            InputCommand::CycleTheme => "Next color theme",
+            InputCommand::CycleCellShape => "Next cell shape",
+            InputCommand::ToggleGridLines => "Toggle grid lines",
+            InputCommand::ToggleRulers => "Toggle coordinate rulers",
+            InputCommand::CycleBackground => "Next background style",
+            InputCommand::ToggleTrails => "Toggle trails",
+            InputCommand::ToggleNeighborColors => "Color cells by live neighbors",
+            InputCommand::TogglePreview => "Preview the next generation",
+            InputCommand::ToggleOnionSkin => "Outline the previous generation",
+            InputCommand::ToggleCellOrigins => "Tint painted cells apart from born ones",
+            InputCommand::CycleSymmetry => "Next painting symmetry",
+            InputCommand::ToggleStampBrush => "Stamp patterns as a brush",
+            InputCommand::CycleBoundary => "Dead / wrapping edges",
+            InputCommand::ToggleSeamlessWrap => "Tile a wrapping board endlessly",
+            InputCommand::CycleRule => "Next rule",
+            InputCommand::EnterRule => "Type a rule",
+            InputCommand::GoToGeneration => "Go to a generation",
+            InputCommand::ToggleMinimap => "Toggle minimap",
+            InputCommand::ToggleGraph => "Toggle population graph",
+            InputCommand::ToggleInspector => "Toggle cell inspector",
+            InputCommand::ToggleCursor => "Toggle keyboard cursor",
+            InputCommand::Measure => "Measure between two cells",
+            InputCommand::FitView => "Zoom to fit the live cells",
+            InputCommand::ToggleHelp => "Toggle this help",
+            InputCommand::Save => "Save to saves/",
+            InputCommand::LoadNextSave => "Load next save",
+            InputCommand::Quit => "Quit",
+            InputCommand::Randomize => "Random board",
+            InputCommand::Clear => "Clear the board",
+            InputCommand::PlayMatch => "Two-player match / end setup turn",
+            #[cfg(feature = "gif")]
+            InputCommand::ToggleRecording => "Start / stop GIF recording",
+            #[cfg(feature = "audio")]
+            InputCommand::CycleSound => "Next sound mode",
+            #[cfg(feature = "scripting")]
+            InputCommand::RerunScript => "Clear the board and run the script again",
+            InputCommand::Screenshot => "Save a PNG screenshot",
+            InputCommand::ExportSvg => "Save an SVG of the board",
+            InputCommand::Undo => "Undo",
+            InputCommand::Redo => "Redo",
+            InputCommand::Copy => "Copy selection",
+            InputCommand::Cut => "Cut selection",
+            InputCommand::ClearSelection => "Clear selection",
+            InputCommand::FillNoise => "Fill selection with random cells",
+            InputCommand::Paste => "Paste clipboard",
+            InputCommand::CopyRle => "Copy as RLE to the system clipboard",
+            InputCommand::CopyShareString => "Copy as a share string to the system clipboard",
+            InputCommand::PasteRle => "Paste RLE or a share string from the system clipboard",
+            InputCommand::CycleReference => "Show the reference layer above, below or not at all",
+            InputCommand::MoveReference => "Move the reference layer with WASD or HJKL",
+            InputCommand::CommitReference => "Stamp the reference layer into the board",
+            InputCommand::NextPalettePage => "Next pattern page",
+            InputCommand::SpeedPreset(n) => return format!("Speed preset {n}"),
+            InputCommand::SelectPattern(n) => return format!("Pattern {n} of the page"),
+            InputCommand::SaveBookmark(n) => return format!("Bookmark the board as {n}"),
+            InputCommand::RestoreBookmark(n) => return format!("Go back to bookmark {n}"),
+            InputCommand::SaveSlot(n) => return format!("Quick-save the session in slot {n}"),
+            InputCommand::LoadSlot(n) => return format!("Quick-load the session in slot {n}"),
+            InputCommand::SelectTool(tool) => return format!("Tool: {}", tool.label()),
+            InputCommand::Grow(edge) => return format!("Grow board {}", edge_name(edge)),
+            InputCommand::Shrink(edge) => return format!("Shrink board {}", edge_name(edge)),
+            InputCommand::RotatePaste => "Rotate paste",
+            InputCommand::FlipPasteHorizontal => "Flip paste horizontally",
+            InputCommand::FlipPasteVertical => "Flip paste vertically / move selection",
+            InputCommand::RestoreSession => "Restore previous session / reload pattern file",
+            InputCommand::Cancel => "Cancel / close",
+        };
+        text.to_string()
+    }
+}
+
+/// How an edge is spelled in action names and descriptions
+fn edge_name(edge: Edge) -> &'static str {
+    match edge {
+        Edge::Left => "left",
+        Edge::Right => "right",
+        Edge::Top => "top",
+        Edge::Bottom => "bottom",
+    }
+}
+
+/// A key, plus the modifiers that have to be held with it, triggering a command
+///
+/// `ctrl` has to match exactly, so `V` and `Ctrl+V` can do different things.
+/// `shift` is only checked when required, so `Shift+/` still works as `?` on
+/// layouts where that needs shift.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Binding {
+    pub key: KeyCode,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub command: InputCommand,
+}
+
+impl Binding {
+    pub const fn new(key: KeyCode, command: InputCommand) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            command,
+        }
+    }
+
+    pub const fn ctrl(key: KeyCode, command: InputCommand) -> Self {
+        Self {
+            ctrl: true,
+            ..Self::new(key, command)
+        }
+    }
+
+    pub const fn shift(key: KeyCode, command: InputCommand) -> Self {
+        Self {
+            shift: true,
+            ..Self::new(key, command)
+        }
+    }
+
+    pub const fn ctrl_shift(key: KeyCode, command: InputCommand) -> Self {
+        Self {
+            ctrl: true,
+            shift: true,
+            ..Self::new(key, command)
+        }
+    }
+
+    /// Whether the binding fires for its key given the modifiers currently held
+    pub fn matches(&self, ctrl: bool, shift: bool) -> bool {
+        self.ctrl == ctrl && (!self.shift || shift)
+    }
+
+    /// Human readable form, e.g. `Ctrl+S`
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        if self.ctrl {
+            label.push_str("Ctrl+");
+        }
+        if self.shift {
+            label.push_str("Shift+");
+        }
+        label.push_str(key_name(self.key).unwrap_or("?"));
+        label
+    }
+}
+
+/// The commands a key press triggers given the modifiers held
+/// Bindings that require shift win over the same key without it, so
+/// `Ctrl+Shift+Z` doesn't also fire `Ctrl+Z`.
+pub fn commands_for(
+    bindings: &[Binding],
+    key: KeyCode,
+    ctrl: bool,
+    shift: bool,
+) -> Vec<InputCommand> {
+    let matching: Vec<&Binding> = bindings
+        .iter()
+        .filter(|binding| binding.key == key && binding.matches(ctrl, shift))
+        .collect();
+    let specific = matching.iter().any(|binding| binding.shift);
+    matching
+        .into_iter()
+        .filter(|binding| binding.shift || !specific)
+        .map(|binding| binding.command)
+        .collect()
+}
+
+/// The keys available for bindings, with the names used to display them
+pub const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::Key0, "0"),
+    (KeyCode::Key1, "1"),
+    (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"),
+    (KeyCode::Key5, "5"),
+    (KeyCode::Key6, "6"),
+    (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"),
+    (KeyCode::Key9, "9"),
+    (KeyCode::F1, "F1"),
+    (KeyCode::F2, "F2"),
+    (KeyCode::F3, "F3"),
+    (KeyCode::F4, "F4"),
+    (KeyCode::F5, "F5"),
+    (KeyCode::F6, "F6"),
+    (KeyCode::F7, "F7"),
+    (KeyCode::F8, "F8"),
+    (KeyCode::F9, "F9"),
+    (KeyCode::F10, "F10"),
+    (KeyCode::F11, "F11"),
+    (KeyCode::F12, "F12"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Escape, "Escape"),
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Minus, "-"),
+    (KeyCode::Equal, "="),
+    (KeyCode::Slash, "/"),
+    (KeyCode::Backslash, "\\"),
+    (KeyCode::Comma, ","),
+    (KeyCode::Period, "."),
+    (KeyCode::Semicolon, ";"),
+    (KeyCode::Apostrophe, "'"),
+    (KeyCode::LeftBracket, "["),
+    (KeyCode::RightBracket, "]"),
+    (KeyCode::GraveAccent, "`"),
+];
+
+/// Parse a key with optional modifiers, e.g. `Ctrl+S` or `shift+/`
+/// Key and modifier names are case insensitive.
+pub fn parse_key(spec: &str) -> Option<(KeyCode, bool, bool)> {
+    let mut parts: Vec<&str> = spec.split('+').map(str::trim).collect();
+    let name = parts.pop()?;
+    let key = KEY_NAMES
+        .iter()
+        .find(|(_, key_name)| key_name.eq_ignore_ascii_case(name))
+        .map(|(code, _)| *code)?;
+
+    let (mut ctrl, mut shift) = (false, false);
+    for modifier in parts {
+        match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => ctrl = true,
+            "shift" => shift = true,
+            _ => return None,
+        }
+    }
+    Some((key, ctrl, shift))
+}
+
+/// Display name of a key, if it can be bound
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(code, _)| *code == key)
+        .map(|(_, name)| *name)
+}
+
+/// The built-in bindings, in the order the help overlay lists them
+pub fn default_bindings() -> Vec<Binding> {
+    use InputCommand::*;
+
+    let mut bindings = vec![
+        Binding::new(KeyCode::H, ToggleHelp),
+        Binding::shift(KeyCode::Slash, ToggleHelp),
+        Binding::new(KeyCode::Space, TogglePause),
+        Binding::new(KeyCode::Minus, SlowDown),
+        Binding::new(KeyCode::Equal, SpeedUp),
+        Binding::shift(KeyCode::Enter, ToggleTurbo),
+        Binding::shift(KeyCode::Key1, SpeedPreset(1)),
+        Binding::shift(KeyCode::Key2, SpeedPreset(2)),
+        Binding::shift(KeyCode::Key3, SpeedPreset(3)),
+        Binding::shift(KeyCode::Key4, SpeedPreset(4)),
+        Binding::new(KeyCode::Left, StepBack),
+        Binding::new(KeyCode::Right, StepForward),
+        Binding::new(KeyCode::A, ToggleAutoPause),
+        Binding::new(KeyCode::N, Randomize),
+        Binding::new(KeyCode::Backspace, Clear),
+        Binding::new(KeyCode::J, PlayMatch),
+        Binding::new(KeyCode::O, SelectTool(Tool::Toggle)),
+        Binding::new(KeyCode::D, SelectTool(Tool::Paint)),
+        Binding::new(KeyCode::E, SelectTool(Tool::Erase)),
+        Binding::new(KeyCode::U, SelectTool(Tool::FloodFill)),
+        Binding::new(KeyCode::S, SelectTool(Tool::Select)),
+        Binding::new(KeyCode::Q, SelectTool(Tool::Tile)),
+        Binding::shift(KeyCode::D, ToggleStampBrush),
+        Binding::new(KeyCode::M, CycleSymmetry),
+        Binding::new(KeyCode::W, CycleBoundary),
+        Binding::shift(KeyCode::W, ToggleSeamlessWrap),
+        Binding::new(KeyCode::RightBracket, CycleRule),
+        Binding::ctrl(KeyCode::R, EnterRule),
+        Binding::ctrl(KeyCode::G, GoToGeneration),
+        Binding::new(KeyCode::T, CycleTheme),
+        Binding::shift(KeyCode::T, CycleCellShape),
+        Binding::new(KeyCode::G, ToggleGridLines),
+        Binding::shift(KeyCode::R, ToggleRulers),
+        Binding::shift(KeyCode::G, CycleBackground),
+        Binding::new(KeyCode::L, ToggleTrails),
+        Binding::shift(KeyCode::I, ToggleNeighborColors),
+        Binding::shift(KeyCode::N, TogglePreview),
+        Binding::shift(KeyCode::P, ToggleOnionSkin),
+        Binding::shift(KeyCode::O, ToggleCellOrigins),
+        Binding::new(KeyCode::B, ToggleMinimap),
+        Binding::new(KeyCode::P, ToggleGraph),
+        Binding::new(KeyCode::I, ToggleInspector),
+        Binding::new(KeyCode::C, ToggleCursor),
+        Binding::shift(KeyCode::M, Measure),
+        Binding::new(KeyCode::Z, FitView),
+        Binding::ctrl(KeyCode::S, Save),
+        Binding::ctrl(KeyCode::O, LoadNextSave),
+        Binding::ctrl(KeyCode::Q, Quit),
+        Binding::new(KeyCode::F12, Screenshot),
+        Binding::shift(KeyCode::F12, ExportSvg),
+    ];
+    #[cfg(feature = "gif")]
+    bindings.push(Binding::shift(KeyCode::F9, ToggleRecording));
+    #[cfg(feature = "audio")]
+    bindings.push(Binding::shift(KeyCode::Z, CycleSound));
+    #[cfg(feature = "scripting")]
+    bindings.push(Binding::shift(KeyCode::Backspace, RerunScript));
+    bindings.extend([
+        Binding::ctrl(KeyCode::Z, Undo),
+        Binding::ctrl(KeyCode::Y, Redo),
+        Binding::ctrl_shift(KeyCode::Z, Redo),
+        Binding::ctrl(KeyCode::C, Copy),
+        Binding::ctrl(KeyCode::X, Cut),
+        Binding::new(KeyCode::Delete, ClearSelection),
+        Binding::new(KeyCode::X, FillNoise),
+        Binding::ctrl(KeyCode::V, Paste),
+        Binding::ctrl_shift(KeyCode::C, CopyRle),
+        Binding::ctrl_shift(KeyCode::S, CopyShareString),
+        Binding::ctrl_shift(KeyCode::V, PasteRle),
+        Binding::ctrl(KeyCode::L, CycleReference),
+        Binding::ctrl_shift(KeyCode::L, MoveReference),
+        Binding::ctrl(KeyCode::Enter, CommitReference),
+        Binding::new(KeyCode::R, RotatePaste),
+        Binding::new(KeyCode::F, FlipPasteHorizontal),
+        Binding::new(KeyCode::V, FlipPasteVertical),
+        Binding::new(KeyCode::Tab, NextPalettePage),
+    ]);
+    let number_keys = [
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+        KeyCode::Key6,
+        KeyCode::Key7,
+        KeyCode::Key8,
+        KeyCode::Key9,
+    ];
+    // Plain number keys go back to bookmarks, so the palette takes Ctrl+Shift
+    for (i, key) in number_keys.into_iter().enumerate() {
+        bindings.push(Binding::ctrl_shift(key, SelectPattern(i + 1)));
+    }
+    for (i, key) in number_keys.into_iter().enumerate() {
+        bindings.extend([
+            Binding::ctrl(key, SaveBookmark(i + 1)),
+            Binding::new(key, RestoreBookmark(i + 1)),
+        ]);
+    }
+    let function_keys = [
+        KeyCode::F1,
+        KeyCode::F2,
+        KeyCode::F3,
+        KeyCode::F4,
+        KeyCode::F5,
+        KeyCode::F6,
+        KeyCode::F7,
+        KeyCode::F8,
+        KeyCode::F9,
+    ];
+    for (i, key) in function_keys.into_iter().enumerate() {
+        bindings.extend([
+            Binding::ctrl(key, SaveSlot(i + 1)),
+            Binding::new(key, LoadSlot(i + 1)),
+        ]);
+    }
+    for (key, edge) in [
+        (KeyCode::Left, Edge::Left),
+        (KeyCode::Right, Edge::Right),
+        (KeyCode::Up, Edge::Top),
+        (KeyCode::Down, Edge::Bottom),
+    ] {
+        bindings.extend([
+            Binding::ctrl(key, Grow(edge)),
+            Binding::ctrl_shift(key, Shrink(edge)),
+        ]);
+    }
+    bindings.extend([
+        Binding::new(KeyCode::Y, RestoreSession),
+        Binding::new(KeyCode::Escape, Cancel),
+    ]);
+    bindings
+}
+
+/// Read the bindings from `keybindings.toml` contents
+///
+/// Each line maps an action to a key or a list of keys:
+/// ```toml
+/// toggle_pause = "Space"
+/// save = ["Ctrl+S", "F5"]
+/// ```
+/// Actions missing from the file keep their default keys. This is a small
+/// subset of TOML: one `name = value` pair per line, `#` comments, and basic
+/// strings with `\\` and `\"` escapes.
+///
+/// # Returns
+/// The complete binding table, or a `FormatError` for unknown actions or keys,
+/// malformed lines, and keys bound to more than one action
+pub fn parse_bindings(input: &str) -> Result<Vec<Binding>, FormatError> {
+    let mut configured: Vec<(usize, Binding)> = Vec::new();
+
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| FormatError::new(line_number, "expected 'action = \"key\"'"))?;
+        let name = name.trim();
+        let command = InputCommand::from_name(name)
+            .ok_or_else(|| FormatError::new(line_number, format!("unknown action '{name}'")))?;
+        if configured.iter().any(|(_, b)| b.command == command) {
+            return Err(FormatError::new(
+                line_number,
+                format!("action '{name}' is listed twice"),
+            ));
+        }
+
+        for spec in parse_value(value.trim()).map_err(|msg| FormatError::new(line_number, msg))? {
+            let (key, ctrl, shift) = parse_key(&spec)
+                .ok_or_else(|| FormatError::new(line_number, format!("unknown key '{spec}'")))?;
+            configured.push((
+                line_number,
+                Binding {
+                    key,
+                    ctrl,
+                    shift,
+                    command,
+                },
+            ));
+        }
+    }
+
+    // Configured actions replace their defaults, the others keep them
+    let mut bindings: Vec<(usize, Binding)> = default_bindings()
+        .into_iter()
+        .filter(|default| !configured.iter().any(|(_, b)| b.command == default.command))
+        .map(|binding| (0, binding))
+        .collect();
+    for (line_number, binding) in configured {
+        let same_key = |(_, other): &&(usize, Binding)| {
+            (other.key, other.ctrl, other.shift) == (binding.key, binding.ctrl, binding.shift)
+        };
+        if let Some((_, other)) = bindings.iter().find(same_key) {
+            return Err(FormatError::new(
+                line_number,
+                format!(
+                    "{} is already bound to '{}'",
+                    binding.label(),
+                    other.command.name()
+                ),
+            ));
+        }
+        bindings.push((line_number, binding));
+    }
+
+    // Keep the help overlay in the same order as the defaults
+    let order = InputCommand::all();
+    bindings.sort_by_key(|(_, b)| order.iter().position(|c| *c == b.command));
+    Ok(bindings.into_iter().map(|(_, binding)| binding).collect())
+}
+
+/// The contents of a `keybindings.toml` holding the default bindings
+pub fn default_file() -> String {
+    let mut out = String::from("# Key bindings: action = \"key\" or action = [\"key\", ...]\n");
+    out.push_str("# Modifiers are written as Ctrl+ and Shift+, e.g. \"Ctrl+S\"\n");
+    let defaults = default_bindings();
+    for command in InputCommand::all() {
+        let keys: Vec<String> = defaults
+            .iter()
+            .filter(|binding| binding.command == command)
+            .map(|binding| {
+                format!(
+                    "\"{}\"",
+                    binding.label().replace('\\', "\\\\").replace('"', "\\\"")
+                )
+            })
+            .collect();
+        let value = match keys.as_slice() {
+            [key] => key.clone(),
+            keys => format!("[{}]", keys.join(", ")),
+        };
+        out.push_str(&format!("{} = {}\n", command.name(), value));
+    }
+    out
+}
+
+/// Remove a trailing `#` comment, ignoring `#` inside strings
+pub fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => (),
+        }
+    }
+    line
+}
+
+/// Parse a basic string or an array of basic strings
+fn parse_value(value: &str) -> Result<Vec<String>, String> {
+    let mut chars = value.chars().peekable();
+    let array = chars.peek() == Some(&'[');
+    if array {
+        chars.next();
+    }
+
+    let mut strings = Vec::new();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.next() {
+            Some('"') => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('\\' | '"')) => string.push(c),
+                            _ => return Err("unsupported escape sequence".to_string()),
+                        },
+                        Some(c) => string.push(c),
+                        None => return Err("unterminated string".to_string()),
+                    }
+                }
+                strings.push(string);
+            }
+            Some(']') if array => break,
+            _ => return Err("expected a quoted key name".to_string()),
+        }
+        if !array {
+            break;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.next() {
+            Some(',') => (),
+            Some(']') => break,
+            _ => return Err("expected ',' or ']'".to_string()),
+        }
+    }
+
+    if chars.any(|c| !c.is_whitespace()) {
+        return Err("unexpected characters after the value".to_string());
+    }
+    Ok(strings)
+}
+
+/// Help overlay entries: each command with all the keys bound to it joined,
+/// e.g. `("H / Shift+/", "Toggle this help")`, in the order of first appearance
+pub fn help_entries(bindings: &[Binding]) -> Vec<(String, String)> {
+    let mut entries: Vec<(InputCommand, String)> = Vec::new();
+    for binding in bindings {
+        match entries.iter_mut().find(|(cmd, _)| *cmd == binding.command) {
+            Some((_, keys)) => {
+                keys.push_str(" / ");
+                keys.push_str(&binding.label());
+            }
+            None => entries.push((binding.command, binding.label())),
+        }
+    }
+    entries
+        .into_iter()
+        .map(|(command, keys)| (keys, command.description()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modifiers() {
+        let paste = Binding::ctrl(KeyCode::V, InputCommand::Paste);
+        let flip = Binding::new(KeyCode::V, InputCommand::FlipPasteVertical);
+        assert!(paste.matches(true, false));
+        assert!(!paste.matches(false, false));
+        assert!(flip.matches(false, false));
+        assert!(!flip.matches(true, false));
+
+        // Shift is only checked when the binding asks for it
+        let help = Binding::shift(KeyCode::Slash, InputCommand::ToggleHelp);
+        assert!(help.matches(false, true));
+        assert!(!help.matches(false, false));
+        assert!(flip.matches(false, true));
+    }
+
+    #[test]
+    fn test_shift_bindings_take_precedence() {
+        let bindings = default_bindings();
+        assert_eq!(
+            commands_for(&bindings, KeyCode::Z, true, false),
+            vec![InputCommand::Undo]
+        );
+        assert_eq!(
+            commands_for(&bindings, KeyCode::Z, true, true),
+            vec![InputCommand::Redo]
+        );
+        // Shift is still ignored by bindings that don't mention it
+        assert_eq!(
+            commands_for(&bindings, KeyCode::Space, false, true),
+            vec![InputCommand::TogglePause]
+        );
+        assert_eq!(
+            commands_for(&bindings, KeyCode::Z, false, false),
+            vec![InputCommand::FitView]
+        );
+    }
+
+    #[test]
+    fn test_labels() {
+        assert_eq!(
+            Binding::ctrl(KeyCode::S, InputCommand::Save).label(),
+            "Ctrl+S"
+        );
+        assert_eq!(
+            Binding::shift(KeyCode::Slash, InputCommand::ToggleHelp).label(),
+            "Shift+/"
+        );
+        assert_eq!(
+            Binding::new(KeyCode::Minus, InputCommand::SlowDown).label(),
+            "-"
+        );
+    }
+
+    #[test]
+    fn test_default_keys_are_unique() {
+        let bindings = default_bindings();
+        for (i, a) in bindings.iter().enumerate() {
+            assert!(key_name(a.key).is_some(), "{:?} has no name", a.key);
+            for b in &bindings[i + 1..] {
+                assert!(
+                    (a.key, a.ctrl, a.shift) != (b.key, b.ctrl, b.shift),
+                    "{} is bound twice",
+                    a.label()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_command_has_a_default_binding() {
+        let bindings = default_bindings();
+        for command in InputCommand::all() {
+            assert!(
+                bindings.iter().any(|binding| binding.command == command),
+                "{} has no default binding",
+                command.name()
+            );
+            assert_eq!(InputCommand::from_name(&command.name()), Some(command));
+        }
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("Ctrl+S"), Some((KeyCode::S, true, false)));
+        assert_eq!(parse_key("shift + /"), Some((KeyCode::Slash, false, true)));
+        assert_eq!(parse_key("space"), Some((KeyCode::Space, false, false)));
+        assert_eq!(parse_key("Hyper+S"), None);
+        assert_eq!(parse_key("Ctrl+"), None);
+        assert_eq!(parse_key("Foo"), None);
+    }
+
+    #[test]
+    fn test_parse_bindings() {
+        let input = "# swap pause and help\n\
+                     toggle_pause = \"K\"\n\
+                     save = [\"Ctrl+S\", \"F10\"] # two keys\n\
+                     \n";
+        let bindings = parse_bindings(input).unwrap();
+
+        let keys = |command| -> Vec<String> {
+            bindings
+                .iter()
+                .filter(|b| b.command == command)
+                .map(Binding::label)
+                .collect()
+        };
+        assert_eq!(keys(InputCommand::TogglePause), vec!["K"]);
+        assert_eq!(keys(InputCommand::Save), vec!["Ctrl+S", "F10"]);
+        // Unlisted actions keep their defaults
+        assert_eq!(keys(InputCommand::ToggleHelp), vec!["H", "Shift+/"]);
+    }
+
+    #[test]
+    fn test_parse_bindings_errors() {
+        let error = |input: &str| parse_bindings(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("\nexplode = \"E\""),
+            "line 2: unknown action 'explode'"
+        );
+        assert_eq!(
+            error("toggle_pause = \"Hyper\""),
+            "line 1: unknown key 'Hyper'"
+        );
+        assert_eq!(
+            error("toggle_pause = Space"),
+            "line 1: expected a quoted key name"
+        );
+        assert_eq!(error("toggle_pause"), "line 1: expected 'action = \"key\"'");
+        assert_eq!(
+            error("toggle_pause = \"K\"\ntoggle_pause = \"O\""),
+            "line 2: action 'toggle_pause' is listed twice"
+        );
+        // Duplicates within the file, and against remaining defaults
+        assert_eq!(
+            error("toggle_pause = \"K\"\nsave = \"K\""),
+            "line 2: K is already bound to 'toggle_pause'"
+        );
+        assert_eq!(
+            error("toggle_pause = \"B\""),
+            "line 1: B is already bound to 'toggle_minimap'"
+        );
+    }
+
+    #[test]
+    fn test_default_file_round_trips() {
+        assert_eq!(parse_bindings(&default_file()).unwrap(), default_bindings());
+    }
+
+    #[test]
+    fn test_help_entries_group_keys() {
+        let entries = help_entries(&default_bindings());
+        assert_eq!(
+            entries[0],
+            ("H / Shift+/".to_string(), "Toggle this help".to_string())
+        );
+        // One entry per command: help and redo have two keys each
+        let commands = default_bindings().len() - 2;
+        assert_eq!(entries.len(), commands);
+    }
+}
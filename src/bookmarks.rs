@@ -0,0 +1,106 @@
+use crate::conways::Grid;
+
+/// Number of bookmark slots, one per number key
+pub const BOOKMARK_SLOTS: usize = 9;
+
+/// Boards saved by the player to jump back to, numbered 1 to `BOOKMARK_SLOTS`
+///
+/// Every bookmark is a copy of the board, generation included, owned by the
+/// store: restoring hands out another copy, so simulating the restored board
+/// never changes the bookmark. Undo and redo leave bookmarks alone.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Bookmarks {
+    slots: [Option<Grid>; BOOKMARK_SLOTS],
+}
+
+impl Bookmarks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep a copy of `grid` in `slot`, from 1 to `BOOKMARK_SLOTS`
+    ///
+    /// # Returns
+    /// The bookmark that was in the slot before, or `None` if it was empty or
+    /// `slot` doesn't exist
+    pub fn save(&mut self, slot: usize, grid: &Grid) -> Option<Grid> {
+        let entry = self.slots.get_mut(slot.checked_sub(1)?)?;
+        entry.replace(grid.clone())
+    }
+
+    /// A copy of the board bookmarked in `slot`, or `None` if the slot is empty
+    pub fn restore(&self, slot: usize) -> Option<Grid> {
+        self.slots.get(slot.checked_sub(1)?)?.clone()
+    }
+
+    /// The occupied slots and their boards, in slot order
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Grid)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, grid)| Some((i + 1, grid.as_ref()?)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::CellState;
+
+    fn blinker() -> Grid {
+        let mut grid = Grid::new(5, 5);
+        for x in 1..=3 {
+            grid.set(x, 2, CellState::Alive);
+        }
+        grid
+    }
+
+    #[test]
+    fn test_restore_is_a_copy() {
+        let mut bookmarks = Bookmarks::new();
+        let mut grid = blinker();
+        grid.set_generation(17);
+        assert_eq!(bookmarks.save(3, &grid), None);
+
+        // Neither the board nor the restored copy touch the bookmark
+        grid.next_cell_generation();
+        let mut restored = bookmarks.restore(3).unwrap();
+        assert_eq!(restored, blinker());
+        assert_eq!(restored.generation(), 17);
+        restored.next_cell_generation();
+        restored.toggle(0, 0);
+        assert_eq!(bookmarks.restore(3).unwrap(), blinker());
+        assert_eq!(bookmarks.restore(3).unwrap().generation(), 17);
+    }
+
+    #[test]
+    fn test_overwrite_returns_previous() {
+        let mut bookmarks = Bookmarks::new();
+        bookmarks.save(1, &blinker());
+        let empty = Grid::new(5, 5);
+        assert_eq!(bookmarks.save(1, &empty), Some(blinker()));
+        assert_eq!(bookmarks.restore(1), Some(empty));
+    }
+
+    #[test]
+    fn test_empty_and_missing_slots() {
+        let mut bookmarks = Bookmarks::new();
+        assert!(bookmarks.is_empty());
+        assert_eq!(bookmarks.restore(2), None);
+        assert_eq!(bookmarks.restore(0), None);
+        assert_eq!(bookmarks.restore(BOOKMARK_SLOTS + 1), None);
+        assert_eq!(bookmarks.save(0, &blinker()), None);
+        assert_eq!(bookmarks.save(BOOKMARK_SLOTS + 1, &blinker()), None);
+        assert!(bookmarks.is_empty());
+
+        bookmarks.save(9, &blinker());
+        bookmarks.save(2, &blinker());
+        let slots: Vec<_> = bookmarks.iter().map(|(slot, _)| slot).collect();
+        assert_eq!(slots, vec![2, 9]);
+        assert!(!bookmarks.is_empty());
+    }
+}
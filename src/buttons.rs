@@ -0,0 +1,102 @@
+use crate::bindings::InputCommand;
+use crate::camera::Bounds;
+use crate::input::Point;
+
+/// Size of a single button, in pixels
+pub const BUTTON_WIDTH: f32 = 76.0;
+pub const BUTTON_HEIGHT: f32 = 24.0;
+
+/// Space between the buttons, and between them and the strip edges, in pixels
+const BUTTON_GAP: f32 = 6.0;
+
+/// Height of the strip along the bottom edge holding the buttons
+pub const STRIP_HEIGHT: f32 = BUTTON_HEIGHT + 2.0 * BUTTON_GAP;
+
+/// The buttons from left to right, each triggering the same command as its key
+/// The first one reads "Play" or "Pause" depending on `paused`.
+pub fn buttons(paused: bool) -> [(&'static str, InputCommand); 7] {
+    [
+        (
+            if paused { "Play" } else { "Pause" },
+            InputCommand::TogglePause,
+        ),
+        ("Step", InputCommand::StepForward),
+        ("Clear", InputCommand::Clear),
+        ("Random", InputCommand::Randomize),
+        ("Save", InputCommand::Save),
+        ("Speed-", InputCommand::SlowDown),
+        ("Speed+", InputCommand::SpeedUp),
+    ]
+}
+
+/// The strip along the bottom edge of the screen; clicks on it never reach the board
+pub fn strip_bounds(screen: (f32, f32)) -> Bounds {
+    Bounds {
+        x: 0.0,
+        y: screen.1 - STRIP_HEIGHT,
+        w: screen.0,
+        h: STRIP_HEIGHT,
+    }
+}
+
+/// Where each of `count` buttons goes, left-aligned in the strip
+pub fn layout(screen: (f32, f32), count: usize) -> Vec<Bounds> {
+    let strip = strip_bounds(screen);
+    (0..count)
+        .map(|i| Bounds {
+            x: BUTTON_GAP + i as f32 * (BUTTON_WIDTH + BUTTON_GAP),
+            y: strip.y + BUTTON_GAP,
+            w: BUTTON_WIDTH,
+            h: BUTTON_HEIGHT,
+        })
+        .collect()
+}
+
+/// Index of the button under `point`, if any
+pub fn button_at(point: Point, buttons: &[Bounds]) -> Option<usize> {
+    buttons.iter().position(|bounds| bounds.contains(point))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: (f32, f32) = (800.0, 600.0);
+
+    #[test]
+    fn test_layout_along_bottom() {
+        let rects = layout(SCREEN, 7);
+        let strip = strip_bounds(SCREEN);
+        assert_eq!(rects.len(), 7);
+        assert_eq!(strip.y + strip.h, SCREEN.1);
+        for pair in rects.windows(2) {
+            // Left to right, without overlapping
+            assert!(pair[0].x + pair[0].w < pair[1].x);
+            assert_eq!(pair[0].y, pair[1].y);
+        }
+        for rect in &rects {
+            assert!(rect.y > strip.y && rect.y + rect.h < strip.y + strip.h);
+        }
+    }
+
+    #[test]
+    fn test_button_at() {
+        let rects = layout(SCREEN, 7);
+        let center = |rect: &Bounds| (rect.x + rect.w / 2.0, rect.y + rect.h / 2.0);
+        for (i, rect) in rects.iter().enumerate() {
+            assert_eq!(button_at(center(rect), &rects), Some(i));
+        }
+        // The gaps between buttons and the rest of the screen hit nothing
+        let gap = (rects[0].x + rects[0].w + 1.0, rects[0].y + 1.0);
+        assert_eq!(button_at(gap, &rects), None);
+        assert_eq!(button_at((400.0, 300.0), &rects), None);
+        // The strip still catches the gap, so it doesn't paint the board
+        assert!(strip_bounds(SCREEN).contains(gap));
+    }
+
+    #[test]
+    fn test_play_pause_label() {
+        assert_eq!(buttons(true)[0], ("Play", InputCommand::TogglePause));
+        assert_eq!(buttons(false)[0], ("Pause", InputCommand::TogglePause));
+    }
+}
@@ -0,0 +1,330 @@
+/// Smallest and largest size of a cell on screen, in pixels
+pub const MIN_CELL_SIZE: f32 = 1.0;
+pub const MAX_CELL_SIZE: f32 = 64.0;
+
+/// Cells left free around a pattern framed with `Camera::framing`, on each side
+pub const FIT_MARGIN: f32 = 2.0;
+
+/// Seconds a `Glide` takes to reach its camera
+pub const GLIDE_DURATION: f32 = 0.2;
+
+/// An axis aligned rectangle, in whatever units the context uses
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Bounds {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl Bounds {
+    /// Whether the point lies inside the rectangle
+    pub fn contains(&self, (x, y): (f32, f32)) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.w && y < self.y + self.h
+    }
+}
+
+/// Maps between screen pixels and grid cells, supporting zoom and pan
+///
+/// `origin` is the (fractional) cell shown at the top-left corner of the screen
+/// and `cell_size` is how many pixels a cell takes on screen.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Camera {
+    pub origin: (f32, f32),
+    pub cell_size: f32,
+}
+
+impl Camera {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            origin: (0.0, 0.0),
+            cell_size: cell_size.clamp(MIN_CELL_SIZE, MAX_CELL_SIZE),
+        }
+    }
+
+    /// Convert a screen position into fractional cell coordinates
+    pub fn screen_to_cell(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (
+            self.origin.0 + x / self.cell_size,
+            self.origin.1 + y / self.cell_size,
+        )
+    }
+
+    /// Convert cell coordinates into the screen position of the cell's top-left corner
+    pub fn cell_to_screen(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (
+            (x - self.origin.0) * self.cell_size,
+            (y - self.origin.1) * self.cell_size,
+        )
+    }
+
+    /// Move the view by a distance in screen pixels, e.g. the mouse movement of a drag
+    pub fn pan_by(&mut self, (dx, dy): (f32, f32)) {
+        self.origin.0 -= dx / self.cell_size;
+        self.origin.1 -= dy / self.cell_size;
+    }
+
+    /// Multiply the zoom by `factor`, keeping the cell under `anchor` in place
+    ///
+    /// # Arguments
+    /// * `anchor` - Screen position that must not move, usually the cursor
+    /// * `factor` - Values above 1 zoom in, values below 1 zoom out
+    pub fn zoom_at(&mut self, anchor: (f32, f32), factor: f32) {
+        let before = self.screen_to_cell(anchor);
+        self.cell_size = (self.cell_size * factor).clamp(MIN_CELL_SIZE, MAX_CELL_SIZE);
+        let after = self.screen_to_cell(anchor);
+        self.origin.0 += before.0 - after.0;
+        self.origin.1 += before.1 - after.1;
+    }
+
+    /// Center the view on a cell
+    pub fn center_on(&mut self, (x, y): (f32, f32), screen: (f32, f32)) {
+        self.origin = (
+            x - screen.0 / self.cell_size / 2.0,
+            y - screen.1 / self.cell_size / 2.0,
+        );
+    }
+
+    /// The camera showing all of `target`, in cells, centered on a screen of
+    /// the given size with `margin` cells to spare on every side
+    ///
+    /// The zoom is the largest that fits both ways, within the zoom limits; a
+    /// pattern too large for the smallest cells overflows evenly on both sides.
+    pub fn framing(target: Bounds, margin: f32, screen: (f32, f32)) -> Self {
+        let fit = |screen: f32, cells: f32| screen / (cells + 2.0 * margin).max(1.0);
+        let mut camera = Self::new(fit(screen.0, target.w).min(fit(screen.1, target.h)));
+        camera.center_on(
+            (target.x + target.w / 2.0, target.y + target.h / 2.0),
+            screen,
+        );
+        camera
+    }
+
+    /// The cell at the center of a screen of the given size
+    fn center(&self, screen: (f32, f32)) -> (f32, f32) {
+        self.screen_to_cell((screen.0 / 2.0, screen.1 / 2.0))
+    }
+
+    /// The part of the world visible on a screen of the given size, in cells
+    pub fn viewport(&self, screen: (f32, f32)) -> Bounds {
+        Bounds {
+            x: self.origin.0,
+            y: self.origin.1,
+            w: screen.0 / self.cell_size,
+            h: screen.1 / self.cell_size,
+        }
+    }
+
+    /// Range of rows and columns at least partially visible on screen, clipped to the grid
+    /// Returns `(x_start..x_end, y_start..y_end)` so drawing can skip hidden cells
+    pub fn visible_cells(
+        &self,
+        screen: (f32, f32),
+        grid: (usize, usize),
+    ) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+        let view = self.viewport(screen);
+        let clip = |start: f32, len: f32, max: usize| {
+            let first = start.floor().clamp(0.0, max as f32) as usize;
+            let last = (start + len).ceil().clamp(0.0, max as f32) as usize;
+            first..last
+        };
+        (clip(view.x, view.w, grid.0), clip(view.y, view.h, grid.1))
+    }
+}
+
+/// A camera move animated over `GLIDE_DURATION` seconds
+///
+/// The center of the view moves in a straight line while the zoom changes by
+/// the same factor every frame, so zooming in and out both look steady.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Glide {
+    from: Camera,
+    to: Camera,
+    elapsed: f32,
+}
+
+impl Glide {
+    pub fn new(from: Camera, to: Camera) -> Self {
+        Self {
+            from,
+            to,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Move on by `dt` seconds
+    ///
+    /// # Returns
+    /// The camera to show on a screen of the given size, and whether the
+    /// glide has arrived
+    pub fn advance(&mut self, dt: f32, screen: (f32, f32)) -> (Camera, bool) {
+        self.elapsed += dt;
+        let t = (self.elapsed / GLIDE_DURATION).min(1.0);
+        if t >= 1.0 {
+            return (self.to, true);
+        }
+        // Ease out, fast at first and settling at the end
+        let t = 1.0 - (1.0 - t) * (1.0 - t);
+        let (from, to) = (self.from.center(screen), self.to.center(screen));
+        let ratio = self.to.cell_size / self.from.cell_size;
+        let mut camera = Camera::new(self.from.cell_size * ratio.powf(t));
+        camera.center_on(
+            (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t),
+            screen,
+        );
+        (camera, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: (f32, f32) = (800.0, 600.0);
+
+    #[test]
+    fn test_screen_cell_round_trip() {
+        let mut camera = Camera::new(10.0);
+        camera.origin = (3.5, -2.0);
+
+        assert_eq!(camera.screen_to_cell((0.0, 0.0)), (3.5, -2.0));
+        assert_eq!(camera.screen_to_cell((25.0, 40.0)), (6.0, 2.0));
+        assert_eq!(camera.cell_to_screen((6.0, 2.0)), (25.0, 40.0));
+    }
+
+    #[test]
+    fn test_viewport_at_different_zoom_levels() {
+        let camera = Camera::new(10.0);
+        assert_eq!(
+            camera.viewport(SCREEN),
+            Bounds {
+                x: 0.0,
+                y: 0.0,
+                w: 80.0,
+                h: 60.0
+            }
+        );
+
+        let mut zoomed_in = Camera::new(20.0);
+        zoomed_in.origin = (10.0, 5.0);
+        assert_eq!(
+            zoomed_in.viewport(SCREEN),
+            Bounds {
+                x: 10.0,
+                y: 5.0,
+                w: 40.0,
+                h: 30.0
+            }
+        );
+
+        let zoomed_out = Camera::new(2.5);
+        assert_eq!(zoomed_out.viewport(SCREEN).w, 320.0);
+        assert_eq!(zoomed_out.viewport(SCREEN).h, 240.0);
+    }
+
+    #[test]
+    fn test_zoom_keeps_anchor_fixed() {
+        let mut camera = Camera::new(10.0);
+        let anchor = (400.0, 300.0);
+        let before = camera.screen_to_cell(anchor);
+
+        camera.zoom_at(anchor, 2.0);
+        assert_eq!(camera.cell_size, 20.0);
+        assert_eq!(camera.screen_to_cell(anchor), before);
+        assert_eq!(camera.viewport(SCREEN).w, 40.0);
+
+        // Zoom is clamped at both ends
+        camera.zoom_at(anchor, 100.0);
+        assert_eq!(camera.cell_size, MAX_CELL_SIZE);
+        camera.zoom_at(anchor, 0.0001);
+        assert_eq!(camera.cell_size, MIN_CELL_SIZE);
+    }
+
+    #[test]
+    fn test_pan_moves_by_screen_pixels() {
+        let mut camera = Camera::new(20.0);
+        camera.pan_by((40.0, -20.0));
+        assert_eq!(camera.origin, (-2.0, 1.0));
+    }
+
+    #[test]
+    fn test_center_on() {
+        let mut camera = Camera::new(20.0);
+        camera.center_on((50.0, 50.0), SCREEN);
+        let view = camera.viewport(SCREEN);
+        assert_eq!((view.x + view.w / 2.0, view.y + view.h / 2.0), (50.0, 50.0));
+    }
+
+    #[test]
+    fn test_visible_cells_are_clipped_to_grid() {
+        let mut camera = Camera::new(10.0);
+        camera.origin = (-5.5, 40.2);
+        let (xs, ys) = camera.visible_cells(SCREEN, (100, 80));
+        assert_eq!(xs, 0..75);
+        assert_eq!(ys, 40..80);
+    }
+
+    /// The live cells of a pattern, `w` x `h` cells large
+    fn pattern(x: f32, y: f32, w: f32, h: f32) -> Bounds {
+        Bounds { x, y, w, h }
+    }
+
+    /// Whether `target` lies whole inside the view, with `margin` cells to spare
+    fn frames(camera: &Camera, target: Bounds, margin: f32) -> bool {
+        let view = camera.viewport(SCREEN);
+        let eps = 1e-3;
+        view.x <= target.x - margin + eps
+            && view.y <= target.y - margin + eps
+            && view.x + view.w >= target.x + target.w + margin - eps
+            && view.y + view.h >= target.y + target.h + margin - eps
+    }
+
+    #[test]
+    fn test_framing_tall_and_wide_patterns() {
+        // Height decides for a tall pattern, and width for a wide one
+        let tall = pattern(10.0, 20.0, 4.0, 56.0);
+        let camera = Camera::framing(tall, FIT_MARGIN, SCREEN);
+        assert_eq!(camera.cell_size, 10.0);
+        assert!(frames(&camera, tall, FIT_MARGIN));
+        assert_eq!(camera.center(SCREEN), (12.0, 48.0));
+
+        let wide = pattern(0.0, 0.0, 196.0, 3.0);
+        let camera = Camera::framing(wide, FIT_MARGIN, SCREEN);
+        assert_eq!(camera.cell_size, 4.0);
+        assert!(frames(&camera, wide, FIT_MARGIN));
+    }
+
+    #[test]
+    fn test_framing_tiny_pattern_stops_at_max_zoom() {
+        let single = pattern(40.0, 30.0, 1.0, 1.0);
+        let camera = Camera::framing(single, FIT_MARGIN, SCREEN);
+        assert_eq!(camera.cell_size, MAX_CELL_SIZE);
+        assert_eq!(camera.center(SCREEN), (40.5, 30.5));
+    }
+
+    #[test]
+    fn test_framing_board_filling_pattern() {
+        let board = pattern(0.0, 0.0, 400.0, 300.0);
+        let camera = Camera::framing(board, FIT_MARGIN, SCREEN);
+        assert!(camera.cell_size < 2.0);
+        assert!(frames(&camera, board, FIT_MARGIN));
+        // Past the smallest zoom the pattern overflows on both sides alike
+        let huge = pattern(0.0, 0.0, 4000.0, 100.0);
+        let camera = Camera::framing(huge, FIT_MARGIN, SCREEN);
+        assert_eq!(camera.cell_size, MIN_CELL_SIZE);
+        assert_eq!(camera.center(SCREEN), (2000.0, 50.0));
+    }
+
+    #[test]
+    fn test_glide_arrives_after_duration() {
+        let from = Camera::new(10.0);
+        let to = Camera::framing(pattern(100.0, 100.0, 10.0, 10.0), FIT_MARGIN, SCREEN);
+        let mut glide = Glide::new(from, to);
+        let (halfway, done) = glide.advance(GLIDE_DURATION / 2.0, SCREEN);
+        assert!(!done);
+        assert!(halfway.cell_size > from.cell_size && halfway.cell_size < to.cell_size);
+        let (end, done) = glide.advance(GLIDE_DURATION, SCREEN);
+        assert!(done);
+        assert_eq!(end, to);
+    }
+}
@@ -0,0 +1,1066 @@
+use crate::backend::Backend;
+use crate::background::BackgroundStyle;
+use crate::camera::{MAX_CELL_SIZE, MIN_CELL_SIZE};
+use crate::conways::{BoundaryCondition, CellState, Grid, InsertMode};
+use crate::daily::{self, Date};
+use crate::error::ConwayError;
+use crate::formats;
+use crate::patterns::{self, Pattern};
+use crate::rng;
+use crate::rule::{Rule, RuleError};
+use crate::shape::CellShape;
+use crate::theme::{self, Theme};
+use macroquad::color::Color;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Range accepted by `--speed`, in generations per second
+pub const MIN_SPEED: f32 = 0.5;
+pub const MAX_SPEED: f32 = 100.0;
+
+/// Density of random boards when `--density` isn't given
+pub const DEFAULT_DENSITY: f64 = 0.3;
+
+/// Largest board accepted by `--width` and `--height`
+pub const MAX_GRID_SIDE: usize = 4096;
+
+/// Seconds between two autosaves when `conways.toml` doesn't say
+pub const DEFAULT_AUTOSAVE_INTERVAL: f32 = 30.0;
+
+pub const HELP: &str = "\
+Conway's Game of Life
+
+Usage: game-of-life [OPTIONS]
+
+Options:
+  --width <CELLS>            Board width (default 80)
+  --height <CELLS>           Board height (default 60)
+  --size <W>x<H>             Board width and height at once, e.g. 200x100
+  --cell-size <PIXELS>       Initial size of a cell on screen (default 10)
+  --speed <GEN/S>            Generations per second (default 10)
+  --seed <N>                 Start from a random board with this seed (decimal or 0x hex)
+  --density <0..1>           Start from a random board with this density (default 0.3)
+  --daily                    Start from today's soup, the same for everyone on the
+                             same UTC day: an 80x60 board, density 0.3, Conway's
+                             rule, with the seed hashed from the date
+  --daily-date <YYYY-MM-DD>  Start from the daily soup of another day
+  --pattern <FILE>           Start from a pattern file (.rle, .cells, .life, .lif,
+                             .json, .lifesnap, .mc)
+  --start <NAME>             Start from a built-in pattern, e.g. glider, gosper-gun
+  --script <FILE>            Start from the board a script draws, see scripts/ for
+                             an example; Shift+Backspace clears the board and runs
+                             it again (scripting feature)
+  --script-budget <N>        Steps a script may take before it's stopped
+                             (default 100000000)
+  --puzzle <FILE>            Solve a puzzle file, see puzzles/ for examples
+  --reference <FILE>         Show a pattern file faintly over the board to line
+                             cells up with; Ctrl+L shows it below or hides it
+  --at <X>,<Y>               Put the starting pattern's top-left corner at this
+                             cell instead of centering it
+  --rule <RULE>              Rulestring to simulate, e.g. B36/S23 (default B3/S23)
+  --backend <NAME>           How runs of many generations step the board: grid,
+                             bits for 64 cells at a time or sparse for only the
+                             live cells (default grid); wrapping boards always
+                             step as a grid
+  --paused                   Start paused
+  --no-auto-pause            Keep running once the board is stable or oscillating
+  --stable-period <N>        Longest oscillator period that auto-pauses (default 15)
+  --restore                  Restore the previous session without asking
+  --color <NAME=#RRGGBB>     Override a theme color, can be repeated
+  --max-gif-frames <N>       Frame limit for GIF recordings (default 1000)
+  --rewind <N>               Generations kept for stepping backwards (default 100)
+  --record <FILE>            Log every command and edit to a replay file, written on exit
+  --replay <FILE>            Play back a replay file, with the same options it was
+                             recorded with; Escape stops it
+  --record-run <FILE>        Write every generation of the run to a file of board
+                             changes, e.g. out.lifereplay, on exit
+  --play-run <FILE>          Watch a run written with --record-run without
+                             simulating it; step, scrub and change speed as usual
+  --stats-csv <FILE>         Write a CSV row per generation with its population,
+                             births, deaths and step time, here or headless
+  --host <PORT>              Let other games join this board over TCP on this port;
+                             it runs here, and everyone's edits apply in the order
+                             they arrive
+  --join <HOST>:<PORT>       Join the board of a game started with --host, painting
+                             and pausing it together
+  --websocket <PORT>         Serve the board to read-only WebSocket viewers on this
+                             port, e.g. a web dashboard (websocket feature)
+  --dump-default-keybindings Write the default keybindings.toml and exit
+  --write-default-config     Write a commented conways.toml with the defaults and exit
+  --tui                      Run in the terminal instead of a window (unix only)
+  -h, --help                 Print this help and exit
+
+Headless mode:
+  --headless                 Simulate without opening a window
+  --generations <N>          Generations to simulate (default 1000)
+  --out <FILE>               Write the final board (.rle, .cells, .life, .lif, .json,
+                             .lifesnap, .mc)
+  --export-svg <FILE>        Write the final board as an SVG picture, black on white
+  --report                   Print population and stabilization statistics
+  --puzzle <FILE>            With --pattern, check whether that board solves
+                             the puzzle; exits with 1 when it doesn't
+
+Exit codes: 0 on success, 1 when a --puzzle check fails, 2 for invalid
+arguments or file types, 3 when a pattern file can't be parsed and 4 when a
+file can't be read or written.
+
+Defaults for the window and board size, cell size, speed, theme, rule, edges,
+auto-pause and autosave interval are read from conways.toml in the working
+directory when it exists; options given here take precedence over it.
+
+The starting board is, in order of precedence: --script, then --pattern, then --start, then a
+random board when --seed or --density is given, then the previous session with --restore,
+and otherwise a glider.
+";
+
+/// Everything that can be set from the command line or `conways.toml`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    /// Size of the window, in pixels
+    pub window_size: (i32, i32),
+    pub width: usize,
+    pub height: usize,
+    pub cell_size: f32,
+    /// Seconds between two generations
+    pub update_interval: f32,
+    pub seed: Option<u64>,
+    pub density: Option<f64>,
+    /// Day of the daily soup being played, which sets the seed and board
+    pub daily: Option<Date>,
+    pub pattern: Option<PathBuf>,
+    /// Script drawing the starting board, run with the `scripting` feature
+    pub script: Option<PathBuf>,
+    /// Steps the script may take
+    #[cfg(feature = "scripting")]
+    pub script_budget: u64,
+    /// Pattern file shown as a reference layer, which never takes part in the simulation
+    pub reference: Option<PathBuf>,
+    /// Puzzle file to solve
+    pub puzzle: Option<PathBuf>,
+    /// Built-in pattern to start from
+    pub start: Option<&'static Pattern>,
+    /// Top-left cell of the starting pattern, which is centered otherwise
+    pub at: Option<(isize, isize)>,
+    pub paused: bool,
+    /// Pause once the board repeats with a period up to `stable_period`
+    pub auto_pause: bool,
+    /// Pause while the window is in the background
+    pub pause_on_focus_loss: bool,
+    pub stable_period: usize,
+    pub restore: bool,
+    pub color_overrides: Vec<(String, Color)>,
+    /// Index of the starting theme in `Theme::presets`
+    pub theme: usize,
+    /// Marks drawn on dead cells
+    pub background: BackgroundStyle,
+    /// How live cells are drawn
+    pub cell_shape: CellShape,
+    /// Rows and columns shown across each edge of a wrapping board, 0 for none
+    pub phantom_width: usize,
+    /// Cells between the top-left corners of two tiled copies, the pattern's
+    /// size plus a gap when `None`
+    pub tile_pitch: Option<(usize, usize)>,
+    /// Speed the speed presets multiply, in generations per second
+    pub preset_base: f32,
+    /// Multiples of `preset_base` the speed presets jump to
+    pub speed_presets: Vec<f32>,
+    pub boundary: BoundaryCondition,
+    /// Rule the board starts evolving by
+    pub rule: Rule,
+    /// Rules cycled through at runtime
+    pub rules: Vec<Rule>,
+    /// Backend running many generations at once, in turbo, fast-forwards and
+    /// headless runs
+    pub backend: Backend,
+    /// Seconds between two autosaves, 0 to only save on exit
+    pub autosave_interval: f32,
+    #[cfg(feature = "gif")]
+    pub max_gif_frames: usize,
+    /// Generations kept for stepping backwards
+    pub rewind: usize,
+    /// Replay file to log the session to
+    pub record: Option<PathBuf>,
+    /// Replay file to play back
+    pub replay: Option<PathBuf>,
+    /// File to write the boards of the run to, on exit
+    pub record_run: Option<PathBuf>,
+    /// Run recording to watch instead of simulating
+    pub play_run: Option<PathBuf>,
+    /// CSV file to write the statistics of every generation to
+    pub stats_csv: Option<PathBuf>,
+    /// Port to let other games join this one on
+    pub host: Option<u16>,
+    /// Address of the game to join
+    pub join: Option<String>,
+    /// Port to serve the board to WebSocket viewers on
+    #[cfg(feature = "websocket")]
+    pub websocket: Option<u16>,
+    pub dump_keybindings: bool,
+    pub write_config: bool,
+    pub headless: bool,
+    #[cfg(all(feature = "tui", unix))]
+    pub tui: bool,
+    pub generations: u64,
+    pub out: Option<PathBuf>,
+    /// SVG picture to draw the final board in, headless
+    pub export_svg: Option<PathBuf>,
+    pub report: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            window_size: (800, 600),
+            width: 80,
+            height: 60,
+            cell_size: 10.0,
+            update_interval: 0.1,
+            seed: None,
+            density: None,
+            daily: None,
+            pattern: None,
+            script: None,
+            #[cfg(feature = "scripting")]
+            script_budget: crate::script::DEFAULT_BUDGET,
+            reference: None,
+            puzzle: None,
+            start: None,
+            at: None,
+            paused: false,
+            auto_pause: true,
+            pause_on_focus_loss: true,
+            stable_period: crate::cycle::DEFAULT_STABLE_PERIOD,
+            restore: false,
+            color_overrides: Vec::new(),
+            theme: 0,
+            background: BackgroundStyle::Off,
+            cell_shape: CellShape::Square,
+            phantom_width: crate::phantom::DEFAULT_PHANTOM_WIDTH,
+            tile_pitch: None,
+            preset_base: crate::speed_presets::DEFAULT_PRESET_BASE,
+            speed_presets: crate::speed_presets::DEFAULT_PRESET_MULTIPLES.to_vec(),
+            boundary: BoundaryCondition::Dead,
+            rule: Rule::conway(),
+            rules: Rule::presets(),
+            backend: Backend::Grid,
+            autosave_interval: DEFAULT_AUTOSAVE_INTERVAL,
+            #[cfg(feature = "gif")]
+            max_gif_frames: 1000,
+            rewind: crate::timeline::DEFAULT_REWIND,
+            record: None,
+            replay: None,
+            record_run: None,
+            play_run: None,
+            stats_csv: None,
+            host: None,
+            join: None,
+            #[cfg(feature = "websocket")]
+            websocket: None,
+            dump_keybindings: false,
+            write_config: false,
+            headless: false,
+            #[cfg(all(feature = "tui", unix))]
+            tui: false,
+            generations: 1000,
+            out: None,
+            export_svg: None,
+            report: false,
+        }
+    }
+}
+
+impl Config {
+    /// Whether the options ask for a random starting board
+    pub fn wants_random(&self) -> bool {
+        self.script.is_none()
+            && self.pattern.is_none()
+            && self.start.is_none()
+            && (self.seed.is_some() || self.density.is_some())
+    }
+
+    /// Whether the options pick a starting board, rather than leaving it to
+    /// the previous session or the default
+    pub fn explicit_board(&self) -> bool {
+        self.script.is_some()
+            || self.pattern.is_some()
+            || self.start.is_some()
+            || self.puzzle.is_some()
+            || self.wants_random()
+    }
+
+    /// The file the starting board is read from, the script or the pattern
+    pub fn board_file(&self) -> Option<&Path> {
+        self.script.as_deref().or(self.pattern.as_deref())
+    }
+
+    /// The board to start from: the script's, the pattern file, a library
+    /// pattern, a random board, or a lone spaceship, following the precedence
+    /// described in `HELP`, with `--rule` unless the script set another
+    pub fn starting_grid(&self) -> Result<Grid, ConwayError> {
+        #[cfg(feature = "scripting")]
+        if let Some(path) = &self.script {
+            return self.scripted_grid(path);
+        }
+        let mut grid = if let Some(path) = &self.pattern {
+            self.place(&formats::load_file(path)?)
+        } else if let Some(start) = self.start {
+            self.place(&start.to_grid())
+        } else {
+            self.generated_grid()
+        };
+        grid.set_rule(self.rule);
+        Ok(grid)
+    }
+
+    /// The board the script at `path` draws on an empty board of `--width`
+    /// by `--height`, with `rand()` drawn from `--seed`
+    #[cfg(feature = "scripting")]
+    fn scripted_grid(&self, path: &Path) -> Result<Grid, ConwayError> {
+        let script = load_script(path)?;
+        let mut grid = Grid::new(self.width, self.height);
+        grid.set_rule(self.rule);
+        let seed = self.seed.unwrap_or_else(rng::clock_seed);
+        run_script(&script, &mut grid, seed, self.script_budget)?;
+        Ok(grid)
+    }
+
+    /// A random board, or a lone spaceship
+    fn generated_grid(&self) -> Grid {
+        let mut grid = Grid::new(self.width, self.height);
+        if self.wants_random() {
+            grid.randomize(
+                self.density.unwrap_or(DEFAULT_DENSITY),
+                self.seed.unwrap_or_else(rng::clock_seed),
+            );
+        } else {
+            // A lightweight spaceship, clipped on boards too small to hold it
+            let cells = [
+                (10, 10),
+                (13, 10),
+                (14, 11),
+                (10, 12),
+                (14, 12),
+                (11, 13),
+                (12, 13),
+                (13, 13),
+                (14, 13),
+            ];
+            for (x, y) in cells {
+                if x < grid.width() && y < grid.height() {
+                    grid.set(x, y, CellState::Alive);
+                }
+            }
+        }
+        grid
+    }
+
+    /// Put the starting pattern on the board, at `--at` or centered
+    /// A centered pattern larger than the board grows it, one placed with
+    /// `--at` is clipped instead.
+    fn place(&self, pattern: &Grid) -> Grid {
+        match self.at {
+            Some((x, y)) => {
+                let mut grid = Grid::new(self.width, self.height);
+                grid.insert_pattern(pattern, x, y, InsertMode::Overwrite);
+                grid
+            }
+            None => Grid::centered(pattern, self.width, self.height),
+        }
+    }
+}
+
+/// The script at `path`, read and parsed
+#[cfg(feature = "scripting")]
+pub fn load_script(path: &Path) -> Result<crate::script::Script, ConwayError> {
+    crate::script::Script::parse(&std::fs::read_to_string(path)?)
+        .map_err(|err| ConwayError::parse("script", err))
+}
+
+/// Run `script` onto `grid`, with `rand()` drawn from `seed` and at most
+/// `budget` steps
+/// A failing script leaves what it drew so far on the board.
+#[cfg(feature = "scripting")]
+pub fn run_script(
+    script: &crate::script::Script,
+    grid: &mut Grid,
+    seed: u64,
+    budget: u64,
+) -> Result<(), ConwayError> {
+    script
+        .run(grid, &mut rng::SplitMix64::new(seed), budget)
+        .map_err(|err| {
+            ConwayError::InvalidArgument(format!(
+                "script failed at line {}: {}",
+                err.line, err.message
+            ))
+        })
+}
+
+/// What the command line asks for
+#[derive(Debug, PartialEq)]
+pub enum Invocation {
+    Run(Box<Config>),
+    Help,
+}
+
+/// Error produced by invalid command line arguments
+#[derive(Debug, PartialEq)]
+pub enum CliError {
+    UnknownOption(String),
+    MissingValue(String),
+    InvalidValue {
+        option: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::UnknownOption(option) => write!(f, "unknown option '{option}'"),
+            CliError::MissingValue(option) => write!(f, "{option} needs a value"),
+            CliError::InvalidValue {
+                option,
+                value,
+                reason,
+            } => write!(f, "invalid value '{value}' for {option}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Parse the command line arguments, without the program name
+///
+/// # Arguments
+/// * `args` - The arguments
+/// * `config` - The settings to start from, which the arguments override
+///
+/// # Returns
+/// The `Config` to run with, `Invocation::Help` when help was asked for, or
+/// the first problem found
+pub fn parse_args(args: &[String], mut config: Config) -> Result<Invocation, CliError> {
+    let mut args = args.iter();
+    let mut daily = false;
+    let mut daily_date = None;
+
+    while let Some(option) = args.next() {
+        let option = option.as_str();
+        let mut value = || {
+            args.next()
+                .map(String::as_str)
+                .ok_or_else(|| CliError::MissingValue(option.to_string()))
+        };
+        let invalid = |value: &str, reason: &str| CliError::InvalidValue {
+            option: option.to_string(),
+            value: value.to_string(),
+            reason: reason.to_string(),
+        };
+
+        match option {
+            "-h" | "--help" => return Ok(Invocation::Help),
+            "--width" | "--height" => {
+                let value = value()?;
+                let side = value
+                    .parse()
+                    .ok()
+                    .filter(|side| (1..=MAX_GRID_SIDE).contains(side))
+                    .ok_or_else(|| {
+                        invalid(value, &format!("expected 1 to {MAX_GRID_SIDE} cells"))
+                    })?;
+                if option == "--width" {
+                    config.width = side;
+                } else {
+                    config.height = side;
+                }
+            }
+            "--size" => {
+                let value = value()?;
+                let side = |side: &str| {
+                    side.parse()
+                        .ok()
+                        .filter(|side| (1..=MAX_GRID_SIDE).contains(side))
+                };
+                let (width, height) = value
+                    .split_once(['x', 'X'])
+                    .and_then(|(width, height)| Some((side(width)?, side(height)?)))
+                    .ok_or_else(|| {
+                        invalid(
+                            value,
+                            &format!("expected <width>x<height>, 1 to {MAX_GRID_SIDE} cells each"),
+                        )
+                    })?;
+                config.width = width;
+                config.height = height;
+            }
+            "--cell-size" => {
+                let value = value()?;
+                config.cell_size = value
+                    .parse()
+                    .ok()
+                    .filter(|size| (MIN_CELL_SIZE..=MAX_CELL_SIZE).contains(size))
+                    .ok_or_else(|| {
+                        invalid(
+                            value,
+                            &format!("expected {MIN_CELL_SIZE} to {MAX_CELL_SIZE} pixels"),
+                        )
+                    })?;
+            }
+            "--speed" => {
+                let value = value()?;
+                let speed: f32 = value
+                    .parse()
+                    .ok()
+                    .filter(|speed| (MIN_SPEED..=MAX_SPEED).contains(speed))
+                    .ok_or_else(|| {
+                        invalid(
+                            value,
+                            &format!("expected {MIN_SPEED} to {MAX_SPEED} generations per second"),
+                        )
+                    })?;
+                config.update_interval = 1.0 / speed;
+            }
+            "--seed" => {
+                let value = value()?;
+                let seed = match value.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16),
+                    None => value.parse(),
+                };
+                config.seed = Some(seed.map_err(|_| invalid(value, "expected an integer"))?);
+            }
+            "--daily" => daily = true,
+            "--daily-date" => {
+                let value = value()?;
+                daily_date =
+                    Some(Date::parse(value).ok_or_else(|| invalid(value, "expected YYYY-MM-DD"))?);
+            }
+            "--density" => {
+                let value = value()?;
+                let density = value
+                    .parse()
+                    .ok()
+                    .filter(|density| (0.0..=1.0).contains(density))
+                    .ok_or_else(|| invalid(value, "expected a number from 0 to 1"))?;
+                config.density = Some(density);
+            }
+            "--pattern" => config.pattern = Some(PathBuf::from(value()?)),
+            #[cfg(feature = "scripting")]
+            "--script" => config.script = Some(PathBuf::from(value()?)),
+            #[cfg(feature = "scripting")]
+            "--script-budget" => {
+                let value = value()?;
+                config.script_budget = value
+                    .parse()
+                    .ok()
+                    .filter(|&budget| budget > 0)
+                    .ok_or_else(|| invalid(value, "expected a positive number of steps"))?;
+            }
+            "--puzzle" => config.puzzle = Some(PathBuf::from(value()?)),
+            "--reference" => config.reference = Some(PathBuf::from(value()?)),
+            "--start" => {
+                let value = value()?;
+                let pattern = patterns::find(value).ok_or_else(|| {
+                    let names: Vec<_> = patterns::LIBRARY.iter().map(Pattern::slug).collect();
+                    invalid(
+                        value,
+                        &format!("unknown pattern, expected one of {}", names.join(", ")),
+                    )
+                })?;
+                config.start = Some(pattern);
+            }
+            "--at" => {
+                let value = value()?;
+                let at = value
+                    .split_once(',')
+                    .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+                    .ok_or_else(|| invalid(value, "expected <x>,<y>"))?;
+                config.at = Some(at);
+            }
+            "--rule" => {
+                let value = value()?;
+                config.rule = value
+                    .parse()
+                    .map_err(|err: RuleError| invalid(value, &err.to_string()))?;
+            }
+            "--backend" => {
+                let value = value()?;
+                config.backend = Backend::ALL
+                    .into_iter()
+                    .find(|backend| backend.label().eq_ignore_ascii_case(value))
+                    .ok_or_else(|| invalid(value, "expected grid, bits or sparse"))?;
+            }
+            "--paused" => config.paused = true,
+            "--no-auto-pause" => config.auto_pause = false,
+            "--stable-period" => {
+                let value = value()?;
+                config.stable_period = value
+                    .parse()
+                    .map_err(|_| invalid(value, "expected a number of generations"))?;
+            }
+            "--restore" => config.restore = true,
+            "--color" => {
+                let value = value()?;
+                let parsed = value.split_once('=').and_then(|(name, hex)| {
+                    let color = theme::parse_hex_color(hex)?;
+                    Theme::classic().color_mut(name)?;
+                    Some((name.to_string(), color))
+                });
+                config
+                    .color_overrides
+                    .push(parsed.ok_or_else(|| invalid(value, "expected name=#rrggbb"))?);
+            }
+            #[cfg(feature = "gif")]
+            "--max-gif-frames" => {
+                let value = value()?;
+                config.max_gif_frames = value
+                    .parse()
+                    .map_err(|_| invalid(value, "expected a number of frames"))?;
+            }
+            "--rewind" => {
+                let value = value()?;
+                config.rewind = value
+                    .parse()
+                    .map_err(|_| invalid(value, "expected a number of generations"))?;
+            }
+            "--record" => config.record = Some(PathBuf::from(value()?)),
+            "--replay" => config.replay = Some(PathBuf::from(value()?)),
+            "--record-run" => config.record_run = Some(PathBuf::from(value()?)),
+            "--play-run" => config.play_run = Some(PathBuf::from(value()?)),
+            "--stats-csv" => config.stats_csv = Some(PathBuf::from(value()?)),
+            "--host" => {
+                let value = value()?;
+                config.host = Some(
+                    value
+                        .parse()
+                        .map_err(|_| invalid(value, "expected a port number"))?,
+                );
+            }
+            "--join" => config.join = Some(value()?.to_string()),
+            #[cfg(feature = "websocket")]
+            "--websocket" => {
+                let value = value()?;
+                config.websocket = Some(
+                    value
+                        .parse()
+                        .map_err(|_| invalid(value, "expected a port number"))?,
+                );
+            }
+            "--dump-default-keybindings" => config.dump_keybindings = true,
+            "--write-default-config" => config.write_config = true,
+            "--headless" => config.headless = true,
+            #[cfg(all(feature = "tui", unix))]
+            "--tui" => config.tui = true,
+            "--generations" => {
+                let value = value()?;
+                config.generations = value
+                    .parse()
+                    .map_err(|_| invalid(value, "expected a number of generations"))?;
+            }
+            "--out" => config.out = Some(PathBuf::from(value()?)),
+            "--export-svg" => config.export_svg = Some(PathBuf::from(value()?)),
+            "--report" => config.report = true,
+            _ => return Err(CliError::UnknownOption(option.to_string())),
+        }
+    }
+
+    if daily || daily_date.is_some() {
+        let date = daily_date.unwrap_or_else(Date::today);
+        if config.script.is_some()
+            || config.pattern.is_some()
+            || config.start.is_some()
+            || config.puzzle.is_some()
+        {
+            return Err(CliError::InvalidValue {
+                option: "--daily".to_string(),
+                value: date.to_string(),
+                reason: "can't be combined with --script, --pattern, --start or --puzzle"
+                    .to_string(),
+            });
+        }
+        // Everything that shapes the soup is fixed, whatever conways.toml says
+        (config.width, config.height) = daily::DAILY_SIZE;
+        config.seed = Some(daily::daily_seed(date));
+        config.density = Some(daily::DAILY_DENSITY);
+        config.rule = Rule::conway();
+        config.boundary = BoundaryCondition::Dead;
+        config.at = None;
+        config.daily = Some(date);
+    }
+    if let (Some(_), Some(replay)) = (&config.record, &config.replay) {
+        return Err(CliError::InvalidValue {
+            option: "--replay".to_string(),
+            value: replay.display().to_string(),
+            reason: "can't be combined with --record".to_string(),
+        });
+    }
+    if let (Some(_), Some(join)) = (&config.host, &config.join) {
+        return Err(CliError::InvalidValue {
+            option: "--join".to_string(),
+            value: join.clone(),
+            reason: "can't be combined with --host".to_string(),
+        });
+    }
+    if let (Some(_), Some(play)) = (&config.record_run, &config.play_run) {
+        return Err(CliError::InvalidValue {
+            option: "--play-run".to_string(),
+            value: play.display().to_string(),
+            reason: "can't be combined with --record-run".to_string(),
+        });
+    }
+    Ok(Invocation::Run(Box::new(config)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Invocation, CliError> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        parse_args(&args, Config::default())
+    }
+
+    fn config(args: &[&str]) -> Config {
+        match parse(args) {
+            Ok(Invocation::Run(config)) => *config,
+            other => panic!("expected a config, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_defaults() {
+        assert_eq!(config(&[]), Config::default());
+    }
+
+    #[test]
+    fn test_good_arguments() {
+        let config = config(&[
+            "--width",
+            "200",
+            "--height",
+            "100",
+            "--cell-size",
+            "4",
+            "--speed",
+            "20",
+            "--seed",
+            "0xff",
+            "--density",
+            "0.5",
+            "--rule",
+            "b3/s23",
+            "--paused",
+            "--color",
+            "alive=#ff0000",
+            "--rewind",
+            "500",
+            "--no-auto-pause",
+            "--stable-period",
+            "40",
+            "--backend",
+            "Bits",
+        ]);
+        assert_eq!((config.width, config.height), (200, 100));
+        assert_eq!(config.cell_size, 4.0);
+        assert_eq!(config.update_interval, 0.05);
+        assert_eq!(config.seed, Some(255));
+        assert_eq!(config.density, Some(0.5));
+        assert!(config.paused);
+        assert_eq!(config.rewind, 500);
+        assert!(!config.auto_pause);
+        assert_eq!(config.stable_period, 40);
+        assert_eq!(config.backend, Backend::Bits);
+        assert_eq!(config.rule, Rule::conway());
+        assert_eq!(
+            config.color_overrides,
+            vec![("alive".to_string(), Color::from_rgba(255, 0, 0, 255))]
+        );
+        assert!(config.wants_random());
+    }
+
+    #[test]
+    fn test_arguments_override_settings() {
+        let settings = Config {
+            width: 300,
+            height: 200,
+            theme: 2,
+            ..Config::default()
+        };
+        let args: Vec<String> = ["--width", "50"].iter().map(|a| a.to_string()).collect();
+        let Ok(Invocation::Run(config)) = parse_args(&args, settings) else {
+            panic!("expected a config");
+        };
+        assert_eq!((config.width, config.height), (50, 200));
+        assert_eq!(config.theme, 2);
+    }
+
+    #[test]
+    fn test_headless_arguments() {
+        let config = config(&[
+            "--headless",
+            "--generations",
+            "5000",
+            "--pattern",
+            "acorn.rle",
+            "--out",
+            "result.rle",
+            "--export-svg",
+            "result.svg",
+            "--report",
+        ]);
+        assert!(config.headless && config.report);
+        assert_eq!(config.generations, 5000);
+        assert_eq!(config.out, Some(PathBuf::from("result.rle")));
+        assert_eq!(config.export_svg, Some(PathBuf::from("result.svg")));
+        assert_eq!(
+            parse(&["--generations", "-5"]).unwrap_err().to_string(),
+            "invalid value '-5' for --generations: expected a number of generations"
+        );
+    }
+
+    #[test]
+    fn test_starting_grid() {
+        let random = config(&["--seed", "7", "--width", "20", "--height", "10"]);
+        let grid = random.starting_grid().unwrap();
+        assert_eq!((grid.width(), grid.height()), (20, 10));
+        assert_eq!(grid.seed(), Some(7));
+        assert_eq!(grid, random.starting_grid().unwrap());
+
+        // The default spaceship is clipped on tiny boards
+        let tiny = config(&["--width", "12", "--height", "12"]);
+        assert_eq!(tiny.starting_grid().unwrap().population(), 1);
+    }
+
+    #[test]
+    fn test_start_pattern() {
+        let glider = config(&["--start", "Gosper-Gun", "--size", "40x20"]);
+        assert_eq!(glider.start.unwrap().name, "Gosper glider gun");
+        assert!(glider.explicit_board());
+        let grid = glider.starting_grid().unwrap();
+        assert_eq!((grid.width(), grid.height()), (40, 20));
+        assert_eq!(grid.population(), 36);
+
+        let error = parse(&["--start", "spaceship"]).unwrap_err().to_string();
+        assert!(error.starts_with(
+            "invalid value 'spaceship' for --start: unknown pattern, expected one of glider, lwss,"
+        ));
+        assert!(error.contains("gosper-glider-gun"));
+    }
+
+    #[test]
+    fn test_start_pattern_centering() {
+        // A 3x3 pattern is centered exactly on odd boards, and one cell up and
+        // left of the center on even ones
+        let corner = |size: &str| {
+            let grid = config(&["--start", "r-pentomino", "--size", size])
+                .starting_grid()
+                .unwrap();
+            let pentomino = patterns::find("r-pentomino").unwrap().to_grid();
+            (0..grid.height())
+                .flat_map(|y| (0..grid.width()).map(move |x| (x, y)))
+                .find(|&(x, y)| {
+                    let mut placed = Grid::new(grid.width(), grid.height());
+                    placed.insert_pattern(
+                        &pentomino,
+                        x as isize,
+                        y as isize,
+                        InsertMode::Overwrite,
+                    );
+                    placed == grid
+                })
+                .unwrap()
+        };
+        assert_eq!(corner("9x7"), (3, 2));
+        assert_eq!(corner("10x8"), (3, 2));
+        assert_eq!(corner("11x11"), (4, 4));
+        // Boards smaller than the pattern grow to fit it
+        assert_eq!(corner("2x2"), (0, 0));
+    }
+
+    #[test]
+    fn test_start_pattern_at() {
+        let config = config(&["--start", "block", "--at", "-1, 3", "--size", "5x5"]);
+        assert_eq!(config.at, Some((-1, 3)));
+        let grid = config.starting_grid().unwrap();
+        assert_eq!((grid.width(), grid.height()), (5, 5));
+        // Half of the block lies off the left edge
+        assert_eq!(grid.population(), 2);
+        assert_eq!(grid.get(0, 3), CellState::Alive);
+        assert_eq!(grid.get(0, 4), CellState::Alive);
+
+        assert_eq!(
+            parse(&["--at", "3"]).unwrap_err().to_string(),
+            "invalid value '3' for --at: expected <x>,<y>"
+        );
+        assert_eq!(
+            parse(&["--size", "10"]).unwrap_err().to_string(),
+            "invalid value '10' for --size: expected <width>x<height>, 1 to 4096 cells each"
+        );
+    }
+
+    #[test]
+    fn test_record_and_replay_exclude_each_other() {
+        assert_eq!(
+            config(&["--record", "demo.replay"]).record,
+            Some(PathBuf::from("demo.replay"))
+        );
+        assert_eq!(
+            parse(&["--record", "a.replay", "--replay", "b.replay"])
+                .unwrap_err()
+                .to_string(),
+            "invalid value 'b.replay' for --replay: can't be combined with --record"
+        );
+        assert_eq!(
+            config(&["--record-run", "out.lifereplay"]).record_run,
+            Some(PathBuf::from("out.lifereplay"))
+        );
+        assert_eq!(
+            parse(&["--record-run", "a.lifereplay", "--play-run", "b.lifereplay"])
+                .unwrap_err()
+                .to_string(),
+            "invalid value 'b.lifereplay' for --play-run: can't be combined with --record-run"
+        );
+    }
+
+    #[cfg(feature = "websocket")]
+    #[test]
+    fn test_websocket_port() {
+        assert_eq!(config(&["--websocket", "8080"]).websocket, Some(8080));
+        assert!(parse(&["--websocket", "web"]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "scripting")]
+    fn test_script_and_budget() {
+        let config = config(&["--script", "seed.rhai", "--script-budget", "5000"]);
+        assert_eq!(config.script, Some(PathBuf::from("seed.rhai")));
+        assert_eq!(config.script_budget, 5000);
+        assert!(config.explicit_board() && !config.wants_random());
+        assert_eq!(
+            parse(&["--script-budget", "0"]).unwrap_err().to_string(),
+            "invalid value '0' for --script-budget: expected a positive number of steps"
+        );
+    }
+
+    #[test]
+    fn test_host_and_join() {
+        assert_eq!(config(&["--host", "7777"]).host, Some(7777));
+        assert_eq!(
+            config(&["--join", "example.org:7777"]).join.as_deref(),
+            Some("example.org:7777")
+        );
+        assert_eq!(
+            parse(&["--host", "77777"]).unwrap_err().to_string(),
+            "invalid value '77777' for --host: expected a port number"
+        );
+        assert_eq!(
+            parse(&["--host", "7777", "--join", "localhost:7777"])
+                .unwrap_err()
+                .to_string(),
+            "invalid value 'localhost:7777' for --join: can't be combined with --host"
+        );
+    }
+
+    #[test]
+    fn test_daily_soup_is_the_same_for_everyone() {
+        let date = Date::parse("2024-06-01").unwrap();
+        let plain = config(&["--daily-date", "2024-06-01"]);
+        // Board settings from the command line or conways.toml don't change it
+        let tweaked = config(&[
+            "--size",
+            "30x30",
+            "--seed",
+            "5",
+            "--rule",
+            "B36/S23",
+            "--daily-date",
+            "2024-06-01",
+        ]);
+        assert_eq!(plain, tweaked);
+        assert_eq!(plain.daily, Some(date));
+        assert_eq!(plain.seed, Some(daily::daily_seed(date)));
+        assert_eq!(
+            plain.starting_grid().unwrap(),
+            tweaked.starting_grid().unwrap()
+        );
+        assert_eq!(
+            (plain.width, plain.height, plain.density),
+            (80, 60, Some(daily::DAILY_DENSITY))
+        );
+
+        assert!(config(&["--daily"]).daily.is_some());
+        assert_eq!(
+            parse(&["--daily-date", "2024-02-30"])
+                .unwrap_err()
+                .to_string(),
+            "invalid value '2024-02-30' for --daily-date: expected YYYY-MM-DD"
+        );
+        assert_eq!(
+            parse(&["--daily-date", "2024-06-01", "--start", "glider"])
+                .unwrap_err()
+                .to_string(),
+            "invalid value '2024-06-01' for --daily: can't be combined with --script, --pattern, --start or --puzzle"
+        );
+    }
+
+    #[test]
+    fn test_help() {
+        assert_eq!(parse(&["--help"]), Ok(Invocation::Help));
+        assert_eq!(parse(&["--paused", "-h"]), Ok(Invocation::Help));
+    }
+
+    #[test]
+    fn test_pattern_takes_precedence_over_random() {
+        // A reference layer isn't a starting board
+        let reference = config(&["--reference", "glider.rle"]);
+        assert_eq!(reference.reference, Some(PathBuf::from("glider.rle")));
+        assert!(!reference.explicit_board());
+        let config = config(&["--density", "0.2", "--pattern", "acorn.rle"]);
+        assert_eq!(config.pattern, Some(PathBuf::from("acorn.rle")));
+        assert!(!config.wants_random());
+    }
+
+    #[test]
+    fn test_bad_arguments() {
+        assert_eq!(
+            parse(&["--frobnicate"]),
+            Err(CliError::UnknownOption("--frobnicate".to_string()))
+        );
+
+        let reason = |args: &[&str]| match parse(args) {
+            Err(CliError::InvalidValue { reason, .. }) => reason,
+            other => panic!("expected an invalid value, got {other:?}"),
+        };
+        assert_eq!(reason(&["--width", "0"]), "expected 1 to 4096 cells");
+        assert_eq!(reason(&["--height", "tall"]), "expected 1 to 4096 cells");
+        assert_eq!(reason(&["--cell-size", "100"]), "expected 1 to 64 pixels");
+        assert_eq!(
+            reason(&["--density", "1.5"]),
+            "expected a number from 0 to 1"
+        );
+        assert_eq!(reason(&["--seed", "-1"]), "expected an integer");
+        assert_eq!(
+            reason(&["--rule", "B39/S23"]),
+            "'9' isn't a neighbor count from 0 to 8"
+        );
+        assert_eq!(reason(&["--color", "nope=#fff"]), "expected name=#rrggbb");
+        assert_eq!(
+            reason(&["--backend", "hashlife"]),
+            "expected grid, bits or sparse"
+        );
+    }
+
+    #[test]
+    fn test_missing_values() {
+        for option in ["--width", "--speed", "--seed", "--pattern", "--color"] {
+            assert_eq!(
+                parse(&[option]),
+                Err(CliError::MissingValue(option.to_string()))
+            );
+        }
+        assert_eq!(
+            parse(&["--speed", "0.1"]).unwrap_err().to_string(),
+            "invalid value '0.1' for --speed: expected 0.5 to 100 generations per second"
+        );
+    }
+}
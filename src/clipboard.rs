@@ -0,0 +1,35 @@
+use crate::conways::{Grid, Region};
+
+/// Cells copied out of the board, kept around so they can be pasted or exported later
+/// The pattern is stored as a standalone `Grid`, which also records its dimensions
+pub struct Clipboard {
+    pattern: Grid,
+}
+
+impl Clipboard {
+    /// Copy the cells of `region` out of `grid`
+    ///
+    /// # Arguments
+    /// * `grid` - The grid to copy from
+    /// * `region` - The area to copy, clipped to the grid bounds
+    pub fn copy(grid: &Grid, region: Region) -> Self {
+        Self {
+            pattern: grid.extract_region(region),
+        }
+    }
+
+    /// Returns the copied cells
+    pub fn pattern(&self) -> &Grid {
+        &self.pattern
+    }
+
+    /// Returns the width of the copied pattern
+    pub fn width(&self) -> usize {
+        self.pattern.width()
+    }
+
+    /// Returns the height of the copied pattern
+    pub fn height(&self) -> usize {
+        self.pattern.height()
+    }
+}
@@ -0,0 +1,589 @@
+//! Editing one board together over TCP, `--host <PORT>` in one game and
+//! `--join <HOST>:<PORT>` in the others
+//!
+//! The host's board is the only one that steps. Peers send it their painting,
+//! toggling and pausing, which it applies in the order they arrive, its own
+//! edits included, and it sends every peer what changed each frame. Other
+//! changes made on a peer, like pasting, stay there until the host's board
+//! overwrites them.
+//!
+//! Everything sent is a frame: its length as a little-endian `u32`, counting
+//! the kind byte, then the kind byte and the payload, numbers little-endian:
+//!
+//! | Kind | From | Payload |
+//! |------|------|---------|
+//! | 0 | host | The whole board as a `.lifesnap` snapshot, first thing to every peer and whenever the board changes size |
+//! | 1 | host | The board's changes, see `GridDelta` |
+//! | 2 | host | 1 when the host is paused, 0 when it runs |
+//! | 3 | peer | Set a cell: `x` and `y` as `u32`s, then 1 for alive or 0 for dead |
+//! | 4 | peer | Toggle a cell: `x` and `y` as `u32`s |
+//! | 5 | peer | 1 to pause, 0 to run |
+//!
+//! All the waiting on sockets happens on threads of their own, talking to the
+//! game through channels, so a slow or vanished peer never holds up a frame.
+//! A peer with `QUEUE_LEN` frames waiting is skipped until it makes room for
+//! a fresh snapshot, and one that takes longer than `WRITE_TIMEOUT` to take a
+//! frame is dropped.
+
+use crate::conways::delta::GridDelta;
+use crate::conways::{CellState, Grid};
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Longest frame read, enough for a snapshot of the largest board
+pub const MAX_FRAME_LEN: usize = 1 << 24;
+
+/// Frames waiting for a peer before it's skipped until it catches up
+const QUEUE_LEN: usize = 64;
+
+/// How long a peer has to take a frame before it's dropped
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SNAPSHOT: u8 = 0;
+const DELTA: u8 = 1;
+const PAUSED: u8 = 2;
+const SET: u8 = 3;
+const TOGGLE: u8 = 4;
+const PAUSE: u8 = 5;
+
+/// An edit a peer asks the host for
+#[derive(Clone, PartialEq, Debug)]
+pub enum Edit {
+    Set {
+        x: usize,
+        y: usize,
+        state: CellState,
+    },
+    Toggle {
+        x: usize,
+        y: usize,
+    },
+    /// Pause when `true`, run when `false`
+    Pause(bool),
+}
+
+impl Edit {
+    fn to_frame(&self) -> Vec<u8> {
+        let word = |n: usize| u32::try_from(n).unwrap_or(u32::MAX).to_le_bytes();
+        match self {
+            Edit::Set { x, y, state } => {
+                let alive = (*state == CellState::Alive) as u8;
+                frame(SET, &[&word(*x)[..], &word(*y), &[alive]].concat())
+            }
+            Edit::Toggle { x, y } => frame(TOGGLE, &[word(*x), word(*y)].concat()),
+            Edit::Pause(paused) => frame(PAUSE, &[*paused as u8]),
+        }
+    }
+
+    fn from_frame(kind: u8, payload: &[u8]) -> io::Result<Self> {
+        let word = |at: usize| u32::from_le_bytes(payload[at..at + 4].try_into().unwrap()) as usize;
+        match (kind, payload.len()) {
+            (SET, 9) => Ok(Edit::Set {
+                x: word(0),
+                y: word(4),
+                state: if payload[8] == 1 {
+                    CellState::Alive
+                } else {
+                    CellState::Dead
+                },
+            }),
+            (TOGGLE, 8) => Ok(Edit::Toggle {
+                x: word(0),
+                y: word(4),
+            }),
+            (PAUSE, 1) => Ok(Edit::Pause(payload[0] == 1)),
+            _ => Err(invalid(format!(
+                "unexpected {}-byte message of kind {kind}",
+                payload.len()
+            ))),
+        }
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// The frame of a message, see the [module docs](self)
+fn frame(kind: u8, payload: &[u8]) -> Vec<u8> {
+    let len = u32::try_from(payload.len() + 1).expect("frames are checked against MAX_FRAME_LEN");
+    let mut frame = Vec::with_capacity(payload.len() + 5);
+    frame.extend_from_slice(&len.to_le_bytes());
+    frame.push(kind);
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// The kind and payload of the next frame
+fn read_frame(input: &mut impl Read) -> io::Result<(u8, Vec<u8>)> {
+    let mut len = [0; 4];
+    input.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len == 0 || len > MAX_FRAME_LEN {
+        return Err(invalid(format!("a frame of {len} bytes")));
+    }
+    let mut frame = vec![0; len];
+    input.read_exact(&mut frame)?;
+    let kind = frame.remove(0);
+    Ok((kind, frame))
+}
+
+fn snapshot_frame(grid: &Grid) -> Vec<u8> {
+    let mut snapshot = Vec::new();
+    grid.write_snapshot(&mut snapshot)
+        .expect("writing to memory can't fail");
+    frame(SNAPSHOT, &snapshot)
+}
+
+/// A peer connected to the host
+struct Peer {
+    /// Frames for its writer thread to send
+    frames: SyncSender<Arc<[u8]>>,
+    /// Cleared by its reader thread once the connection is gone
+    connected: Arc<AtomicBool>,
+    /// Set while it's missing frames, from joining or from its queue filling
+    /// up, until a snapshot catches it up
+    behind: bool,
+}
+
+impl Peer {
+    /// Start the threads reading edits from and writing frames to `stream`
+    fn start(stream: TcpStream, edits: Sender<Edit>) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let mut reader = stream.try_clone()?;
+        let mut writer = stream;
+        let connected = Arc::new(AtomicBool::new(true));
+        let still_connected = Arc::clone(&connected);
+        thread::spawn(move || {
+            // Whatever went wrong, a broken frame included, ends the peer
+            while let Ok(edit) =
+                read_frame(&mut reader).and_then(|(kind, payload)| Edit::from_frame(kind, &payload))
+            {
+                if edits.send(edit).is_err() {
+                    break;
+                }
+            }
+            still_connected.store(false, Ordering::Relaxed);
+        });
+        let (frames, queued) = mpsc::sync_channel::<Arc<[u8]>>(QUEUE_LEN);
+        thread::spawn(move || {
+            for frame in queued {
+                if writer.write_all(&frame).is_err() {
+                    break;
+                }
+            }
+            // Also wakes the reader, for the host dropping the peer
+            let _ = writer.shutdown(Shutdown::Both);
+        });
+        Ok(Self {
+            frames,
+            connected,
+            behind: true,
+        })
+    }
+
+    /// Queue `frame`, falling behind if there's no room for it
+    fn send(&mut self, frame: &Arc<[u8]>) {
+        // A peer that's gone is dropped on the next sync
+        if !self.behind {
+            self.behind = self.frames.try_send(Arc::clone(frame)).is_err();
+        }
+    }
+
+    /// Queue `snapshot` for a peer that's behind, once there's room for it
+    fn catch_up(&mut self, snapshot: &Arc<[u8]>) {
+        if self.behind {
+            self.behind = self.frames.try_send(Arc::clone(snapshot)).is_err();
+        }
+    }
+}
+
+/// Which end of a shared board a game is
+pub enum Collab {
+    Host(Box<Host>),
+    Join(Client),
+}
+
+/// The game everyone else joins, see the [module docs](self)
+pub struct Host {
+    address: SocketAddr,
+    edits: Receiver<Edit>,
+    joined: Receiver<Peer>,
+    peers: Vec<Peer>,
+    /// The board every peer has with its `content_hash`, and the pause
+    /// state, `None` before the first sync
+    sent: Option<(Grid, u64, bool)>,
+}
+
+impl Host {
+    /// Listen for peers on `address`, accepting them on a thread of its own
+    pub fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let (edit_sender, edits) = mpsc::channel();
+        let (joiner, joined) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(peer) = stream.and_then(|stream| Peer::start(stream, edit_sender.clone()))
+                else {
+                    continue;
+                };
+                if joiner.send(peer).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            address,
+            edits,
+            joined,
+            peers: Vec::new(),
+            sent: None,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Peers connected at the last sync
+    pub fn peers(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// The edits peers sent since the last call, in the order they arrived
+    pub fn edits(&mut self) -> Vec<Edit> {
+        self.edits.try_iter().collect()
+    }
+
+    /// Send the peers how `grid` and the pause state changed since the last
+    /// sync, and a snapshot to peers that joined or fell behind since
+    pub fn sync(&mut self, grid: &Grid, paused: bool) {
+        self.peers
+            .retain(|peer| peer.connected.load(Ordering::Relaxed));
+        let mut frames: Vec<Arc<[u8]>> = Vec::new();
+        // Reading one board rather than two; were the hashes to collide, the
+        // edit would go out with the next change
+        let hash = grid.content_hash();
+        match &self.sent {
+            Some((sent, was, _)) if sent.generation() == grid.generation() && *was == hash => (),
+            Some((sent, _, _)) => {
+                // Whichever is smaller, most of the board changing at once
+                let delta = GridDelta::between(sent, grid).map(|delta| delta.to_bytes());
+                frames.push(
+                    match delta {
+                        Some(delta) if delta.len() < grid.width() * grid.height() / 4 => {
+                            frame(DELTA, &delta)
+                        }
+                        _ => snapshot_frame(grid),
+                    }
+                    .into(),
+                );
+            }
+            None => (),
+        }
+        if self.sent.as_ref().map(|(_, _, was)| *was) != Some(paused) {
+            frames.push(frame(PAUSED, &[paused as u8]).into());
+        }
+        for frame in &frames {
+            for peer in &mut self.peers {
+                peer.send(frame);
+            }
+        }
+
+        self.peers.extend(self.joined.try_iter());
+        if self.peers.iter().any(|peer| peer.behind) {
+            // Queued as one, so that a peer gets both or neither
+            let mut snapshot = snapshot_frame(grid);
+            snapshot.extend(frame(PAUSED, &[paused as u8]));
+            let snapshot: Arc<[u8]> = snapshot.into();
+            for peer in &mut self.peers {
+                peer.catch_up(&snapshot);
+            }
+        }
+        if !frames.is_empty() || self.sent.is_none() {
+            self.sent = Some((grid.clone(), hash, paused));
+        }
+    }
+}
+
+/// What a `Client::sync` brought in
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Received {
+    /// The board was replaced by a snapshot, maybe of another size or rule
+    pub snapshot: bool,
+    /// Cells or the generation changed
+    pub changed: bool,
+    /// Whether the host is paused, if it said so
+    pub paused: Option<bool>,
+}
+
+/// A game joined to a host, see the [module docs](self)
+pub struct Client {
+    stream: TcpStream,
+    frames: Receiver<io::Result<(u8, Vec<u8>)>>,
+}
+
+impl Client {
+    /// Connect to a host, reading what it sends on a thread of its own
+    pub fn connect(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+        let mut reader = stream.try_clone()?;
+        let (sender, frames) = mpsc::channel();
+        thread::spawn(move || loop {
+            let frame = read_frame(&mut reader);
+            let failed = frame.is_err();
+            if sender.send(frame).is_err() || failed {
+                break;
+            }
+        });
+        Ok(Self { stream, frames })
+    }
+
+    /// Ask the host for an edit
+    pub fn send(&mut self, edit: &Edit) -> io::Result<()> {
+        self.stream.write_all(&edit.to_frame())
+    }
+
+    /// Bring `grid` up to date with everything the host sent since the last
+    /// call
+    ///
+    /// # Returns
+    /// What changed, or an error once the connection is gone or the host sent
+    /// something that isn't a message
+    pub fn sync(&mut self, grid: &mut Grid) -> io::Result<Received> {
+        let mut received = Received::default();
+        for frame in self.frames.try_iter() {
+            let (kind, payload) = frame?;
+            let broken = |err: crate::error::ConwayError| invalid(err.to_string());
+            match (kind, &payload[..]) {
+                (SNAPSHOT, snapshot) => {
+                    *grid = Grid::read_snapshot(snapshot).map_err(broken)?;
+                    received.snapshot = true;
+                }
+                (DELTA, delta) => GridDelta::from_bytes(delta)
+                    .and_then(|delta| delta.apply(grid))
+                    .map_err(broken)?,
+                (PAUSED, [paused]) => {
+                    received.paused = Some(*paused == 1);
+                    continue;
+                }
+                (kind, payload) => {
+                    return Err(invalid(format!(
+                        "unexpected {}-byte message of kind {kind}",
+                        payload.len()
+                    )))
+                }
+            }
+            received.changed = true;
+        }
+        Ok(received)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn hash(grid: &Grid) -> (u64, u64) {
+        (grid.content_hash(), grid.generation())
+    }
+
+    /// Call `done` until it's true, failing after a few seconds
+    fn wait_until(mut done: impl FnMut() -> bool) {
+        let start = Instant::now();
+        while !done() {
+            assert!(start.elapsed() < Duration::from_secs(5), "timed out");
+            thread::sleep(Duration::from_millis(2));
+        }
+    }
+
+    fn host() -> Host {
+        Host::bind("127.0.0.1:0").unwrap()
+    }
+
+    /// A client whose board has caught up with `grid` on the host
+    fn join(host: &mut Host, grid: &Grid) -> (Client, Grid) {
+        let mut client = Client::connect(host.local_addr()).unwrap();
+        let mut copy = Grid::new(1, 1);
+        let peers = host.peers();
+        wait_until(|| {
+            host.sync(grid, false);
+            host.peers() > peers
+        });
+        wait_until(|| client.sync(&mut copy).unwrap().snapshot);
+        (client, copy)
+    }
+
+    #[test]
+    fn test_a_client_tracks_the_host() {
+        let mut host = host();
+        let mut grid = Grid::new(64, 48);
+        grid.randomize(0.3, 11);
+        grid.set_rule("B36/S23".parse().unwrap());
+        let (mut client, mut copy) = join(&mut host, &grid);
+        assert_eq!((hash(&copy), copy.rule()), (hash(&grid), grid.rule()));
+
+        for _ in 0..30 {
+            grid.next_cell_generation();
+            host.sync(&grid, false);
+        }
+        wait_until(|| {
+            client.sync(&mut copy).unwrap();
+            hash(&copy) == hash(&grid)
+        });
+        assert_eq!(copy.generation(), 30);
+
+        // Growing the board needs a snapshot, which a delta can't carry
+        grid.resize(80, 50, (3, 1));
+        host.sync(&grid, true);
+        let mut paused = None;
+        wait_until(|| {
+            let received = client.sync(&mut copy).unwrap();
+            paused = paused.or(received.paused);
+            hash(&copy) == hash(&grid) && paused.is_some()
+        });
+        assert_eq!((copy.width(), paused), (80, Some(true)));
+    }
+
+    #[test]
+    fn test_late_joiners_get_the_current_board() {
+        let mut host = host();
+        let mut grid = Grid::new(40, 40);
+        grid.randomize(0.4, 2);
+        let (mut early, mut early_copy) = join(&mut host, &grid);
+        for _ in 0..12 {
+            grid.next_cell_generation();
+            host.sync(&grid, false);
+        }
+
+        let (mut late, mut late_copy) = join(&mut host, &grid);
+        assert_eq!(late_copy.generation(), 12);
+        grid.next_cell_generation();
+        host.sync(&grid, false);
+        wait_until(|| {
+            early.sync(&mut early_copy).unwrap();
+            late.sync(&mut late_copy).unwrap();
+            hash(&early_copy) == hash(&grid) && hash(&late_copy) == hash(&grid)
+        });
+    }
+
+    #[test]
+    fn test_edits_arrive_in_order() {
+        let mut host = host();
+        let grid = Grid::new(10, 10);
+        let (mut first, _) = join(&mut host, &grid);
+        let (mut second, _) = join(&mut host, &grid);
+
+        let edits = [
+            Edit::Set {
+                x: 3,
+                y: 4,
+                state: CellState::Alive,
+            },
+            Edit::Toggle { x: 9, y: 0 },
+            Edit::Pause(true),
+        ];
+        for edit in &edits {
+            first.send(edit).unwrap();
+        }
+        let mut received = Vec::new();
+        wait_until(|| {
+            received.extend(host.edits());
+            received.len() == 3
+        });
+        assert_eq!(received, edits);
+
+        // The same cell from both: whichever the host got last wins
+        first.send(&edits[0]).unwrap();
+        wait_until(|| {
+            received = host.edits();
+            !received.is_empty()
+        });
+        let dead = Edit::Set {
+            x: 3,
+            y: 4,
+            state: CellState::Dead,
+        };
+        second.send(&dead).unwrap();
+        wait_until(|| {
+            received.extend(host.edits());
+            received.len() == 2
+        });
+        assert_eq!(received, [edits[0].clone(), dead]);
+    }
+
+    #[test]
+    fn test_a_peer_falling_behind_gets_a_snapshot() {
+        let mut host = host();
+        let (frames, queued) = mpsc::sync_channel(QUEUE_LEN);
+        host.peers.push(Peer {
+            frames,
+            connected: Arc::new(AtomicBool::new(true)),
+            behind: true,
+        });
+        let mut grid = Grid::new(16, 16);
+        grid.randomize(0.3, 2);
+        host.sync(&grid, false);
+        // The snapshot and then a delta for every toggle, with nothing taken
+        // off the queue, so the last ones don't fit
+        for _ in 0..QUEUE_LEN + 10 {
+            grid.toggle(3, 4);
+            host.sync(&grid, false);
+        }
+        assert!(host.peers[0].behind);
+        assert_eq!(queued.try_iter().count(), QUEUE_LEN);
+
+        // Once there's room again it's caught up with the board as it is
+        host.sync(&grid, true);
+        assert!(!host.peers[0].behind);
+        let queued: Vec<Arc<[u8]>> = queued.try_iter().collect();
+        assert_eq!(queued.len(), 1);
+        let mut bytes = &queued[0][..];
+        let (kind, snapshot) = read_frame(&mut bytes).unwrap();
+        assert_eq!(kind, SNAPSHOT);
+        assert_eq!(Grid::read_snapshot(&snapshot[..]).unwrap(), grid);
+        assert_eq!(read_frame(&mut bytes).unwrap(), (PAUSED, vec![1]));
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn test_peers_leaving_dont_stop_the_host() {
+        let mut host = host();
+        let mut grid = Grid::new(30, 30);
+        grid.randomize(0.3, 5);
+        let (mut stays, mut copy) = join(&mut host, &grid);
+        let (leaves, _) = join(&mut host, &grid);
+        drop(leaves);
+        // A peer sending garbage is dropped like one that left
+        let mut garbage = TcpStream::connect(host.local_addr()).unwrap();
+        garbage.write_all(b"\xff\xff\xff\xffhello").unwrap();
+        TcpStream::connect(host.local_addr())
+            .unwrap()
+            .write_all(&frame(TOGGLE, &[1, 2, 3]))
+            .unwrap();
+
+        wait_until(|| {
+            grid.next_cell_generation();
+            host.sync(&grid, false);
+            host.peers() == 1
+        });
+        assert!(host.edits().is_empty());
+        wait_until(|| {
+            stays.sync(&mut copy).unwrap();
+            hash(&copy) == hash(&grid)
+        });
+
+        // And the host going away ends the client's connection
+        drop(host);
+        wait_until(|| stays.sync(&mut copy).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::conways::{CellState, Grid};
+use crate::history::History;
+
+/// An edit to the board requested by the player
+#[derive(Clone, PartialEq, Debug)]
+pub enum Command {
+    /// Set a single cell to the given state
+    Set {
+        x: usize,
+        y: usize,
+        state: CellState,
+    },
+    /// Flip a single cell between dead and alive
+    Toggle { x: usize, y: usize },
+    /// Start grouping edits into a single undo step, e.g. when a mouse button goes down
+    Begin,
+    /// Finish the undo step started by `Begin`, e.g. when the mouse button is released
+    End,
+}
+
+impl Command {
+    fn apply(&self, grid: &mut Grid, history: &mut History) {
+        match self {
+            Command::Set { x, y, state } => grid.set(*x, *y, state.clone()),
+            Command::Toggle { x, y } => grid.toggle(*x, *y),
+            Command::Begin => history.begin(grid),
+            Command::End => history.end(grid),
+        }
+    }
+}
+
+/// Edits waiting to be applied to the board
+///
+/// Input handling only queues commands; the game applies the whole queue at
+/// the start of its update, right before the simulation may step. A step
+/// therefore always sees every edit made during the frame, never part of them,
+/// and painting works the same whether the simulation is running or paused.
+pub struct CommandQueue {
+    commands: Vec<Command>,
+}
+
+impl CommandQueue {
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, command: Command) {
+        self.commands.push(command);
+    }
+
+    /// The commands waiting to be applied, oldest first
+    pub fn pending(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Whether any queued command changes cells, rather than only grouping undo steps
+    pub fn has_edits(&self) -> bool {
+        self.commands
+            .iter()
+            .any(|command| matches!(command, Command::Set { .. } | Command::Toggle { .. }))
+    }
+
+    /// Every queued command, oldest first, emptying the queue without
+    /// applying them, e.g. to send them to the game that does
+    pub fn take(&mut self) -> Vec<Command> {
+        std::mem::take(&mut self.commands)
+    }
+
+    /// Apply every queued command to the grid, in order, emptying the queue
+    /// Edits outside a `Begin`/`End` pair aren't undoable.
+    pub fn apply(&mut self, grid: &mut Grid, history: &mut History) {
+        for command in self.commands.drain(..) {
+            command.apply(grid, history);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands_apply_in_order() {
+        let mut grid = Grid::new(3, 3);
+        let mut history = History::new();
+        let mut queue = CommandQueue::new();
+        queue.push(Command::Toggle { x: 1, y: 1 });
+        queue.push(Command::Set {
+            x: 0,
+            y: 0,
+            state: CellState::Alive,
+        });
+        queue.push(Command::Toggle { x: 0, y: 0 });
+
+        // Nothing changes until the queue is applied
+        assert_eq!(grid.population(), 0);
+        assert!(queue.has_edits());
+        queue.apply(&mut grid, &mut history);
+        assert!(!queue.has_edits());
+        assert_eq!(grid.get(1, 1), CellState::Alive);
+        assert_eq!(grid.get(0, 0), CellState::Dead);
+
+        // The queue is empty afterwards
+        queue.apply(&mut grid, &mut history);
+        assert_eq!(grid.population(), 1);
+    }
+
+    #[test]
+    fn test_paint_mid_run_affects_next_generation() {
+        // A vertical blinker, part way through a run
+        let mut grid = Grid::new(5, 5);
+        for y in 1..4 {
+            grid.set(2, y, CellState::Alive);
+        }
+        grid.next_cell_generation();
+        grid.next_cell_generation();
+
+        // Painting a cell next to it gives (1, 1) three neighbors: it is born,
+        // which an unpainted blinker would never do
+        let mut history = History::new();
+        let mut queue = CommandQueue::new();
+        queue.push(Command::Set {
+            x: 1,
+            y: 2,
+            state: CellState::Alive,
+        });
+        queue.apply(&mut grid, &mut history);
+        grid.next_cell_generation();
+
+        assert_eq!(grid.get(1, 1), CellState::Alive);
+        assert_eq!(grid.get(1, 3), CellState::Alive);
+        assert_eq!(grid.generation(), 3);
+    }
+
+    /// Queue the edits of a left-button drag over `cells`, one frame per cell
+    fn drag(
+        queue: &mut CommandQueue,
+        grid: &mut Grid,
+        history: &mut History,
+        cells: &[(usize, usize)],
+    ) {
+        queue.push(Command::Begin);
+        for &(x, y) in cells {
+            queue.push(Command::Set {
+                x,
+                y,
+                state: CellState::Alive,
+            });
+            queue.apply(grid, history);
+        }
+        queue.push(Command::End);
+        queue.apply(grid, history);
+    }
+
+    #[test]
+    fn test_drag_undoes_as_one_step() {
+        let mut grid = Grid::new(5, 5);
+        let mut history = History::new();
+        let mut queue = CommandQueue::new();
+
+        drag(
+            &mut queue,
+            &mut grid,
+            &mut history,
+            &[(0, 0), (1, 0), (2, 0)],
+        );
+        drag(&mut queue, &mut grid, &mut history, &[(0, 4), (1, 4)]);
+        assert_eq!(grid.population(), 5);
+
+        assert!(history.undo(&mut grid));
+        assert_eq!(grid.population(), 3);
+        assert!(history.undo(&mut grid));
+        assert_eq!(grid.population(), 0);
+        assert!(!history.undo(&mut grid));
+    }
+
+    #[test]
+    fn test_begin_and_end_in_the_same_frame() {
+        // A quick click: the button goes down and up before the queue is applied
+        let mut grid = Grid::new(3, 3);
+        let mut history = History::new();
+        let mut queue = CommandQueue::new();
+        queue.push(Command::Begin);
+        queue.push(Command::Toggle { x: 1, y: 1 });
+        queue.push(Command::End);
+        queue.apply(&mut grid, &mut history);
+
+        assert!(history.undo(&mut grid));
+        assert_eq!(grid.population(), 0);
+        assert!(history.redo(&mut grid));
+        assert_eq!(grid.get(1, 1), CellState::Alive);
+    }
+
+    #[test]
+    fn test_drag_without_changes_is_not_a_step() {
+        let mut grid = Grid::new(3, 3);
+        grid.set(0, 0, CellState::Alive);
+        let mut history = History::new();
+        let mut queue = CommandQueue::new();
+
+        // Painting over cells that are already alive
+        drag(&mut queue, &mut grid, &mut history, &[(0, 0)]);
+        assert!(!history.undo(&mut grid));
+    }
+}
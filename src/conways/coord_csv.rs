@@ -0,0 +1,229 @@
+//! Live cells as CSV, one `x,y` row each, for spreadsheets and quick scripts
+//!
+//! ```text
+//! x,y
+//! 1,0
+//! 2,1
+//! ```
+//!
+//! Only the cells are kept, not the size of the grid, its rule or its
+//! generation. Reading, the `x,y` header is optional, blank lines are
+//! skipped, spaces around a number are allowed and a cell listed twice is
+//! simply alive.
+
+use super::{CellState, Grid};
+use crate::error::ConwayError;
+use crate::formats::MAX_PATTERN_CELLS;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// A malformed row at the 1-based `line`
+fn invalid(line: usize, msg: impl Into<String>) -> ConwayError {
+    ConwayError::ParseError {
+        format: "CSV",
+        line,
+        col: None,
+        msg: msg.into(),
+    }
+}
+
+impl Grid {
+    /// The live cells as CSV, row by row after an `x,y` header, see the
+    /// [module docs](self)
+    ///
+    /// # Example
+    /// ```
+    /// use game_of_life::conways::{CellState, Grid};
+    ///
+    /// let mut grid = Grid::new(3, 3);
+    /// grid.set(2, 0, CellState::Alive);
+    /// grid.set(0, 1, CellState::Alive);
+    /// assert_eq!(grid.to_coord_csv(), "x,y\n2,0\n0,1\n");
+    /// ```
+    pub fn to_coord_csv(&self) -> String {
+        let mut csv = String::from("x,y\n");
+        for (y, row) in self.grid.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if *cell == CellState::Alive {
+                    let _ = writeln!(csv, "{x},{y}");
+                }
+            }
+        }
+        csv
+    }
+
+    /// Read live cells from CSV, like `to_coord_csv` writes
+    ///
+    /// # Arguments
+    /// * `text` - The CSV, a row per live cell
+    /// * `size` - The width and height of the grid, or `None` for the
+    ///   bounding box of the cells, which may then be negative and are
+    ///   moved so the box's top-left corner is `(0, 0)`
+    ///
+    /// # Returns
+    /// The grid, or a `ParseError` naming the line of a row that isn't two
+    /// numbers, or of a cell off a grid of the given `size`; also when the
+    /// grid would have more than `MAX_PATTERN_CELLS` cells
+    ///
+    /// # Example
+    /// ```
+    /// use game_of_life::conways::{CellState, Grid};
+    ///
+    /// let grid = Grid::from_coord_csv("-1,5\n1,6\n", None).unwrap();
+    /// assert_eq!((grid.width(), grid.height()), (3, 2));
+    /// assert_eq!(grid.get(0, 0), CellState::Alive);
+    /// assert!(Grid::from_coord_csv("3,0", Some((3, 3))).is_err());
+    /// ```
+    pub fn from_coord_csv(text: &str, size: Option<(usize, usize)>) -> Result<Grid, ConwayError> {
+        let mut cells = Vec::new();
+        for (i, row) in text.lines().enumerate() {
+            let line = i + 1;
+            let row = row.trim();
+            if row.is_empty() || (cells.is_empty() && row.eq_ignore_ascii_case("x,y")) {
+                continue;
+            }
+            let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+            let [x, y] = fields[..] else {
+                return Err(invalid(
+                    line,
+                    format!("expected x,y, found {} fields", fields.len()),
+                ));
+            };
+            let number = |field: &str, name: &str| {
+                field
+                    .parse::<i64>()
+                    .map_err(|_| invalid(line, format!("{name} '{field}' isn't a whole number")))
+            };
+            cells.push((number(x, "x")?, number(y, "y")?, line));
+        }
+
+        // The box the cells have to fit, as its corner and size
+        let (left, top, width, height) = match size {
+            Some((width, height)) => (0, 0, width, height),
+            None if cells.is_empty() => (0, 0, 0, 0),
+            None => {
+                let xs = cells.iter().map(|&(x, ..)| x);
+                let ys = cells.iter().map(|&(_, y, _)| y);
+                let (left, right) = (xs.clone().min().unwrap_or(0), xs.max().unwrap_or(0));
+                let (top, bottom) = (ys.clone().min().unwrap_or(0), ys.max().unwrap_or(0));
+                let side = |from: i64, to: i64| {
+                    let side = to.abs_diff(from).checked_add(1);
+                    side.and_then(|side| usize::try_from(side).ok())
+                        .unwrap_or(usize::MAX)
+                };
+                (left, top, side(left, right), side(top, bottom))
+            }
+        };
+        match width.max(1).checked_mul(height.max(1)) {
+            Some(count) if count <= MAX_PATTERN_CELLS => (),
+            _ => {
+                return Err(invalid(
+                    0,
+                    format!("a {width}x{height} grid is larger than {MAX_PATTERN_CELLS} cells"),
+                ))
+            }
+        }
+
+        let mut grid = Grid::new(width, height);
+        for (x, y, line) in cells {
+            let column = usize::try_from(x.abs_diff(left)).ok().filter(|_| x >= left);
+            let row = usize::try_from(y.abs_diff(top)).ok().filter(|_| y >= top);
+            match (column, row) {
+                (Some(column), Some(row)) if column < width && row < height => {
+                    grid.grid[row][column] = CellState::Alive;
+                }
+                _ => {
+                    return Err(invalid(
+                        line,
+                        format!("cell {x},{y} is outside the {width}x{height} grid"),
+                    ))
+                }
+            }
+        }
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(text: &str, size: Option<(usize, usize)>) -> (usize, String) {
+        match Grid::from_coord_csv(text, size) {
+            Err(ConwayError::ParseError { line, msg, .. }) => (line, msg),
+            other => panic!("not a parse error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_grids_round_trip() {
+        let mut grid = Grid::new(40, 30);
+        grid.randomize(0.3, 9);
+        let read = Grid::from_coord_csv(&grid.to_coord_csv(), Some((40, 30))).unwrap();
+        assert_eq!(read, grid);
+        assert_eq!(Grid::new(4, 4).to_coord_csv(), "x,y\n");
+    }
+
+    #[test]
+    fn test_without_a_size_the_grid_fits_the_cells() {
+        let grid = Grid::from_coord_csv("x,y\n10,-3\n12,-1\n10,-3\n", None).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 3));
+        assert_eq!(grid.population(), 2);
+        assert_eq!(grid.get(0, 0), CellState::Alive);
+        assert_eq!(grid.get(2, 2), CellState::Alive);
+
+        let empty = Grid::from_coord_csv("x,y\n", None).unwrap();
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+    }
+
+    #[test]
+    fn test_a_given_size_clips_nothing() {
+        let grid = Grid::from_coord_csv("0,0\n4,2\n", Some((5, 3))).unwrap();
+        assert_eq!((grid.width(), grid.height(), grid.population()), (5, 3, 2));
+        assert_eq!(
+            message("0,0\n5,2\n", Some((5, 3))),
+            (2, "cell 5,2 is outside the 5x3 grid".into())
+        );
+        assert_eq!(
+            message("-1,0\n", Some((5, 3))),
+            (1, "cell -1,0 is outside the 5x3 grid".into())
+        );
+    }
+
+    #[test]
+    fn test_blank_lines_and_spaces_are_allowed() {
+        let text = "X,Y \r\n\n 1 , 2\t\r\n   \n0,0   \n";
+        let grid = Grid::from_coord_csv(text, Some((2, 3))).unwrap();
+        assert_eq!(grid.population(), 2);
+        assert_eq!(grid.get(1, 2), CellState::Alive);
+    }
+
+    #[test]
+    fn test_malformed_rows_are_refused_by_line() {
+        assert_eq!(
+            message("0,0\n\n1\n", None),
+            (3, "expected x,y, found 1 fields".into())
+        );
+        assert_eq!(
+            message("0,0,1", None),
+            (1, "expected x,y, found 3 fields".into())
+        );
+        assert_eq!(
+            message("0,0\n1.5,2", None),
+            (2, "x '1.5' isn't a whole number".into())
+        );
+        // The header is only one before the first cell
+        assert_eq!(
+            message("0,0\nx,y", None),
+            (2, "x 'x' isn't a whole number".into())
+        );
+        assert!(message("0,0\n100000,100000", None)
+            .1
+            .contains("larger than"));
+        assert!(message(&format!("{},0\n{},0", i64::MIN, i64::MAX), None)
+            .1
+            .contains("larger than"));
+    }
+}
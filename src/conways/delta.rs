@@ -0,0 +1,223 @@
+//! The cells that changed between two boards of the same size, for sending a
+//! running board a generation at a time instead of whole
+//!
+//! In bytes, every number little-endian: the generation after the change as
+//! a `u64`, how many cells were born and how many died as `u32`s, then the
+//! `x` and `y` of every cell born and after them of every cell that died,
+//! each a `u32`.
+
+use super::{CellState, Grid};
+use crate::error::ConwayError;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Bytes before the cells: the generation and the two counts
+const HEADER_LEN: usize = 16;
+
+fn invalid(msg: impl Into<String>) -> ConwayError {
+    ConwayError::ParseError {
+        format: "delta",
+        line: 0,
+        col: None,
+        msg: msg.into(),
+    }
+}
+
+/// What changed from one board to another, see the [module docs](self)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GridDelta {
+    /// Generation of the board after the change
+    pub generation: u64,
+    /// Cells that came to life, row by row
+    pub born: Vec<(usize, usize)>,
+    /// Cells that died, row by row
+    pub died: Vec<(usize, usize)>,
+}
+
+impl GridDelta {
+    /// What turns `before` into `after`, or `None` when they aren't the same
+    /// size, which no delta can cover
+    ///
+    /// # Example
+    /// ```
+    /// use game_of_life::conways::delta::GridDelta;
+    /// use game_of_life::conways::{CellState, Grid};
+    ///
+    /// let mut blinker = Grid::new(5, 5);
+    /// for x in 1..4 {
+    ///     blinker.set(x, 2, CellState::Alive);
+    /// }
+    /// let next = blinker.next_generation();
+    /// let delta = GridDelta::between(&blinker, &next).unwrap();
+    /// assert_eq!((delta.born.len(), delta.died.len()), (2, 2));
+    /// delta.apply(&mut blinker).unwrap();
+    /// assert_eq!(blinker, next);
+    /// ```
+    pub fn between(before: &Grid, after: &Grid) -> Option<Self> {
+        if (before.width, before.height) != (after.width, after.height) {
+            return None;
+        }
+        let mut delta = Self {
+            generation: after.generation,
+            ..Self::default()
+        };
+        for (y, (old, new)) in before.grid.iter().zip(&after.grid).enumerate() {
+            for (x, (old, new)) in old.iter().zip(new).enumerate() {
+                match (old, new) {
+                    (CellState::Dead, CellState::Alive) => delta.born.push((x, y)),
+                    (CellState::Alive, CellState::Dead) => delta.died.push((x, y)),
+                    _ => (),
+                }
+            }
+        }
+        Some(delta)
+    }
+
+    /// Whether no cell changed, though the generation may have
+    pub fn is_empty(&self) -> bool {
+        self.born.is_empty() && self.died.is_empty()
+    }
+
+    /// Bring the cells of the delta to life or kill them, and move `grid` to
+    /// its generation
+    ///
+    /// # Returns
+    /// `OutOfBounds` for the first cell off the grid, in which case nothing
+    /// is changed
+    pub fn apply(&self, grid: &mut Grid) -> Result<(), ConwayError> {
+        let outside = self
+            .born
+            .iter()
+            .chain(&self.died)
+            .find(|(x, y)| *x >= grid.width || *y >= grid.height);
+        if let Some(&(x, y)) = outside {
+            return Err(ConwayError::OutOfBounds {
+                x,
+                y,
+                width: grid.width,
+                height: grid.height,
+            });
+        }
+        for &(x, y) in &self.born {
+            grid.set(x, y, CellState::Alive);
+        }
+        for &(x, y) in &self.died {
+            grid.set(x, y, CellState::Dead);
+        }
+        grid.generation = self.generation;
+        Ok(())
+    }
+
+    /// The delta in bytes, see the [module docs](self)
+    ///
+    /// # Panics
+    /// When a count or a coordinate doesn't fit a `u32`, which takes a grid
+    /// far bigger than any the game opens
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let cells = self.born.len() + self.died.len();
+        let mut bytes = Vec::with_capacity(HEADER_LEN + cells * 8);
+        let word = |n: usize| u32::try_from(n).expect("too big for a delta").to_le_bytes();
+        bytes.extend_from_slice(&self.generation.to_le_bytes());
+        bytes.extend_from_slice(&word(self.born.len()));
+        bytes.extend_from_slice(&word(self.died.len()));
+        for &(x, y) in self.born.iter().chain(&self.died) {
+            bytes.extend_from_slice(&word(x));
+            bytes.extend_from_slice(&word(y));
+        }
+        bytes
+    }
+
+    /// Read a delta written by `to_bytes`
+    ///
+    /// # Returns
+    /// The delta, or a `ParseError` when `bytes` is shorter or longer than
+    /// its counts say
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ConwayError> {
+        let Some((header, cells)) = bytes.split_first_chunk::<HEADER_LEN>() else {
+            return Err(invalid(format!(
+                "shorter than its {HEADER_LEN}-byte header"
+            )));
+        };
+        let word = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().expect("4 bytes")) as usize;
+        let (born, died) = (word(&header[8..12]), word(&header[12..]));
+        if Some(cells.len()) != born.checked_add(died).and_then(|n| n.checked_mul(8)) {
+            return Err(invalid(format!(
+                "{} bytes of cells for {born} born and {died} dead",
+                cells.len()
+            )));
+        }
+        let mut cells = cells
+            .chunks_exact(8)
+            .map(|cell| (word(&cell[..4]), word(&cell[4..])));
+        Ok(Self {
+            generation: u64::from_le_bytes(header[..8].try_into().expect("8 bytes")),
+            born: cells.by_ref().take(born).collect(),
+            died: cells.collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_deltas_replay_a_run() {
+        let mut grid = Grid::new(40, 30);
+        grid.randomize(0.3, 4);
+        let mut copy = grid.clone();
+        for _ in 0..20 {
+            let before = grid.clone();
+            grid.next_cell_generation();
+            let delta = GridDelta::between(&before, &grid).unwrap();
+            let read = GridDelta::from_bytes(&delta.to_bytes()).unwrap();
+            assert_eq!(read, delta);
+            read.apply(&mut copy).unwrap();
+            assert_eq!((copy.generation(), &copy), (grid.generation(), &grid));
+        }
+        let still = GridDelta::between(&grid, &grid).unwrap();
+        assert!(still.is_empty());
+        assert_eq!(still.to_bytes().len(), HEADER_LEN);
+        assert_eq!(GridDelta::between(&grid, &Grid::new(30, 40)), None);
+    }
+
+    #[test]
+    fn test_cells_off_the_grid_change_nothing() {
+        let delta = GridDelta {
+            generation: 9,
+            born: vec![(1, 1), (5, 0)],
+            died: Vec::new(),
+        };
+        let mut grid = Grid::new(5, 5);
+        assert!(matches!(
+            delta.apply(&mut grid),
+            Err(ConwayError::OutOfBounds { x: 5, y: 0, .. })
+        ));
+        assert_eq!((grid.generation(), grid.population()), (0, 0));
+    }
+
+    #[test]
+    fn test_malformed_bytes_are_refused() {
+        let message = |bytes: &[u8]| match GridDelta::from_bytes(bytes) {
+            Err(ConwayError::ParseError { msg, .. }) => msg,
+            other => panic!("not a parse error: {other:?}"),
+        };
+        let bytes = GridDelta {
+            generation: 1,
+            born: vec![(2, 3)],
+            died: vec![(4, 5)],
+        }
+        .to_bytes();
+        assert_eq!(bytes.len(), HEADER_LEN + 16);
+        assert_eq!(message(&bytes[..10]), "shorter than its 16-byte header");
+        assert_eq!(
+            message(&bytes[..bytes.len() - 1]),
+            "15 bytes of cells for 1 born and 1 dead"
+        );
+        let mut huge = bytes.clone();
+        huge[8..16].fill(0xff);
+        assert!(message(&huge).contains("4294967295 born"));
+    }
+}
@@ -0,0 +1,251 @@
+//! Hashes of a grid's size and cells that are the same on every platform and
+//! in every release, unlike the standard library's hasher; like comparing
+//! grids, they leave the generation, rule and edges out
+
+use super::{CellState, Grid};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+impl Grid {
+    /// Feed the bytes the hashes are over to `feed`, a row at a time
+    fn content_bytes(&self, mut feed: impl FnMut(&[u8])) {
+        feed(&(self.width as u64).to_le_bytes());
+        feed(&(self.height as u64).to_le_bytes());
+        let mut bytes = alloc::vec::Vec::with_capacity(self.width);
+        for row in &self.grid {
+            bytes.clear();
+            bytes.extend(row.iter().map(|cell| (*cell == CellState::Alive) as u8));
+            feed(&bytes);
+        }
+    }
+
+    /// A 64-bit FNV-1a hash of the grid's size and cells: of its width and
+    /// then its height as little-endian `u64`s, then of every cell row by
+    /// row, 1 for alive and 0 for dead
+    ///
+    /// Two grids that are equal always hash the same, so a differing hash
+    /// proves they differ; a matching one only makes it very likely they
+    /// don't, for checks where that's enough, e.g. a replay ending on the
+    /// board it was recorded on. This is the `board` of replay files, so it
+    /// can't change.
+    ///
+    /// # Example
+    /// ```
+    /// use game_of_life::conways::Grid;
+    ///
+    /// let mut grid = Grid::new(8, 8);
+    /// let empty = grid.content_hash();
+    /// grid.toggle(3, 4);
+    /// assert_ne!(grid.content_hash(), empty);
+    /// grid.toggle(3, 4);
+    /// assert_eq!(grid.content_hash(), empty);
+    /// ```
+    ///
+    /// see more: <http://www.isthe.com/chongo/tech/comp/fnv/>
+    pub fn content_hash(&self) -> u64 {
+        let mut hash = FNV_OFFSET;
+        self.content_bytes(|bytes| {
+            for &byte in bytes {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        });
+        hash
+    }
+
+    /// The SHA-256 of the grid's size and cells, the same bytes
+    /// `content_hash` hashes, for when a collision has to be out of the
+    /// question rather than only unlikely
+    #[cfg(feature = "digest")]
+    pub fn content_digest(&self) -> [u8; 32] {
+        let mut sha = Sha256::new();
+        self.content_bytes(|bytes| sha.update(bytes));
+        sha.finish()
+    }
+}
+
+/// Round constants, the first 32 bits of the fractional parts of the cube
+/// roots of the first 64 primes
+#[cfg(feature = "digest")]
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-256, fed in pieces; no crate for it is available to this build
+///
+/// see more: <https://csrc.nist.gov/pubs/fips/180-4/upd1/final>
+#[cfg(feature = "digest")]
+struct Sha256 {
+    state: [u32; 8],
+    block: [u8; 64],
+    /// Bytes of `block` filled so far
+    filled: usize,
+    /// Bytes fed in all
+    len: u64,
+}
+
+#[cfg(feature = "digest")]
+impl Sha256 {
+    fn new() -> Self {
+        Self {
+            state: [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+                0x5be0cd19,
+            ],
+            block: [0; 64],
+            filled: 0,
+            len: 0,
+        }
+    }
+
+    fn update(&mut self, mut bytes: &[u8]) {
+        self.len += bytes.len() as u64;
+        while !bytes.is_empty() {
+            let take = (64 - self.filled).min(bytes.len());
+            self.block[self.filled..self.filled + take].copy_from_slice(&bytes[..take]);
+            self.filled += take;
+            bytes = &bytes[take..];
+            if self.filled == 64 {
+                self.compress();
+                self.filled = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> [u8; 32] {
+        let bits = self.len.wrapping_mul(8);
+        // A 1 bit, zeros up to 8 bytes short of a block, then the length in bits
+        self.update(&[0x80]);
+        while self.filled != 56 {
+            self.update(&[0]);
+        }
+        self.update(&bits.to_be_bytes());
+        let mut digest = [0; 32];
+        for (chunk, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    fn compress(&mut self) {
+        let mut w = [0u32; 64];
+        for (word, chunk) in w.iter_mut().zip(self.block.chunks_exact(4)) {
+            *word = u32::from_be_bytes(chunk.try_into().expect("4 bytes"));
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for (k, w) in K.iter().zip(w) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(choice)
+                .wrapping_add(*k)
+                .wrapping_add(w);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(majority);
+            (h, g, f, e, d, c, b, a) = (g, f, e, d.wrapping_add(t1), c, b, a, t1.wrapping_add(t2));
+        }
+        for (word, add) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(add);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::rle;
+
+    fn glider() -> Grid {
+        Grid::centered(&rle::decode("x = 3, y = 3\nbo$2bo$3o!").unwrap(), 8, 8)
+    }
+
+    #[test]
+    fn test_content_hash_is_pinned() {
+        // Changing these breaks every replay file written so far
+        assert_eq!(Grid::new(0, 0).content_hash(), 0x8820_1fb9_60ff_6465);
+        assert_eq!(Grid::new(8, 8).content_hash(), 0xe672_005b_d96a_fa65);
+        assert_eq!(glider().content_hash(), 0xce46_1b1b_d363_642c);
+    }
+
+    #[test]
+    fn test_content_hash_leaves_the_metadata_out() {
+        let mut grid = glider();
+        let hash = grid.content_hash();
+        grid.set_generation(40);
+        grid.set_rule("B36/S23".parse().unwrap());
+        assert_eq!(grid.content_hash(), hash);
+        // Same cells in a different shape
+        assert_ne!(
+            Grid::new(4, 16).content_hash(),
+            Grid::new(8, 8).content_hash()
+        );
+        grid.toggle(0, 0);
+        assert_ne!(grid.content_hash(), hash);
+    }
+
+    #[cfg(feature = "digest")]
+    fn hex(digest: [u8; 32]) -> alloc::string::String {
+        digest
+            .iter()
+            .map(|byte| alloc::format!("{byte:02x}"))
+            .collect()
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn test_sha256_matches_the_standards_examples() {
+        let sha = |pieces: &[&[u8]]| {
+            let mut sha = Sha256::new();
+            for piece in pieces {
+                sha.update(piece);
+            }
+            hex(sha.finish())
+        };
+        assert_eq!(
+            sha(&[b"abc"]),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            sha(&[
+                b"abcdbcdecdefdefgefghfghighij",
+                b"hijkijkljklmklmnlmnomnopnopq"
+            ]),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(
+            sha(&[]),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn test_content_digest_is_pinned() {
+        assert_eq!(
+            hex(Grid::new(0, 0).content_digest()),
+            "374708fff7719dd5979ec875d56cd2286f6d3cf7ec317a3b25632aab28ec37bb"
+        );
+        assert_eq!(
+            hex(glider().content_digest()),
+            "965d50461a567c378fcc4fafb0e0a7b28d6b933340ca19611dee8770e31d09a6"
+        );
+    }
+}
@@ -0,0 +1,501 @@
+//! A JSON form of the grid, for tools that would rather not parse RLE
+//!
+//! ```json
+//! {
+//!   "width": 5,
+//!   "height": 4,
+//!   "rule": "B3/S23",
+//!   "generation": 1,
+//!   "live": [[2, 0], [0, 1], [2, 1]]
+//! }
+//! ```
+//!
+//! `live` lists the live cells as `[x, y]`, row by row, so a sparse board
+//! stays small. Reading, `rule` defaults to B3/S23, `generation` to 0, and
+//! keys this version doesn't know are skipped. The boundary, seed and which
+//! cells were born aren't kept, a snapshot keeps those.
+
+use super::{CellState, Grid};
+use crate::error::ConwayError;
+use crate::formats::MAX_PATTERN_CELLS;
+use crate::rule::Rule;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// Deepest nesting of arrays and objects read in keys this version skips
+const MAX_DEPTH: usize = 64;
+
+/// Reads JSON text in the shape `to_json` writes, reporting errors where in
+/// the text they are
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// A `ParseError` for the text at byte `at`, `msg` saying what's wrong
+    fn error_at(&self, at: usize, msg: impl Into<String>) -> ConwayError {
+        let before = &self.text[..at];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        ConwayError::ParseError {
+            format: "JSON",
+            line: before.matches('\n').count() + 1,
+            col: Some(before[line_start..].chars().count() + 1),
+            msg: msg.into(),
+        }
+    }
+
+    fn error(&self, msg: impl Into<String>) -> ConwayError {
+        self.error_at(self.pos, msg)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skip whitespace then `byte`, or fail saying `what` was expected
+    fn expect(&mut self, byte: u8, what: &str) -> Result<(), ConwayError> {
+        self.skip_whitespace();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {what}")))
+        }
+    }
+
+    /// Whether the next byte past whitespace is `byte`, skipping it if so
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.peek() == Some(byte);
+        self.pos += found as usize;
+        found
+    }
+
+    /// The elements of an array or the members of an object, `element`
+    /// reading each one up to its comma, with `close` ending the list
+    fn list(
+        &mut self,
+        close: u8,
+        mut element: impl FnMut(&mut Self) -> Result<(), ConwayError>,
+    ) -> Result<(), ConwayError> {
+        if self.eat(close) {
+            return Ok(());
+        }
+        loop {
+            element(self)?;
+            if self.eat(close) {
+                return Ok(());
+            }
+            let what = if close == b']' {
+                "',' or ']'"
+            } else {
+                "',' or '}'"
+            };
+            self.expect(b',', what)?;
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ConwayError> {
+        self.expect(b'"', "a string")?;
+        let mut out = String::new();
+        loop {
+            let rest = &self.text[self.pos..];
+            let Some(end) = rest.find(['"', '\\']) else {
+                return Err(self.error("unterminated string"));
+            };
+            if let Some(at) = rest[..end].find(|c: char| c < ' ') {
+                return Err(self.error_at(self.pos + at, "control character in a string"));
+            }
+            out.push_str(&rest[..end]);
+            self.pos += end + 1;
+            if rest.as_bytes()[end] == b'"' {
+                return Ok(out);
+            }
+            let escape = self.pos - 1;
+            let unescaped = match self.peek() {
+                Some(b'"') => '"',
+                Some(b'\\') => '\\',
+                Some(b'/') => '/',
+                Some(b'b') => '\u{8}',
+                Some(b'f') => '\u{c}',
+                Some(b'n') => '\n',
+                Some(b'r') => '\r',
+                Some(b't') => '\t',
+                Some(b'u') => {
+                    self.pos += 1;
+                    let mut code = self.hex4(escape)?;
+                    // A surrogate pair spells one character in two escapes
+                    if (0xd800..0xdc00).contains(&code) && self.text[self.pos..].starts_with("\\u")
+                    {
+                        self.pos += 2;
+                        let low = self.hex4(escape)?;
+                        if (0xdc00..0xe000).contains(&low) {
+                            code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                        }
+                    }
+                    out.push(
+                        char::from_u32(code)
+                            .ok_or_else(|| self.error_at(escape, "invalid \\u escape"))?,
+                    );
+                    continue;
+                }
+                _ => return Err(self.error_at(escape, "invalid escape")),
+            };
+            out.push(unescaped);
+            self.pos += 1;
+        }
+    }
+
+    /// The 4 hex digits of a `\u` escape starting at byte `escape`
+    fn hex4(&mut self, escape: usize) -> Result<u32, ConwayError> {
+        let digits = self.text.get(self.pos..self.pos + 4);
+        match digits.filter(|d| d.bytes().all(|b| b.is_ascii_hexdigit())) {
+            Some(digits) => {
+                self.pos += 4;
+                Ok(u32::from_str_radix(digits, 16).unwrap_or_default())
+            }
+            None => Err(self.error_at(escape, "invalid \\u escape")),
+        }
+    }
+
+    /// The text of a number, as JSON spells them
+    fn number(&mut self) -> Result<&'a str, ConwayError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let digits = |parser: &mut Self| {
+            let from = parser.pos;
+            while parser.peek().is_some_and(|b| b.is_ascii_digit()) {
+                parser.pos += 1;
+            }
+            parser.pos > from
+        };
+        self.eat(b'-');
+        let leading_zero = self.peek() == Some(b'0');
+        let int_start = self.pos;
+        let mut valid = digits(self) && !(leading_zero && self.pos - int_start > 1);
+        if valid && self.peek() == Some(b'.') {
+            self.pos += 1;
+            valid = digits(self);
+        }
+        if valid && matches!(self.peek(), Some(b'e' | b'E')) {
+            self.pos += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            valid = digits(self);
+        }
+        if valid {
+            Ok(&self.text[start..self.pos])
+        } else {
+            Err(self.error_at(start, "invalid number"))
+        }
+    }
+
+    /// A number that has to be a whole one from 0 up, which `what` names
+    fn whole(&mut self, what: &str) -> Result<u64, ConwayError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        if !matches!(self.peek(), Some(b'-' | b'0'..=b'9')) {
+            return Err(self.error(format!("expected {what}")));
+        }
+        self.number()?
+            .parse()
+            .map_err(|_| self.error_at(start, format!("{what} must be a whole number from 0 up")))
+    }
+
+    /// Skip any value, nested no deeper than `MAX_DEPTH`
+    fn skip_value(&mut self, depth: usize) -> Result<(), ConwayError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                self.list(b'}', |parser| {
+                    parser.string()?;
+                    parser.expect(b':', "':'")?;
+                    parser.skip_value(depth + 1)
+                })
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                self.list(b']', |parser| parser.skip_value(depth + 1))
+            }
+            Some(b'"') => self.string().map(drop),
+            Some(b'-' | b'0'..=b'9') => self.number().map(drop),
+            _ => {
+                for word in ["true", "false", "null"] {
+                    if self.text[self.pos..].starts_with(word) {
+                        self.pos += word.len();
+                        return Ok(());
+                    }
+                }
+                Err(self.error("expected a value"))
+            }
+        }
+    }
+}
+
+impl Grid {
+    /// The grid as JSON, see the [module docs](self)
+    ///
+    /// # Example
+    /// ```
+    /// use game_of_life::conways::{CellState, Grid};
+    ///
+    /// let mut grid = Grid::new(3, 2);
+    /// grid.set(1, 0, CellState::Alive);
+    /// let json = grid.to_json();
+    /// assert!(json.contains(r#""live": [[1, 0]]"#));
+    /// assert_eq!(Grid::from_json(&json).unwrap(), grid);
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut live = String::new();
+        for (y, row) in self.grid.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if *cell == CellState::Alive {
+                    let comma = if live.is_empty() { "" } else { ", " };
+                    let _ = write!(live, "{comma}[{x}, {y}]");
+                }
+            }
+        }
+        format!(
+            "{{\n  \"width\": {},\n  \"height\": {},\n  \"rule\": \"{}\",\n  \"generation\": {},\n  \"live\": [{live}]\n}}\n",
+            self.width, self.height, self.rule, self.generation
+        )
+    }
+
+    /// Read a grid from JSON in the shape `to_json` writes
+    ///
+    /// # Returns
+    /// The grid, an `InvalidRule` error for a bad rulestring, or a
+    /// `ParseError` saying where the text isn't JSON, lacks `width`, `height`
+    /// or `live`, has more than `MAX_PATTERN_CELLS` cells, or lists a cell
+    /// twice or off the grid
+    pub fn from_json(text: &str) -> Result<Grid, ConwayError> {
+        let mut parser = Parser { text, pos: 0 };
+        let (mut width, mut height, mut rule, mut generation) = (None, None, None, None);
+        // Cells with where they are in the text, checked once the size is known
+        let mut live: Option<Vec<(u64, u64, usize)>> = None;
+        let mut keys = Vec::new();
+
+        parser.expect(b'{', "'{'")?;
+        parser.list(b'}', |parser| {
+            parser.skip_whitespace();
+            let at = parser.pos;
+            let key = parser.string()?;
+            if keys.contains(&key) {
+                return Err(parser.error_at(at, format!("\"{key}\" appears twice")));
+            }
+            parser.expect(b':', "':'")?;
+            match key.as_str() {
+                "width" => width = Some(parser.whole("the width")?),
+                "height" => height = Some(parser.whole("the height")?),
+                "generation" => generation = Some(parser.whole("the generation")?),
+                "rule" => {
+                    rule = Some(parser.string()?.parse::<Rule>()?);
+                }
+                "live" => {
+                    let mut cells = Vec::new();
+                    parser.expect(b'[', "a list of cells")?;
+                    parser.list(b']', |parser| {
+                        parser.skip_whitespace();
+                        let at = parser.pos;
+                        parser.expect(b'[', "a cell, [x, y]")?;
+                        let x = parser.whole("x")?;
+                        parser.expect(b',', "','")?;
+                        let y = parser.whole("y")?;
+                        parser.expect(b']', "']' after a cell's y")?;
+                        cells.push((x, y, at));
+                        Ok(())
+                    })?;
+                    live = Some(cells);
+                }
+                _ => parser.skip_value(0)?,
+            }
+            keys.push(key);
+            Ok(())
+        })?;
+        parser.skip_whitespace();
+        if parser.pos < text.len() {
+            return Err(parser.error("text after the grid"));
+        }
+
+        let missing = |key: &str| parser.error_at(0, format!("\"{key}\" is missing"));
+        let size = |side: u64| usize::try_from(side).unwrap_or(usize::MAX);
+        let width = size(width.ok_or_else(|| missing("width"))?);
+        let height = size(height.ok_or_else(|| missing("height"))?);
+        let live = live.ok_or_else(|| missing("live"))?;
+        match width.max(1).checked_mul(height.max(1)) {
+            Some(cells) if cells <= MAX_PATTERN_CELLS => (),
+            _ => {
+                return Err(parser.error_at(
+                    0,
+                    format!("a {width}x{height} grid is larger than {MAX_PATTERN_CELLS} cells"),
+                ))
+            }
+        }
+
+        let mut grid = Grid::new(width, height);
+        if let Some(rule) = rule {
+            grid.set_rule(rule);
+        }
+        grid.generation = generation.unwrap_or(0);
+        for (x, y, at) in live {
+            let (x, y) = (size(x), size(y));
+            if x >= width || y >= height {
+                return Err(parser.error_at(
+                    at,
+                    format!("cell {x},{y} is outside the {width}x{height} grid"),
+                ));
+            }
+            if grid.grid[y][x] == CellState::Alive {
+                return Err(parser.error_at(at, format!("cell {x},{y} is listed twice")));
+            }
+            grid.grid[y][x] = CellState::Alive;
+        }
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::BoundaryCondition;
+    use alloc::string::ToString;
+
+    /// The exact text `to_json` writes for `glider()`, frozen so the schema
+    /// can't drift
+    const GLIDER: &str = include_str!("../../tests/fixtures/glider.json");
+
+    fn glider() -> Grid {
+        let mut grid = Grid::new(5, 4);
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            grid.set(x, y, CellState::Alive);
+        }
+        grid.set_rule("B36/S23".parse().unwrap());
+        grid.next_cell_generation();
+        grid
+    }
+
+    /// Where and why `text` was refused
+    fn error(text: &str) -> (usize, Option<usize>, String) {
+        match Grid::from_json(text) {
+            Err(ConwayError::ParseError { line, col, msg, .. }) => (line, col, msg),
+            other => panic!("not a parse error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_the_schema_is_frozen() {
+        assert_eq!(glider().to_json(), GLIDER);
+        let read = Grid::from_json(GLIDER).unwrap();
+        assert_eq!(read, glider());
+        assert_eq!(read.generation(), 1);
+        assert_eq!(read.rule().to_string(), "B36/S23");
+    }
+
+    #[test]
+    fn test_grids_round_trip() {
+        for (seed, (width, height)) in [(1, (300, 200)), (2, (1, 40)), (3, (17, 1))] {
+            let mut grid = Grid::new(width, height);
+            grid.randomize(0.3, seed);
+            grid.set_rule(Rule::presets()[seed as usize]);
+            grid.step_n(3);
+            let read = Grid::from_json(&grid.to_json()).unwrap();
+            assert_eq!(read, grid);
+            assert_eq!((read.generation(), read.rule()), (3, grid.rule()));
+        }
+        let empty = Grid::new(0, 0);
+        assert_eq!(Grid::from_json(&empty.to_json()).unwrap(), empty);
+    }
+
+    #[test]
+    fn test_any_json_spelling_reads() {
+        let text = "\t{\"live\":[ [ 0,1 ] ,[2,0]],\"future\":{\"a\":[1.5e-3,true,null,\"\\u00e9\\\"\"]},\r\n\"height\":2,\"width\":3}\n";
+        let read = Grid::from_json(text).unwrap();
+        let mut expected = Grid::new(3, 2);
+        expected.set(0, 1, CellState::Alive);
+        expected.set(2, 0, CellState::Alive);
+        assert_eq!(read, expected);
+        assert_eq!((read.generation(), read.rule()), (0, Rule::conway()));
+        assert_eq!(read.boundary(), BoundaryCondition::Dead);
+    }
+
+    #[test]
+    fn test_bad_cells_are_refused_where_they_are() {
+        let text = "{\"width\": 3, \"height\": 2,\n \"live\": [[0, 0], [3, 1]]}";
+        assert_eq!(
+            error(text),
+            (2, Some(19), "cell 3,1 is outside the 3x2 grid".to_string())
+        );
+        let text = "{\"width\": 3, \"height\": 2, \"live\": [[1, 1], [0, 0], [1, 1]]}";
+        assert_eq!(
+            error(text),
+            (1, Some(52), "cell 1,1 is listed twice".to_string())
+        );
+        let text = "{\"width\": 3, \"height\": 2, \"live\": [[1, -1]]}";
+        assert_eq!(error(text).2, "y must be a whole number from 0 up");
+        let text = "{\"width\": 3, \"height\": 2, \"live\": [[1, 1, 1]]}";
+        assert_eq!(error(text).2, "expected ']' after a cell's y");
+    }
+
+    #[test]
+    fn test_malformed_json_is_refused() {
+        let message = |text: &str| error(text).2;
+        assert_eq!(message(""), "expected '{'");
+        assert_eq!(
+            message("{\"width\": 3, \"height\": 3}"),
+            "\"live\" is missing"
+        );
+        assert_eq!(
+            message("{\"live\": [], \"height\": 3}"),
+            "\"width\" is missing"
+        );
+        assert_eq!(
+            message("{\"width\": 1, \"width\": 2}"),
+            "\"width\" appears twice"
+        );
+        assert_eq!(
+            message("{\"width\": 2.5}"),
+            "the width must be a whole number from 0 up"
+        );
+        assert_eq!(message("{\"width\": 01}"), "invalid number");
+        assert_eq!(
+            message("{\"width\": 1 \"height\": 1}"),
+            "expected ',' or '}'"
+        );
+        assert_eq!(
+            message("{\"live\": [], \"width\": 1, \"height\": 1} x"),
+            "text after the grid"
+        );
+        assert_eq!(message("{\"rule\": \"B3"), "unterminated string");
+        assert_eq!(message("{\"x\": \"\\q\"}"), "invalid escape");
+        assert_eq!(message("{\"x\": nil}"), "expected a value");
+        let deep = format!("{{\"x\": {}{}}}", "[".repeat(100), "]".repeat(100));
+        assert_eq!(message(&deep), "nested too deeply");
+        assert!(
+            message("{\"width\": 99999, \"height\": 99999, \"live\": []}").contains("larger than")
+        );
+        assert!(matches!(
+            Grid::from_json("{\"rule\": \"B9/S23\"}"),
+            Err(ConwayError::InvalidRule(_))
+        ));
+        // Cut anywhere, refused rather than a panic
+        for cut in 0..GLIDER.len() {
+            let _ = Grid::from_json(&GLIDER[..cut]);
+        }
+        assert_eq!(error("{\n  \"width\": x").0, 2);
+        assert_eq!(error("{\"é\": x}").1, Some(7));
+    }
+}
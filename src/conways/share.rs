@@ -0,0 +1,230 @@
+//! Patterns as one pasteable token, e.g. for a chat message
+//!
+//! A share string is `life1:` and then, in URL-safe base64 without padding,
+//! a snapshot of the pattern cropped to its live cells, without sections,
+//! followed by its FNV-1a checksum as 4 little-endian bytes. The checksum
+//! catches a string mangled on the way, which would otherwise often still
+//! read as some other pattern.
+
+use super::Grid;
+use crate::error::ConwayError;
+
+/// What every share string starts with, naming its version
+pub const SHARE_PREFIX: &str = "life1:";
+
+/// Longest share string read, refused before decoding anything
+pub const MAX_SHARE_LEN: usize = 1 << 20;
+
+/// The URL-safe base64 alphabet, the 64 digits in order
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+fn invalid(msg: impl Into<String>) -> ConwayError {
+    ConwayError::ParseError {
+        format: "share string",
+        line: 0,
+        col: None,
+        msg: msg.into(),
+    }
+}
+
+/// The 32-bit FNV-1a hash of `bytes`
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let word = chunk.iter().enumerate().fold(0u32, |word, (i, byte)| {
+            word | (*byte as u32) << (16 - 8 * i)
+        });
+        // 3 bytes make 4 digits, and the 1 or 2 at the end 2 or 3
+        for i in 0..=chunk.len() {
+            text.push(ALPHABET[(word >> (18 - 6 * i)) as usize & 63] as char);
+        }
+    }
+    text
+}
+
+/// The bytes of unpadded URL-safe base64, or `None` when `text` isn't that,
+/// including when the bits of its last digit past the last byte aren't 0, so
+/// no two strings read as the same bytes
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 4 == 1 {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3 + 2);
+    for chunk in text.as_bytes().chunks(4) {
+        let mut word = 0u32;
+        for (i, digit) in chunk.iter().enumerate() {
+            let value = ALPHABET.iter().position(|d| d == digit)? as u32;
+            word |= value << (18 - 6 * i);
+        }
+        if word & ((1 << (32 - 8 * chunk.len())) - 1) != 0 {
+            return None;
+        }
+        bytes.extend_from_slice(&word.to_be_bytes()[1..chunk.len()]);
+    }
+    Some(bytes)
+}
+
+impl Grid {
+    /// The grid's live cells as a share string, see the [module docs](self)
+    ///
+    /// The string keeps the rule, and nothing else of the grid but its
+    /// cells: `from_share_string` gives a grid of the live cells' bounding
+    /// box at generation 0.
+    ///
+    /// # Example
+    /// ```
+    /// use game_of_life::conways::{CellState, Grid};
+    ///
+    /// let mut grid = Grid::new(50, 50);
+    /// for (x, y) in [(21, 20), (22, 21), (20, 22), (21, 22), (22, 22)] {
+    ///     grid.set(x, y, CellState::Alive);
+    /// }
+    /// let shared = grid.to_share_string();
+    /// assert!(shared.starts_with("life1:"));
+    /// let glider = Grid::from_share_string(&shared).unwrap();
+    /// assert_eq!((glider.width(), glider.height(), glider.population()), (3, 3, 5));
+    /// ```
+    pub fn to_share_string(&self) -> String {
+        let mut cropped = match self.live_bounds() {
+            Some(bounds) => self.extract_region(bounds),
+            None => Grid::new(0, 0),
+        };
+        cropped.rule = self.rule;
+        let mut bytes = Vec::new();
+        cropped
+            .write_snapshot_cells(&mut bytes)
+            .expect("writing to memory can't fail");
+        bytes.extend_from_slice(&checksum(&bytes).to_le_bytes());
+        format!("{SHARE_PREFIX}{}", encode_base64(&bytes))
+    }
+
+    /// Read a grid from a share string, ignoring whitespace around it
+    ///
+    /// # Returns
+    /// The grid, or a `ParseError` when `text` isn't a share string, is
+    /// longer than `MAX_SHARE_LEN`, or was changed since it was made
+    pub fn from_share_string(text: &str) -> Result<Grid, ConwayError> {
+        let text = text.trim();
+        if text.len() > MAX_SHARE_LEN {
+            return Err(invalid(format!("longer than {MAX_SHARE_LEN} characters")));
+        }
+        let Some(digits) = text.strip_prefix(SHARE_PREFIX) else {
+            return Err(invalid(format!("doesn't start with {SHARE_PREFIX}")));
+        };
+        let bytes = decode_base64(digits).ok_or_else(|| invalid("not URL-safe base64"))?;
+        let Some((payload, sum)) = bytes.split_last_chunk::<4>() else {
+            return Err(invalid("too short"));
+        };
+        if checksum(payload) != u32::from_le_bytes(*sum) {
+            return Err(invalid(
+                "the checksum doesn't match, the string was changed",
+            ));
+        }
+        Grid::read_snapshot(payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::{CellState, InsertMode};
+    use crate::patterns;
+    use crate::rule::Rule;
+
+    fn message(text: &str) -> String {
+        match Grid::from_share_string(text) {
+            Err(ConwayError::ParseError { msg, .. }) => msg,
+            other => panic!("not a parse error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_base64_round_trips() {
+        for len in 0..10 {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 97 + 250) as u8).collect();
+            assert_eq!(decode_base64(&encode_base64(&bytes)), Some(bytes));
+        }
+        // The examples of RFC 4648, without their padding
+        assert_eq!(encode_base64(b"foob"), "Zm9vYg");
+        assert_eq!(encode_base64(b"fooba"), "Zm9vYmE");
+        assert_eq!(encode_base64(&[0xfb, 0xff]), "-_8");
+        assert_eq!(decode_base64("Zm9vYmFy"), Some(b"foobar".to_vec()));
+        assert_eq!(decode_base64("Zm9vY"), None);
+        assert_eq!(decode_base64("Zm9vYh"), None);
+        assert_eq!(decode_base64("Zm9v+g"), None);
+    }
+
+    #[test]
+    fn test_patterns_round_trip() {
+        for pattern in patterns::LIBRARY {
+            let mut grid = Grid::new(
+                pattern.to_grid().width() + 7,
+                pattern.to_grid().height() + 4,
+            );
+            grid.insert_pattern(&pattern.to_grid(), 3, 2, InsertMode::Overwrite);
+            let read = Grid::from_share_string(&grid.to_share_string()).unwrap();
+            let bounds = grid.live_bounds().unwrap();
+            assert_eq!(read, grid.extract_region(bounds), "{}", pattern.slug());
+        }
+
+        let mut soup = Grid::new(120, 90);
+        soup.randomize(0.3, 5);
+        soup.set_rule("B36/S23".parse().unwrap());
+        soup.step_n(4);
+        let shared = soup.to_share_string();
+        assert!(!shared.contains(['+', '/', '=']));
+        let read = Grid::from_share_string(&format!("  {shared}\n")).unwrap();
+        assert_eq!(read.population(), soup.population());
+        assert_eq!(
+            (read.generation(), read.rule().to_string()),
+            (0, "B36/S23".into())
+        );
+
+        let empty = Grid::from_share_string(&Grid::new(9, 9).to_share_string()).unwrap();
+        assert_eq!(
+            (empty.width(), empty.height(), empty.rule()),
+            (0, 0, Rule::conway())
+        );
+    }
+
+    #[test]
+    fn test_tampered_strings_are_refused() {
+        let mut grid = Grid::new(3, 3);
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            grid.set(x, y, CellState::Alive);
+        }
+        let shared = grid.to_share_string();
+        for i in SHARE_PREFIX.len()..shared.len() {
+            let mut tampered = shared.clone().into_bytes();
+            tampered[i] = if tampered[i] == b'A' { b'B' } else { b'A' };
+            let tampered = String::from_utf8(tampered).unwrap();
+            assert!(
+                Grid::from_share_string(&tampered).is_err(),
+                "changed at {i}"
+            );
+        }
+        for cut in SHARE_PREFIX.len()..shared.len() {
+            assert!(
+                Grid::from_share_string(&shared[..cut]).is_err(),
+                "cut at {cut}"
+            );
+        }
+        assert_eq!(message("life2:AAAA"), "doesn't start with life1:");
+        assert_eq!(message("life1:AA!A"), "not URL-safe base64");
+        assert_eq!(message("life1:AAA"), "too short");
+        assert_eq!(
+            message("life1:AAAAAAAA"),
+            "the checksum doesn't match, the string was changed"
+        );
+        assert_eq!(
+            message(&format!("life1:{}", "A".repeat(MAX_SHARE_LEN))),
+            format!("longer than {MAX_SHARE_LEN} characters")
+        );
+    }
+}
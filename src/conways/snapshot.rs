@@ -0,0 +1,372 @@
+//! A binary format for whole grids, quick to write and read however big the
+//! grid, keeping what RLE leaves out: the generation, boundary and seed
+//!
+//! Version 1, every number little-endian:
+//!
+//! | Bytes | Field |
+//! |-------|-------|
+//! | 8 | Magic, `LIFESNAP` |
+//! | 2 | Version, `u16` |
+//! | 8, 8 | Width and height, `u64` |
+//! | 8 | Generation, `u64` |
+//! | 1 | Boundary, 0 for dead cells and 1 for wrapping |
+//! | 1, 8 | 1 and the seed, or 0 and 8 zero bytes for a grid without one |
+//! | 1, n | Length of the rulestring then the rulestring, e.g. `B3/S23` |
+//! | ⌈w·h/8⌉ | Cells row by row, a bit each, lowest bit first, 1 for alive |
+//!
+//! Sections follow until the end of the file, each a 4-byte tag, a `u64`
+//! length and that many bytes. Readers skip tags they don't know, so later
+//! versions can add sections without breaking older readers; version 1 has
+//! `BORN`, whether each cell was born of a generation, packed like the cells.
+//! A custom ruleset isn't written, only the B/S rule the grid last had.
+
+use super::{BoundaryCondition, CellState, Grid};
+use crate::error::ConwayError;
+use crate::formats::MAX_PATTERN_CELLS;
+use std::io::{self, Read, Write};
+
+/// First bytes of every snapshot
+pub const SNAPSHOT_MAGIC: &[u8; 8] = b"LIFESNAP";
+
+/// The version written, and the newest one read
+pub const SNAPSHOT_VERSION: u16 = 1;
+
+/// Tag of the section holding which cells were born
+const BORN_TAG: &[u8; 4] = b"BORN";
+
+/// A malformed snapshot, `msg` saying how
+fn invalid(msg: impl Into<String>) -> ConwayError {
+    ConwayError::ParseError {
+        format: "snapshot",
+        line: 0,
+        col: None,
+        msg: msg.into(),
+    }
+}
+
+/// An I/O error, a snapshot ending too soon being malformed rather than an
+/// I/O failure
+fn read_error(err: io::Error) -> ConwayError {
+    if err.kind() == io::ErrorKind::UnexpectedEof {
+        invalid("truncated")
+    } else {
+        ConwayError::Io(err)
+    }
+}
+
+/// `bits` packed 8 to a byte, the first in the lowest bit
+fn pack(bits: impl Iterator<Item = bool>, count: usize) -> Vec<u8> {
+    let mut bytes = vec![0; count.div_ceil(8)];
+    for (i, bit) in bits.enumerate() {
+        bytes[i / 8] |= (bit as u8) << (i % 8);
+    }
+    bytes
+}
+
+/// Bit `i` of bytes packed by `pack`
+fn bit(bytes: &[u8], i: usize) -> bool {
+    bytes[i / 8] & (1 << (i % 8)) != 0
+}
+
+fn read_array<const N: usize>(input: &mut impl Read) -> Result<[u8; N], ConwayError> {
+    let mut bytes = [0; N];
+    input.read_exact(&mut bytes).map_err(read_error)?;
+    Ok(bytes)
+}
+
+fn read_u64(input: &mut impl Read) -> Result<u64, ConwayError> {
+    read_array(input).map(u64::from_le_bytes)
+}
+
+fn read_vec(input: &mut impl Read, len: usize) -> Result<Vec<u8>, ConwayError> {
+    let mut bytes = vec![0; len];
+    input.read_exact(&mut bytes).map_err(read_error)?;
+    Ok(bytes)
+}
+
+/// The tag of the next section, or `None` at the end of the snapshot
+fn read_tag(input: &mut impl Read) -> Result<Option<[u8; 4]>, ConwayError> {
+    let mut tag = [0; 4];
+    let mut filled = 0;
+    while filled < tag.len() {
+        match input.read(&mut tag[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => return Err(invalid("truncated")),
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+            Err(err) => return Err(read_error(err)),
+        }
+    }
+    Ok(Some(tag))
+}
+
+impl Grid {
+    /// Write the grid to `out` as a binary snapshot, see the [module docs](self)
+    ///
+    /// Cells go out in a few large writes, so there's no need to buffer `out`.
+    ///
+    /// # Example
+    /// ```
+    /// use game_of_life::conways::Grid;
+    ///
+    /// let mut grid = Grid::new(300, 200);
+    /// grid.randomize(0.3, 7);
+    /// grid.next_cell_generation();
+    /// let mut bytes = Vec::new();
+    /// grid.write_snapshot(&mut bytes).unwrap();
+    ///
+    /// let read = Grid::read_snapshot(bytes.as_slice()).unwrap();
+    /// assert_eq!(read, grid);
+    /// assert_eq!((read.generation(), read.seed()), (1, Some(7)));
+    /// ```
+    pub fn write_snapshot(&self, mut out: impl Write) -> Result<(), ConwayError> {
+        self.write_snapshot_cells(&mut out)?;
+        let born = pack(self.born.iter().copied(), self.width * self.height);
+        out.write_all(BORN_TAG)?;
+        out.write_all(&(born.len() as u64).to_le_bytes())?;
+        out.write_all(&born)?;
+        Ok(())
+    }
+
+    /// The header and cells of `write_snapshot`, a snapshot without sections
+    pub(super) fn write_snapshot_cells(&self, out: &mut impl Write) -> Result<(), ConwayError> {
+        let rule = self.rule.to_string();
+        let mut header = Vec::with_capacity(64);
+        header.extend_from_slice(SNAPSHOT_MAGIC);
+        header.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        header.extend_from_slice(&(self.width as u64).to_le_bytes());
+        header.extend_from_slice(&(self.height as u64).to_le_bytes());
+        header.extend_from_slice(&self.generation.to_le_bytes());
+        header.push(match self.boundary {
+            BoundaryCondition::Dead => 0,
+            BoundaryCondition::Wrap => 1,
+        });
+        header.push(self.seed.is_some() as u8);
+        header.extend_from_slice(&self.seed.unwrap_or(0).to_le_bytes());
+        header.push(rule.len() as u8);
+        header.extend_from_slice(rule.as_bytes());
+        out.write_all(&header)?;
+
+        let count = self.width * self.height;
+        let alive = self.grid.iter().flatten().map(|c| *c == CellState::Alive);
+        out.write_all(&pack(alive, count))?;
+        Ok(())
+    }
+
+    /// Read a grid written by `write_snapshot`
+    ///
+    /// # Returns
+    /// The grid, an `Io` error when `input` fails, or a `ParseError` when it
+    /// isn't a snapshot, comes from a newer version, is cut short, or says
+    /// the grid has more than `MAX_PATTERN_CELLS` cells
+    pub fn read_snapshot(mut input: impl Read) -> Result<Grid, ConwayError> {
+        let input = &mut input;
+        if read_array::<8>(input)? != *SNAPSHOT_MAGIC {
+            return Err(invalid("not a snapshot"));
+        }
+        match u16::from_le_bytes(read_array(input)?) {
+            0 => return Err(invalid("version 0 doesn't exist")),
+            version if version > SNAPSHOT_VERSION => {
+                return Err(invalid(format!(
+                    "version {version} is newer than this build reads, {SNAPSHOT_VERSION}"
+                )))
+            }
+            _ => (),
+        }
+        let (width, height) = (read_u64(input)?, read_u64(input)?);
+        let size = |side: u64| usize::try_from(side).unwrap_or(usize::MAX);
+        let (width, height) = (size(width), size(height));
+        let count = match width.max(1).checked_mul(height.max(1)) {
+            Some(cells) if cells <= MAX_PATTERN_CELLS => width * height,
+            _ => {
+                return Err(invalid(format!(
+                    "a {width}x{height} grid is larger than {MAX_PATTERN_CELLS} cells"
+                )))
+            }
+        };
+        let generation = read_u64(input)?;
+        let boundary = match read_array::<1>(input)? {
+            [0] => BoundaryCondition::Dead,
+            [1] => BoundaryCondition::Wrap,
+            [other] => return Err(invalid(format!("unknown boundary {other}"))),
+        };
+        let has_seed = read_array::<1>(input)?[0] != 0;
+        let seed = read_u64(input)?;
+        let rule_len = read_array::<1>(input)?[0] as usize;
+        let rule = read_vec(input, rule_len)?;
+        let rule = core::str::from_utf8(&rule)
+            .map_err(|_| invalid("the rulestring isn't UTF-8"))?
+            .parse()?;
+        let cells = read_vec(input, count.div_ceil(8))?;
+
+        let mut grid = Grid::new(width, height);
+        for (y, row) in grid.grid.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                if bit(&cells, y * width + x) {
+                    *cell = CellState::Alive;
+                }
+            }
+        }
+        grid.generation = generation;
+        grid.boundary = boundary;
+        grid.seed = has_seed.then_some(seed);
+        grid.rule = rule;
+
+        while let Some(tag) = read_tag(input)? {
+            let len = read_u64(input)?;
+            if tag == *BORN_TAG {
+                if len != count.div_ceil(8) as u64 {
+                    return Err(invalid(format!("a BORN section of {len} bytes")));
+                }
+                let born = read_vec(input, count.div_ceil(8))?;
+                for (i, flag) in grid.born.iter_mut().enumerate() {
+                    *flag = bit(&born, i);
+                }
+            } else {
+                // A section of a later version, which this one does without
+                let skipped = io::copy(&mut input.take(len), &mut io::sink())?;
+                if skipped < len {
+                    return Err(invalid("truncated"));
+                }
+            }
+        }
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rule::Rule;
+
+    /// Every byte of a version 1 snapshot, frozen so the format can't drift
+    const GLIDER_V1: &[u8] = include_bytes!("../../tests/fixtures/glider-v1.lifesnap");
+
+    fn glider() -> Grid {
+        let mut grid = Grid::new(5, 4);
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            grid.set(x, y, CellState::Alive);
+        }
+        grid.set_boundary(BoundaryCondition::Wrap);
+        grid.set_rule("B36/S23".parse().unwrap());
+        grid.next_cell_generation();
+        grid
+    }
+
+    fn bytes(grid: &Grid) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        grid.write_snapshot(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_version_1_is_frozen() {
+        assert_eq!(bytes(&glider()), GLIDER_V1);
+        let read = Grid::read_snapshot(GLIDER_V1).unwrap();
+        assert_eq!(read, glider());
+        assert_eq!(read.generation(), 1);
+        assert_eq!(read.boundary(), BoundaryCondition::Wrap);
+        assert_eq!(read.rule().to_string(), "B36/S23");
+        assert_eq!(read.seed(), None);
+        // The cells born of the generation stay born
+        for y in 0..4 {
+            for x in 0..5 {
+                assert_eq!(read.is_born(x, y), glider().is_born(x, y));
+            }
+        }
+        assert!(read.is_born(0, 1));
+    }
+
+    #[test]
+    fn test_large_random_grids_round_trip() {
+        for (seed, (width, height)) in [(1, (1000, 1000)), (2, (4096, 3)), (3, (7, 9))] {
+            let mut grid = Grid::new(width, height);
+            grid.randomize(0.37, seed);
+            grid.set_rule(Rule::presets()[seed as usize]);
+            grid.step_n(2);
+            let read = Grid::read_snapshot(bytes(&grid).as_slice()).unwrap();
+            assert_eq!(read, grid);
+            assert_eq!(read.generation(), 2);
+            assert_eq!(read.seed(), Some(seed));
+            assert_eq!(read.rule(), grid.rule());
+        }
+        let empty = Grid::new(0, 0);
+        assert_eq!(
+            Grid::read_snapshot(bytes(&empty).as_slice()).unwrap(),
+            empty
+        );
+    }
+
+    #[test]
+    fn test_unknown_sections_are_skipped() {
+        let mut bytes = GLIDER_V1.to_vec();
+        bytes.extend_from_slice(b"NEWS");
+        bytes.extend_from_slice(&3u64.to_le_bytes());
+        bytes.extend_from_slice(b"abc");
+        assert_eq!(Grid::read_snapshot(bytes.as_slice()).unwrap(), glider());
+    }
+
+    #[test]
+    fn test_malformed_snapshots_are_refused() {
+        let message = |bytes: &[u8]| match Grid::read_snapshot(bytes) {
+            Err(ConwayError::ParseError { msg, .. }) => msg,
+            other => panic!("not a parse error: {other:?}"),
+        };
+        assert_eq!(message(b"x = 3, y = 3\nbo$2bo$3o!"), "not a snapshot");
+        assert_eq!(message(b""), "truncated");
+
+        let mut future = GLIDER_V1.to_vec();
+        future[8] = 2;
+        assert_eq!(
+            message(&future),
+            "version 2 is newer than this build reads, 1"
+        );
+        future[8] = 0;
+        assert_eq!(message(&future), "version 0 doesn't exist");
+
+        // Cut anywhere but between the cells and the sections, where a
+        // snapshot may end
+        let sections = GLIDER_V1.len() - (4 + 8 + 3);
+        for len in 0..GLIDER_V1.len() {
+            let cut = &GLIDER_V1[..len];
+            if len == sections {
+                let read = Grid::read_snapshot(cut).unwrap();
+                assert_eq!(read, glider());
+                assert!(!read.is_born(0, 1));
+            } else {
+                assert_eq!(message(cut), "truncated", "cut at {len}");
+            }
+        }
+        let mut skipped = GLIDER_V1.to_vec();
+        skipped.extend_from_slice(b"NEWS");
+        skipped.extend_from_slice(&30u64.to_le_bytes());
+        assert_eq!(message(&skipped), "truncated");
+
+        let mut huge = GLIDER_V1.to_vec();
+        huge[10..18].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(message(&huge).contains("larger than"));
+        let mut boundary = GLIDER_V1.to_vec();
+        boundary[34] = 7;
+        assert_eq!(message(&boundary), "unknown boundary 7");
+        let mut rule = GLIDER_V1.to_vec();
+        rule[46] = b'9';
+        assert!(matches!(
+            Grid::read_snapshot(rule.as_slice()),
+            Err(ConwayError::InvalidRule(_))
+        ));
+    }
+
+    #[test]
+    fn test_failing_readers_are_io_errors() {
+        struct Failing;
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk on fire"))
+            }
+        }
+        assert!(matches!(
+            Grid::read_snapshot(Failing),
+            Err(ConwayError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,244 @@
+//! The grid as an SVG picture, for papers and blog posts
+//!
+//! Every run of live cells in a row is one `rect`, so the file stays small
+//! on boards full of still lifes and spaceships alike.
+
+use super::{CellState, Grid};
+use alloc::format;
+use alloc::string::String;
+use core::fmt::Write;
+
+/// Stroke width of grid lines, as a fraction of a cell
+const GRID_LINE_WIDTH: f32 = 0.05;
+
+/// Colors of `Grid::to_svg`, each any color SVG understands, e.g. `#1e293b`
+/// or `teal`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgStyle {
+    pub alive: String,
+    /// Fill of the whole picture, or `None` to leave dead cells transparent
+    pub background: Option<String>,
+    /// Lines between the cells, or `None` for none
+    pub grid_lines: Option<String>,
+}
+
+/// Black cells on white, without grid lines
+impl Default for SvgStyle {
+    fn default() -> Self {
+        Self {
+            alive: "#000000".into(),
+            background: Some("#ffffff".into()),
+            grid_lines: None,
+        }
+    }
+}
+
+/// The runs of live cells in `row`, each its first column and length
+fn live_runs(row: &[CellState]) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let mut x = 0;
+    core::iter::from_fn(move || {
+        let start = x + row[x..].iter().position(|cell| *cell == CellState::Alive)?;
+        let len = row[start..]
+            .iter()
+            .position(|cell| *cell == CellState::Dead)
+            .unwrap_or(row.len() - start);
+        x = start + len;
+        Some((start, len))
+    })
+}
+
+/// `value` safe to put between the double quotes of an attribute
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Grid {
+    /// The grid as an SVG document, every cell a square `cell_size` units
+    /// wide, with the view box covering the whole grid
+    ///
+    /// # Example
+    /// ```
+    /// use game_of_life::conways::svg::SvgStyle;
+    /// use game_of_life::conways::{CellState, Grid};
+    ///
+    /// let mut grid = Grid::new(4, 2);
+    /// for x in 0..3 {
+    ///     grid.set(x, 1, CellState::Alive);
+    /// }
+    /// let svg = grid.to_svg(10.0, &SvgStyle::default());
+    /// assert!(svg.contains(r#"viewBox="0 0 40 20""#));
+    /// // The three cells in a row are one rectangle
+    /// assert!(svg.contains(r#"<rect x="0" y="10" width="30" height="10"/>"#));
+    /// ```
+    pub fn to_svg(&self, cell_size: f32, style: &SvgStyle) -> String {
+        let (width, height) = (
+            self.width as f32 * cell_size,
+            self.height as f32 * cell_size,
+        );
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+        );
+        if let Some(background) = &style.background {
+            let _ = writeln!(
+                svg,
+                "<rect width=\"{width}\" height=\"{height}\" fill=\"{}\"/>",
+                escape(background)
+            );
+        }
+        let _ = writeln!(svg, "<g fill=\"{}\">", escape(&style.alive));
+        for (y, row) in self.grid.iter().enumerate() {
+            for (x, len) in live_runs(row) {
+                let _ = writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{cell_size}\"/>",
+                    x as f32 * cell_size,
+                    y as f32 * cell_size,
+                    len as f32 * cell_size
+                );
+            }
+        }
+        svg.push_str("</g>\n");
+        if let Some(lines) = &style.grid_lines {
+            let mut path = String::new();
+            for x in 0..=self.width {
+                let _ = write!(path, "M{} 0V{height}", x as f32 * cell_size);
+            }
+            for y in 0..=self.height {
+                let _ = write!(path, "M0 {}H{width}", y as f32 * cell_size);
+            }
+            let _ = writeln!(
+                svg,
+                "<path d=\"{path}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>",
+                escape(lines),
+                cell_size * GRID_LINE_WIDTH
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// The cells of a row spelled like plaintext, `O` alive and `.` dead
+    fn row(cells: &str) -> Vec<CellState> {
+        cells
+            .chars()
+            .map(|c| match c {
+                'O' => CellState::Alive,
+                _ => CellState::Dead,
+            })
+            .collect()
+    }
+
+    /// The tags of `svg` in order, each its name with a `/` in front when
+    /// it closes, after checking every tag is closed in the order opened
+    /// and every attribute value is quoted
+    fn tags(svg: &str) -> Vec<String> {
+        let mut tags = Vec::new();
+        let mut open: Vec<&str> = Vec::new();
+        let mut rest = svg.trim();
+        while !rest.is_empty() {
+            assert!(rest.starts_with('<'), "text outside a tag: {rest:?}");
+            let end = rest.find('>').expect("unclosed tag");
+            let tag = &rest[1..end];
+            rest = rest[end + 1..].trim_start();
+            let (tag, empty) = match tag.strip_suffix('/') {
+                Some(tag) => (tag, true),
+                None => (tag, false),
+            };
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(open.pop(), Some(name), "mismatched </{name}>");
+                tags.push(format!("/{name}"));
+                continue;
+            }
+            let (name, mut attributes) = tag.split_once(' ').unwrap_or((tag, ""));
+            while let Some((_, value)) = attributes.trim_start().split_once('=') {
+                assert!(value.starts_with('"'), "unquoted attribute in <{tag}>");
+                let close = value[1..].find('"').expect("unterminated attribute");
+                assert!(!value[1..close + 1].contains('<'));
+                attributes = &value[close + 2..];
+            }
+            tags.push(name.into());
+            if !empty {
+                open.push(name);
+            }
+        }
+        assert!(open.is_empty(), "{open:?} never closed");
+        tags
+    }
+
+    #[test]
+    fn test_runs_of_live_cells_merge() {
+        let runs = |cells: &str| live_runs(&row(cells)).collect::<Vec<_>>();
+        assert_eq!(runs(".OOO..O.OO"), [(1, 3), (6, 1), (8, 2)]);
+        assert_eq!(runs("OOOO"), [(0, 4)]);
+        assert_eq!(runs("O..O"), [(0, 1), (3, 1)]);
+        assert_eq!(runs("...."), []);
+        assert_eq!(runs(""), []);
+    }
+
+    #[test]
+    fn test_the_picture_is_well_formed() {
+        let mut grid = Grid::new(30, 20);
+        grid.randomize(0.4, 3);
+        let style = SvgStyle {
+            alive: "#ff8800".into(),
+            background: None,
+            grid_lines: Some("rgb(1, 2, 3)".into()),
+        };
+        let svg = grid.to_svg(2.5, &style);
+        let tags = tags(&svg);
+        assert_eq!(tags[..2], ["svg", "g"]);
+        assert_eq!(tags[tags.len() - 3..], ["/g", "path", "/svg"]);
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="75" height="50" viewBox="0 0 75 50">"#
+        ));
+        assert!(svg.contains(r#"stroke="rgb(1, 2, 3)" stroke-width="0.125""#));
+
+        // As many rectangles as runs, covering every live cell
+        let runs: Vec<(usize, usize)> = grid.grid.iter().flat_map(|row| live_runs(row)).collect();
+        assert_eq!(tags.iter().filter(|tag| *tag == "rect").count(), runs.len());
+        assert_eq!(
+            runs.iter().map(|(_, len)| len).sum::<usize>(),
+            grid.population()
+        );
+        assert!(runs.len() < grid.population());
+    }
+
+    #[test]
+    fn test_default_style_and_escaping() {
+        let mut grid = Grid::new(3, 1);
+        grid.set(1, 0, CellState::Alive);
+        assert_eq!(
+            grid.to_svg(4.0, &SvgStyle::default()),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"12\" height=\"4\" viewBox=\"0 0 12 4\">\n\
+             <rect width=\"12\" height=\"4\" fill=\"#ffffff\"/>\n\
+             <g fill=\"#000000\">\n\
+             <rect x=\"4\" y=\"0\" width=\"4\" height=\"4\"/>\n\
+             </g>\n\
+             </svg>\n"
+        );
+        let style = SvgStyle {
+            alive: "\"/><script>".into(),
+            ..SvgStyle::default()
+        };
+        let svg = grid.to_svg(4.0, &style);
+        assert!(svg.contains("fill=\"&quot;/&gt;&lt;script&gt;\""));
+        tags(&svg);
+        assert_eq!(tags(&Grid::new(0, 0).to_svg(1.0, &style)).len(), 5);
+    }
+}
@@ -0,0 +1,439 @@
+use crate::conways::Region;
+use macroquad::input::KeyCode;
+
+/// Cells the cursor moves at once while Alt is held
+pub const FAST_STEP: usize = 8;
+
+/// Largest count a prefix accumulates, far more than any board is wide
+pub const MAX_COUNT: usize = 9999;
+
+/// Keys that move the cursor, with the direction they move it in
+/// Both WASD and the vi keys work, for either hand position.
+pub const MOVE_KEYS: [(KeyCode, (isize, isize)); 8] = [
+    (KeyCode::W, (0, -1)),
+    (KeyCode::A, (-1, 0)),
+    (KeyCode::S, (0, 1)),
+    (KeyCode::D, (1, 0)),
+    (KeyCode::K, (0, -1)),
+    (KeyCode::H, (-1, 0)),
+    (KeyCode::J, (0, 1)),
+    (KeyCode::L, (1, 0)),
+];
+
+/// Something the player asked the keyboard cursor to do
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CursorAction {
+    /// Move by `FAST_STEP` cells when `fast`, one otherwise, extending the
+    /// selection from where the cursor was when `extend`
+    Move {
+        direction: (isize, isize),
+        fast: bool,
+        extend: bool,
+    },
+    /// Flip the cell under the cursor
+    Toggle,
+    /// Jump to the top row, or to a given row with a count
+    Top,
+    /// Jump to the bottom row, or to a given row with a count
+    Bottom,
+}
+
+/// What the game has to do after the cursor handled an action
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CursorEffect {
+    None,
+    Toggle((usize, usize)),
+    /// Select the region, as if it had been dragged with the mouse
+    Select(Region),
+}
+
+/// The action a key press maps to while the cursor is active
+/// - WASD / HJKL: move, faster with Alt, extending the selection with Shift
+/// - Enter / Shift+Space: toggle the cell under the cursor
+///
+/// Plain Space is left alone, so it still resumes the simulation.
+pub fn action_for(key: KeyCode, shift: bool, alt: bool) -> Option<CursorAction> {
+    if key == KeyCode::Enter || (key == KeyCode::Space && shift) {
+        return Some(CursorAction::Toggle);
+    }
+    MOVE_KEYS
+        .iter()
+        .find(|(code, _)| *code == key)
+        .map(|&(_, direction)| CursorAction::Move {
+            direction,
+            fast: alt,
+            extend: shift,
+        })
+}
+
+/// What a key press did to a `CountPrefix`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PrefixOutcome {
+    /// The key isn't one of the cursor's and goes to the bindings
+    Ignored,
+    /// The key is part of a command still being typed
+    Pending,
+    /// A pending count or `g` was dropped
+    Cleared,
+    /// Carry out the action, `count` times for moves or on row `count` for jumps
+    Apply {
+        action: CursorAction,
+        count: Option<usize>,
+    },
+}
+
+/// The vi-style keys of the keyboard cursor, with their count prefixes
+///
+/// Digits accumulate a count that the next motion uses, e.g. `12l` moves 12
+/// cells right; `gg` and `G` jump to the top and bottom rows, or to the row
+/// given as a count; `x` toggles like Enter; Escape drops a pending count.
+pub struct CountPrefix {
+    count: Option<usize>,
+    /// Whether a first `g` waits for its second
+    g: bool,
+}
+
+impl CountPrefix {
+    pub fn new() -> Self {
+        Self {
+            count: None,
+            g: false,
+        }
+    }
+
+    /// What has been typed so far, as a modal editor shows it, e.g. `12g`
+    pub fn pending(&self) -> Option<String> {
+        let mut text = self
+            .count
+            .map(|count| count.to_string())
+            .unwrap_or_default();
+        if self.g {
+            text.push('g');
+        }
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// Feed a key press, with the modifiers held
+    pub fn key(&mut self, key: KeyCode, shift: bool, alt: bool) -> PrefixOutcome {
+        if key == KeyCode::Escape {
+            if self.pending().is_none() {
+                return PrefixOutcome::Ignored;
+            }
+            *self = Self::new();
+            return PrefixOutcome::Cleared;
+        }
+        if !shift {
+            if let Some(digit) = digit(key) {
+                // A leading zero is no count, as in vi
+                if digit == 0 && self.count.is_none() {
+                    return PrefixOutcome::Ignored;
+                }
+                let count = self.count.unwrap_or(0) * 10 + digit;
+                self.count = Some(count.min(MAX_COUNT));
+                self.g = false;
+                return PrefixOutcome::Pending;
+            }
+        }
+        let action = match key {
+            KeyCode::G if shift => CursorAction::Bottom,
+            KeyCode::G if !self.g => {
+                self.g = true;
+                return PrefixOutcome::Pending;
+            }
+            KeyCode::G => CursorAction::Top,
+            KeyCode::X if !shift => CursorAction::Toggle,
+            _ => match action_for(key, shift, alt) {
+                Some(action) => action,
+                None => return PrefixOutcome::Ignored,
+            },
+        };
+        let count = self.count.take();
+        self.g = false;
+        PrefixOutcome::Apply { action, count }
+    }
+}
+
+/// The value of a digit key of the main row
+fn digit(key: KeyCode) -> Option<usize> {
+    PREFIX_KEYS[..10].iter().position(|&digit| digit == key)
+}
+
+/// Keys `CountPrefix` handles besides the motions of `MOVE_KEYS`, digits first
+pub const PREFIX_KEYS: [KeyCode; 15] = [
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::G,
+    KeyCode::X,
+    KeyCode::Enter,
+    KeyCode::Space,
+    KeyCode::Escape,
+];
+
+/// An outlined cell the keyboard moves around the board, for editing without a mouse
+pub struct Cursor {
+    position: (usize, usize),
+    /// Where the selection being extended started, while moving with Shift held
+    anchor: Option<(usize, usize)>,
+}
+
+impl Cursor {
+    pub fn new(position: (usize, usize)) -> Self {
+        Self {
+            position,
+            anchor: None,
+        }
+    }
+
+    pub fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    /// Carry out an action on a `width` x `height` board
+    /// The cursor never leaves the board, even when it shrank since the
+    /// cursor last moved.
+    pub fn apply(&mut self, action: CursorAction, (width, height): (usize, usize)) -> CursorEffect {
+        let (x, y) = self.position;
+        self.position = (x.min(width - 1), y.min(height - 1));
+        match action {
+            CursorAction::Toggle => CursorEffect::Toggle(self.position),
+            CursorAction::Top | CursorAction::Bottom => {
+                let row = if action == CursorAction::Top {
+                    0
+                } else {
+                    height - 1
+                };
+                self.position.1 = row;
+                self.anchor = None;
+                CursorEffect::None
+            }
+            CursorAction::Move {
+                direction: (dx, dy),
+                fast,
+                extend,
+            } => {
+                let step = if fast { FAST_STEP as isize } else { 1 };
+                let start = self.position;
+                let moved = |value: usize, delta: isize, size: usize| {
+                    (value as isize + delta * step).clamp(0, size as isize - 1) as usize
+                };
+                self.position = (moved(start.0, dx, width), moved(start.1, dy, height));
+                if !extend {
+                    self.anchor = None;
+                    return CursorEffect::None;
+                }
+                let anchor = *self.anchor.get_or_insert(start);
+                CursorEffect::Select(Region::from_corners(anchor, self.position))
+            }
+        }
+    }
+
+    /// Carry out an action with the count typed before it
+    /// A count repeats moves and picks the row jumps go to, counting from 1;
+    /// toggling ignores it.
+    pub fn apply_counted(
+        &mut self,
+        action: CursorAction,
+        count: Option<usize>,
+        (width, height): (usize, usize),
+    ) -> CursorEffect {
+        match (action, count) {
+            (CursorAction::Top | CursorAction::Bottom, Some(row)) => {
+                let (x, _) = self.position;
+                self.position = (x.min(width - 1), row.clamp(1, height) - 1);
+                self.anchor = None;
+                CursorEffect::None
+            }
+            (CursorAction::Move { .. }, Some(times)) => {
+                let mut effect = CursorEffect::None;
+                for _ in 0..times {
+                    effect = self.apply(action, (width, height));
+                }
+                effect
+            }
+            _ => self.apply(action, (width, height)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOARD: (usize, usize) = (20, 10);
+
+    fn step(cursor: &mut Cursor, key: KeyCode, shift: bool, alt: bool) -> CursorEffect {
+        cursor.apply(action_for(key, shift, alt).unwrap(), BOARD)
+    }
+
+    #[test]
+    fn test_moves_both_layouts() {
+        let mut cursor = Cursor::new((5, 5));
+        step(&mut cursor, KeyCode::D, false, false);
+        step(&mut cursor, KeyCode::L, false, false);
+        step(&mut cursor, KeyCode::W, false, false);
+        assert_eq!(cursor.position(), (7, 4));
+        step(&mut cursor, KeyCode::H, false, false);
+        step(&mut cursor, KeyCode::J, false, false);
+        step(&mut cursor, KeyCode::S, false, false);
+        assert_eq!(cursor.position(), (6, 6));
+        assert_eq!(action_for(KeyCode::Q, false, false), None);
+    }
+
+    #[test]
+    fn test_stays_on_board() {
+        let mut cursor = Cursor::new((1, 1));
+        for _ in 0..3 {
+            step(&mut cursor, KeyCode::A, false, false);
+            step(&mut cursor, KeyCode::W, false, false);
+        }
+        assert_eq!(cursor.position(), (0, 0));
+
+        // Moving fast stops at the far edges instead of overshooting
+        for _ in 0..5 {
+            step(&mut cursor, KeyCode::D, false, true);
+        }
+        step(&mut cursor, KeyCode::S, false, true);
+        assert_eq!(cursor.position(), (19, 8));
+        step(&mut cursor, KeyCode::S, false, true);
+        assert_eq!(cursor.position(), (19, 9));
+
+        // A board that shrank pulls the cursor back in
+        let effect = cursor.apply(CursorAction::Toggle, (4, 4));
+        assert_eq!(effect, CursorEffect::Toggle((3, 3)));
+    }
+
+    #[test]
+    fn test_shift_extends_selection_from_start() {
+        let mut cursor = Cursor::new((4, 4));
+        assert_eq!(
+            step(&mut cursor, KeyCode::D, false, false),
+            CursorEffect::None
+        );
+        step(&mut cursor, KeyCode::D, true, false);
+        let effect = step(&mut cursor, KeyCode::S, true, true);
+        assert_eq!(
+            effect,
+            CursorEffect::Select(Region::from_corners((5, 4), (6, 9)))
+        );
+
+        // Moving without shift starts the next selection from scratch
+        step(&mut cursor, KeyCode::A, false, false);
+        let effect = step(&mut cursor, KeyCode::W, true, false);
+        assert_eq!(
+            effect,
+            CursorEffect::Select(Region::from_corners((5, 9), (5, 8)))
+        );
+    }
+
+    #[test]
+    fn test_toggle_keys() {
+        let mut cursor = Cursor::new((2, 3));
+        assert_eq!(
+            step(&mut cursor, KeyCode::Enter, false, false),
+            CursorEffect::Toggle((2, 3))
+        );
+        assert_eq!(
+            step(&mut cursor, KeyCode::Space, true, false),
+            CursorEffect::Toggle((2, 3))
+        );
+        // Plain space keeps pausing and resuming
+        assert_eq!(action_for(KeyCode::Space, false, false), None);
+    }
+
+    /// Feed unshifted keys to `prefix`, returning the last outcome
+    fn type_keys(prefix: &mut CountPrefix, keys: &[KeyCode]) -> PrefixOutcome {
+        let mut outcome = PrefixOutcome::Ignored;
+        for &key in keys {
+            outcome = prefix.key(key, false, false);
+        }
+        outcome
+    }
+
+    #[test]
+    fn test_counts_accumulate_until_a_motion() {
+        let mut prefix = CountPrefix::new();
+        let outcome = type_keys(&mut prefix, &[KeyCode::Key1, KeyCode::Key2]);
+        assert_eq!(outcome, PrefixOutcome::Pending);
+        assert_eq!(prefix.pending().as_deref(), Some("12"));
+        let PrefixOutcome::Apply { action, count } = prefix.key(KeyCode::L, false, false) else {
+            panic!("a motion applies");
+        };
+        assert_eq!(count, Some(12));
+        assert_eq!(prefix.pending(), None);
+
+        let mut cursor = Cursor::new((0, 0));
+        cursor.apply_counted(action, count, BOARD);
+        assert_eq!(cursor.position(), (12, 0));
+        // Counts past the edge stop there
+        cursor.apply_counted(action, Some(100), BOARD);
+        assert_eq!(cursor.position(), (19, 0));
+    }
+
+    #[test]
+    fn test_escape_and_leading_zero() {
+        let mut prefix = CountPrefix::new();
+        assert_eq!(
+            prefix.key(KeyCode::Key0, false, false),
+            PrefixOutcome::Ignored
+        );
+        assert_eq!(
+            prefix.key(KeyCode::Escape, false, false),
+            PrefixOutcome::Ignored
+        );
+        type_keys(&mut prefix, &[KeyCode::Key3, KeyCode::Key0, KeyCode::G]);
+        assert_eq!(prefix.pending().as_deref(), Some("30g"));
+        assert_eq!(
+            prefix.key(KeyCode::Escape, false, false),
+            PrefixOutcome::Cleared
+        );
+        assert_eq!(prefix.pending(), None);
+        // Shifted digits stay speed preset keys
+        assert_eq!(
+            prefix.key(KeyCode::Key2, true, false),
+            PrefixOutcome::Ignored
+        );
+        assert_eq!(prefix.key(KeyCode::Q, false, false), PrefixOutcome::Ignored);
+    }
+
+    /// Feed a key to `prefix` and carry out what it completes on `cursor`
+    fn run(
+        prefix: &mut CountPrefix,
+        cursor: &mut Cursor,
+        key: KeyCode,
+        shift: bool,
+    ) -> CursorEffect {
+        match prefix.key(key, shift, false) {
+            PrefixOutcome::Apply { action, count } => cursor.apply_counted(action, count, BOARD),
+            _ => CursorEffect::None,
+        }
+    }
+
+    #[test]
+    fn test_row_jumps_and_x() {
+        let mut prefix = CountPrefix::new();
+        let mut cursor = Cursor::new((4, 5));
+        run(&mut prefix, &mut cursor, KeyCode::G, true);
+        assert_eq!(cursor.position(), (4, 9));
+        run(&mut prefix, &mut cursor, KeyCode::G, false);
+        assert_eq!(prefix.pending().as_deref(), Some("g"));
+        run(&mut prefix, &mut cursor, KeyCode::G, false);
+        assert_eq!(cursor.position(), (4, 0));
+
+        // A count picks the row, from 1
+        run(&mut prefix, &mut cursor, KeyCode::Key3, false);
+        run(&mut prefix, &mut cursor, KeyCode::G, true);
+        assert_eq!(cursor.position(), (4, 2));
+        assert_eq!(
+            run(&mut prefix, &mut cursor, KeyCode::X, false),
+            CursorEffect::Toggle((4, 2))
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use crate::backend::GridBackend;
+use alloc::collections::VecDeque;
+
+/// Longest period the game auto-pauses for when `--stable-period` isn't given
+/// Long enough for the common oscillators, up to the pentadecathlon, and short
+/// enough that a period 30 glider gun whose gliders die at the edge keeps running.
+pub const DEFAULT_STABLE_PERIOD: usize = 15;
+
+/// The board started repeating: the state at `generation` comes back every `period` generations
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Stabilization {
+    pub generation: u64,
+    pub period: u64,
+}
+
+/// Notices when the board enters a cycle
+///
+/// Every observed generation is hashed and compared with the hashes of the
+/// last `window` ones, so still lifes and oscillators with a period up to
+/// `window` are found while gliders flying across an empty board are not.
+pub struct CycleDetector {
+    window: usize,
+    history: VecDeque<(u64, u64)>,
+    found: bool,
+}
+
+impl CycleDetector {
+    /// A detector for periods up to `window`; 0 never detects anything
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            history: VecDeque::with_capacity(window),
+            found: false,
+        }
+    }
+
+    /// Look at the board after a generation
+    ///
+    /// # Returns
+    /// The cycle the board entered, the first time one is found. Afterwards
+    /// nothing is reported until `reset` is called.
+    pub fn observe(&mut self, grid: &(impl GridBackend + ?Sized)) -> Option<Stabilization> {
+        if self.found || self.window == 0 {
+            return None;
+        }
+        let hash = grid.content_hash();
+        let generation = grid.generation();
+        let repeat = self
+            .history
+            .iter()
+            .find(|(_, h)| *h == hash)
+            .map(|&(start, _)| Stabilization {
+                generation: start,
+                period: generation - start,
+            });
+        if repeat.is_some() {
+            self.found = true;
+            return repeat;
+        }
+        if self.history.len() == self.window {
+            self.history.pop_front();
+        }
+        self.history.push_back((generation, hash));
+        None
+    }
+
+    /// Forget the generations seen so far, e.g. after the board was edited
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.found = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{self, sparse::SparseGrid};
+    use crate::conways::{CellState, Grid};
+
+    fn blinker() -> Grid {
+        let mut grid = Grid::new(5, 5);
+        for x in 1..4 {
+            grid.set(x, 2, CellState::Alive);
+        }
+        grid
+    }
+
+    /// Observe `steps` generations, returning the first cycle found
+    fn watch(grid: &mut Grid, detector: &mut CycleDetector, steps: usize) -> Option<Stabilization> {
+        let mut found = detector.observe(grid);
+        for _ in 0..steps {
+            grid.next_cell_generation();
+            found = found.or(detector.observe(grid));
+        }
+        found
+    }
+
+    #[test]
+    fn test_detects_oscillator_once() {
+        let mut grid = blinker();
+        let mut detector = CycleDetector::new(DEFAULT_STABLE_PERIOD);
+        assert_eq!(
+            watch(&mut grid, &mut detector, 5),
+            Some(Stabilization {
+                generation: 0,
+                period: 2
+            })
+        );
+        // Already reported
+        assert_eq!(watch(&mut grid, &mut detector, 5), None);
+
+        detector.reset();
+        assert_eq!(
+            watch(&mut grid, &mut detector, 5),
+            Some(Stabilization {
+                generation: 10,
+                period: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_window_limits_period() {
+        // A blinker repeats every 2 generations, which a window of 1 can't see
+        let mut grid = blinker();
+        assert_eq!(watch(&mut grid, &mut CycleDetector::new(1), 10), None);
+        assert_eq!(watch(&mut grid, &mut CycleDetector::new(0), 10), None);
+    }
+
+    #[test]
+    fn test_any_backend_is_watched_alike() {
+        let mut grid: SparseGrid = backend::convert(&blinker());
+        let mut detector = CycleDetector::new(DEFAULT_STABLE_PERIOD);
+        let found = (0..5).find_map(|_| {
+            grid.step();
+            detector.observe(&grid)
+        });
+        assert_eq!(
+            found,
+            Some(Stabilization {
+                generation: 1,
+                period: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use crate::cycle::Stabilization;
+use std::fmt;
+
+/// Share of live cells in the daily soup
+pub const DAILY_DENSITY: f64 = 0.3;
+
+/// Size of the daily board, the same for everyone whatever their settings
+pub const DAILY_SIZE: (usize, usize) = (80, 60);
+
+/// A day of the Gregorian calendar
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// The date `days` days after 1970-01-01
+    /// see more: <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+    pub fn from_unix_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        let year = (year_of_era + era * 400 + i64::from(month <= 2)) as i32;
+        Self { year, month, day }
+    }
+
+    /// Today in UTC, from the system clock
+    /// Goes through miniquad, since `SystemTime::now` panics in the browser.
+    pub fn today() -> Self {
+        let seconds = macroquad::miniquad::date::now();
+        Self::from_unix_days((seconds / 86_400.0).floor() as i64)
+    }
+
+    /// Read a `YYYY-MM-DD` date
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split('-');
+        let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() || year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return None;
+        }
+        let date = Self {
+            year: year.parse().ok()?,
+            month: month.parse().ok()?,
+            day: day.parse().ok()?,
+        };
+        let leap = date.year % 4 == 0 && (date.year % 100 != 0 || date.year % 400 == 0);
+        let days_in_month = match date.month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => return None,
+        };
+        (1..=days_in_month).contains(&date.day).then_some(date)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Seed of the daily soup for `date`: the 64-bit FNV-1a hash of the date
+/// written as `YYYY-MM-DD`, so anyone can work it out without the game
+/// see more: <http://www.isthe.com/chongo/tech/comp/fnv/>
+pub fn daily_seed(date: Date) -> u64 {
+    date.to_string()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+}
+
+/// The line introducing the soup of `date`
+pub fn title(date: Date) -> String {
+    format!("Daily soup for {date} — seed {:#018x}", daily_seed(date))
+}
+
+/// How the daily soup has fared so far, for comparing with other players
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lifespan {
+    pub date: Date,
+    pub peak_population: usize,
+    /// First generation at which `peak_population` was reached
+    pub peak_generation: u64,
+    /// `None` while the soup is still changing
+    pub stabilization: Option<Stabilization>,
+}
+
+impl Lifespan {
+    /// Start following the soup of `date`, `population` cells large
+    pub fn new(date: Date, population: usize) -> Self {
+        Self {
+            date,
+            peak_population: population,
+            peak_generation: 0,
+            stabilization: None,
+        }
+    }
+
+    /// Note the population after a generation
+    pub fn record(&mut self, generation: u64, population: usize) {
+        if population > self.peak_population {
+            self.peak_population = population;
+            self.peak_generation = generation;
+        }
+    }
+
+    /// Note the soup settling down, keeping the first time it did
+    pub fn stabilized(&mut self, stabilization: Stabilization) {
+        self.stabilization.get_or_insert(stabilization);
+    }
+}
+
+impl fmt::Display for Lifespan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Daily {}: peak {} at gen {}",
+            self.date, self.peak_population, self.peak_generation
+        )?;
+        match self.stabilization {
+            Some(Stabilization { generation, period }) => {
+                write!(f, ", stable since gen {generation} (period {period})")
+            }
+            None => write!(f, ", still evolving"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> Date {
+        Date::parse(text).unwrap()
+    }
+
+    #[test]
+    fn test_seed_is_fnv1a_of_the_date() {
+        assert_eq!(daily_seed(date("2024-06-01")), 0xc825_9d60_1ccb_eed6);
+        assert_ne!(
+            daily_seed(date("2024-06-01")),
+            daily_seed(date("2024-06-02"))
+        );
+        assert_eq!(
+            title(date("2024-06-01")),
+            "Daily soup for 2024-06-01 — seed 0xc8259d601ccbeed6"
+        );
+    }
+
+    #[test]
+    fn test_from_unix_days() {
+        assert_eq!(Date::from_unix_days(0), date("1970-01-01"));
+        assert_eq!(Date::from_unix_days(19_875), date("2024-06-01"));
+        assert_eq!(Date::from_unix_days(11_016), date("2000-02-29"));
+        assert_eq!(Date::from_unix_days(-1), date("1969-12-31"));
+    }
+
+    #[test]
+    fn test_parse_dates() {
+        assert_eq!(date("2024-02-29").to_string(), "2024-02-29");
+        for bad in [
+            "2023-02-29",
+            "2024-13-01",
+            "2024-04-31",
+            "2024-6-1",
+            "24-06-01",
+            "2024-06-01-01",
+            "today",
+        ] {
+            assert_eq!(Date::parse(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_lifespan_keeps_peak_and_first_stabilization() {
+        let mut lifespan = Lifespan::new(date("2024-06-01"), 10);
+        lifespan.record(1, 12);
+        lifespan.record(2, 8);
+        lifespan.record(3, 12);
+        lifespan.stabilized(Stabilization {
+            generation: 3,
+            period: 2,
+        });
+        lifespan.stabilized(Stabilization {
+            generation: 9,
+            period: 1,
+        });
+        assert_eq!(
+            lifespan.to_string(),
+            "Daily 2024-06-01: peak 12 at gen 1, stable since gen 3 (period 2)"
+        );
+    }
+}
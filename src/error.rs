@@ -0,0 +1,192 @@
+use crate::formats::FormatError;
+use crate::rule::RuleError;
+use alloc::string::String;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+/// Error produced by the fallible parts of the library
+/// - `OutOfBounds`: A cell outside the board was asked for
+/// - `DimensionMismatch`: Two boards that have to be the same size aren't
+/// - `ParseError`: Text in one of the file formats is malformed
+/// - `Io`: A file couldn't be read or written, only with the `std` feature
+/// - `InvalidRule`: A rulestring isn't valid B/S notation
+/// - `InvalidArgument`: An argument the function can't work with, e.g. a file
+///   extension matching no known format
+#[derive(Debug)]
+pub enum ConwayError {
+    OutOfBounds {
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    },
+    DimensionMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    /// `line` and `col` count from 1, `0` and `None` when unknown
+    ParseError {
+        format: &'static str,
+        line: usize,
+        col: Option<usize>,
+        msg: String,
+    },
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    InvalidRule(RuleError),
+    InvalidArgument(String),
+}
+
+impl ConwayError {
+    /// `err`, found parsing text in `format`, e.g. "RLE"
+    pub fn parse(format: &'static str, err: FormatError) -> Self {
+        ConwayError::ParseError {
+            format,
+            line: err.line,
+            col: None,
+            msg: err.message,
+        }
+    }
+}
+
+impl fmt::Display for ConwayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConwayError::OutOfBounds {
+                x,
+                y,
+                width,
+                height,
+            } => write!(f, "cell {x},{y} is outside the {width}x{height} board"),
+            ConwayError::DimensionMismatch { expected, found } => write!(
+                f,
+                "expected a {}x{} board, found {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            ConwayError::ParseError {
+                format,
+                line,
+                col,
+                msg,
+            } => {
+                write!(f, "{format} parse error")?;
+                match (line, col) {
+                    (0, _) => (),
+                    (line, None) => write!(f, " at line {line}")?,
+                    (line, Some(col)) => write!(f, " at line {line}, column {col}")?,
+                }
+                write!(f, ": {msg}")
+            }
+            #[cfg(feature = "std")]
+            ConwayError::Io(err) => write!(f, "{err}"),
+            ConwayError::InvalidRule(err) => write!(f, "invalid rule: {err}"),
+            ConwayError::InvalidArgument(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl core::error::Error for ConwayError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            ConwayError::Io(err) => Some(err),
+            ConwayError::InvalidRule(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for ConwayError {
+    fn from(err: io::Error) -> Self {
+        ConwayError::Io(err)
+    }
+}
+
+/// A pattern file's error, when the format it was in isn't known any better
+impl From<FormatError> for ConwayError {
+    fn from(err: FormatError) -> Self {
+        ConwayError::parse("pattern", err)
+    }
+}
+
+impl From<RuleError> for ConwayError {
+    fn from(err: RuleError) -> Self {
+        ConwayError::InvalidRule(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use core::error::Error;
+
+    #[test]
+    fn test_messages_say_what_went_wrong() {
+        let err = ConwayError::OutOfBounds {
+            x: 7,
+            y: 2,
+            width: 5,
+            height: 4,
+        };
+        assert_eq!(err.to_string(), "cell 7,2 is outside the 5x4 board");
+        let err = ConwayError::DimensionMismatch {
+            expected: (24, 20),
+            found: (5, 5),
+        };
+        assert_eq!(err.to_string(), "expected a 24x20 board, found 5x5");
+        let err = ConwayError::parse("RLE", FormatError::new(2, "unexpected character 'q'"));
+        assert_eq!(
+            err.to_string(),
+            "RLE parse error at line 2: unexpected character 'q'"
+        );
+        let err = ConwayError::ParseError {
+            format: "plaintext",
+            line: 3,
+            col: Some(4),
+            msg: "unexpected character 'x'".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "plaintext parse error at line 3, column 4: unexpected character 'x'"
+        );
+        // No line to point at
+        let err = ConwayError::from(FormatError::new(0, "empty file"));
+        assert_eq!(err.to_string(), "pattern parse error: empty file");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_io_errors_keep_the_source() {
+        let err = ConwayError::from(io::Error::new(io::ErrorKind::NotFound, "no such file"));
+        assert_eq!(err.to_string(), "no such file");
+        let source = err.source().unwrap().downcast_ref::<io::Error>().unwrap();
+        assert_eq!(source.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_conversions_keep_the_source() {
+        let err = ConwayError::from("B9/S23".parse::<crate::rule::Rule>().unwrap_err());
+        assert_eq!(
+            err.to_string(),
+            "invalid rule: '9' isn't a neighbor count from 0 to 8"
+        );
+        assert_eq!(
+            err.source().unwrap().downcast_ref::<RuleError>(),
+            Some(&RuleError::Neighbors('9'))
+        );
+
+        let err = ConwayError::from(FormatError::new(5, "invalid size"));
+        assert!(matches!(
+            &err,
+            ConwayError::ParseError {
+                format: "pattern",
+                line: 5,
+                ..
+            }
+        ));
+        assert!(err.source().is_none());
+    }
+}
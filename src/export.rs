@@ -0,0 +1,115 @@
+use crate::conways::svg::SvgStyle;
+use crate::conways::{CellState, Grid};
+use crate::render::{ImageRenderer, Renderer};
+use image::{ImageResult, RgbaImage};
+use macroquad::color::{Color, BLACK, WHITE};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory where screenshots are written, relative to the working directory
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+/// Size in pixels of one cell in exported images
+pub const SCREENSHOT_SCALE: u32 = 4;
+
+/// Size in SVG units of one cell in exported SVGs
+pub const SVG_CELL_SIZE: f32 = 10.0;
+
+const ALIVE: Color = WHITE;
+const DEAD: Color = BLACK;
+
+/// Render the grid into an image where every cell is a `scale` x `scale` square
+///
+/// The image is built from the cells alone, so it is pixel-perfect and free of
+/// any HUD or overlay regardless of how the board is shown on screen.
+///
+/// # Arguments
+/// * `grid` - The grid to render
+/// * `scale` - Size in pixels of one cell
+///
+/// # Returns
+/// An image of `width * scale` by `height * scale` pixels
+pub fn grid_to_image(grid: &Grid, scale: u32) -> RgbaImage {
+    let mut renderer = ImageRenderer::new(grid.width(), grid.height(), scale, DEAD);
+    renderer.draw_cells(
+        &mut (0..grid.height())
+            .flat_map(|y| (0..grid.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| grid.get(x, y) == CellState::Alive)
+            .map(|(x, y)| (x, y, ALIVE)),
+    );
+    renderer.into_image()
+}
+
+/// Render the grid with `grid_to_image` and write it to `path` as a PNG,
+/// creating the parent directory if needed
+pub fn save_png(grid: &Grid, scale: u32, path: &Path) -> ImageResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    grid_to_image(grid, scale).save_with_format(path, image::ImageFormat::Png)
+}
+
+/// Write the grid to `path` as an SVG in `style`, see `Grid::to_svg`,
+/// creating the parent directory if needed
+pub fn save_svg(grid: &Grid, style: &SvgStyle, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, grid.to_svg(SVG_CELL_SIZE, style))
+}
+
+/// A fresh path in `SCREENSHOT_DIR` including the generation number,
+/// e.g. `screenshots/gen-42-20240601-134502.png`
+pub fn screenshot_path(generation: u64, timestamp: &str) -> PathBuf {
+    Path::new(SCREENSHOT_DIR).join(format!("gen-{generation}-{timestamp}.png"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::to_rgba;
+
+    #[test]
+    fn test_screenshot_path() {
+        assert_eq!(
+            screenshot_path(42, "20240601-134502"),
+            Path::new("screenshots/gen-42-20240601-134502.png")
+        );
+    }
+
+    #[test]
+    fn test_png_dimensions_match_grid_times_scale() {
+        let mut grid = Grid::new(7, 5);
+        grid.set(2, 3, CellState::Alive);
+
+        let dir = std::env::temp_dir().join(format!("conways-png-{}", std::process::id()));
+        let path = dir.join("grid.png");
+        save_png(&grid, 3, &path).unwrap();
+
+        let image = image::open(&path).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (21, 15));
+        // Every pixel of the live cell's square is lit, and nothing else
+        assert_eq!(*image.get_pixel(6, 9), to_rgba(ALIVE));
+        assert_eq!(*image.get_pixel(8, 11), to_rgba(ALIVE));
+        assert_eq!(*image.get_pixel(5, 9), to_rgba(DEAD));
+        assert_eq!(*image.get_pixel(9, 12), to_rgba(DEAD));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_svg_covers_the_grid() {
+        let mut grid = Grid::new(7, 5);
+        grid.set(2, 3, CellState::Alive);
+
+        let dir = std::env::temp_dir().join(format!("conways-svg-{}", std::process::id()));
+        let path = dir.join("grid.svg");
+        save_svg(&grid, &SvgStyle::default(), &path).unwrap();
+
+        let svg = fs::read_to_string(&path).unwrap();
+        assert!(svg.contains(r#"viewBox="0 0 70 50""#));
+        assert!(svg.contains(r#"<rect x="20" y="30" width="10" height="10"/>"#));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,735 @@
+//! A C interface to the engine, for driving the simulation from C or C++
+//!
+//! Boards are opaque `ConwayGrid` pointers, made by `conway_grid_new` or
+//! `conway_grid_load_rle` and given back to `conway_grid_free`. Every function
+//! takes a null pointer and a cell off the board without harm, reporting it
+//! through its return value, a `ConwayStatus` for the calls that can fail in
+//! more than one way, and no panic ever crosses into C: the function that hit
+//! one returns `CONWAY_STATUS_PANIC`, or the same value as for bad arguments.
+//!
+//! The header is `ffi/game_of_life.h`, generated by running
+//! `cbindgen --config ffi/cbindgen.toml --output ffi/game_of_life.h` and kept
+//! in the repository, so C users need no Rust tooling besides cargo. `make ffi`
+//! builds the library to link against.
+
+use crate::conways::{CellState, Grid};
+use crate::cycle::{CycleDetector, Stabilization};
+use crate::formats::{rle, MAX_PATTERN_CELLS};
+use crate::patterns;
+use crate::rule::Rule;
+use std::fmt::Write;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Run `f`, or return `fallback` if it panics
+fn guard<T>(fallback: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+/// What a call came to, `CONWAY_STATUS_OK` when it did what was asked; every
+/// other status but `CONWAY_STATUS_PANIC` leaves the board as it was
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConwayStatus {
+    Ok = 0,
+    /// A board or buffer was null
+    NullPointer = 1,
+    /// A cell was off the board
+    OutOfBounds = 2,
+    /// A buffer wasn't the size the call needs
+    BufferSize = 3,
+    /// A rulestring that isn't `B<digits>/S<digits>`, or neighbor counts above 8
+    InvalidRule = 4,
+    /// More generations than one call runs
+    TooLarge = 5,
+    /// The library hit a bug, which may have left the board half changed
+    Panic = 6,
+}
+
+/// A new board of dead cells, or null when it would have more than
+/// `MAX_PATTERN_CELLS` cells
+#[no_mangle]
+pub extern "C" fn conway_grid_new(width: usize, height: usize) -> *mut Grid {
+    match width.max(1).checked_mul(height.max(1)) {
+        Some(cells) if cells <= MAX_PATTERN_CELLS => guard(std::ptr::null_mut(), || {
+            Box::into_raw(Box::new(Grid::new(width, height)))
+        }),
+        _ => std::ptr::null_mut(),
+    }
+}
+
+/// Free a board, doing nothing for null
+///
+/// # Safety
+/// `grid` is null or a board from this library not freed yet, and isn't used
+/// again afterwards
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_free(grid: *mut Grid) {
+    if !grid.is_null() {
+        guard((), || drop(unsafe { Box::from_raw(grid) }));
+    }
+}
+
+/// Make the cell at `(x, y)` alive or dead
+///
+/// # Safety
+/// `grid` is null or a live board from this library
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_set(
+    grid: *mut Grid,
+    x: usize,
+    y: usize,
+    alive: bool,
+) -> ConwayStatus {
+    let Some(grid) = (unsafe { grid.as_mut() }) else {
+        return ConwayStatus::NullPointer;
+    };
+    let state = if alive {
+        CellState::Alive
+    } else {
+        CellState::Dead
+    };
+    guard(ConwayStatus::Panic, || match grid.try_set(x, y, state) {
+        Ok(()) => ConwayStatus::Ok,
+        Err(_) => ConwayStatus::OutOfBounds,
+    })
+}
+
+/// 1 when the cell at `(x, y)` is alive, 0 when it is dead and -1 when the
+/// board is null or the cell outside it
+///
+/// # Safety
+/// `grid` is null or a live board from this library
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_get(grid: *const Grid, x: usize, y: usize) -> i32 {
+    let Some(grid) = (unsafe { grid.as_ref() }) else {
+        return -1;
+    };
+    guard(-1, || match grid.try_get(x, y) {
+        Ok(CellState::Alive) => 1,
+        Ok(CellState::Dead) => 0,
+        Err(_) => -1,
+    })
+}
+
+/// Copy every cell into the `len` bytes at `cells`, row by row, 1 for a live
+/// cell and 0 for a dead one; `len` must be the board's width times height
+///
+/// # Safety
+/// `grid` is null or a live board from this library, and `cells` is null or
+/// points to `len` writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_read_cells(
+    grid: *const Grid,
+    cells: *mut u8,
+    len: usize,
+) -> ConwayStatus {
+    let Some(grid) = (unsafe { grid.as_ref() }) else {
+        return ConwayStatus::NullPointer;
+    };
+    if cells.is_null() {
+        return ConwayStatus::NullPointer;
+    }
+    if len != grid.width() * grid.height() {
+        return ConwayStatus::BufferSize;
+    }
+    let cells = unsafe { std::slice::from_raw_parts_mut(cells, len) };
+    guard(ConwayStatus::Panic, || {
+        let width = grid.width();
+        for (i, cell) in cells.iter_mut().enumerate() {
+            *cell = (grid.get(i % width, i / width) == CellState::Alive) as u8;
+        }
+        ConwayStatus::Ok
+    })
+}
+
+/// Set every cell from the `len` bytes at `cells`, laid out like
+/// `conway_grid_read_cells` writes them, any byte but 0 a live cell
+///
+/// # Safety
+/// `grid` is null or a live board from this library, and `cells` is null or
+/// points to `len` readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_write_cells(
+    grid: *mut Grid,
+    cells: *const u8,
+    len: usize,
+) -> ConwayStatus {
+    let Some(grid) = (unsafe { grid.as_mut() }) else {
+        return ConwayStatus::NullPointer;
+    };
+    if cells.is_null() {
+        return ConwayStatus::NullPointer;
+    }
+    if len != grid.width() * grid.height() {
+        return ConwayStatus::BufferSize;
+    }
+    let cells = unsafe { std::slice::from_raw_parts(cells, len) };
+    guard(ConwayStatus::Panic, || {
+        let width = grid.width();
+        for (i, &cell) in cells.iter().enumerate() {
+            let state = if cell == 0 {
+                CellState::Dead
+            } else {
+                CellState::Alive
+            };
+            grid.set(i % width, i / width, state);
+        }
+        ConwayStatus::Ok
+    })
+}
+
+/// Most generations `conway_grid_step` and `conway_grid_lifespan` run in one
+/// call
+///
+/// A generation takes time in proportion to the board's area, some tens of
+/// milliseconds for a million cells, so a call on a big board can take
+/// minutes. Step a board further with several calls.
+pub const CONWAY_GRID_MAX_STEP: u64 = 10_000;
+
+/// Advance the board `generations` generations, `CONWAY_STATUS_TOO_LARGE`
+/// above `CONWAY_GRID_MAX_STEP`
+///
+/// # Safety
+/// `grid` is null or a live board from this library
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_step(grid: *mut Grid, generations: u64) -> ConwayStatus {
+    let Some(grid) = (unsafe { grid.as_mut() }) else {
+        return ConwayStatus::NullPointer;
+    };
+    if generations > CONWAY_GRID_MAX_STEP {
+        return ConwayStatus::TooLarge;
+    }
+    guard(ConwayStatus::Panic, || {
+        grid.step_n(generations);
+        ConwayStatus::Ok
+    })
+}
+
+/// Number of live cells, 0 for null
+///
+/// # Safety
+/// `grid` is null or a live board from this library
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_population(grid: *const Grid) -> usize {
+    match unsafe { grid.as_ref() } {
+        Some(grid) => guard(0, || grid.population()),
+        None => 0,
+    }
+}
+
+/// Number of columns, 0 for null
+///
+/// # Safety
+/// `grid` is null or a live board from this library
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_width(grid: *const Grid) -> usize {
+    unsafe { grid.as_ref() }.map_or(0, Grid::width)
+}
+
+/// Number of rows, 0 for null
+///
+/// # Safety
+/// `grid` is null or a live board from this library
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_height(grid: *const Grid) -> usize {
+    unsafe { grid.as_ref() }.map_or(0, Grid::height)
+}
+
+/// Number of generations the board was stepped, 0 for null
+///
+/// # Safety
+/// `grid` is null or a live board from this library
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_generation(grid: *const Grid) -> u64 {
+    unsafe { grid.as_ref() }.map_or(0, Grid::generation)
+}
+
+/// Write the rule the board steps by to `birth` and `survival`, as masks of
+/// neighbor counts: bit `n` is set when `n` neighbors bring a dead cell to
+/// life, or keep a live one alive
+///
+/// # Safety
+/// `grid` is null or a live board from this library, and `birth` and
+/// `survival` are null or writable
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_rule(
+    grid: *const Grid,
+    birth: *mut u16,
+    survival: *mut u16,
+) -> ConwayStatus {
+    let (Some(grid), false, false) = (
+        unsafe { grid.as_ref() },
+        birth.is_null(),
+        survival.is_null(),
+    ) else {
+        return ConwayStatus::NullPointer;
+    };
+    let masks = grid.rule().masks();
+    unsafe { (birth.write(masks.0), survival.write(masks.1)) };
+    ConwayStatus::Ok
+}
+
+/// Step the board by the rule of the masks `conway_grid_rule` writes,
+/// `CONWAY_STATUS_INVALID_RULE` with a bit above 8 set
+///
+/// # Safety
+/// `grid` is null or a live board from this library
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_set_rule(
+    grid: *mut Grid,
+    birth: u16,
+    survival: u16,
+) -> ConwayStatus {
+    let Some(grid) = (unsafe { grid.as_mut() }) else {
+        return ConwayStatus::NullPointer;
+    };
+    match Rule::from_masks(birth, survival) {
+        Some(rule) => guard(ConwayStatus::Panic, || {
+            grid.set_rule(rule);
+            ConwayStatus::Ok
+        }),
+        None => ConwayStatus::InvalidRule,
+    }
+}
+
+/// Read the `len` bytes of UTF-8 at `text`, a rulestring such as `B36/S23`,
+/// into the masks `conway_grid_set_rule` takes
+///
+/// # Safety
+/// `text` is null or points to `len` readable bytes, and `birth` and
+/// `survival` are null or writable
+#[no_mangle]
+pub unsafe extern "C" fn conway_rule_parse(
+    text: *const u8,
+    len: usize,
+    birth: *mut u16,
+    survival: *mut u16,
+) -> ConwayStatus {
+    if text.is_null() || birth.is_null() || survival.is_null() {
+        return ConwayStatus::NullPointer;
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(text, len) };
+    let rule = std::str::from_utf8(bytes).ok().map(str::parse::<Rule>);
+    let Some(Ok(rule)) = rule else {
+        return ConwayStatus::InvalidRule;
+    };
+    let masks = rule.masks();
+    unsafe { (birth.write(masks.0), survival.write(masks.1)) };
+    ConwayStatus::Ok
+}
+
+/// Longest period of a cycle `conway_grid_lifespan` counts a board settling
+/// into, the same as the game's headless report
+pub const CONWAY_LIFESPAN_MAX_PERIOD: u64 = 30;
+
+/// How a board fared over a run of `conway_grid_lifespan`, in generations
+/// counted like `conway_grid_generation`
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ConwayLifespan {
+    /// Where the run stopped: the generation the board was seen to settle at,
+    /// or the last one run
+    pub generation: u64,
+    /// Live cells at `generation`
+    pub population: usize,
+    /// Most live cells of any generation run, and the first one that had them
+    pub peak_population: usize,
+    pub peak_generation: u64,
+    /// Whether the board came back to a state it was in at most
+    /// `CONWAY_LIFESPAN_MAX_PERIOD` generations before
+    pub settled: bool,
+    /// With `settled`, the first generation of the cycle it settled into, and
+    /// its period, 1 for a still life
+    pub settled_at: u64,
+    pub period: u64,
+}
+
+/// Run a copy of the board for up to `generations` generations, stopping
+/// once it settles, and write how it fared to `lifespan`; the board is left
+/// as it was, and more than `CONWAY_GRID_MAX_STEP` generations are
+/// `CONWAY_STATUS_TOO_LARGE`
+///
+/// # Safety
+/// `grid` is null or a live board from this library, and `lifespan` is null
+/// or writable
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_lifespan(
+    grid: *const Grid,
+    generations: u64,
+    lifespan: *mut ConwayLifespan,
+) -> ConwayStatus {
+    let (Some(grid), false) = (unsafe { grid.as_ref() }, lifespan.is_null()) else {
+        return ConwayStatus::NullPointer;
+    };
+    if generations > CONWAY_GRID_MAX_STEP {
+        return ConwayStatus::TooLarge;
+    }
+    let run = guard(None, || {
+        let mut board = grid.clone();
+        let end = board.generation().saturating_add(generations);
+        let mut detector = CycleDetector::new(CONWAY_LIFESPAN_MAX_PERIOD as usize);
+        let mut run = ConwayLifespan {
+            peak_population: board.population(),
+            peak_generation: board.generation(),
+            ..ConwayLifespan::default()
+        };
+        loop {
+            if let Some(Stabilization { generation, period }) = detector.observe(&board) {
+                (run.settled, run.settled_at, run.period) = (true, generation, period);
+                break;
+            }
+            if board.generation() >= end {
+                break;
+            }
+            board.next_cell_generation();
+            if board.population() > run.peak_population {
+                run.peak_population = board.population();
+                run.peak_generation = board.generation();
+            }
+        }
+        run.generation = board.generation();
+        run.population = board.population();
+        Some(run)
+    });
+    match run {
+        Some(run) => {
+            unsafe { lifespan.write(run) };
+            ConwayStatus::Ok
+        }
+        None => ConwayStatus::Panic,
+    }
+}
+
+/// Write the objects on the board and how many of each as `len` bytes of
+/// UTF-8 at `text`, ending with a nul, and the length of the text without it
+/// to `written`
+///
+/// Every line is a count and a name, e.g. `2 block`, the most common object
+/// first, with objects `patterns::identify` doesn't know as `unidentified`.
+/// When the text doesn't fit, which `len` 0 with a null `text` always does,
+/// `written` still gets its length, and the call is `CONWAY_STATUS_BUFFER_SIZE`.
+///
+/// # Safety
+/// `grid` is null or a live board from this library, `text` is null or
+/// points to `len` writable bytes, and `written` is null or writable
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_census(
+    grid: *const Grid,
+    text: *mut u8,
+    len: usize,
+    written: *mut usize,
+) -> ConwayStatus {
+    let (Some(grid), false) = (unsafe { grid.as_ref() }, written.is_null()) else {
+        return ConwayStatus::NullPointer;
+    };
+    let Some(census) = guard(None, || {
+        let mut out = String::new();
+        for (name, count) in patterns::census(grid) {
+            let _ = writeln!(out, "{count} {}", name.unwrap_or("unidentified"));
+        }
+        Some(out)
+    }) else {
+        return ConwayStatus::Panic;
+    };
+    unsafe { written.write(census.len()) };
+    if len <= census.len() {
+        return ConwayStatus::BufferSize;
+    }
+    if text.is_null() {
+        return ConwayStatus::NullPointer;
+    }
+    let text = unsafe { std::slice::from_raw_parts_mut(text, len) };
+    text[..census.len()].copy_from_slice(census.as_bytes());
+    text[census.len()] = 0;
+    ConwayStatus::Ok
+}
+
+/// A board decoded from `len` bytes of UTF-8 RLE text at `text`, which needn't
+/// end with a nul, or null when the text is null, isn't UTF-8 or isn't valid RLE
+///
+/// # Safety
+/// `text` is null or points to `len` readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn conway_grid_load_rle(text: *const u8, len: usize) -> *mut Grid {
+    if text.is_null() {
+        return std::ptr::null_mut();
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(text, len) };
+    guard(std::ptr::null_mut(), || {
+        match std::str::from_utf8(bytes).ok().map(rle::decode::<Grid>) {
+            Some(Ok(grid)) => Box::into_raw(Box::new(grid)),
+            _ => std::ptr::null_mut(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn test_a_blinker_turns_through_the_c_interface() {
+        let grid = conway_grid_new(5, 5);
+        assert!(!grid.is_null());
+        unsafe {
+            for x in 1..4 {
+                assert_eq!(conway_grid_set(grid, x, 2, true), ConwayStatus::Ok);
+            }
+            assert_eq!(conway_grid_population(grid), 3);
+            assert_eq!(conway_grid_step(grid, 1), ConwayStatus::Ok);
+            assert_eq!(conway_grid_get(grid, 2, 1), 1);
+            assert_eq!(conway_grid_get(grid, 1, 2), 0);
+            // Back where it started after a full period
+            assert_eq!(conway_grid_step(grid, 3), ConwayStatus::Ok);
+            assert_eq!(conway_grid_get(grid, 1, 2), 1);
+            assert_eq!((conway_grid_width(grid), conway_grid_height(grid)), (5, 5));
+            conway_grid_free(grid);
+        }
+    }
+
+    #[test]
+    fn test_bad_arguments_are_reported_not_fatal() {
+        unsafe {
+            use ConwayStatus::*;
+            assert_eq!(conway_grid_set(ptr::null_mut(), 0, 0, true), NullPointer);
+            assert_eq!(conway_grid_get(ptr::null(), 0, 0), -1);
+            assert_eq!(conway_grid_step(ptr::null_mut(), 1), NullPointer);
+            assert_eq!(conway_grid_population(ptr::null()), 0);
+            conway_grid_free(ptr::null_mut());
+            assert!(conway_grid_load_rle(ptr::null(), 4).is_null());
+
+            let grid = conway_grid_new(3, 3);
+            assert_eq!(conway_grid_set(grid, 3, 0, true), OutOfBounds);
+            assert_eq!(conway_grid_get(grid, 0, 7), -1);
+            assert_eq!(conway_grid_population(grid), 0);
+            // Too many generations at once are refused rather than blocking for hours
+            assert_eq!(conway_grid_set(grid, 1, 1, true), Ok);
+            assert_eq!(conway_grid_step(grid, u64::MAX), TooLarge);
+            assert_eq!(conway_grid_step(grid, CONWAY_GRID_MAX_STEP + 1), TooLarge);
+            assert_eq!(conway_grid_generation(grid), 0);
+            assert_eq!(conway_grid_step(grid, CONWAY_GRID_MAX_STEP), Ok);
+            assert_eq!(conway_grid_generation(grid), CONWAY_GRID_MAX_STEP);
+            conway_grid_free(grid);
+        }
+        assert!(conway_grid_new(usize::MAX, 2).is_null());
+        assert!(conway_grid_new(0, MAX_PATTERN_CELLS + 1).is_null());
+    }
+
+    #[test]
+    fn test_rle_loads_from_a_byte_buffer() {
+        // Only the first `len` bytes count, with no nul needed
+        let text = b"x = 3, y = 3\nbo$2bo$3o!garbage";
+        unsafe {
+            let grid = conway_grid_load_rle(text.as_ptr(), text.len() - 7);
+            assert!(!grid.is_null());
+            assert_eq!(conway_grid_population(grid), 5);
+            assert_eq!(conway_grid_get(grid, 1, 0), 1);
+            conway_grid_free(grid);
+
+            assert!(conway_grid_load_rle(text.as_ptr(), 10).is_null());
+            let invalid = [b'x', 0xff, b'!'];
+            assert!(conway_grid_load_rle(invalid.as_ptr(), invalid.len()).is_null());
+        }
+    }
+
+    #[test]
+    fn test_cells_copy_in_and_out_in_bulk() {
+        let grid = conway_grid_new(3, 2);
+        let mut cells = [0u8; 6];
+        unsafe {
+            assert_eq!(
+                conway_grid_write_cells(grid, [0, 1, 0, 7, 0, 1].as_ptr(), 6),
+                ConwayStatus::Ok
+            );
+            assert_eq!(conway_grid_population(grid), 3);
+            assert_eq!(conway_grid_get(grid, 0, 1), 1);
+            assert_eq!(
+                conway_grid_read_cells(grid, cells.as_mut_ptr(), 6),
+                ConwayStatus::Ok
+            );
+            assert_eq!(cells, [0, 1, 0, 1, 0, 1]);
+
+            // A buffer of any other size is refused, touching nothing
+            assert_eq!(
+                conway_grid_read_cells(grid, cells.as_mut_ptr(), 5),
+                ConwayStatus::BufferSize
+            );
+            assert_eq!(
+                conway_grid_write_cells(grid, [0; 7].as_ptr(), 7),
+                ConwayStatus::BufferSize
+            );
+            assert_eq!(conway_grid_population(grid), 3);
+            assert_eq!(
+                conway_grid_read_cells(grid, ptr::null_mut(), 6),
+                ConwayStatus::NullPointer
+            );
+            assert_eq!(
+                conway_grid_write_cells(ptr::null_mut(), cells.as_ptr(), 6),
+                ConwayStatus::NullPointer
+            );
+            conway_grid_free(grid);
+        }
+    }
+
+    #[test]
+    fn test_rules_as_masks() {
+        let grid = conway_grid_new(5, 5);
+        let (mut birth, mut survival) = (0, 0);
+        unsafe {
+            assert_eq!(
+                conway_grid_rule(grid, &mut birth, &mut survival),
+                ConwayStatus::Ok
+            );
+            assert_eq!((birth, survival), Rule::conway().masks());
+
+            let text = b"B36/S23";
+            assert_eq!(
+                conway_rule_parse(text.as_ptr(), text.len(), &mut birth, &mut survival),
+                ConwayStatus::Ok
+            );
+            assert_eq!(
+                conway_grid_set_rule(grid, birth, survival),
+                ConwayStatus::Ok
+            );
+            assert_eq!((*grid).rule().to_string(), "B36/S23");
+
+            for text in [&b"B9/S23"[..], b"B3", &[b'B', 0xff]] {
+                assert_eq!(
+                    conway_rule_parse(text.as_ptr(), text.len(), &mut birth, &mut survival),
+                    ConwayStatus::InvalidRule
+                );
+            }
+            assert_eq!(
+                conway_grid_set_rule(grid, 1 << 9, 0),
+                ConwayStatus::InvalidRule
+            );
+            assert_eq!((*grid).rule().to_string(), "B36/S23");
+            assert_eq!(
+                conway_grid_rule(grid, ptr::null_mut(), &mut survival),
+                ConwayStatus::NullPointer
+            );
+            assert_eq!(
+                conway_grid_set_rule(ptr::null_mut(), 0, 0),
+                ConwayStatus::NullPointer
+            );
+            conway_grid_free(grid);
+        }
+    }
+
+    #[test]
+    fn test_the_lifespan_of_a_board_leaves_it_alone() {
+        // An R-pentomino, which a 20x20 board cuts short
+        let text = b"x = 3, y = 3\nb2o$2o$bo!";
+        let mut lifespan = ConwayLifespan::default();
+        unsafe {
+            let pattern = conway_grid_load_rle(text.as_ptr(), text.len());
+            let grid = Box::into_raw(Box::new(Grid::centered(&*pattern, 20, 20)));
+            assert_eq!(
+                conway_grid_lifespan(grid, 1000, &mut lifespan),
+                ConwayStatus::Ok
+            );
+            assert!(lifespan.settled);
+            assert_eq!(lifespan.generation, lifespan.settled_at + lifespan.period);
+            assert!(lifespan.peak_population > 5 && lifespan.peak_generation > 0);
+            assert_eq!(conway_grid_generation(grid), 0);
+            assert_eq!(conway_grid_population(grid), 5);
+
+            // Cut short before it settles
+            assert_eq!(
+                conway_grid_lifespan(grid, 3, &mut lifespan),
+                ConwayStatus::Ok
+            );
+            assert!(!lifespan.settled);
+            assert_eq!(lifespan.generation, 3);
+            assert_eq!(
+                conway_grid_lifespan(grid, CONWAY_GRID_MAX_STEP + 1, &mut lifespan),
+                ConwayStatus::TooLarge
+            );
+            assert_eq!(
+                conway_grid_lifespan(grid, 1, ptr::null_mut()),
+                ConwayStatus::NullPointer
+            );
+            conway_grid_free(pattern);
+            conway_grid_free(grid);
+        }
+
+        // A board with nothing on it is a still life from the start
+        let empty = conway_grid_new(4, 4);
+        unsafe {
+            assert_eq!(
+                conway_grid_lifespan(empty, 10, &mut lifespan),
+                ConwayStatus::Ok
+            );
+            conway_grid_free(empty);
+        }
+        assert_eq!(
+            lifespan,
+            ConwayLifespan {
+                generation: 1,
+                settled: true,
+                settled_at: 0,
+                period: 1,
+                ..ConwayLifespan::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_the_census_asks_for_room_first() {
+        let text = b"x = 12, y = 3\n2o3b2o3bo$2o3b2o3bo$10bo!";
+        let mut written = 0;
+        unsafe {
+            let grid = conway_grid_load_rle(text.as_ptr(), text.len());
+            assert_eq!(
+                conway_grid_census(grid, ptr::null_mut(), 0, &mut written),
+                ConwayStatus::BufferSize
+            );
+            let expected = "2 block\n1 blinker\n";
+            assert_eq!(written, expected.len());
+            let mut buffer = vec![0xaa; written + 1];
+            assert_eq!(
+                conway_grid_census(grid, buffer.as_mut_ptr(), written, &mut written),
+                ConwayStatus::BufferSize
+            );
+            assert_eq!(
+                conway_grid_census(grid, buffer.as_mut_ptr(), buffer.len(), &mut written),
+                ConwayStatus::Ok
+            );
+            assert_eq!(&buffer[..written], expected.as_bytes());
+            assert_eq!(buffer[written], 0);
+
+            (*grid).set(0, 2, CellState::Alive);
+            let mut buffer = [0; 64];
+            conway_grid_census(grid, buffer.as_mut_ptr(), buffer.len(), &mut written);
+            // The block the cell touches is no known object anymore
+            assert_eq!(&buffer[..written], b"1 unidentified\n1 blinker\n1 block\n");
+            assert_eq!(
+                conway_grid_census(grid, buffer.as_mut_ptr(), 64, ptr::null_mut()),
+                ConwayStatus::NullPointer
+            );
+            conway_grid_free(grid);
+        }
+    }
+
+    #[test]
+    fn test_panics_stay_on_the_rust_side() {
+        assert_eq!(guard(7, || panic!("boom")), 7);
+        assert_eq!(guard(7, || 3), 3);
+    }
+
+    #[test]
+    fn test_the_c_example_matches_the_header() {
+        // Compiled only, since linking needs the library built as a C one;
+        // skipped where there is no C compiler
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ffi");
+        let status = std::process::Command::new("cc")
+            .args(["-std=c99", "-Wall", "-Werror", "-fsyntax-only", "-I"])
+            .arg(&dir)
+            .arg(dir.join("example.c"))
+            .status();
+        if let Ok(status) = status {
+            assert!(status.success(), "ffi/example.c doesn't compile");
+        }
+    }
+}
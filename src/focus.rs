@@ -0,0 +1,114 @@
+/// Frames longer than this are taken to mean the window was hidden or
+/// minimized in between, in seconds
+/// The graphical frontend gets no focus events, but most platforms stop
+/// drawing windows nobody can see.
+pub const AWAY_GAP: f32 = 1.0;
+
+/// Whether a frame of `dt` seconds means the window was away in between
+pub fn was_away(dt: f32) -> bool {
+    dt > AWAY_GAP
+}
+
+/// What pausing on focus loss asks of the game after a focus change
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FocusAction {
+    None,
+    Pause,
+    Resume,
+}
+
+/// Pauses the simulation while the window doesn't have focus
+///
+/// Only a simulation that was running when focus was lost is resumed when it
+/// comes back: pausing by hand beforehand, or resuming by hand in between,
+/// leaves the state to the player.
+#[derive(Clone, Debug)]
+pub struct FocusPause {
+    focused: bool,
+    /// Whether the current pause is ours, to undo once focus comes back
+    paused_it: bool,
+}
+
+impl FocusPause {
+    pub fn new() -> Self {
+        Self {
+            focused: true,
+            paused_it: false,
+        }
+    }
+
+    /// Note a change of focus, given whether the simulation is running
+    /// Repeated events for the same focus are ignored.
+    pub fn focus(&mut self, focused: bool, running: bool) -> FocusAction {
+        if focused == self.focused {
+            return FocusAction::None;
+        }
+        self.focused = focused;
+        if !focused {
+            self.paused_it = running;
+            return if running {
+                FocusAction::Pause
+            } else {
+                FocusAction::None
+            };
+        }
+        let resume = self.paused_it && !running;
+        self.paused_it = false;
+        if resume {
+            FocusAction::Resume
+        } else {
+            FocusAction::None
+        }
+    }
+
+    /// The player paused or resumed by hand, which wins over pausing on focus loss
+    pub fn forget(&mut self) {
+        self.paused_it = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pauses_and_resumes_running_simulation() {
+        let mut focus = FocusPause::new();
+        assert_eq!(focus.focus(false, true), FocusAction::Pause);
+        // A repeated loss doesn't forget who paused
+        assert_eq!(focus.focus(false, false), FocusAction::None);
+        assert_eq!(focus.focus(true, false), FocusAction::Resume);
+        assert_eq!(focus.focus(true, true), FocusAction::None);
+    }
+
+    #[test]
+    fn test_keeps_pause_by_hand() {
+        let mut focus = FocusPause::new();
+        assert_eq!(focus.focus(false, false), FocusAction::None);
+        assert_eq!(focus.focus(true, false), FocusAction::None);
+    }
+
+    #[test]
+    fn test_resumed_by_hand_while_away() {
+        let mut focus = FocusPause::new();
+        assert_eq!(focus.focus(false, true), FocusAction::Pause);
+        // Already running again when focus comes back, and it stays that way
+        assert_eq!(focus.focus(true, true), FocusAction::None);
+
+        // The next loss starts over
+        assert_eq!(focus.focus(false, true), FocusAction::Pause);
+        assert_eq!(focus.focus(true, false), FocusAction::Resume);
+
+        // Resuming and pausing again by hand makes the pause the player's
+        assert_eq!(focus.focus(false, true), FocusAction::Pause);
+        focus.forget();
+        assert_eq!(focus.focus(true, false), FocusAction::None);
+    }
+
+    #[test]
+    fn test_was_away() {
+        assert!(!was_away(1.0 / 60.0));
+        assert!(!was_away(AWAY_GAP));
+        assert!(was_away(AWAY_GAP * 3.0));
+    }
+}
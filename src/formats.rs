@@ -0,0 +1,267 @@
+pub mod life106;
+pub mod macrocell;
+pub mod plaintext;
+pub mod rle;
+
+#[cfg(feature = "std")]
+use crate::conways::Grid;
+#[cfg(feature = "std")]
+use crate::error::ConwayError;
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
+
+/// File extensions `load_file` knows how to parse
+pub const SUPPORTED_EXTENSIONS: &[&str] =
+    &["rle", "cells", "life", "lif", "json", "lifesnap", "mc"];
+
+/// Most cells a decoded pattern may have, as many as on the largest board the
+/// game opens; bigger sizes in a file are refused before anything is allocated
+pub const MAX_PATTERN_CELLS: usize = 4096 * 4096;
+
+/// Error produced when a pattern file cannot be parsed
+/// `line` is the 1-based line of the input where the problem was found
+#[derive(Debug, PartialEq, Eq)]
+pub struct FormatError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl FormatError {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl core::error::Error for FormatError {}
+
+/// A `FormatError` at `line` when a `width` by `height` pattern has more than
+/// `MAX_PATTERN_CELLS` cells, a side of 0 counting as 1 since every row costs
+/// memory even when empty
+fn check_size(line: usize, width: usize, height: usize) -> Result<(), FormatError> {
+    match width.max(1).checked_mul(height.max(1)) {
+        Some(cells) if cells <= MAX_PATTERN_CELLS => Ok(()),
+        _ => Err(FormatError::new(
+            line,
+            format!("a {width}x{height} pattern is larger than {MAX_PATTERN_CELLS} cells"),
+        )),
+    }
+}
+
+/// The lowercase extension of `path`, or an `InvalidArgument` error when it
+/// matches none of `SUPPORTED_EXTENSIONS`
+#[cfg(feature = "std")]
+fn extension(path: &Path) -> Result<String, ConwayError> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if SUPPORTED_EXTENSIONS.contains(&extension.as_str()) {
+        Ok(extension)
+    } else {
+        Err(ConwayError::InvalidArgument(format!(
+            "unsupported file type '{extension}'"
+        )))
+    }
+}
+
+/// Load a pattern file, picking the parser from the file extension
+/// - `.rle`: Run Length Encoded
+/// - `.cells`: Plaintext
+/// - `.life`, `.lif`: Life 1.06
+/// - `.json`: The JSON form, see `conways::json`
+/// - `.lifesnap`: A binary snapshot, see `conways::snapshot`
+///
+/// # Arguments
+/// * `path` - The file to load
+///
+/// # Returns
+/// The decoded `Grid`, or a `ConwayError` describing what went wrong
+#[cfg(feature = "std")]
+pub fn load_file(path: &Path) -> Result<Grid, ConwayError> {
+    let grid = decode_file(path)?;
+    crate::log::info!(
+        "loaded {} ({}x{}, {} live cells)",
+        path.display(),
+        grid.width(),
+        grid.height(),
+        grid.population()
+    );
+    Ok(grid)
+}
+
+#[cfg(feature = "std")]
+fn decode_file(path: &Path) -> Result<Grid, ConwayError> {
+    let extension = extension(path)?;
+    if extension == "lifesnap" {
+        return Grid::read_snapshot(fs::File::open(path)?);
+    }
+    let text = fs::read_to_string(path)?;
+    if extension == "json" {
+        return Grid::from_json(&text);
+    }
+    let (format, decoded) = match extension.as_str() {
+        "rle" => ("RLE", rle::decode(&text)),
+        "cells" => ("plaintext", plaintext::decode(&text)),
+        "mc" => ("macrocell", macrocell::decode(&text)),
+        _ => ("Life 1.06", life106::decode(&text)),
+    };
+    decoded.map_err(|err| ConwayError::parse(format, err))
+}
+
+/// Save a grid, picking the format from the file extension like `load_file`
+///
+/// # Arguments
+/// * `grid` - The grid to save
+/// * `path` - The file to write
+/// * `comments` - Lines of metadata, kept in the formats that support comments
+#[cfg(feature = "std")]
+pub fn save_file(grid: &Grid, path: &Path, comments: &[String]) -> Result<(), ConwayError> {
+    let extension = extension(path)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if extension == "lifesnap" {
+        let mut out = std::io::BufWriter::new(fs::File::create(path)?);
+        grid.write_snapshot(&mut out)?;
+        return Ok(std::io::Write::flush(&mut out)?);
+    }
+
+    let text = match extension.as_str() {
+        "rle" => rle::encode(grid, &grid.rule().to_string(), comments),
+        "cells" => plaintext::encode(grid),
+        "json" => grid.to_json(),
+        "mc" => macrocell::encode(grid, comments),
+        _ => life106::encode(grid),
+    };
+    fs::write(path, text)?;
+    Ok(())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A scratch file in the system temp directory, removed when dropped
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, contents: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("conways-{}-{name}", std::process::id()));
+            fs::write(&path, contents).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_dispatch_on_extension() {
+        let rle = TempFile::new("glider.rle", "x = 3, y = 3\nbo$2bo$3o!");
+        let cells = TempFile::new("glider.cells", "!Glider\n.O\n..O\nOOO");
+        let life = TempFile::new("glider.LIF", "#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2");
+
+        let (expected, records) = crate::log::capture(|| load_file(&rle.0).unwrap());
+        assert_eq!(expected.population(), 5);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].level, crate::log::Level::Info);
+        assert!(records[0]
+            .message
+            .ends_with("glider.rle (3x3, 5 live cells)"));
+        assert_eq!(load_file(&cells.0).unwrap(), expected);
+        assert_eq!(load_file(&life.0).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_save_file_round_trips() {
+        let rle = TempFile::new("saved.rle", "");
+        let cells = TempFile::new("saved.cells", "");
+        let life = TempFile::new("saved.lif", "");
+        let json = TempFile::new("saved.json", "");
+        let macrocell = TempFile::new("saved.mc", "");
+        let grid = load_file(&TempFile::new("glider.rle", "x = 3, y = 3\nbo$2bo$3o!").0).unwrap();
+
+        for file in [&rle, &cells, &life, &json, &macrocell] {
+            save_file(&grid, &file.0, &[]).unwrap();
+            assert_eq!(load_file(&file.0).unwrap(), grid);
+        }
+        // Snapshots keep the whole board, the generation included
+        let mut stepped = grid.clone();
+        stepped.step_n(3);
+        let snapshot = TempFile::new("saved.lifesnap", "");
+        save_file(&stepped, &snapshot.0, &[]).unwrap();
+        let loaded = load_file(&snapshot.0).unwrap();
+        assert_eq!((loaded.generation(), loaded), (3, stepped));
+        assert!(matches!(
+            save_file(&grid, Path::new("glider.png"), &[]),
+            Err(ConwayError::InvalidArgument(msg)) if msg == "unsupported file type 'png'"
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_extension() {
+        let file = TempFile::new("glider.txt", ".O");
+        assert!(matches!(
+            load_file(&file.0),
+            Err(ConwayError::InvalidArgument(msg)) if msg == "unsupported file type 'txt'"
+        ));
+    }
+
+    #[test]
+    fn test_corrupt_file_reports_parse_error() {
+        let file = TempFile::new("corrupt.rle", "x = 3, y = 3\nbo$2bo$3q!");
+        // Failing is the caller's to report, not worth a second message
+        let (err, records) = crate::log::capture(|| load_file(&file.0).unwrap_err());
+        assert!(records.is_empty());
+        assert!(matches!(
+            &err,
+            ConwayError::ParseError {
+                format: "RLE",
+                line: 2,
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "RLE parse error at line 2: unexpected character 'q'"
+        );
+    }
+
+    #[test]
+    fn test_fuzzing_corpus_is_valid() {
+        // The seeds the fuzzer mutates, which are only useful if they parse
+        let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus");
+        let mut seeds = 0;
+        for target in fs::read_dir(corpus).unwrap() {
+            for seed in fs::read_dir(target.unwrap().path()).unwrap() {
+                let path = seed.unwrap().path();
+                assert!(load_file(&path).is_ok(), "{} doesn't load", path.display());
+                seeds += 1;
+            }
+        }
+        assert!(seeds >= 9);
+    }
+
+    #[test]
+    fn test_missing_file_reports_io_error() {
+        let path = std::env::temp_dir().join("conways-does-not-exist.rle");
+        assert!(matches!(load_file(&path), Err(ConwayError::Io(_))));
+    }
+}
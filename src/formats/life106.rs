@@ -0,0 +1,129 @@
+use super::{check_size, FormatError};
+use crate::backend::GridBackend;
+use crate::conways::CellState;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Header line every Life 1.06 file starts with
+const HEADER: &str = "#Life 1.06";
+
+/// Encode a grid as Life 1.06, listing the live cells row by row
+pub fn encode(grid: &(impl GridBackend + ?Sized)) -> String {
+    let mut out = format!("{HEADER}\n");
+    for (x, y) in grid.live_cells() {
+        out.push_str(&format!("{x} {y}\n"));
+    }
+    out
+}
+
+/// Decode a Life 1.06 (`.life`/`.lif`) pattern into a board of any backend just
+/// large enough to hold it
+///
+/// The format lists one `x y` coordinate pair per live cell. Coordinates can be
+/// negative, so the cells are translated to make the top-left corner of their
+/// bounding box the origin of the grid, which may hold at most
+/// `MAX_PATTERN_CELLS` cells.
+///
+/// # Arguments
+/// * `input` - The Life 1.06 text
+///
+/// # Returns
+/// The decoded board, or a `FormatError` pointing at the offending line
+///
+/// see more: <https://conwaylife.com/wiki/Life_1.06>
+pub fn decode<B: GridBackend>(input: &str) -> Result<B, FormatError> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()));
+
+    match lines.next() {
+        Some((_, HEADER)) => (),
+        _ => return Err(FormatError::new(1, format!("expected '{HEADER}' header"))),
+    }
+
+    let mut cells = Vec::new();
+    for (line_number, line) in lines {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut coords = line.split_whitespace().map(|n| n.parse::<i64>());
+        match (coords.next(), coords.next(), coords.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => cells.push((x, y)),
+            _ => {
+                return Err(FormatError::new(
+                    line_number,
+                    format!("expected 'x y' coordinates, found '{line}'"),
+                ))
+            }
+        }
+    }
+
+    // Sides of the bounding box, in `i128` since coordinates far apart can be
+    // further apart than an `i64` holds
+    let side = |coords: &mut dyn Iterator<Item = i64>| {
+        let (min, max) = coords.fold((i64::MAX, i64::MIN), |(min, max), n| {
+            (min.min(n), max.max(n))
+        });
+        (min, (max as i128 - min as i128 + 1).max(0))
+    };
+    let (min_x, width) = side(&mut cells.iter().map(|(x, _)| *x));
+    let (min_y, height) = side(&mut cells.iter().map(|(_, y)| *y));
+    let size = |side: i128| usize::try_from(side).unwrap_or(usize::MAX);
+    let (width, height) = (size(width), size(height));
+    check_size(0, width, height)?;
+
+    let mut grid = B::with_size(width, height);
+    for (x, y) in cells {
+        grid.set((x - min_x) as usize, (y - min_y) as usize, CellState::Alive);
+    }
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::Grid;
+
+    #[test]
+    fn test_encode_round_trip() {
+        let mut grid = Grid::new(3, 3);
+        grid.set(1, 0, CellState::Alive);
+        grid.set(0, 2, CellState::Alive);
+        grid.set(2, 2, CellState::Alive);
+
+        let text = encode(&grid);
+        assert_eq!(text, "#Life 1.06\n1 0\n0 2\n2 2\n");
+        assert_eq!(decode::<Grid>(&text).unwrap(), grid);
+    }
+
+    #[test]
+    fn test_decode_negative_coordinates() {
+        let grid = decode::<Grid>("#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 3));
+        assert_eq!(grid.population(), 5);
+        assert_eq!(grid.get(1, 0), CellState::Alive);
+        assert_eq!(grid.get(0, 2), CellState::Alive);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(decode::<Grid>("0 0\n").unwrap_err().line, 1);
+        assert_eq!(
+            decode::<Grid>("#Life 1.06\n0 0\n1 x\n").unwrap_err(),
+            FormatError::new(3, "expected 'x y' coordinates, found '1 x'")
+        );
+    }
+
+    #[test]
+    fn test_decode_refuses_absurd_sizes() {
+        assert_eq!(
+            decode::<Grid>("#Life 1.06\n0 0\n5000 5000\n").unwrap_err(),
+            FormatError::new(0, "a 5001x5001 pattern is larger than 16777216 cells")
+        );
+        // Found fuzzing: the width of cells this far apart overflowed an i64
+        let far = "#Life 1.06\n-9223372036854775808 0\n9223372036854775807 0\n";
+        assert!(decode::<Grid>(far).is_err());
+    }
+}
@@ -0,0 +1,533 @@
+//! Golly's macrocell format (`.mc`), the quadtree HashLife steps written out
+//! node by node, so enormous sparse patterns stay small
+//!
+//! After the `[M2]` header and `#` lines, of which `#R` gives the rule and
+//! `#G` the generation, every line is a node, numbered from 1 in order:
+//! - A leaf, 8x8 cells: rows of `.` and `*` each ending with `$`, trailing
+//!   dead cells and rows left out, e.g. `.*$..*$***$` for a glider
+//! - `k nw ne sw se`, a square of 2^k cells, `k` from 4 up, made of the
+//!   four quadrants numbered, each a node of level `k - 1` (leaves are level
+//!   3) from an earlier line, or 0 for an empty one
+//!
+//! The last node is the whole pattern. Equal quadrants are written once and
+//! referred to wherever they appear.
+//!
+//! see more: <https://conwaylife.com/wiki/Macrocell>
+
+use super::{check_size, FormatError};
+use crate::backend::GridBackend;
+use crate::conways::{CellState, Grid};
+use crate::rule::Rule;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// Header line every macrocell file starts with
+const HEADER: &str = "[M2]";
+
+/// Level of the leaves, squares of 2^3 cells
+const LEAF_LEVEL: u32 = 3;
+
+/// Highest level read, the biggest whose cells a `u64` can number
+const MAX_LEVEL: u32 = 63;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Node {
+    /// 8x8 cells, a byte per row, the lowest bit the leftmost cell
+    Leaf([u8; 8]),
+    /// A square of 2^`level` cells, its quadrants in the order nw, ne, sw,
+    /// se, each 0 when empty or the number of a node a level down
+    Branch { level: u32, children: [usize; 4] },
+}
+
+impl Node {
+    fn level(&self) -> u32 {
+        match self {
+            Node::Leaf(_) => LEAF_LEVEL,
+            Node::Branch { level, .. } => *level,
+        }
+    }
+}
+
+/// The top-left and bottom-right cells of a box, both in it
+pub type Corners = ((u64, u64), (u64, u64));
+
+/// Where quadrant `i` of a node of `level` starts, relative to the node
+fn quadrant(level: u32, i: usize) -> (u64, u64) {
+    let half = 1 << (level - 1);
+    (half * (i as u64 % 2), half * (i as u64 / 2))
+}
+
+/// A pattern as a macrocell quadtree, which can be far bigger than any grid
+/// since its cells are never laid out unless asked for
+#[derive(Clone, Debug, PartialEq)]
+pub struct Macrocell {
+    /// Every node, node `n` at `n - 1` and the last one the root
+    nodes: Vec<Node>,
+    pub rule: Rule,
+    pub generation: u64,
+}
+
+impl Macrocell {
+    /// The live cells of `grid` as a quadtree, with its rule and generation
+    pub fn from_grid(grid: &Grid) -> Self {
+        let side = grid.width().max(grid.height()).max(1);
+        // At least level 4, so the root is a node like any other
+        let level = (usize::BITS - (side - 1).leading_zeros()).max(LEAF_LEVEL + 1);
+        let mut builder = Builder {
+            grid,
+            nodes: Vec::new(),
+            known: BTreeMap::new(),
+        };
+        builder.build(0, 0, level);
+        Self {
+            nodes: builder.nodes,
+            rule: grid.rule(),
+            generation: grid.generation(),
+        }
+    }
+
+    /// Read a macrocell file, see the [module docs](self)
+    ///
+    /// # Returns
+    /// The pattern, or a `FormatError` pointing at a line that isn't a node,
+    /// refers to a node not defined yet or of the wrong level, or gives a
+    /// rule or generation that can't be read
+    pub fn parse(input: &str) -> Result<Self, FormatError> {
+        let mut lines = input
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+        match lines.next() {
+            Some((_, header)) if header.starts_with(HEADER) => (),
+            _ => return Err(FormatError::new(1, format!("expected '{HEADER}' header"))),
+        }
+
+        let mut macrocell = Self {
+            nodes: Vec::new(),
+            rule: Rule::conway(),
+            generation: 0,
+        };
+        for (line_number, line) in lines {
+            let error = |msg: String| FormatError::new(line_number, msg);
+            if let Some(rule) = line.strip_prefix("#R") {
+                macrocell.rule = rule
+                    .trim()
+                    .parse()
+                    .map_err(|_| error(format!("unsupported rule '{}'", rule.trim())))?;
+            } else if let Some(generation) = line.strip_prefix("#G") {
+                macrocell.generation = generation
+                    .trim()
+                    .parse()
+                    .map_err(|_| error(format!("invalid generation '{}'", generation.trim())))?;
+            } else if line.starts_with('#') {
+                continue;
+            } else if line.starts_with(['.', '*', '$']) {
+                macrocell.nodes.push(parse_leaf(line).map_err(error)?);
+            } else {
+                let node = parse_branch(line, &macrocell.nodes).map_err(error)?;
+                macrocell.nodes.push(node);
+            }
+        }
+        Ok(macrocell)
+    }
+
+    /// The macrocell text of the pattern, with `comments` as `#C` lines
+    pub fn encode(&self, comments: &[String]) -> String {
+        let mut out = format!("{HEADER} (game-of-life)\n#R {}\n", self.rule);
+        if self.generation != 0 {
+            let _ = writeln!(out, "#G {}", self.generation);
+        }
+        for comment in comments {
+            let _ = writeln!(out, "#C {comment}");
+        }
+        for node in &self.nodes {
+            match node {
+                Node::Leaf(rows) => {
+                    let last_row = rows.iter().rposition(|row| *row != 0).unwrap_or(0);
+                    for row in &rows[..=last_row] {
+                        for x in 0..8 - row.leading_zeros() {
+                            out.push(if row & (1 << x) != 0 { '*' } else { '.' });
+                        }
+                        out.push('$');
+                    }
+                }
+                Node::Branch { level, children } => {
+                    let [nw, ne, sw, se] = children;
+                    let _ = write!(out, "{level} {nw} {ne} {sw} {se}");
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Level of the root, the pattern being a square of 2^level cells; 0
+    /// for an empty pattern
+    pub fn level(&self) -> u32 {
+        self.nodes.last().map_or(0, Node::level)
+    }
+
+    /// Number of live cells, counted node by node rather than cell by cell
+    pub fn population(&self) -> u128 {
+        let mut counts: Vec<u128> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let count = match node {
+                Node::Leaf(rows) => rows.iter().map(|row| row.count_ones() as u128).sum(),
+                Node::Branch { children, .. } => children
+                    .iter()
+                    .filter(|&&child| child != 0)
+                    .map(|&child| counts[child - 1])
+                    .sum(),
+            };
+            counts.push(count);
+        }
+        counts.last().copied().unwrap_or(0)
+    }
+
+    /// The top-left and bottom-right live cells of the bounding box of the
+    /// live cells, `None` when there are none, worked out node by node
+    pub fn bounds(&self) -> Option<Corners> {
+        let mut bounds: Vec<Option<Corners>> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let mut corners = None;
+            let mut include = |min: (u64, u64), max: (u64, u64)| {
+                corners = Some(match corners {
+                    None => (min, max),
+                    Some(((x0, y0), (x1, y1))) => (
+                        (min.0.min(x0), min.1.min(y0)),
+                        (max.0.max(x1), max.1.max(y1)),
+                    ),
+                });
+            };
+            match node {
+                Node::Leaf(rows) => {
+                    for (y, row) in rows.iter().enumerate() {
+                        for x in (0..8).filter(|x| row & (1 << x) != 0) {
+                            include((x, y as u64), (x, y as u64));
+                        }
+                    }
+                }
+                Node::Branch { level, children } => {
+                    for (i, &child) in children.iter().enumerate() {
+                        if let Some(Some((min, max))) = child.checked_sub(1).map(|c| bounds[c]) {
+                            let (dx, dy) = quadrant(*level, i);
+                            include((min.0 + dx, min.1 + dy), (max.0 + dx, max.1 + dy));
+                        }
+                    }
+                }
+            }
+            bounds.push(corners);
+        }
+        bounds.last().copied().flatten()
+    }
+
+    /// Every live cell, from the top-left corner of the root; as many as
+    /// `population` says, so only for patterns sparse enough to list
+    pub fn live_cells(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        let mut stack = Vec::from_iter(self.nodes.len().checked_sub(1).map(|root| (root, 0, 0)));
+        let mut leaf = Vec::new();
+        core::iter::from_fn(move || loop {
+            if let Some(cell) = leaf.pop() {
+                return Some(cell);
+            }
+            let (node, x, y) = stack.pop()?;
+            match &self.nodes[node] {
+                Node::Leaf(rows) => {
+                    for (dy, row) in rows.iter().enumerate().rev() {
+                        for dx in (0..8).rev().filter(|dx| row & (1 << dx) != 0) {
+                            leaf.push((x + dx, y + dy as u64));
+                        }
+                    }
+                }
+                Node::Branch { level, children } => {
+                    for (i, &child) in children.iter().enumerate().rev() {
+                        if child != 0 {
+                            let (dx, dy) = quadrant(*level, i);
+                            stack.push((child - 1, x + dx, y + dy));
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// The pattern on a board of any backend just large enough to hold its
+    /// live cells, with its rule and generation
+    ///
+    /// # Returns
+    /// The grid, or a `FormatError` when it would have more than
+    /// `MAX_PATTERN_CELLS` cells, found before laying any out
+    pub fn to_grid<B: GridBackend>(&self) -> Result<B, FormatError> {
+        let Some(((x0, y0), (x1, y1))) = self.bounds() else {
+            let mut grid = B::with_size(0, 0);
+            grid.set_rule(self.rule);
+            grid.set_generation(self.generation);
+            return Ok(grid);
+        };
+        let side = |from: u64, to: u64| usize::try_from(to - from + 1).unwrap_or(usize::MAX);
+        let (width, height) = (side(x0, x1), side(y0, y1));
+        check_size(0, width, height)?;
+
+        let mut grid = B::with_size(width, height);
+        for (x, y) in self.live_cells() {
+            grid.set((x - x0) as usize, (y - y0) as usize, CellState::Alive);
+        }
+        grid.set_rule(self.rule);
+        grid.set_generation(self.generation);
+        Ok(grid)
+    }
+}
+
+/// A leaf line, rows of `.` and `*` ending in `$`
+fn parse_leaf(line: &str) -> Result<Node, String> {
+    let mut rows = [0u8; 8];
+    let (mut x, mut y) = (0, 0);
+    for c in line.chars() {
+        match c {
+            '.' | '*' if x >= 8 => return Err("a leaf row is wider than 8 cells".into()),
+            '.' | '*' if y >= 8 => return Err("a leaf is taller than 8 rows".into()),
+            '.' => x += 1,
+            '*' => {
+                rows[y] |= 1 << x;
+                x += 1;
+            }
+            '$' => (x, y) = (0, y + 1),
+            c => return Err(format!("unexpected character '{c}' in a leaf")),
+        }
+    }
+    Ok(Node::Leaf(rows))
+}
+
+/// A `k nw ne sw se` line, its quadrants among the `nodes` read so far
+fn parse_branch(line: &str, nodes: &[Node]) -> Result<Node, String> {
+    let numbers: Vec<&str> = line.split_whitespace().collect();
+    let [level, nw, ne, sw, se] = numbers[..] else {
+        return Err(format!(
+            "expected a leaf or 'level nw ne sw se', found '{line}'"
+        ));
+    };
+    let level = match level.parse::<u32>() {
+        Ok(level) if (LEAF_LEVEL + 1..=MAX_LEVEL).contains(&level) => level,
+        _ => {
+            return Err(format!(
+                "invalid level '{level}', nodes go from {} to {MAX_LEVEL}",
+                LEAF_LEVEL + 1
+            ))
+        }
+    };
+    let mut children = [0usize; 4];
+    for (child, number) in children.iter_mut().zip([nw, ne, sw, se]) {
+        *child = number
+            .parse()
+            .map_err(|_| format!("invalid node number '{number}'"))?;
+        match child.checked_sub(1).map(|i| nodes.get(i)) {
+            None => (),
+            Some(None) => return Err(format!("node {child} isn't defined yet")),
+            Some(Some(node)) if node.level() != level - 1 => {
+                return Err(format!(
+                    "node {child} is level {}, a level {level} node needs level {}",
+                    node.level(),
+                    level - 1
+                ))
+            }
+            Some(Some(_)) => (),
+        }
+    }
+    Ok(Node::Branch { level, children })
+}
+
+/// Builds the quadtree of a grid, writing every distinct node once
+struct Builder<'a> {
+    grid: &'a Grid,
+    nodes: Vec<Node>,
+    known: BTreeMap<Node, usize>,
+}
+
+impl Builder<'_> {
+    /// The number of the node of `level` with its top-left corner at
+    /// `(x, y)`, 0 when it's empty
+    fn build(&mut self, x: usize, y: usize, level: u32) -> usize {
+        if x >= self.grid.width() || y >= self.grid.height() {
+            return 0;
+        }
+        let node = if level == LEAF_LEVEL {
+            let mut rows = [0u8; 8];
+            for (dy, row) in rows.iter_mut().enumerate() {
+                for dx in 0..8 {
+                    let (cx, cy) = (x + dx, y + dy);
+                    if cx < self.grid.width()
+                        && cy < self.grid.height()
+                        && self.grid.get(cx, cy) == CellState::Alive
+                    {
+                        *row |= 1 << dx;
+                    }
+                }
+            }
+            if rows == [0; 8] {
+                return 0;
+            }
+            Node::Leaf(rows)
+        } else {
+            let mut children = [0; 4];
+            for (i, child) in children.iter_mut().enumerate() {
+                let (dx, dy) = quadrant(level, i);
+                *child = self.build(x + dx as usize, y + dy as usize, level - 1);
+            }
+            if children == [0; 4] {
+                return 0;
+            }
+            Node::Branch { level, children }
+        };
+        if let Some(&number) = self.known.get(&node) {
+            return number;
+        }
+        self.nodes.push(node);
+        self.known.insert(node, self.nodes.len());
+        self.nodes.len()
+    }
+}
+
+/// Encode a grid's live cells as macrocell text, with its rule and
+/// generation, and `comments` as `#C` lines
+pub fn encode(grid: &Grid, comments: &[String]) -> String {
+    Macrocell::from_grid(grid).encode(comments)
+}
+
+/// Decode a macrocell pattern into a board of any backend just large enough
+/// to hold its live cells; `Macrocell::parse` reads patterns too big for one
+///
+/// # Returns
+/// The decoded board, or a `FormatError` pointing at the offending line
+pub fn decode<B: GridBackend>(input: &str) -> Result<B, FormatError> {
+    Macrocell::parse(input)?.to_grid()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns;
+
+    /// Two gliders and a block, 2^40 cells apart, written node by node
+    const SPARSE: &str = include_str!("../../tests/fixtures/sparse.mc");
+
+    fn error(input: &str) -> FormatError {
+        Macrocell::parse(input).unwrap_err()
+    }
+
+    #[test]
+    fn test_a_glider_gun_round_trips() {
+        let mut gun = patterns::find("gosper-glider-gun").unwrap().to_grid();
+        gun.set_rule("B36/S23".parse().unwrap());
+        gun.set_generation(30);
+        let text = encode(&gun, &["Gosper glider gun".into()]);
+        assert!(text.starts_with("[M2] (game-of-life)\n#R B36/S23\n#G 30\n#C Gosper glider gun\n"));
+        let read = decode::<Grid>(&text).unwrap();
+        assert_eq!(read, gun);
+        assert_eq!((read.rule(), read.generation()), (gun.rule(), 30));
+
+        // A soup, cropped to its live cells on the way back
+        let mut soup = Grid::new(100, 70);
+        soup.randomize(0.2, 8);
+        let read = decode::<Grid>(&encode(&soup, &[])).unwrap();
+        assert_eq!(read, soup.extract_region(soup.live_bounds().unwrap()));
+        assert_eq!(
+            decode::<Grid>(&encode(&Grid::new(5, 5), &[])).unwrap(),
+            Grid::new(0, 0)
+        );
+    }
+
+    #[test]
+    fn test_equal_quadrants_are_written_once() {
+        // Blocks 16 cells apart, each alone in its leaf
+        let mut grid = Grid::new(64, 64);
+        for y in (0..64).step_by(16) {
+            for x in (0..64).step_by(16) {
+                grid.insert_pattern(
+                    &patterns::find("block").unwrap().to_grid(),
+                    x,
+                    y,
+                    crate::conways::InsertMode::Overwrite,
+                );
+            }
+        }
+        let macrocell = Macrocell::from_grid(&grid);
+        // A leaf, then one node per level above it
+        assert_eq!(macrocell.nodes.len(), 4);
+        assert_eq!((macrocell.level(), macrocell.population()), (6, 64));
+        assert_eq!(macrocell.to_grid::<Grid>().unwrap().population(), 64);
+    }
+
+    #[test]
+    fn test_a_huge_sparse_pattern_loads_without_laying_out_cells() {
+        let sparse = Macrocell::parse(SPARSE).unwrap();
+        assert_eq!(sparse.level(), 40);
+        assert_eq!(sparse.population(), 14);
+        let far = (1 << 40) - 1;
+        assert_eq!(sparse.bounds(), Some(((0, 0), (far, far))));
+        let mut cells: Vec<(u64, u64)> = sparse.live_cells().collect();
+        cells.sort();
+        assert_eq!(cells.len(), 14);
+        assert_eq!(cells[..2], [(0, 2), (1, 0)]);
+        assert_eq!(cells[13], (far, far));
+        // Far too big for a grid, which is refused rather than allocated
+        assert!(sparse
+            .to_grid::<Grid>()
+            .unwrap_err()
+            .message
+            .contains("larger than"));
+        // Written back node for node
+        assert_eq!(Macrocell::parse(&sparse.encode(&[])).unwrap(), sparse);
+    }
+
+    #[test]
+    fn test_malformed_files_are_refused_by_line() {
+        assert_eq!(
+            error("x = 3, y = 3\n3o!"),
+            FormatError::new(1, "expected '[M2]' header")
+        );
+        assert_eq!(
+            error("[M2]\n.*$\n4 1 2 0 0"),
+            FormatError::new(3, "node 2 isn't defined yet")
+        );
+        assert_eq!(
+            error("[M2]\n.*$\n4 1 0 0 0\n4 0 0 2 0"),
+            FormatError::new(4, "node 2 is level 4, a level 4 node needs level 3")
+        );
+        assert_eq!(
+            error("[M2]\n3 0 0 0 0"),
+            FormatError::new(2, "invalid level '3', nodes go from 4 to 63")
+        );
+        assert_eq!(
+            error("[M2]\n#R B3/S23\n\n4 1 0 0"),
+            FormatError::new(4, "expected a leaf or 'level nw ne sw se', found '4 1 0 0'")
+        );
+        assert_eq!(
+            error("[M2]\n4 x 0 0 0"),
+            FormatError::new(2, "invalid node number 'x'")
+        );
+        assert_eq!(
+            error("[M2]\n.........*$"),
+            FormatError::new(2, "a leaf row is wider than 8 cells")
+        );
+        assert_eq!(
+            error("[M2]\n$$$$$$$$*$"),
+            FormatError::new(2, "a leaf is taller than 8 rows")
+        );
+        assert_eq!(
+            error("[M2]\n.*o$"),
+            FormatError::new(2, "unexpected character 'o' in a leaf")
+        );
+        assert_eq!(
+            error("[M2]\n#R LifeHistory"),
+            FormatError::new(2, "unsupported rule 'LifeHistory'")
+        );
+        assert_eq!(
+            error("[M2]\n#G -4"),
+            FormatError::new(2, "invalid generation '-4'")
+        );
+    }
+}
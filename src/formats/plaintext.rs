@@ -0,0 +1,114 @@
+use super::{check_size, FormatError};
+use crate::backend::GridBackend;
+use crate::conways::CellState;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Encode a grid as plaintext, one row per line with `O` for live cells
+/// Trailing dead cells of each row are left out, as is customary for the format.
+pub fn encode(grid: &(impl GridBackend + ?Sized)) -> String {
+    let mut out = String::new();
+    // The row being written and the column after its last live cell so far
+    let (mut row, mut column) = (0, 0);
+    for (x, y) in grid.live_cells() {
+        for _ in row..y {
+            out.push('\n');
+        }
+        if y > row {
+            (row, column) = (y, 0);
+        }
+        out.extend(core::iter::repeat_n('.', x - column));
+        out.push('O');
+        column = x + 1;
+    }
+    for _ in row..grid.height() {
+        out.push('\n');
+    }
+    out
+}
+
+/// Decode a plaintext (`.cells`) pattern into a board of any backend just
+/// large enough to hold it
+///
+/// Lines starting with `!` are comments, `O` is a live cell and `.` a dead one.
+/// Rows shorter than the widest row are padded with dead cells, and patterns of
+/// more than `MAX_PATTERN_CELLS` cells are refused.
+///
+/// # Arguments
+/// * `input` - The plaintext pattern
+///
+/// # Returns
+/// The decoded board, or a `FormatError` pointing at the offending line
+///
+/// see more: <https://conwaylife.com/wiki/Plaintext>
+pub fn decode<B: GridBackend>(input: &str) -> Result<B, FormatError> {
+    let rows: Vec<(usize, &str)> = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end()))
+        .filter(|(_, line)| !line.starts_with('!'))
+        .collect();
+
+    let width = rows.iter().map(|(_, row)| row.len()).max().unwrap_or(0);
+    check_size(0, width, rows.len())?;
+    let mut grid = B::with_size(width, rows.len());
+
+    for (y, (line_number, row)) in rows.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            match c {
+                'O' => grid.set(x, y, CellState::Alive),
+                '.' => (),
+                c => {
+                    return Err(FormatError::new(
+                        *line_number,
+                        format!("unexpected character '{c}'"),
+                    ))
+                }
+            }
+        }
+    }
+
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::Grid;
+
+    #[test]
+    fn test_encode_round_trip() {
+        let grid = decode::<Grid>(".O\n..O\nOOO\n...\n").unwrap();
+        let text = encode(&grid);
+        assert_eq!(text, ".O\n..O\nOOO\n\n");
+        assert_eq!(decode::<Grid>(&text).unwrap(), grid);
+    }
+
+    #[test]
+    fn test_decode_with_comments_and_ragged_rows() {
+        let grid = decode::<Grid>("!Name: Glider\n!\n.O\n..O\nOOO\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 3));
+        assert_eq!(grid.population(), 5);
+        assert_eq!(grid.get(1, 0), CellState::Alive);
+        assert_eq!(grid.get(2, 0), CellState::Dead);
+    }
+
+    #[test]
+    fn test_decode_rejects_unknown_characters() {
+        assert_eq!(
+            decode::<Grid>("!comment\n.O.\n.X.").unwrap_err(),
+            FormatError::new(3, "unexpected character 'X'")
+        );
+    }
+
+    #[test]
+    fn test_decode_refuses_absurd_sizes() {
+        // A long row and many short ones make a board far bigger than the text
+        let text = format!("{}\n{}", ".".repeat(100_000), "\n".repeat(200));
+        assert_eq!(
+            decode::<Grid>(&text).unwrap_err(),
+            FormatError::new(0, "a 100000x201 pattern is larger than 16777216 cells")
+        );
+    }
+}
@@ -0,0 +1,364 @@
+use super::{check_size, FormatError};
+use crate::backend::GridBackend;
+use crate::conways::CellState;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Maximum length of an encoded line, as recommended by the RLE specification
+const MAX_LINE_LENGTH: usize = 70;
+
+/// Encode a grid as Run Length Encoded (RLE) text
+///
+/// # Arguments
+/// * `grid` - The grid to encode
+/// * `rule` - The rulestring written in the header, e.g. `B3/S23`
+/// * `comments` - Lines written as `#C` comments before the header
+///
+/// # Returns
+/// The RLE text, including the `x = .., y = .., rule = ..` header
+///
+/// see more: <https://conwaylife.com/wiki/Run_Length_Encoded>
+pub fn encode(grid: &(impl GridBackend + ?Sized), rule: &str, comments: &[String]) -> String {
+    let mut out = String::new();
+    for comment in comments {
+        out.push_str(&format!("#C {comment}\n"));
+    }
+    out.push_str(&format!(
+        "x = {}, y = {}, rule = {}\n",
+        grid.width(),
+        grid.height(),
+        rule
+    ));
+
+    let mut line = String::new();
+    for token in tokens(grid) {
+        if line.len() + token.len() > MAX_LINE_LENGTH {
+            out.push_str(&line);
+            out.push('\n');
+            line.clear();
+        }
+        line.push_str(&token);
+    }
+    out.push_str(&line);
+    out.push('\n');
+    out
+}
+
+/// The cells of `grid` as RLE on a single line, without a header, for files
+/// that keep a board on a line of their own
+pub fn encode_line(grid: &(impl GridBackend + ?Sized)) -> String {
+    tokens(grid).concat()
+}
+
+/// Decode a board written by `encode_line`, given its size
+pub fn decode_line<B: GridBackend>(
+    width: usize,
+    height: usize,
+    line: &str,
+) -> Result<B, FormatError> {
+    decode(&format!("x = {width}, y = {height}\n{line}"))
+}
+
+/// The runs of every row of `grid`, ending with `!`
+fn tokens(grid: &(impl GridBackend + ?Sized)) -> Vec<String> {
+    // The runs of live cells as their row, first column and length
+    let mut runs: Vec<(usize, usize, usize)> = Vec::new();
+    for (x, y) in grid.live_cells() {
+        match runs.last_mut() {
+            Some((row, first, count)) if *row == y && *first + *count == x => *count += 1,
+            _ => runs.push((y, x, 1)),
+        }
+    }
+
+    // Trailing dead cells of a row and empty rows at the end are implied, so
+    // only the dead cells and row ends before a run are written
+    let mut tokens = Vec::new();
+    let (mut row, mut column) = (0, 0);
+    for (y, first, count) in runs {
+        if y > row {
+            tokens.push(run_token(y - row, '$'));
+            (row, column) = (y, 0);
+        }
+        if first > column {
+            tokens.push(run_token(first - column, 'b'));
+        }
+        tokens.push(run_token(count, 'o'));
+        column = first + count;
+    }
+    tokens.push("!".to_string());
+    tokens
+}
+
+/// Decode Run Length Encoded (RLE) text into a board of any backend, sized
+/// from its header
+///
+/// Comment lines (starting with `#`) are skipped. The header must declare the
+/// pattern size, at most `MAX_PATTERN_CELLS` cells, every live cell must fit
+/// inside it, and the pattern must end with `!` so truncated files are detected.
+///
+/// # Arguments
+/// * `input` - The RLE text
+///
+/// # Returns
+/// The decoded board, or a `FormatError` pointing at the offending line
+pub fn decode<B: GridBackend>(input: &str) -> Result<B, FormatError> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    let (header_line, header) = lines
+        .next()
+        .ok_or_else(|| FormatError::new(1, "missing RLE header"))?;
+    let (width, height) = parse_header(header_line, header)?;
+    check_size(header_line, width, height)?;
+    let mut grid = B::with_size(width, height);
+
+    let (mut x, mut y) = (0usize, 0usize);
+    let mut count: Option<usize> = None;
+
+    for (line_number, line) in lines {
+        for c in line.chars() {
+            match c {
+                '0'..='9' => {
+                    let digit = c.to_digit(10).unwrap_or(0) as usize;
+                    count = Some(
+                        count
+                            .unwrap_or(0)
+                            .checked_mul(10)
+                            .and_then(|n| n.checked_add(digit))
+                            .ok_or_else(|| FormatError::new(line_number, "run count overflow"))?,
+                    );
+                }
+                'b' | 'o' => {
+                    let run = count.take().unwrap_or(1);
+                    if c == 'o' {
+                        if y >= height || x.checked_add(run).is_none_or(|end| end > width) {
+                            return Err(FormatError::new(
+                                line_number,
+                                "pattern exceeds the size declared in the header",
+                            ));
+                        }
+                        for dx in 0..run {
+                            grid.set(x + dx, y, CellState::Alive);
+                        }
+                    }
+                    x = x.saturating_add(run);
+                }
+                '$' => {
+                    y = y.saturating_add(count.take().unwrap_or(1));
+                    x = 0;
+                }
+                '!' => return Ok(grid),
+                c if c.is_whitespace() => (),
+                c => {
+                    return Err(FormatError::new(
+                        line_number,
+                        format!("unexpected character '{c}'"),
+                    ))
+                }
+            }
+        }
+    }
+
+    Err(FormatError::new(
+        input.lines().count().max(1),
+        "missing '!' terminator",
+    ))
+}
+
+fn run_token(count: usize, tag: char) -> String {
+    if count == 1 {
+        tag.to_string()
+    } else {
+        format!("{count}{tag}")
+    }
+}
+
+/// Parse a `x = 3, y = 3, rule = B3/S23` header into the pattern dimensions
+fn parse_header(line_number: usize, header: &str) -> Result<(usize, usize), FormatError> {
+    let (mut width, mut height) = (None, None);
+
+    for field in header.split(',') {
+        let (key, value) = field.split_once('=').ok_or_else(|| {
+            FormatError::new(
+                line_number,
+                format!("malformed header field '{}'", field.trim()),
+            )
+        })?;
+        let parse_size = |value: &str| {
+            value.trim().parse::<usize>().map_err(|_| {
+                FormatError::new(line_number, format!("invalid size '{}'", value.trim()))
+            })
+        };
+        match key.trim() {
+            "x" => width = Some(parse_size(value)?),
+            "y" => height = Some(parse_size(value)?),
+            _ => (),
+        }
+    }
+
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height)),
+        _ => Err(FormatError::new(
+            line_number,
+            "header must declare both x and y",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::sparse::SparseGrid;
+    use crate::conways::Grid;
+
+    fn glider() -> Grid {
+        let mut grid = Grid::new(3, 3);
+        grid.set(1, 0, CellState::Alive);
+        grid.set(2, 1, CellState::Alive);
+        grid.set(0, 2, CellState::Alive);
+        grid.set(1, 2, CellState::Alive);
+        grid.set(2, 2, CellState::Alive);
+        grid
+    }
+
+    #[test]
+    fn test_encode_glider() {
+        let rle = encode(&glider(), "B3/S23", &[]);
+        assert_eq!(rle, "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+    }
+
+    #[test]
+    fn test_encode_skips_empty_rows() {
+        let mut grid = Grid::new(3, 4);
+        grid.set(0, 1, CellState::Alive);
+        grid.set(2, 3, CellState::Alive);
+
+        let rle = encode(&grid, "B3/S23", &["hello".to_string()]);
+        assert_eq!(rle, "#C hello\nx = 3, y = 4, rule = B3/S23\n$o2$2bo!\n");
+    }
+
+    #[test]
+    fn test_encode_takes_time_by_the_population() {
+        // Laid out a cell at a time, this board would never finish encoding
+        let side = 1 << 40;
+        let mut grid = SparseGrid::with_size(side, side);
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            grid.set(side / 2 + x, side / 2 + y, CellState::Alive);
+        }
+        let half = side / 2;
+        assert_eq!(
+            encode_line(&grid),
+            format!("{half}${}bo${}bo${half}b3o!", half + 1, half + 2)
+        );
+    }
+
+    #[test]
+    fn test_single_line_round_trip() {
+        assert_eq!(encode_line(&glider()), "bo$2bo$3o!");
+        assert_eq!(decode_line::<Grid>(3, 3, "bo$2bo$3o!").unwrap(), glider());
+        assert_eq!(decode_line::<Grid>(4, 3, "!").unwrap(), Grid::new(4, 3));
+        assert!(decode_line::<Grid>(2, 2, "3o!").is_err());
+    }
+
+    #[test]
+    fn test_decode_glider() {
+        let grid = decode::<Grid>("#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!").unwrap();
+        assert_eq!(grid, glider());
+    }
+
+    #[test]
+    fn test_decode_into_any_backend() {
+        use crate::backend::{self, bits::BitGrid};
+
+        let text = "x = 70, y = 3\nbo$2bo66bo$3o!";
+        let grid: Grid = decode(text).unwrap();
+        let bits: BitGrid = decode(text).unwrap();
+        let sparse: SparseGrid = decode(text).unwrap();
+        assert_eq!(backend::convert::<Grid>(&bits), grid);
+        assert_eq!(backend::convert::<Grid>(&sparse), grid);
+        assert_eq!((sparse.width(), sparse.population()), (70, 6));
+        assert!(decode::<SparseGrid>("x = 3, y = 1\n4o!").is_err());
+    }
+
+    #[test]
+    fn test_round_trip_wraps_long_lines() {
+        let mut grid = Grid::new(200, 5);
+        for x in (0..200).step_by(2) {
+            grid.set(x, 2, CellState::Alive);
+        }
+
+        let rle = encode(&grid, "B3/S23", &[]);
+        assert!(rle.lines().all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert_eq!(decode::<Grid>(&rle).unwrap(), grid);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(decode::<Grid>("").unwrap_err().line, 1);
+        assert_eq!(decode::<Grid>("x = 3\nooo!").unwrap_err().line, 1);
+        assert_eq!(
+            decode::<Grid>("x = 2, y = 1\nooo!").unwrap_err(),
+            FormatError::new(2, "pattern exceeds the size declared in the header")
+        );
+        assert_eq!(
+            decode::<Grid>("x = 3, y = 3\n#C comment\nbo$\n2bz!").unwrap_err(),
+            FormatError::new(4, "unexpected character 'z'")
+        );
+        assert_eq!(
+            decode::<Grid>("x = 3, y = 3\nbo$2bo$").unwrap_err(),
+            FormatError::new(2, "missing '!' terminator")
+        );
+    }
+
+    #[test]
+    fn test_absurd_sizes_are_refused() {
+        // Refused from the header, before allocating the board
+        assert_eq!(
+            decode::<Grid>(
+                "x = 100000, y = 100000
+!"
+            )
+            .unwrap_err(),
+            FormatError::new(1, "a 100000x100000 pattern is larger than 16777216 cells")
+        );
+        assert!(decode::<Grid>(
+            "x = 0, y = 18446744073709551615
+!"
+        )
+        .is_err());
+        assert!(decode::<Grid>(
+            "x = 18446744073709551615, y = 18446744073709551615
+!"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_huge_runs_do_not_overflow() {
+        // Found fuzzing: runs adding up past `usize::MAX` used to panic
+        let runs = "18446744073709551615b18446744073709551615bo!";
+        assert!(decode::<Grid>(&format!(
+            "x = 3, y = 3
+{runs}"
+        ))
+        .is_err());
+        let rows = "18446744073709551615$18446744073709551615$o!";
+        assert!(decode::<Grid>(&format!(
+            "x = 3, y = 3
+{rows}"
+        ))
+        .is_err());
+        assert_eq!(
+            decode::<Grid>(
+                "x = 3, y = 3
+18446744073709551615b!"
+            )
+            .unwrap(),
+            Grid::new(3, 3)
+        );
+    }
+}
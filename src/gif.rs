@@ -0,0 +1,388 @@
+use crate::conways::Grid;
+use crate::export;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory where recordings are written, relative to the working directory
+pub const RECORDING_DIR: &str = "recordings";
+
+/// Largest side, in pixels, of a recorded frame; bigger grids use a smaller cell scale
+pub const MAX_FRAME_SIDE: u32 = 640;
+
+/// Palette shared by every frame: index 0 is a dead cell, index 1 a live one
+const PALETTE: [u8; 6] = [0, 0, 0, 255, 255, 255];
+
+/// Largest code width allowed by the GIF flavour of LZW
+const MAX_CODE_BITS: u32 = 12;
+
+/// One recorded frame, as palette indices
+struct Frame {
+    pixels: Vec<u8>,
+    /// How long the frame is shown, in hundredths of a second
+    delay: u16,
+}
+
+/// Collects one frame per simulation step and encodes them as an animated GIF
+///
+/// Frames are rendered through the same path as PNG export. To keep memory in
+/// check the recorder refuses frames once `max_frames` have been collected.
+pub struct GifRecorder {
+    width: u32,
+    height: u32,
+    scale: u32,
+    max_frames: usize,
+    frames: Vec<Frame>,
+}
+
+impl GifRecorder {
+    /// Create a recorder for frames of a `grid_width` x `grid_height` grid
+    pub fn new(grid_width: usize, grid_height: usize, max_frames: usize) -> Self {
+        let longest = grid_width.max(grid_height).max(1) as u32;
+        let scale = (MAX_FRAME_SIDE / longest).clamp(1, export::SCREENSHOT_SCALE);
+        Self {
+            width: grid_width as u32 * scale,
+            height: grid_height as u32 * scale,
+            scale,
+            max_frames,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Append the current state of `grid` as a new frame
+    ///
+    /// # Arguments
+    /// * `grid` - The grid to capture, with the dimensions given to `new`
+    /// * `seconds` - How long the frame should be shown
+    ///
+    /// # Returns
+    /// `false` if the frame was dropped, because the recorder is full or the
+    /// grid no longer has the dimensions the recording was started with
+    pub fn push(&mut self, grid: &Grid, seconds: f32) -> bool {
+        let same_size = grid.width() as u32 * self.scale == self.width
+            && grid.height() as u32 * self.scale == self.height;
+        if self.is_full() || !same_size {
+            return false;
+        }
+        let image = export::grid_to_image(grid, self.scale);
+        let pixels = image.pixels().map(|p| u8::from(p.0[0] > 127)).collect();
+        // Most viewers clamp delays below 2/100 s to something much slower
+        let delay = (seconds * 100.0).round().clamp(2.0, u16::MAX as f32) as u16;
+        self.frames.push(Frame { pixels, delay });
+        true
+    }
+
+    /// Whether the frame limit has been reached
+    pub fn is_full(&self) -> bool {
+        self.frames.len() >= self.max_frames
+    }
+
+    /// Number of frames recorded so far
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Encode every recorded frame as a looping animated GIF
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(b"GIF89a");
+
+        // Logical screen descriptor with a 2 entry global color table
+        out.extend_from_slice(&(self.width as u16).to_le_bytes());
+        out.extend_from_slice(&(self.height as u16).to_le_bytes());
+        out.extend_from_slice(&[0x80, 0, 0]);
+        out.extend_from_slice(&PALETTE);
+
+        // NETSCAPE2.0 application extension: loop forever
+        out.extend_from_slice(&[0x21, 0xff, 0x0b]);
+        out.extend_from_slice(b"NETSCAPE2.0");
+        out.extend_from_slice(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+
+        for frame in &self.frames {
+            // Graphic control extension carrying the frame delay
+            out.extend_from_slice(&[0x21, 0xf9, 0x04, 0x00]);
+            out.extend_from_slice(&frame.delay.to_le_bytes());
+            out.extend_from_slice(&[0x00, 0x00]);
+
+            // Image descriptor covering the whole screen, no local color table
+            out.push(0x2c);
+            out.extend_from_slice(&[0, 0, 0, 0]);
+            out.extend_from_slice(&(self.width as u16).to_le_bytes());
+            out.extend_from_slice(&(self.height as u16).to_le_bytes());
+            out.push(0x00);
+
+            // GIF requires a minimum code size of at least 2, even for 2 colors
+            let min_code_size = 2;
+            out.push(min_code_size);
+            for block in lzw_encode(&frame.pixels, min_code_size).chunks(255) {
+                out.push(block.len() as u8);
+                out.extend_from_slice(block);
+            }
+            out.push(0x00);
+        }
+
+        out.push(0x3b);
+        out
+    }
+
+    /// Encode the recording and write it to `path`, creating the parent directory if needed
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.encode())
+    }
+}
+
+/// A fresh path in `RECORDING_DIR` including the generation the recording started at,
+/// e.g. `recordings/gen-42-20240601-134502.gif`
+pub fn recording_path(generation: u64, timestamp: &str) -> PathBuf {
+    Path::new(RECORDING_DIR).join(format!("gen-{generation}-{timestamp}.gif"))
+}
+
+/// Writes variable width codes least significant bit first, as GIF expects
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u32) {
+        self.buffer |= u32::from(code) << self.bits;
+        self.bits += width;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Compress palette indices with the variable code width LZW used by GIF
+/// see more: <https://www.w3.org/Graphics/GIF/spec-gif89a.txt> (Appendix F)
+fn lzw_encode(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut writer = BitWriter {
+        bytes: Vec::new(),
+        buffer: 0,
+        bits: 0,
+    };
+
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = end + 1;
+    let mut width = u32::from(min_code_size) + 1;
+    writer.write(clear, width);
+
+    let mut prefix: Option<u16> = None;
+    for &index in indices {
+        let Some(current) = prefix else {
+            prefix = Some(u16::from(index));
+            continue;
+        };
+        if let Some(&code) = table.get(&(current, index)) {
+            prefix = Some(code);
+            continue;
+        }
+
+        writer.write(current, width);
+        if next_code < (1 << MAX_CODE_BITS) {
+            table.insert((current, index), next_code);
+            // The decoder widens its codes one step later than we add them
+            if u32::from(next_code) == 1 << width && width < MAX_CODE_BITS {
+                width += 1;
+            }
+            next_code += 1;
+        } else {
+            // The table is full: start over with a fresh one
+            writer.write(clear, width);
+            table.clear();
+            next_code = end + 1;
+            width = u32::from(min_code_size) + 1;
+        }
+        prefix = Some(u16::from(index));
+    }
+
+    if let Some(current) = prefix {
+        writer.write(current, width);
+    }
+    writer.write(end, width);
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::CellState;
+
+    /// A decoded frame: its size and palette indices
+    type DecodedFrame = (u16, u16, Vec<u8>);
+
+    /// Minimal GIF decoder understanding exactly what `GifRecorder::encode` writes
+    fn decode(bytes: &[u8]) -> (u16, u16, Vec<DecodedFrame>) {
+        assert_eq!(&bytes[..6], b"GIF89a");
+        let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+        let (width, height) = (u16_at(6), u16_at(8));
+        let color_table = 3 << ((bytes[10] & 0x07) + 1);
+        let mut i = 13 + color_table;
+
+        let mut frames = Vec::new();
+        loop {
+            match bytes[i] {
+                0x21 => {
+                    // Extension: skip its sub-blocks
+                    i += 2;
+                    while bytes[i] != 0 {
+                        i += bytes[i] as usize + 1;
+                    }
+                    i += 1;
+                }
+                0x2c => {
+                    let (w, h) = (u16_at(i + 5), u16_at(i + 7));
+                    let min_code_size = bytes[i + 10];
+                    i += 11;
+                    let mut data = Vec::new();
+                    while bytes[i] != 0 {
+                        let len = bytes[i] as usize;
+                        data.extend_from_slice(&bytes[i + 1..i + 1 + len]);
+                        i += len + 1;
+                    }
+                    i += 1;
+                    frames.push((w, h, lzw_decode(&data, min_code_size)));
+                }
+                0x3b => break,
+                other => panic!("unexpected block {other:#x}"),
+            }
+        }
+        (width, height, frames)
+    }
+
+    fn lzw_decode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1u16 << min_code_size;
+        let end = clear + 1;
+        let reset = || -> Vec<Vec<u8>> {
+            let mut table: Vec<Vec<u8>> = (0..clear).map(|i| vec![i as u8]).collect();
+            table.push(Vec::new());
+            table.push(Vec::new());
+            table
+        };
+
+        let mut table = reset();
+        let mut width = u32::from(min_code_size) + 1;
+        let (mut buffer, mut bits, mut pos) = (0u32, 0u32, 0usize);
+        let mut previous: Option<Vec<u8>> = None;
+        let mut out = Vec::new();
+
+        loop {
+            while bits < width {
+                buffer |= u32::from(data[pos]) << bits;
+                pos += 1;
+                bits += 8;
+            }
+            let code = (buffer & ((1 << width) - 1)) as u16;
+            buffer >>= width;
+            bits -= width;
+
+            if code == clear {
+                table = reset();
+                width = u32::from(min_code_size) + 1;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+
+            let entry = match (table.get(code as usize), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(prev)) => {
+                    let mut entry = prev.clone();
+                    entry.push(prev[0]);
+                    entry
+                }
+                (None, None) => panic!("invalid code {code}"),
+            };
+            out.extend_from_slice(&entry);
+            if let Some(mut prev) = previous.take() {
+                prev.push(entry[0]);
+                table.push(prev);
+                if table.len() == 1 << width && width < MAX_CODE_BITS {
+                    width += 1;
+                }
+            }
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn test_lzw_round_trip() {
+        // Random enough, and long enough, to fill the code table and force a reset
+        let mut rng = crate::rng::SplitMix64::new(1);
+        let indices: Vec<u8> = (0..40_000).map(|_| (rng.next_u64() % 4) as u8).collect();
+        assert_eq!(lzw_decode(&lzw_encode(&indices, 2), 2), indices);
+    }
+
+    #[test]
+    fn test_blinker_animation() {
+        let mut grid = Grid::new(5, 5);
+        grid.set(1, 2, CellState::Alive);
+        grid.set(2, 2, CellState::Alive);
+        grid.set(3, 2, CellState::Alive);
+
+        let mut recorder = GifRecorder::new(5, 5, 100);
+        for _ in 0..10 {
+            assert!(recorder.push(&grid, 0.1));
+            grid.next_cell_generation();
+        }
+
+        let (width, height, frames) = decode(&recorder.encode());
+        let side = 5 * export::SCREENSHOT_SCALE as u16;
+        assert_eq!((width, height), (side, side));
+        assert_eq!(frames.len(), 10);
+
+        let scale = export::SCREENSHOT_SCALE as usize;
+        for (n, (w, h, pixels)) in frames.iter().enumerate() {
+            assert_eq!((*w, *h), (side, side));
+            assert_eq!(pixels.len(), side as usize * side as usize);
+            // Sample the center of cell (1, 2): alive only in the horizontal phase
+            let at = (2 * scale + scale / 2) * side as usize + scale + scale / 2;
+            assert_eq!(pixels[at], u8::from(n % 2 == 0));
+        }
+    }
+
+    #[test]
+    fn test_recorder_stops_at_max_frames() {
+        let grid = Grid::new(3, 3);
+        let mut recorder = GifRecorder::new(3, 3, 2);
+        assert!(recorder.push(&grid, 0.1));
+        assert!(recorder.push(&grid, 0.1));
+        assert!(recorder.is_full());
+        assert!(!recorder.push(&grid, 0.1));
+        assert_eq!(recorder.len(), 2);
+    }
+
+    #[test]
+    fn test_recorder_rejects_resized_grid() {
+        let mut recorder = GifRecorder::new(3, 3, 10);
+        assert!(!recorder.push(&Grid::new(4, 3), 0.1));
+        assert_eq!(recorder.len(), 0);
+    }
+
+    #[test]
+    fn test_large_grids_are_downscaled() {
+        let recorder = GifRecorder::new(1000, 200, 1);
+        assert_eq!((recorder.width, recorder.height), (1000, 200));
+
+        let recorder = GifRecorder::new(200, 100, 1);
+        assert_eq!((recorder.width, recorder.height), (600, 300));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::buttons::STRIP_HEIGHT;
+use crate::camera::Bounds;
+use crate::input::Point;
+
+/// Generations shown by the population graph
+pub const GRAPH_GENERATIONS: usize = 300;
+
+/// Height of the population graph, in pixels
+pub const GRAPH_HEIGHT: f32 = 80.0;
+
+/// Widest the population graph gets, in pixels
+pub const GRAPH_MAX_WIDTH: f32 = 400.0;
+
+/// Gap between the graph and the screen edges, leaving room for the HUD and
+/// the buttons below it
+const GRAPH_MARGIN: f32 = 10.0;
+const HUD_SPACE: f32 = 30.0 + STRIP_HEIGHT;
+
+/// Where the population graph goes: the bottom-left corner, above the HUD and the buttons
+pub fn graph_bounds(screen: (f32, f32)) -> Bounds {
+    let w = (screen.0 - 2.0 * GRAPH_MARGIN).clamp(0.0, GRAPH_MAX_WIDTH);
+    Bounds {
+        x: GRAPH_MARGIN,
+        y: screen.1 - HUD_SPACE - GRAPH_HEIGHT,
+        w,
+        h: GRAPH_HEIGHT,
+    }
+}
+
+/// Turn a history of values into a line graph filling `bounds`
+///
+/// Values are spread evenly across the width, oldest on the left. The y axis
+/// scales so the largest value touches the top, and 0 sits on the bottom edge.
+///
+/// # Arguments
+/// * `history` - The values to plot, oldest first
+/// * `bounds` - The area to draw in, in screen coordinates
+/// * `points` - Cleared and filled with the polyline, so one buffer can be reused every frame
+pub fn polyline(history: &[usize], bounds: Bounds, points: &mut Vec<Point>) {
+    points.clear();
+    let max = history.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let spacing = bounds.w / history.len().saturating_sub(1).max(1) as f32;
+    points.extend(history.iter().enumerate().map(|(i, &value)| {
+        (
+            bounds.x + i as f32 * spacing,
+            bounds.y + bounds.h - bounds.h * value as f32 / max,
+        )
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: Bounds = Bounds {
+        x: 10.0,
+        y: 100.0,
+        w: 40.0,
+        h: 20.0,
+    };
+
+    #[test]
+    fn test_polyline_scaling() {
+        let mut points = Vec::new();
+        polyline(&[0, 5, 10, 5, 0], BOUNDS, &mut points);
+        assert_eq!(
+            points,
+            vec![
+                (10.0, 120.0),
+                (20.0, 110.0),
+                (30.0, 100.0),
+                (40.0, 110.0),
+                (50.0, 120.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_polyline_empty_history() {
+        let mut points = vec![(1.0, 1.0)];
+        polyline(&[], BOUNDS, &mut points);
+        assert!(points.is_empty());
+    }
+
+    #[test]
+    fn test_polyline_constant_history() {
+        let mut points = Vec::new();
+        polyline(&[7, 7, 7], BOUNDS, &mut points);
+        assert!(points.iter().all(|&(_, y)| y == BOUNDS.y));
+
+        // An empty board stays on the bottom edge instead of dividing by zero
+        polyline(&[0, 0], BOUNDS, &mut points);
+        assert_eq!(points, vec![(10.0, 120.0), (50.0, 120.0)]);
+    }
+
+    #[test]
+    fn test_graph_bounds() {
+        let bounds = graph_bounds((1000.0, 600.0));
+        assert_eq!((bounds.x, bounds.w), (GRAPH_MARGIN, GRAPH_MAX_WIDTH));
+        assert_eq!(bounds.y + bounds.h, 600.0 - HUD_SPACE);
+        // Narrow windows shrink the graph
+        assert_eq!(graph_bounds((100.0, 600.0)).w, 80.0);
+    }
+}
@@ -0,0 +1,459 @@
+use crate::backend::GridBackend;
+use crate::cli::Config;
+use crate::conways::svg::SvgStyle;
+use crate::cycle::{CycleDetector, Stabilization};
+use crate::error::ConwayError;
+use crate::export;
+use crate::formats;
+use crate::listeners::SharedListeners;
+use crate::puzzle::{Outcome, Puzzle};
+use crate::stats_csv::StatsCsv;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Exit codes of a headless run
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_PARSE: i32 = 3;
+pub const EXIT_IO: i32 = 4;
+/// A `--puzzle` check whose board doesn't solve the puzzle
+pub const EXIT_UNSOLVED: i32 = 1;
+
+/// Longest oscillator period recognized when detecting stabilization
+pub const MAX_PERIOD: u64 = 30;
+
+/// Statistics gathered over a headless run
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Report {
+    pub generations: u64,
+    pub final_population: usize,
+    pub peak_population: usize,
+    /// First generation at which `peak_population` was reached
+    pub peak_generation: u64,
+    /// `None` if the board never repeated within `MAX_PERIOD` generations
+    pub stabilization: Option<Stabilization>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "generations: {}", self.generations)?;
+        writeln!(f, "final population: {}", self.final_population)?;
+        writeln!(
+            f,
+            "peak population: {} (generation {})",
+            self.peak_population, self.peak_generation
+        )?;
+        match self.stabilization {
+            Some(Stabilization { generation, period }) => {
+                write!(f, "stabilized: generation {generation} (period {period})")
+            }
+            None => write!(f, "stabilized: not detected"),
+        }
+    }
+}
+
+/// Error produced by a headless run: the file involved and what went wrong with it
+#[derive(Debug)]
+pub struct HeadlessError {
+    pub path: PathBuf,
+    pub source: ConwayError,
+}
+
+impl fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.source)
+    }
+}
+
+impl std::error::Error for HeadlessError {}
+
+/// Advance a grid `generations` times, keeping track of population and repetition
+///
+/// Stabilization is detected with a `CycleDetector` looking back `MAX_PERIOD`
+/// generations. An observer set on a `Grid` is told about every generation.
+pub fn simulate(grid: &mut (impl GridBackend + ?Sized), generations: u64) -> Report {
+    let mut detector = CycleDetector::new(MAX_PERIOD as usize);
+    let mut report = Report {
+        generations,
+        final_population: grid.population(),
+        peak_population: grid.population(),
+        peak_generation: 0,
+        stabilization: None,
+    };
+
+    for generation in 0..=generations {
+        if generation > 0 {
+            grid.step();
+            let population = grid.population();
+            if population > report.peak_population {
+                report.peak_population = population;
+                report.peak_generation = generation;
+            }
+        }
+
+        if let Some(stabilization) = detector.observe(grid) {
+            report.stabilization = Some(stabilization);
+        }
+    }
+
+    report.final_population = grid.population();
+    report
+}
+
+/// Run the simulation described by the command line without a window
+///
+/// # Returns
+/// The report, after writing the final board to `--out` and `--export-svg`
+/// and the statistics of every generation to `--stats-csv` if given
+pub fn execute(config: &Config) -> Result<Report, HeadlessError> {
+    let mut grid = config.starting_grid().map_err(|source| HeadlessError {
+        path: config.board_file().map(Into::into).unwrap_or_default(),
+        source,
+    })?;
+    let csv_error = |path: &PathBuf| {
+        let path = path.clone();
+        move |err: std::io::Error| HeadlessError {
+            path,
+            source: ConwayError::Io(err),
+        }
+    };
+    // Before simulating, so an unwritable path doesn't waste a long run
+    let listeners = SharedListeners::default();
+    if let Some(path) = &config.stats_csv {
+        let csv = StatsCsv::create(path, clock).map_err(csv_error(path))?;
+        listeners.lock().stats_csv = Some((path.clone(), csv));
+        grid.set_observer(listeners.observer());
+    }
+    let report = config
+        .backend
+        .run(&mut grid, |board| simulate(board, config.generations));
+    grid.take_observer();
+    let csv = listeners.lock().stats_csv.take();
+    if let Some((path, mut csv)) = csv {
+        match csv.take_error() {
+            Some(err) => return Err(csv_error(&path)(err)),
+            None => csv.flush().map_err(csv_error(&path))?,
+        }
+    }
+    if let Some(path) = &config.out {
+        let comments = [format!("generation {}", config.generations)];
+        formats::save_file(&grid, path, &comments).map_err(|source| HeadlessError {
+            path: path.clone(),
+            source,
+        })?;
+    }
+    if let Some(path) = &config.export_svg {
+        export::save_svg(&grid, &SvgStyle::default(), path).map_err(|err| HeadlessError {
+            path: path.clone(),
+            source: ConwayError::Io(err),
+        })?;
+    }
+    Ok(report)
+}
+
+/// Seconds since the Unix epoch, timing the steps for `--stats-csv`
+fn clock() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64())
+}
+
+/// Check whether the `--pattern` board, the puzzle's starting board with the
+/// player's edits, solves the `--puzzle`
+///
+/// # Returns
+/// The outcome, or an explanation when the board can't be a solution
+pub fn check_puzzle(config: &Config) -> Result<Result<Outcome, String>, HeadlessError> {
+    let puzzle_path = config.puzzle.clone().unwrap_or_default();
+    let puzzle = Puzzle::load(&puzzle_path).map_err(|source| HeadlessError {
+        path: puzzle_path,
+        source,
+    })?;
+    let board_path = config.pattern.clone().unwrap_or_default();
+    let board = formats::load_file(&board_path).map_err(|source| HeadlessError {
+        path: board_path,
+        source,
+    })?;
+    if (board.width(), board.height()) != (puzzle.start.width(), puzzle.start.height()) {
+        return Ok(Err(format!(
+            "the board is {}x{}, the puzzle's is {}x{}",
+            board.width(),
+            board.height(),
+            puzzle.start.width(),
+            puzzle.start.height()
+        )));
+    }
+    let edits = puzzle.edits(&board);
+    if edits > puzzle.budget {
+        return Ok(Err(format!(
+            "{edits} cells changed, the budget is {}",
+            puzzle.budget
+        )));
+    }
+    Ok(Ok(puzzle.evaluate(&board)))
+}
+
+/// Headless entry point: run, print the report if asked for and any error
+/// With `--puzzle` the board is checked against the puzzle instead.
+///
+/// # Returns
+/// The process exit code
+pub fn run(config: &Config) -> i32 {
+    if config.puzzle.is_some() {
+        return match check_puzzle(config) {
+            Ok(Ok(Outcome::Solved(generation))) => {
+                println!("solved at generation {generation}");
+                EXIT_SUCCESS
+            }
+            Ok(Ok(Outcome::Failed)) => {
+                println!("not solved");
+                EXIT_UNSOLVED
+            }
+            Ok(Err(reason)) => {
+                println!("not a solution: {reason}");
+                EXIT_UNSOLVED
+            }
+            Err(err) => {
+                eprintln!("error: {err}");
+                exit_code(&err.source)
+            }
+        };
+    }
+    match execute(config) {
+        Ok(report) => {
+            if config.report {
+                if let Some(date) = config.daily {
+                    println!("{}", crate::daily::title(date));
+                }
+                println!("{report}");
+            }
+            EXIT_SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            exit_code(&err.source)
+        }
+    }
+}
+
+/// The exit code reporting a failure to load or save a pattern
+pub fn exit_code(err: &ConwayError) -> i32 {
+    match err {
+        ConwayError::Io(_) => EXIT_IO,
+        ConwayError::ParseError { .. } | ConwayError::InvalidRule(_) => EXIT_PARSE,
+        ConwayError::OutOfBounds { .. }
+        | ConwayError::DimensionMismatch { .. }
+        | ConwayError::InvalidArgument(_) => EXIT_USAGE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn scratch(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("conways-headless-{}-{name}", std::process::id()))
+    }
+
+    fn config(pattern: &str, rle: &str, generations: u64) -> Config {
+        let path = scratch(pattern);
+        fs::write(&path, rle).unwrap();
+        Config {
+            width: 40,
+            height: 40,
+            pattern: Some(path),
+            generations,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_blinker_report() {
+        let config = config("blinker.rle", "x = 3, y = 1\n3o!", 10);
+        let report = execute(&config).unwrap();
+        assert_eq!(
+            report,
+            Report {
+                generations: 10,
+                final_population: 3,
+                peak_population: 3,
+                peak_generation: 0,
+                stabilization: Some(Stabilization {
+                    generation: 0,
+                    period: 2
+                }),
+            }
+        );
+        let _ = fs::remove_file(config.pattern.unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "scripting")]
+    fn test_a_script_draws_the_board_and_its_rule_holds() {
+        let path = scratch("seed.rhai");
+        fs::write(&path, "rule(\"B/S\");\nfor x in 0..3 { set(x, 5); }").unwrap();
+        let mut config = Config {
+            script: Some(path.clone()),
+            generations: 1,
+            ..Config::default()
+        };
+        // Under B/S the blinker dies out rather than turning
+        let report = execute(&config).unwrap();
+        assert_eq!((report.peak_population, report.final_population), (3, 0));
+
+        fs::write(&path, "let x = 1;\nset(x, -1);").unwrap();
+        config.rule = "B36/S23".parse().unwrap();
+        let err = execute(&config).unwrap_err();
+        assert_eq!(exit_code(&err.source), EXIT_USAGE);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "{}: script failed at line 2: (1, -1) is off the 80x60 board",
+                path.display()
+            )
+        );
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_every_backend_reports_the_same() {
+        use crate::backend::{self, bits::BitGrid, sparse::SparseGrid};
+        use crate::conways::Grid;
+
+        let pattern = formats::rle::decode("x = 3, y = 3\nb2o$2o$bo!").unwrap();
+        let grid = Grid::centered(&pattern, 120, 120);
+        let report = simulate(&mut grid.clone(), 200);
+        let mut bits: BitGrid = backend::convert(&grid);
+        let mut sparse: SparseGrid = backend::convert(&grid);
+        assert_eq!(simulate(&mut bits, 200), report);
+        assert_eq!(simulate(&mut sparse, 200), report);
+        assert_eq!(report.peak_population, 221);
+    }
+
+    #[test]
+    fn test_every_backend_runs_the_same() {
+        use crate::backend::Backend;
+
+        let reports: Vec<_> = Backend::ALL
+            .into_iter()
+            .map(|backend| {
+                let config = Config {
+                    backend,
+                    ..config("backends.rle", "x = 3, y = 3\nb2o$2o$bo!", 300)
+                };
+                let report = execute(&config).unwrap();
+                let _ = fs::remove_file(config.pattern.unwrap());
+                report
+            })
+            .collect();
+        assert_eq!(reports[0].final_population, 33);
+        assert!(reports.iter().all(|report| *report == reports[0]));
+    }
+
+    #[test]
+    fn test_r_pentomino_report_and_output() {
+        let mut config = config("r-pentomino.rle", "x = 3, y = 3\nb2o$2o$bo!", 300);
+        let out = scratch("r-pentomino-out.cells");
+        config.out = Some(out.clone());
+        let svg = scratch("r-pentomino-out.svg");
+        config.export_svg = Some(svg.clone());
+
+        let report = execute(&config).unwrap();
+        // On an unbounded plane it takes 1103 generations to settle, the
+        // edges of a 40x40 board cut that short
+        assert_eq!(report.final_population, 33);
+        assert_eq!(report.peak_population, 147);
+        assert_eq!(report.peak_generation, 167);
+        assert_eq!(
+            report.stabilization,
+            Some(Stabilization {
+                generation: 248,
+                period: 1
+            })
+        );
+        assert_eq!(formats::load_file(&out).unwrap().population(), 33);
+        assert!(fs::read_to_string(&svg).unwrap().starts_with("<svg "));
+
+        let _ = fs::remove_file(config.pattern.unwrap());
+        let _ = fs::remove_file(out);
+        let _ = fs::remove_file(svg);
+    }
+
+    #[test]
+    fn test_blinker_statistics_csv() {
+        let mut config = config("blinker-stats.rle", "x = 3, y = 1\n3o!", 10);
+        let csv = scratch("blinker-stats.csv");
+        config.stats_csv = Some(csv.clone());
+        execute(&config).unwrap();
+
+        let text = fs::read_to_string(&csv).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some(crate::stats_csv::CSV_HEADER));
+        let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+        assert_eq!(rows.len(), 10);
+        for (i, row) in rows.iter().enumerate() {
+            assert_eq!(row[0], (i + 1).to_string());
+            // The blinker keeps 3 cells, two of them dying and two born every turn
+            assert_eq!(&row[1..4], ["3", "2", "2"]);
+            assert!(row[4].parse::<f64>().is_ok_and(|ms| ms >= 0.0));
+        }
+
+        // An unwritable path fails before simulating
+        config.stats_csv = Some(scratch("missing-dir").join("stats.csv"));
+        let err = execute(&config).unwrap_err();
+        assert!(matches!(err.source, ConwayError::Io(_)));
+        assert_eq!(run(&config), EXIT_IO);
+
+        let _ = fs::remove_file(config.pattern.unwrap());
+        let _ = fs::remove_file(csv);
+    }
+
+    #[test]
+    fn test_check_puzzle() {
+        let puzzle = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("puzzles/crowd.toml");
+        // The starting fragment with the R-pentomino's missing cell added
+        let mut config = config(
+            "crowd-solution.rle",
+            "x = 60, y = 60\n30$31b2o$30b2o$31bo!",
+            1,
+        );
+        config.puzzle = Some(puzzle);
+        assert!(matches!(
+            check_puzzle(&config).unwrap(),
+            Ok(Outcome::Solved(_))
+        ));
+        assert_eq!(run(&config), EXIT_SUCCESS);
+
+        fs::write(
+            config.pattern.as_ref().unwrap(),
+            "x = 60, y = 60\n30$31bo$30b2o$31bo!",
+        )
+        .unwrap();
+        assert_eq!(check_puzzle(&config).unwrap(), Ok(Outcome::Failed));
+        fs::write(config.pattern.as_ref().unwrap(), "x = 60, y = 60\n2o$2o!").unwrap();
+        assert_eq!(
+            check_puzzle(&config).unwrap(),
+            Err("8 cells changed, the budget is 1".to_string())
+        );
+        fs::write(config.pattern.as_ref().unwrap(), "x = 3, y = 1\n3o!").unwrap();
+        assert_eq!(run(&config), EXIT_UNSOLVED);
+        let _ = fs::remove_file(config.pattern.unwrap());
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        let mut config = config("broken.rle", "x = 3, y = 1\n3o", 1);
+        assert_eq!(run(&config), EXIT_PARSE);
+        let _ = fs::remove_file(config.pattern.as_ref().unwrap());
+
+        config.pattern = Some(scratch("missing.rle"));
+        assert_eq!(run(&config), EXIT_IO);
+
+        config.pattern = None;
+        config.out = Some(scratch("result.png"));
+        assert_eq!(run(&config), EXIT_USAGE);
+    }
+}
@@ -0,0 +1,51 @@
+/// Mouse controls listed by the help overlay after the key bindings
+/// These aren't rebindable, so they don't go through the bindings table.
+pub const MOUSE_HELP: &[(&str, &str)] = &[
+    ("Left click / drag", "Use the tool"),
+    ("Right click / drag", "Erase (paint with erase tool)"),
+    ("Shift+drag", "Select a region"),
+    ("Alt+click", "Stamp a paste, overwriting"),
+    ("Wheel", "Zoom"),
+    ("Middle drag", "Pan"),
+    ("Two-finger drag / pinch", "Pan / zoom"),
+];
+
+/// Keys of the keyboard cursor, which only take effect while it is shown and
+/// the simulation is paused
+pub const CURSOR_HELP: &[(&str, &str)] = &[
+    ("WASD / HJKL", "Move the cursor (Alt: faster)"),
+    ("Shift+WASD / HJKL", "Select from the cursor"),
+    ("Enter / Shift+Space", "Toggle the cell under the cursor"),
+];
+
+/// Split `count` entries into columns that fit side by side on screen
+///
+/// # Arguments
+/// * `count` - Number of entries to lay out
+/// * `column_width` - Width of a single column, including spacing, in pixels
+/// * `available` - Width the columns have to fit in, in pixels
+///
+/// # Returns
+/// `(columns, rows)`, filled top to bottom then left to right. There is always
+/// at least one column, even when it doesn't fit.
+pub fn layout_columns(count: usize, column_width: f32, available: f32) -> (usize, usize) {
+    let fit = (available / column_width.max(1.0)).floor().max(1.0) as usize;
+    let columns = fit.min(count.max(1));
+    (columns, count.div_ceil(columns))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_columns() {
+        // Wide screen: as many columns as fit
+        assert_eq!(layout_columns(40, 250.0, 800.0), (3, 14));
+        // Narrow screen: a single long column
+        assert_eq!(layout_columns(40, 250.0, 300.0), (1, 40));
+        // Never more columns than entries
+        assert_eq!(layout_columns(2, 100.0, 800.0), (2, 1));
+        assert_eq!(layout_columns(0, 100.0, 800.0), (1, 0));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::conways::Grid;
+
+/// Most undo steps kept; the oldest are dropped beyond this
+pub const HISTORY_LIMIT: usize = 200;
+
+/// Most cells kept across all undo steps, so huge boards keep fewer steps
+pub const HISTORY_CELL_BUDGET: usize = 32_000_000;
+
+/// Undo and redo stacks of whole-board snapshots
+///
+/// Every undo step is the board as it was before a change. Changes are either
+/// recorded one at a time with `record`, or grouped between `begin` and `end`
+/// into a transaction, so a whole mouse drag undoes in one go. Snapshots
+/// include the generation counter, which is how undo rewinds simulation steps.
+pub struct History {
+    undo: Vec<Grid>,
+    redo: Vec<Grid>,
+    /// The board when the open transaction began
+    pending: Option<Grid>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            pending: None,
+        }
+    }
+
+    /// Remember the board right before a change, as its own undo step
+    pub fn record(&mut self, grid: &Grid) {
+        self.push(grid.clone());
+    }
+
+    /// Open a transaction: changes until `end` undo as a single step
+    /// Does nothing when a transaction is already open.
+    pub fn begin(&mut self, grid: &Grid) {
+        if self.pending.is_none() {
+            self.pending = Some(grid.clone());
+        }
+    }
+
+    /// Close the open transaction, keeping it as an undo step if it changed the board
+    pub fn end(&mut self, grid: &Grid) {
+        if let Some(before) = self.pending.take() {
+            if before != *grid {
+                self.push(before);
+            }
+        }
+    }
+
+    /// Advance the grid one generation, as an undo step of its own
+    /// A transaction open across the step is split around it, so a single undo
+    /// never takes back both painting and a generation.
+    pub fn step(&mut self, grid: &mut Grid) {
+        let open = self.pending.is_some();
+        self.end(grid);
+        self.record(grid);
+        grid.next_cell_generation();
+        if open {
+            self.begin(grid);
+        }
+    }
+
+    /// Go back to the board before the latest undo step
+    ///
+    /// # Returns
+    /// `false` when there is nothing to undo, leaving the grid untouched
+    pub fn undo(&mut self, grid: &mut Grid) -> bool {
+        self.end(grid);
+        let Some(before) = self.undo.pop() else {
+            return false;
+        };
+        self.redo.push(std::mem::replace(grid, before));
+        true
+    }
+
+    /// Reapply the latest undone step
+    ///
+    /// # Returns
+    /// `false` when there is nothing to redo, leaving the grid untouched
+    pub fn redo(&mut self, grid: &mut Grid) -> bool {
+        self.end(grid);
+        let Some(after) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push(std::mem::replace(grid, after));
+        true
+    }
+
+    /// A new change makes the undone steps unreachable
+    fn push(&mut self, snapshot: Grid) {
+        self.redo.clear();
+        let limit = step_limit(snapshot.width() * snapshot.height());
+        self.undo.push(snapshot);
+        if self.undo.len() > limit {
+            self.undo.drain(..self.undo.len() - limit);
+        }
+    }
+}
+
+/// How many undo steps fit in the budget for boards of `cells` cells
+fn step_limit(cells: usize) -> usize {
+    (HISTORY_CELL_BUDGET / cells.max(1)).clamp(1, HISTORY_LIMIT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::CellState;
+
+    #[test]
+    fn test_undo_redo() {
+        let mut grid = Grid::new(3, 3);
+        let mut history = History::new();
+        assert!(!history.undo(&mut grid));
+
+        history.record(&grid);
+        grid.set(1, 1, CellState::Alive);
+        assert!(history.undo(&mut grid));
+        assert_eq!(grid.population(), 0);
+        assert!(!history.undo(&mut grid));
+
+        assert!(history.redo(&mut grid));
+        assert_eq!(grid.get(1, 1), CellState::Alive);
+        assert!(!history.redo(&mut grid));
+    }
+
+    #[test]
+    fn test_new_change_drops_redo() {
+        let mut grid = Grid::new(3, 3);
+        let mut history = History::new();
+        history.record(&grid);
+        grid.set(0, 0, CellState::Alive);
+        history.undo(&mut grid);
+
+        history.record(&grid);
+        grid.set(2, 2, CellState::Alive);
+        assert!(!history.redo(&mut grid));
+    }
+
+    #[test]
+    fn test_empty_transaction_is_not_a_step() {
+        let mut grid = Grid::new(3, 3);
+        let mut history = History::new();
+        history.begin(&grid);
+        history.end(&grid);
+        assert!(!history.undo(&mut grid));
+    }
+
+    #[test]
+    fn test_steps_rewind_generations() {
+        let mut grid = Grid::new(5, 5);
+        for x in 1..4 {
+            grid.set(x, 2, CellState::Alive);
+        }
+        let start = grid.clone();
+        let mut history = History::new();
+        history.step(&mut grid);
+        history.step(&mut grid);
+
+        history.undo(&mut grid);
+        assert_eq!(grid.generation(), 1);
+        history.undo(&mut grid);
+        assert_eq!(grid, start);
+        assert_eq!(grid.generation(), 0);
+    }
+
+    #[test]
+    fn test_step_splits_open_transaction() {
+        let mut grid = Grid::new(5, 5);
+        let mut history = History::new();
+        history.begin(&grid);
+        grid.set(0, 0, CellState::Alive);
+        history.step(&mut grid);
+        grid.set(4, 4, CellState::Alive);
+        history.end(&grid);
+
+        // Painting after the step, the step, then painting before it
+        history.undo(&mut grid);
+        assert_eq!(grid.get(4, 4), CellState::Dead);
+        assert_eq!(grid.generation(), 1);
+        history.undo(&mut grid);
+        assert_eq!(grid.generation(), 0);
+        assert_eq!(grid.get(0, 0), CellState::Alive);
+        history.undo(&mut grid);
+        assert_eq!(grid.population(), 0);
+        assert!(!history.undo(&mut grid));
+    }
+
+    #[test]
+    fn test_history_limit() {
+        let mut grid = Grid::new(2, 2);
+        let mut history = History::new();
+        for _ in 0..HISTORY_LIMIT + 10 {
+            history.step(&mut grid);
+        }
+        let mut undone = 0;
+        while history.undo(&mut grid) {
+            undone += 1;
+        }
+        assert_eq!(undone, HISTORY_LIMIT);
+        assert_eq!(grid.generation(), 10);
+    }
+
+    #[test]
+    fn test_large_boards_keep_fewer_steps() {
+        assert_eq!(step_limit(100 * 100), HISTORY_LIMIT);
+        assert_eq!(step_limit(4096 * 4096), 1);
+        assert_eq!(step_limit(HISTORY_CELL_BUDGET / 10), 10);
+        assert_eq!(step_limit(0), HISTORY_LIMIT);
+    }
+}
@@ -0,0 +1,373 @@
+use crate::conways::{BoundaryCondition, CellState, Grid};
+
+/// Cells each player may place during their setup turn
+pub const DEFAULT_BUDGET: usize = 30;
+
+/// Generations a match runs for before it is scored
+pub const MATCH_GENERATIONS: u64 = 200;
+
+/// One of the two sides of an Immigration match
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Player {
+    One,
+    Two,
+}
+
+impl Player {
+    pub fn label(self) -> &'static str {
+        match self {
+            Player::One => "Player 1",
+            Player::Two => "Player 2",
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Player::One => 0,
+            Player::Two => 1,
+        }
+    }
+}
+
+/// The owner of every live cell of a board, for the Immigration variant
+///
+/// Survivors keep their color and a newborn cell takes the color most of its
+/// live neighbors have, so under Conway's rule each birth goes to the player
+/// owning at least two of its three parents. A birth with no majority, e.g.
+/// from an uncolored parent or a tie under other rules, belongs to nobody.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Colors {
+    width: usize,
+    height: usize,
+    cells: Vec<Option<Player>>,
+}
+
+impl Colors {
+    /// Colors for a `width` x `height` board where no cell belongs to anybody
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![None; width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Player> {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x]
+        } else {
+            None
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, player: Option<Player>) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = player;
+        }
+    }
+
+    /// Color `after`, the generation following `before`
+    /// Boards of another size than the colors start over uncolored.
+    pub fn advance(&mut self, before: &Grid, after: &Grid) {
+        if (after.width(), after.height()) != (self.width, self.height) {
+            *self = Self::new(after.width(), after.height());
+            return;
+        }
+        let mut cells = vec![None; self.cells.len()];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if after.get(x, y) == CellState::Dead {
+                    continue;
+                }
+                cells[y * self.width + x] = if before.get(x, y) == CellState::Alive {
+                    self.get(x, y)
+                } else {
+                    self.majority(before, x, y)
+                };
+            }
+        }
+        self.cells = cells;
+    }
+
+    /// The color most live neighbors of `(x, y)` on `grid` have, if any
+    fn majority(&self, grid: &Grid, x: usize, y: usize) -> Option<Player> {
+        let wrap = grid.boundary() == BoundaryCondition::Wrap;
+        let mut counts = [0; 2];
+        for dy in -1..=1isize {
+            for dx in -1..=1isize {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                let (mut nx, mut ny) = (x as isize + dx, y as isize + dy);
+                if wrap {
+                    nx = nx.rem_euclid(self.width as isize);
+                    ny = ny.rem_euclid(self.height as isize);
+                }
+                if nx < 0 || ny < 0 {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                if nx < self.width && ny < self.height && grid.get(nx, ny) == CellState::Alive {
+                    if let Some(player) = self.get(nx, ny) {
+                        counts[player.index()] += 1;
+                    }
+                }
+            }
+        }
+        match counts[0].cmp(&counts[1]) {
+            std::cmp::Ordering::Greater => Some(Player::One),
+            std::cmp::Ordering::Less => Some(Player::Two),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// Live cells on `grid` of each player, player one first
+    pub fn scores(&self, grid: &Grid) -> [usize; 2] {
+        let mut scores = [0; 2];
+        for y in 0..self.height.min(grid.height()) {
+            for x in 0..self.width.min(grid.width()) {
+                if grid.get(x, y) == CellState::Alive {
+                    if let Some(player) = self.get(x, y) {
+                        scores[player.index()] += 1;
+                    }
+                }
+            }
+        }
+        scores
+    }
+}
+
+/// Where a match is at
+/// - `Setup`: The player places cells of their color, the simulation waits
+/// - `Running`: The board evolves for the match's generations
+/// - `Results`: The final counts are in
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    Setup(Player),
+    Running,
+    Results,
+}
+
+/// A two-player Immigration match: both players paint cells in turn, then the
+/// board runs for a fixed number of generations, and whoever owns more live
+/// cells at the end wins
+#[derive(Clone, Debug)]
+pub struct Match {
+    phase: Phase,
+    colors: Colors,
+    /// Cells each player can still place, player one first
+    budgets: [usize; 2],
+    generations: u64,
+    /// Generation the board was at when the simulation started
+    start: u64,
+}
+
+impl Match {
+    /// A match on an empty `width` x `height` board, starting with player one's setup
+    pub fn new(width: usize, height: usize, budget: usize, generations: u64) -> Self {
+        Self {
+            phase: Phase::Setup(Player::One),
+            colors: Colors::new(width, height),
+            budgets: [budget; 2],
+            generations,
+            start: 0,
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn colors(&self) -> &Colors {
+        &self.colors
+    }
+
+    /// Cells `player` can still place
+    pub fn budget(&self, player: Player) -> usize {
+        self.budgets[player.index()]
+    }
+
+    /// Generations left before the match is scored, while it runs
+    pub fn remaining(&self, grid: &Grid) -> u64 {
+        (self.start + self.generations).saturating_sub(grid.generation())
+    }
+
+    /// Place a cell of the player in setup at `(x, y)`, or take back one of
+    /// theirs placed there earlier
+    ///
+    /// # Returns
+    /// Whether the board changed: nothing happens outside setup, on cells of
+    /// the other player, or once the budget is spent
+    pub fn claim(&mut self, grid: &mut Grid, x: usize, y: usize) -> bool {
+        let Phase::Setup(player) = self.phase else {
+            return false;
+        };
+        if x >= grid.width() || y >= grid.height() {
+            return false;
+        }
+        let budget = &mut self.budgets[player.index()];
+        match (grid.get(x, y), self.colors.get(x, y)) {
+            (CellState::Alive, Some(owner)) if owner == player => {
+                grid.set(x, y, CellState::Dead);
+                self.colors.set(x, y, None);
+                *budget += 1;
+                true
+            }
+            (CellState::Dead, _) if *budget > 0 => {
+                grid.set(x, y, CellState::Alive);
+                self.colors.set(x, y, Some(player));
+                *budget -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Finish the current setup turn: player two goes next, then the board
+    /// runs from its current generation
+    pub fn end_turn(&mut self, grid: &Grid) -> Phase {
+        self.phase = match self.phase {
+            Phase::Setup(Player::One) => Phase::Setup(Player::Two),
+            Phase::Setup(Player::Two) => {
+                self.start = grid.generation();
+                Phase::Running
+            }
+            phase => phase,
+        };
+        self.phase
+    }
+
+    /// Color the generation `after` following `before`, ending the match once
+    /// it ran for all of its generations
+    /// Generations stepped through by hand during setup are colored too, they
+    /// just don't count towards the match.
+    pub fn advance(&mut self, before: &Grid, after: &Grid) -> Phase {
+        self.colors.advance(before, after);
+        if self.phase == Phase::Running && self.remaining(after) == 0 {
+            self.phase = Phase::Results;
+        }
+        self.phase
+    }
+
+    /// Live cells of each player on `grid`, player one first
+    pub fn scores(&self, grid: &Grid) -> [usize; 2] {
+        self.colors.scores(grid)
+    }
+
+    /// The player owning more live cells, `None` for a draw
+    pub fn winner(&self, grid: &Grid) -> Option<Player> {
+        let [one, two] = self.scores(grid);
+        match one.cmp(&two) {
+            std::cmp::Ordering::Greater => Some(Player::One),
+            std::cmp::Ordering::Less => Some(Player::Two),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run `game` to its end, the way the game loop does
+    fn play_out(game: &mut Match, grid: &mut Grid) {
+        while game.phase() == Phase::Running {
+            let before = grid.clone();
+            grid.next_cell_generation();
+            game.advance(&before, grid);
+        }
+    }
+
+    #[test]
+    fn test_phases_follow_turns() {
+        let mut grid = Grid::new(20, 20);
+        let mut game = Match::new(20, 20, 3, 10);
+        assert_eq!(game.phase(), Phase::Setup(Player::One));
+        assert!(game.claim(&mut grid, 1, 1));
+        assert_eq!(game.end_turn(&grid), Phase::Setup(Player::Two));
+        // Player one's cells are off limits to player two
+        assert!(!game.claim(&mut grid, 1, 1));
+        assert!(game.claim(&mut grid, 10, 10));
+        assert_eq!(game.end_turn(&grid), Phase::Running);
+        // No painting once the board runs
+        assert!(!game.claim(&mut grid, 5, 5));
+
+        play_out(&mut game, &mut grid);
+        assert_eq!(game.phase(), Phase::Results);
+        assert_eq!(grid.generation(), 10);
+        assert_eq!(game.end_turn(&grid), Phase::Results);
+    }
+
+    #[test]
+    fn test_budget_limits_cells() {
+        let mut grid = Grid::new(10, 10);
+        let mut game = Match::new(10, 10, 2, 10);
+        assert!(game.claim(&mut grid, 0, 0));
+        assert!(game.claim(&mut grid, 1, 0));
+        assert_eq!(game.budget(Player::One), 0);
+        assert!(!game.claim(&mut grid, 2, 0));
+        assert_eq!(grid.population(), 2);
+
+        // Taking a cell back refunds it
+        assert!(game.claim(&mut grid, 0, 0));
+        assert_eq!(game.budget(Player::One), 1);
+        assert!(game.claim(&mut grid, 2, 0));
+        assert_eq!(game.budget(Player::Two), 2);
+    }
+
+    #[test]
+    fn test_births_take_majority_color() {
+        // A blinker of two cells of player one and one of player two
+        let mut grid = Grid::new(5, 5);
+        let mut colors = Colors::new(5, 5);
+        for (x, player) in [(1, Player::One), (2, Player::One), (3, Player::Two)] {
+            grid.set(x, 2, CellState::Alive);
+            colors.set(x, 2, Some(player));
+        }
+        let before = grid.clone();
+        grid.next_cell_generation();
+        colors.advance(&before, &grid);
+        // The center survives with its color, both births have all three parents
+        assert_eq!(colors.get(2, 2), Some(Player::One));
+        assert_eq!(colors.get(2, 1), Some(Player::One));
+        assert_eq!(colors.get(2, 3), Some(Player::One));
+        assert_eq!(colors.get(1, 2), None);
+        assert_eq!(colors.scores(&grid), [3, 0]);
+    }
+
+    #[test]
+    fn test_scoring_endgame() {
+        // Player one builds a block, a still life, player two a lone pair that dies out
+        let mut grid = Grid::new(20, 20);
+        let mut game = Match::new(20, 20, DEFAULT_BUDGET, 5);
+        for (x, y) in [(3, 3), (4, 3), (3, 4), (4, 4)] {
+            game.claim(&mut grid, x, y);
+        }
+        game.end_turn(&grid);
+        for (x, y) in [(15, 15), (16, 15)] {
+            game.claim(&mut grid, x, y);
+        }
+        game.end_turn(&grid);
+        assert_eq!(game.scores(&grid), [4, 2]);
+
+        play_out(&mut game, &mut grid);
+        assert_eq!(game.scores(&grid), [4, 0]);
+        assert_eq!(game.winner(&grid), Some(Player::One));
+        assert_eq!(game.remaining(&grid), 0);
+    }
+
+    #[test]
+    fn test_draw_and_resized_board() {
+        let grid = Grid::new(8, 8);
+        let game = Match::new(8, 8, 1, 1);
+        assert_eq!(game.winner(&grid), None);
+
+        let mut colors = Colors::new(8, 8);
+        colors.set(0, 0, Some(Player::Two));
+        let mut bigger = Grid::new(10, 8);
+        bigger.set(0, 0, CellState::Alive);
+        colors.advance(&grid, &bigger);
+        assert_eq!(colors.get(0, 0), None);
+        assert_eq!(colors.scores(&bigger), [0, 0]);
+    }
+}
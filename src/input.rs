@@ -0,0 +1,218 @@
+/// A position on screen, in pixels
+pub type Point = (f32, f32);
+
+/// Distance in pixels the pointer has to travel before a press becomes a drag
+pub const DRAG_THRESHOLD: f32 = 4.0;
+
+/// Time in seconds a press can be held in place before it becomes a drag
+pub const CLICK_TIMEOUT: f32 = 0.25;
+
+/// What a mouse button did during the current frame
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Gesture {
+    /// Nothing worth acting on happened
+    None,
+    /// The button was released before the press turned into a drag
+    Click(Point),
+    /// The pointer is being dragged from `from` to `to`.
+    /// On the first drag frame `from` is the position where the press started,
+    /// so the cell under the initial press gets painted as well.
+    Drag { from: Point, to: Point },
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum State {
+    Idle,
+    Pressed { start: Point, held: f32 },
+    Dragging { last: Point },
+}
+
+/// Small state machine telling a short click apart from a press-and-drag
+///
+/// A press stays pending until the pointer moves further than `DRAG_THRESHOLD`
+/// or is held longer than `CLICK_TIMEOUT`, at which point it turns into a drag.
+/// Releasing a pending press produces a single `Gesture::Click`.
+pub struct GestureTracker {
+    state: State,
+}
+
+impl GestureTracker {
+    pub fn new() -> Self {
+        Self { state: State::Idle }
+    }
+
+    /// Feed the tracker with the button state for the current frame
+    ///
+    /// # Arguments
+    /// * `down` - Whether the button is currently held
+    /// * `pos` - The current pointer position
+    /// * `dt` - Time elapsed since the previous frame, in seconds
+    ///
+    /// # Returns
+    /// The `Gesture` the game should react to this frame
+    pub fn update(&mut self, down: bool, pos: Point, dt: f32) -> Gesture {
+        let (state, gesture) = match (self.state, down) {
+            (State::Idle, true) => (
+                State::Pressed {
+                    start: pos,
+                    held: 0.0,
+                },
+                Gesture::None,
+            ),
+            (State::Idle, false) => (State::Idle, Gesture::None),
+            (State::Pressed { start, held }, true) => {
+                let held = held + dt;
+                if distance(start, pos) >= DRAG_THRESHOLD || held >= CLICK_TIMEOUT {
+                    (
+                        State::Dragging { last: pos },
+                        Gesture::Drag {
+                            from: start,
+                            to: pos,
+                        },
+                    )
+                } else {
+                    (State::Pressed { start, held }, Gesture::None)
+                }
+            }
+            (State::Pressed { start, .. }, false) => (State::Idle, Gesture::Click(start)),
+            (State::Dragging { last }, true) => (
+                State::Dragging { last: pos },
+                Gesture::Drag {
+                    from: last,
+                    to: pos,
+                },
+            ),
+            (State::Dragging { .. }, false) => (State::Idle, Gesture::None),
+        };
+
+        self.state = state;
+        gesture
+    }
+}
+
+/// How a pair of fingers moved between two frames
+///
+/// # Returns
+/// The distance the midpoint between the fingers travelled, to pan by, and the
+/// ratio between the new and old distance between the fingers, to zoom by
+pub fn two_finger_motion(before: [Point; 2], after: [Point; 2]) -> (Point, f32) {
+    let (a, b) = (midpoint(before), midpoint(after));
+    let spread = distance(before[0], before[1]);
+    let zoom = if spread > 0.0 {
+        distance(after[0], after[1]) / spread
+    } else {
+        1.0
+    };
+    ((b.0 - a.0, b.1 - a.1), zoom)
+}
+
+/// The point halfway between two fingers
+pub fn midpoint([a, b]: [Point; 2]) -> Point {
+    ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+}
+
+/// Straight-line distance between two points
+pub fn distance(a: Point, b: Point) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn test_short_press_is_a_click() {
+        let mut tracker = GestureTracker::new();
+
+        assert_eq!(tracker.update(true, (10.0, 10.0), FRAME), Gesture::None);
+        assert_eq!(tracker.update(true, (11.0, 10.0), FRAME), Gesture::None);
+        assert_eq!(
+            tracker.update(false, (11.0, 10.0), FRAME),
+            Gesture::Click((10.0, 10.0))
+        );
+
+        // Nothing else happens once the button is released
+        assert_eq!(tracker.update(false, (11.0, 10.0), FRAME), Gesture::None);
+    }
+
+    #[test]
+    fn test_moving_past_threshold_is_a_drag() {
+        let mut tracker = GestureTracker::new();
+
+        tracker.update(true, (10.0, 10.0), FRAME);
+        assert_eq!(
+            tracker.update(true, (20.0, 10.0), FRAME),
+            Gesture::Drag {
+                from: (10.0, 10.0),
+                to: (20.0, 10.0)
+            }
+        );
+        assert_eq!(
+            tracker.update(true, (30.0, 10.0), FRAME),
+            Gesture::Drag {
+                from: (20.0, 10.0),
+                to: (30.0, 10.0)
+            }
+        );
+
+        // Releasing a drag never produces a click
+        assert_eq!(tracker.update(false, (30.0, 10.0), FRAME), Gesture::None);
+    }
+
+    #[test]
+    fn test_holding_still_past_timeout_is_a_drag() {
+        let mut tracker = GestureTracker::new();
+
+        tracker.update(true, (10.0, 10.0), FRAME);
+        assert_eq!(
+            tracker.update(true, (10.0, 10.0), CLICK_TIMEOUT),
+            Gesture::Drag {
+                from: (10.0, 10.0),
+                to: (10.0, 10.0)
+            }
+        );
+        assert_eq!(tracker.update(false, (10.0, 10.0), FRAME), Gesture::None);
+    }
+
+    #[test]
+    fn test_two_finger_pan_and_pinch() {
+        // Both fingers move right by 10: a pure pan
+        let (pan, zoom) =
+            two_finger_motion([(0.0, 0.0), (100.0, 0.0)], [(10.0, 0.0), (110.0, 0.0)]);
+        assert_eq!((pan, zoom), ((10.0, 0.0), 1.0));
+
+        // Fingers spread apart around the same midpoint: a pure zoom
+        let (pan, zoom) =
+            two_finger_motion([(40.0, 50.0), (60.0, 50.0)], [(30.0, 50.0), (70.0, 50.0)]);
+        assert_eq!((pan, zoom), ((0.0, 0.0), 2.0));
+
+        // Fingers on the same spot can't zoom
+        let (_, zoom) = two_finger_motion([(5.0, 5.0), (5.0, 5.0)], [(0.0, 0.0), (9.0, 9.0)]);
+        assert_eq!(zoom, 1.0);
+    }
+
+    #[test]
+    fn test_click_then_immediately_drag() {
+        let mut tracker = GestureTracker::new();
+
+        // First a quick click...
+        tracker.update(true, (10.0, 10.0), FRAME);
+        assert_eq!(
+            tracker.update(false, (10.0, 10.0), FRAME),
+            Gesture::Click((10.0, 10.0))
+        );
+
+        // ...followed right away by a new press that is dragged
+        assert_eq!(tracker.update(true, (50.0, 50.0), FRAME), Gesture::None);
+        assert_eq!(
+            tracker.update(true, (50.0, 60.0), FRAME),
+            Gesture::Drag {
+                from: (50.0, 50.0),
+                to: (50.0, 60.0)
+            }
+        );
+        assert_eq!(tracker.update(false, (50.0, 60.0), FRAME), Gesture::None);
+    }
+}
@@ -0,0 +1,58 @@
+//! The simulation engine behind the `game-of-life` app, for anyone who wants
+//! Conway's Game of Life without the window
+//!
+//! - [`conways`]: the grid of cells and how it steps from one generation to the next, and its JSON, CSV and binary snapshot forms
+//! - [`backend`]: the `GridBackend` trait, for code that takes a board stored cell by cell, bit-packed or sparse alike
+//! - [`rule`]: Life-like rules in B/S notation, e.g. `B36/S23`
+//! - [`formats`]: reading and writing RLE, plaintext, Life 1.06 and macrocell pattern files
+//! - [`patterns`]: the built-in pattern library, and naming the objects on a board
+//! - [`error`](mod@error): `ConwayError`, what loading and saving files and the checked cell accessors fail with
+//! - [`observer`]: callbacks for the births, deaths and population of every generation
+//! - [`log`](mod@log): messages about loading files, settling boards and every generation, for a logger the program sets
+//! - [`cycle`]: telling when a board stopped changing or settled into an oscillation
+//! - [`rng`]: the `Rng` trait every random board is drawn through, and seeded generators that are the same on every platform
+//! - `script`: with the `scripting` feature, seeding a board from a small script
+//! - `ffi`: with the `ffi` feature, a C interface to the grid
+//! - `stream`: with the `async` feature, the generations of a board as an async stream on any runtime
+//!
+//! ```
+//! use game_of_life::conways::{CellState, Grid};
+//!
+//! let mut grid = Grid::new(5, 5);
+//! for x in 1..4 {
+//!     grid.set(x, 2, CellState::Alive);
+//! }
+//! grid.next_cell_generation();
+//! // The blinker turned upright
+//! assert_eq!(grid.get(2, 1), CellState::Alive);
+//! assert_eq!(grid.get(1, 2), CellState::Dead);
+//! assert_eq!(grid.population(), 3);
+//! ```
+//!
+//! Without the default `std` feature the library is `no_std`, needing only
+//! `alloc`, so the grid, rules, RLE and plaintext codecs and cycle detection
+//! run on a microcontroller driving an LED matrix; reading and writing files,
+//! the clock seed and `patterns::identify` need `std`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+pub mod backend;
+pub mod conways;
+pub mod cycle;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod formats;
+pub mod log;
+pub mod observer;
+pub mod patterns;
+pub mod rng;
+pub mod rule;
+#[cfg(feature = "scripting")]
+pub mod script;
+#[cfg(feature = "async")]
+pub mod stream;
+
+pub use error::ConwayError;
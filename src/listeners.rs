@@ -0,0 +1,118 @@
+use crate::conways::Grid;
+use crate::observer::{GridObserver, Stats};
+#[cfg(feature = "audio")]
+use crate::sound::DieOffs;
+use crate::stats_csv::StatsCsv;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// What the game listens to the simulation for, told about every generation
+/// by the board it is set on
+#[derive(Default)]
+pub struct Listeners {
+    /// The `--stats-csv` file getting a row per generation
+    pub stats_csv: Option<(PathBuf, StatsCsv)>,
+    /// Die-offs since the last frame, for die-off clicks
+    #[cfg(feature = "audio")]
+    pub die_offs: DieOffs,
+}
+
+impl Listeners {
+    /// Whether anything listens, without which a board needs no observer
+    pub fn listening(&self) -> bool {
+        self.stats_csv.is_some() || cfg!(feature = "audio")
+    }
+}
+
+impl GridObserver for Listeners {
+    fn before_generation(&mut self, generation: u64) {
+        if let Some((_, csv)) = &mut self.stats_csv {
+            csv.before_generation(generation);
+        }
+    }
+
+    fn on_generation(&mut self, generation: u64, stats: &Stats) {
+        if let Some((_, csv)) = &mut self.stats_csv {
+            csv.on_generation(generation, stats);
+        }
+        #[cfg(feature = "audio")]
+        self.die_offs.on_generation(generation, stats);
+    }
+}
+
+/// The listeners, shared between the game and the board it steps
+///
+/// Only whole generations cross the lock, births and deaths are left to the
+/// default no-ops, so a big board locks no more often than a small one.
+#[derive(Clone, Default)]
+pub struct SharedListeners(Arc<Mutex<Listeners>>);
+
+impl SharedListeners {
+    pub fn lock(&self) -> MutexGuard<'_, Listeners> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// A handle to set on the board with `Grid::set_observer`
+    pub fn observer(&self) -> Box<dyn GridObserver> {
+        Box::new(self.clone())
+    }
+
+    /// Set a handle on `grid` when anything listens, and take any off it
+    /// otherwise, which leaves `Backend::run` free to step it elsewhere
+    pub fn attach(&self, grid: &mut Grid) {
+        if self.lock().listening() {
+            grid.set_observer(self.observer());
+        } else {
+            grid.take_observer();
+        }
+    }
+}
+
+impl GridObserver for SharedListeners {
+    fn before_generation(&mut self, generation: u64) {
+        self.lock().before_generation(generation);
+    }
+
+    fn on_generation(&mut self, generation: u64, stats: &Stats) {
+        self.lock().on_generation(generation, stats);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::CellState;
+
+    #[test]
+    fn test_the_game_sees_what_the_board_was_told() {
+        let listeners = SharedListeners::default();
+        let mut grid = Grid::new(5, 5);
+        for x in 1..4 {
+            grid.set(x, 2, CellState::Alive);
+        }
+        grid.set_observer(listeners.observer());
+        grid.step_n(4);
+        #[cfg(feature = "audio")]
+        assert_eq!(listeners.lock().die_offs.take(), 2);
+        assert!(listeners.lock().stats_csv.is_none());
+        // Replacing the board's observer leaves the listeners to the game
+        grid.take_observer();
+        assert_eq!(Arc::strong_count(&listeners.0), 1);
+    }
+
+    #[test]
+    fn test_boards_only_get_an_observer_when_anything_listens() {
+        let listeners = SharedListeners::default();
+        let mut grid = Grid::new(5, 5);
+        grid.set_observer(listeners.observer());
+        listeners.attach(&mut grid);
+        assert_eq!(grid.has_observer(), cfg!(feature = "audio"));
+
+        let path = std::env::temp_dir().join("listeners_attach.csv");
+        let csv = StatsCsv::create(&path, || 0.0).unwrap();
+        listeners.lock().stats_csv = Some((path.clone(), csv));
+        listeners.attach(&mut grid);
+        assert!(grid.has_observer());
+        std::fs::remove_file(path).ok();
+    }
+}
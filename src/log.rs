@@ -0,0 +1,373 @@
+//! Messages about what the engine and the app are up to, for whoever wants
+//! them: a stand-in for the `log` crate's facade, which isn't available to
+//! this build, with its levels and its `error!` to `trace!` macros
+//!
+//! Nothing is logged until a logger is set with [`set_logger`], and a
+//! message that no logger wants isn't even formatted. The app sets a
+//! [`StderrLogger`], which honours `RUST_LOG` the way `env_logger` does.
+//! Without the `std` feature there is nowhere to keep a logger and the
+//! macros do nothing.
+//!
+//! ```
+//! use game_of_life::log::{self, Level};
+//!
+//! let ((), records) = log::capture(|| log::warn!("corrupt autosave ignored"));
+//! assert_eq!(records.len(), 1);
+//! assert_eq!(records[0].level, Level::Warn);
+//! assert_eq!(records[0].message, "corrupt autosave ignored");
+//! ```
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::cell::RefCell;
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
+/// How much a message matters, from the most to the least
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// Something failed
+    Error,
+    /// Something went wrong that the program recovered from
+    Warn,
+    /// Lifecycle events, e.g. a pattern loaded or a board that stabilized
+    Info,
+    /// What happens every generation
+    Debug,
+    /// Even more than that
+    Trace,
+}
+
+impl Level {
+    /// The level named `name`, in any case, e.g. `warn` or `INFO`
+    pub fn parse(name: &str) -> Option<Level> {
+        [
+            Level::Error,
+            Level::Warn,
+            Level::Info,
+            Level::Debug,
+            Level::Trace,
+        ]
+        .into_iter()
+        .find(|level| name.eq_ignore_ascii_case(level.name()))
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// One message, as a logger is given it
+#[derive(Clone, Copy, Debug)]
+pub struct Record<'a> {
+    pub level: Level,
+    /// The module that logged it, e.g. `game_of_life::formats`
+    pub target: &'a str,
+    pub args: fmt::Arguments<'a>,
+}
+
+/// Where messages go
+pub trait Log: Send + Sync {
+    /// Whether a message at `level` from `target` would be logged, checked
+    /// before the message is formatted
+    fn enabled(&self, level: Level, target: &str) -> bool;
+
+    /// Write `record` out
+    fn log(&self, record: &Record);
+}
+
+#[cfg(feature = "std")]
+static LOGGER: OnceLock<Box<dyn Log>> = OnceLock::new();
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    /// Messages `capture` is collecting on this thread
+    static CAPTURED: RefCell<Option<Vec<Captured>>> = const { RefCell::new(None) };
+}
+
+/// Send every message from now on to `logger`
+///
+/// # Returns
+/// `false`, leaving the logger as it is, if one was set already
+#[cfg(feature = "std")]
+pub fn set_logger(logger: impl Log + 'static) -> bool {
+    LOGGER.set(Box::new(logger)).is_ok()
+}
+
+/// Whether a message at `level` from `target` would go anywhere
+pub fn enabled(level: Level, target: &str) -> bool {
+    #[cfg(feature = "std")]
+    {
+        CAPTURED.with_borrow(Option::is_some)
+            || LOGGER
+                .get()
+                .is_some_and(|logger| logger.enabled(level, target))
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        let _ = (level, target);
+        false
+    }
+}
+
+/// What the macros call once `enabled` said yes
+#[doc(hidden)]
+pub fn __log(level: Level, target: &str, args: fmt::Arguments) {
+    #[cfg(feature = "std")]
+    {
+        let captured = CAPTURED.with_borrow_mut(|captured| match captured {
+            Some(records) => {
+                records.push(Captured {
+                    level,
+                    target: target.to_string(),
+                    message: args.to_string(),
+                });
+                true
+            }
+            None => false,
+        });
+        if let Some(logger) = LOGGER.get().filter(|_| !captured) {
+            logger.log(&Record {
+                level,
+                target,
+                args,
+            });
+        }
+    }
+    #[cfg(not(feature = "std"))]
+    let _ = (level, target, args);
+}
+
+/// A message kept by [`capture`]
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Captured {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// Run `f`, keeping the messages logged on this thread while it runs, at
+/// every level, instead of passing them to the logger
+///
+/// For tests, which run side by side on their own threads and so only see
+/// their own messages.
+#[cfg(feature = "std")]
+pub fn capture<R>(f: impl FnOnce() -> R) -> (R, Vec<Captured>) {
+    let outer = CAPTURED.replace(Some(Vec::new()));
+    let result = f();
+    let records = CAPTURED.replace(outer).unwrap_or_default();
+    (result, records)
+}
+
+/// Which messages a [`StderrLogger`] writes, in `env_logger`'s notation:
+/// comma-separated directives, each a level, a module path that then logs
+/// everything, or `path=level`, with `off` for nothing, e.g.
+/// `warn,game_of_life::formats=debug`
+///
+/// The directive with the longest module path that a message's target is in
+/// decides; a bare level covers every other target.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Filter {
+    /// The most detailed level of targets no directive names, `None` for off
+    default: Option<Level>,
+    directives: Vec<(String, Option<Level>)>,
+}
+
+#[cfg(feature = "std")]
+impl Filter {
+    /// Read a filter, skipping directives that don't make sense the way
+    /// `env_logger` does
+    pub fn parse(spec: &str) -> Filter {
+        let level = |name: &str| match name.trim() {
+            name if name.eq_ignore_ascii_case("off") => Some(None),
+            name => Level::parse(name).map(Some),
+        };
+        let mut filter = Filter::default();
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, name)) => {
+                    if let Some(level) = level(name) {
+                        filter.directives.push((target.trim().to_string(), level));
+                    }
+                }
+                None => match level(directive) {
+                    Some(level) => filter.default = level,
+                    None => filter
+                        .directives
+                        .push((directive.to_string(), Some(Level::Trace))),
+                },
+            }
+        }
+        filter
+    }
+
+    /// Whether a message at `level` from `target` passes
+    pub fn enabled(&self, level: Level, target: &str) -> bool {
+        let within = |path: &str| {
+            target
+                .strip_prefix(path)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        };
+        self.directives
+            .iter()
+            .filter(|(path, _)| within(path))
+            .max_by_key(|(path, _)| path.len())
+            .map_or(self.default, |(_, level)| *level)
+            .is_some_and(|most| level <= most)
+    }
+}
+
+/// Writes messages to stderr as `[WARN  target] message`
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct StderrLogger {
+    filter: Filter,
+}
+
+#[cfg(feature = "std")]
+impl StderrLogger {
+    pub fn new(filter: Filter) -> Self {
+        Self { filter }
+    }
+
+    /// A logger with the filter in `RUST_LOG`, or `default` when it isn't set
+    pub fn from_env(default: &str) -> Self {
+        let spec = std::env::var("RUST_LOG").unwrap_or_else(|_| default.to_string());
+        Self::new(Filter::parse(&spec))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Log for StderrLogger {
+    fn enabled(&self, level: Level, target: &str) -> bool {
+        self.filter.enabled(level, target)
+    }
+
+    fn log(&self, record: &Record) {
+        std::eprintln!("[{:<5} {}] {}", record.level, record.target, record.args);
+    }
+}
+
+// The macros are exported under names of their own and used through this
+// module, so that they don't take the crate root's `log!`, `warn!` and the
+// rest, and switching to the `log` crate is a change of path
+
+/// Log a message at a level, `format!` style
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log {
+    ($level:expr, $($arg:tt)+) => {{
+        let level = $level;
+        if $crate::log::enabled(level, ::core::module_path!()) {
+            $crate::log::__log(level, ::core::module_path!(), ::core::format_args!($($arg)+));
+        }
+    }};
+}
+
+/// Log a message at [`Level::Error`](crate::log::Level::Error)
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __error {
+    ($($arg:tt)+) => { $crate::__log!($crate::log::Level::Error, $($arg)+) };
+}
+
+/// Log a message at [`Level::Warn`](crate::log::Level::Warn)
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __warn {
+    ($($arg:tt)+) => { $crate::__log!($crate::log::Level::Warn, $($arg)+) };
+}
+
+/// Log a message at [`Level::Info`](crate::log::Level::Info)
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __info {
+    ($($arg:tt)+) => { $crate::__log!($crate::log::Level::Info, $($arg)+) };
+}
+
+/// Log a message at [`Level::Debug`](crate::log::Level::Debug)
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __debug {
+    ($($arg:tt)+) => { $crate::__log!($crate::log::Level::Debug, $($arg)+) };
+}
+
+/// Log a message at [`Level::Trace`](crate::log::Level::Trace)
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __trace {
+    ($($arg:tt)+) => { $crate::__log!($crate::log::Level::Trace, $($arg)+) };
+}
+
+#[doc(inline)]
+pub use crate::{
+    __debug as debug, __error as error, __info as info, __log as log, __trace as trace,
+    __warn as warn,
+};
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_picks_the_closest_directive() {
+        let filter =
+            Filter::parse("warn, game_of_life::formats=debug,game_of_life::formats::rle=off,app");
+        assert!(filter.enabled(Level::Warn, "game_of_life::cycle"));
+        assert!(!filter.enabled(Level::Info, "game_of_life::cycle"));
+        assert!(filter.enabled(Level::Debug, "game_of_life::formats"));
+        assert!(filter.enabled(Level::Debug, "game_of_life::formats::life106"));
+        assert!(!filter.enabled(Level::Error, "game_of_life::formats::rle"));
+        // A module path only covers its own modules, not ones that share a prefix
+        assert!(!filter.enabled(Level::Info, "game_of_life::formatsx"));
+        assert!(filter.enabled(Level::Trace, "app::collab"));
+        assert!(!Filter::parse("").enabled(Level::Error, "app"));
+        assert!(!Filter::parse("nonsense=loud,OFF").enabled(Level::Error, "nonsense"));
+        assert!(Filter::parse("INFO").enabled(Level::Info, "app"));
+    }
+
+    #[test]
+    fn test_capture_keeps_this_threads_messages() {
+        let (sum, records) = capture(|| {
+            crate::log::info!("loaded {}", "glider.rle");
+            let ((), inner) = capture(|| crate::log::debug!("inner"));
+            assert_eq!(inner.len(), 1);
+            std::thread::spawn(|| crate::log::error!("elsewhere"))
+                .join()
+                .unwrap();
+            1 + 1
+        });
+        assert_eq!(sum, 2);
+        assert_eq!(
+            records,
+            [Captured {
+                level: Level::Info,
+                target: "game_of_life::log::tests".to_string(),
+                message: "loaded glider.rle".to_string(),
+            }]
+        );
+        assert!(!CAPTURED.with_borrow(Option::is_some));
+    }
+
+    #[test]
+    fn test_levels_print_padded() {
+        assert_eq!(format!("[{:<5}]", Level::Warn), "[WARN ]");
+        assert_eq!(Level::parse("Debug"), Some(Level::Debug));
+        assert_eq!(Level::parse("verbose"), None);
+        assert!(Level::Error < Level::Trace);
+    }
+}
@@ -1,10 +1,133 @@
-mod conways;
+mod background;
+mod bindings;
+mod bookmarks;
+mod buttons;
+mod camera;
+mod cli;
+mod clipboard;
+mod collab;
+mod command;
+mod cursor;
+mod daily;
+mod export;
+mod focus;
+#[cfg(feature = "gif")]
+mod gif;
+mod graph;
+mod headless;
+mod help;
+mod history;
+mod immigration;
+mod input;
+mod listeners;
+mod measure;
+mod minimap;
+mod notice;
+mod palette;
+mod paste;
+mod phantom;
+mod preview;
+mod puzzle;
+mod reference;
+mod render;
+mod replay;
+mod ruler;
+mod run_recording;
+mod saves;
+mod scrubber;
+mod selection;
+mod session;
+mod settings;
+mod shape;
+mod slider;
+mod slots;
+#[cfg(any(feature = "audio", test))]
+mod sound;
+mod speed_presets;
+mod stats;
+mod stats_csv;
+mod status_bar;
+mod symmetry;
+mod text_entry;
+mod theme;
+mod tile;
+mod timeline;
+mod timing;
+mod title;
+mod tool;
+mod touch;
+#[cfg(all(feature = "tui", unix))]
+mod tui;
+mod watch;
+#[cfg(feature = "websocket")]
+mod websocket;
+mod wrap;
+use backend::Backend;
+use background::BackgroundStyle;
+use bindings::{Binding, InputCommand};
+use bookmarks::Bookmarks;
+use camera::{Camera, Glide};
+use cli::{Config, Invocation};
+use clipboard::Clipboard;
+use collab::{Collab, Edit};
+use command::{Command, CommandQueue};
+use conways::svg::SvgStyle;
+use conways::{BoundaryCondition, Edge};
+use cursor::{CountPrefix, Cursor, CursorAction, CursorEffect, PrefixOutcome};
+use cycle::{CycleDetector, Stabilization};
+use daily::Lifespan;
+use focus::{FocusAction, FocusPause};
+use game_of_life::log::{debug, error, info, warn, StderrLogger};
+#[cfg(feature = "scripting")]
+use game_of_life::script;
+use game_of_life::{backend, conways, cycle, error, formats, observer, patterns, rng, rule};
+use history::History;
+use immigration::{Match, Phase};
+use input::{Gesture, GestureTracker, Point};
+use listeners::SharedListeners;
 use macroquad::prelude::*;
+use measure::{Measurement, Measurer};
+use notice::Notice;
+use palette::Palette;
+use paste::PendingPaste;
+use preview::Preview;
+use puzzle::{Attempt, Outcome, Puzzle, Stage};
+use reference::{Placement, ReferenceLayer, REFERENCE_ALPHA};
+use render::{Renderer, ScreenRenderer};
+use replay::{Event, Replay, ReplayError};
+use rng::SplitMix64;
+use rule::Rule;
+use run_recording::RunRecording;
+use selection::Selection;
+use session::Session;
+use shape::CellShape;
+use slots::{SlotAction, SlotInfo, Slots};
+use speed_presets::SpeedPresets;
+use stats::Stats;
+use stats_csv::StatsCsv;
+use symmetry::Symmetry;
+use text_entry::{EntryKey, EntryOutcome, TextEntry};
+use theme::Theme;
+use timeline::Timeline;
+use timing::{Accumulator, RateMeter, Turbo};
+use title::TitleUpdater;
+use tool::Tool;
+use touch::{TouchGesture, TouchRecognizer};
+use watch::FileWatcher;
 
-const GRID_WIDTH: usize = 80;
-const GRID_HEIGHT: usize = 60;
-const CELL_SIZE: f32 = 10.0;
-const UPDATE_INTERVAL: f32 = 0.1;
+const MIN_UPDATE_INTERVAL: f32 = 1.0 / slider::MAX_SLIDER_SPEED;
+const MAX_UPDATE_INTERVAL: f32 = 1.0 / slider::MIN_SLIDER_SPEED;
+/// Opacity of the onion skin's outlines, as a share of the live cell color's
+const ONION_ALPHA: f32 = 0.4;
+/// Number of generations a dead cell's trail takes to fade out
+const TRAIL_LENGTH: u8 = 8;
+const HUD_FONT_SIZE: f32 = 20.0;
+/// Zoom change for one notch of the mouse wheel
+const ZOOM_STEP: f32 = 1.25;
+/// Distance in pixels between the pointer and the cell inspector
+const INSPECTOR_OFFSET: f32 = 16.0;
+/// Rows or columns added or removed by one resize key press
+const RESIZE_STEP: isize = 10;
 
 #[derive(PartialEq)]
 enum State {
@@ -14,109 +137,5688 @@ enum State {
 
 struct Game {
     grid: conways::Grid,
-    last_update: f32,
+    /// Size of the board from the command line; loaded patterns are centered on
+    /// a board at least this large
+    board_size: (usize, usize),
+    /// Share of cells alive in random boards and noise, from the command line
+    density: f64,
+    /// Time towards the next fixed-speed generations
+    accumulator: Accumulator,
+    update_interval: f32,
+    speed_presets: SpeedPresets,
+    /// Set while turbo mode steps as fast as the frame budget allows,
+    /// instead of once per `update_interval`
+    turbo: Option<Turbo>,
+    /// Generations per second actually run, shown by the HUD next to the target speed
+    rate: RateMeter,
+    /// Population after every generation, plotted by the graph
+    stats: Stats,
+    /// Peak and stabilization of the `--daily` soup, while it's on the board
+    lifespan: Option<Lifespan>,
+    /// Keeps the generation and population in the title, a few times a second
+    title: TitleUpdater,
+    show_graph: bool,
+    /// Whether the cell under the cursor is described next to it
+    show_inspector: bool,
+    /// The population graph as a polyline in screen space, rebuilt every frame
+    /// into the same buffer
+    graph: Vec<Point>,
     state: State,
+    left_button: GestureTracker,
+    right_button: GestureTracker,
+    symmetry: Symmetry,
+    /// What the left mouse button does
+    tool: Tool,
+    /// State a toggle tool drag paints, picked from the cell the drag started on
+    drag_state: Option<conways::CellState>,
+    /// The keyboard cursor, while shown
+    cursor: Option<Cursor>,
+    /// Count and `g` typed before a cursor motion
+    prefix: CountPrefix,
+    /// Distances between cells the player measured, and the mode picking them
+    measure: Measurer,
+    /// Edges used for the following generations, applied to whatever board is loaded
+    boundary: BoundaryCondition,
+    /// Rule used for the following generations, applied to whatever board is loaded
+    rule: Rule,
+    /// Rules `CycleRule` switches between
+    rules: Vec<Rule>,
+    /// The rulestring being typed, while the rule entry is open
+    rule_entry: Option<TextEntry>,
+    /// The generation being typed, while the go to generation entry is open
+    goto_entry: Option<TextEntry>,
+    /// The generation being fast-forwarded to, a frame's budget at a time
+    fast_forward: Option<FastForward>,
+    /// Whether the board was edited since it was last saved or loaded
+    dirty: bool,
+    quitting: Quitting,
+    /// Painting done this frame, applied before the next simulation step
+    edits: CommandQueue,
+    /// The board shared with the games that joined it, or the one joined
+    collab: Option<Collab>,
+    /// Sends the board to WebSocket viewers
+    #[cfg(feature = "websocket")]
+    broadcaster: Option<websocket::Broadcaster>,
+    /// Undo and redo steps for edits and simulation steps
+    history: History,
+    /// The latest generations, for stepping backwards with the arrow keys
+    timeline: Timeline,
+    /// A shrink that would remove live cells, waiting for the same key to be pressed again
+    pending_resize: Option<InputCommand>,
+    /// The quick save slots and what they hold
+    slots: Slots,
+    /// A slot save or load that needs confirming, waiting for the same key to be pressed again
+    pending_slot: Option<InputCommand>,
+    /// Whether to pause once `detector` finds the board stable or oscillating
+    auto_pause: bool,
+    detector: CycleDetector,
+    selection: Option<Selection>,
+    clipboard: Option<Clipboard>,
+    paste: Option<PendingPaste>,
+    palette: Palette,
+    bookmarks: Bookmarks,
+    /// The two-player Immigration match being played, if any
+    immigration: Option<Match>,
+    /// The `--puzzle` being solved
+    puzzle: Option<Attempt>,
+    /// Pauses while the window is in the background, unless turned off in `conways.toml`
+    focus_pause: Option<FocusPause>,
+    notice: Notice,
+    /// How many saves back the next `Ctrl+O` loads, so repeated presses cycle
+    load_index: usize,
+    since_autosave: f32,
+    /// Seconds between autosaves, 0 when only saving on exit
+    autosave_interval: f32,
+    /// A previous session found at startup, waiting for the user to restore or dismiss it
+    session_offer: Option<Session>,
+    /// The `--pattern` file, watched for changes made in other programs
+    pattern_watch: Option<FileWatcher>,
+    /// The `--script` file and its budget, to run again
+    #[cfg(feature = "scripting")]
+    script: Option<(std::path::PathBuf, u64)>,
+    /// Whether the pattern file changed and reloading it is being offered
+    reload_offer: bool,
+    theme: Theme,
+    /// Index of `theme` in `Theme::presets`
+    theme_index: usize,
+    /// Colors from `--color name=#hex`, applied on top of every preset
+    color_overrides: Vec<(String, Color)>,
+    show_grid_lines: bool,
+    show_rulers: bool,
+    background_style: BackgroundStyle,
+    /// The background rendered for a combination of style, camera, screen
+    /// size, board size and color, kept until any of them changes
+    background: Option<(BackgroundView, Texture2D)>,
+    cell_shape: CellShape,
+    /// Rows and columns shown across each edge while the board wraps
+    phantom_width: usize,
+    /// Whether a wrapping board is drawn as endless copies of itself, with
+    /// the camera free to pan across them, instead of with `phantom_width` strips
+    seamless_wrap: bool,
+    /// `conways.toml` to remember the cell shape in, when it changes
+    settings_path: Option<std::path::PathBuf>,
+    show_trails: bool,
+    /// Whether live cells are colored by their live neighbor count instead of
+    /// the theme; trails only color dead cells, so both can be on at once
+    show_neighbor_colors: bool,
+    /// Whether placed cells are tinted apart from the ones born of the rule
+    show_origins: bool,
+    /// The births and deaths of the next generation, shown while paused
+    preview: Option<Preview>,
+    show_onion_skin: bool,
+    /// The board one generation back, outlined under the current one when the
+    /// onion skin is on; kept while it is off too, so turning it on shows
+    /// something right away
+    onion: Option<conways::Grid>,
+    /// The `--reference` pattern shown over the board, outside the simulation
+    reference: Option<ReferenceLayer>,
+    /// Whether the keyboard cursor's keys move the reference layer
+    moving_reference: bool,
+    /// Generations left before each cell's trail fades, row by row
+    trail: Vec<u8>,
+    /// Keyboard bindings, read by both the input dispatcher and the help overlay
+    bindings: Vec<Binding>,
+    show_help: bool,
+    camera: Camera,
+    /// The camera the game started with, which an empty board fits back to
+    home: Camera,
+    /// Whether the view should zoom to fit the live cells on the next frame,
+    /// which is when the screen size is known
+    fit_requested: bool,
+    /// The camera on its way to a new framing
+    glide: Option<Glide>,
+    /// Last position of a middle button drag panning the view
+    pan_from: Option<Point>,
+    show_minimap: bool,
+    /// Density of the grid drawn by the minimap, rebuilt every `MINIMAP_REFRESH` seconds
+    minimap: Option<Texture2D>,
+    since_minimap: f32,
+    /// Whether the left button was pressed on the minimap, so dragging keeps moving the view
+    minimap_drag: bool,
+    /// Whether the left button was pressed on the button strip, so dragging off it doesn't paint
+    strip_press: bool,
+    /// Whether that press was on the timeline bar, so dragging scrubs through history
+    scrubbing: bool,
+    /// Whether that press was on the speed slider, so dragging changes the speed
+    sliding: bool,
+    /// Whether pasting stamps like a brush, see `InputCommand::ToggleStampBrush`
+    stamp_brush: bool,
+    /// Top-left cell of the last copy the stamp brush laid down this drag
+    brush_last: Option<(isize, isize)>,
+    /// Whether the selection being moved was grabbed with Ctrl + drag, so
+    /// releasing the button drops it
+    drag_move: bool,
+    /// The region being dragged out with the tile tool
+    tiling: Option<Selection>,
+    /// Cells between tiled copies, the clipboard's size plus a gap when `None`
+    tile_pitch: Option<(usize, usize)>,
+    /// Tells taps, drags and pinches apart on touch screens
+    touch: TouchRecognizer,
+    /// Every command and edit of the session, written to the path on exit with `--record`
+    input_log: Option<(std::path::PathBuf, Replay)>,
+    /// Every board of the run, written to the path on exit with `--record-run`
+    run_log: Option<(std::path::PathBuf, RunRecording)>,
+    /// The `--stats-csv` file and die-off clicks, told about every generation
+    /// by the board
+    listeners: SharedListeners,
+    /// Backend turbo frames and fast-forwards step the board by
+    backend: Backend,
+    /// Hash of the last board of the `--play-run` recording being watched,
+    /// whose boards running steps through instead of simulating
+    run_end: Option<u64>,
+    /// Frames of a `--replay` still to be played back
+    playback: Option<std::vec::IntoIter<replay::Frame>>,
+    /// Seeds for random boards and noise while recording or replaying, which
+    /// need the same ones every time; taken from the clock otherwise
+    seeds: Option<SplitMix64>,
+    /// Saves kept in memory in the browser, which has no file system, oldest first
+    #[cfg(target_arch = "wasm32")]
+    memory_saves: Vec<conways::Grid>,
+    #[cfg(feature = "gif")]
+    recorder: Option<gif::GifRecorder>,
+    /// Frame limit for GIF recordings, guarding against unbounded memory use
+    #[cfg(feature = "gif")]
+    max_gif_frames: usize,
+    /// Which simulation events make a sound
+    #[cfg(feature = "audio")]
+    cues: sound::Cues,
+    /// Generated once the window is open, the frontend the sounds play in
+    #[cfg(feature = "audio")]
+    sounds: Option<Sounds>,
 }
 
-impl Game {
-    fn new() -> Self {
-        let mut grid = conways::Grid::new(GRID_WIDTH, GRID_HEIGHT);
-        Self::setup_glider(&mut grid);
+/// The generated sounds, loaded into the audio context
+#[cfg(feature = "audio")]
+struct Sounds {
+    ticks: Vec<macroquad::audio::Sound>,
+    click: macroquad::audio::Sound,
+}
 
-        Self {
-            grid,
-            last_update: 0.0,
-            state: State::Running,
+#[cfg(feature = "audio")]
+impl Sounds {
+    async fn load() -> Result<Self, macroquad::Error> {
+        let mut ticks = Vec::new();
+        for bytes in sound::tick_sounds() {
+            ticks.push(macroquad::audio::load_sound_from_bytes(&bytes).await?);
         }
+        let click = macroquad::audio::load_sound_from_bytes(&sound::click_sound()).await?;
+        Ok(Self { ticks, click })
     }
+}
+
+/// Prompt shown when quitting would lose edits
+const QUIT_PROMPT: &str =
+    "Unsaved changes: press Y to quit without saving, S to save and quit, Esc to cancel";
+
+/// Notice for the slot keys when there is no data directory to keep slots in
+const NO_SLOTS: &str = "Save slots need a data directory, set HOME or XDG_DATA_HOME";
+
+/// Notice for the reference layer's keys when no `--reference` was given
+const NO_REFERENCE: &str = "No reference layer, start with --reference <FILE> to show one";
+
+/// Where closing the game is at
+/// - `No`: Playing on
+/// - `Asking`: The board has unsaved changes and `QUIT_PROMPT` is up
+/// - `Yes`: The game closes at the end of the frame
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Quitting {
+    No,
+    Asking,
+    Yes,
+}
+
+/// A run to a generation typed into the go to generation entry
+struct FastForward {
+    target: u64,
+    /// Where the run started, for the progress shown meanwhile
+    from: u64,
+    turbo: Turbo,
+}
+
+/// Everything the pre-rendered background depends on
+#[derive(PartialEq)]
+struct BackgroundView {
+    style: BackgroundStyle,
+    camera: Camera,
+    screen: (usize, usize),
+    grid: (usize, usize),
+    color: Color,
+}
 
-    fn setup_glider(grid: &mut conways::Grid) {
-        grid.set(10, 10, conways::CellState::Alive);
-        grid.set(13, 10, conways::CellState::Alive);
-        grid.set(14, 11, conways::CellState::Alive);
-        grid.set(10, 12, conways::CellState::Alive);
-        grid.set(14, 12, conways::CellState::Alive);
-        grid.set(11, 13, conways::CellState::Alive);
-        grid.set(12, 13, conways::CellState::Alive);
-        grid.set(13, 13, conways::CellState::Alive);
-        grid.set(14, 13, conways::CellState::Alive);
+impl Game {
+    fn new(config: &Config, grid: conways::Grid) -> Self {
+        let mut game = Self {
+            grid,
+            board_size: (config.width, config.height),
+            density: config.density.unwrap_or(cli::DEFAULT_DENSITY),
+            accumulator: Accumulator::new(),
+            update_interval: config.update_interval,
+            speed_presets: SpeedPresets::new(config.preset_base, config.speed_presets.clone()),
+            turbo: None,
+            rate: RateMeter::new(),
+            stats: Stats::new(),
+            lifespan: None,
+            title: TitleUpdater::new(),
+            show_graph: false,
+            show_inspector: false,
+            graph: Vec::with_capacity(graph::GRAPH_GENERATIONS),
+            state: if config.paused {
+                State::Paused
+            } else {
+                State::Running
+            },
+            left_button: GestureTracker::new(),
+            right_button: GestureTracker::new(),
+            symmetry: Symmetry::None,
+            tool: Tool::default(),
+            drag_state: None,
+            boundary: config.boundary,
+            rule: config.rule,
+            rules: config.rules.clone(),
+            rule_entry: None,
+            goto_entry: None,
+            fast_forward: None,
+            dirty: false,
+            quitting: Quitting::No,
+            edits: CommandQueue::new(),
+            collab: None,
+            #[cfg(feature = "websocket")]
+            broadcaster: None,
+            history: History::new(),
+            timeline: Timeline::new(config.rewind),
+            pending_resize: None,
+            slots: Slots::new(None),
+            pending_slot: None,
+            cursor: None,
+            prefix: CountPrefix::new(),
+            measure: Measurer::new(),
+            auto_pause: config.auto_pause,
+            detector: CycleDetector::new(config.stable_period),
+            selection: None,
+            clipboard: None,
+            paste: None,
+            palette: Palette::new(),
+            bookmarks: Bookmarks::new(),
+            immigration: None,
+            puzzle: None,
+            focus_pause: config.pause_on_focus_loss.then(FocusPause::new),
+            notice: Notice::new(),
+            load_index: 0,
+            since_autosave: 0.0,
+            autosave_interval: config.autosave_interval,
+            session_offer: None,
+            pattern_watch: None,
+            #[cfg(feature = "scripting")]
+            script: config
+                .script
+                .clone()
+                .map(|path| (path, config.script_budget)),
+            reload_offer: false,
+            theme: Theme::classic(),
+            theme_index: config.theme,
+            color_overrides: config.color_overrides.clone(),
+            show_grid_lines: false,
+            show_rulers: false,
+            background_style: config.background,
+            background: None,
+            cell_shape: config.cell_shape,
+            phantom_width: config.phantom_width,
+            seamless_wrap: true,
+            settings_path: None,
+            show_trails: false,
+            show_neighbor_colors: false,
+            show_origins: false,
+            preview: None,
+            show_onion_skin: false,
+            onion: None,
+            reference: None,
+            moving_reference: false,
+            trail: Vec::new(),
+            bindings: bindings::default_bindings(),
+            show_help: false,
+            camera: Camera::new(config.cell_size),
+            home: Camera::new(config.cell_size),
+            fit_requested: false,
+            glide: None,
+            pan_from: None,
+            show_minimap: false,
+            minimap: None,
+            since_minimap: 0.0,
+            minimap_drag: false,
+            strip_press: false,
+            scrubbing: false,
+            sliding: false,
+            drag_move: false,
+            stamp_brush: false,
+            brush_last: None,
+            tiling: None,
+            tile_pitch: config.tile_pitch,
+            touch: TouchRecognizer::new(),
+            input_log: None,
+            run_log: None,
+            listeners: SharedListeners::default(),
+            backend: config.backend,
+            run_end: None,
+            playback: None,
+            seeds: None,
+            #[cfg(target_arch = "wasm32")]
+            memory_saves: Vec::new(),
+            #[cfg(feature = "gif")]
+            recorder: None,
+            #[cfg(feature = "gif")]
+            max_gif_frames: config.max_gif_frames,
+            #[cfg(feature = "audio")]
+            cues: sound::Cues::new(),
+            #[cfg(feature = "audio")]
+            #[cfg(feature = "audio")]
+            sounds: None,
+        };
+        game.set_theme(Theme::presets()[game.theme_index].clone());
+        // The starting board counts, so a still life pauses after a single generation
+        game.detector.observe(&game.grid);
+        game
     }
 
     fn update(&mut self, dt: f32) {
-        self.last_update += dt;
+        if let Some((_, log)) = &mut self.input_log {
+            log.end_frame(dt, self.edits.pending());
+        }
+        self.notice.update(dt);
+        // Also when paused, so the inspector counts neighbors across the edges in use
+        self.grid.set_boundary(self.boundary);
+        self.grid.set_rule(self.rule);
+        self.sync_collab();
+        if self.edits.has_edits() {
+            self.timeline.truncate();
+            self.detector.reset();
+            self.dirty = true;
+        }
+        self.edits.apply(&mut self.grid, &mut self.history);
+        if let Some(preview) = self
+            .preview
+            .as_mut()
+            .filter(|_| self.state == State::Paused)
+        {
+            preview.refresh(&self.grid);
+        }
+
+        if let Some(watch) = &mut self.pattern_watch {
+            if watch.poll(dt) {
+                self.reload_offer = true;
+            }
+        }
+
+        self.since_autosave += dt;
+        if self.autosave_interval > 0.0 && self.since_autosave >= self.autosave_interval {
+            self.autosave();
+        }
+
+        self.since_minimap += dt;
+        if self.show_minimap && self.since_minimap >= minimap::MINIMAP_REFRESH {
+            self.refresh_minimap();
+        }
+
+        #[cfg(feature = "audio")]
+        let generation = self.grid.generation();
+        if self.fast_forward.is_some() {
+            self.run_fast_forward(dt);
+        } else if self.state == State::Running && !self.mode_waits() {
+            self.step_simulation(dt);
+        } else {
+            self.rate.add(0, dt);
+        }
+        #[cfg(feature = "audio")]
+        self.play_cue(dt, self.grid.generation() != generation);
+        if let Some(Collab::Host(host)) = &mut self.collab {
+            let peers = host.peers();
+            host.sync(&self.grid, self.state == State::Paused);
+            if host.peers() != peers {
+                let joined = if host.peers() > peers {
+                    "joined"
+                } else {
+                    "left"
+                };
+                self.notice
+                    .show(format!("A player {joined}, {} connected", host.peers()));
+            }
+        }
+        #[cfg(feature = "websocket")]
+        if let Some(broadcaster) = &mut self.broadcaster {
+            let viewers = broadcaster.viewers();
+            broadcaster.sync(&self.grid, dt);
+            if broadcaster.viewers() > viewers {
+                self.notice
+                    .show(format!("{} watching over WebSocket", broadcaster.viewers()));
+            }
+        }
 
-        // Update grid every UPDATE_INTERVAL seconds
-        if self.last_update >= UPDATE_INTERVAL && self.state == State::Running {
-            self.grid.next_cell_generation();
-            self.last_update = 0.0;
+        if self.show_graph {
+            graph::polyline(
+                self.stats.recent(graph::GRAPH_GENERATIONS),
+                graph::graph_bounds((screen_width(), screen_height())),
+                &mut self.graph,
+            );
+        }
+    }
+
+    /// Run the generations due this frame, in turbo mode or at the fixed speed
+    fn step_simulation(&mut self, dt: f32) {
+        self.grid.set_boundary(self.boundary);
+        self.grid.set_rule(self.rule);
+        self.listeners.attach(&mut self.grid);
+        let plain = self.immigration.is_none() && self.puzzle.is_none();
+        // Matches and puzzles look at every generation, which turbo frames skip over
+        let watching = self.run_end.is_some();
+        if let Some(turbo) = self.turbo.as_mut().filter(|_| plain && !watching) {
+            // The whole frame undoes as one step, snapshotting every generation would be too slow
+            turbo.adapt(dt as f64);
+            self.history.record(&self.grid);
+            let before = self.grid.clone();
+            let (stats, detector) = (&mut self.stats, &mut self.detector);
+            let lifespan = &mut self.lifespan;
+            let mut stable = None;
+            let steps = self.backend.run(&mut self.grid, |board| {
+                turbo.run(macroquad::miniquad::date::now, || {
+                    board.step();
+                    let population = board.population();
+                    stats.record(population);
+                    if let Some(lifespan) = lifespan.as_mut() {
+                        lifespan.record(board.generation(), population);
+                    }
+                    stable = stable.or_else(|| detector.observe(board));
+                })
+            });
+            self.rate.add(steps, dt);
+            self.check_stats_csv();
+            // Stepping back goes to the start of the frame, like undo
+            self.timeline.record(&before, &self.grid);
+            self.record_run();
+            self.update_trail();
+            if let Some(stable) = stable {
+                self.stagnated(stable);
+            }
+
+            #[cfg(feature = "gif")]
+            self.record_frame();
+            // Possibly many generations back, which `onion_skin` leaves out
+            debug!(
+                "{steps} generations to {} this frame, population {}",
+                self.grid.generation(),
+                self.grid.population()
+            );
+            self.onion = Some(before);
+        } else {
+            // One generation every `update_interval` seconds, however long frames take
+            let steps = self.accumulator.advance(dt, self.update_interval);
+            for _ in 0..steps {
+                self.step_once();
+                if self.state != State::Running {
+                    break;
+                }
+            }
+            self.rate.add(steps as u64, dt);
+        }
+    }
+
+    /// Advance a single generation, keeping it in the undo history and timeline
+    fn step_once(&mut self) {
+        if self.play_run_step() {
+            return;
+        }
+        self.grid.set_boundary(self.boundary);
+        self.grid.set_rule(self.rule);
+        self.listeners.attach(&mut self.grid);
+        let before = self.grid.clone();
+        self.history.step(&mut self.grid);
+        self.timeline.record(&before, &self.grid);
+        self.record_run();
+        self.check_stats_csv();
+        self.stats.record(self.grid.population());
+        if let Some(lifespan) = &mut self.lifespan {
+            lifespan.record(self.grid.generation(), self.grid.population());
+        }
+        self.update_trail();
+        if let Some(game) = &mut self.immigration {
+            let running = game.phase() == Phase::Running;
+            if game.advance(&before, &self.grid) == Phase::Results && running {
+                self.state = State::Paused;
+                self.notice.show("Match over");
+            }
+        }
+        if let Some(attempt) = &mut self.puzzle {
+            if let Some(outcome) = attempt.observe(&self.grid) {
+                self.state = State::Paused;
+                self.notice.show(match outcome {
+                    Outcome::Solved(generation) => {
+                        format!("Solved at generation {generation}! Space to try again")
+                    }
+                    Outcome::Failed => format!(
+                        "Not solved within {} generations, Space to try again",
+                        attempt.puzzle().generations
+                    ),
+                });
+            }
+        }
+        #[cfg(feature = "gif")]
+        self.record_frame();
+
+        if let Some(stable) = self.detector.observe(&self.grid) {
+            self.stagnated(stable);
+        }
+        debug!(
+            "generation {}, population {}",
+            self.grid.generation(),
+            self.grid.population()
+        );
+        self.onion = Some(before);
+    }
+
+    /// Play the sound this frame makes, if any, once the sounds are loaded
+    #[cfg(feature = "audio")]
+    fn play_cue(&mut self, dt: f32, stepped: bool) {
+        let die_off = self.listeners.lock().die_offs.take();
+        let cue = self
+            .cues
+            .frame(dt, stepped, self.grid.population() as u64, die_off);
+        let (Some(cue), Some(sounds)) = (cue, &self.sounds) else {
+            return;
+        };
+        let sound = match cue {
+            sound::Cue::Tick(pitch) => &sounds.ticks[pitch],
+            sound::Cue::Click => &sounds.click,
+        };
+        macroquad::audio::play_sound_once(sound);
+    }
+
+    /// Pause on a board that stopped changing, if auto-pause is on,
+    /// so unattended soups don't keep burning power
+    /// Running matches and puzzles always play out to their end.
+    fn stagnated(&mut self, Stabilization { generation, period }: Stabilization) {
+        info!("stabilized with period {period} at generation {generation}");
+        if let Some(lifespan) = &mut self.lifespan {
+            lifespan.stabilized(Stabilization { generation, period });
+        }
+        if self.auto_pause && !self.mode_runs() {
+            self.state = State::Paused;
+            self.notice.show(format!(
+                "Stable (period {period}) since generation {generation}"
+            ));
+        }
+    }
+
+    /// Queue the edits peers sent when hosting; when joined, send the
+    /// player's edits to the host instead of applying them, and take in the
+    /// host's board
+    fn sync_collab(&mut self) {
+        match &mut self.collab {
+            None => (),
+            Some(Collab::Host(host)) => {
+                let (width, height) = (self.grid.width(), self.grid.height());
+                for edit in host.edits() {
+                    match edit {
+                        Edit::Set { x, y, .. } | Edit::Toggle { x, y }
+                            if x >= width || y >= height => {}
+                        Edit::Set { x, y, state } => self.edits.push(Command::Set { x, y, state }),
+                        Edit::Toggle { x, y } => self.edits.push(Command::Toggle { x, y }),
+                        Edit::Pause(true) => self.state = State::Paused,
+                        Edit::Pause(false) => self.state = State::Running,
+                    }
+                }
+            }
+            Some(Collab::Join(client)) => {
+                let mut sent = Ok(());
+                for command in self.edits.take() {
+                    let edit = match command {
+                        Command::Set { x, y, state } => Edit::Set { x, y, state },
+                        Command::Toggle { x, y } => Edit::Toggle { x, y },
+                        Command::Begin | Command::End => continue,
+                    };
+                    sent = sent.and_then(|()| client.send(&edit));
+                }
+                match sent.and_then(|()| client.sync(&mut self.grid)) {
+                    Ok(received) => {
+                        if received.snapshot {
+                            self.board_replaced();
+                            self.rule = self.grid.rule();
+                            self.boundary = self.grid.boundary();
+                            self.board_size = (self.grid.width(), self.grid.height());
+                        }
+                        match received.paused {
+                            Some(true) => self.state = State::Paused,
+                            Some(false) => self.state = State::Running,
+                            None => (),
+                        }
+                    }
+                    Err(err) => {
+                        // The board stays, to play on alone
+                        self.collab = None;
+                        self.state = State::Paused;
+                        self.notice
+                            .show(format!("Lost the connection to the host: {err}"));
+                    }
+                }
+            }
         }
     }
 
+    /// Forget everything about the previous board once it was replaced by a different one
+    fn board_replaced(&mut self) {
+        self.lifespan = None;
+        self.onion = None;
+        self.stats.clear();
+        self.timeline.clear();
+        self.detector.reset();
+    }
+
     fn draw(&self) {
-        for y in 0..GRID_HEIGHT {
-            for x in 0..GRID_WIDTH {
-                if self.grid.get(x, y) == conways::CellState::Alive {
-                    draw_rectangle(
-                        x as f32 * CELL_SIZE,
-                        y as f32 * CELL_SIZE,
-                        CELL_SIZE,
-                        CELL_SIZE,
-                        WHITE,
-                    );
+        clear_background(self.theme.background);
+        if let Some((_, texture)) = &self.background {
+            draw_texture(texture, 0.0, 0.0, WHITE);
+        }
+
+        for view in self.board_views() {
+            self.draw_board(&view);
+        }
+
+        if self.boundary == BoundaryCondition::Wrap && !self.seamless() {
+            let mut color = self.theme.alive;
+            color.a *= phantom::PHANTOM_ALPHA;
+            let view = self.camera.viewport((screen_width(), screen_height()));
+            for (x, y) in phantom::phantom_cells(&self.grid, self.phantom_width, view) {
+                self.draw_cell_at(&self.camera, (x as f32, y as f32), color);
+            }
+        }
+
+        if !self.seamless() {
+            self.draw_boundary();
+        }
+
+        self.draw_tiling();
+        self.draw_measurements();
+
+        if let Some(cursor) = &self.cursor {
+            let (x, y) = cursor.position();
+            if x < self.grid.width() && y < self.grid.height() {
+                let (sx, sy) = self.camera.cell_to_screen((x as f32, y as f32));
+                let size = self.camera.cell_size;
+                draw_rectangle_lines(sx, sy, size, size, 2.0, self.theme.highlight);
+            }
+        }
+
+        if self.show_rulers {
+            self.draw_rulers();
+        }
+        if self.show_minimap {
+            self.draw_minimap();
+        }
+        if self.show_graph {
+            self.draw_graph();
+        }
+
+        self.draw_hud();
+        self.draw_buttons();
+
+        if self.show_inspector {
+            self.draw_inspector();
+        }
+        if self.show_help {
+            self.draw_help();
+        }
+        if let Some(game) = &self.immigration {
+            if game.phase() == Phase::Results {
+                self.draw_results(game);
+            }
+        }
+    }
+
+    /// The cameras to draw the board through: the camera itself, or one per
+    /// copy in view when a wrapping board is tiled seamlessly
+    fn board_views(&self) -> Vec<Camera> {
+        if !self.seamless() {
+            return vec![self.camera];
+        }
+        let dims = (self.grid.width(), self.grid.height());
+        let view = self.camera.viewport((screen_width(), screen_height()));
+        wrap::tiles(view, dims)
+            .into_iter()
+            .map(|tile| wrap::tile_camera(&self.camera, tile, dims))
+            .collect()
+    }
+
+    /// The board and what sits on it, seen through `view`
+    fn draw_board(&self, view: &Camera) {
+        // Only cells at least partially on screen are drawn
+        let visible = view.visible_cells(
+            (screen_width(), screen_height()),
+            (self.grid.width(), self.grid.height()),
+        );
+        let mut renderer = ScreenRenderer::new(view, self.cell_shape);
+        self.render_board(&mut renderer, visible);
+    }
+
+    /// The board's layers within `xs` and `ys`, bottom to top: trails, the
+    /// onion skin, live cells, the preview, grid lines, the selection and the
+    /// paste, with the reference layer below or above the live cells
+    fn render_board(
+        &self,
+        renderer: &mut dyn Renderer,
+        (xs, ys): (std::ops::Range<usize>, std::ops::Range<usize>),
+    ) {
+        let visible = || {
+            let xs = xs.clone();
+            ys.clone()
+                .flat_map(move |y| xs.clone().map(move |x| (x, y)))
+        };
+
+        self.render_reference(renderer, Placement::Below, (&xs, &ys));
+
+        if self.show_trails && self.trail.len() == self.grid.width() * self.grid.height() {
+            renderer.draw_cells(&mut visible().filter_map(|(x, y)| {
+                let age = self.trail[y * self.grid.width() + x];
+                (age > 0 && self.grid.get(x, y) == conways::CellState::Dead).then(|| {
+                    let mut color = self.theme.trail;
+                    color.a *= age as f32 / TRAIL_LENGTH as f32;
+                    (x, y, color)
+                })
+            }));
+        }
+
+        // The previous generation sits between the trails and the live cells:
+        // its outlines show around cells that died, and under the ones that
+        // survived, whatever colors those are drawn in
+        if let Some(previous) = self.onion_skin() {
+            let mut color = self.theme.alive;
+            color.a *= ONION_ALPHA;
+            renderer.outline_cells(
+                &mut visible()
+                    .filter(|&(x, y)| previous.get(x, y) == conways::CellState::Alive)
+                    .map(|(x, y)| (x, y, color)),
+                1.0,
+            );
+        }
+
+        renderer.draw_cells(
+            &mut visible()
+                .filter(|&(x, y)| self.grid.get(x, y) == conways::CellState::Alive)
+                .map(|(x, y)| {
+                    let owner = self
+                        .immigration
+                        .as_ref()
+                        .and_then(|game| game.colors().get(x, y));
+                    // Counted again on the drawn cells only, leaving the simulation alone
+                    let color = if let Some(player) = owner {
+                        theme::player_color(player)
+                    } else if self.show_neighbor_colors {
+                        theme::neighbor_color(self.grid.count_neighbors(x, y))
+                    } else if self.show_origins && !self.grid.is_born(x, y) {
+                        theme::painted_color(self.theme.alive)
+                    } else {
+                        self.theme.alive
+                    };
+                    (x, y, color)
+                }),
+        );
+
+        self.render_reference(renderer, Placement::Above, (&xs, &ys));
+
+        if let Some(preview) = self
+            .preview
+            .as_ref()
+            .filter(|_| self.state == State::Paused)
+        {
+            self.render_preview(renderer, preview);
+        }
+
+        if self.show_grid_lines {
+            let (width, height) = (self.grid.width() as f32, self.grid.height() as f32);
+            for x in xs.start..=xs.end {
+                let x = x as f32;
+                renderer.draw_line((x, 0.0), (x, height), 1.0, self.theme.grid_lines);
+            }
+            for y in ys.start..=ys.end {
+                let y = y as f32;
+                renderer.draw_line((0.0, y), (width, y), 1.0, self.theme.grid_lines);
+            }
+        }
+
+        if let Some(selection) = &self.selection {
+            let region = selection.region();
+            let mut fill = self.theme.selection;
+            fill.a = 0.15;
+            renderer.draw_overlay(
+                (region.x as f32, region.y as f32),
+                (region.width as f32, region.height as f32),
+                fill,
+                self.theme.selection,
+            );
+        }
+
+        if let Some(paste) = &self.paste {
+            let (px, py) = paste.origin(self.cursor_cell());
+            renderer.draw_cells(
+                &mut self
+                    .grid
+                    .place(paste.pattern(), px, py)
+                    .filter(|&(_, _, state)| *state == conways::CellState::Alive)
+                    .map(|(x, y, _)| (x, y, self.theme.ghost)),
+            );
+        }
+    }
+
+    /// The generation right before the board, when the onion skin shows it
+    /// Left out when the board didn't get here in a single step, e.g. after a
+    /// turbo frame or stepping back.
+    fn onion_skin(&self) -> Option<&conways::Grid> {
+        self.onion.as_ref().filter(|previous| {
+            self.show_onion_skin && previous.generation() + 1 == self.grid.generation()
+        })
+    }
+
+    /// Fade in the reference layer's cells, when it is shown at `placement`,
+    /// and outline the whole layer while it is being moved
+    fn render_reference(
+        &self,
+        renderer: &mut dyn Renderer,
+        placement: Placement,
+        (xs, ys): (&std::ops::Range<usize>, &std::ops::Range<usize>),
+    ) {
+        let Some(layer) = self
+            .reference
+            .as_ref()
+            .filter(|layer| layer.placement() == placement)
+        else {
+            return;
+        };
+        let mut color = self.theme.selection;
+        color.a *= REFERENCE_ALPHA;
+        renderer.draw_cells(
+            &mut layer
+                .cells(&self.grid)
+                .into_iter()
+                .filter(|(x, y)| xs.contains(x) && ys.contains(y))
+                .map(|(x, y)| (x, y, color)),
+        );
+        if self.moving_reference {
+            let (x, y) = layer.offset();
+            let (w, h) = layer.size();
+            renderer.draw_overlay(
+                (x as f32, y as f32),
+                (w as f32, h as f32),
+                Color::new(0.0, 0.0, 0.0, 0.0),
+                color,
+            );
+        }
+    }
+
+    /// Outline the cells about to be born and cross out those about to die
+    fn render_preview(&self, renderer: &mut dyn Renderer, preview: &Preview) {
+        let thickness = (renderer.cell_size() / 8.0).clamp(1.0, 3.0);
+        renderer.outline_cells(
+            &mut preview
+                .births()
+                .iter()
+                .map(|&(x, y)| (x, y, preview::BIRTH_COLOR)),
+            thickness,
+        );
+        for &(x, y) in preview.deaths() {
+            let (x, y) = (x as f32, y as f32);
+            let color = preview::DEATH_COLOR;
+            renderer.draw_line((x, y), (x + 1.0, y + 1.0), thickness, color);
+            renderer.draw_line((x + 1.0, y), (x, y + 1.0), thickness, color);
+        }
+    }
+
+    /// The region being tiled, with the copies it would get ghosted inside
+    fn draw_tiling(&self) {
+        let Some(region) = self.tiling.as_ref().map(Selection::region) else {
+            return;
+        };
+        if let Some(pattern) = self.clipboard.as_ref().map(Clipboard::pattern) {
+            let pitch = self
+                .tile_pitch
+                .unwrap_or_else(|| tile::default_pitch(pattern));
+            let size = (pattern.width(), pattern.height());
+            let origins = tile::tile_origins(region, size, pitch).unwrap_or_default();
+            for (ox, oy) in origins {
+                for (x, y, state) in self.grid.place(pattern, ox as isize, oy as isize) {
+                    if *state == conways::CellState::Alive && region.contains(x, y) {
+                        self.draw_cell(&self.camera, x, y, self.theme.ghost);
+                    }
                 }
             }
         }
+        let (x, y) = self
+            .camera
+            .cell_to_screen((region.x as f32, region.y as f32));
+        let (w, h) = (
+            region.width as f32 * self.camera.cell_size,
+            region.height as f32 * self.camera.cell_size,
+        );
+        draw_rectangle_lines(x, y, w, h, 2.0, self.theme.highlight);
     }
 
-    fn handle_input(&mut self) {
-        if is_key_pressed(KeyCode::Space) {
-            self.state = match self.state {
-                State::Running => State::Paused,
-                State::Paused => State::Running,
+    /// Lines between measured cells, labeled with their distance, and the one
+    /// being measured from the picked cell to the pointer
+    fn draw_measurements(&self) {
+        let center =
+            |(x, y): (usize, usize)| self.camera.cell_to_screen((x as f32 + 0.5, y as f32 + 0.5));
+        let mut lines: Vec<_> = self.measure.pinned().to_vec();
+        if let measure::Stage::Second(from) = self.measure.stage() {
+            let to = self.cell_at(mouse_position()).unwrap_or(from);
+            lines.push(Measurement { from, to });
+        }
+        for measurement in lines {
+            let (from, to) = (center(measurement.from), center(measurement.to));
+            draw_line(from.0, from.1, to.0, to.1, 2.0, self.theme.highlight);
+            for (x, y) in [from, to] {
+                draw_circle(x, y, 3.0, self.theme.highlight);
+            }
+            let text = measurement.to_string();
+            let size = measure_text(&text, None, HUD_FONT_SIZE as u16, 1.0);
+            let (x, y) = ((from.0 + to.0) / 2.0 + 6.0, (from.1 + to.1) / 2.0 - 6.0);
+            let mut panel = self.theme.background;
+            panel.a = 0.8;
+            draw_rectangle(
+                x - 2.0,
+                y - size.offset_y - 2.0,
+                size.width + 4.0,
+                size.height + 4.0,
+                panel,
+            );
+            draw_text(&text, x, y, HUD_FONT_SIZE, self.theme.highlight);
+        }
+    }
+
+    /// Dim the board and show the final counts of a match
+    fn draw_results(&self, game: &Match) {
+        let mut dim = self.theme.background;
+        dim.a = 0.8;
+        draw_rectangle(0.0, 0.0, screen_width(), screen_height(), dim);
+        let [one, two] = game.scores(&self.grid);
+        let verdict = match game.winner(&self.grid) {
+            Some(player) => format!("{} wins!", player.label()),
+            None => "It's a draw".to_string(),
+        };
+        let lines = [
+            (verdict, self.theme.highlight),
+            (
+                format!("{}: {one} cells", immigration::Player::One.label()),
+                theme::player_color(immigration::Player::One),
+            ),
+            (
+                format!("{}: {two} cells", immigration::Player::Two.label()),
+                theme::player_color(immigration::Player::Two),
+            ),
+            ("Press J to close".to_string(), self.theme.hud_text),
+        ];
+        let top = screen_height() / 2.0 - HUD_FONT_SIZE * lines.len() as f32 / 2.0;
+        for (i, (text, color)) in lines.iter().enumerate() {
+            let width = measure_text(text, None, HUD_FONT_SIZE as u16, 1.0).width;
+            draw_text(
+                text,
+                (screen_width() - width) / 2.0,
+                top + i as f32 * HUD_FONT_SIZE * 1.5,
+                HUD_FONT_SIZE,
+                *color,
+            );
+        }
+    }
+
+    /// Dim the board and list every key binding and mouse control, in columns
+    /// when they don't fit in a single one
+    fn draw_help(&self) {
+        let mut dim = self.theme.background;
+        dim.a = 0.85;
+        draw_rectangle(0.0, 0.0, screen_width(), screen_height(), dim);
+
+        let mut entries = bindings::help_entries(&self.bindings);
+        entries.extend(
+            help::MOUSE_HELP
+                .iter()
+                .chain(help::CURSOR_HELP)
+                .map(|(keys, description)| (keys.to_string(), description.to_string())),
+        );
+        entries.extend(
+            self.slots
+                .filled()
+                .map(|(n, info)| (format!("Slot {n}"), info.label())),
+        );
+
+        let size = HUD_FONT_SIZE as u16;
+        let key_width = entries
+            .iter()
+            .map(|(keys, _)| measure_text(keys, None, size, 1.0).width)
+            .fold(0.0, f32::max);
+        let text_width = entries
+            .iter()
+            .map(|(_, description)| measure_text(description, None, size, 1.0).width)
+            .fold(0.0, f32::max);
+        let gap = HUD_FONT_SIZE;
+        let column_width = key_width + text_width + 2.0 * gap;
+        let margin = HUD_FONT_SIZE;
+        let (_, rows) = help::layout_columns(entries.len(), column_width, screen_width() - margin);
+
+        for (i, (keys, description)) in entries.iter().enumerate() {
+            let (column, row) = (i / rows, i % rows);
+            let x = margin + column as f32 * column_width;
+            let y = margin + HUD_FONT_SIZE * (row as f32 + 1.0);
+            draw_text(keys, x, y, HUD_FONT_SIZE, self.theme.highlight);
+            draw_text(
+                description,
+                x + key_width + gap,
+                y,
+                HUD_FONT_SIZE,
+                self.theme.hud_text,
+            );
+        }
+    }
+
+    /// Draw the minimap in the top-right corner, outlining the part of the grid on screen
+    fn draw_minimap(&self) {
+        let screen = (screen_width(), screen_height());
+        let dims = (self.grid.width(), self.grid.height());
+        let bounds = minimap::minimap_bounds(screen, dims);
+
+        draw_rectangle(
+            bounds.x,
+            bounds.y,
+            bounds.w,
+            bounds.h,
+            self.theme.background,
+        );
+        if let Some(texture) = &self.minimap {
+            draw_texture_ex(
+                texture,
+                bounds.x,
+                bounds.y,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(bounds.w, bounds.h)),
+                    ..Default::default()
+                },
+            );
+        }
+        draw_rectangle_lines(
+            bounds.x,
+            bounds.y,
+            bounds.w,
+            bounds.h,
+            1.0,
+            self.theme.grid_lines,
+        );
+
+        let view = minimap::viewport_rect(&self.camera, screen, dims, bounds);
+        draw_rectangle_lines(view.x, view.y, view.w, view.h, 1.0, self.theme.highlight);
+    }
+
+    /// Column numbers along the top and row numbers along the left, with the
+    /// ones of the cell under the pointer picked out
+    fn draw_rulers(&self) {
+        let screen = (screen_width(), screen_height());
+        let (top, left) = (ruler::top_bounds(screen), ruler::left_bounds(screen));
+        let mut panel = self.theme.background;
+        panel.a = 0.85;
+        draw_rectangle(0.0, 0.0, screen.0, top.h, panel);
+        draw_rectangle(left.x, left.y, left.w, left.h, panel);
+
+        let size = ruler::RULER_FONT_SIZE;
+        let hovered = self.cell_at(mouse_position());
+        // Picked out labels cover the ones they land between
+        let label = |text: &str, (x, y): Point, right_aligned: bool, picked: bool| {
+            let dims = measure_text(text, None, size as u16, 1.0);
+            let x = if right_aligned {
+                x - dims.width
+            } else {
+                x - dims.width / 2.0
+            };
+            let color = if picked {
+                draw_rectangle(
+                    x - 2.0,
+                    y - dims.offset_y,
+                    dims.width + 4.0,
+                    dims.height,
+                    panel,
+                );
+                self.theme.highlight
+            } else {
+                self.theme.hud_text
+            };
+            draw_text(text, x, y, size, color);
+        };
+        let baseline = top.h - 4.0;
+        for (column, x) in ruler::column_ticks(&self.camera, screen, self.grid.width()) {
+            draw_line(x, top.h - 3.0, x, top.h, 1.0, self.theme.grid_lines);
+            label(&column.to_string(), (x, baseline), false, false);
+        }
+        for (row, y) in ruler::row_ticks(&self.camera, screen, self.grid.height()) {
+            draw_line(left.w - 3.0, y, left.w, y, 1.0, self.theme.grid_lines);
+            label(
+                &row.to_string(),
+                (left.w - 5.0, y + size / 3.0),
+                true,
+                false,
+            );
+        }
+
+        if let Some((x, y)) = hovered {
+            let half = self.camera.cell_size / 2.0;
+            let (sx, sy) = self.camera.cell_to_screen((x as f32, y as f32));
+            if top.contains((sx + half, top.y)) {
+                let at = (sx + half, baseline);
+                label(&x.to_string(), at, false, true);
+            }
+            if left.contains((left.x, sy + half)) {
+                let at = (left.w - 5.0, sy + half + size / 3.0);
+                label(&y.to_string(), at, true, true);
+            }
+        }
+    }
+
+    /// Room the rulers take along the top and left edges, which overlays
+    /// anchored there keep out of
+    fn ruler_insets(&self) -> (f32, f32) {
+        if self.show_rulers {
+            (ruler::RULER_WIDTH, ruler::RULER_HEIGHT)
+        } else {
+            (0.0, 0.0)
+        }
+    }
+
+    /// A text entry above the HUD, followed by the reason its text was
+    /// rejected, if it was
+    fn draw_entry(&self, entry: &TextEntry) {
+        let y = screen_height() - buttons::STRIP_HEIGHT - HUD_FONT_SIZE - 16.0;
+        let line = format!(
+            "{}: {}_  (Enter to apply, Esc to cancel)",
+            entry.prompt(),
+            entry.text()
+        );
+        let mut panel = self.theme.background;
+        panel.a = 0.8;
+        let width = measure_text(&line, None, HUD_FONT_SIZE as u16, 1.0).width;
+        draw_rectangle(
+            4.0,
+            y - HUD_FONT_SIZE,
+            width + 12.0,
+            HUD_FONT_SIZE + 8.0,
+            panel,
+        );
+        draw_text(&line, 10.0, y, HUD_FONT_SIZE, self.theme.highlight);
+        if let Some(error) = entry.error() {
+            draw_text(error, width + 24.0, y, HUD_FONT_SIZE, self.theme.warning);
+        }
+    }
+
+    /// The row of buttons along the bottom edge, the one under the mouse highlighted
+    fn draw_buttons(&self) {
+        let screen = (screen_width(), screen_height());
+        let strip = buttons::strip_bounds(screen);
+        let mut panel = self.theme.background;
+        panel.a = 0.8;
+        draw_rectangle(strip.x, strip.y, strip.w, strip.h, panel);
+
+        // The timeline bar, filled up to the shown generation
+        let count = self.timeline.len();
+        if count > 1 {
+            let bar = scrubber::bar_bounds(screen);
+            let marker = scrubber::marker_x(self.timeline.position(), bar, count);
+            let mut track = self.theme.hud_text;
+            track.a = 0.2;
+            draw_rectangle(bar.x, bar.y, bar.w, bar.h, track);
+            let mut filled = self.theme.highlight;
+            filled.a = 0.5;
+            draw_rectangle(bar.x, bar.y, marker - bar.x, bar.h, filled);
+            draw_rectangle(marker - 1.5, bar.y, 3.0, bar.h, self.theme.highlight);
+        }
+
+        let labels = buttons::buttons(self.state == State::Paused);
+        let rects = buttons::layout(screen, labels.len());
+        let hovered = buttons::button_at(mouse_position(), &rects);
+        for (i, ((label, _), rect)) in labels.iter().zip(&rects).enumerate() {
+            let color = if hovered == Some(i) {
+                let mut fill = self.theme.highlight;
+                fill.a = 0.2;
+                draw_rectangle(rect.x, rect.y, rect.w, rect.h, fill);
+                self.theme.highlight
+            } else {
+                self.theme.hud_text
             };
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, color);
+            let size = measure_text(label, None, HUD_FONT_SIZE as u16, 1.0);
+            draw_text(
+                label,
+                rect.x + (rect.w - size.width) / 2.0,
+                rect.y + (rect.h + size.height) / 2.0,
+                HUD_FONT_SIZE,
+                color,
+            );
+        }
+
+        // The speed slider, its handle following the speed however it was set
+        let (left, width, y) = slider::track(screen);
+        let x = slider::handle_x(1.0 / self.update_interval, screen);
+        let active = self.sliding || slider::hit(mouse_position(), screen);
+        let color = if active {
+            self.theme.highlight
+        } else {
+            self.theme.hud_text
+        };
+        draw_line(left, y, left + width, y, 2.0, color);
+        draw_rectangle(
+            x - slider::HANDLE_WIDTH / 2.0,
+            y - slider::HANDLE_HEIGHT / 2.0,
+            slider::HANDLE_WIDTH,
+            slider::HANDLE_HEIGHT,
+            color,
+        );
+        if active {
+            let label = format!("{:.1} gen/s", 1.0 / self.update_interval);
+            let size = measure_text(&label, None, HUD_FONT_SIZE as u16, 1.0);
+            let label_x = (x - size.width / 2.0).min(screen.0 - size.width - 4.0);
+            draw_text(&label, label_x, strip.y - 6.0, HUD_FONT_SIZE, color);
         }
+    }
+
+    /// Describe the cell under the cursor next to it, unless the cursor is off the board
+    fn draw_inspector(&self) {
+        let pos = mouse_position();
+        let Some(cell) = self.cell_at(pos) else {
+            return;
+        };
+        let text = self.describe_cell(cell);
+        let size = measure_text(&text, None, HUD_FONT_SIZE as u16, 1.0);
+        // Below and to the right of the pointer, flipped over when that leaves the screen
+        let mut x = pos.0 + INSPECTOR_OFFSET;
+        let mut y = pos.1 + INSPECTOR_OFFSET;
+        if x + size.width + 8.0 > screen_width() {
+            x = pos.0 - INSPECTOR_OFFSET - size.width - 8.0;
+        }
+        if y + size.height + 8.0 > screen_height() {
+            y = pos.1 - INSPECTOR_OFFSET - size.height - 8.0;
+        }
+        let mut panel = self.theme.background;
+        panel.a = 0.8;
+        draw_rectangle(x, y, size.width + 8.0, size.height + 8.0, panel);
+        draw_text(
+            &text,
+            x + 4.0,
+            y + 4.0 + size.offset_y,
+            HUD_FONT_SIZE,
+            self.theme.hud_text,
+        );
+    }
 
+    /// What the inspector shows for a cell
+    fn describe_cell(&self, (x, y): (usize, usize)) -> String {
+        let state = match self.grid.get(x, y) {
+            conways::CellState::Alive => "alive",
+            conways::CellState::Dead => "dead",
+        };
+        let neighbors = self.grid.count_neighbors(x, y);
+        let mut text = format!("({x}, {y}) {state}, {neighbors} live neighbors");
+        // Only while paused, as a running object changes its shape every frame
         if self.state == State::Paused {
-            let cast_to_usize = |num: f32| num as usize / CELL_SIZE as usize;
-            let (x, y) = (
-                cast_to_usize(mouse_position().0),
-                cast_to_usize(mouse_position().1),
-            );
-            match (
-                is_mouse_button_down(MouseButton::Left),
-                is_mouse_button_down(MouseButton::Right),
-            ) {
-                (true, false) => self.grid.set(x, y, conways::CellState::Alive),
-                (false, true) => self.grid.set(x, y, conways::CellState::Dead),
-                _ => (),
+            match self.grid.component(x, y) {
+                Some(cells) if cells.is_empty() => {}
+                Some(cells) => match patterns::identify(&cells) {
+                    Some(name) => text.push_str(&format!(" — {name}")),
+                    None => text.push_str(&format!(" — {} cells", cells.len())),
+                },
+                None => text.push_str(&format!(" — over {} cells", conways::COMPONENT_LIMIT)),
             }
         }
+        text
     }
-}
 
-fn conf() -> Conf {
-    Conf {
-        window_title: "Conway's Game of Life".to_string(),
-        window_width: 800,
-        window_height: 600,
-        ..Default::default()
+    /// Population over the latest generations, as a line over a translucent panel
+    fn draw_graph(&self) {
+        let bounds = graph::graph_bounds((screen_width(), screen_height()));
+        let mut panel = self.theme.background;
+        panel.a = 0.8;
+        draw_rectangle(bounds.x, bounds.y, bounds.w, bounds.h, panel);
+        draw_rectangle_lines(
+            bounds.x,
+            bounds.y,
+            bounds.w,
+            bounds.h,
+            1.0,
+            self.theme.grid_lines,
+        );
+        for pair in self.graph.windows(2) {
+            let ((x1, y1), (x2, y2)) = (pair[0], pair[1]);
+            draw_line(x1, y1, x2, y2, 1.5, self.theme.highlight);
+        }
+        if let Some(population) = self.stats.recent(1).first() {
+            draw_text(
+                &format!("{population}"),
+                bounds.x + 4.0,
+                bounds.y + HUD_FONT_SIZE,
+                HUD_FONT_SIZE,
+                self.theme.hud_text,
+            );
+        }
     }
-}
 
-#[macroquad::main(conf)]
-async fn main() {
-    let mut game = Game::new();
-    loop {
-        clear_background(BLACK);
+    /// Rebuild the minimap texture from the grid's density map
+    /// Render the background again if anything it depends on changed since the last time
+    fn refresh_background(&mut self) {
+        if self.background_style == BackgroundStyle::Off {
+            self.background = None;
+            return;
+        }
+        let view = BackgroundView {
+            style: self.background_style,
+            camera: self.camera,
+            screen: (screen_width() as usize, screen_height() as usize),
+            grid: (self.grid.width(), self.grid.height()),
+            color: self.theme.grid_lines,
+        };
+        if self
+            .background
+            .as_ref()
+            .is_some_and(|(last, _)| *last == view)
+        {
+            return;
+        }
+        let bytes = background::render(
+            view.style,
+            &view.camera,
+            view.screen,
+            view.grid,
+            view.color.into(),
+        );
+        let texture = Texture2D::from_rgba8(view.screen.0 as u16, view.screen.1 as u16, &bytes);
+        texture.set_filter(FilterMode::Nearest);
+        self.background = Some((view, texture));
+    }
 
-        let dt = get_frame_time();
+    fn refresh_minimap(&mut self) {
+        self.since_minimap = 0.0;
+        let tile = minimap::tile_size((self.grid.width(), self.grid.height()));
+        let (density, (w, h)) = self.grid.density_map(tile);
+        let full = (tile * tile) as f32;
 
-        game.handle_input();
+        let mut bytes = Vec::with_capacity(density.len() * 4);
+        for count in density {
+            let mut color = self.theme.alive;
+            // Any live cell shows up, denser tiles are brighter
+            color.a = if count == 0 {
+                0.0
+            } else {
+                0.35 + 0.65 * count as f32 / full
+            };
+            let rgba: [u8; 4] = color.into();
+            bytes.extend_from_slice(&rgba);
+        }
 
-        game.update(dt);
+        let texture = Texture2D::from_rgba8(w as u16, h as u16, &bytes);
+        texture.set_filter(FilterMode::Nearest);
+        self.minimap = Some(texture);
+    }
 
-        game.draw();
+    /// The generation, population and pause state, as the terminal's status line shows them
+    #[cfg(all(feature = "tui", unix))]
+    fn summary(&self) -> String {
+        title::summary(
+            self.grid.generation(),
+            self.grid.population(),
+            self.state == State::Paused,
+        )
+    }
 
-        next_frame().await
+    /// Bring the title up to date, when it is time to
+    fn refresh_title(&mut self, dt: f32) {
+        let paused = self.state == State::Paused;
+        if let Some(text) =
+            self.title
+                .poll(dt, self.grid.generation(), self.grid.population(), paused)
+        {
+            title::set_terminal_title(&text);
+        }
+    }
+
+    /// The speed as the status bar shows it, with the preset or turbo running
+    fn speed_label(&self) -> String {
+        if self.turbo.is_some() {
+            return format!("Turbo: {:.0} gen/s", self.rate.rate());
+        }
+        let speed = 1.0 / self.update_interval;
+        match self.speed_presets.label(speed) {
+            Some(preset) => format!("Speed: {preset} ({:.1} actual)", self.rate.rate()),
+            None => format!("Speed: {speed:.1} gen/s ({:.1} actual)", self.rate.rate()),
+        }
+    }
+
+    /// The text of every segment with something to show, left to right
+    fn status_texts(&self) -> Vec<String> {
+        status_segments()
+            .into_iter()
+            .filter_map(|segment| segment(self))
+            .collect()
+    }
+
+    fn draw_hud(&self) {
+        let screen = (screen_width(), screen_height());
+        let bar = status_bar::bar_bounds(screen);
+        let mut panel = self.theme.background;
+        panel.a = 0.85;
+        draw_rectangle(bar.x, bar.y, bar.w, bar.h, panel);
+        let measure = |text: &str| measure_text(text, None, HUD_FONT_SIZE as u16, 1.0).width;
+        let layout = status_bar::layout(&self.status_texts(), screen.0, measure);
+        let baseline = bar.y + bar.h - 10.0;
+        let mut faded = self.theme.hud_text;
+        faded.a *= 0.5;
+        for x in layout.separators {
+            draw_text(status_bar::SEPARATOR, x, baseline, HUD_FONT_SIZE, faded);
+        }
+        for (x, text) in &layout.segments {
+            draw_text(text, *x, baseline, HUD_FONT_SIZE, self.theme.hud_text);
+        }
+        if let Some(lifespan) = &self.lifespan {
+            draw_text(
+                &lifespan.to_string(),
+                status_bar::MARGIN,
+                bar.y - 6.0,
+                HUD_FONT_SIZE,
+                self.theme.hud_text,
+            );
+        }
+
+        #[cfg(feature = "gif")]
+        if let Some(recorder) = &self.recorder {
+            let text = format!("REC {} frames", recorder.len());
+            let width = measure_text(&text, None, HUD_FONT_SIZE as u16, 1.0).width;
+            draw_text(
+                &text,
+                screen_width() - width - 10.0,
+                self.ruler_insets().1 + HUD_FONT_SIZE,
+                HUD_FONT_SIZE,
+                self.theme.warning,
+            );
+        }
+
+        if let Some(text) = self.notice.text() {
+            let (left, top) = self.ruler_insets();
+            draw_text(
+                text,
+                left + 10.0,
+                top + HUD_FONT_SIZE,
+                HUD_FONT_SIZE,
+                self.theme.highlight,
+            );
+        }
+
+        if let Some(entry) = self.rule_entry.as_ref().or(self.goto_entry.as_ref()) {
+            self.draw_entry(entry);
+        }
+
+        let prompt = if self.quitting == Quitting::Asking {
+            Some(QUIT_PROMPT)
+        } else if self.session_offer.is_some() {
+            Some("Previous session found: press Y to restore it, Esc to dismiss")
+        } else if self.reload_offer {
+            Some("Pattern file changed: press Y to reload it, Esc to dismiss")
+        } else {
+            None
+        };
+        if let Some(prompt) = prompt {
+            let width = measure_text(prompt, None, HUD_FONT_SIZE as u16, 1.0).width;
+            draw_text(
+                prompt,
+                (screen_width() - width) / 2.0,
+                screen_height() / 2.0,
+                HUD_FONT_SIZE,
+                self.theme.highlight,
+            );
+        }
+    }
+
+    /// Trigger the commands bound to the keys pressed this frame
+    fn dispatch_keys(&mut self, ctrl: bool, shift: bool, alt: bool) {
+        let mut pressed: Vec<KeyCode> = self
+            .bindings
+            .iter()
+            .map(|binding| binding.key)
+            .filter(|&key| is_key_pressed(key))
+            .collect();
+        pressed.dedup();
+        // The reference layer being moved takes the keyboard cursor's keys,
+        // running or paused, moving faster with Alt like the cursor
+        if self.moving_reference && !ctrl {
+            let step = if alt { cursor::FAST_STEP as isize } else { 1 };
+            for (key, (dx, dy)) in cursor::MOVE_KEYS {
+                if is_key_pressed(key) {
+                    let board = (self.grid.width(), self.grid.height());
+                    if let Some(layer) = &mut self.reference {
+                        layer.nudge((dx * step, dy * step), board);
+                    }
+                    pressed.retain(|&other| other != key);
+                }
+            }
+        // While paused, the keyboard cursor takes its keys over from the bindings;
+        // Ctrl combinations such as Ctrl+S keep working
+        } else if self.cursor.is_some() && self.state == State::Paused && !ctrl {
+            let keys = cursor::MOVE_KEYS
+                .map(|(key, _)| key)
+                .into_iter()
+                .chain(cursor::PREFIX_KEYS);
+            for key in keys.filter(|&key| is_key_pressed(key)) {
+                match self.prefix.key(key, shift, alt) {
+                    PrefixOutcome::Ignored => continue,
+                    PrefixOutcome::Pending | PrefixOutcome::Cleared => (),
+                    PrefixOutcome::Apply { action, count } => self.use_cursor(action, count),
+                }
+                pressed.retain(|&other| other != key);
+            }
+        }
+        let commands: Vec<_> = pressed
+            .into_iter()
+            .flat_map(|key| bindings::commands_for(&self.bindings, key, ctrl, shift))
+            .collect();
+        for command in commands {
+            self.apply(command);
+        }
+    }
+
+    /// Feed the keys typed this frame to the open entry
+    fn type_entry(&mut self) {
+        let mut keys: Vec<EntryKey> = std::iter::from_fn(get_char_pressed)
+            .map(EntryKey::Char)
+            .collect();
+        for (code, key) in [
+            (KeyCode::Backspace, EntryKey::Backspace),
+            (KeyCode::Enter, EntryKey::Enter),
+            (KeyCode::Escape, EntryKey::Escape),
+        ] {
+            if is_key_pressed(code) {
+                keys.push(key);
+            }
+        }
+        for key in keys {
+            if self.goto_entry.is_some() {
+                self.goto_entry_key(key);
+            } else {
+                self.rule_entry_key(key);
+            }
+        }
+    }
+
+    /// Edit the go to generation entry, heading there once it holds a number
+    fn goto_entry_key(&mut self, key: EntryKey) {
+        let Some(entry) = &mut self.goto_entry else {
+            return;
+        };
+        let parse = |text: &str| {
+            text.parse::<u64>()
+                .map_err(|_| format!("'{text}' isn't a generation number"))
+        };
+        match entry.key(key, parse) {
+            EntryOutcome::Editing => (),
+            EntryOutcome::Cancelled => self.goto_entry = None,
+            EntryOutcome::Submitted(generation) => {
+                self.goto_entry = None;
+                self.go_to(generation);
+            }
+        }
+    }
+
+    /// Show generation `target`: rewind to it when the timeline still has
+    /// it, or run there when it is yet to come
+    fn go_to(&mut self, target: u64) {
+        if self.immigration.is_some() || self.puzzle.is_some() {
+            self.notice
+                .show("Can't jump to a generation during a match or puzzle");
+            return;
+        }
+        self.log(Event::GoTo(target));
+        let shown = self.grid.generation();
+        if let Some(index) = self.timeline.index_of(target, shown) {
+            self.scrub_to(index);
+        } else if target < shown {
+            self.notice.show(format!(
+                "Generation {target} is no longer kept, the oldest one is {}",
+                shown - self.timeline.position() as u64
+            ));
+        } else {
+            self.scrub_to(self.timeline.len() - 1);
+            self.history.record(&self.grid);
+            self.fast_forward = Some(FastForward {
+                target,
+                from: self.grid.generation(),
+                turbo: Turbo::new(),
+            });
+        }
+    }
+
+    /// Run this frame's share of a fast-forward, as many generations as fit
+    /// in the turbo budget
+    fn run_fast_forward(&mut self, dt: f32) {
+        let Some(run) = &mut self.fast_forward else {
+            return;
+        };
+        run.turbo.adapt(dt as f64);
+        let target = run.target;
+        self.listeners.attach(&mut self.grid);
+        let before = self.grid.clone();
+        let stats = &mut self.stats;
+        let steps = self.backend.run(&mut self.grid, |board| {
+            run.turbo.run_while(macroquad::miniquad::date::now, || {
+                board.step();
+                stats.record(board.population());
+                board.generation() < target
+            })
+        });
+        self.rate.add(steps, dt);
+        self.check_stats_csv();
+        self.timeline.record(&before, &self.grid);
+        self.detector.reset();
+        self.update_trail();
+        if self.grid.generation() >= target {
+            self.fast_forward = None;
+            self.notice.show(format!("Generation {target}"));
+        }
+    }
+
+    /// Edit the rule entry, switching to the typed rule once it is valid
+    fn rule_entry_key(&mut self, key: EntryKey) {
+        let Some(entry) = &mut self.rule_entry else {
+            return;
+        };
+        match entry.key(key, str::parse::<Rule>) {
+            EntryOutcome::Editing => (),
+            EntryOutcome::Cancelled => self.rule_entry = None,
+            EntryOutcome::Submitted(rule) => {
+                self.rule_entry = None;
+                self.log(Event::Rule(rule));
+                self.set_rule(rule);
+            }
+        }
+    }
+
+    /// Evolve the following generations by `rule`, keeping the board as it is
+    fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.grid.set_rule(rule);
+        self.detector.reset();
+        let text = match rule.name() {
+            Some(name) => format!("Rule: {name} ({rule})"),
+            None => format!("Rule: {rule}"),
+        };
+        self.notice.show(text);
+    }
+
+    fn handle_input(&mut self) {
+        let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        let alt = is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt);
+
+        // The quit prompt takes every key and click until it is answered
+        if self.quitting == Quitting::Asking {
+            for key in [KeyCode::Y, KeyCode::S, KeyCode::Escape] {
+                if is_key_pressed(key) {
+                    self.answer_quit(key);
+                }
+            }
+            return;
+        }
+
+        // An open entry takes every key press
+        if self.rule_entry.is_some() || self.goto_entry.is_some() {
+            self.type_entry();
+        } else {
+            self.dispatch_keys(ctrl, shift, alt);
+        }
+
+        // The wheel zooms around the cursor, dragging with the middle button pans
+        let pos = mouse_position();
+        let (_, wheel) = mouse_wheel();
+        if wheel != 0.0 {
+            let factor = if wheel > 0.0 {
+                ZOOM_STEP
+            } else {
+                1.0 / ZOOM_STEP
+            };
+            self.camera.zoom_at(pos, factor);
+            // Zooming or panning by hand takes over from a glide
+            self.glide = None;
+        }
+        // Two fingers pan and pinch to zoom; a single finger taps or paints,
+        // once it is clear it isn't the first of two
+        let fingers: Vec<_> = touches()
+            .into_iter()
+            .filter(|touch| !matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled))
+            .map(|touch| (touch.id, (touch.position.x, touch.position.y)))
+            .collect();
+        let touch = self.touch.update(&fingers, get_frame_time());
+        if let TouchGesture::Pinch { pan, zoom, around } = touch {
+            self.camera.pan_by(pan);
+            self.camera.zoom_at(around, zoom);
+            self.glide = None;
+        }
+        if self.touch.is_panning() {
+            // The mouse faked from the fingers mustn't paint once they let go
+            self.left_button.update(false, pos, get_frame_time());
+            return;
+        }
+
+        if is_mouse_button_down(MouseButton::Middle) {
+            if let Some(last) = self.pan_from {
+                self.camera.pan_by((pos.0 - last.0, pos.1 - last.1));
+                self.glide = None;
+            }
+            self.pan_from = Some(pos);
+        } else {
+            self.pan_from = None;
+        }
+
+        // Releasing a button always closes the undo step of its drag, even when
+        // the drag ended somewhere that doesn't paint; a selection being moved
+        // keeps its own step open until dropped
+        let moving = self.paste.as_ref().is_some_and(PendingPaste::is_move);
+        if !moving
+            && (is_mouse_button_released(MouseButton::Left)
+                || is_mouse_button_released(MouseButton::Right))
+        {
+            self.edits.push(Command::End);
+        }
+
+        // Clicking the buttons triggers their commands, dragging on the timeline
+        // bar above them scrubs through history, dragging the slider beside them
+        // sets the speed, and the strip holding all three never passes clicks
+        // through to the board
+        let screen = (screen_width(), screen_height());
+        let strip = buttons::strip_bounds(screen);
+        let bar = scrubber::bar_bounds(screen);
+        if is_mouse_button_pressed(MouseButton::Left) && strip.contains(pos) {
+            self.strip_press = true;
+            self.scrubbing = bar.contains(pos) && self.timeline.len() > 1;
+            self.sliding = slider::hit(pos, screen);
+            let labels = buttons::buttons(self.state == State::Paused);
+            let rects = buttons::layout(screen, labels.len());
+            if let Some(i) = buttons::button_at(pos, &rects) {
+                self.apply(labels[i].1);
+            }
+        }
+        if self.strip_press {
+            self.strip_press = is_mouse_button_down(MouseButton::Left);
+            if self.scrubbing && self.strip_press {
+                self.scrub_to(scrubber::index_at(pos.0, bar, self.timeline.len()));
+            }
+            if self.sliding && self.strip_press {
+                self.set_speed(slider::speed_at(pos.0, screen));
+            }
+            return;
+        }
+        if strip.contains(pos) {
+            // Let go of drags that end on the strip, so they don't turn into clicks later
+            let dt = get_frame_time();
+            self.left_button.update(false, pos, dt);
+            self.right_button.update(false, pos, dt);
+            return;
+        }
+
+        // Clicking the minimap moves the view there instead of editing the board
+        if self.show_minimap {
+            let screen = (screen_width(), screen_height());
+            let dims = (self.grid.width(), self.grid.height());
+            let bounds = minimap::minimap_bounds(screen, dims);
+            if is_mouse_button_pressed(MouseButton::Left) && bounds.contains(pos) {
+                self.minimap_drag = true;
+            }
+            if self.minimap_drag {
+                if is_mouse_button_down(MouseButton::Left) {
+                    let clamped = (
+                        pos.0.clamp(bounds.x, bounds.x + bounds.w - 1.0),
+                        pos.1.clamp(bounds.y, bounds.y + bounds.h - 1.0),
+                    );
+                    if let Some(cell) = minimap::cell_at(clamped, dims, bounds) {
+                        self.camera.center_on(cell, screen);
+                    }
+                    return;
+                }
+                self.minimap_drag = false;
+                return;
+            }
+        }
+
+        // Ctrl + dragging the selection moves it, grabbed by the cell pressed
+        if ctrl {
+            if let (Some(selection), Some((x, y)), true) = (
+                &self.selection,
+                self.cell_at(pos),
+                is_mouse_button_pressed(MouseButton::Left),
+            ) {
+                let region = selection.region();
+                if (region.x..region.x + region.width).contains(&x)
+                    && (region.y..region.y + region.height).contains(&y)
+                {
+                    self.log(Event::Lift {
+                        x: x - region.x,
+                        y: y - region.y,
+                    });
+                    self.lift_selection(Some((x - region.x, y - region.y)));
+                    self.drag_move = self.paste.is_some();
+                    return;
+                }
+            }
+        }
+
+        // While pasting, clicks stamp the pending pattern instead of painting,
+        // and a dragged selection lands where the button is released
+        if self.drag_move {
+            if !is_mouse_button_down(MouseButton::Left) {
+                let mode = if alt {
+                    conways::InsertMode::Overwrite
+                } else {
+                    conways::InsertMode::Or
+                };
+                self.stamp_paste(mode);
+            }
+            return;
+        }
+        if self.stamp_brush && self.paste.as_ref().is_some_and(|paste| !paste.is_move()) {
+            // The whole drag undoes in one step, like painting
+            if is_mouse_button_pressed(MouseButton::Left) {
+                self.edits.push(Command::Begin);
+                self.brush_last = None;
+            }
+            if is_mouse_button_down(MouseButton::Left) {
+                let mode = if alt {
+                    conways::InsertMode::Overwrite
+                } else {
+                    conways::InsertMode::Or
+                };
+                self.brush_at(self.cursor_cell(), mode);
+            }
+            return;
+        }
+        if self.paste.is_some() {
+            if is_mouse_button_pressed(MouseButton::Left) {
+                let mode = if alt {
+                    conways::InsertMode::Overwrite
+                } else {
+                    conways::InsertMode::Or
+                };
+                self.state = State::Paused;
+                self.stamp_paste(mode);
+            }
+            return;
+        }
+
+        // Painting works while running too, see `CommandQueue` for when edits land
+        let dt = get_frame_time();
+
+        // Measuring takes clicks over from every tool, leaving the board alone
+        if self.measure.is_active() {
+            if let Gesture::Click(at) =
+                self.left_button
+                    .update(is_mouse_button_down(MouseButton::Left), pos, dt)
+            {
+                if let Some(cell) = self.cell_at(at) {
+                    self.measure.click(cell);
+                }
+            }
+            return;
+        }
+
+        // During a match or a puzzle clicks claim cells within the budget, and
+        // nothing else edits the board
+        if self.immigration.is_some() || self.puzzle.is_some() {
+            if let Gesture::Click(at) =
+                self.left_button
+                    .update(is_mouse_button_down(MouseButton::Left), pos, dt)
+            {
+                if let Some(cell) = self.cell_at(at) {
+                    self.claim(cell);
+                }
+            }
+            return;
+        }
+
+        // Shift + drag draws a selection marquee instead of painting
+        if self.selection.as_ref().is_some_and(Selection::is_dragging) {
+            let cell = self.clamped_cell_at(pos);
+            if let Some(selection) = self.selection.as_mut() {
+                if is_mouse_button_down(MouseButton::Left) {
+                    selection.extend_to(cell);
+                } else {
+                    let region = selection.region();
+                    self.select(region);
+                }
+            }
+            return;
+        }
+        // The tile tool drags out a region like a selection, tiling it on release
+        if self.tiling.is_some() {
+            let cell = self.clamped_cell_at(pos);
+            if let Some(tiling) = self.tiling.as_mut() {
+                tiling.extend_to(cell);
+            }
+            if !is_mouse_button_down(MouseButton::Left) {
+                if let Some(region) = self.tiling.take().map(|tiling| tiling.region()) {
+                    self.tile_region(region);
+                }
+            }
+            return;
+        }
+        if self.tool == Tool::Tile && !shift && is_mouse_button_pressed(MouseButton::Left) {
+            if let Some(cell) = self.cell_at(pos) {
+                self.tiling = Some(Selection::start(cell));
+            }
+            return;
+        }
+        if self.tool == Tool::FloodFill && is_mouse_button_pressed(MouseButton::Left) {
+            if let Some(cell) = self.cell_at(pos) {
+                self.flood_fill(cell);
+            }
+            return;
+        }
+        if (shift || self.tool == Tool::Select) && is_mouse_button_pressed(MouseButton::Left) {
+            if let Some(cell) = self.cell_at(pos) {
+                self.selection = Some(Selection::start(cell));
+                return;
+            }
+        }
+
+        // Everything painted until the button is released undoes in one step
+        if is_mouse_button_pressed(MouseButton::Left) || is_mouse_button_pressed(MouseButton::Right)
+        {
+            self.edits.push(Command::Begin);
+            self.drag_state = None;
+        }
+
+        // Fingers paint through their recognizer instead of the mouse faked from
+        // them, which can't wait to tell a tap from the first of two fingers
+        let gesture = match touch {
+            TouchGesture::Tap(at) => Gesture::Click(at),
+            TouchGesture::Drag { from, to } => Gesture::Drag { from, to },
+            _ if self.touch.is_touching() => Gesture::None,
+            _ => self
+                .left_button
+                .update(is_mouse_button_down(MouseButton::Left), pos, dt),
+        };
+        self.use_tool(gesture);
+
+        // The right button erases, or paints alive while the erase tool is active
+        let state = if self.tool == Tool::Erase {
+            conways::CellState::Alive
+        } else {
+            conways::CellState::Dead
+        };
+        match self
+            .right_button
+            .update(is_mouse_button_down(MouseButton::Right), pos, dt)
+        {
+            Gesture::Click(at) => self.paint(at, at, state),
+            Gesture::Drag { from, to } => self.paint(from, to, state),
+            Gesture::None => (),
+        }
+    }
+
+    /// Act on a left button gesture with the active painting tool
+    /// Filling, selecting and pasting react to the press itself, in `handle_input`.
+    fn use_tool(&mut self, gesture: Gesture) {
+        match (self.tool, gesture) {
+            (Tool::Paint, Gesture::Click(at)) => self.paint(at, at, conways::CellState::Alive),
+            (Tool::Paint, Gesture::Drag { from, to }) => {
+                self.paint(from, to, conways::CellState::Alive)
+            }
+            (Tool::Erase, Gesture::Click(at)) => self.paint(at, at, conways::CellState::Dead),
+            (Tool::Erase, Gesture::Drag { from, to }) => {
+                self.paint(from, to, conways::CellState::Dead)
+            }
+            (Tool::Toggle, Gesture::Click(at)) => {
+                if let Some((x, y)) = self.cell_at(at) {
+                    for (x, y) in self.mirrored(x, y) {
+                        self.edits.push(Command::Toggle { x, y });
+                    }
+                }
+            }
+            (Tool::Toggle, Gesture::Drag { from, to }) => {
+                // The first drag frame starts where the press did, before anything was painted
+                if self.drag_state.is_none() {
+                    self.drag_state = self.cell_at(from).map(|(x, y)| match self.grid.get(x, y) {
+                        conways::CellState::Alive => conways::CellState::Dead,
+                        conways::CellState::Dead => conways::CellState::Alive,
+                    });
+                }
+                if let Some(state) = self.drag_state.clone() {
+                    self.paint(from, to, state);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Carry out a keyboard cursor action
+    /// Toggling goes through the edit queue like a click would, so it is
+    /// mirrored and undone in one step; selecting works like a mouse selection.
+    fn use_cursor(&mut self, action: CursorAction, count: Option<usize>) {
+        let dims = (self.grid.width(), self.grid.height());
+        let Some(cursor) = &mut self.cursor else {
+            return;
+        };
+        match cursor.apply_counted(action, count, dims) {
+            CursorEffect::None => (),
+            CursorEffect::Toggle(cell) if self.immigration.is_some() || self.puzzle.is_some() => {
+                self.claim(cell)
+            }
+            CursorEffect::Toggle((x, y)) => {
+                self.edits.push(Command::Begin);
+                for (x, y) in self.mirrored(x, y) {
+                    self.edits.push(Command::Toggle { x, y });
+                }
+                self.edits.push(Command::End);
+            }
+            CursorEffect::Select(region) => self.select(region),
+        }
+    }
+
+    /// Flip the connected region around a cell, and its mirrored counterparts,
+    /// as a single undo step
+    fn flood_fill(&mut self, (x, y): (usize, usize)) {
+        self.log(Event::Fill { x, y });
+        let state = match self.grid.get(x, y) {
+            conways::CellState::Alive => conways::CellState::Dead,
+            conways::CellState::Dead => conways::CellState::Alive,
+        };
+        let before = self.grid.clone();
+        for (x, y) in self.mirrored(x, y) {
+            if self.grid.flood_fill(x, y, state.clone()).is_none() {
+                self.grid = before;
+                self.notice.show(format!(
+                    "Region too large to fill (over {} cells)",
+                    conways::FLOOD_FILL_LIMIT
+                ));
+                return;
+            }
+        }
+        if self.grid != before {
+            self.dirty = true;
+            self.history.record(&before);
+            self.timeline.truncate();
+            self.detector.reset();
+        }
+    }
+
+    /// Make the left button use `tool`, leaving paste mode if it was active
+    fn set_tool(&mut self, tool: Tool) {
+        self.stop_pasting();
+        self.tool = tool;
+    }
+
+    /// Follow the cursor with `pattern`, for clicks to stamp it
+    fn start_paste(&mut self, pattern: conways::Grid) {
+        self.stop_pasting();
+        self.paste = Some(PendingPaste::new(pattern));
+        self.tool = Tool::Paste;
+        self.state = State::Paused;
+    }
+
+    /// Drop the pending paste, going back to the default tool if pasting was
+    /// active; a selection being moved goes back where it was
+    fn stop_pasting(&mut self) {
+        if let Some(paste) = self.paste.take() {
+            paste.put_back(&mut self.grid, &mut self.history);
+        }
+        self.drag_move = false;
+        if self.tool == Tool::Paste {
+            self.tool = Tool::default();
+        }
+    }
+
+    /// Pick the selection up off the board, to stamp it elsewhere
+    ///
+    /// # Arguments
+    /// * `grab` - The cell held by the cursor, relative to the selection;
+    ///   the selection's center when `None`
+    fn lift_selection(&mut self, grab: Option<(usize, usize)>) {
+        if self.paste.is_some() || self.immigration.is_some() || self.puzzle.is_some() {
+            return;
+        }
+        let Some(region) = self.selection.take().map(|selection| selection.region()) else {
+            return;
+        };
+        self.timeline.truncate();
+        self.detector.reset();
+        self.paste = Some(PendingPaste::lift(
+            &mut self.grid,
+            &mut self.history,
+            region,
+            grab,
+        ));
+        self.tool = Tool::Paste;
+        self.state = State::Paused;
+    }
+
+    /// Carry out a keyboard command
+    fn apply(&mut self, command: InputCommand) {
+        self.log(Event::Input(command));
+        if !matches!(command, InputCommand::Shrink(_)) {
+            self.pending_resize = None;
+        }
+        if !matches!(
+            command,
+            InputCommand::SaveSlot(_) | InputCommand::LoadSlot(_)
+        ) {
+            self.pending_slot = None;
+        }
+        // A selection being moved goes back in place before anything else
+        // touches the board or its history
+        if self.paste.as_ref().is_some_and(PendingPaste::is_move) && changes_board(command) {
+            self.stop_pasting();
+        }
+        match command {
+            InputCommand::TogglePause => {
+                if self.puzzle_pause() {
+                    return;
+                }
+                if let Some(focus) = &mut self.focus_pause {
+                    focus.forget();
+                }
+                if let Some(Collab::Join(client)) = &mut self.collab {
+                    // Paused once the host says so
+                    let _ = client.send(&Edit::Pause(self.state == State::Running));
+                    return;
+                }
+                self.state = match self.state {
+                    State::Running => State::Paused,
+                    State::Paused => State::Running,
+                };
+            }
+            InputCommand::ToggleAutoPause => {
+                self.auto_pause = !self.auto_pause;
+                self.notice.show(if self.auto_pause {
+                    "Auto-pause on"
+                } else {
+                    "Auto-pause off"
+                });
+            }
+            InputCommand::StepBack => {
+                self.state = State::Paused;
+                self.detector.reset();
+                if !self.timeline.back(&mut self.grid) {
+                    self.notice.show("No earlier generations kept");
+                }
+            }
+            InputCommand::StepForward => {
+                self.state = State::Paused;
+                let before = self.grid.clone();
+                if self.timeline.forward(&mut self.grid) {
+                    self.onion = Some(before);
+                } else {
+                    self.step_once();
+                }
+            }
+            InputCommand::SlowDown => {
+                self.update_interval = (self.update_interval * 2.0).min(MAX_UPDATE_INTERVAL);
+            }
+            InputCommand::SpeedUp => {
+                self.update_interval = (self.update_interval / 2.0).max(MIN_UPDATE_INTERVAL);
+            }
+            InputCommand::SpeedPreset(n) => {
+                if let Some(speed) = self.speed_presets.press(n, 1.0 / self.update_interval) {
+                    self.update_interval =
+                        (1.0 / speed).clamp(MIN_UPDATE_INTERVAL, MAX_UPDATE_INTERVAL);
+                }
+            }
+            InputCommand::CycleTheme => {
+                let presets = Theme::presets();
+                self.theme_index = (self.theme_index + 1) % presets.len();
+                self.set_theme(presets[self.theme_index].clone());
+                self.notice.show(format!("Theme: {}", self.theme.name));
+            }
+            InputCommand::CycleCellShape => {
+                self.cell_shape = self.cell_shape.next();
+                self.notice
+                    .show(format!("Cell shape: {}", self.cell_shape.label()));
+                self.remember_setting("cell_shape", &format!("\"{}\"", self.cell_shape.label()));
+            }
+            InputCommand::CycleRule => {
+                // Rules off the list, e.g. typed ones, move on to the first one
+                let next = self
+                    .rules
+                    .iter()
+                    .position(|&rule| rule == self.rule)
+                    .map_or(0, |i| (i + 1) % self.rules.len());
+                if let Some(&rule) = self.rules.get(next) {
+                    self.set_rule(rule);
+                }
+            }
+            InputCommand::EnterRule => self.rule_entry = Some(TextEntry::new("Rule")),
+            InputCommand::GoToGeneration => {
+                self.goto_entry = Some(TextEntry::new("Go to generation"))
+            }
+            InputCommand::ToggleGridLines => self.show_grid_lines = !self.show_grid_lines,
+            InputCommand::ToggleRulers => self.show_rulers = !self.show_rulers,
+            InputCommand::CycleBackground => {
+                self.background_style = self.background_style.next();
+                self.notice
+                    .show(format!("Background: {}", self.background_style.label()));
+            }
+            InputCommand::ToggleTrails => self.show_trails = !self.show_trails,
+            InputCommand::ToggleOnionSkin => self.show_onion_skin = !self.show_onion_skin,
+            InputCommand::ToggleCellOrigins => {
+                self.show_origins = !self.show_origins;
+                self.notice.show(if self.show_origins {
+                    "Painted cells tinted"
+                } else {
+                    "Cell origins hidden"
+                });
+            }
+            InputCommand::TogglePreview => {
+                self.preview = match self.preview {
+                    Some(_) => None,
+                    None => Some(Preview::new()),
+                };
+            }
+            InputCommand::ToggleNeighborColors => {
+                self.show_neighbor_colors = !self.show_neighbor_colors
+            }
+            InputCommand::CycleSymmetry => self.symmetry = self.symmetry.next(),
+            InputCommand::ToggleStampBrush => {
+                self.stamp_brush = !self.stamp_brush;
+                self.notice.show(if self.stamp_brush {
+                    "Stamp brush on: pick a pattern (1-9), then click or drag"
+                } else {
+                    "Stamp brush off"
+                });
+            }
+            InputCommand::SelectTool(tool) => self.set_tool(tool),
+            InputCommand::CycleBoundary => {
+                self.boundary = self.boundary.next();
+                self.notice
+                    .show(format!("Edges: {}", self.boundary.label()));
+            }
+            InputCommand::ToggleSeamlessWrap => {
+                self.seamless_wrap = !self.seamless_wrap;
+                self.notice.show(if self.seamless_wrap {
+                    "Wrapping boards tile endlessly"
+                } else {
+                    "Wrapping boards show their edges"
+                });
+            }
+            InputCommand::Grow(edge) => self.resize(command, edge, RESIZE_STEP),
+            InputCommand::Shrink(edge) => self.resize(command, edge, -RESIZE_STEP),
+            InputCommand::ToggleMinimap => {
+                self.show_minimap = !self.show_minimap;
+                // Show up to date contents right away instead of waiting for the next refresh
+                self.since_minimap = minimap::MINIMAP_REFRESH;
+            }
+            InputCommand::ToggleHelp => self.show_help = !self.show_help,
+            InputCommand::ToggleGraph => self.show_graph = !self.show_graph,
+            InputCommand::ToggleInspector => self.show_inspector = !self.show_inspector,
+            InputCommand::FitView => self.fit_requested = true,
+            InputCommand::Measure => {
+                if self.measure.toggle() == measure::Stage::First {
+                    self.notice
+                        .show("Measure: click two cells, Escape clears the measurements");
+                }
+            }
+            InputCommand::ToggleCursor => {
+                self.cursor = match self.cursor {
+                    Some(_) => None,
+                    None => {
+                        let (width, height) = (self.grid.width(), self.grid.height());
+                        self.notice
+                            .show("Keyboard cursor: WASD or HJKL while paused");
+                        Some(Cursor::new((width / 2, height / 2)))
+                    }
+                };
+            }
+            // Turbo runs as many generations as fit in a frame, which differs every time
+            InputCommand::ToggleTurbo if self.input_log.is_some() || self.playback.is_some() => {
+                self.notice
+                    .show("Turbo is off while recording or replaying");
+            }
+            InputCommand::ToggleTurbo => {
+                self.turbo = match self.turbo {
+                    Some(_) => None,
+                    None => Some(Turbo::new()),
+                };
+                self.accumulator.reset();
+            }
+            InputCommand::Save => self.save(),
+            InputCommand::LoadNextSave => self.load_next_save(),
+            InputCommand::Quit => self.request_quit(),
+            InputCommand::Randomize => {
+                self.before_edit();
+                let seed = self.next_seed();
+                self.grid.randomize(self.density, seed);
+            }
+            InputCommand::Clear => self.clear(),
+            InputCommand::FillNoise => self.fill_noise(),
+            #[cfg(feature = "scripting")]
+            InputCommand::RerunScript => self.rerun_script(),
+            #[cfg(feature = "gif")]
+            InputCommand::ToggleRecording => {
+                if self.recorder.is_some() {
+                    self.stop_recording();
+                } else {
+                    self.start_recording();
+                }
+            }
+            #[cfg(feature = "audio")]
+            InputCommand::CycleSound => {
+                let mode = self.cues.cycle();
+                if self.sounds.is_none() && mode != sound::SoundMode::Off {
+                    self.notice.show("Sound isn't available in the terminal");
+                } else {
+                    self.notice.show(format!("Sound: {}", mode.label()));
+                }
+            }
+            InputCommand::Screenshot => self.screenshot(),
+            InputCommand::ExportSvg => self.export_svg(),
+            InputCommand::Undo => {
+                if self.history.undo(&mut self.grid) {
+                    self.dirty = true;
+                    self.timeline.clear();
+                    self.detector.reset();
+                    // Otherwise an undone generation would be stepped again right away
+                    self.state = State::Paused;
+                } else {
+                    self.notice.show("Nothing to undo");
+                }
+            }
+            InputCommand::Redo => {
+                if self.history.redo(&mut self.grid) {
+                    self.dirty = true;
+                    self.timeline.clear();
+                    self.detector.reset();
+                    self.state = State::Paused;
+                } else {
+                    self.notice.show("Nothing to redo");
+                }
+            }
+            InputCommand::Copy => self.copy_selection(),
+            InputCommand::Cut => {
+                self.copy_selection();
+                self.clear_selection();
+            }
+            InputCommand::ClearSelection => self.clear_selection(),
+            InputCommand::Paste => {
+                if let Some(clipboard) = &self.clipboard {
+                    self.start_paste(clipboard.pattern().clone());
+                }
+            }
+            InputCommand::CopyRle => self.copy_rle(),
+            InputCommand::CopyShareString => self.copy_share_string(),
+            // A replay can't read the clipboard it was recorded with, the
+            // pattern pasted follows in its own event
+            InputCommand::PasteRle if self.playback.is_some() => (),
+            InputCommand::PasteRle => match miniquad::window::clipboard_get() {
+                Some(text) => self.paste_rle(&text),
+                None => self
+                    .notice
+                    .show("Nothing to paste, the system clipboard can't be read"),
+            },
+            InputCommand::CycleReference => match &mut self.reference {
+                Some(layer) => {
+                    let placement = layer.cycle_placement();
+                    self.notice
+                        .show(format!("Reference layer {}", placement.label()));
+                }
+                None => self.notice.show(NO_REFERENCE),
+            },
+            InputCommand::MoveReference if self.reference.is_none() => {
+                self.notice.show(NO_REFERENCE)
+            }
+            InputCommand::MoveReference => self.moving_reference = !self.moving_reference,
+            // A replay is played back without the reference layer, the
+            // pattern stamped follows in its own event
+            InputCommand::CommitReference if self.playback.is_some() => (),
+            InputCommand::CommitReference => self.commit_reference(),
+            InputCommand::NextPalettePage => self.palette.next_page(),
+            InputCommand::SelectPattern(n) => {
+                if let Some(pattern) = self.palette.select(n) {
+                    self.start_paste(pattern.to_grid());
+                }
+            }
+            InputCommand::SaveBookmark(slot) => {
+                let replaced = self.bookmarks.save(slot, &self.grid).is_some();
+                self.notice.show(format!(
+                    "Bookmark {slot} {} at generation {}",
+                    if replaced { "replaced" } else { "saved" },
+                    self.grid.generation()
+                ));
+            }
+            InputCommand::RestoreBookmark(slot) => match self.bookmarks.restore(slot) {
+                Some(grid) => {
+                    self.history.record(&self.grid);
+                    self.board_replaced();
+                    self.grid = grid;
+                    self.dirty = true;
+                    self.state = State::Paused;
+                    self.notice.show(format!(
+                        "Bookmark {slot} restored, generation {}",
+                        self.grid.generation()
+                    ));
+                }
+                None => self.notice.show(format!("Bookmark {slot} is empty")),
+            },
+            InputCommand::SaveSlot(n) => self.save_slot(n),
+            InputCommand::LoadSlot(n) => self.load_slot(n),
+            InputCommand::RotatePaste => {
+                if let Some(paste) = self.paste.as_mut() {
+                    paste.rotate_cw();
+                }
+            }
+            InputCommand::FlipPasteHorizontal => {
+                if let Some(paste) = self.paste.as_mut() {
+                    paste.flip_horizontal();
+                }
+            }
+            InputCommand::FlipPasteVertical => match self.paste.as_mut() {
+                Some(paste) => paste.flip_vertical(),
+                None => self.lift_selection(None),
+            },
+            InputCommand::RestoreSession => {
+                if let Some(session) = self.session_offer.take() {
+                    self.restore(session);
+                } else if self.reload_offer {
+                    self.reload_pattern();
+                }
+            }
+            InputCommand::PlayMatch => self.play_match(),
+            InputCommand::Cancel => {
+                if self.fast_forward.take().is_some() {
+                    self.notice
+                        .show(format!("Stopped at generation {}", self.grid.generation()));
+                }
+                self.session_offer = None;
+                self.reload_offer = false;
+                self.show_help = false;
+                self.selection = None;
+                self.measure.cancel();
+                self.moving_reference = false;
+                self.stop_pasting();
+            }
+        }
+    }
+
+    /// Move the match on: start one on a cleared board of the same size, end
+    /// the current setup turn, or close the results
+    fn play_match(&mut self) {
+        let Some(game) = &mut self.immigration else {
+            self.puzzle = None;
+            self.before_edit();
+            self.board_replaced();
+            let (width, height) = (self.grid.width(), self.grid.height());
+            self.grid = conways::Grid::new(width, height);
+            self.immigration = Some(Match::new(
+                width,
+                height,
+                immigration::DEFAULT_BUDGET,
+                immigration::MATCH_GENERATIONS,
+            ));
+            self.state = State::Paused;
+            self.turbo = None;
+            self.notice
+                .show("Immigration match: players place their cells in turn");
+            return;
+        };
+        match game.phase() {
+            Phase::Setup(_) => {
+                if game.end_turn(&self.grid) == Phase::Running {
+                    self.detector.reset();
+                    self.state = State::Running;
+                }
+            }
+            Phase::Running => self.notice.show("The match is still running"),
+            Phase::Results => self.immigration = None,
+        }
+    }
+
+    /// Start working on `puzzle`, paused on its starting board
+    fn start_puzzle(&mut self, puzzle: Puzzle) {
+        self.history.record(&self.grid);
+        self.board_replaced();
+        self.grid = puzzle.board();
+        self.rule = puzzle.rule;
+        self.immigration = None;
+        self.state = State::Paused;
+        self.notice.show(if puzzle.description.is_empty() {
+            puzzle.name.clone()
+        } else {
+            format!("{}: {}", puzzle.name, puzzle.description)
+        });
+        self.puzzle = Some(Attempt::new(puzzle));
+    }
+
+    /// Run the edited puzzle board, or go back to editing it once a run is over
+    /// # Returns
+    /// Whether the puzzle took the pause key, which otherwise pauses as usual
+    fn puzzle_pause(&mut self) -> bool {
+        let Some(attempt) = &mut self.puzzle else {
+            return false;
+        };
+        match attempt.stage() {
+            Stage::Setup => {
+                if attempt.start(&self.grid) {
+                    self.detector.reset();
+                    self.state = State::Running;
+                } else {
+                    self.notice.show("More edits than the puzzle allows");
+                }
+                true
+            }
+            Stage::Running => false,
+            Stage::Over(_) => {
+                self.grid = attempt.retry();
+                self.board_replaced();
+                self.state = State::Paused;
+                true
+            }
+        }
+    }
+
+    /// Place a cell for the player in setup, or take one of theirs back; or
+    /// flip a cell of the puzzle, within its budget
+    fn claim(&mut self, (x, y): (usize, usize)) {
+        if self.immigration.is_none() && self.puzzle.is_none() {
+            return;
+        }
+        self.log(Event::Claim { x, y });
+        let changed = match (&mut self.immigration, &self.puzzle) {
+            (Some(game), _) => game.claim(&mut self.grid, x, y),
+            (None, Some(attempt)) => attempt.toggle(&mut self.grid, x, y),
+            (None, None) => false,
+        };
+        if changed {
+            self.timeline.truncate();
+            self.detector.reset();
+        }
+    }
+
+    /// Whether a match or puzzle holds the simulation back: during setup, and
+    /// once it is over
+    fn mode_waits(&self) -> bool {
+        let match_waits = self
+            .immigration
+            .as_ref()
+            .is_some_and(|game| game.phase() != Phase::Running);
+        let puzzle_waits = self
+            .puzzle
+            .as_ref()
+            .is_some_and(|attempt| attempt.stage() != Stage::Running);
+        // The host steps the board of a joined game
+        let joined = matches!(self.collab, Some(Collab::Join(_)));
+        match_waits || puzzle_waits || joined
+    }
+
+    /// Whether a match or a puzzle run is underway
+    fn mode_runs(&self) -> bool {
+        (self.immigration.is_some() || self.puzzle.is_some()) && !self.mode_waits()
+    }
+
+    /// What the match or the puzzle is waiting for, shown by both frontends
+    fn mode_status(&self) -> Option<String> {
+        if let Some(run) = &self.fast_forward {
+            let done = self.grid.generation() - run.from;
+            let share = done as f64 / (run.target - run.from).max(1) as f64;
+            return Some(format!(
+                "GOING TO GENERATION {}: {:.0}%, Esc stops",
+                run.target,
+                share * 100.0
+            ));
+        }
+        if let Some(attempt) = &self.puzzle {
+            let puzzle = attempt.puzzle();
+            return Some(match attempt.stage() {
+                Stage::Setup => format!(
+                    "PUZZLE {}: {} within {} generations, {} edits left, Space runs",
+                    puzzle.name,
+                    puzzle.goal,
+                    puzzle.generations,
+                    attempt.edits_left(&self.grid)
+                ),
+                Stage::Running => format!("PUZZLE {}: {}", puzzle.name, puzzle.goal),
+                Stage::Over(Outcome::Solved(_)) => format!("PUZZLE {}: SOLVED", puzzle.name),
+                Stage::Over(Outcome::Failed) => format!("PUZZLE {}: FAILED", puzzle.name),
+            });
+        }
+        let game = self.immigration.as_ref()?;
+        Some(match game.phase() {
+            Phase::Setup(player) => format!(
+                "MATCH: {} places cells, {} left, J ends the turn",
+                player.label(),
+                game.budget(player)
+            ),
+            Phase::Running => {
+                let [one, two] = game.scores(&self.grid);
+                format!(
+                    "MATCH: {one} - {two}, {} generations left",
+                    game.remaining(&self.grid)
+                )
+            }
+            Phase::Results => "MATCH OVER: J closes".to_string(),
+        })
+    }
+
+    /// Set the cells under both ends of a drag segment, and their mirrored
+    /// counterparts, to `state`
+    fn paint(&mut self, from: Point, to: Point, state: conways::CellState) {
+        for pos in [from, to] {
+            if let Some((x, y)) = self.cell_at(pos) {
+                for (x, y) in self.mirrored(x, y) {
+                    self.edits.push(Command::Set {
+                        x,
+                        y,
+                        state: state.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Every cell affected by an edit at `(x, y)` under the active symmetry mode
+    fn mirrored(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        symmetry::mirror_coords(x, y, self.symmetry, self.grid.width(), self.grid.height())
+    }
+
+    /// Save the grid to a new timestamped file, reporting the outcome on screen
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save(&mut self) {
+        let path = saves::timestamped_path();
+        match saves::save_grid(&self.grid, &path) {
+            Ok(()) => {
+                self.notice.show(format!("Saved to {}", path.display()));
+                self.load_index = 0;
+                self.dirty = false;
+            }
+            Err(err) => {
+                error!("failed to save {}: {err}", path.display());
+                self.notice.show(format!("Save failed: {err}"));
+            }
+        }
+    }
+
+    /// Keep a copy of the grid in memory for the rest of the visit, since the
+    /// browser has no file system to save to
+    #[cfg(target_arch = "wasm32")]
+    fn save(&mut self) {
+        self.memory_saves.push(self.grid.clone());
+        self.notice
+            .show(format!("Saved in memory (#{})", self.memory_saves.len()));
+        self.load_index = 0;
+        self.dirty = false;
+    }
+
+    /// Switch to a theme, keeping the user's color overrides
+    fn set_theme(&mut self, mut theme: Theme) {
+        for (name, color) in &self.color_overrides {
+            if let Some(slot) = theme.color_mut(name) {
+                *slot = *color;
+            }
+        }
+        self.theme = theme;
+    }
+
+    /// Refresh the fading trail left behind by dead cells after a step
+    fn update_trail(&mut self) {
+        let len = self.grid.width() * self.grid.height();
+        if self.trail.len() != len {
+            self.trail = vec![0; len];
+        }
+        for (i, age) in self.trail.iter_mut().enumerate() {
+            let (x, y) = (i % self.grid.width(), i / self.grid.width());
+            *age = match self.grid.get(x, y) {
+                conways::CellState::Alive => TRAIL_LENGTH,
+                conways::CellState::Dead => age.saturating_sub(1),
+            };
+        }
+    }
+
+    /// Write the current session to the platform data directory
+    /// Skipped while a previous session is still being offered, so it isn't
+    /// overwritten before the user had a chance to restore it.
+    fn autosave(&mut self) {
+        self.since_autosave = 0.0;
+        if self.session_offer.is_some() {
+            return;
+        }
+        let Some(path) = session::session_path() else {
+            return;
+        };
+
+        if let Err(err) = self.session().save(&path) {
+            warn!("failed to autosave to {}: {err}", path.display());
+        }
+    }
+
+    /// Everything `restore` needs to come back to this moment
+    fn session(&self) -> Session {
+        Session {
+            grid: self.grid.clone(),
+            update_interval: self.update_interval,
+            rule: self.rule.to_string(),
+            bookmarks: self.bookmarks.clone(),
+            camera: Some(self.camera),
+            saved: Some(saves::timestamp_now()),
+        }
+    }
+
+    /// Go through with a slot `command` unless `warning` has to be shown
+    /// first; the same command right after confirms it
+    fn confirm_slot(&mut self, command: InputCommand, warning: Option<String>) -> bool {
+        match warning {
+            Some(warning) if self.pending_slot != Some(command) => {
+                self.pending_slot = Some(command);
+                self.notice.show(warning);
+                false
+            }
+            _ => {
+                self.pending_slot = None;
+                true
+            }
+        }
+    }
+
+    /// Quick-save the session into slot `n`, asking first when it is filled
+    fn save_slot(&mut self, n: usize) {
+        let Some(path) = self.slots.path(n) else {
+            self.notice.show(NO_SLOTS);
+            return;
+        };
+        let warning = slots::warning(SlotAction::Save(n), self.slots.info(n), self.dirty);
+        if !self.confirm_slot(InputCommand::SaveSlot(n), warning) {
+            return;
+        }
+        let session = self.session();
+        match session.save(&path) {
+            Ok(()) => {
+                let info = SlotInfo::of(&session);
+                self.notice
+                    .show(format!("Saved slot {n}: {}", info.label()));
+                self.slots.saved(n, info);
+                self.dirty = false;
+            }
+            Err(err) => {
+                error!("failed to save {}: {err}", path.display());
+                self.notice.show(format!("Saving slot {n} failed: {err}"));
+            }
+        }
+    }
+
+    /// Quick-load the session in slot `n`, asking first when that would lose edits
+    fn load_slot(&mut self, n: usize) {
+        let Some(path) = self.slots.path(n) else {
+            self.notice.show(NO_SLOTS);
+            return;
+        };
+        let Some(info) = self.slots.info(n).cloned() else {
+            self.notice.show(format!("Slot {n} is empty"));
+            return;
+        };
+        let warning = slots::warning(SlotAction::Load(n), Some(&info), self.dirty);
+        if !self.confirm_slot(InputCommand::LoadSlot(n), warning) {
+            return;
+        }
+        match Session::load(&path) {
+            Ok(session) => {
+                let info = SlotInfo::of(&session);
+                self.restore(session);
+                self.slots.saved(n, info.clone());
+                self.dirty = false;
+                self.notice
+                    .show(format!("Loaded slot {n}: {}", info.label()));
+            }
+            Err(err) => {
+                error!("failed to load {}: {err}", path.display());
+                self.notice.show(format!("Loading slot {n} failed: {err}"));
+            }
+        }
+    }
+
+    /// Pick up a previous session, pausing so nothing changes before the user looks
+    fn restore(&mut self, session: Session) {
+        match session.rule.parse() {
+            Ok(rule) => self.rule = rule,
+            Err(err) => warn!("ignoring the session's rule {}: {err}", session.rule),
+        }
+        self.history.record(&self.grid);
+        self.board_replaced();
+        self.grid = session.grid;
+        self.bookmarks = session.bookmarks;
+        self.update_interval = session.update_interval;
+        if let Some(camera) = session.camera {
+            self.camera = camera;
+        }
+        self.state = State::Paused;
+        self.notice.show("Session restored");
+    }
+
+    /// Export the board, without any overlay, as a PNG in the screenshots directory
+    fn screenshot(&mut self) {
+        if cfg!(target_arch = "wasm32") {
+            self.notice
+                .show("Screenshots aren't available in the browser");
+            return;
+        }
+        let path = export::screenshot_path(self.grid.generation(), &saves::timestamp_now());
+        match export::save_png(&self.grid, export::SCREENSHOT_SCALE, &path) {
+            Ok(()) => self
+                .notice
+                .show(format!("Screenshot saved to {}", path.display())),
+            Err(err) => {
+                error!("failed to save screenshot {}: {err}", path.display());
+                self.notice.show(format!("Screenshot failed: {err}"));
+            }
+        }
+    }
+
+    /// Export the board as an SVG in the screenshots directory, in the colors
+    /// of the theme and with grid lines when they are shown
+    fn export_svg(&mut self) {
+        if cfg!(target_arch = "wasm32") {
+            self.notice
+                .show("SVG export isn't available in the browser");
+            return;
+        }
+        let style = SvgStyle {
+            alive: theme::to_hex_color(self.theme.alive),
+            background: Some(theme::to_hex_color(self.theme.background)),
+            grid_lines: self
+                .show_grid_lines
+                .then(|| theme::to_hex_color(self.theme.grid_lines)),
+        };
+        let path = export::screenshot_path(self.grid.generation(), &saves::timestamp_now())
+            .with_extension("svg");
+        match export::save_svg(&self.grid, &style, &path) {
+            Ok(()) => self.notice.show(format!("SVG saved to {}", path.display())),
+            Err(err) => {
+                error!("failed to save SVG {}: {err}", path.display());
+                self.notice.show(format!("SVG export failed: {err}"));
+            }
+        }
+    }
+
+    /// Start recording a GIF, beginning with the current state of the board
+    #[cfg(feature = "gif")]
+    fn start_recording(&mut self) {
+        if cfg!(target_arch = "wasm32") {
+            self.notice.show("Recording isn't available in the browser");
+            return;
+        }
+        let mut recorder =
+            gif::GifRecorder::new(self.grid.width(), self.grid.height(), self.max_gif_frames);
+        recorder.push(&self.grid, self.update_interval);
+        self.recorder = Some(recorder);
+        self.notice.show("Recording started");
+    }
+
+    /// Append the board to the recording, if any, stopping once it can't take more frames
+    #[cfg(feature = "gif")]
+    fn record_frame(&mut self) {
+        let Some(recorder) = self.recorder.as_mut() else {
+            return;
+        };
+        if !recorder.push(&self.grid, self.update_interval) {
+            let full = recorder.is_full();
+            self.stop_recording();
+            if full {
+                self.notice.show(format!(
+                    "Recording stopped: reached the {} frame limit",
+                    self.max_gif_frames
+                ));
+            } else {
+                self.notice.show("Recording stopped: the grid size changed");
+            }
+        }
+    }
+
+    /// Stop recording and write the GIF to the recordings directory
+    #[cfg(feature = "gif")]
+    fn stop_recording(&mut self) {
+        let Some(recorder) = self.recorder.take() else {
+            return;
+        };
+        let start = self
+            .grid
+            .generation()
+            .saturating_sub(recorder.len() as u64 - 1);
+        let path = gif::recording_path(start, &saves::timestamp_now());
+        match recorder.save(&path) {
+            Ok(()) => self
+                .notice
+                .show(format!("Recording saved to {}", path.display())),
+            Err(err) => {
+                error!("failed to save recording {}: {err}", path.display());
+                self.notice.show(format!("Recording failed: {err}"));
+            }
+        }
+    }
+
+    /// Load the next file from the saves directory, newest first, wrapping
+    /// around to the newest one after the oldest
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_next_save(&mut self) {
+        match saves::list_saves() {
+            Ok(saves) if saves.is_empty() => self.notice.show("No saves found"),
+            Ok(saves) => {
+                let path = &saves[self.load_index % saves.len()];
+                self.load(path);
+                self.load_index = (self.load_index + 1) % saves.len();
+            }
+            Err(err) => {
+                error!("failed to list saves: {err}");
+                self.notice.show(format!("Could not list saves: {err}"));
+            }
+        }
+    }
+
+    /// Load the in-memory saves newest first, cycling on repeated calls
+    #[cfg(target_arch = "wasm32")]
+    fn load_next_save(&mut self) {
+        if self.memory_saves.is_empty() {
+            self.notice.show("No saves found");
+            return;
+        }
+        let count = self.memory_saves.len();
+        let index = count - 1 - self.load_index % count;
+        self.history.record(&self.grid);
+        self.board_replaced();
+        self.grid = self.memory_saves[index].clone();
+        self.dirty = false;
+        self.state = State::Paused;
+        self.notice.show(format!("Loaded save #{}", index + 1));
+        self.load_index = (self.load_index + 1) % count;
+    }
+
+    /// Replace the board with a pattern file, pausing the simulation
+    /// Patterns smaller than the configured board are centered on it.
+    /// On failure the current board is kept and the error shown on screen.
+    fn load(&mut self, path: &std::path::Path) {
+        match formats::load_file(path) {
+            Ok(pattern) => {
+                self.history.record(&self.grid);
+                self.board_replaced();
+                self.grid = conways::Grid::centered(&pattern, self.board_size.0, self.board_size.1);
+                self.dirty = false;
+                self.state = State::Paused;
+                self.selection = None;
+                self.stop_pasting();
+                self.notice.show(format!("Loaded {}", path.display()));
+            }
+            Err(err) => {
+                error!("failed to load {}: {err}", path.display());
+                self.notice
+                    .show(format!("Could not load {}: {err}", path.display()));
+            }
+        }
+    }
+
+    /// Load the `--pattern` file again after it changed
+    /// A file that no longer parses keeps the current board, and the next
+    /// change is offered again.
+    fn reload_pattern(&mut self) {
+        self.reload_offer = false;
+        if let Some(path) = self
+            .pattern_watch
+            .as_ref()
+            .map(|watch| watch.path().to_owned())
+        {
+            self.load(&path);
+        }
+    }
+
+    /// Kill every cell, as an edit that can be undone
+    fn clear(&mut self) {
+        self.before_edit();
+        self.onion = None;
+        let (width, height) = (self.grid.width(), self.grid.height());
+        self.grid.clear_region(conways::Region {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        });
+    }
+
+    /// Clear the board and run the `--script` file on it again, read afresh
+    /// A script that no longer parses keeps the current board; one that fails
+    /// running keeps what it drew, and either says why in the notice.
+    #[cfg(feature = "scripting")]
+    fn rerun_script(&mut self) {
+        let Some((path, budget)) = self.script.clone() else {
+            self.notice.show("No --script to run again");
+            return;
+        };
+        let failed = |game: &mut Self, err: error::ConwayError| {
+            error!("{}: {err}", path.display());
+            game.notice.show(format!("{}: {err}", path.display()));
+        };
+        let script = match cli::load_script(&path) {
+            Ok(script) => script,
+            Err(err) => return failed(self, err),
+        };
+        self.clear();
+        let seed = self.next_seed();
+        let ran = cli::run_script(&script, &mut self.grid, seed, budget);
+        // Kept from here on, as the board's rule is set from the game's every frame
+        self.rule = self.grid.rule();
+        match ran {
+            Ok(()) => self.notice.show(format!("Ran {} again", path.display())),
+            Err(err) => failed(self, err),
+        }
+    }
+
+    /// Copy the selected cells into the clipboard
+    fn copy_selection(&mut self) {
+        if let Some(selection) = &self.selection {
+            self.clipboard = Some(Clipboard::copy(&self.grid, selection.region()));
+        }
+    }
+
+    /// The selection, or every live cell when nothing is selected, `None`
+    /// on an empty board
+    fn region_to_copy(&self) -> Option<conways::Region> {
+        match &self.selection {
+            Some(selection) => Some(selection.region()),
+            None => self.grid.live_bounds(),
+        }
+    }
+
+    /// The cells of `region_to_copy` as RLE text
+    fn rle_to_copy(&self) -> Option<String> {
+        let copied = Clipboard::copy(&self.grid, self.region_to_copy()?);
+        Some(formats::rle::encode(
+            copied.pattern(),
+            &self.rule.to_string(),
+            &[],
+        ))
+    }
+
+    /// Put the selection, or the board cropped to its live cells, on the
+    /// system clipboard as RLE, for Golly or the LifeWiki to paste
+    fn copy_rle(&mut self) {
+        match self.rle_to_copy() {
+            Some(text) => {
+                miniquad::window::clipboard_set(&text);
+                self.notice.show("Copied to the system clipboard as RLE");
+            }
+            None => self.notice.show("Nothing to copy, the board is empty"),
+        }
+    }
+
+    /// Put the live cells of `region_to_copy` on the system clipboard as a
+    /// share string, a single token to paste into a chat message
+    fn copy_share_string(&mut self) {
+        let Some(region) = self.region_to_copy() else {
+            self.notice.show("Nothing to copy, the board is empty");
+            return;
+        };
+        let mut copied = self.grid.extract_region(region);
+        copied.set_rule(self.rule);
+        if copied.population() == 0 {
+            self.notice.show("Nothing to copy, the selection is empty");
+            return;
+        }
+        miniquad::window::clipboard_set(&copied.to_share_string());
+        self.notice
+            .show("Copied to the system clipboard as a share string");
+    }
+
+    /// Start pasting the RLE pattern or share string in `text`, or show why
+    /// it can't be
+    fn paste_rle(&mut self, text: &str) {
+        let pattern = if text.trim_start().starts_with(conways::share::SHARE_PREFIX) {
+            conways::Grid::from_share_string(text).map_err(|err| err.to_string())
+        } else {
+            formats::rle::decode(text).map_err(|err| err.to_string())
+        };
+        match pattern {
+            Ok(pattern) => {
+                self.log(Event::PastePattern(pattern.clone()));
+                self.notice.show(format!(
+                    "Pasting {}x{} from the system clipboard",
+                    pattern.width(),
+                    pattern.height()
+                ));
+                self.start_paste(pattern);
+            }
+            Err(err) => self.notice.show(format!(
+                "The system clipboard holds no RLE pattern or share string: {err}"
+            )),
+        }
+    }
+
+    /// Stamp the reference layer into the board where it sits, as one undo step
+    fn commit_reference(&mut self) {
+        let Some(layer) = self.reference.take() else {
+            self.notice.show(NO_REFERENCE);
+            return;
+        };
+        let (x, y) = layer.offset();
+        self.log(Event::Reference {
+            x,
+            y,
+            pattern: layer.pattern().clone(),
+        });
+        self.before_edit();
+        layer.commit(&mut self.grid);
+        self.reference = Some(layer);
+        self.moving_reference = false;
+        self.notice.show("Reference layer stamped into the board");
+    }
+
+    /// Kill every cell inside the selection
+    fn clear_selection(&mut self) {
+        if let Some(region) = self.selection.as_ref().map(Selection::region) {
+            self.before_edit();
+            self.grid.clear_region(region);
+        }
+    }
+
+    /// Grow or shrink the board by `delta` on one edge, as a single undo step
+    ///
+    /// Shrinking away live cells only warns the first time; `command` has to
+    /// come again right after to go through with it. The view moves along with
+    /// the cells, so a resize on the left or top doesn't make the board jump.
+    fn resize(&mut self, command: InputCommand, edge: Edge, delta: isize) {
+        let mut resized = self.grid.clone();
+        let shift = resized.grow(edge, delta);
+        if resized.width() > cli::MAX_GRID_SIDE || resized.height() > cli::MAX_GRID_SIDE {
+            self.notice.show(format!(
+                "Boards are at most {} cells on a side",
+                cli::MAX_GRID_SIDE
+            ));
+            return;
+        }
+
+        let clipped = self.grid.population() - resized.population();
+        if clipped > 0 && self.pending_resize != Some(command) {
+            self.pending_resize = Some(command);
+            self.notice.show(format!(
+                "Shrinking removes {clipped} live cells, press again to confirm"
+            ));
+            return;
+        }
+        self.pending_resize = None;
+
+        self.history.record(&self.grid);
+        self.grid = resized;
+        self.dirty = true;
+        self.board_size = (self.grid.width(), self.grid.height());
+        self.camera.origin.0 += shift.0 as f32;
+        self.camera.origin.1 += shift.1 as f32;
+        // Everything that refers to cells by position is stale now
+        self.selection = None;
+        self.trail.clear();
+        self.since_minimap = minimap::MINIMAP_REFRESH;
+        self.timeline.clear();
+        self.detector.reset();
+        self.notice.show(format!(
+            "Board size {}x{}",
+            self.grid.width(),
+            self.grid.height()
+        ));
+    }
+
+    /// Call right before changing cells outside the command queue
+    /// The change becomes an undo step, and any generations stepped back over
+    /// can no longer be replayed.
+    fn before_edit(&mut self) {
+        self.history.record(&self.grid);
+        self.timeline.truncate();
+        self.detector.reset();
+        self.dirty = true;
+    }
+
+    /// Close the game, or ask first when that would lose unsaved edits
+    fn request_quit(&mut self) {
+        if self.quitting == Quitting::No {
+            self.quitting = if self.dirty {
+                Quitting::Asking
+            } else {
+                Quitting::Yes
+            };
+        }
+    }
+
+    /// Answer the quit prompt with `key`: Y quits as is, S saves first and
+    /// only quits once the save went through, Escape keeps playing
+    fn answer_quit(&mut self, key: KeyCode) {
+        if self.quitting != Quitting::Asking {
+            return;
+        }
+        match key {
+            KeyCode::Y => self.quitting = Quitting::Yes,
+            KeyCode::S => {
+                self.save();
+                if !self.dirty {
+                    self.quitting = Quitting::Yes;
+                }
+            }
+            KeyCode::Escape => self.quitting = Quitting::No,
+            _ => {}
+        }
+    }
+
+    /// Fill the selection with random cells at the board's density
+    /// The seed is shown on screen so the same noise can be reproduced.
+    fn fill_noise(&mut self) {
+        if let Some(region) = self.selection.as_ref().map(Selection::region) {
+            let seed = self.next_seed();
+            self.before_edit();
+            self.grid.randomize_region(region, self.density, seed);
+            self.notice.show(format!("Noise seed {seed}"));
+        }
+    }
+
+    /// Stamp the pending paste pattern under the cursor
+    fn stamp_paste(&mut self, mode: conways::InsertMode) {
+        if let Some(paste) = &self.paste {
+            let origin = paste.origin(self.cursor_cell());
+            self.stamp_paste_at(origin, mode);
+        }
+    }
+
+    /// Lay down a copy of the pending paste centered on `cursor` with the stamp
+    /// brush, unless it would overlap the drag's last copy
+    /// The cells go through the edit queue like painting does, so brushing
+    /// works while the simulation runs.
+    fn brush_at(&mut self, cursor: (isize, isize), mode: conways::InsertMode) {
+        let Some(paste) = &self.paste else {
+            return;
+        };
+        let origin = paste.origin(cursor);
+        let pattern = paste.pattern();
+        let size = (pattern.width(), pattern.height());
+        if !paste::brush_spaced(self.brush_last, origin, size) {
+            return;
+        }
+        self.brush_last = Some(origin);
+        for (x, y, state) in self.grid.place(pattern, origin.0, origin.1) {
+            if *state == conways::CellState::Alive || mode == conways::InsertMode::Overwrite {
+                self.edits.push(Command::Set {
+                    x,
+                    y,
+                    state: state.clone(),
+                });
+            }
+        }
+    }
+
+    /// Stamp the pending paste pattern with its top-left corner at a cell
+    /// A selection being moved is dropped there, which ends the move.
+    fn stamp_paste_at(&mut self, (x, y): (isize, isize), mode: conways::InsertMode) {
+        if self.paste.is_none() {
+            return;
+        }
+        self.log(Event::Stamp { x, y, mode });
+        if let Some(paste) = self.paste.take_if(|paste| paste.is_move()) {
+            self.timeline.truncate();
+            self.detector.reset();
+            paste.drop_move(&mut self.grid, &mut self.history, (x, y), mode);
+            self.stop_pasting();
+            return;
+        }
+        self.before_edit();
+        if let Some(paste) = &self.paste {
+            self.grid.insert_pattern(paste.pattern(), x, y, mode);
+        }
+    }
+
+    /// Fill `region` with copies of the clipboard, as a single undo step
+    fn tile_region(&mut self, region: conways::Region) {
+        let Some(pattern) = self.clipboard.as_ref().map(|c| c.pattern().clone()) else {
+            self.notice.show("Copy a pattern to tile first");
+            return;
+        };
+        self.log(Event::Tile(region));
+        let pitch = self
+            .tile_pitch
+            .unwrap_or_else(|| tile::default_pitch(&pattern));
+        let before = self.grid.clone();
+        match tile::tile(&mut self.grid, &pattern, region, pitch) {
+            Ok(copies) => {
+                if self.grid != before {
+                    self.dirty = true;
+                    self.history.record(&before);
+                    self.timeline.truncate();
+                    self.detector.reset();
+                }
+                self.notice.show(format!("Tiled {copies} copies"));
+            }
+            Err(err) => self.notice.show(format!("Can't tile: {err}")),
+        }
+    }
+
+    /// Finish selecting `region`, copying it to the clipboard
+    fn select(&mut self, region: conways::Region) {
+        self.log(Event::Select(region));
+        let mut selection = Selection::start((region.x, region.y));
+        selection.extend_to((region.x + region.width - 1, region.y + region.height - 1));
+        selection.finish();
+        self.selection = Some(selection);
+        self.copy_selection();
+    }
+
+    /// A seed for a random board or noise patch
+    fn next_seed(&mut self) -> u64 {
+        match &mut self.seeds {
+            Some(seeds) => seeds.next_u64(),
+            None => rng::clock_seed(),
+        }
+    }
+
+    /// Remember an event for `--record`, when recording
+    fn log(&mut self, event: Event) {
+        if let Some((_, log)) = &mut self.input_log {
+            log.record(event);
+        }
+    }
+
+    /// Pause while the window is in the background, and pick up again once it's back
+    /// Left alone while recording or playing back, so replays come out the same.
+    fn focus_changed(&mut self, focused: bool) {
+        if self.input_log.is_some() || self.playback.is_some() {
+            return;
+        }
+        let Some(focus) = &mut self.focus_pause else {
+            return;
+        };
+        match focus.focus(focused, self.state == State::Running) {
+            FocusAction::None => (),
+            FocusAction::Pause => self.state = State::Paused,
+            FocusAction::Resume => self.state = State::Running,
+        }
+    }
+
+    /// Show the kept generation at `index` of the timeline, pausing there
+    /// Resuming from it drops the later generations, as after stepping back.
+    fn scrub_to(&mut self, index: usize) {
+        if index == self.timeline.position() && self.state == State::Paused {
+            return;
+        }
+        self.log(Event::Seek(index));
+        if self.paste.as_ref().is_some_and(PendingPaste::is_move) {
+            self.stop_pasting();
+        }
+        self.state = State::Paused;
+        self.detector.reset();
+        self.timeline.seek(&mut self.grid, index);
+    }
+
+    /// Set `key` to the TOML literal `value` in `conways.toml`, keeping everything
+    /// else in it, so the choice is still there next time
+    fn remember_setting(&self, key: &str, value: &str) {
+        let Some(path) = &self.settings_path else {
+            return;
+        };
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => {
+                warn!("failed to read {}: {err}", path.display());
+                return;
+            }
+        };
+        if let Err(err) = std::fs::write(path, settings::set_key(&text, key, value)) {
+            warn!("failed to write {}: {err}", path.display());
+        }
+    }
+
+    /// Log everything from now on, to be written to `path` on exit
+    fn start_input_log(&mut self, path: std::path::PathBuf, config: &Config) {
+        let seed = rng::clock_seed();
+        self.seeds = Some(SplitMix64::new(seed));
+        let header = replay::Header::new(config, &self.grid, seed);
+        self.input_log = Some((path, Replay::new(header)));
+    }
+
+    /// Start writing down every board of the run for `--record-run`
+    fn start_run_log(&mut self, path: std::path::PathBuf) {
+        self.grid.set_rule(self.rule);
+        self.grid.set_boundary(self.boundary);
+        self.run_log = Some((path, RunRecording::new(&self.grid)));
+    }
+
+    /// Add the board to the `--record-run` recording
+    /// A resize can't be recorded, so the recording is written and stops there.
+    fn record_run(&mut self) {
+        let Some((_, run)) = &mut self.run_log else {
+            return;
+        };
+        if run.record(&self.grid).is_err() {
+            self.save_run_log();
+            self.run_log = None;
+            self.notice
+                .show("The board was resized, the run recorded until then is saved");
+        }
+    }
+
+    /// Stop writing the `--stats-csv` file once a row failed, keeping the rows
+    /// written so far
+    fn check_stats_csv(&mut self) {
+        let mut listeners = self.listeners.lock();
+        let Some((path, csv)) = &mut listeners.stats_csv else {
+            return;
+        };
+        let Some(err) = csv.take_error() else {
+            return;
+        };
+        eprintln!("error: failed to write {}: {err}", path.display());
+        let message = format!("Stopped writing {}: {err}", path.display());
+        listeners.stats_csv = None;
+        drop(listeners);
+        self.notice.show(message);
+    }
+
+    /// Write the rows of the `--stats-csv` file still in its buffer
+    fn flush_stats_csv(&mut self) {
+        if let Some((path, csv)) = &mut self.listeners.lock().stats_csv {
+            if let Err(err) = csv.flush() {
+                eprintln!("error: failed to write {}: {err}", path.display());
+            }
+        }
+    }
+
+    /// Write the `--record-run` recording, with the edits since its last generation
+    fn save_run_log(&mut self) {
+        if let Some((path, run)) = &mut self.run_log {
+            // A resized board is left out, the run was recorded up to it
+            let _ = run.record(&self.grid);
+            match run.save(path) {
+                Ok(()) => println!(
+                    "Recorded {} generations of the run to {}",
+                    run.len(),
+                    path.display()
+                ),
+                Err(err) => eprintln!("error: failed to write {}: {err}", path.display()),
+            }
+        }
+    }
+
+    /// Watch a `--play-run` recording from its first board, running through
+    /// its boards instead of simulating them
+    fn start_run_playback(&mut self, run: RunRecording) {
+        self.board_replaced();
+        self.rule = run.rule();
+        self.boundary = run.boundary();
+        let (grid, timeline) = run.play(self.timeline.capacity());
+        self.grid = grid;
+        self.timeline = timeline;
+        self.run_end = Some(self.grid.content_hash());
+        self.board_size = (self.grid.width(), self.grid.height());
+        self.timeline.seek(&mut self.grid, 0);
+        self.notice.show(format!(
+            "Watching a recorded run of {} generations",
+            run.len()
+        ));
+    }
+
+    /// Show the next board of the run being watched, in place of simulating one
+    ///
+    /// # Returns
+    /// Whether that took care of the generation; once the board was changed
+    /// and the rest of the run dropped, it simulates from there instead
+    fn play_run_step(&mut self) -> bool {
+        let Some(end) = self.run_end else {
+            return false;
+        };
+        let before = self.grid.clone();
+        if self.timeline.forward(&mut self.grid) {
+            self.stats.record(self.grid.population());
+            self.update_trail();
+            #[cfg(feature = "gif")]
+            self.record_frame();
+            self.onion = Some(before);
+            return true;
+        }
+        if self.grid.content_hash() == end {
+            self.state = State::Paused;
+            self.notice.show("End of the recorded run");
+            return true;
+        }
+        self.run_end = None;
+        false
+    }
+
+    /// Write the `--record` log, if recording
+    fn save_input_log(&self) {
+        if let Some((path, log)) = &self.input_log {
+            match log.save(path) {
+                Ok(()) => println!("Recorded the session to {}", path.display()),
+                Err(err) => eprintln!("error: failed to write {}: {err}", path.display()),
+            }
+        }
+    }
+
+    /// Play back a recorded session from the start, instead of reading input
+    /// Fails when the replay was recorded by another version, with other
+    /// settings or from another board, since it would come out differently.
+    fn start_playback(&mut self, replay: Replay, config: &Config) -> Result<(), ReplayError> {
+        let seed = replay.header.seed;
+        replay
+            .header
+            .check(&replay::Header::new(config, &self.grid, seed))?;
+        self.seeds = Some(SplitMix64::new(seed));
+        self.playback = Some(replay.into_frames());
+        Ok(())
+    }
+
+    /// Glide the camera towards the live cells when asked to, see `FitView`
+    /// Empty boards go back to the camera the game started with.
+    fn move_camera(&mut self, dt: f32, screen: (f32, f32)) {
+        if std::mem::take(&mut self.fit_requested) {
+            let target = match self.grid.live_bounds() {
+                Some(region) => Camera::framing(
+                    camera::Bounds {
+                        x: region.x as f32,
+                        y: region.y as f32,
+                        w: region.width as f32,
+                        h: region.height as f32,
+                    },
+                    camera::FIT_MARGIN,
+                    screen,
+                ),
+                None => self.home,
+            };
+            self.glide = Some(Glide::new(self.camera, target));
+        }
+        if let Some(glide) = self.glide.as_mut() {
+            let (camera, arrived) = glide.advance(dt, screen);
+            self.camera = camera;
+            if arrived {
+                self.glide = None;
+            }
+        }
+        // Every copy looks the same, so the camera is brought back over the
+        // board itself however far it was panned
+        if self.seamless() {
+            let dims = (self.grid.width(), self.grid.height());
+            self.camera = wrap::canonical_camera(self.camera, dims);
+        }
+    }
+
+    /// Run at `speed` generations per second, within the slider's range
+    fn set_speed(&mut self, speed: f32) {
+        let interval = (1.0 / speed).clamp(MIN_UPDATE_INTERVAL, MAX_UPDATE_INTERVAL);
+        if interval != self.update_interval {
+            self.log(Event::Speed(speed));
+            self.update_interval = interval;
+        }
+    }
+
+    /// Stop playing back, handing control to the player
+    fn stop_playback(&mut self) {
+        if self.playback.take().is_some() {
+            self.notice.show("Replay finished");
+        }
+    }
+
+    /// Carry out the next frame of the replay
+    ///
+    /// # Returns
+    /// How long the frame took when it was recorded, to update the game
+    /// with, or `None` once the replay is over
+    fn play_frame(&mut self) -> Option<f32> {
+        let Some(frame) = self.playback.as_mut().and_then(Iterator::next) else {
+            self.stop_playback();
+            return None;
+        };
+        for event in frame.events {
+            match event {
+                Event::Input(command) => self.apply(command),
+                Event::Edit(command) => self.edits.push(command),
+                Event::Fill { x, y } => self.flood_fill((x, y)),
+                Event::Stamp { x, y, mode } => self.stamp_paste_at((x, y), mode),
+                Event::Lift { x, y } => self.lift_selection(Some((x, y))),
+                Event::Select(region) => self.select(region),
+                Event::Tile(region) => self.tile_region(region),
+                Event::PastePattern(pattern) => {
+                    self.log(Event::PastePattern(pattern.clone()));
+                    self.start_paste(pattern);
+                }
+                Event::Reference { x, y, pattern } => {
+                    self.log(Event::Reference {
+                        x,
+                        y,
+                        pattern: pattern.clone(),
+                    });
+                    self.before_edit();
+                    self.grid
+                        .insert_pattern(&pattern, x, y, conways::InsertMode::Or);
+                }
+                Event::Seek(index) => self.scrub_to(index),
+                Event::Speed(speed) => self.set_speed(speed),
+                Event::GoTo(generation) => self.go_to(generation),
+                Event::Claim { x, y } => self.claim((x, y)),
+                Event::Rule(rule) => {
+                    self.log(Event::Rule(rule));
+                    self.set_rule(rule);
+                }
+            }
+        }
+        Some(frame.dt)
+    }
+
+    /// Whether the board is drawn tiled, which maps every screen position to
+    /// a cell of it
+    fn seamless(&self) -> bool {
+        self.seamless_wrap && self.boundary == BoundaryCondition::Wrap
+    }
+
+    /// The cell of the board itself a position over any of its tiled copies
+    /// stands for
+    fn wrapped_cell_at(&self, pos: Point) -> (usize, usize) {
+        let (x, y) = self.camera.screen_to_cell(pos);
+        let dims = (self.grid.width(), self.grid.height());
+        wrap::canonical_cell((x.floor() as isize, y.floor() as isize), dims)
+    }
+
+    /// The cell under the mouse cursor, which may lie outside the grid
+    fn cursor_cell(&self) -> (isize, isize) {
+        if self.seamless() {
+            let (x, y) = self.wrapped_cell_at(mouse_position());
+            return (x as isize, y as isize);
+        }
+        let (x, y) = self.camera.screen_to_cell(mouse_position());
+        (x.floor() as isize, y.floor() as isize)
+    }
+
+    /// Map a screen position to the nearest grid cell, clamping positions
+    /// outside the grid to its edges
+    fn clamped_cell_at(&self, pos: Point) -> (usize, usize) {
+        if self.seamless() {
+            return self.wrapped_cell_at(pos);
+        }
+        let (x, y) = self.camera.screen_to_cell(pos);
+        let clamp = |v: f32, len: usize| (v.max(0.0) as usize).min(len - 1);
+        (clamp(x, self.grid.width()), clamp(y, self.grid.height()))
+    }
+
+    /// Map a screen position to the grid cell under it, if any
+    fn cell_at(&self, pos: Point) -> Option<(usize, usize)> {
+        if self.seamless() {
+            return Some(self.wrapped_cell_at(pos));
+        }
+        let (x, y) = self.camera.screen_to_cell(pos);
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let (x, y) = (x as usize, y as usize);
+        (x < self.grid.width() && y < self.grid.height()).then_some((x, y))
+    }
+
+    /// Fill the square of the cell at `(x, y)`
+    /// Outline the board to show its edges: solid for dead edges, dashed for wrapping ones
+    fn draw_boundary(&self) {
+        let (left, top) = self.camera.cell_to_screen((0.0, 0.0));
+        let (right, bottom) = self
+            .camera
+            .cell_to_screen((self.grid.width() as f32, self.grid.height() as f32));
+        match self.boundary {
+            BoundaryCondition::Dead => {
+                draw_rectangle_lines(
+                    left,
+                    top,
+                    right - left,
+                    bottom - top,
+                    2.0,
+                    self.theme.hud_text,
+                );
+            }
+            BoundaryCondition::Wrap => {
+                let corners = [(left, top), (right, top), (right, bottom), (left, bottom)];
+                for i in 0..corners.len() {
+                    let (from, to) = (corners[i], corners[(i + 1) % corners.len()]);
+                    draw_dashed_line(from, to, self.theme.highlight);
+                }
+            }
+        }
+    }
+
+    fn draw_cell(&self, view: &Camera, x: usize, y: usize, color: Color) {
+        self.draw_cell_at(view, (x as f32, y as f32), color);
+    }
+
+    /// Draw a cell at world coordinates, which may lie outside the board
+    fn draw_cell_at(&self, view: &Camera, cell: (f32, f32), color: Color) {
+        ScreenRenderer::new(view, self.cell_shape).fill_at(cell, color);
+    }
+}
+
+/// Length of a dash, and of the gap after it, in the border of a wrapping board
+const DASH_LENGTH: f32 = 8.0;
+
+/// Whether `command` edits the board or moves through its history, which a
+/// selection being moved has to be put back for first
+fn changes_board(command: InputCommand) -> bool {
+    use InputCommand::*;
+    #[cfg(feature = "scripting")]
+    if command == RerunScript {
+        return true;
+    }
+    matches!(
+        command,
+        StepBack
+            | StepForward
+            | Randomize
+            | Clear
+            | PlayMatch
+            | Grow(_)
+            | Shrink(_)
+            | LoadNextSave
+            | Undo
+            | Redo
+            | Cut
+            | ClearSelection
+            | FillNoise
+            | Paste
+            | PasteRle
+            | CommitReference
+            | SelectPattern(_)
+            | RestoreBookmark(_)
+            | LoadSlot(_)
+            | RestoreSession
+    )
+}
+
+/// Works out one segment of the status bar, or `None` while it has nothing to show
+type StatusSegment = fn(&Game) -> Option<String>;
+
+/// The status bar's segments, left to right, so a feature shows itself on the
+/// bar by adding its segment here; on narrow windows the last ones are cut first
+fn status_segments() -> Vec<StatusSegment> {
+    vec![
+        |game| {
+            let state = match game.state {
+                State::Running => "Running",
+                State::Paused => "Paused",
+            };
+            Some(state.to_string())
+        },
+        |game| game.mode_status(),
+        |game| Some(format!("Gen {}", game.grid.generation())),
+        |game| Some(format!("Pop {}", game.grid.population())),
+        |game| Some(game.speed_label()),
+        |game| Some(format!("Rule: {}", game.rule)),
+        |game| Some(format!("Edges: {}", game.boundary.label())),
+        |game| Some(format!("Tool: {}", game.tool.label())),
+        |game| {
+            let brush = game.paste.as_ref().filter(|_| game.stamp_brush)?.pattern();
+            Some(format!("Brush: {}x{}", brush.width(), brush.height()))
+        },
+        |game| {
+            let zoom = game.camera.cell_size / game.home.cell_size * 100.0;
+            Some(format!("Zoom: {zoom:.0}%"))
+        },
+        |game| Some(format!("Symmetry: {}", game.symmetry.label())),
+        |game| {
+            let pattern = game.palette.selected()?;
+            Some(format!(
+                "Pattern: {} (page {}/{})",
+                pattern.name,
+                game.palette.page() + 1,
+                Palette::page_count()
+            ))
+        },
+        |game| {
+            if game.bookmarks.is_empty() {
+                return None;
+            }
+            let slots: Vec<_> = game
+                .bookmarks
+                .iter()
+                .map(|(slot, _)| slot.to_string())
+                .collect();
+            Some(format!("Bookmarks: {}", slots.join(" ")))
+        },
+        |game| {
+            let clipboard = game.clipboard.as_ref()?;
+            Some(format!(
+                "Clipboard: {}x{}",
+                clipboard.width(),
+                clipboard.height()
+            ))
+        },
+        |game| {
+            let rewound = game.timeline.rewound();
+            (rewound > 0).then(|| format!("History: {rewound} back, Right to replay"))
+        },
+        |game| {
+            game.cursor.as_ref()?;
+            Some(game.prefix.pending()?.to_string())
+        },
+        |game| {
+            let layer = game.reference.as_ref()?;
+            let (x, y) = layer.offset();
+            let moving = if game.moving_reference {
+                ", WASD/HJKL move, Ctrl+Enter stamps"
+            } else {
+                ""
+            };
+            Some(format!(
+                "Reference: {} at {x},{y}{moving}",
+                layer.placement().label()
+            ))
+        },
+        |game| {
+            let hint = "Measure: click two cells, Shift+M leaves, Esc clears";
+            game.measure.is_active().then(|| hint.to_string())
+        },
+        |game| {
+            game.paste.as_ref()?;
+            let hint = if game.stamp_brush {
+                "Brush: click or drag to stamp, Alt overwrites, R/F/V rotate/flip"
+            } else {
+                "Paste: click to stamp, Alt+click overwrites, R/F/V rotate/flip"
+            };
+            Some(hint.to_string())
+        },
+    ]
+}
+
+fn draw_dashed_line(from: Point, to: Point, color: Color) {
+    let length = input::distance(from, to);
+    let dashes = (length / (2.0 * DASH_LENGTH)).ceil() as usize;
+    for i in 0..dashes {
+        let start = i as f32 * 2.0 * DASH_LENGTH / length;
+        let end = ((i as f32 * 2.0 + 1.0) * DASH_LENGTH / length).min(1.0);
+        let at = |t: f32| (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+        let ((x1, y1), (x2, y2)) = (at(start), at(end));
+        draw_line(x1, y1, x2, y2, 2.0, color);
+    }
+}
+
+/// Parse the command line, printing the help text or the error and exiting
+/// when there is nothing to run
+fn parse_config() -> Config {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::parse_args(&args, load_settings()) {
+        Ok(Invocation::Run(config)) => *config,
+        Ok(Invocation::Help) => {
+            print!("{}", cli::HELP);
+            std::process::exit(0);
+        }
+        Err(err) => {
+            eprintln!("error: {err}\nRun with --help to see the available options");
+            std::process::exit(2);
+        }
+    }
+}
+
+/// The defaults from `conways.toml`, or the built-in ones when there is none
+/// Unknown keys are only warned about, but an invalid file ends the program.
+fn load_settings() -> Config {
+    let mut config = Config::default();
+    if cfg!(target_arch = "wasm32") {
+        return config;
+    }
+    match std::fs::read_to_string(settings::SETTINGS_FILE) {
+        Ok(text) => match settings::apply_settings(&text, &mut config) {
+            Ok(warnings) => {
+                for warning in warnings {
+                    warn!("{warning}");
+                }
+            }
+            Err(err) => {
+                eprintln!("error: invalid {}: {err}", settings::SETTINGS_FILE);
+                std::process::exit(1);
+            }
+        },
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => (),
+        Err(err) => {
+            eprintln!("error: failed to read {}: {err}", settings::SETTINGS_FILE);
+            std::process::exit(1);
+        }
+    }
+    config
+}
+
+/// The key bindings from `keybindings.toml`, or the defaults when there is none
+/// An invalid file is reported and ends the program, rather than being half applied.
+fn load_bindings() -> Vec<Binding> {
+    if cfg!(target_arch = "wasm32") {
+        return bindings::default_bindings();
+    }
+    match std::fs::read_to_string(bindings::KEYBINDINGS_FILE) {
+        Ok(text) => match bindings::parse_bindings(&text) {
+            Ok(bindings) => bindings,
+            Err(err) => {
+                eprintln!("error: invalid {}: {err}", bindings::KEYBINDINGS_FILE);
+                std::process::exit(1);
+            }
+        },
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => bindings::default_bindings(),
+        Err(err) => {
+            eprintln!(
+                "error: failed to read {}: {err}",
+                bindings::KEYBINDINGS_FILE
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+fn conf(config: &Config) -> Conf {
+    Conf {
+        window_title: title::APP_NAME.to_string(),
+        window_width: config.window_size.0,
+        window_height: config.window_size.1,
+        ..Default::default()
+    }
+}
+
+fn main() {
+    // Warnings show unless RUST_LOG asks for more or for less
+    game_of_life::log::set_logger(StderrLogger::from_env("warn"));
+    // Everything up to opening the window happens here, so `--help`, bad
+    // arguments and headless runs never flash a window
+    let config = parse_config();
+    if config.headless {
+        std::process::exit(headless::run(&config));
+    }
+
+    if config.dump_keybindings {
+        match std::fs::write(bindings::KEYBINDINGS_FILE, bindings::default_file()) {
+            Ok(()) => println!("Wrote {}", bindings::KEYBINDINGS_FILE),
+            Err(err) => {
+                eprintln!(
+                    "error: failed to write {}: {err}",
+                    bindings::KEYBINDINGS_FILE
+                );
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if config.write_config {
+        match std::fs::write(settings::SETTINGS_FILE, settings::default_file()) {
+            Ok(()) => println!("Wrote {}", settings::SETTINGS_FILE),
+            Err(err) => {
+                eprintln!("error: failed to write {}: {err}", settings::SETTINGS_FILE);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let grid = match config.starting_grid() {
+        Ok(grid) => grid,
+        Err(err) => {
+            let path = config.board_file().unwrap_or(std::path::Path::new(""));
+            eprintln!("error: could not load {}: {err}", path.display());
+            std::process::exit(headless::exit_code(&err));
+        }
+    };
+    let mut game = Game::new(&config, grid);
+    // `--rule`, or the one a script picked
+    game.rule = game.grid.rule();
+    game.bindings = load_bindings();
+    if let Some(date) = config.daily {
+        game.lifespan = Some(Lifespan::new(date, game.grid.population()));
+        game.notice.show(daily::title(date));
+    }
+    if let Some(path) = &config.puzzle {
+        match Puzzle::load(path) {
+            Ok(puzzle) => game.start_puzzle(puzzle),
+            Err(err) => {
+                eprintln!("error: could not load {}: {err}", path.display());
+                std::process::exit(headless::exit_code(&err));
+            }
+        }
+    }
+    if !cfg!(target_arch = "wasm32") {
+        game.settings_path = Some(settings::SETTINGS_FILE.into());
+        game.slots = Slots::new(session::session_path());
+    }
+
+    if let Some(path) = &config.replay {
+        let started = Replay::load(path).and_then(|replay| game.start_playback(replay, &config));
+        if let Err(err) = started {
+            eprintln!("error: can't replay {}: {err}", path.display());
+            std::process::exit(1);
+        }
+    }
+    if let Some(path) = &config.record {
+        game.start_input_log(path.clone(), &config);
+    }
+    if let Some(path) = &config.play_run {
+        match RunRecording::load(path) {
+            Ok(run) => game.start_run_playback(run),
+            Err(err) => {
+                eprintln!("error: can't play {}: {err}", path.display());
+                std::process::exit(1);
+            }
+        }
+    }
+    // Reloads aren't recorded, so replays go without them
+    if let Some(path) = &config.pattern {
+        if config.record.is_none() && config.replay.is_none() && !cfg!(target_arch = "wasm32") {
+            game.pattern_watch = Some(FileWatcher::new(path.clone()));
+        }
+    }
+
+    // A board asked for on the command line wins over the previous session, and
+    // replays have to start from the same board they were recorded from
+    let explicit_board = config.explicit_board()
+        || config.record.is_some()
+        || config.replay.is_some()
+        || config.play_run.is_some();
+    match session::session_path().and_then(|path| Session::restore(&path)) {
+        Some(_) if explicit_board => (),
+        Some(session) if config.restore => game.restore(session),
+        Some(session) => game.session_offer = Some(session),
+        None => (),
+    }
+    // From the board the run starts on, after a restored session
+    if let Some(path) = &config.record_run {
+        game.start_run_log(path.clone());
+    }
+    if let Some(port) = config.host {
+        match collab::Host::bind(("0.0.0.0", port)) {
+            Ok(host) => {
+                let port = host.local_addr().port();
+                info!("hosting on port {port}");
+                game.collab = Some(Collab::Host(Box::new(host)));
+                game.notice.show(format!("Others can join on port {port}"));
+            }
+            Err(err) => {
+                eprintln!("error: can't host on port {port}: {err}");
+                std::process::exit(headless::EXIT_IO);
+            }
+        }
+    }
+    #[cfg(feature = "websocket")]
+    if let Some(port) = config.websocket {
+        match websocket::Broadcaster::bind(("0.0.0.0", port)) {
+            Ok(broadcaster) => {
+                let port = broadcaster.local_addr().port();
+                info!("serving WebSocket viewers on port {port}");
+                game.broadcaster = Some(broadcaster);
+                game.notice
+                    .show(format!("WebSocket viewers can connect on port {port}"));
+            }
+            Err(err) => {
+                eprintln!("error: can't serve WebSocket viewers on port {port}: {err}");
+                std::process::exit(headless::EXIT_IO);
+            }
+        }
+    }
+    if let Some(address) = &config.join {
+        match collab::Client::connect(address.as_str()) {
+            Ok(client) => {
+                info!("joined {address}");
+                game.collab = Some(Collab::Join(client));
+                game.notice.show(format!("Joined {address}"));
+            }
+            Err(err) => {
+                eprintln!("error: can't join {address}: {err}");
+                std::process::exit(headless::EXIT_IO);
+            }
+        }
+    }
+    if let Some(path) = &config.stats_csv {
+        match StatsCsv::create(path, macroquad::miniquad::date::now) {
+            Ok(csv) => game.listeners.lock().stats_csv = Some((path.clone(), csv)),
+            Err(err) => {
+                eprintln!("error: can't write {}: {err}", path.display());
+                std::process::exit(headless::EXIT_IO);
+            }
+        }
+    }
+    // Centered on the board the game ends up with
+    if let Some(path) = &config.reference {
+        match formats::load_file(path) {
+            Ok(pattern) => {
+                let board = (game.grid.width(), game.grid.height());
+                game.reference = Some(ReferenceLayer::new(pattern, board));
+            }
+            Err(err) => {
+                eprintln!("error: could not load {}: {err}", path.display());
+                std::process::exit(headless::exit_code(&err));
+            }
+        }
+    }
+
+    #[cfg(all(feature = "tui", unix))]
+    if config.tui {
+        if let Err(err) = run_tui(game) {
+            eprintln!("error: terminal mode failed: {err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    macroquad::Window::from_config(conf(&config), run(game));
+}
+
+/// The game loop in terminal mode: keys go through the same binding table and
+/// `Game::apply` as in the window, arrow keys pan and `q` quits
+#[cfg(all(feature = "tui", unix))]
+fn run_tui(mut game: Game) -> std::io::Result<()> {
+    let terminal = tui::RawTerminal::enter()?;
+    let mut origin = (0, 0);
+    let mut size = None;
+    let mut last_frame = std::time::Instant::now();
+
+    loop {
+        for key in terminal.read_keys()? {
+            match key {
+                key if game.quitting == Quitting::Asking => {
+                    if let Some((code, _, _)) = tui::key_code(key) {
+                        game.answer_quit(code);
+                    }
+                }
+                tui::TermKey::Char('q') => game.request_quit(),
+                tui::TermKey::Up => origin.1 -= tui::PAN_STEP * 2,
+                tui::TermKey::Down => origin.1 += tui::PAN_STEP * 2,
+                tui::TermKey::Left => origin.0 -= tui::PAN_STEP,
+                tui::TermKey::Right => origin.0 += tui::PAN_STEP,
+                tui::TermKey::FocusIn => game.focus_changed(true),
+                tui::TermKey::FocusOut => game.focus_changed(false),
+                tui::TermKey::Escape if game.playback.is_some() => game.stop_playback(),
+                _ if game.playback.is_some() => (),
+                key => {
+                    let Some((code, ctrl, shift)) = tui::key_code(key) else {
+                        continue;
+                    };
+                    for command in bindings::commands_for(&game.bindings, code, ctrl, shift) {
+                        // The minimap needs a window to draw its texture
+                        if command != InputCommand::ToggleMinimap {
+                            game.apply(command);
+                        }
+                    }
+                }
+            }
+        }
+        if game.quitting == Quitting::Yes {
+            drop(terminal);
+            #[cfg(feature = "gif")]
+            game.stop_recording();
+            game.autosave();
+            game.save_input_log();
+            game.save_run_log();
+            game.flush_stats_csv();
+            return Ok(());
+        }
+
+        let now = std::time::Instant::now();
+        let dt = game
+            .play_frame()
+            .unwrap_or_else(|| now.duration_since(last_frame).as_secs_f32());
+        game.update(dt);
+        game.refresh_title(dt);
+        last_frame = now;
+
+        // Clear everything when the terminal is resized, so no stale text is left over
+        let (columns, rows) = terminal.size().unwrap_or((80, 24));
+        if size != Some((columns, rows)) {
+            print!("\x1b[2J");
+            size = Some((columns, rows));
+        }
+        let lines = tui::render(&game.grid, origin, columns, rows.saturating_sub(1));
+        let mut status = format!(
+            " {}  Speed {:.1} gen/s ({:.1} actual)  Rule {}  Edges {}",
+            game.summary(),
+            1.0 / game.update_interval,
+            game.rate.rate(),
+            game.rule,
+            game.boundary.label()
+        );
+        if game.quitting == Quitting::Asking {
+            status.push_str(&format!("  {QUIT_PROMPT}"));
+        } else if game.reload_offer {
+            status.push_str("  Pattern file changed: y reloads, Esc dismisses");
+        }
+        if let Some(lifespan) = &game.lifespan {
+            status.push_str(&format!("  {lifespan}"));
+        }
+        if let Some(text) = game.mode_status() {
+            status.push_str(&format!("  {text}"));
+        }
+        if let Some(text) = game.notice.text() {
+            status.push_str(&format!("  {text}"));
+        }
+        status.push_str("  | q quit, arrows pan, Space pause");
+        let status: String = status.chars().take(columns).collect();
+        terminal.draw(&lines, &status)?;
+
+        std::thread::sleep(std::time::Duration::from_secs_f32(tui::FRAME_TIME));
+    }
+}
+
+/// The game loop, running once the window is open
+async fn run(mut game: Game) {
+    // Intercept window close requests so the session gets saved on exit
+    prevent_quit();
+    #[cfg(feature = "audio")]
+    match Sounds::load().await {
+        Ok(sounds) => game.sounds = Some(sounds),
+        Err(err) => warn!("sound is off, failed to load the sounds: {err}"),
+    }
+    loop {
+        // A replay stands in for the player until it is over or stopped with Escape
+        if game.playback.is_some() && is_key_pressed(KeyCode::Escape) {
+            game.stop_playback();
+        }
+        let dt = match game.play_frame() {
+            Some(dt) => dt,
+            None => {
+                game.handle_input();
+                get_frame_time()
+            }
+        };
+        // The window gets no focus events, but a long gap between frames means it
+        // was hidden: the frame after it runs paused instead of catching up on
+        // the time away, and the next one picks up again
+        game.focus_changed(!focus::was_away(dt));
+
+        game.update(dt);
+        game.refresh_title(dt);
+
+        game.move_camera(dt, (screen_width(), screen_height()));
+        game.refresh_background();
+        game.draw();
+
+        if is_quit_requested() {
+            game.request_quit();
+        }
+        if game.quitting == Quitting::Yes {
+            #[cfg(feature = "gif")]
+            game.stop_recording();
+            game.autosave();
+            game.save_input_log();
+            game.save_run_log();
+            game.flush_stats_csv();
+            break;
+        }
+
+        next_frame().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use conways::CellState;
+
+    /// A game running `pattern` in the middle of a `size` x `size` board, one generation per frame
+    fn game_with(name: &str, size: usize) -> Game {
+        let config = Config {
+            width: size,
+            height: size,
+            update_interval: 0.01,
+            ..Config::default()
+        };
+        let pattern = patterns::LIBRARY
+            .iter()
+            .find(|pattern| pattern.name == name)
+            .unwrap()
+            .to_grid();
+        Game::new(&config, conways::Grid::centered(&pattern, size, size))
+    }
+
+    fn run_frames(game: &mut Game, frames: usize) {
+        for _ in 0..frames {
+            game.update(game.update_interval);
+        }
+    }
+
+    #[test]
+    fn test_auto_pause_on_oscillator() {
+        let mut game = game_with("blinker", 10);
+        run_frames(&mut game, 5);
+        assert!(game.state == State::Paused);
+        assert_eq!(game.grid.generation(), 2);
+        assert_eq!(
+            game.notice.text(),
+            Some("Stable (period 2) since generation 0")
+        );
+    }
+
+    #[test]
+    fn test_no_auto_pause_on_glider_gun() {
+        // Large enough that no glider reaches the edge in 300 generations
+        let mut game = game_with("Gosper glider gun", 150);
+        run_frames(&mut game, 300);
+        assert!(game.state == State::Running);
+        assert_eq!(game.grid.generation(), 300);
+    }
+
+    #[test]
+    fn test_edit_clears_stagnation() {
+        let mut game = game_with("blinker", 10);
+        run_frames(&mut game, 5);
+        assert!(game.state == State::Paused);
+
+        // Add a block: the board is stable again, with a different state
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            game.edits.push(Command::Set {
+                x,
+                y,
+                state: CellState::Alive,
+            });
+        }
+        game.apply(InputCommand::TogglePause);
+        run_frames(&mut game, 5);
+        assert!(game.state == State::Paused);
+        assert_eq!(game.grid.generation(), 5);
+    }
+
+    /// A glider about to leave the bottom-right corner of an 8x8 board
+    fn glider_at_corner() -> Game {
+        let config = Config {
+            width: 8,
+            height: 8,
+            update_interval: 0.01,
+            auto_pause: false,
+            ..Config::default()
+        };
+        let mut grid = conways::Grid::new(8, 8);
+        for (x, y) in [(6, 4), (7, 5), (5, 6), (6, 6), (7, 6)] {
+            grid.set(x, y, CellState::Alive);
+        }
+        Game::new(&config, grid)
+    }
+
+    #[test]
+    fn test_glider_crosses_seam_after_switching_to_wrap() {
+        let mut game = glider_at_corner();
+        game.apply(InputCommand::CycleBoundary);
+        assert_eq!(game.boundary, BoundaryCondition::Wrap);
+
+        // A glider moves one cell diagonally every 4 generations
+        run_frames(&mut game, 8);
+        assert_eq!(game.grid.population(), 5);
+        let mut moved = conways::Grid::new(8, 8);
+        for (x, y) in [(0, 6), (1, 7), (7, 0), (0, 0), (1, 0)] {
+            moved.set(x, y, CellState::Alive);
+        }
+        assert_eq!(game.grid, moved);
+
+        // Back to dead edges: the glider breaks up on the corner
+        game.apply(InputCommand::CycleBoundary);
+        run_frames(&mut game, 16);
+        assert_ne!(game.grid.population(), 5);
+    }
+
+    #[test]
+    fn test_shrinking_live_cells_needs_confirmation() {
+        let mut game = game_with("blinker", 30);
+        game.state = State::Paused;
+        game.grid.set(29, 0, CellState::Alive);
+        let shrink = InputCommand::Shrink(Edge::Right);
+
+        game.apply(shrink);
+        assert_eq!(game.grid.width(), 30);
+        assert_eq!(
+            game.notice.text(),
+            Some("Shrinking removes 1 live cells, press again to confirm")
+        );
+
+        game.apply(shrink);
+        assert_eq!((game.grid.width(), game.grid.height()), (20, 30));
+        assert_eq!(game.grid.population(), 3);
+
+        // The whole resize undoes in one step
+        game.apply(InputCommand::Undo);
+        assert_eq!(game.grid.width(), 30);
+        assert_eq!(game.grid.get(29, 0), CellState::Alive);
+    }
+
+    #[test]
+    fn test_other_commands_cancel_resize_confirmation() {
+        let mut game = game_with("blinker", 30);
+        game.grid.set(0, 29, CellState::Alive);
+        let shrink = InputCommand::Shrink(Edge::Bottom);
+
+        game.apply(shrink);
+        game.apply(InputCommand::TogglePause);
+        game.apply(shrink);
+        assert_eq!(game.grid.height(), 30);
+    }
+
+    #[test]
+    fn test_resize_keeps_view_on_cells() {
+        let mut game = game_with("blinker", 30);
+        let blinker = game.grid.clone();
+        let origin = game.camera.origin;
+
+        // The board is empty on the left, so shrinking there needs no confirmation
+        game.apply(InputCommand::Shrink(Edge::Left));
+        assert_eq!(game.grid.width(), 20);
+        assert_eq!(game.camera.origin, (origin.0 - 10.0, origin.1));
+
+        game.apply(InputCommand::Grow(Edge::Top));
+        assert_eq!(game.grid.height(), 40);
+        assert_eq!(game.camera.origin, (origin.0 - 10.0, origin.1 + 10.0));
+        let mut moved = blinker.clone();
+        moved.resize(20, 40, (-10, 10));
+        assert_eq!(game.grid, moved);
+    }
+
+    /// The screen position of the center of a cell
+    fn screen_pos(game: &Game, (x, y): (usize, usize)) -> Point {
+        game.camera.cell_to_screen((x as f32 + 0.5, y as f32 + 0.5))
+    }
+
+    #[test]
+    fn test_flood_fill_tool_undoes_in_one_step() {
+        let mut game = game_with("block", 10);
+        game.state = State::Paused;
+        game.apply(InputCommand::SelectTool(Tool::FloodFill));
+
+        // Everything but the block comes alive
+        game.flood_fill((0, 0));
+        assert_eq!(game.grid.population(), 100);
+        game.flood_fill((0, 0));
+        assert_eq!(game.grid.population(), 0);
+
+        game.apply(InputCommand::Undo);
+        assert_eq!(game.grid.population(), 100);
+        game.apply(InputCommand::Undo);
+        assert_eq!(game.grid.population(), 4);
+    }
+
+    #[test]
+    fn test_tools_decide_what_clicks_do() {
+        let mut game = game_with("block", 10);
+        game.state = State::Paused;
+        let empty = screen_pos(&game, (0, 0));
+
+        game.apply(InputCommand::SelectTool(Tool::Erase));
+        game.use_tool(Gesture::Click(empty));
+        game.update(0.0);
+        assert_eq!(game.grid.get(0, 0), CellState::Dead);
+
+        game.apply(InputCommand::SelectTool(Tool::Toggle));
+        game.use_tool(Gesture::Click(empty));
+        game.update(0.0);
+        assert_eq!(game.grid.get(0, 0), CellState::Alive);
+
+        // A toggle drag starting on a live cell erases along the way
+        let to = screen_pos(&game, (1, 0));
+        game.grid.set(1, 0, CellState::Alive);
+        game.use_tool(Gesture::Drag { from: empty, to });
+        game.update(0.0);
+        assert_eq!(game.grid.get(0, 0), CellState::Dead);
+        assert_eq!(game.grid.get(1, 0), CellState::Dead);
+    }
+
+    #[test]
+    fn test_choosing_a_tool_leaves_paste_mode() {
+        let mut game = game_with("block", 10);
+        game.apply(InputCommand::SelectPattern(1));
+        assert_eq!(game.tool, Tool::Paste);
+
+        game.apply(InputCommand::SelectTool(Tool::Paint));
+        assert!(game.paste.is_none());
+        assert_eq!(game.tool, Tool::Paint);
+
+        game.apply(InputCommand::SelectPattern(1));
+        game.apply(InputCommand::Cancel);
+        assert_eq!(game.tool, Tool::default());
+    }
+
+    #[test]
+    fn test_tile_tool_repeats_the_clipboard_in_one_step() {
+        let mut game = game_with("block", 20);
+        game.state = State::Paused;
+        game.grid = conways::Grid::new(20, 20);
+        let region = conways::Region::from_corners((0, 0), (8, 8));
+        game.tile_region(region);
+        assert_eq!(game.grid.population(), 0);
+
+        game.grid.set(0, 0, CellState::Alive);
+        game.grid.set(1, 0, CellState::Alive);
+        game.select(conways::Region::from_corners((0, 0), (1, 0)));
+        // A copy every 3 columns on every row
+        game.tile_pitch = Some((3, 1));
+        game.tile_region(region);
+        assert_eq!(game.grid.population(), 9 * (2 + 2 + 2));
+        game.apply(InputCommand::Undo);
+        assert_eq!(game.grid.population(), 2);
+
+        // A pitch tighter than the pattern is refused
+        game.tile_pitch = Some((1, 1));
+        game.tile_region(region);
+        assert_eq!(game.grid.population(), 2);
+    }
+
+    #[test]
+    fn test_inspector_counts_neighbors_across_edges_in_use() {
+        let mut game = game_with("blinker", 10);
+        game.state = State::Paused;
+        game.grid = conways::Grid::new(10, 10);
+        for x in 4..=6 {
+            game.grid.set(x, 5, CellState::Alive);
+        }
+        assert_eq!(
+            game.describe_cell((5, 5)),
+            "(5, 5) alive, 2 live neighbors — blinker"
+        );
+        assert_eq!(game.describe_cell((5, 4)), "(5, 4) dead, 3 live neighbors");
+
+        game.grid.set(0, 0, CellState::Alive);
+        assert_eq!(game.describe_cell((9, 9)), "(9, 9) dead, 0 live neighbors");
+        game.apply(InputCommand::CycleBoundary);
+        game.update(0.0);
+        assert_eq!(game.describe_cell((9, 9)), "(9, 9) dead, 1 live neighbors");
+    }
+
+    #[test]
+    fn test_auto_pause_can_be_turned_off() {
+        let mut game = game_with("blinker", 10);
+        game.apply(InputCommand::ToggleAutoPause);
+        run_frames(&mut game, 5);
+        assert!(game.state == State::Running);
+    }
+
+    fn session_config() -> Config {
+        Config {
+            width: 40,
+            height: 40,
+            update_interval: 0.01,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_replay_reproduces_session() {
+        let config = session_config();
+        let start = game_with("R-pentomino", 40).grid;
+        let mut game = Game::new(&config, start.clone());
+        game.start_input_log(std::path::PathBuf::from("unused.replay"), &config);
+
+        let dts = [0.004, 0.013, 0.021, 0.007, 0.016];
+        for frame in 0..40 {
+            match frame {
+                3 => {
+                    game.edits.push(Command::Begin);
+                    game.edits.push(Command::Set {
+                        x: 2,
+                        y: 2,
+                        state: CellState::Alive,
+                    });
+                    game.edits.push(Command::Toggle { x: 3, y: 2 });
+                    game.edits.push(Command::End);
+                }
+                8 => game.apply(InputCommand::Randomize),
+                12 => game.select(conways::Region {
+                    x: 5,
+                    y: 5,
+                    width: 10,
+                    height: 8,
+                }),
+                13 => game.apply(InputCommand::FillNoise),
+                17 => {
+                    game.apply(InputCommand::SelectPattern(1));
+                    game.stamp_paste_at((20, 20), conways::InsertMode::Overwrite);
+                }
+                21 => game.flood_fill((0, 39)),
+                _ => (),
+            }
+            game.update(dts[frame % dts.len()]);
+        }
+        let (_, log) = game.input_log.take().unwrap();
+
+        let replay = Replay::parse(&log.to_text()).unwrap();
+        let mut replayed = Game::new(&config, start);
+        replayed.start_playback(replay, &config).unwrap();
+        while let Some(dt) = replayed.play_frame() {
+            replayed.update(dt);
+        }
+        assert!(replayed.playback.is_none());
+        assert_eq!(replayed.grid.generation(), game.grid.generation());
+        assert_eq!(replayed.grid.content_hash(), game.grid.content_hash());
+    }
+
+    #[test]
+    fn test_replay_needs_same_settings() {
+        let config = session_config();
+        let mut game = game_with("R-pentomino", 40);
+        game.start_input_log(std::path::PathBuf::from("unused.replay"), &config);
+        let (_, log) = game.input_log.take().unwrap();
+
+        let other = Config {
+            update_interval: 0.5,
+            ..session_config()
+        };
+        let mut replayed = game_with("R-pentomino", 40);
+        assert!(replayed.start_playback(log, &other).is_err());
+        assert!(replayed.playback.is_none());
+    }
+
+    #[test]
+    fn test_keyboard_cursor_edits_and_selects() {
+        let mut game = game_with("block", 10);
+        game.apply(InputCommand::TogglePause);
+        game.apply(InputCommand::ToggleCursor);
+        let (x, y) = game.cursor.as_ref().unwrap().position();
+        assert_eq!((x, y), (5, 5));
+
+        let before = game.grid.get(x, y);
+        game.use_cursor(CursorAction::Toggle, None);
+        game.update(0.0);
+        assert_ne!(game.grid.get(x, y), before);
+        game.apply(InputCommand::Undo);
+        assert_eq!(game.grid.get(x, y), before);
+
+        let extend = CursorAction::Move {
+            direction: (1, 1),
+            fast: false,
+            extend: true,
+        };
+        game.use_cursor(extend, None);
+        game.use_cursor(extend, None);
+        let selection = game.selection.as_ref().unwrap();
+        assert!(!selection.is_dragging());
+        assert_eq!(
+            selection.region(),
+            conways::Region::from_corners((5, 5), (7, 7))
+        );
+        assert!(game.clipboard.is_some());
+
+        game.apply(InputCommand::ToggleCursor);
+        assert!(game.cursor.is_none());
+    }
+
+    #[test]
+    fn test_clear_undoes_in_one_step() {
+        let mut game = game_with("Gosper glider gun", 60);
+        let before = game.grid.clone();
+        game.apply(InputCommand::Clear);
+        assert_eq!(game.grid.population(), 0);
+        game.apply(InputCommand::Undo);
+        assert_eq!(game.grid, before);
+    }
+
+    #[test]
+    fn test_cycle_rule_keeps_board() {
+        let mut game = game_with("R-pentomino", 40);
+        let before = game.grid.clone();
+        game.apply(InputCommand::CycleRule);
+        assert_eq!(game.rule.to_string(), "B36/S23");
+        assert_eq!(game.notice.text(), Some("Rule: HighLife (B36/S23)"));
+        assert_eq!(game.grid, before);
+        run_frames(&mut game, 1);
+        assert_eq!(game.grid.rule(), game.rule);
+
+        // The list wraps around
+        for _ in 1..rule::PRESETS.len() {
+            game.apply(InputCommand::CycleRule);
+        }
+        assert_eq!(game.rule, Rule::conway());
+    }
+
+    #[test]
+    fn test_rule_entry() {
+        let mut game = game_with("R-pentomino", 40);
+        game.apply(InputCommand::EnterRule);
+        for c in "B2/S9".chars() {
+            game.rule_entry_key(EntryKey::Char(c));
+        }
+        game.rule_entry_key(EntryKey::Enter);
+        // Invalid rules keep the entry open with the error
+        let entry = game.rule_entry.as_ref().unwrap();
+        assert_eq!(
+            entry.error(),
+            Some("'9' isn't a neighbor count from 0 to 8")
+        );
+        assert_eq!(game.rule, Rule::conway());
+
+        game.rule_entry_key(EntryKey::Backspace);
+        game.rule_entry_key(EntryKey::Enter);
+        assert!(game.rule_entry.is_none());
+        assert_eq!(game.rule.to_string(), "B2/S");
+
+        // Escape leaves the rule alone
+        game.apply(InputCommand::EnterRule);
+        game.rule_entry_key(EntryKey::Char('B'));
+        game.rule_entry_key(EntryKey::Escape);
+        assert!(game.rule_entry.is_none());
+        assert_eq!(game.rule.to_string(), "B2/S");
+    }
+
+    #[test]
+    fn test_cell_shape_is_remembered() {
+        let path = std::env::temp_dir().join(format!("conways-shape-{}.toml", std::process::id()));
+        std::fs::write(&path, "# Mine\nwidth = 90\n").unwrap();
+        let mut game = game_with("glider", 10);
+        game.settings_path = Some(path.clone());
+
+        game.apply(InputCommand::CycleCellShape);
+        assert_eq!(game.cell_shape, CellShape::Circle);
+        game.apply(InputCommand::CycleCellShape);
+        assert_eq!(game.cell_shape, CellShape::Rounded);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(text, "# Mine\nwidth = 90\ncell_shape = \"rounded\"\n");
+        let mut config = Config::default();
+        settings::apply_settings(&text, &mut config).unwrap();
+        assert_eq!(config.cell_shape, CellShape::Rounded);
+    }
+
+    #[test]
+    fn test_scrub_then_resume_truncates() {
+        let mut game = game_with("R-pentomino", 40);
+        run_frames(&mut game, 20);
+        assert_eq!(game.grid.generation(), 20);
+        let early = game_with("R-pentomino", 40);
+
+        game.scrub_to(0);
+        assert!(game.state == State::Paused);
+        assert_eq!(game.grid, early.grid);
+        game.scrub_to(12);
+        assert_eq!(game.grid.generation(), 12);
+        assert_eq!(game.timeline.len(), 21);
+
+        // Running again from there replaces the generations after it
+        game.apply(InputCommand::TogglePause);
+        run_frames(&mut game, 1);
+        assert_eq!(game.grid.generation(), 13);
+        assert_eq!(game.timeline.len(), 14);
+        assert_eq!(game.timeline.position(), 13);
+    }
+
+    #[test]
+    fn test_bookmarks_survive_running_and_undo() {
+        let mut game = game_with("R-pentomino", 40);
+        run_frames(&mut game, 5);
+        let bookmarked = game.grid.clone();
+        game.apply(InputCommand::SaveBookmark(2));
+
+        run_frames(&mut game, 10);
+        game.apply(InputCommand::RestoreBookmark(2));
+        assert!(game.state == State::Paused);
+        assert_eq!(game.grid, bookmarked);
+        assert_eq!(game.grid.generation(), 5);
+
+        // Running from the restored board leaves the bookmark as it was
+        game.apply(InputCommand::TogglePause);
+        run_frames(&mut game, 3);
+        game.apply(InputCommand::Undo);
+        game.apply(InputCommand::RestoreBookmark(2));
+        assert_eq!(game.grid, bookmarked);
+
+        // An empty slot changes nothing
+        let before = game.grid.clone();
+        game.apply(InputCommand::RestoreBookmark(7));
+        assert_eq!(game.grid, before);
+    }
+
+    #[test]
+    fn test_focus_loss_pauses() {
+        let mut game = game_with("R-pentomino", 40);
+        game.focus_changed(false);
+        assert!(game.state == State::Paused);
+        game.focus_changed(true);
+        assert!(game.state == State::Running);
+
+        // A pause by hand stays
+        game.apply(InputCommand::TogglePause);
+        game.focus_changed(false);
+        game.focus_changed(true);
+        assert!(game.state == State::Paused);
+
+        // Turned off in the config
+        let config = Config {
+            pause_on_focus_loss: false,
+            ..session_config()
+        };
+        let mut game = Game::new(&config, conways::Grid::new(10, 10));
+        game.focus_changed(false);
+        assert!(game.state == State::Running);
+    }
+
+    #[test]
+    fn test_reload_changed_pattern() {
+        let path = std::env::temp_dir().join(format!("conways-reload-{}.rle", std::process::id()));
+        std::fs::write(&path, "x = 3, y = 1\n3o!\n").unwrap();
+        let config = session_config();
+        let mut game = Game::new(&config, config.starting_grid().unwrap());
+        game.pattern_watch = Some(FileWatcher::new(path.clone()));
+        run_frames(&mut game, 5);
+        assert!(!game.reload_offer);
+
+        let touch = |seconds| {
+            let time = std::time::SystemTime::now() + std::time::Duration::from_secs(seconds);
+            let file = std::fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(time).unwrap();
+        };
+        let wait_for_change = |game: &mut Game| {
+            game.update(watch::POLL_INTERVAL);
+            assert!(game.reload_offer);
+        };
+
+        // A broken file keeps the board as it is
+        std::fs::write(&path, "x = 3, y = 1\n3z!\n").unwrap();
+        touch(10);
+        wait_for_change(&mut game);
+        let before = game.grid.clone();
+        game.apply(InputCommand::RestoreSession);
+        assert!(!game.reload_offer);
+        assert_eq!(game.grid, before);
+        assert!(game.notice.text().unwrap().starts_with("Could not load"));
+
+        // Fixed, it replaces the board, from generation 0 and paused
+        std::fs::write(&path, "x = 3, y = 1\n3o!\n").unwrap();
+        touch(20);
+        wait_for_change(&mut game);
+        game.apply(InputCommand::RestoreSession);
+        std::fs::remove_file(&path).unwrap();
+        assert!(game.state == State::Paused);
+        assert_eq!(game.grid.generation(), 0);
+        assert_eq!(game.grid.population(), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "scripting")]
+    fn test_rerunning_the_script() {
+        let path = std::env::temp_dir().join(format!("conways-rerun-{}.rhai", std::process::id()));
+        std::fs::write(&path, "for x in 0..3 { set(x, 5); }").unwrap();
+        let config = Config {
+            script: Some(path.clone()),
+            paused: true,
+            ..session_config()
+        };
+        let mut game = Game::new(&config, config.starting_grid().unwrap());
+        assert_eq!(game.grid.population(), 3);
+
+        // Read afresh, onto a cleared board, picking its rule
+        std::fs::write(&path, "rule(\"B36/S23\");\nset(9, 9);").unwrap();
+        game.apply(InputCommand::RerunScript);
+        assert_eq!(
+            (game.grid.population(), game.grid.get(9, 9)),
+            (1, CellState::Alive)
+        );
+        assert_eq!(game.rule.to_string(), "B36/S23");
+        game.apply(InputCommand::Undo);
+        assert_eq!(game.grid.population(), 3);
+
+        // A failing script keeps what it drew and says where it stopped
+        std::fs::write(&path, "set(1, 1);\nset(99, 0);").unwrap();
+        game.apply(InputCommand::RerunScript);
+        assert_eq!(
+            (game.grid.population(), game.grid.get(1, 1)),
+            (1, CellState::Alive)
+        );
+        assert_eq!(
+            game.notice.text(),
+            Some(&*format!(
+                "{}: script failed at line 2: (99, 0) is off the 40x40 board",
+                path.display()
+            ))
+        );
+
+        // One that doesn't parse leaves the board alone
+        std::fs::write(&path, "\nlet = 3;").unwrap();
+        game.apply(InputCommand::RerunScript);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(game.grid.population(), 1);
+        assert_eq!(
+            game.notice.text(),
+            Some(&*format!(
+                "{}: script parse error at line 2: expected a name",
+                path.display()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_immigration_match_plays_out() {
+        let mut game = game_with("blinker", 30);
+        game.apply(InputCommand::PlayMatch);
+        assert_eq!(game.grid.population(), 0);
+        for cell in [(5, 5), (6, 5), (5, 6), (6, 6)] {
+            game.claim(cell);
+        }
+        // Nothing runs during setup, even unpaused
+        game.apply(InputCommand::TogglePause);
+        run_frames(&mut game, 5);
+        assert_eq!(game.grid.generation(), 0);
+
+        game.apply(InputCommand::PlayMatch);
+        for cell in [(5, 5), (20, 20), (21, 20)] {
+            game.claim(cell);
+        }
+        assert_eq!(game.grid.population(), 6);
+        game.apply(InputCommand::PlayMatch);
+        // The block is stable, which doesn't end the match early
+        run_frames(&mut game, immigration::MATCH_GENERATIONS as usize + 10);
+        let result = game.immigration.as_ref().unwrap();
+        assert_eq!(result.phase(), Phase::Results);
+        assert_eq!(result.scores(&game.grid), [4, 0]);
+        assert_eq!(game.grid.generation(), immigration::MATCH_GENERATIONS);
+        assert!(game.state == State::Paused);
+
+        game.apply(InputCommand::PlayMatch);
+        assert!(game.immigration.is_none());
+    }
+
+    #[test]
+    fn test_puzzle_run_and_retry() {
+        let path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("puzzles/traffic-light.toml");
+        let mut game = game_with("glider", 40);
+        game.start_puzzle(Puzzle::load(&path).unwrap());
+        assert_eq!((game.grid.width(), game.grid.height()), (30, 30));
+        game.claim((14, 15));
+        // The budget is a single cell
+        game.claim((0, 0));
+        assert_eq!(game.grid.population(), 4);
+
+        game.apply(InputCommand::TogglePause);
+        assert!(game.state == State::Running);
+        run_frames(&mut game, 30);
+        let attempt = game.puzzle.as_ref().unwrap();
+        assert_eq!(attempt.stage(), Stage::Over(Outcome::Solved(9)));
+        assert_eq!(game.grid.generation(), 9);
+        assert!(game.state == State::Paused);
+
+        // Trying again starts over from the edited board
+        game.apply(InputCommand::TogglePause);
+        assert_eq!(game.puzzle.as_ref().unwrap().stage(), Stage::Setup);
+        assert_eq!(game.grid.generation(), 0);
+        assert_eq!(game.grid.population(), 4);
+    }
+
+    #[test]
+    fn test_inspector_names_hovered_objects_while_paused() {
+        let mut game = game_with("beehive", 12);
+        assert_eq!(game.describe_cell((4, 5)), "(4, 5) alive, 2 live neighbors");
+        game.state = State::Paused;
+        assert_eq!(
+            game.describe_cell((4, 5)),
+            "(4, 5) alive, 2 live neighbors — beehive"
+        );
+
+        // An unknown cluster shows its size instead
+        game.grid.set(8, 5, CellState::Alive);
+        assert!(game.describe_cell((4, 5)).ends_with(" — 7 cells"));
+    }
+
+    #[test]
+    fn test_measure_mode_pins_until_cancelled() {
+        let mut game = game_with("glider", 20);
+        game.apply(InputCommand::Measure);
+        assert!(game.measure.is_active());
+        game.measure.click((2, 3));
+        game.measure.click((8, 1));
+        assert_eq!(game.measure.pinned()[0].chebyshev(), 6);
+
+        // Leaving the mode keeps the measurement, Escape clears it
+        game.apply(InputCommand::Measure);
+        assert!(!game.measure.is_active());
+        assert_eq!(game.measure.pinned().len(), 1);
+        game.apply(InputCommand::Cancel);
+        assert!(game.measure.pinned().is_empty());
+    }
+
+    #[test]
+    fn test_moving_a_selection_undoes_in_one_step() {
+        let mut game = game_with("blinker", 12);
+        game.state = State::Paused;
+        let before = game.grid.clone();
+        let region = conways::Region::from_corners((4, 4), (6, 6));
+        game.select(region);
+        game.apply(InputCommand::FlipPasteVertical);
+        assert!(game.paste.as_ref().is_some_and(PendingPaste::is_move));
+        assert_eq!(game.grid.population(), 0);
+
+        game.stamp_paste_at((0, 0), conways::InsertMode::Or);
+        assert!(game.paste.is_none());
+        assert_eq!(game.grid.get(0, 1), CellState::Alive);
+        assert_eq!(game.grid.population(), 3);
+        game.apply(InputCommand::Undo);
+        assert_eq!(game.grid, before);
+    }
+
+    #[test]
+    fn test_cancelled_move_puts_cells_back() {
+        let mut game = game_with("blinker", 12);
+        game.state = State::Paused;
+        let before = game.grid.clone();
+        game.select(conways::Region::from_corners((4, 4), (6, 6)));
+        game.apply(InputCommand::FlipPasteVertical);
+        game.apply(InputCommand::Cancel);
+        assert_eq!(game.grid, before);
+        assert_eq!(game.tool, Tool::default());
+
+        // Undoing during a move puts the cells back before undoing what came before
+        game.grid.set(0, 0, CellState::Alive);
+        game.before_edit();
+        game.grid.set(11, 11, CellState::Alive);
+        game.select(conways::Region::from_corners((4, 4), (6, 6)));
+        game.apply(InputCommand::FlipPasteVertical);
+        game.apply(InputCommand::Undo);
+        assert!(game.paste.is_none());
+        assert_eq!(game.grid.population(), 4);
+        assert_eq!(game.grid.get(11, 11), CellState::Dead);
+    }
+
+    #[test]
+    fn test_fit_view_glides_to_the_live_cells() {
+        const SCREEN: (f32, f32) = (800.0, 600.0);
+        let mut game = game_with("glider", 50);
+        game.state = State::Paused;
+        game.apply(InputCommand::FitView);
+        game.move_camera(0.0, SCREEN);
+        // Arrives after the glide's duration, whatever the frame rate
+        for _ in 0..20 {
+            game.move_camera(1.0 / 60.0, SCREEN);
+        }
+        assert!(game.glide.is_none());
+        let glider = game.grid.live_bounds().unwrap();
+        let view = game.camera.viewport(SCREEN);
+        assert!(view.x <= glider.x as f32 && view.y <= glider.y as f32);
+        assert!(view.x + view.w >= (glider.x + glider.width) as f32);
+        assert_eq!(game.camera.cell_size, camera::MAX_CELL_SIZE);
+
+        // An empty board goes back to the starting view
+        game.apply(InputCommand::Clear);
+        game.apply(InputCommand::FitView);
+        game.move_camera(1.0, SCREEN);
+        assert_eq!(game.camera, game.home);
+    }
+
+    #[test]
+    fn test_slider_speed_is_clamped_and_shared_with_keys() {
+        let mut game = game_with("blinker", 10);
+        game.set_speed(20.0);
+        assert_eq!(game.update_interval, 0.05);
+        game.apply(InputCommand::SpeedUp);
+        assert_eq!(game.update_interval, 0.025);
+
+        game.set_speed(slider::MAX_SLIDER_SPEED * 10.0);
+        assert_eq!(game.update_interval, MIN_UPDATE_INTERVAL);
+        game.apply(InputCommand::SpeedUp);
+        assert_eq!(game.update_interval, MIN_UPDATE_INTERVAL);
+        game.set_speed(slider::MIN_SLIDER_SPEED / 10.0);
+        assert_eq!(game.update_interval, MAX_UPDATE_INTERVAL);
+    }
+
+    #[test]
+    fn test_preview_follows_edits_without_stepping() {
+        let mut game = game_with("blinker", 10);
+        game.state = State::Paused;
+        game.apply(InputCommand::TogglePreview);
+        game.update(0.0);
+        let births = |game: &Game| game.preview.as_ref().unwrap().births().len();
+        assert_eq!(births(&game), 2);
+        assert_eq!(game.grid.generation(), 0);
+
+        // Killing an end of the blinker leaves a domino that births nothing
+        let (x, y) = game.grid.live_bounds().map(|r| (r.x, r.y)).unwrap();
+        game.edits.push(Command::Begin);
+        game.edits.push(Command::Toggle { x, y });
+        game.edits.push(Command::End);
+        game.update(0.0);
+        assert_eq!(births(&game), 0);
+
+        game.apply(InputCommand::TogglePreview);
+        assert!(game.preview.is_none());
+    }
+
+    #[test]
+    fn test_onion_skin_shows_one_generation_back() {
+        let mut game = game_with("blinker", 10);
+        game.state = State::Paused;
+        game.apply(InputCommand::ToggleOnionSkin);
+        assert!(game.onion_skin().is_none());
+
+        let start = game.grid.clone();
+        game.apply(InputCommand::StepForward);
+        assert_eq!(game.onion_skin(), Some(&start));
+        // Stepping back only keeps the generation after the board
+        game.apply(InputCommand::StepBack);
+        assert!(game.onion_skin().is_none());
+        // Replaying a kept generation counts as a step
+        game.apply(InputCommand::StepForward);
+        assert_eq!(game.onion_skin(), Some(&start));
+
+        game.apply(InputCommand::Clear);
+        assert!(game.onion_skin().is_none());
+    }
+
+    #[test]
+    fn test_go_to_generation_both_ways() {
+        let mut game = game_with("glider", 30);
+        game.state = State::Paused;
+        for _ in 0..10 {
+            game.apply(InputCommand::StepForward);
+        }
+        let at_4 = {
+            let mut other = game_with("glider", 30);
+            other.state = State::Paused;
+            for _ in 0..4 {
+                other.apply(InputCommand::StepForward);
+            }
+            other.grid
+        };
+
+        // Kept generations are rewound to
+        game.apply(InputCommand::GoToGeneration);
+        for key in [EntryKey::Char('4'), EntryKey::Enter] {
+            game.goto_entry_key(key);
+        }
+        assert!(game.goto_entry.is_none());
+        assert_eq!(game.grid, at_4);
+        assert_eq!(game.grid.generation(), 4);
+
+        // Later ones are run to over as many frames as it takes
+        game.go_to(500);
+        while game.fast_forward.is_some() {
+            game.update(1.0 / 60.0);
+        }
+        assert_eq!(game.grid.generation(), 500);
+        assert!(game.state == State::Paused);
+        game.apply(InputCommand::Undo);
+        assert_eq!(game.grid.generation(), 10);
+    }
+
+    #[test]
+    fn test_every_backend_fast_forwards_alike() {
+        let boards: Vec<_> = Backend::ALL
+            .into_iter()
+            .map(|backend| {
+                let mut game = game_with("R-pentomino", 40);
+                game.backend = backend;
+                game.state = State::Paused;
+                game.go_to(300);
+                while game.fast_forward.is_some() {
+                    game.update(1.0 / 60.0);
+                }
+                assert_eq!(game.grid.generation(), 300, "{}", backend.label());
+                game.grid
+            })
+            .collect();
+        assert!(boards.iter().all(|board| *board == boards[0]));
+    }
+
+    #[test]
+    fn test_go_to_generation_errors_and_cancel() {
+        let mut game = game_with("glider", 30);
+        game.state = State::Paused;
+        game.timeline = Timeline::new(2);
+        for _ in 0..10 {
+            game.apply(InputCommand::StepForward);
+        }
+        game.go_to(3);
+        assert_eq!(game.grid.generation(), 10);
+        assert_eq!(
+            game.notice.text(),
+            Some("Generation 3 is no longer kept, the oldest one is 8")
+        );
+
+        game.apply(InputCommand::GoToGeneration);
+        for c in "12x".chars() {
+            game.goto_entry_key(EntryKey::Char(c));
+        }
+        game.goto_entry_key(EntryKey::Enter);
+        assert!(game.goto_entry.is_some());
+        game.goto_entry_key(EntryKey::Escape);
+        assert!(game.goto_entry.is_none());
+
+        game.go_to(u64::MAX);
+        game.update(1.0 / 60.0);
+        game.apply(InputCommand::Cancel);
+        assert!(game.fast_forward.is_none());
+        assert!(game.grid.generation() > 10);
+    }
+
+    #[test]
+    fn test_stamp_brush_spaces_copies_in_one_step() {
+        let mut game = game_with("blinker", 30);
+        game.grid = conways::Grid::new(30, 30);
+        game.apply(InputCommand::ToggleStampBrush);
+        game.apply(InputCommand::SelectPattern(1));
+        let size = {
+            let pattern = game.paste.as_ref().unwrap().pattern();
+            (pattern.width() as isize, pattern.height() as isize)
+        };
+
+        // A drag one cell at a time across the board
+        game.edits.push(Command::Begin);
+        for x in 5..5 + 3 * size.0 {
+            game.brush_at((x, 10), conways::InsertMode::Or);
+        }
+        game.edits.push(Command::End);
+        game.update(0.0);
+        let copies = game.grid.population() / game.paste.as_ref().unwrap().pattern().population();
+        assert_eq!(copies, 3);
+
+        game.apply(InputCommand::Undo);
+        assert_eq!(game.grid.population(), 0);
+    }
+
+    #[test]
+    fn test_cell_origins_follow_steps_and_timeline() {
+        let mut game = game_with("blinker", 10);
+        game.state = State::Paused;
+        game.apply(InputCommand::ToggleCellOrigins);
+        assert!(game.show_origins);
+        let painted: Vec<_> = (0..10)
+            .flat_map(|y| (0..10).map(move |x| (x, y)))
+            .filter(|&(x, y)| game.grid.get(x, y) == conways::CellState::Alive)
+            .collect();
+        assert!(painted.iter().all(|&(x, y)| !game.grid.is_born(x, y)));
+
+        game.apply(InputCommand::StepForward);
+        let born = (0..10)
+            .flat_map(|y| (0..10).map(move |x| (x, y)))
+            .filter(|&(x, y)| game.grid.is_born(x, y))
+            .count();
+        assert_eq!(born, 2);
+        // Replaying a kept generation brings the same cells back as born
+        game.apply(InputCommand::StepBack);
+        game.apply(InputCommand::StepForward);
+        let replayed = (0..10)
+            .flat_map(|y| (0..10).map(move |x| (x, y)))
+            .filter(|&(x, y)| game.grid.is_born(x, y))
+            .count();
+        assert_eq!(replayed, 2);
+    }
+
+    #[test]
+    fn test_seamless_wrap_maps_copies_to_the_board() {
+        let mut game = game_with("glider", 20);
+        game.camera = Camera {
+            origin: (-5.0, -2.0),
+            cell_size: 10.0,
+        };
+        // Dead edges leave the space around the board alone
+        assert_eq!(game.cell_at((0.0, 0.0)), None);
+        assert_eq!(game.clamped_cell_at((0.0, 0.0)), (0, 0));
+
+        game.apply(InputCommand::CycleBoundary);
+        assert!(game.seamless());
+        assert_eq!(game.cell_at((0.0, 0.0)), Some((15, 18)));
+        assert_eq!(game.clamped_cell_at((55.0, 25.0)), (0, 0));
+        // Far out on the copy beyond the bottom-right corner
+        assert_eq!(game.cell_at((305.0, 419.0)), Some((5, 19)));
+
+        game.apply(InputCommand::ToggleSeamlessWrap);
+        assert!(!game.seamless());
+        assert_eq!(game.cell_at((0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn test_speed_presets_toggle_back() {
+        let mut game = game_with("glider", 20);
+        game.update_interval = 0.25;
+        game.apply(InputCommand::SpeedPreset(2));
+        assert_eq!(game.update_interval, 0.05);
+        assert_eq!(
+            game.speed_presets
+                .label(1.0 / game.update_interval)
+                .as_deref(),
+            Some("2x (20 g/s)")
+        );
+        game.apply(InputCommand::SpeedPreset(4));
+        assert_eq!(game.update_interval, 1.0 / 80.0);
+        // The running preset again goes back to the speed before it
+        game.apply(InputCommand::SpeedPreset(4));
+        assert_eq!(game.update_interval, 0.05);
+        game.apply(InputCommand::SpeedPreset(2));
+        assert_eq!(game.update_interval, 0.05);
+
+        // Sliding to another speed leaves the preset, which jumps back to it
+        game.set_speed(5.0);
+        assert_eq!(game.speed_presets.label(1.0 / game.update_interval), None);
+        game.apply(InputCommand::SpeedPreset(1));
+        assert_eq!(game.update_interval, 0.1);
+        game.apply(InputCommand::SpeedPreset(1));
+        assert_eq!(game.update_interval, 0.2);
+    }
+
+    #[test]
+    fn test_edits_make_the_board_dirty() {
+        let mut game = game_with("glider", 20);
+        // Running generations changes nothing worth saving
+        run_frames(&mut game, 3);
+        assert!(!game.dirty);
+
+        game.edits.push(Command::Set {
+            x: 1,
+            y: 1,
+            state: CellState::Alive,
+        });
+        game.update(0.0);
+        assert!(game.dirty);
+
+        // Loading a file starts clean, randomizing and undoing make it dirty
+        let path = std::env::temp_dir().join(format!("conways-dirty-{}.cells", std::process::id()));
+        std::fs::write(&path, "O\n").unwrap();
+        game.load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(!game.dirty);
+        game.apply(InputCommand::Randomize);
+        assert!(game.dirty);
+        game.dirty = false;
+        game.apply(InputCommand::Undo);
+        assert!(game.dirty);
+        game.dirty = false;
+        game.apply(InputCommand::Grow(Edge::Right));
+        assert!(game.dirty);
+    }
+
+    #[test]
+    fn test_quit_asks_only_with_unsaved_changes() {
+        let mut game = game_with("glider", 20);
+        game.apply(InputCommand::Quit);
+        assert_eq!(game.quitting, Quitting::Yes);
+
+        let mut game = game_with("glider", 20);
+        game.apply(InputCommand::Clear);
+        game.apply(InputCommand::Quit);
+        assert_eq!(game.quitting, Quitting::Asking);
+        // Other keys leave the prompt up, Escape keeps playing
+        game.answer_quit(KeyCode::Q);
+        assert_eq!(game.quitting, Quitting::Asking);
+        game.answer_quit(KeyCode::Escape);
+        assert_eq!(game.quitting, Quitting::No);
+        assert!(game.dirty);
+
+        // Closing the window asks the same, and Y quits as is
+        game.request_quit();
+        assert_eq!(game.quitting, Quitting::Asking);
+        game.answer_quit(KeyCode::Y);
+        assert_eq!(game.quitting, Quitting::Yes);
+        // Answers only count while asking
+        game.answer_quit(KeyCode::Escape);
+        assert_eq!(game.quitting, Quitting::Yes);
+    }
+
+    #[test]
+    fn test_status_segments_follow_the_game() {
+        let mut game = game_with("glider", 20);
+        let texts = game.status_texts();
+        assert_eq!(texts[0], "Running");
+        assert_eq!(texts[1..3], ["Gen 0", "Pop 5"]);
+        assert!(texts.contains(&"Zoom: 100%".to_string()));
+        assert!(!texts.iter().any(|text| text.starts_with("Brush")));
+
+        // Each segment follows its own part of the game
+        run_frames(&mut game, 3);
+        game.state = State::Paused;
+        game.camera.cell_size *= 2.0;
+        let texts = game.status_texts();
+        assert_eq!(texts[0], "Paused");
+        assert_eq!(texts[1], format!("Gen {}", game.grid.generation()));
+        assert!(texts.contains(&"Zoom: 200%".to_string()));
+        assert!(texts.contains(&format!("Rule: {}", game.rule)));
+    }
+
+    #[test]
+    fn test_rle_round_trips_through_the_system_clipboard() {
+        let mut game = game_with("glider", 20);
+        let glider = game.grid.live_bounds().unwrap();
+        // With nothing selected, the board cropped to its live cells
+        let text = game.rle_to_copy().unwrap();
+        assert!(text.starts_with(&format!("x = 3, y = 3, rule = {}", game.rule)));
+        game.paste_rle(&text);
+        let pasted = game.paste.as_ref().unwrap().pattern().clone();
+        assert_eq!(pasted, *Clipboard::copy(&game.grid, glider).pattern());
+        assert_eq!(game.tool, Tool::Paste);
+        game.stop_pasting();
+
+        // A selection is copied whole, empty margins included
+        let margin = conways::Region {
+            x: glider.x - 1,
+            y: glider.y - 1,
+            width: glider.width + 2,
+            height: glider.height + 1,
+        };
+        game.select(margin);
+        game.paste_rle(&game.rle_to_copy().unwrap());
+        let pasted = game.paste.as_ref().unwrap().pattern();
+        assert_eq!((pasted.width(), pasted.height()), (5, 4));
+        assert_eq!(pasted.population(), 5);
+        game.stop_pasting();
+
+        // Text that isn't RLE leaves paste mode alone and says why
+        game.paste_rle("hello from another app");
+        assert!(game.paste.is_none());
+        assert!(game.notice.text().unwrap().contains("no RLE pattern"));
+
+        game.apply(InputCommand::Clear);
+        game.selection = None;
+        assert_eq!(game.rle_to_copy(), None);
+    }
+
+    #[test]
+    fn test_a_joined_game_shows_and_edits_the_hosts_board() {
+        let mut host = game_with("glider", 20);
+        let server = collab::Host::bind("127.0.0.1:0").unwrap();
+        let address = server.local_addr();
+        host.collab = Some(Collab::Host(Box::new(server)));
+        let mut guest = game_with("block", 10);
+        guest.collab = Some(Collab::Join(collab::Client::connect(address).unwrap()));
+        let mut frames = 0;
+        let mut run_until =
+            |host: &mut Game, guest: &mut Game, done: &dyn Fn(&Game, &Game) -> bool| {
+                while !done(host, guest) {
+                    frames += 1;
+                    assert!(frames < 2000, "the boards never matched");
+                    host.update(host.update_interval);
+                    guest.update(guest.update_interval);
+                    std::thread::sleep(std::time::Duration::from_millis(2));
+                }
+            };
+        let same = |host: &Game, guest: &Game| {
+            host.grid.content_hash() == guest.grid.content_hash()
+                && host.grid.generation() == guest.grid.generation()
+        };
+        // While the host runs the guest may always be a frame behind it
+        run_until(&mut host, &mut guest, &|_, guest| {
+            guest.grid.generation() > 5
+        });
+
+        // The guest's pause and painting happen on the host
+        guest.apply(InputCommand::TogglePause);
+        run_until(&mut host, &mut guest, &|host, guest| {
+            host.state == State::Paused && guest.state == State::Paused && same(host, guest)
+        });
+        guest.edits.push(Command::Toggle { x: 0, y: 0 });
+        run_until(&mut host, &mut guest, &|host, guest| {
+            host.grid.get(0, 0) == CellState::Alive && same(host, guest)
+        });
+        assert!(guest.edits.pending().is_empty());
+    }
+
+    #[test]
+    fn test_share_strings_paste_like_rle() {
+        let mut game = game_with("glider", 20);
+        let glider = game.grid.live_bounds().unwrap();
+        let shared = game.grid.to_share_string();
+        game.paste_rle(&format!("{shared}\n"));
+        let pasted = game.paste.as_ref().unwrap().pattern().clone();
+        assert_eq!(pasted, *Clipboard::copy(&game.grid, glider).pattern());
+        game.stop_pasting();
+
+        // A mangled one is refused as such, not read as RLE
+        game.paste_rle(&shared[..shared.len() - 2]);
+        assert!(game.paste.is_none());
+        assert!(game
+            .notice
+            .text()
+            .unwrap()
+            .contains("share string parse error"));
+    }
+
+    #[test]
+    fn test_recorded_run_plays_back_without_simulating() {
+        let path =
+            std::env::temp_dir().join(format!("conways-run-{}.lifereplay", std::process::id()));
+        let mut game = game_with("glider", 20);
+        let start = game.grid.clone();
+        game.start_run_log(path.clone());
+        run_frames(&mut game, 10);
+        game.state = State::Paused;
+        // An edit after the last generation is written along with it
+        game.grid.toggle(0, 0);
+        let recorded = game.grid.clone();
+        game.save_run_log();
+
+        let mut watcher = game_with("blinker", 8);
+        watcher.start_run_playback(RunRecording::load(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(watcher.grid, start);
+        assert_eq!(watcher.grid.generation(), 0);
+        assert_eq!(watcher.board_size, (20, 20));
+
+        // Running goes through the recorded boards, then stops at the last one
+        // A rule that would blow the glider up shows nothing is simulated
+        watcher.rule = "B2/S".parse().unwrap();
+        while watcher.state == State::Running || watcher.grid.generation() == 0 {
+            watcher.state = State::Running;
+            run_frames(&mut watcher, 5);
+        }
+        assert_eq!(watcher.grid.content_hash(), recorded.content_hash());
+        assert_eq!(watcher.notice.text(), Some("End of the recorded run"));
+
+        // Scrubbing back works the same as stepping back through history
+        watcher.scrub_to(3);
+        assert_eq!(watcher.grid.generation(), 3);
+        watcher.apply(InputCommand::StepForward);
+        assert_eq!(watcher.grid.generation(), 4);
+
+        // Changing the board leaves the run behind and simulates from there
+        watcher.grid.toggle(10, 10);
+        watcher.timeline.truncate();
+        watcher.apply(InputCommand::StepForward);
+        assert_eq!(watcher.grid.generation(), 5);
+        assert_eq!(watcher.run_end, None);
+    }
+
+    #[test]
+    fn test_reference_layer_stays_out_of_the_simulation() {
+        let mut game = game_with("blinker", 20);
+        game.apply(InputCommand::CommitReference);
+        assert_eq!(game.notice.text(), Some(NO_REFERENCE));
+
+        let mut glider = conways::Grid::new(3, 3);
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            glider.set(x, y, CellState::Alive);
+        }
+        game.reference = Some(ReferenceLayer::new(glider, (20, 20)));
+        run_frames(&mut game, 10);
+        assert_eq!(game.grid.population(), 3);
+        assert_eq!(game.reference.as_ref().unwrap().offset(), (8, 8));
+
+        game.apply(InputCommand::MoveReference);
+        assert!(game.moving_reference);
+        game.reference.as_mut().unwrap().nudge((-8, 0), (20, 20));
+        game.apply(InputCommand::TogglePause);
+        let before = game.grid.clone();
+        game.apply(InputCommand::CommitReference);
+        assert!(!game.moving_reference);
+        assert_eq!(game.grid.population(), 8);
+        assert_eq!(game.grid.get(1, 8), CellState::Alive);
+        assert!(game.dirty);
+        // The layer stays where it was, and the stamp undoes in one step
+        assert_eq!(game.reference.as_ref().unwrap().offset(), (0, 8));
+        game.apply(InputCommand::Undo);
+        assert_eq!(game.grid, before);
+
+        game.apply(InputCommand::CycleReference);
+        assert_eq!(
+            game.reference.as_ref().unwrap().placement(),
+            Placement::Below
+        );
+    }
+
+    #[test]
+    fn test_quick_save_slots_ask_before_losing_anything() {
+        let dir = std::env::temp_dir().join(format!("conways-slot-game-{}", std::process::id()));
+        let mut game = game_with("glider", 20);
+        game.apply(InputCommand::SaveSlot(1));
+        assert_eq!(game.notice.text(), Some(NO_SLOTS));
+
+        game.slots = Slots::new(Some(dir.join("session.txt")));
+        game.apply(InputCommand::TogglePause);
+        game.camera.cell_size = 7.0;
+        game.apply(InputCommand::SaveSlot(3));
+        assert!(game
+            .notice
+            .text()
+            .unwrap()
+            .starts_with("Saved slot 3: gen 0, pop 5"));
+        let saved = game.grid.clone();
+
+        // Overwriting asks first, and anything else in between calls it off
+        game.grid.toggle(0, 0);
+        game.dirty = true;
+        game.apply(InputCommand::SaveSlot(3));
+        assert!(game
+            .notice
+            .text()
+            .unwrap()
+            .contains("press again to overwrite"));
+        game.apply(InputCommand::ToggleGridLines);
+        game.apply(InputCommand::SaveSlot(3));
+        assert!(game.notice.text().unwrap().contains("press again"));
+        game.apply(InputCommand::SaveSlot(3));
+        assert_eq!(game.slots.info(3).unwrap().population, 6);
+        assert!(!game.dirty);
+
+        // Loading over unsaved edits asks first too
+        game.apply(InputCommand::LoadSlot(2));
+        assert_eq!(game.notice.text(), Some("Slot 2 is empty"));
+        game.grid = saved.clone();
+        game.camera.cell_size = 30.0;
+        game.dirty = true;
+        game.apply(InputCommand::LoadSlot(3));
+        assert!(game.notice.text().unwrap().contains("Unsaved changes"));
+        assert_eq!(game.grid, saved);
+        game.apply(InputCommand::LoadSlot(3));
+        assert_eq!(game.grid.population(), 6);
+        assert_eq!(game.camera.cell_size, 7.0);
+        assert!(!game.dirty);
+        // The load undoes like any other board replacement
+        game.apply(InputCommand::Undo);
+        assert_eq!(game.grid, saved);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_blinker_renders_to_exact_pixels() {
+        let game = game_with("blinker", 5);
+        let mut renderer = render::ImageRenderer::new(5, 5, 2, game.theme.background);
+        game.render_board(&mut renderer, (0..5, 0..5));
+        let image = renderer.into_image();
+        assert_eq!(image.dimensions(), (10, 10));
+
+        let alive = render::to_rgba(game.theme.alive);
+        let background = render::to_rgba(game.theme.background);
+        let mut lit = Vec::new();
+        for (x, y, pixel) in image.enumerate_pixels() {
+            if *pixel == alive {
+                lit.push((x, y));
+            } else {
+                assert_eq!(*pixel, background, "stray pixel at {x},{y}");
+            }
+        }
+        // The three cells of the row in the middle, two pixels a side
+        let expected: Vec<(u32, u32)> = (4..6).flat_map(|y| (2..8).map(move |x| (x, y))).collect();
+        assert_eq!(lit, expected);
     }
 }
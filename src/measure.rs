@@ -0,0 +1,190 @@
+use std::fmt;
+
+/// The distance between two cells, e.g. the offset between a glider and the
+/// target it has to hit
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Measurement {
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+}
+
+impl Measurement {
+    /// Columns from `from` to `to`, negative towards the left
+    pub fn dx(&self) -> isize {
+        self.to.0 as isize - self.from.0 as isize
+    }
+
+    /// Rows from `from` to `to`, negative upwards
+    pub fn dy(&self) -> isize {
+        self.to.1 as isize - self.from.1 as isize
+    }
+
+    /// Generations a signal at the speed of light takes between the cells
+    pub fn chebyshev(&self) -> usize {
+        self.dx().unsigned_abs().max(self.dy().unsigned_abs())
+    }
+
+    pub fn euclidean(&self) -> f64 {
+        (self.dx() as f64).hypot(self.dy() as f64)
+    }
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Δx {}, Δy {}, Chebyshev {}, Euclidean {:.2}",
+            self.dx(),
+            self.dy(),
+            self.chebyshev(),
+            self.euclidean()
+        )
+    }
+}
+
+/// Where the measure mode is at
+/// - `Off`: Clicks edit the board as usual
+/// - `First`: The next click picks the first cell
+/// - `Second`: The first cell is picked, the next click completes the measurement
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stage {
+    Off,
+    First,
+    Second((usize, usize)),
+}
+
+/// The measure mode: pairs of clicked cells and the measurements pinned so far
+///
+/// Measurements stay on screen after the mode is left, until cancelled.
+pub struct Measurer {
+    stage: Stage,
+    pinned: Vec<Measurement>,
+}
+
+impl Measurer {
+    pub fn new() -> Self {
+        Self {
+            stage: Stage::Off,
+            pinned: Vec::new(),
+        }
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    /// Whether clicks go to the measure mode
+    pub fn is_active(&self) -> bool {
+        self.stage != Stage::Off
+    }
+
+    pub fn pinned(&self) -> &[Measurement] {
+        &self.pinned
+    }
+
+    /// Enter the mode, or leave it, dropping a half-done measurement
+    pub fn toggle(&mut self) -> Stage {
+        self.stage = match self.stage {
+            Stage::Off => Stage::First,
+            _ => Stage::Off,
+        };
+        self.stage
+    }
+
+    /// Pick `cell` as the next end of a measurement
+    ///
+    /// # Returns
+    /// The measurement it completes, which is pinned, if it was the second end
+    pub fn click(&mut self, cell: (usize, usize)) -> Option<Measurement> {
+        match self.stage {
+            Stage::Off => None,
+            Stage::First => {
+                self.stage = Stage::Second(cell);
+                None
+            }
+            Stage::Second(from) => {
+                let measurement = Measurement { from, to: cell };
+                self.pinned.push(measurement);
+                self.stage = Stage::First;
+                Some(measurement)
+            }
+        }
+    }
+
+    /// Step back out of the mode: a pending first cell is dropped first, then
+    /// the pinned measurements go and the mode is left
+    ///
+    /// # Returns
+    /// Whether there was anything to cancel
+    pub fn cancel(&mut self) -> bool {
+        match self.stage {
+            Stage::Second(_) => self.stage = Stage::First,
+            _ if self.is_active() || !self.pinned.is_empty() => {
+                self.pinned.clear();
+                self.stage = Stage::Off;
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distances() {
+        let measurement = Measurement {
+            from: (10, 4),
+            to: (7, 8),
+        };
+        assert_eq!((measurement.dx(), measurement.dy()), (-3, 4));
+        assert_eq!(measurement.chebyshev(), 4);
+        assert_eq!(measurement.euclidean(), 5.0);
+        assert_eq!(
+            measurement.to_string(),
+            "Δx -3, Δy 4, Chebyshev 4, Euclidean 5.00"
+        );
+    }
+
+    #[test]
+    fn test_clicks_pin_pairs() {
+        let mut measurer = Measurer::new();
+        // Clicks are left alone outside the mode
+        assert_eq!(measurer.click((1, 1)), None);
+        assert_eq!(measurer.toggle(), Stage::First);
+
+        assert_eq!(measurer.click((1, 1)), None);
+        assert_eq!(measurer.stage(), Stage::Second((1, 1)));
+        let first = measurer.click((4, 5)).unwrap();
+        assert_eq!(first.chebyshev(), 4);
+        assert_eq!(measurer.stage(), Stage::First);
+
+        measurer.click((0, 0));
+        measurer.click((2, 0));
+        assert_eq!(measurer.pinned().len(), 2);
+
+        // Leaving keeps the pins on screen
+        assert_eq!(measurer.toggle(), Stage::Off);
+        assert_eq!(measurer.pinned().len(), 2);
+    }
+
+    #[test]
+    fn test_cancel_steps_back() {
+        let mut measurer = Measurer::new();
+        assert!(!measurer.cancel());
+        measurer.toggle();
+        measurer.click((1, 1));
+        measurer.click((2, 2));
+        measurer.click((3, 3));
+
+        assert!(measurer.cancel());
+        assert_eq!(measurer.stage(), Stage::First);
+        assert_eq!(measurer.pinned().len(), 1);
+        assert!(measurer.cancel());
+        assert_eq!(measurer.stage(), Stage::Off);
+        assert!(measurer.pinned().is_empty());
+        assert!(!measurer.cancel());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::camera::{Bounds, Camera};
+
+/// Largest side of the minimap on screen, in pixels
+pub const MINIMAP_SIZE: f32 = 160.0;
+
+/// Distance between the minimap and the screen edges, in pixels
+pub const MINIMAP_MARGIN: f32 = 10.0;
+
+/// Seconds between two refreshes of the density data shown by the minimap
+pub const MINIMAP_REFRESH: f32 = 0.25;
+
+/// Size of the density tiles, in cells, so the whole grid fits in `MINIMAP_SIZE` pixels
+pub fn tile_size(grid: (usize, usize)) -> usize {
+    let longest = grid.0.max(grid.1).max(1) as f32;
+    (longest / MINIMAP_SIZE).ceil().max(1.0) as usize
+}
+
+/// Where the minimap goes on screen: the top-right corner, keeping the grid's aspect ratio
+pub fn minimap_bounds(screen: (f32, f32), grid: (usize, usize)) -> Bounds {
+    let longest = grid.0.max(grid.1).max(1) as f32;
+    let scale = MINIMAP_SIZE / longest;
+    let (w, h) = (grid.0 as f32 * scale, grid.1 as f32 * scale);
+    Bounds {
+        x: screen.0 - w - MINIMAP_MARGIN,
+        y: MINIMAP_MARGIN,
+        w,
+        h,
+    }
+}
+
+/// The camera viewport drawn on the minimap, clipped to the minimap itself
+pub fn viewport_rect(
+    camera: &Camera,
+    screen: (f32, f32),
+    grid: (usize, usize),
+    minimap: Bounds,
+) -> Bounds {
+    let view = camera.viewport(screen);
+    let scale = minimap.w / grid.0.max(1) as f32;
+
+    let x0 = (view.x * scale).clamp(0.0, minimap.w);
+    let y0 = (view.y * scale).clamp(0.0, minimap.h);
+    let x1 = ((view.x + view.w) * scale).clamp(0.0, minimap.w);
+    let y1 = ((view.y + view.h) * scale).clamp(0.0, minimap.h);
+    Bounds {
+        x: minimap.x + x0,
+        y: minimap.y + y0,
+        w: x1 - x0,
+        h: y1 - y0,
+    }
+}
+
+/// The cell under a screen point inside the minimap, used to jump the camera there
+pub fn cell_at(point: (f32, f32), grid: (usize, usize), minimap: Bounds) -> Option<(f32, f32)> {
+    if !minimap.contains(point) {
+        return None;
+    }
+    let scale = minimap.w / grid.0.max(1) as f32;
+    Some(((point.0 - minimap.x) / scale, (point.1 - minimap.y) / scale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: (f32, f32) = (800.0, 600.0);
+
+    #[test]
+    fn test_tile_size() {
+        assert_eq!(tile_size((80, 60)), 1);
+        assert_eq!(tile_size((320, 100)), 2);
+        assert_eq!(tile_size((1000, 1000)), 7);
+    }
+
+    #[test]
+    fn test_minimap_keeps_aspect_ratio() {
+        let minimap = minimap_bounds(SCREEN, (200, 100));
+        assert_eq!((minimap.w, minimap.h), (160.0, 80.0));
+        assert_eq!((minimap.x, minimap.y), (630.0, 10.0));
+    }
+
+    #[test]
+    fn test_viewport_rect_follows_zoom() {
+        let grid = (320, 240);
+        let minimap = minimap_bounds(SCREEN, grid);
+        // Half a minimap pixel per cell
+        assert_eq!(minimap.w, 160.0);
+
+        let camera = Camera::new(10.0);
+        let rect = viewport_rect(&camera, SCREEN, grid, minimap);
+        assert_eq!((rect.w, rect.h), (40.0, 30.0));
+        assert_eq!((rect.x, rect.y), (minimap.x, minimap.y));
+
+        let mut zoomed_in = Camera::new(20.0);
+        zoomed_in.origin = (100.0, 50.0);
+        let rect = viewport_rect(&zoomed_in, SCREEN, grid, minimap);
+        assert_eq!((rect.w, rect.h), (20.0, 15.0));
+        assert_eq!((rect.x, rect.y), (minimap.x + 50.0, minimap.y + 25.0));
+    }
+
+    #[test]
+    fn test_viewport_rect_is_clipped() {
+        let grid = (80, 60);
+        let minimap = minimap_bounds(SCREEN, grid);
+
+        // Zoomed out so far the whole grid is visible with room to spare
+        let mut camera = Camera::new(2.0);
+        camera.origin = (-20.0, -20.0);
+        assert_eq!(viewport_rect(&camera, SCREEN, grid, minimap), minimap);
+    }
+
+    #[test]
+    fn test_click_maps_to_cell() {
+        let grid = (320, 240);
+        let minimap = minimap_bounds(SCREEN, grid);
+        assert_eq!(
+            cell_at((minimap.x + 80.0, minimap.y + 60.0), grid, minimap),
+            Some((160.0, 120.0))
+        );
+        assert_eq!(cell_at((0.0, 0.0), grid, minimap), None);
+    }
+}
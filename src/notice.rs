@@ -0,0 +1,51 @@
+/// How long a notice stays on screen, in seconds
+pub const NOTICE_DURATION: f32 = 3.0;
+
+/// A short message flashed on screen for a few seconds, e.g. "Saved to ..."
+pub struct Notice {
+    text: String,
+    remaining: f32,
+}
+
+impl Notice {
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+            remaining: 0.0,
+        }
+    }
+
+    /// Show a message, replacing the current one
+    pub fn show(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.remaining = NOTICE_DURATION;
+    }
+
+    /// Count down the time left on screen
+    pub fn update(&mut self, dt: f32) {
+        self.remaining = (self.remaining - dt).max(0.0);
+    }
+
+    /// The message to display, if it hasn't expired yet
+    pub fn text(&self) -> Option<&str> {
+        (self.remaining > 0.0).then_some(self.text.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notice_expires() {
+        let mut notice = Notice::new();
+        assert_eq!(notice.text(), None);
+
+        notice.show("Saved");
+        notice.update(NOTICE_DURATION / 2.0);
+        assert_eq!(notice.text(), Some("Saved"));
+
+        notice.update(NOTICE_DURATION);
+        assert_eq!(notice.text(), None);
+    }
+}
@@ -0,0 +1,55 @@
+/// What one generation did to the board, handed to `GridObserver::on_generation`
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Stats {
+    /// Live cells after the generation
+    pub population: usize,
+    /// Cells the generation brought to life
+    pub births: usize,
+    /// Cells the generation killed
+    pub deaths: usize,
+}
+
+/// Told about every generation a grid steps, for sound, logging or
+/// networking without touching the stepping itself; see `Grid::set_observer`
+///
+/// For each generation, `before_generation` comes first, then `on_birth` and
+/// `on_death` for every cell that changed, row by row, and `on_generation`
+/// last. Every method does nothing unless implemented.
+///
+/// # Example
+/// ```
+/// use game_of_life::conways::{CellState, Grid};
+/// use game_of_life::observer::{GridObserver, Stats};
+/// use std::sync::{Arc, Mutex};
+///
+/// /// Keeps the births of every generation
+/// struct Births(Arc<Mutex<Vec<usize>>>);
+///
+/// impl GridObserver for Births {
+///     fn on_generation(&mut self, _generation: u64, stats: &Stats) {
+///         self.0.lock().unwrap().push(stats.births);
+///     }
+/// }
+///
+/// let mut grid = Grid::new(5, 5);
+/// for x in 1..4 {
+///     grid.set(x, 2, CellState::Alive);
+/// }
+/// let births = Arc::new(Mutex::new(Vec::new()));
+/// grid.set_observer(Box::new(Births(Arc::clone(&births))));
+/// grid.step_n(3);
+/// assert_eq!(*births.lock().unwrap(), vec![2, 2, 2]);
+/// ```
+pub trait GridObserver: Send + Sync {
+    /// The grid is about to step to `generation`
+    fn before_generation(&mut self, _generation: u64) {}
+
+    /// The cell at `(x, y)` came to life
+    fn on_birth(&mut self, _x: usize, _y: usize) {}
+
+    /// The cell at `(x, y)` died
+    fn on_death(&mut self, _x: usize, _y: usize) {}
+
+    /// The grid stepped to `generation`, which did what `stats` says
+    fn on_generation(&mut self, _generation: u64, _stats: &Stats) {}
+}
@@ -0,0 +1,103 @@
+use crate::patterns::{Pattern, LIBRARY};
+
+/// Number of patterns reachable from the number keys on a single page
+pub const PAGE_SIZE: usize = 9;
+
+/// Maps Ctrl+Shift and the number keys 1-9 onto the built-in pattern library
+/// When the library holds more than `PAGE_SIZE` patterns they are split into
+/// pages, and the number keys select from the current page.
+pub struct Palette {
+    page: usize,
+    selected: Option<usize>,
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Self {
+            page: 0,
+            selected: None,
+        }
+    }
+
+    /// Total number of pages needed to show the whole library
+    pub fn page_count() -> usize {
+        LIBRARY.len().div_ceil(PAGE_SIZE)
+    }
+
+    /// The page the number keys currently select from, starting at 0
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    /// Move to the next page, wrapping back to the first one
+    pub fn next_page(&mut self) {
+        self.page = (self.page + 1) % Self::page_count();
+    }
+
+    /// Select the pattern bound to a number key on the current page
+    ///
+    /// # Arguments
+    /// * `key` - The number key pressed, from 1 to 9
+    ///
+    /// # Returns
+    /// The selected `Pattern`, or `None` if no pattern is bound to the key
+    pub fn select(&mut self, key: usize) -> Option<&'static Pattern> {
+        if !(1..=PAGE_SIZE).contains(&key) {
+            return None;
+        }
+        let index = self.page * PAGE_SIZE + key - 1;
+        let pattern = LIBRARY.get(index)?;
+        self.selected = Some(index);
+        Some(pattern)
+    }
+
+    /// The most recently selected pattern, if any
+    pub fn selected(&self) -> Option<&'static Pattern> {
+        self.selected.map(|index| &LIBRARY[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_page_maps_to_library_start() {
+        let mut palette = Palette::new();
+        assert_eq!(palette.select(1).map(|p| p.name), Some(LIBRARY[0].name));
+        assert_eq!(palette.select(9).map(|p| p.name), Some(LIBRARY[8].name));
+        assert_eq!(palette.selected().map(|p| p.name), Some(LIBRARY[8].name));
+    }
+
+    #[test]
+    fn test_second_page_offsets_by_page_size() {
+        let mut palette = Palette::new();
+        palette.next_page();
+        assert_eq!(palette.page(), 1);
+        assert_eq!(palette.select(1).map(|p| p.name), Some(LIBRARY[9].name));
+    }
+
+    #[test]
+    fn test_unbound_keys_select_nothing() {
+        let mut palette = Palette::new();
+        assert!(palette.select(0).is_none());
+        assert!(palette.select(10).is_none());
+
+        // The last page is only partially filled
+        for _ in 1..Palette::page_count() {
+            palette.next_page();
+        }
+        let free_slot = LIBRARY.len() % PAGE_SIZE + 1;
+        assert!(palette.select(free_slot).is_none());
+        assert!(palette.selected().is_none());
+    }
+
+    #[test]
+    fn test_pages_wrap_around() {
+        let mut palette = Palette::new();
+        for _ in 0..Palette::page_count() {
+            palette.next_page();
+        }
+        assert_eq!(palette.page(), 0);
+    }
+}
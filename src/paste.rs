@@ -0,0 +1,292 @@
+use crate::conways::{Grid, InsertMode, Region};
+use crate::history::History;
+
+/// A pattern waiting to be stamped onto the board while in paste mode
+/// It can be rotated and flipped before being placed, and is positioned
+/// centered under the cursor so transforming it doesn't make it jump around.
+///
+/// A selection being moved is a paste too, one lifted off the board: it
+/// keeps the cell it was grabbed by under the cursor until transformed, and
+/// is stamped only once.
+pub struct PendingPaste {
+    pattern: Grid,
+    /// Cell of the pattern under the cursor, its center when `None`
+    grab: Option<(usize, usize)>,
+    /// Where a moved selection came from and its cells before any
+    /// transform, to put it back on cancel
+    lifted: Option<(Region, Grid)>,
+}
+
+impl PendingPaste {
+    pub fn new(pattern: Grid) -> Self {
+        Self {
+            pattern,
+            grab: None,
+            lifted: None,
+        }
+    }
+
+    /// Lift the cells of `region` off `grid`, to move them elsewhere
+    ///
+    /// This opens a transaction of `history`, so the lift and the drop by
+    /// `drop_move` undo as one step, and cancelling with `put_back` leaves no
+    /// step at all. Generations running meanwhile split it, as for any
+    /// transaction.
+    ///
+    /// # Arguments
+    /// * `grab` - The cell held by the cursor, relative to the region
+    pub fn lift(
+        grid: &mut Grid,
+        history: &mut History,
+        region: Region,
+        grab: Option<(usize, usize)>,
+    ) -> Self {
+        history.begin(grid);
+        let pattern = grid.extract_region(region);
+        grid.clear_region(region);
+        Self {
+            pattern: pattern.clone(),
+            grab,
+            lifted: Some((region, pattern)),
+        }
+    }
+
+    /// Whether this is a selection being moved rather than a copy
+    pub fn is_move(&self) -> bool {
+        self.lifted.is_some()
+    }
+
+    /// Stamp a moved selection with its top-left corner at `(x, y)`, closing
+    /// the transaction `lift` opened
+    /// Cells falling off the board are lost, as for any stamp.
+    pub fn drop_move(
+        self,
+        grid: &mut Grid,
+        history: &mut History,
+        (x, y): (isize, isize),
+        mode: InsertMode,
+    ) {
+        grid.insert_pattern(&self.pattern, x, y, mode);
+        history.end(grid);
+    }
+
+    /// Put a moved selection back where it was lifted from, as it was
+    /// Copies have nothing to put back.
+    pub fn put_back(self, grid: &mut Grid, history: &mut History) {
+        if let Some((region, cells)) = self.lifted {
+            let (x, y) = (region.x as isize, region.y as isize);
+            grid.insert_pattern(&cells, x, y, InsertMode::Overwrite);
+            history.end(grid);
+        }
+    }
+
+    /// The pattern as it will be stamped, transforms applied
+    pub fn pattern(&self) -> &Grid {
+        &self.pattern
+    }
+
+    /// Rotate the pending pattern 90° clockwise
+    pub fn rotate_cw(&mut self) {
+        self.pattern = self.pattern.rotate_cw();
+        self.grab = None;
+    }
+
+    /// Mirror the pending pattern left to right
+    pub fn flip_horizontal(&mut self) {
+        self.pattern = self.pattern.flip_horizontal();
+        self.grab = None;
+    }
+
+    /// Mirror the pending pattern top to bottom
+    pub fn flip_vertical(&mut self) {
+        self.pattern = self.pattern.flip_vertical();
+        self.grab = None;
+    }
+
+    /// Top-left cell of the pattern when its bounding box is centered on
+    /// `cursor`, or when the grabbed cell is under it
+    /// The result may lie outside the grid when the cursor is near an edge
+    ///
+    /// # Arguments
+    /// * `cursor` - The cell under the cursor
+    pub fn origin(&self, cursor: (isize, isize)) -> (isize, isize) {
+        let (gx, gy) = self
+            .grab
+            .unwrap_or((self.pattern.width() / 2, self.pattern.height() / 2));
+        (cursor.0 - gx as isize, cursor.1 - gy as isize)
+    }
+}
+
+/// Whether a stamp brush dragged to `origin` lays down another copy of a
+/// `size` pattern, the last copy being at `last`
+///
+/// Copies need the cursor to have moved by at least the pattern's width or
+/// height since the last one, so a slow drag leaves a row of separate copies
+/// instead of smearing them into one blob. The first copy of a drag always goes.
+pub fn brush_spaced(
+    last: Option<(isize, isize)>,
+    origin: (isize, isize),
+    size: (usize, usize),
+) -> bool {
+    last.is_none_or(|(x, y)| {
+        origin.0.abs_diff(x) >= size.0.max(1) || origin.1.abs_diff(y) >= size.1.max(1)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::CellState;
+
+    /// An L-tromino in a 2x3 box, which looks different under every transform
+    fn l_shape() -> Grid {
+        let mut grid = Grid::new(2, 3);
+        grid.set(0, 0, CellState::Alive);
+        grid.set(0, 1, CellState::Alive);
+        grid.set(0, 2, CellState::Alive);
+        grid.set(1, 2, CellState::Alive);
+        grid
+    }
+
+    #[test]
+    fn test_four_rotations_round_trip() {
+        let mut paste = PendingPaste::new(l_shape());
+        for _ in 0..4 {
+            paste.rotate_cw();
+        }
+        assert_eq!(paste.pattern(), &l_shape());
+    }
+
+    #[test]
+    fn test_double_flip_is_half_turn() {
+        let mut flipped = PendingPaste::new(l_shape());
+        flipped.flip_horizontal();
+        flipped.flip_vertical();
+
+        let mut rotated = PendingPaste::new(l_shape());
+        rotated.rotate_cw();
+        rotated.rotate_cw();
+
+        assert_eq!(flipped.pattern(), rotated.pattern());
+    }
+
+    #[test]
+    fn test_flip_composes_with_rotation() {
+        // Rotating then mirroring left to right transposes the pattern,
+        // just like mirroring top to bottom and then rotating
+        let mut a = PendingPaste::new(l_shape());
+        a.rotate_cw();
+        a.flip_horizontal();
+
+        let mut b = PendingPaste::new(l_shape());
+        b.flip_vertical();
+        b.rotate_cw();
+
+        assert_eq!(a.pattern(), b.pattern());
+        assert_eq!(a.pattern().get(2, 0), CellState::Alive);
+        assert_eq!(a.pattern().get(2, 1), CellState::Alive);
+    }
+
+    #[test]
+    fn test_rotation_stays_centered_on_cursor() {
+        let mut paste = PendingPaste::new(Grid::new(3, 1));
+        assert_eq!(paste.origin((10, 10)), (9, 10));
+
+        // The middle cell is still under the cursor once the bar is vertical
+        paste.rotate_cw();
+        assert_eq!(paste.origin((10, 10)), (10, 9));
+    }
+
+    /// A 10x10 board with a blinker in the region (1, 1) to (3, 3), its
+    /// history holding a single step
+    fn board_with_blinker() -> (Grid, History, Region) {
+        let mut grid = Grid::new(10, 10);
+        let mut history = History::new();
+        history.record(&grid);
+        for x in 1..=3 {
+            grid.set(x, 2, CellState::Alive);
+        }
+        (grid, history, Region::from_corners((1, 1), (3, 3)))
+    }
+
+    #[test]
+    fn test_move_undoes_in_one_step() {
+        let (mut grid, mut history, region) = board_with_blinker();
+        let before = grid.clone();
+        let lifted = PendingPaste::lift(&mut grid, &mut history, region, Some((0, 1)));
+        assert_eq!(grid.population(), 0);
+        assert!(lifted.is_move());
+
+        // Grabbed by its left end, dropped with that end on (6, 7)
+        let origin = lifted.origin((6, 7));
+        assert_eq!(origin, (6, 6));
+        lifted.drop_move(&mut grid, &mut history, origin, InsertMode::Or);
+        assert_eq!(grid.population(), 3);
+        assert_eq!(grid.get(8, 7), CellState::Alive);
+
+        assert!(history.undo(&mut grid));
+        assert_eq!(grid, before);
+        assert!(history.undo(&mut grid));
+        assert_eq!(grid.population(), 0);
+        assert!(!history.undo(&mut grid));
+    }
+
+    #[test]
+    fn test_cancelled_move_restores_cells_without_a_step() {
+        let (mut grid, mut history, region) = board_with_blinker();
+        let before = grid.clone();
+        let mut lifted = PendingPaste::lift(&mut grid, &mut history, region, None);
+        // Transforms don't change what is put back
+        lifted.rotate_cw();
+        lifted.put_back(&mut grid, &mut history);
+        assert_eq!(grid, before);
+
+        // The only undo step is the one recorded before the move
+        assert!(history.undo(&mut grid));
+        assert_eq!(grid.population(), 0);
+        assert!(!history.undo(&mut grid));
+    }
+
+    #[test]
+    fn test_move_off_the_board_clips() {
+        let (mut grid, mut history, region) = board_with_blinker();
+        let lifted = PendingPaste::lift(&mut grid, &mut history, region, None);
+        lifted.drop_move(&mut grid, &mut history, (8, 0), InsertMode::Or);
+        // Only the left end of the blinker is still on the board
+        assert_eq!(grid.population(), 2);
+        assert_eq!(grid.get(9, 1), CellState::Alive);
+        history.undo(&mut grid);
+        assert_eq!(grid.population(), 3);
+    }
+
+    #[test]
+    fn test_generation_during_move_splits_its_step() {
+        let (mut grid, mut history, region) = board_with_blinker();
+        let lifted = PendingPaste::lift(&mut grid, &mut history, region, None);
+        history.step(&mut grid);
+        lifted.drop_move(&mut grid, &mut history, (5, 5), InsertMode::Or);
+        // Undo takes back the drop, then the generation, then the lift
+        history.undo(&mut grid);
+        assert_eq!((grid.population(), grid.generation()), (0, 1));
+        history.undo(&mut grid);
+        assert_eq!((grid.population(), grid.generation()), (0, 0));
+        history.undo(&mut grid);
+        assert_eq!(grid.population(), 3);
+    }
+
+    #[test]
+    fn test_brush_spacing() {
+        let glider = (3, 3);
+        assert!(brush_spaced(None, (5, 5), glider));
+        // Less than a pattern away, in any direction, overlaps the last copy
+        assert!(!brush_spaced(Some((5, 5)), (5, 5), glider));
+        assert!(!brush_spaced(Some((5, 5)), (7, 3), glider));
+        assert!(!brush_spaced(Some((5, 5)), (3, 7), glider));
+        // A pattern's width or height away along one axis is enough
+        assert!(brush_spaced(Some((5, 5)), (8, 5), glider));
+        assert!(brush_spaced(Some((5, 5)), (5, 2), glider));
+        // Wide patterns need wider steps sideways only
+        assert!(!brush_spaced(Some((0, 0)), (5, 0), (6, 1)));
+        assert!(brush_spaced(Some((0, 0)), (0, 1), (6, 1)));
+    }
+}
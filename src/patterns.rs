@@ -0,0 +1,436 @@
+use crate::conways::Grid;
+use crate::formats::rle;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use {
+    crate::conways::CellState,
+    std::{collections::HashMap, sync::OnceLock},
+};
+
+/// Longest period `identify` recognizes every phase of; objects with longer
+/// periods, or that never settle, are only known in the phase they are stored in
+pub const MAX_IDENTIFIED_PERIOD: u64 = 15;
+
+/// A named pattern from the built-in library, stored as RLE text
+#[derive(Debug, PartialEq)]
+pub struct Pattern {
+    pub name: &'static str,
+    rle: &'static str,
+}
+
+impl Pattern {
+    /// Build a grid just large enough to hold the pattern
+    ///
+    /// # Example
+    /// ```
+    /// use game_of_life::patterns::LIBRARY;
+    ///
+    /// let glider = LIBRARY[0].to_grid();
+    /// ```
+    pub fn to_grid(&self) -> Grid {
+        rle::decode(self.rle).expect("built-in patterns are valid RLE")
+    }
+
+    /// The name as typed on the command line, e.g. `gosper-glider-gun`
+    pub fn slug(&self) -> String {
+        words(self.name).join("-")
+    }
+}
+
+/// Look up a library pattern by name, ignoring case, dashes and spaces
+///
+/// Words may be left out as long as the ones given are in order and only
+/// one pattern matches, so `gosper-gun` finds the Gosper glider gun.
+///
+/// # Example
+/// ```
+/// use game_of_life::patterns::find;
+///
+/// assert_eq!(find("r_pentomino").unwrap().name, "R-pentomino");
+/// ```
+pub fn find(name: &str) -> Option<&'static Pattern> {
+    let query = words(name);
+    if let Some(pattern) = LIBRARY.iter().find(|pattern| words(pattern.name) == query) {
+        return Some(pattern);
+    }
+
+    let mut matches = LIBRARY.iter().filter(|pattern| {
+        let mut names = words(pattern.name).into_iter();
+        !query.is_empty() && query.iter().all(|word| names.any(|name| name == *word))
+    });
+    match (matches.next(), matches.next()) {
+        (Some(pattern), None) => Some(pattern),
+        _ => None,
+    }
+}
+
+/// The name of the object made of `cells`, a connected group of live cells
+///
+/// Objects are recognized whatever their position, rotation or reflection,
+/// and in every phase for oscillators and spaceships up to
+/// `MAX_IDENTIFIED_PERIOD`. Known objects are the library plus a few common
+/// still lifes that aren't in it.
+///
+/// # Example
+/// ```
+/// use game_of_life::patterns::identify;
+///
+/// // A vertical blinker
+/// assert_eq!(identify(&[(4, 1), (4, 2), (4, 3)]), Some("blinker"));
+/// ```
+///
+/// Needs the `std` feature, for the table of known objects it builds once.
+#[cfg(feature = "std")]
+pub fn identify(cells: &[(usize, usize)]) -> Option<&'static str> {
+    static LEXICON: OnceLock<HashMap<Vec<(usize, usize)>, &'static str>> = OnceLock::new();
+    let lexicon = LEXICON.get_or_init(|| {
+        let mut lexicon = HashMap::new();
+        for pattern in LIBRARY.iter().chain(STILL_LIFES) {
+            for phase in phases(&pattern.to_grid()) {
+                lexicon.entry(phase).or_insert(pattern.name);
+            }
+        }
+        lexicon
+    });
+    lexicon.get(&canonical(cells)).copied()
+}
+
+/// The objects on `grid` and how many of each, the most common first and
+/// equally common ones by name, unknown ones before the rest
+///
+/// Every group of live cells touching through edges or corners, up to the
+/// borders of the grid like `Grid::component`, is one object, named by
+/// `identify` or `None` when it isn't a known one.
+///
+/// # Example
+/// ```
+/// use game_of_life::conways::Grid;
+/// use game_of_life::formats::rle;
+/// use game_of_life::patterns::census;
+///
+/// // Two blocks and a blinker
+/// let grid: Grid = rle::decode("x = 12, y = 3\n2o3b2o3bo$2o3b2o3bo$10bo!").unwrap();
+/// assert_eq!(census(&grid), vec![(Some("block"), 2), (Some("blinker"), 1)]);
+/// ```
+///
+/// Needs the `std` feature, like `identify`.
+#[cfg(feature = "std")]
+pub fn census(grid: &Grid) -> Vec<(Option<&'static str>, usize)> {
+    let (width, height) = (grid.width(), grid.height());
+    let mut seen = vec![false; width * height];
+    let mut counts: HashMap<Option<&'static str>, usize> = HashMap::new();
+    for (x, y) in live_cells(grid) {
+        if seen[y * width + x] {
+            continue;
+        }
+        seen[y * width + x] = true;
+        let (mut cells, mut stack) = (Vec::new(), vec![(x, y)]);
+        while let Some((x, y)) = stack.pop() {
+            cells.push((x, y));
+            for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+                for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                    if grid.get(nx, ny) == CellState::Alive && !seen[ny * width + nx] {
+                        seen[ny * width + nx] = true;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+        }
+        *counts.entry(identify(&cells)).or_default() += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_unstable_by(|(a, m), (b, n)| n.cmp(m).then(a.cmp(b)));
+    counts
+}
+
+/// The canonical form of every phase of `pattern`, the first one alone when it
+/// doesn't come back to its shape within `MAX_IDENTIFIED_PERIOD` generations
+#[cfg(feature = "std")]
+fn phases(pattern: &Grid) -> Vec<Vec<(usize, usize)>> {
+    // Room for a spaceship to travel through its whole period
+    let margin = 2 * MAX_IDENTIFIED_PERIOD as usize;
+    let mut grid = Grid::centered(
+        pattern,
+        pattern.width() + 2 * margin,
+        pattern.height() + 2 * margin,
+    );
+    let mut phases = vec![canonical(&live_cells(&grid))];
+    for _ in 0..MAX_IDENTIFIED_PERIOD {
+        grid.next_cell_generation();
+        let phase = canonical(&live_cells(&grid));
+        if phase == phases[0] {
+            return phases;
+        }
+        phases.push(phase);
+    }
+    phases.truncate(1);
+    phases
+}
+
+#[cfg(feature = "std")]
+fn live_cells(grid: &Grid) -> Vec<(usize, usize)> {
+    let mut cells = Vec::new();
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            if grid.get(x, y) == CellState::Alive {
+                cells.push((x, y));
+            }
+        }
+    }
+    cells
+}
+
+/// `cells` moved to the origin, in whichever rotation or reflection sorts
+/// first, so that two copies of the same shape have the same form
+pub fn canonical(cells: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // Four quarter turns, each with and without a mirror
+    let orient = |turn: usize, (x, y): (isize, isize)| {
+        let (x, y) = if turn >= 4 { (-x, y) } else { (x, y) };
+        match turn % 4 {
+            0 => (x, y),
+            1 => (-y, x),
+            2 => (-x, -y),
+            _ => (y, -x),
+        }
+    };
+    (0..8)
+        .map(|turn| {
+            let moved: Vec<_> = cells
+                .iter()
+                .map(|&(x, y)| orient(turn, (x as isize, y as isize)))
+                .collect();
+            let left = moved.iter().map(|cell| cell.0).min().unwrap_or(0);
+            let top = moved.iter().map(|cell| cell.1).min().unwrap_or(0);
+            let mut form: Vec<_> = moved
+                .iter()
+                .map(|&(x, y)| ((x - left) as usize, (y - top) as usize))
+                .collect();
+            form.sort_unstable();
+            form
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+/// The lowercase words of a name, split at spaces, dashes and underscores
+fn words(name: &str) -> Vec<String> {
+    name.split([' ', '-', '_'])
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Built-in patterns, in the order they are offered by the palette
+pub const LIBRARY: &[Pattern] = &[
+    Pattern {
+        name: "glider",
+        rle: "x = 3, y = 3\nbo$2bo$3o!",
+    },
+    Pattern {
+        name: "LWSS",
+        rle: "x = 5, y = 4\nbo2bo$o$o3bo$4o!",
+    },
+    Pattern {
+        name: "pulsar",
+        rle: "x = 13, y = 13\n2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+    },
+    Pattern {
+        name: "Gosper glider gun",
+        rle: "x = 36, y = 9\n24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+    },
+    Pattern {
+        name: "R-pentomino",
+        rle: "x = 3, y = 3\nb2o$2o$bo!",
+    },
+    Pattern {
+        name: "acorn",
+        rle: "x = 7, y = 3\nbo$3bo$2o2b3o!",
+    },
+    Pattern {
+        name: "diehard",
+        rle: "x = 8, y = 3\n6bo$2o$bo3b3o!",
+    },
+    Pattern {
+        name: "pentadecathlon",
+        rle: "x = 10, y = 3\n2bo4bo$2ob4ob2o$2bo4bo!",
+    },
+    Pattern {
+        name: "blinker",
+        rle: "x = 3, y = 1\n3o!",
+    },
+    Pattern {
+        name: "toad",
+        rle: "x = 4, y = 2\nb3o$3o!",
+    },
+    Pattern {
+        name: "beacon",
+        rle: "x = 4, y = 4\n2o$2o$2b2o$2b2o!",
+    },
+    Pattern {
+        name: "block",
+        rle: "x = 2, y = 2\n2o$2o!",
+    },
+    Pattern {
+        name: "beehive",
+        rle: "x = 4, y = 3\nb2o$o2bo$b2o!",
+    },
+];
+
+/// Common still lifes known to `identify` besides the library
+#[cfg(feature = "std")]
+const STILL_LIFES: &[Pattern] = &[
+    Pattern {
+        name: "loaf",
+        rle: "x = 4, y = 4\nb2o$o2bo$bobo$2bo!",
+    },
+    Pattern {
+        name: "boat",
+        rle: "x = 3, y = 3\n2o$obo$bo!",
+    },
+    Pattern {
+        name: "ship",
+        rle: "x = 3, y = 3\n2o$obo$b2o!",
+    },
+    Pattern {
+        name: "tub",
+        rle: "x = 3, y = 3\nbo$obo$bo!",
+    },
+    Pattern {
+        name: "pond",
+        rle: "x = 4, y = 4\nb2o$o2bo$o2bo$b2o!",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::CellState;
+
+    #[test]
+    fn test_glider_cells() {
+        let glider = LIBRARY[0].to_grid();
+        assert_eq!((glider.width(), glider.height()), (3, 3));
+        assert_eq!(glider.get(1, 0), CellState::Alive);
+        assert_eq!(glider.get(2, 1), CellState::Alive);
+        assert_eq!(glider.get(0, 2), CellState::Alive);
+        assert_eq!(glider.get(0, 0), CellState::Dead);
+        assert_eq!(glider.population(), 5);
+    }
+
+    #[test]
+    fn test_find_by_name() {
+        let name = |query: &str| find(query).map(|pattern| pattern.name);
+        assert_eq!(name("glider"), Some("glider"));
+        assert_eq!(name("lwss"), Some("LWSS"));
+        assert_eq!(name("R-Pentomino"), Some("R-pentomino"));
+        assert_eq!(name("r_pentomino"), Some("R-pentomino"));
+        assert_eq!(name("gosper glider gun"), Some("Gosper glider gun"));
+        assert_eq!(name("gosper-gun"), Some("Gosper glider gun"));
+        // An exact name wins over one it is part of
+        assert_eq!(name("GLIDER"), Some("glider"));
+
+        // Words out of order, or not in any name
+        assert_eq!(name("gun-gosper"), None);
+        assert_eq!(name("spaceship"), None);
+        assert_eq!(name(""), None);
+        assert_eq!(name("--"), None);
+    }
+
+    #[test]
+    fn test_slugs_find_their_pattern() {
+        assert_eq!(LIBRARY[3].slug(), "gosper-glider-gun");
+        for pattern in LIBRARY {
+            assert_eq!(find(&pattern.slug()), Some(pattern));
+        }
+    }
+
+    #[test]
+    fn test_library_dimensions_and_populations() {
+        let expected = [
+            ("glider", 3, 3, 5),
+            ("LWSS", 5, 4, 9),
+            ("pulsar", 13, 13, 48),
+            ("Gosper glider gun", 36, 9, 36),
+            ("R-pentomino", 3, 3, 5),
+            ("acorn", 7, 3, 7),
+            ("diehard", 8, 3, 7),
+            ("pentadecathlon", 10, 3, 12),
+            ("blinker", 3, 1, 3),
+            ("toad", 4, 2, 6),
+            ("beacon", 4, 4, 8),
+            ("block", 2, 2, 4),
+            ("beehive", 4, 3, 6),
+        ];
+        assert_eq!(LIBRARY.len(), expected.len());
+
+        for (pattern, (name, width, height, population)) in LIBRARY.iter().zip(expected) {
+            let grid = pattern.to_grid();
+            assert_eq!(pattern.name, name);
+            assert_eq!((grid.width(), grid.height()), (width, height), "{name}");
+            assert_eq!(grid.population(), population, "{name}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_identify_in_any_orientation() {
+        // A beehive standing upright, far from the origin
+        let beehive = [(10, 20), (9, 21), (11, 21), (9, 22), (11, 22), (10, 23)];
+        assert_eq!(identify(&beehive), Some("beehive"));
+        assert_eq!(identify(&[(4, 1), (4, 2), (4, 3)]), Some("blinker"));
+        assert_eq!(
+            identify(&[(0, 0), (1, 0), (0, 1), (2, 1), (1, 2)]),
+            Some("boat")
+        );
+        assert_eq!(identify(&[(0, 0), (1, 0)]), None);
+        assert_eq!(identify(&[]), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_census_counts_every_object() {
+        let mut grid = Grid::new(40, 20);
+        for (pattern, x, y) in [("glider", 1, 1), ("block", 10, 1), ("block", 20, 10)] {
+            let pattern = find(pattern).unwrap().to_grid();
+            grid.insert_pattern(&pattern, x, y, crate::conways::InsertMode::Overwrite);
+        }
+        // A domino, which is no known object, and a blinker touching the edge
+        grid.set(30, 1, CellState::Alive);
+        grid.set(31, 1, CellState::Alive);
+        for y in 17..20 {
+            grid.set(39, y, CellState::Alive);
+        }
+        assert_eq!(
+            census(&grid),
+            vec![
+                (Some("block"), 2),
+                (None, 1),
+                (Some("blinker"), 1),
+                (Some("glider"), 1)
+            ]
+        );
+        assert!(census(&Grid::new(0, 0)).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_identify_every_phase() {
+        let mut grid = Grid::centered(&find("glider").unwrap().to_grid(), 20, 20);
+        for _ in 0..4 {
+            grid.next_cell_generation();
+            assert_eq!(identify(&live_cells(&grid)), Some("glider"));
+        }
+        let mut toad = Grid::centered(&find("toad").unwrap().to_grid(), 8, 8);
+        toad.next_cell_generation();
+        assert_eq!(identify(&live_cells(&toad)), Some("toad"));
+    }
+
+    #[test]
+    fn test_canonical_ignores_position_and_reflection() {
+        let l = [(5, 5), (5, 6), (6, 6)];
+        let mirrored = [(1, 0), (0, 1), (1, 1)];
+        assert_eq!(canonical(&l), canonical(&mirrored));
+        assert_eq!(canonical(&l), vec![(0, 0), (0, 1), (1, 0)]);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::camera::Bounds;
+use crate::conways::{CellState, Grid};
+
+/// Rows and columns copied across each edge of a wrapping board by default
+pub const DEFAULT_PHANTOM_WIDTH: usize = 3;
+
+/// Widest strip `phantom_width` accepts, in cells
+pub const MAX_PHANTOM_WIDTH: usize = 16;
+
+/// Opacity of the copies, as a share of the live cell color's
+pub const PHANTOM_ALPHA: f32 = 0.35;
+
+/// Live cells of the strips just outside a wrapping board, as world coordinates
+///
+/// On a torus the cells past the right edge are the leftmost columns, and so
+/// on for every edge and corner; drawing them ghosted shows a glider crossing
+/// the seam instead of vanishing. Only the ring `width` cells wide around the
+/// board is looked at, skipping what lies outside `view`, so the cost follows
+/// the perimeter and not the area. The width is capped at the board's sides,
+/// past which the copies would repeat.
+pub fn phantom_cells(grid: &Grid, width: usize, view: Bounds) -> Vec<(isize, isize)> {
+    let (w, h) = (grid.width() as isize, grid.height() as isize);
+    let pw = width.min(grid.width()).min(grid.height()) as isize;
+    let mut cells = Vec::new();
+    if pw == 0 {
+        return cells;
+    }
+    // The visible part of the ring's bounding box
+    let clip = |start: f32, len: f32, max: isize| {
+        let first = (start.floor() as isize).max(-pw);
+        let last = ((start + len).ceil() as isize).min(max + pw);
+        first..last
+    };
+    let (xs, ys) = (clip(view.x, view.w, w), clip(view.y, view.h, h));
+    for y in ys {
+        // Rows crossing the board only have their two strips to visit
+        let (left, right) = if (0..h).contains(&y) {
+            (xs.start..xs.end.min(0), xs.start.max(w)..xs.end)
+        } else {
+            (xs.clone(), 0..0)
+        };
+        for x in left.chain(right) {
+            if grid.get_wrapped(x, y) == CellState::Alive {
+                cells.push((x, y));
+            }
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A view showing the whole board and a wide margin around it
+    fn everything(grid: &Grid) -> Bounds {
+        Bounds {
+            x: -50.0,
+            y: -50.0,
+            w: grid.width() as f32 + 100.0,
+            h: grid.height() as f32 + 100.0,
+        }
+    }
+
+    #[test]
+    fn test_edge_cells_show_across_the_seam() {
+        let mut grid = Grid::new(10, 8);
+        grid.set(0, 4, CellState::Alive);
+        grid.set(9, 7, CellState::Alive);
+        // Far from every edge, never copied
+        grid.set(5, 4, CellState::Alive);
+        let mut cells = phantom_cells(&grid, 2, everything(&grid));
+        cells.sort();
+        assert_eq!(
+            cells,
+            // The corner cell shows past three edges, the other one past one
+            vec![(-1, -1), (-1, 7), (9, -1), (10, 4)]
+        );
+    }
+
+    #[test]
+    fn test_width_limits_strips() {
+        let mut grid = Grid::new(10, 10);
+        grid.set(2, 5, CellState::Alive);
+        assert!(phantom_cells(&grid, 2, everything(&grid)).is_empty());
+        assert_eq!(phantom_cells(&grid, 3, everything(&grid)), vec![(12, 5)]);
+        assert!(phantom_cells(&grid, 0, everything(&grid)).is_empty());
+        // Never wider than the board, where copies would overlap
+        assert_eq!(phantom_cells(&grid, 100, everything(&grid)).len(), 8);
+    }
+
+    #[test]
+    fn test_hidden_strips_are_skipped() {
+        let mut grid = Grid::new(10, 10);
+        grid.set(0, 0, CellState::Alive);
+        let view = Bounds {
+            x: 5.0,
+            y: 5.0,
+            w: 20.0,
+            h: 20.0,
+        };
+        assert_eq!(phantom_cells(&grid, 2, view), vec![(10, 10)]);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::conways::{CellState, Grid};
+use macroquad::color::Color;
+
+/// Outline of a cell about to be born
+pub const BIRTH_COLOR: Color = Color::new(0.2, 0.9, 0.3, 0.9);
+
+/// Mark on a cell about to die
+pub const DEATH_COLOR: Color = Color::new(0.95, 0.2, 0.2, 0.8);
+
+/// What the next generation changes on a paused board, to show over it
+///
+/// Worked out from a copy, never stepping the board itself. The copy and the
+/// next generation are kept between refreshes, so following edits one after
+/// the other reuses their memory instead of allocating boards.
+pub struct Preview {
+    /// The board the changes were worked out for
+    seen: Grid,
+    next: Grid,
+    births: Vec<(usize, usize)>,
+    deaths: Vec<(usize, usize)>,
+    /// Whether `seen` holds a board yet
+    fresh: bool,
+}
+
+impl Preview {
+    pub fn new() -> Self {
+        Self {
+            seen: Grid::new(0, 0),
+            next: Grid::new(0, 0),
+            births: Vec::new(),
+            deaths: Vec::new(),
+            fresh: false,
+        }
+    }
+
+    /// Bring the changes up to date with `grid`, if it changed since last time
+    ///
+    /// # Returns
+    /// Whether they had to be worked out again
+    pub fn refresh(&mut self, grid: &Grid) -> bool {
+        // Grids compare by their cells only, the rule and edges matter here too
+        let same = self.fresh
+            && self.seen == *grid
+            && self.seen.rule() == grid.rule()
+            && self.seen.boundary() == grid.boundary();
+        if same {
+            return false;
+        }
+        self.seen.clone_from(grid);
+        self.fresh = true;
+        grid.next_generation_into(&mut self.next);
+        self.births.clear();
+        self.deaths.clear();
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                match (grid.get(x, y), self.next.get(x, y)) {
+                    (CellState::Dead, CellState::Alive) => self.births.push((x, y)),
+                    (CellState::Alive, CellState::Dead) => self.deaths.push((x, y)),
+                    _ => (),
+                }
+            }
+        }
+        true
+    }
+
+    /// Dead cells that come alive in the next generation
+    pub fn births(&self) -> &[(usize, usize)] {
+        &self.births
+    }
+
+    /// Live cells that die in the next generation
+    pub fn deaths(&self) -> &[(usize, usize)] {
+        &self.deaths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::BoundaryCondition;
+
+    fn blinker() -> Grid {
+        let mut grid = Grid::new(5, 5);
+        for x in 1..4 {
+            grid.set(x, 2, CellState::Alive);
+        }
+        grid
+    }
+
+    #[test]
+    fn test_blinker_changes() {
+        let grid = blinker();
+        let mut preview = Preview::new();
+        assert!(preview.refresh(&grid));
+        assert_eq!(preview.births(), [(2, 1), (2, 3)]);
+        assert_eq!(preview.deaths(), [(1, 2), (3, 2)]);
+        // The board itself is left alone
+        assert_eq!(grid, blinker());
+        assert_eq!(grid.generation(), 0);
+    }
+
+    #[test]
+    fn test_refreshes_only_after_changes() {
+        let mut grid = blinker();
+        let mut preview = Preview::new();
+        preview.refresh(&grid);
+        assert!(!preview.refresh(&grid));
+
+        // An edit turns the blinker into a dying domino
+        grid.set(3, 2, CellState::Dead);
+        assert!(preview.refresh(&grid));
+        assert!(preview.births().is_empty());
+        assert_eq!(preview.deaths().len(), 2);
+
+        // So does a change of edges, with the same cells
+        grid.set_boundary(BoundaryCondition::Wrap);
+        assert!(preview.refresh(&grid));
+    }
+}
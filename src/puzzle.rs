@@ -0,0 +1,513 @@
+use crate::bindings::strip_comment;
+use crate::conways::{CellState, Grid};
+use crate::error::ConwayError;
+use crate::formats::{self, FormatError};
+use crate::rule::Rule;
+use crate::settings;
+use std::fmt;
+use std::path::Path;
+
+/// Most generations a puzzle may run for
+pub const MAX_PUZZLE_GENERATIONS: u64 = 100_000;
+
+/// What the board has to look like for a puzzle to be solved
+/// - `Alive`: The cell at `(x, y)` is alive
+/// - `AtLeast`/`AtMost`: The population is at least or at most the count
+/// - `Match`: The cells of the pattern's bounding box, put with its top-left
+///   corner at `(x, y)`, are exactly the pattern's, dead ones included
+#[derive(Clone, Debug, PartialEq)]
+pub enum Goal {
+    Alive { x: usize, y: usize },
+    AtLeast(usize),
+    AtMost(usize),
+    Match { x: usize, y: usize, pattern: Grid },
+}
+
+impl Goal {
+    /// Read a goal line of a puzzle file:
+    /// - `alive X,Y`
+    /// - `population >= N` or `population <= N`
+    /// - `match FILE at X,Y`, where `load` reads the pattern `FILE`
+    pub fn parse(
+        text: &str,
+        load: impl Fn(&str) -> Result<Grid, ConwayError>,
+    ) -> Result<Goal, String> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let count = |n: &str| {
+            n.parse()
+                .map_err(|_| format!("invalid population '{n}', expected a cell count"))
+        };
+        match words.as_slice() {
+            ["alive", cell] => {
+                let (x, y) = parse_cell(cell)?;
+                Ok(Goal::Alive { x, y })
+            }
+            ["population", ">=", n] => Ok(Goal::AtLeast(count(n)?)),
+            ["population", "<=", n] => Ok(Goal::AtMost(count(n)?)),
+            ["match", file, "at", cell] => {
+                let (x, y) = parse_cell(cell)?;
+                let pattern = load(file).map_err(|err| format!("can't load {file}: {err}"))?;
+                Ok(Goal::Match { x, y, pattern })
+            }
+            _ => Err(format!(
+                "invalid goal '{text}', expected 'alive X,Y', 'population >= N', \
+                 'population <= N' or 'match FILE at X,Y'"
+            )),
+        }
+    }
+
+    /// Whether `grid` meets the goal
+    pub fn met(&self, grid: &Grid) -> bool {
+        match self {
+            Goal::Alive { x, y } => grid.get(*x, *y) == CellState::Alive,
+            Goal::AtLeast(count) => grid.population() >= *count,
+            Goal::AtMost(count) => grid.population() <= *count,
+            Goal::Match { x, y, pattern } => {
+                x + pattern.width() <= grid.width()
+                    && y + pattern.height() <= grid.height()
+                    && grid.extract_region(crate::conways::Region {
+                        x: *x,
+                        y: *y,
+                        width: pattern.width(),
+                        height: pattern.height(),
+                    }) == *pattern
+            }
+        }
+    }
+}
+
+impl fmt::Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Goal::Alive { x, y } => write!(f, "cell ({x}, {y}) alive"),
+            Goal::AtLeast(count) => write!(f, "at least {count} live cells"),
+            Goal::AtMost(count) => write!(f, "at most {count} live cells"),
+            Goal::Match { x, y, .. } => write!(f, "the target pattern at ({x}, {y})"),
+        }
+    }
+}
+
+/// `X,Y` as a cell
+fn parse_cell(text: &str) -> Result<(usize, usize), String> {
+    text.split_once(',')
+        .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+        .ok_or_else(|| format!("invalid cell '{text}', expected X,Y"))
+}
+
+/// A board to change with a limited number of edits so that, once run, it
+/// meets a goal within a generation limit
+#[derive(Clone, Debug, PartialEq)]
+pub struct Puzzle {
+    pub name: String,
+    pub description: String,
+    /// The board the player starts editing, also setting the board size
+    pub start: Grid,
+    /// Cells the player may change from `start`, adding or removing them
+    pub budget: usize,
+    /// The goal has to be met at one of the generations from 1 to this one
+    pub generations: u64,
+    pub goal: Goal,
+    pub rule: Rule,
+}
+
+/// How a run of a puzzle ended
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    /// The goal was met at this generation
+    Solved(u64),
+    /// The generation limit was reached without meeting the goal
+    Failed,
+}
+
+impl Puzzle {
+    /// Read a puzzle file
+    ///
+    /// The file uses the `conways.toml` subset of TOML:
+    /// ```toml
+    /// name = "First flight"
+    /// description = "Get a cell to (20, 20)"
+    /// start = "first-flight.rle"
+    /// budget = 5
+    /// generations = 80
+    /// goal = "alive 20,20"
+    /// rule = "B3/S23"
+    /// ```
+    /// `start` and the pattern of a `match` goal are pattern files, read
+    /// with `load`. `description` and `rule` are optional, Conway's rule is the
+    /// default.
+    ///
+    /// # Returns
+    /// The puzzle, or a `FormatError` for malformed lines, unknown or missing
+    /// keys, and pattern files that can't be loaded
+    pub fn parse(
+        input: &str,
+        load: impl Fn(&str) -> Result<Grid, ConwayError>,
+    ) -> Result<Puzzle, FormatError> {
+        let mut name = None;
+        let mut description = String::new();
+        let mut start = None;
+        let mut budget = None;
+        let mut generations = None;
+        let mut goal = None;
+        let mut rule = Rule::conway();
+        let mut last_line = 0;
+
+        for (i, line) in input.lines().enumerate() {
+            let line_number = i + 1;
+            last_line = line_number;
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| FormatError::new(line_number, "expected 'key = value'"))?;
+            let key = key.trim();
+            let value = settings::parse_value(value.trim())
+                .map_err(|msg| FormatError::new(line_number, msg))?;
+            let fail = |expected: &str| {
+                FormatError::new(
+                    line_number,
+                    format!("expected {expected} for '{key}', found {}", value.kind()),
+                )
+            };
+            let invalid = |msg: String| FormatError::new(line_number, msg);
+            let text = || settings::string(&value).ok_or_else(|| fail("a string"));
+
+            match key {
+                "name" => name = Some(text()?.to_string()),
+                "description" => description = text()?.to_string(),
+                "start" => {
+                    let file = text()?;
+                    start = Some(
+                        load(file).map_err(|err| invalid(format!("can't load {file}: {err}")))?,
+                    );
+                }
+                "budget" => {
+                    let cells = settings::integer(&value).ok_or_else(|| fail("an integer"))?;
+                    budget = Some(
+                        usize::try_from(cells)
+                            .map_err(|_| invalid("'budget' can't be negative".to_string()))?,
+                    );
+                }
+                "generations" => {
+                    let count = settings::integer(&value).ok_or_else(|| fail("an integer"))?;
+                    generations = Some(
+                        u64::try_from(count)
+                            .ok()
+                            .filter(|count| (1..=MAX_PUZZLE_GENERATIONS).contains(count))
+                            .ok_or_else(|| {
+                                invalid(format!(
+                                    "'generations' must be 1 to {MAX_PUZZLE_GENERATIONS}"
+                                ))
+                            })?,
+                    );
+                }
+                "goal" => goal = Some(Goal::parse(text()?, &load).map_err(invalid)?),
+                "rule" => {
+                    let text = text()?;
+                    rule = text
+                        .parse()
+                        .map_err(|err| invalid(format!("invalid rule '{text}': {err}")))?;
+                }
+                _ => return Err(invalid(format!("unknown key '{key}'"))),
+            }
+        }
+
+        let missing = |key: &str| FormatError::new(last_line, format!("missing '{key}'"));
+        Ok(Puzzle {
+            name: name.ok_or_else(|| missing("name"))?,
+            description,
+            start: start.ok_or_else(|| missing("start"))?,
+            budget: budget.ok_or_else(|| missing("budget"))?,
+            generations: generations.ok_or_else(|| missing("generations"))?,
+            goal: goal.ok_or_else(|| missing("goal"))?,
+            rule,
+        })
+    }
+
+    /// Read a puzzle file from disk, with the patterns it names next to it
+    pub fn load(path: &Path) -> Result<Puzzle, ConwayError> {
+        let text = std::fs::read_to_string(path)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        Puzzle::parse(&text, |file| formats::load_file(&dir.join(file)))
+            .map_err(|err| ConwayError::parse("puzzle", err))
+    }
+
+    /// The starting board, ready to run by the puzzle's rule
+    pub fn board(&self) -> Grid {
+        let mut grid = self.start.clone();
+        grid.set_rule(self.rule);
+        grid.set_generation(0);
+        grid
+    }
+
+    /// Cells of `board` that differ from the starting board
+    pub fn edits(&self, board: &Grid) -> usize {
+        let mut count = 0;
+        for y in 0..self.start.height() {
+            for x in 0..self.start.width() {
+                if board.get(x, y) != self.start.get(x, y) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// Run `board`, an edited starting board, to its outcome
+    /// The same check the game makes while the player watches.
+    pub fn evaluate(&self, board: &Grid) -> Outcome {
+        let mut attempt = Attempt::new(self.clone());
+        let mut grid = board.clone();
+        grid.set_rule(self.rule);
+        attempt.start(&grid);
+        loop {
+            grid.next_cell_generation();
+            if let Some(outcome) = attempt.observe(&grid) {
+                return outcome;
+            }
+        }
+    }
+}
+
+/// Where an attempt at a puzzle is at
+/// - `Setup`: The player edits the starting board, within the budget
+/// - `Running`: The edited board runs until the goal is met or the limit is reached
+/// - `Over`: The run ended with an outcome
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stage {
+    Setup,
+    Running,
+    Over(Outcome),
+}
+
+/// The player's go at a puzzle, which can be retried from the edited board
+#[derive(Clone, Debug)]
+pub struct Attempt {
+    puzzle: Puzzle,
+    stage: Stage,
+    /// The edited board the current run started from
+    setup: Option<Grid>,
+}
+
+impl Attempt {
+    pub fn new(puzzle: Puzzle) -> Self {
+        Self {
+            puzzle,
+            stage: Stage::Setup,
+            setup: None,
+        }
+    }
+
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    /// Edits still allowed on `board`
+    pub fn edits_left(&self, board: &Grid) -> usize {
+        self.puzzle.budget.saturating_sub(self.puzzle.edits(board))
+    }
+
+    /// Flip the cell at `(x, y)` during setup, if the budget allows
+    /// Flipping an edited cell back is always allowed, and refunds the edit.
+    ///
+    /// # Returns
+    /// Whether the cell changed
+    pub fn toggle(&self, board: &mut Grid, x: usize, y: usize) -> bool {
+        if self.stage != Stage::Setup || x >= board.width() || y >= board.height() {
+            return false;
+        }
+        let edited = board.get(x, y) != self.puzzle.start.get(x, y);
+        if !edited && self.edits_left(board) == 0 {
+            return false;
+        }
+        board.toggle(x, y);
+        true
+    }
+
+    /// Start running `board`
+    ///
+    /// # Returns
+    /// `false`, without starting, when the board has more edits than the
+    /// budget allows, e.g. after editing it some other way
+    pub fn start(&mut self, board: &Grid) -> bool {
+        if self.stage != Stage::Setup || self.puzzle.edits(board) > self.puzzle.budget {
+            return false;
+        }
+        self.setup = Some(board.clone());
+        self.stage = Stage::Running;
+        true
+    }
+
+    /// Look at the board after a generation of the run
+    ///
+    /// # Returns
+    /// The outcome, once the run is over
+    pub fn observe(&mut self, board: &Grid) -> Option<Outcome> {
+        if self.stage != Stage::Running {
+            return None;
+        }
+        let generation = board.generation();
+        let outcome = if generation > 0 && self.puzzle.goal.met(board) {
+            Outcome::Solved(generation)
+        } else if generation >= self.puzzle.generations {
+            Outcome::Failed
+        } else {
+            return None;
+        };
+        self.stage = Stage::Over(outcome);
+        Some(outcome)
+    }
+
+    /// Go back to setup with the board the last run started from, so the
+    /// player can change their edits
+    pub fn retry(&mut self) -> Grid {
+        self.stage = Stage::Setup;
+        self.setup.take().unwrap_or_else(|| self.puzzle.board())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::rle;
+    use std::path::PathBuf;
+
+    fn shipped(name: &str) -> Puzzle {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("puzzles")
+            .join(name);
+        Puzzle::load(&path).unwrap()
+    }
+
+    /// The starting board of `puzzle` with the cells at `cells` flipped
+    fn edited(puzzle: &Puzzle, cells: &[(usize, usize)]) -> Grid {
+        let mut board = puzzle.board();
+        let attempt = Attempt::new(puzzle.clone());
+        for &(x, y) in cells {
+            assert!(attempt.toggle(&mut board, x, y));
+        }
+        board
+    }
+
+    #[test]
+    fn test_shipped_puzzles_have_solutions() {
+        let flight = shipped("first-flight.toml");
+        let glider = [(3, 2), (4, 3), (2, 4), (3, 4), (4, 4)];
+        assert_eq!(
+            flight.evaluate(&edited(&flight, &glider)),
+            Outcome::Solved(64)
+        );
+
+        let crowd = shipped("crowd.toml");
+        assert!(matches!(
+            crowd.evaluate(&edited(&crowd, &[(32, 30)])),
+            Outcome::Solved(_)
+        ));
+
+        let light = shipped("traffic-light.toml");
+        assert_eq!(
+            light.evaluate(&edited(&light, &[(14, 15)])),
+            Outcome::Solved(9)
+        );
+    }
+
+    #[test]
+    fn test_null_edit_fails() {
+        for name in ["first-flight.toml", "crowd.toml", "traffic-light.toml"] {
+            let puzzle = shipped(name);
+            assert_eq!(puzzle.evaluate(&puzzle.board()), Outcome::Failed, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_budget_limits_edits() {
+        let crowd = shipped("crowd.toml");
+        let mut attempt = Attempt::new(crowd.clone());
+        let mut board = crowd.board();
+        assert!(attempt.toggle(&mut board, 0, 0));
+        assert_eq!(attempt.edits_left(&board), 0);
+        assert!(!attempt.toggle(&mut board, 1, 1));
+        // Undoing an edit gives it back
+        assert!(attempt.toggle(&mut board, 0, 0));
+        assert!(attempt.toggle(&mut board, 1, 1));
+
+        // Boards edited past the budget another way don't run
+        board.toggle(2, 2);
+        assert!(!attempt.start(&board));
+        board.toggle(2, 2);
+        assert!(attempt.start(&board));
+        assert!(!attempt.toggle(&mut board, 5, 5));
+    }
+
+    #[test]
+    fn test_retry_keeps_edits() {
+        let flight = shipped("first-flight.toml");
+        let mut attempt = Attempt::new(flight.clone());
+        let mut board = flight.board();
+        attempt.toggle(&mut board, 10, 10);
+        let setup = board.clone();
+        attempt.start(&board);
+        while attempt.observe(&board).is_none() {
+            board.next_cell_generation();
+        }
+        assert_eq!(attempt.stage(), Stage::Over(Outcome::Failed));
+        assert_eq!(attempt.retry(), setup);
+        assert_eq!(attempt.stage(), Stage::Setup);
+    }
+
+    fn parse(input: &str) -> Result<Puzzle, FormatError> {
+        Puzzle::parse(input, |file| match file {
+            "block.rle" => Ok(rle::decode("x = 2, y = 2\n2o$2o!")?),
+            _ => Err(ConwayError::Io(std::io::ErrorKind::NotFound.into())),
+        })
+    }
+
+    #[test]
+    fn test_parse_goals() {
+        let puzzle = parse(
+            "name = \"Block\"\nstart = \"block.rle\"\nbudget = 2\ngenerations = 10\n\
+             goal = \"match block.rle at 0,0\"\nrule = \"B36/S23\"\n",
+        )
+        .unwrap();
+        assert_eq!(puzzle.rule.to_string(), "B36/S23");
+        assert!(puzzle.goal.met(&puzzle.start));
+        assert_eq!(puzzle.goal.to_string(), "the target pattern at (0, 0)");
+
+        let load = |_: &str| Ok(Grid::new(1, 1));
+        let mut grid = Grid::new(5, 5);
+        grid.set(3, 1, CellState::Alive);
+        assert!(Goal::parse("alive 3,1", load).unwrap().met(&grid));
+        assert!(Goal::parse("population >= 1", load).unwrap().met(&grid));
+        assert!(!Goal::parse("population <= 0", load).unwrap().met(&grid));
+        // A target hanging over the edge never matches
+        let goal = Goal::parse("match x at 4,4", |_| Ok(Grid::new(2, 2))).unwrap();
+        assert!(!goal.met(&Grid::new(5, 5)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input: &str| parse(input).unwrap_err().to_string();
+        assert_eq!(
+            error("name = \"A\"\nstart = \"block.rle\"\nbudget = 2\ngoal = \"alive 1,1\"\n"),
+            "line 4: missing 'generations'"
+        );
+        assert!(error("start = \"gone.rle\"\n").starts_with("line 1: can't load gone.rle"));
+        assert_eq!(
+            error("goal = \"alive here\"\n"),
+            "line 1: invalid cell 'here', expected X,Y"
+        );
+        assert_eq!(error("budget = -1\n"), "line 1: 'budget' can't be negative");
+        assert_eq!(
+            error("generations = 0\n"),
+            format!("line 1: 'generations' must be 1 to {MAX_PUZZLE_GENERATIONS}")
+        );
+        assert_eq!(error("size = 3\n"), "line 1: unknown key 'size'");
+        assert!(error("goal = \"win\"\n").starts_with("line 1: invalid goal 'win'"));
+    }
+}
@@ -0,0 +1,172 @@
+use crate::conways::{CellState, Grid, InsertMode};
+
+/// Opacity of the reference layer's cells, faint enough to tell them from live ones
+pub const REFERENCE_ALPHA: f32 = 0.35;
+
+/// Where the reference layer is drawn, relative to the board's live cells
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Placement {
+    Above,
+    Below,
+    Hidden,
+}
+
+impl Placement {
+    /// Above, then below, then hidden, then above again
+    pub fn next(self) -> Self {
+        match self {
+            Placement::Above => Placement::Below,
+            Placement::Below => Placement::Hidden,
+            Placement::Hidden => Placement::Above,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Placement::Above => "above the board",
+            Placement::Below => "below the board",
+            Placement::Hidden => "hidden",
+        }
+    }
+}
+
+/// A pattern shown over the board to line a live one up with, read-only
+///
+/// The layer never takes part in the simulation; its cells only reach the
+/// board when it is committed, which stamps them in where it sits.
+pub struct ReferenceLayer {
+    pattern: Grid,
+    /// Board cell the pattern's top-left corner sits on, which may be off the board
+    offset: (isize, isize),
+    placement: Placement,
+}
+
+impl ReferenceLayer {
+    /// `pattern` centered on a board of `board` size, shown above it
+    pub fn new(pattern: Grid, board: (usize, usize)) -> Self {
+        let offset = (
+            (board.0 as isize - pattern.width() as isize) / 2,
+            (board.1 as isize - pattern.height() as isize) / 2,
+        );
+        let mut layer = Self {
+            pattern,
+            offset,
+            placement: Placement::Above,
+        };
+        layer.nudge((0, 0), board);
+        layer
+    }
+
+    /// Width and height of the pattern
+    pub fn size(&self) -> (usize, usize) {
+        (self.pattern.width(), self.pattern.height())
+    }
+
+    pub fn pattern(&self) -> &Grid {
+        &self.pattern
+    }
+
+    pub fn offset(&self) -> (isize, isize) {
+        self.offset
+    }
+
+    pub fn placement(&self) -> Placement {
+        self.placement
+    }
+
+    /// Switch to the next placement, returning it
+    pub fn cycle_placement(&mut self) -> Placement {
+        self.placement = self.placement.next();
+        self.placement
+    }
+
+    /// Move the layer by `(dx, dy)` cells on a board of `board` size
+    ///
+    /// The offset is clamped so at least one cell of the pattern's bounds
+    /// stays on the board, so a layer can't be nudged out of sight for good.
+    pub fn nudge(&mut self, (dx, dy): (isize, isize), board: (usize, usize)) {
+        let clamp = |at: isize, len: usize, size: usize| {
+            at.clamp(1 - len.max(1) as isize, size.max(1) as isize - 1)
+        };
+        self.offset = (
+            clamp(self.offset.0 + dx, self.pattern.width(), board.0),
+            clamp(self.offset.1 + dy, self.pattern.height(), board.1),
+        );
+    }
+
+    /// The live cells of the layer that fall on `grid`, as board cells
+    pub fn cells(&self, grid: &Grid) -> Vec<(usize, usize)> {
+        grid.place(&self.pattern, self.offset.0, self.offset.1)
+            .filter(|&(_, _, state)| *state == CellState::Alive)
+            .map(|(x, y, _)| (x, y))
+            .collect()
+    }
+
+    /// Stamp the layer into `grid` where it sits, adding its live cells to
+    /// the board's and dropping the ones past its edges
+    pub fn commit(&self, grid: &mut Grid) {
+        grid.insert_pattern(&self.pattern, self.offset.0, self.offset.1, InsertMode::Or);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 3x3 glider
+    fn glider() -> Grid {
+        let mut pattern = Grid::new(3, 3);
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            pattern.set(x, y, CellState::Alive);
+        }
+        pattern
+    }
+
+    #[test]
+    fn test_commit_stamps_the_layer_where_it_sits() {
+        let mut grid = Grid::new(10, 10);
+        grid.set(0, 0, CellState::Alive);
+        let mut layer = ReferenceLayer::new(glider(), (10, 10));
+        assert_eq!(layer.offset(), (3, 3));
+        layer.nudge((2, -1), (10, 10));
+        layer.commit(&mut grid);
+        assert_eq!(grid.population(), 6);
+        assert_eq!(grid.get(6, 2), CellState::Alive);
+        assert_eq!(grid.get(5, 4), CellState::Alive);
+        // The cells already alive stay
+        assert_eq!(grid.get(0, 0), CellState::Alive);
+
+        // Hanging off the board, only what lands on it is stamped
+        let mut grid = Grid::new(10, 10);
+        layer.nudge((3, 0), (10, 10));
+        assert_eq!(layer.offset(), (8, 2));
+        layer.commit(&mut grid);
+        assert_eq!(grid.population(), 3);
+        assert_eq!(layer.cells(&grid).len(), 3);
+    }
+
+    #[test]
+    fn test_offset_keeps_a_cell_on_the_board() {
+        let board = (10, 8);
+        let mut layer = ReferenceLayer::new(glider(), board);
+        layer.nudge((-100, -100), board);
+        assert_eq!(layer.offset(), (-2, -2));
+        layer.nudge((100, 100), board);
+        assert_eq!(layer.offset(), (9, 7));
+        // Only its top-left corner is left on the board, which is dead
+        assert!(layer.cells(&Grid::new(10, 8)).is_empty());
+
+        // A pattern bigger than the board is centered past its edges
+        let layer = ReferenceLayer::new(Grid::new(20, 2), (10, 10));
+        assert_eq!(layer.offset(), (-5, 4));
+    }
+
+    #[test]
+    fn test_placement_cycles() {
+        let mut layer = ReferenceLayer::new(glider(), (10, 10));
+        assert_eq!(layer.placement(), Placement::Above);
+        assert_eq!(layer.cycle_placement(), Placement::Below);
+        assert_eq!(layer.cycle_placement(), Placement::Hidden);
+        assert_eq!(layer.cycle_placement(), Placement::Above);
+    }
+}
@@ -0,0 +1,290 @@
+use crate::camera::Camera;
+use crate::shape::{self, CellShape};
+use image::{Rgba, RgbaImage};
+use macroquad::prelude::{
+    draw_circle, draw_line, draw_rectangle, draw_rectangle_lines, draw_triangle, Color,
+};
+
+/// Where the board and what sits on it are drawn: the window, or an image
+///
+/// Everything is placed in board cells, `(0, 0)` being the top-left corner of
+/// the board, and each backend maps cells to its own pixels.
+pub trait Renderer {
+    /// Size in pixels of one cell
+    fn cell_size(&self) -> f32;
+
+    /// Fill each of `cells`, board cells with the color to fill them with
+    fn draw_cells(&mut self, cells: &mut dyn Iterator<Item = (usize, usize, Color)>);
+
+    /// Outline each of `cells` with lines `thickness` pixels wide
+    fn outline_cells(
+        &mut self,
+        cells: &mut dyn Iterator<Item = (usize, usize, Color)>,
+        thickness: f32,
+    );
+
+    /// A line from one point to another, both in cells
+    fn draw_line(&mut self, from: (f32, f32), to: (f32, f32), thickness: f32, color: Color);
+
+    /// A rectangle of `size` cells with its top-left corner at `at`, filled
+    /// with `fill` and outlined with `outline`, either of which may be
+    /// transparent
+    fn draw_overlay(&mut self, at: (f32, f32), size: (f32, f32), fill: Color, outline: Color);
+}
+
+/// Draws on the window through macroquad, as seen through a camera
+pub struct ScreenRenderer<'a> {
+    view: &'a Camera,
+    shape: CellShape,
+}
+
+impl<'a> ScreenRenderer<'a> {
+    /// Cells are drawn in `shape`, where they are big enough for it
+    pub fn new(view: &'a Camera, shape: CellShape) -> Self {
+        Self { view, shape }
+    }
+
+    /// Fill the cell at world coordinates, which may lie outside the board
+    pub fn fill_at(&self, cell: (f32, f32), color: Color) {
+        let (sx, sy) = self.view.cell_to_screen(cell);
+        let size = self.view.cell_size;
+        let (inset, side) = self.shape.footprint(size);
+        let (sx, sy) = (sx + inset, sy + inset);
+        match self.shape.at_size(size) {
+            CellShape::Square => draw_rectangle(sx, sy, side, side, color),
+            CellShape::Circle => {
+                let radius = side / 2.0;
+                draw_circle(sx + radius, sy + radius, radius, color);
+            }
+            CellShape::Rounded => {
+                for [a, b, c] in shape::rounded_square((sx, sy), side) {
+                    draw_triangle(a.into(), b.into(), c.into(), color);
+                }
+            }
+        }
+    }
+}
+
+impl Renderer for ScreenRenderer<'_> {
+    fn cell_size(&self) -> f32 {
+        self.view.cell_size
+    }
+
+    fn draw_cells(&mut self, cells: &mut dyn Iterator<Item = (usize, usize, Color)>) {
+        for (x, y, color) in cells {
+            self.fill_at((x as f32, y as f32), color);
+        }
+    }
+
+    fn outline_cells(
+        &mut self,
+        cells: &mut dyn Iterator<Item = (usize, usize, Color)>,
+        thickness: f32,
+    ) {
+        let size = self.view.cell_size;
+        for (x, y, color) in cells {
+            let (sx, sy) = self.view.cell_to_screen((x as f32, y as f32));
+            draw_rectangle_lines(sx, sy, size, size, thickness, color);
+        }
+    }
+
+    fn draw_line(&mut self, from: (f32, f32), to: (f32, f32), thickness: f32, color: Color) {
+        let (a, b) = (self.view.cell_to_screen(from), self.view.cell_to_screen(to));
+        draw_line(a.0, a.1, b.0, b.1, thickness, color);
+    }
+
+    fn draw_overlay(&mut self, at: (f32, f32), size: (f32, f32), fill: Color, outline: Color) {
+        let (x, y) = self.view.cell_to_screen(at);
+        let (w, h) = (size.0 * self.view.cell_size, size.1 * self.view.cell_size);
+        if fill.a > 0.0 {
+            draw_rectangle(x, y, w, h, fill);
+        }
+        if outline.a > 0.0 {
+            draw_rectangle_lines(x, y, w, h, 2.0, outline);
+        }
+    }
+}
+
+/// The 8-bit RGBA pixel of `color`
+pub fn to_rgba(color: Color) -> Rgba<u8> {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Rgba([
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        channel(color.a),
+    ])
+}
+
+/// Draws into an image in memory, every cell a square of `scale` pixels
+///
+/// Translucent colors are blended over what is already there, lines are a
+/// pixel wide whatever their thickness and cells are always squares, so the
+/// result is exact and easy to check pixel by pixel.
+pub struct ImageRenderer {
+    image: RgbaImage,
+    scale: u32,
+}
+
+impl ImageRenderer {
+    /// An image of a `width` by `height` board, cleared to `background`
+    pub fn new(width: usize, height: usize, scale: u32, background: Color) -> Self {
+        let (w, h) = (width as u32 * scale, height as u32 * scale);
+        Self {
+            image: RgbaImage::from_pixel(w, h, to_rgba(background)),
+            scale,
+        }
+    }
+
+    pub fn into_image(self) -> RgbaImage {
+        self.image
+    }
+
+    /// Blend `color` over the pixel at `(px, py)`, if it is in the image
+    fn blend(&mut self, px: i64, py: i64, color: Color) {
+        if px < 0 || py < 0 || px >= self.image.width() as i64 || py >= self.image.height() as i64 {
+            return;
+        }
+        let pixel = self.image.get_pixel_mut(px as u32, py as u32);
+        let src = to_rgba(color);
+        let a = color.a.clamp(0.0, 1.0);
+        for i in 0..3 {
+            pixel[i] = (src[i] as f32 * a + pixel[i] as f32 * (1.0 - a)).round() as u8;
+        }
+        pixel[3] = pixel[3].max(src[3]);
+    }
+
+    /// Blend `color` over the pixels from `(x0, y0)` up to `(x1, y1)`, excluded
+    fn fill(&mut self, (x0, y0): (i64, i64), (x1, y1): (i64, i64), color: Color) {
+        for py in y0..y1 {
+            for px in x0..x1 {
+                self.blend(px, py, color);
+            }
+        }
+    }
+
+    /// A point in cells as the pixel it falls on
+    fn pixel(&self, (x, y): (f32, f32)) -> (i64, i64) {
+        let scale = self.scale as f32;
+        ((x * scale).round() as i64, (y * scale).round() as i64)
+    }
+
+    /// Outline the pixels from `a` up to `b`, excluded, `width` pixels inward
+    fn frame(&mut self, a: (i64, i64), b: (i64, i64), width: i64, color: Color) {
+        let width = width.min((b.0 - a.0 + 1) / 2).min((b.1 - a.1 + 1) / 2);
+        self.fill(a, (b.0, a.1 + width), color);
+        self.fill((a.0, b.1 - width), b, color);
+        self.fill((a.0, a.1 + width), (a.0 + width, b.1 - width), color);
+        self.fill((b.0 - width, a.1 + width), (b.0, b.1 - width), color);
+    }
+}
+
+impl Renderer for ImageRenderer {
+    fn cell_size(&self) -> f32 {
+        self.scale as f32
+    }
+
+    fn draw_cells(&mut self, cells: &mut dyn Iterator<Item = (usize, usize, Color)>) {
+        let scale = self.scale as i64;
+        for (x, y, color) in cells {
+            let (px, py) = (x as i64 * scale, y as i64 * scale);
+            self.fill((px, py), (px + scale, py + scale), color);
+        }
+    }
+
+    fn outline_cells(
+        &mut self,
+        cells: &mut dyn Iterator<Item = (usize, usize, Color)>,
+        thickness: f32,
+    ) {
+        let scale = self.scale as i64;
+        let width = (thickness.round() as i64).max(1);
+        for (x, y, color) in cells {
+            let (px, py) = (x as i64 * scale, y as i64 * scale);
+            self.frame((px, py), (px + scale, py + scale), width, color);
+        }
+    }
+
+    fn draw_line(&mut self, from: (f32, f32), to: (f32, f32), _thickness: f32, color: Color) {
+        let (a, b) = (self.pixel(from), self.pixel(to));
+        let steps = (b.0 - a.0).abs().max((b.1 - a.1).abs());
+        for i in 0..=steps {
+            let t = if steps == 0 {
+                0.0
+            } else {
+                i as f32 / steps as f32
+            };
+            let lerp = |from: i64, to: i64| from + ((to - from) as f32 * t).round() as i64;
+            self.blend(lerp(a.0, b.0), lerp(a.1, b.1), color);
+        }
+    }
+
+    fn draw_overlay(&mut self, at: (f32, f32), size: (f32, f32), fill: Color, outline: Color) {
+        let a = self.pixel(at);
+        let b = self.pixel((at.0 + size.0, at.1 + size.1));
+        if fill.a > 0.0 {
+            self.fill(a, b, fill);
+        }
+        if outline.a > 0.0 {
+            self.frame(a, b, 1, outline);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use macroquad::prelude::{BLACK, WHITE};
+
+    #[test]
+    fn test_cells_fill_their_squares() {
+        let mut renderer = ImageRenderer::new(3, 2, 2, BLACK);
+        renderer.draw_cells(&mut [(1, 0, WHITE), (2, 1, WHITE)].into_iter());
+        let image = renderer.into_image();
+        assert_eq!(image.dimensions(), (6, 4));
+        let white: Vec<(u32, u32)> = image
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| **pixel == to_rgba(WHITE))
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert_eq!(
+            white,
+            vec![
+                (2, 0),
+                (3, 0),
+                (2, 1),
+                (3, 1),
+                (4, 2),
+                (5, 2),
+                (4, 3),
+                (5, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_translucent_colors_blend() {
+        let mut renderer = ImageRenderer::new(1, 1, 1, BLACK);
+        let half = Color::new(1.0, 1.0, 1.0, 0.5);
+        renderer.draw_cells(&mut std::iter::once((0, 0, half)));
+        assert_eq!(*renderer.image.get_pixel(0, 0), Rgba([128, 128, 128, 255]));
+        // Off the image, nothing is drawn
+        renderer.draw_line((0.0, 0.0), (-3.0, 5.0), 1.0, WHITE);
+        renderer.draw_overlay((1.0, 1.0), (2.0, 2.0), WHITE, WHITE);
+        assert_eq!(*renderer.image.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn test_outlines_stay_inside_their_cells() {
+        let mut renderer = ImageRenderer::new(1, 1, 4, BLACK);
+        renderer.outline_cells(&mut std::iter::once((0, 0, WHITE)), 1.0);
+        let image = renderer.into_image();
+        let lit: usize = image
+            .pixels()
+            .filter(|pixel| **pixel == to_rgba(WHITE))
+            .count();
+        // The 12 pixels around the edge of a 4x4 square, the middle 4 left dark
+        assert_eq!(lit, 12);
+        assert_eq!(*image.get_pixel(1, 1), to_rgba(BLACK));
+    }
+}
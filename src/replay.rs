@@ -0,0 +1,586 @@
+use crate::bindings::InputCommand;
+use crate::cli::Config;
+use crate::command::Command;
+use crate::conways::{CellState, Grid, InsertMode, Region};
+use crate::formats::{rle, FormatError};
+use crate::rule::Rule;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// First line of every replay file
+const MAGIC: &str = "game-of-life replay";
+
+/// Something the player did to the board or the game during a frame
+#[derive(Clone, PartialEq, Debug)]
+pub enum Event {
+    /// A keyboard command, from any frontend
+    Input(InputCommand),
+    /// A cell edit from painting, applied with the frame's update
+    Edit(Command),
+    /// A flood fill with the fill tool, starting at a cell
+    Fill { x: usize, y: usize },
+    /// The pending paste stamped with its top-left corner at a cell
+    Stamp {
+        x: isize,
+        y: isize,
+        mode: InsertMode,
+    },
+    /// A finished selection drag
+    Select(Region),
+    /// A region filled with copies of the clipboard with the tile tool
+    Tile(Region),
+    /// A rule typed into the rule entry
+    Rule(Rule),
+    /// A jump to the kept generation at an index with the timeline bar
+    Seek(usize),
+    /// A speed, in generations per second, picked with the speed slider
+    Speed(f32),
+    /// A generation typed into the go to generation entry
+    GoTo(u64),
+    /// A cell placed or taken back during the setup of an Immigration match
+    Claim { x: usize, y: usize },
+    /// The selection picked up with Ctrl + drag, grabbed by a cell relative to it
+    Lift { x: usize, y: usize },
+    /// A pattern pasted from the system clipboard, which can't be read back
+    /// the same when the replay is played
+    PastePattern(Grid),
+    /// The `--reference` layer stamped in with its top-left corner at a cell,
+    /// which the replay is played back without
+    Reference { x: isize, y: isize, pattern: Grid },
+}
+
+/// Everything that happened during one frame, and how long it took
+#[derive(Clone, PartialEq, Debug)]
+pub struct Frame {
+    pub dt: f32,
+    pub events: Vec<Event>,
+}
+
+/// What a replay has to be played back on to come out the same
+///
+/// The settings that change how the simulation runs and a hash of the
+/// starting board have to match when playing back. `seed` drives every random
+/// board and noise patch made during the session.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Header {
+    pub version: String,
+    pub config: String,
+    pub board: u64,
+    pub seed: u64,
+}
+
+impl Header {
+    /// The header for a session of this build starting from `grid`
+    pub fn new(config: &Config, grid: &Grid, seed: u64) -> Self {
+        let rules: Vec<_> = config.rules.iter().map(Rule::to_string).collect();
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            config: format!(
+                "size={}x{} interval={} edges={} rule={} rules={} auto_pause={} stable_period={} rewind={}",
+                grid.width(),
+                grid.height(),
+                config.update_interval,
+                config.boundary.label(),
+                config.rule,
+                rules.join(","),
+                config.auto_pause,
+                config.stable_period,
+                config.rewind
+            ),
+            board: grid.content_hash(),
+            seed,
+        }
+    }
+
+    /// Check that a recording can be played back in `running`, the header of
+    /// the current build and settings
+    pub fn check(&self, running: &Header) -> Result<(), ReplayError> {
+        if self.version != running.version {
+            return Err(ReplayError::Version {
+                recorded: self.version.clone(),
+                running: running.version.clone(),
+            });
+        }
+        if self.config != running.config {
+            return Err(ReplayError::Config {
+                recorded: self.config.clone(),
+                running: running.config.clone(),
+            });
+        }
+        if self.board != running.board {
+            return Err(ReplayError::Board);
+        }
+        Ok(())
+    }
+}
+
+/// Error produced when a replay can't be loaded or doesn't fit the current run
+/// - `Io`: The file couldn't be read or written
+/// - `Parse`: The file was read but its contents are malformed
+/// - `Version`: The replay was recorded by a different version of the game
+/// - `Config`: The replay was recorded with different settings
+/// - `Board`: The replay was recorded starting from a different board
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Parse(FormatError),
+    Version { recorded: String, running: String },
+    Config { recorded: String, running: String },
+    Board,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "{err}"),
+            ReplayError::Parse(err) => write!(f, "parse error at {err}"),
+            ReplayError::Version { recorded, running } => write!(
+                f,
+                "recorded by version {recorded}, but this is version {running}"
+            ),
+            ReplayError::Config { recorded, running } => write!(
+                f,
+                "recorded with settings '{recorded}', but running with '{running}'"
+            ),
+            ReplayError::Board => write!(
+                f,
+                "recorded from a different starting board; pass the same --pattern, --start or --seed"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self {
+        ReplayError::Io(err)
+    }
+}
+
+impl From<FormatError> for ReplayError {
+    fn from(err: FormatError) -> Self {
+        ReplayError::Parse(err)
+    }
+}
+
+/// A recorded session: the header followed by every frame
+///
+/// The file is plain text, one line each for the header fields, then a
+/// `frame <dt>` line for every frame followed by a line for each of its events:
+/// ```text
+/// game-of-life replay
+/// version 0.1.0
+/// config size=80x60 interval=0.1 edges=dead auto_pause=true stable_period=15 rewind=100
+/// board 5c2b4e1f0a6d3e88
+/// seed 42
+/// frame 0.016
+/// input toggle_pause
+/// set 3 4 alive
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Replay {
+    pub header: Header,
+    frames: Vec<Frame>,
+    /// Events of the frame in progress
+    current: Vec<Event>,
+}
+
+impl Replay {
+    /// Start recording a session
+    pub fn new(header: Header) -> Self {
+        Self {
+            header,
+            frames: Vec::new(),
+            current: Vec::new(),
+        }
+    }
+
+    /// Remember an event of the current frame
+    pub fn record(&mut self, event: Event) {
+        self.current.push(event);
+    }
+
+    /// Close the current frame, which took `dt` seconds and painted `edits`
+    pub fn end_frame(&mut self, dt: f32, edits: &[Command]) {
+        let mut events = std::mem::take(&mut self.current);
+        events.extend(edits.iter().cloned().map(Event::Edit));
+        self.frames.push(Frame { dt, events });
+    }
+
+    /// The recorded frames, in order, for playing them back
+    pub fn into_frames(self) -> std::vec::IntoIter<Frame> {
+        self.frames.into_iter()
+    }
+
+    /// Write the replay to a file
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        std::fs::write(path, self.to_text())?;
+        Ok(())
+    }
+
+    /// Read a replay from a file
+    pub fn load(path: &Path) -> Result<Replay, ReplayError> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn to_text(&self) -> String {
+        let header = &self.header;
+        let mut out = format!(
+            "{MAGIC}\nversion {}\nconfig {}\nboard {:016x}\nseed {}\n",
+            header.version, header.config, header.board, header.seed
+        );
+        for frame in &self.frames {
+            out.push_str(&format!("frame {}\n", frame.dt));
+            for event in &frame.events {
+                out.push_str(&event_line(event));
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    pub fn parse(text: &str) -> Result<Replay, FormatError> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let mut field = |name: &str| {
+            let (number, line) = lines
+                .next()
+                .ok_or_else(|| FormatError::new(0, format!("missing '{name}' line")))?;
+            let value = if name == MAGIC {
+                (line == MAGIC).then_some("")
+            } else {
+                line.strip_prefix(name)
+                    .and_then(|rest| rest.strip_prefix(' '))
+            };
+            value
+                .map(|value| (number, value.to_string()))
+                .ok_or_else(|| FormatError::new(number, format!("expected '{name}'")))
+        };
+        field(MAGIC)?;
+        let (_, version) = field("version")?;
+        let (_, config) = field("config")?;
+        let (number, board) = field("board")?;
+        let board = u64::from_str_radix(&board, 16)
+            .map_err(|_| FormatError::new(number, "invalid board hash"))?;
+        let (number, seed) = field("seed")?;
+        let seed = seed
+            .parse()
+            .map_err(|_| FormatError::new(number, "invalid seed"))?;
+
+        let mut replay = Replay::new(Header {
+            version,
+            config,
+            board,
+            seed,
+        });
+        for (number, line) in lines {
+            if let Some(dt) = line.strip_prefix("frame ") {
+                let dt = dt
+                    .parse()
+                    .map_err(|_| FormatError::new(number, "invalid frame time"))?;
+                replay.frames.push(Frame {
+                    dt,
+                    events: Vec::new(),
+                });
+                continue;
+            }
+            let event = parse_event(line).map_err(|msg| FormatError::new(number, msg))?;
+            replay
+                .frames
+                .last_mut()
+                .ok_or_else(|| FormatError::new(number, "event before the first frame"))?
+                .events
+                .push(event);
+        }
+        Ok(replay)
+    }
+}
+
+fn event_line(event: &Event) -> String {
+    match event {
+        Event::Input(command) => format!("input {}", command.name()),
+        Event::Edit(Command::Set { x, y, state }) => {
+            let state = match state {
+                CellState::Alive => "alive",
+                CellState::Dead => "dead",
+            };
+            format!("set {x} {y} {state}")
+        }
+        Event::Edit(Command::Toggle { x, y }) => format!("toggle {x} {y}"),
+        Event::Edit(Command::Begin) => "begin".to_string(),
+        Event::Edit(Command::End) => "end".to_string(),
+        Event::Fill { x, y } => format!("fill {x} {y}"),
+        Event::Stamp { x, y, mode } => {
+            let mode = match mode {
+                InsertMode::Or => "or",
+                InsertMode::Overwrite => "overwrite",
+            };
+            format!("stamp {x} {y} {mode}")
+        }
+        Event::Select(region) => format!(
+            "select {} {} {} {}",
+            region.x, region.y, region.width, region.height
+        ),
+        Event::Tile(region) => format!(
+            "tile {} {} {} {}",
+            region.x, region.y, region.width, region.height
+        ),
+        Event::Rule(rule) => format!("rule {rule}"),
+        Event::Seek(index) => format!("seek {index}"),
+        Event::Speed(speed) => format!("speed {speed}"),
+        Event::GoTo(generation) => format!("goto {generation}"),
+        Event::Claim { x, y } => format!("claim {x} {y}"),
+        Event::Lift { x, y } => format!("lift {x} {y}"),
+        Event::PastePattern(pattern) => format!(
+            "paste {} {} {}",
+            pattern.width(),
+            pattern.height(),
+            rle::encode_line(pattern)
+        ),
+        Event::Reference { x, y, pattern } => format!(
+            "reference {x} {y} {} {} {}",
+            pattern.width(),
+            pattern.height(),
+            rle::encode_line(pattern)
+        ),
+    }
+}
+
+fn parse_event(line: &str) -> Result<Event, String> {
+    let mut words = line.split_whitespace();
+    let kind = words.next().unwrap_or_default();
+    let args: Vec<&str> = words.collect();
+    let number = |i: usize| -> Result<usize, String> {
+        args.get(i)
+            .and_then(|arg| arg.parse().ok())
+            .ok_or_else(|| format!("'{kind}' needs a cell coordinate at position {}", i + 1))
+    };
+    let event = match (kind, args.as_slice()) {
+        ("input", [name]) => Event::Input(
+            InputCommand::from_name(name).ok_or_else(|| format!("unknown command '{name}'"))?,
+        ),
+        ("set", [_, _, state]) => Event::Edit(Command::Set {
+            x: number(0)?,
+            y: number(1)?,
+            state: match *state {
+                "alive" => CellState::Alive,
+                "dead" => CellState::Dead,
+                _ => return Err(format!("unknown cell state '{state}'")),
+            },
+        }),
+        ("toggle", [_, _]) => Event::Edit(Command::Toggle {
+            x: number(0)?,
+            y: number(1)?,
+        }),
+        ("begin", []) => Event::Edit(Command::Begin),
+        ("end", []) => Event::Edit(Command::End),
+        ("fill", [_, _]) => Event::Fill {
+            x: number(0)?,
+            y: number(1)?,
+        },
+        ("stamp", [x, y, mode]) => Event::Stamp {
+            x: x.parse()
+                .map_err(|_| format!("invalid stamp position '{x}'"))?,
+            y: y.parse()
+                .map_err(|_| format!("invalid stamp position '{y}'"))?,
+            mode: match *mode {
+                "or" => InsertMode::Or,
+                "overwrite" => InsertMode::Overwrite,
+                _ => return Err(format!("unknown stamp mode '{mode}'")),
+            },
+        },
+        ("select", [_, _, _, _]) => Event::Select(Region {
+            x: number(0)?,
+            y: number(1)?,
+            width: number(2)?,
+            height: number(3)?,
+        }),
+        ("tile", [_, _, _, _]) => Event::Tile(Region {
+            x: number(0)?,
+            y: number(1)?,
+            width: number(2)?,
+            height: number(3)?,
+        }),
+        ("rule", [rule]) => Event::Rule(
+            rule.parse()
+                .map_err(|err| format!("invalid rule '{rule}': {err}"))?,
+        ),
+        ("seek", [index]) => Event::Seek(
+            index
+                .parse()
+                .map_err(|_| format!("invalid generation index '{index}'"))?,
+        ),
+        ("speed", [speed]) => Event::Speed(
+            speed
+                .parse()
+                .ok()
+                .filter(|speed: &f32| *speed > 0.0)
+                .ok_or_else(|| format!("invalid speed '{speed}'"))?,
+        ),
+        ("goto", [generation]) => Event::GoTo(
+            generation
+                .parse()
+                .map_err(|_| format!("invalid generation '{generation}'"))?,
+        ),
+        ("claim", [_, _]) => Event::Claim {
+            x: number(0)?,
+            y: number(1)?,
+        },
+        ("lift", [_, _]) => Event::Lift {
+            x: number(0)?,
+            y: number(1)?,
+        },
+        ("paste", [_, _, cells]) => Event::PastePattern(
+            rle::decode_line(number(0)?, number(1)?, cells)
+                .map_err(|err| format!("invalid pasted pattern: {err}"))?,
+        ),
+        ("reference", [x, y, _, _, cells]) => Event::Reference {
+            x: x.parse()
+                .map_err(|_| format!("invalid reference position '{x}'"))?,
+            y: y.parse()
+                .map_err(|_| format!("invalid reference position '{y}'"))?,
+            pattern: rle::decode_line(number(2)?, number(3)?, cells)
+                .map_err(|err| format!("invalid reference pattern: {err}"))?,
+        },
+        _ => return Err(format!("invalid event '{line}'")),
+    };
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool::Tool;
+
+    fn header() -> Header {
+        Header::new(&Config::default(), &Grid::new(8, 6), 42)
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let mut replay = Replay::new(header());
+        replay.record(Event::Input(InputCommand::TogglePause));
+        replay.record(Event::Input(InputCommand::SelectTool(Tool::FloodFill)));
+        replay.record(Event::Fill { x: 1, y: 2 });
+        replay.end_frame(
+            0.016,
+            &[
+                Command::Begin,
+                Command::Set {
+                    x: 3,
+                    y: 4,
+                    state: CellState::Alive,
+                },
+                Command::Toggle { x: 0, y: 0 },
+                Command::End,
+            ],
+        );
+        replay.end_frame(0.5, &[]);
+        replay.record(Event::Stamp {
+            x: -2,
+            y: 3,
+            mode: InsertMode::Overwrite,
+        });
+        replay.record(Event::Select(Region::from_corners((1, 1), (4, 3))));
+        replay.record(Event::Tile(Region::from_corners((0, 0), (9, 5))));
+        replay.record(Event::Rule("B36/S23".parse().unwrap()));
+        replay.record(Event::Seek(12));
+        replay.record(Event::Speed(37.41));
+        replay.record(Event::GoTo(1_000_000));
+        replay.record(Event::Claim { x: 5, y: 7 });
+        replay.record(Event::Lift { x: 0, y: 2 });
+        let mut glider = Grid::new(3, 3);
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            glider.set(x, y, CellState::Alive);
+        }
+        replay.record(Event::PastePattern(glider.clone()));
+        replay.record(Event::Reference {
+            x: -1,
+            y: 4,
+            pattern: glider,
+        });
+        replay.end_frame(1.0 / 60.0, &[]);
+
+        let text = replay.to_text();
+        assert!(text.starts_with("game-of-life replay\nversion "));
+        assert!(text.contains("\nframe 0.016\ninput toggle_pause\ninput tool_fill\nfill 1 2\n"));
+        assert_eq!(Replay::parse(&text).unwrap(), replay);
+    }
+
+    #[test]
+    fn test_header_checks() {
+        let recorded = header();
+        assert!(recorded.check(&header()).is_ok());
+
+        let newer = Header {
+            version: "99.0.0".to_string(),
+            ..header()
+        };
+        assert!(matches!(
+            recorded.check(&newer),
+            Err(ReplayError::Version { .. })
+        ));
+
+        let faster = Config {
+            update_interval: 0.05,
+            ..Config::default()
+        };
+        let error = recorded
+            .check(&Header::new(&faster, &Grid::new(8, 6), 42))
+            .unwrap_err();
+        let rules = "rules=B3/S23,B36/S23,B3678/S34678,B2/S,B3/S12345,B3/S012345678";
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "recorded with settings 'size=8x6 interval=0.1 edges=dead rule=B3/S23 {rules} \
+                 auto_pause=true stable_period=15 rewind=100', but running with 'size=8x6 \
+                 interval=0.05 edges=dead rule=B3/S23 {rules} auto_pause=true \
+                 stable_period=15 rewind=100'"
+            )
+        );
+
+        let mut other = Grid::new(8, 6);
+        other.toggle(0, 0);
+        assert!(matches!(
+            recorded.check(&Header::new(&Config::default(), &other, 42)),
+            Err(ReplayError::Board)
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |text: &str| Replay::parse(text).unwrap_err().to_string();
+        let header = Replay::new(header()).to_text();
+
+        assert_eq!(error("hello"), "line 1: expected 'game-of-life replay'");
+        assert_eq!(error(MAGIC), "line 0: missing 'version' line");
+        assert_eq!(
+            error(&format!("{header}input toggle_pause")),
+            "line 6: event before the first frame"
+        );
+        assert_eq!(
+            error(&format!("{header}frame 0.1\ninput explode")),
+            "line 7: unknown command 'explode'"
+        );
+        assert_eq!(
+            error(&format!("{header}frame 0.1\nset 1 x alive")),
+            "line 7: 'set' needs a cell coordinate at position 2"
+        );
+        assert_eq!(
+            error(&format!("{header}frame soon")),
+            "line 6: invalid frame time"
+        );
+    }
+
+    #[test]
+    fn test_board_hash() {
+        let mut grid = Grid::new(4, 4);
+        let empty = grid.content_hash();
+        assert_ne!(empty, Grid::new(2, 8).content_hash());
+        grid.toggle(2, 1);
+        assert_ne!(grid.content_hash(), empty);
+        grid.toggle(2, 1);
+        assert_eq!(grid.content_hash(), empty);
+    }
+}
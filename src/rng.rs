@@ -0,0 +1,148 @@
+/// A source of random bits for the randomized parts of the library, e.g.
+/// `Grid::randomize_with`
+///
+/// Everything random goes through a generator handed in by the caller, never
+/// a global one, so a run is reproduced by handing in the same generator in
+/// the same state. Only `next_u64` needs implementing.
+pub trait Rng {
+    /// Returns the next 64 random bits
+    fn next_u64(&mut self) -> u64;
+
+    /// Returns a random number uniformly distributed in `[0, 1)`
+    fn next_f64(&mut self) -> f64 {
+        // Keep the 53 high bits, which is exactly the precision of an f64
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl<R: Rng + ?Sized> Rng for &mut R {
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+}
+
+/// A tiny, fast and deterministic pseudo random number generator
+/// The same seed produces the same sequence on every platform.
+/// see more: <https://prng.di.unimi.it/splitmix64.c>
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns the next 64 random bits
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random number uniformly distributed in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        Rng::next_f64(self)
+    }
+}
+
+impl Rng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        SplitMix64::next_u64(self)
+    }
+}
+
+/// xoshiro256**, a generator with a far longer period than `SplitMix64` for
+/// long runs drawing a lot of numbers, just as deterministic everywhere
+/// see more: <https://prng.di.unimi.it/xoshiro256starstar.c>
+#[derive(Clone, Debug)]
+pub struct Xoshiro256 {
+    state: [u64; 4],
+}
+
+impl Xoshiro256 {
+    /// The state is filled from `SplitMix64` seeded with `seed`, as the
+    /// authors recommend, so it is never all zeros
+    pub fn new(seed: u64) -> Self {
+        let mut seeder = SplitMix64::new(seed);
+        Self::from_state([(); 4].map(|_| seeder.next_u64()))
+    }
+
+    /// A generator in exactly `state`, which mustn't be all zeros
+    pub fn from_state(state: [u64; 4]) -> Self {
+        Self { state }
+    }
+}
+
+impl Rng for Xoshiro256 {
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+}
+
+/// A seed for randomized boards, taken from the system clock
+/// Goes through miniquad, since `SystemTime::now` panics in the browser.
+#[cfg(feature = "gui")]
+pub fn clock_seed() -> u64 {
+    (macroquad::miniquad::date::now() * 1_000_000.0) as u64
+}
+
+/// A seed for randomized boards, taken from the system clock
+#[cfg(all(feature = "std", not(feature = "gui")))]
+pub fn clock_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_micros() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_sequence() {
+        // First outputs of the reference implementation seeded with 0
+        let mut rng = SplitMix64::new(0);
+        assert_eq!(rng.next_u64(), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(rng.next_u64(), 0x6e78_9e6a_a1b9_65f4);
+    }
+
+    #[test]
+    fn test_xoshiro_reference_sequence() {
+        // First outputs of the reference implementation from this state
+        let mut rng = Xoshiro256::from_state([1, 2, 3, 4]);
+        assert_eq!(rng.next_u64(), 11520);
+        assert_eq!(rng.next_u64(), 0);
+        assert_eq!(rng.next_u64(), 1509978240);
+        assert_eq!(rng.next_u64(), 1215971899390074240);
+    }
+
+    #[test]
+    fn test_borrowed_generators_move_on() {
+        let mut rng = SplitMix64::new(0);
+        let mut borrowed = &mut rng;
+        assert_eq!(Rng::next_u64(&mut borrowed), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(rng.next_u64(), 0x6e78_9e6a_a1b9_65f4);
+    }
+
+    #[test]
+    fn test_floats_are_in_unit_interval() {
+        let mut rng = SplitMix64::new(123);
+        for _ in 0..1000 {
+            let value = rng.next_f64();
+            assert!((0.0..1.0).contains(&value));
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use crate::conways::CellState;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/// Well known rules, cycled through at runtime unless `conways.toml` lists others
+pub const PRESETS: &[(&str, &str)] = &[
+    ("Conway", "B3/S23"),
+    ("HighLife", "B36/S23"),
+    ("Day & Night", "B3678/S34678"),
+    ("Seeds", "B2/S"),
+    ("Maze", "B3/S12345"),
+    ("Life without death", "B3/S012345678"),
+];
+
+/// A life-like rule in B/S notation: the neighbor counts that bring a dead
+/// cell to life, and the ones that keep a live cell alive
+///
+/// Conway's Game of Life is `B3/S23`: a dead cell with exactly 3 live
+/// neighbors is born, and a live cell with 2 or 3 survives.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rule {
+    /// Bit `n` is set when `n` neighbors bring a dead cell to life
+    birth: u16,
+    /// Bit `n` is set when `n` neighbors keep a live cell alive
+    survival: u16,
+}
+
+impl Rule {
+    pub const fn conway() -> Self {
+        Self {
+            birth: 1 << 3,
+            survival: 1 << 2 | 1 << 3,
+        }
+    }
+
+    /// Whether a cell is alive in the next generation
+    pub fn next_state(self, alive: bool, neighbors: u8) -> bool {
+        let counts = if alive { self.survival } else { self.birth };
+        counts & (1 << neighbors) != 0
+    }
+
+    /// The rules of `PRESETS`, in order
+    pub fn presets() -> Vec<Rule> {
+        PRESETS
+            .iter()
+            .map(|(_, rule)| rule.parse().expect("presets are valid rulestrings"))
+            .collect()
+    }
+
+    /// A rule from masks of neighbor counts, bit `n` set for `n` neighbors,
+    /// or `None` when a bit above 8 is set
+    pub fn from_masks(birth: u16, survival: u16) -> Option<Self> {
+        ((birth | survival) >> 9 == 0).then_some(Self { birth, survival })
+    }
+
+    /// The birth and survival masks `from_masks` takes
+    pub fn masks(self) -> (u16, u16) {
+        (self.birth, self.survival)
+    }
+
+    /// Name of the rule, if it is one of `PRESETS`
+    pub fn name(self) -> Option<&'static str> {
+        PRESETS
+            .iter()
+            .find(|(_, rule)| rule.parse() == Ok(self))
+            .map(|(name, _)| *name)
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::conway()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = |counts: u16| -> String {
+            (0..=8)
+                .filter(|n| counts & (1 << n) != 0)
+                .map(|n| char::from(b'0' + n as u8))
+                .collect()
+        };
+        write!(f, "B{}/S{}", digits(self.birth), digits(self.survival))
+    }
+}
+
+/// Error produced when parsing a rulestring
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RuleError {
+    /// Not of the form `B<digits>/S<digits>`
+    Syntax,
+    /// A digit that can't be a neighbor count
+    Neighbors(char),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::Syntax => write!(f, "expected B<digits>/S<digits>, e.g. B3/S23"),
+            RuleError::Neighbors(c) => {
+                write!(f, "'{c}' isn't a neighbor count from 0 to 8")
+            }
+        }
+    }
+}
+
+impl core::error::Error for RuleError {}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+
+    /// Parse a rulestring such as `B3/S23`
+    /// Letters are case insensitive and the two halves may come in either order.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (first, second) = s.trim().split_once('/').ok_or(RuleError::Syntax)?;
+        let (mut birth, mut survival) = (None, None);
+        for part in [first, second] {
+            let mut chars = part.trim().chars();
+            let slot = match chars.next().map(|c| c.to_ascii_uppercase()) {
+                Some('B') => &mut birth,
+                Some('S') => &mut survival,
+                _ => return Err(RuleError::Syntax),
+            };
+            if slot.is_some() {
+                return Err(RuleError::Syntax);
+            }
+            let mut counts = 0;
+            for c in chars {
+                match c.to_digit(10) {
+                    Some(n) if n <= 8 => counts |= 1 << n,
+                    Some(_) => return Err(RuleError::Neighbors(c)),
+                    None => return Err(RuleError::Syntax),
+                }
+            }
+            *slot = Some(counts);
+        }
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Ok(Self { birth, survival }),
+            _ => Err(RuleError::Syntax),
+        }
+    }
+}
+
+/// How a cell changes from one generation to the next, for rules a B/S
+/// rulestring can't describe: random ones, ones that depend on where the cell
+/// is or on something outside the board
+///
+/// `Rule` is the ruleset every grid starts with. A grid handed another one
+/// with `Grid::set_ruleset` asks it about every cell; one that turns out to be
+/// a plain B/S rule, as `as_rule` tells, keeps stepping on the fast path.
+///
+/// # Example
+/// ```
+/// use game_of_life::conways::{CellState, Grid};
+/// use game_of_life::rule::{Rule, Ruleset};
+///
+/// /// Conway's rule, except that nothing is born left of the column it holds
+/// struct Barren(usize);
+///
+/// impl Ruleset for Barren {
+///     fn next_state(&self, current: CellState, neighbors: u8, x: usize, _y: usize) -> CellState {
+///         let alive = current == CellState::Alive;
+///         if (alive || x >= self.0) && Rule::conway().next_state(alive, neighbors) {
+///             CellState::Alive
+///         } else {
+///             CellState::Dead
+///         }
+///     }
+/// }
+///
+/// let mut grid = Grid::new(6, 5);
+/// for y in 1..4 {
+///     grid.set(2, y, CellState::Alive);
+/// }
+/// grid.set_ruleset(Barren(3));
+/// grid.next_cell_generation();
+/// // The blinker can't grow its left end
+/// assert_eq!(grid.get(1, 2), CellState::Dead);
+/// assert_eq!(grid.get(3, 2), CellState::Alive);
+/// assert_eq!(grid.population(), 2);
+/// ```
+pub trait Ruleset {
+    /// The state of the cell at `(x, y)` in the next generation
+    ///
+    /// # Arguments
+    /// * `current` - Its state in this generation
+    /// * `neighbors` - How many of its 8 neighbors are alive, counted across
+    ///   the edges the grid has
+    fn next_state(&self, current: CellState, neighbors: u8, x: usize, y: usize) -> CellState;
+
+    /// The B/S rule this ruleset amounts to, if any, for grids to step with
+    /// without asking about every cell
+    fn as_rule(&self) -> Option<Rule> {
+        None
+    }
+}
+
+impl Ruleset for Rule {
+    fn next_state(&self, current: CellState, neighbors: u8, _x: usize, _y: usize) -> CellState {
+        if Rule::next_state(*self, current == CellState::Alive, neighbors) {
+            CellState::Alive
+        } else {
+            CellState::Dead
+        }
+    }
+
+    fn as_rule(&self) -> Option<Rule> {
+        Some(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!("B3/S23".parse(), Ok(Rule::conway()));
+        assert_eq!(" s23/b3 ".parse(), Ok(Rule::conway()));
+        let seeds: Rule = "B2/S".parse().unwrap();
+        assert_eq!(seeds.to_string(), "B2/S");
+        let day_and_night: Rule = "b6378/s87643".parse().unwrap();
+        assert_eq!(day_and_night.to_string(), "B3678/S34678");
+        assert_eq!(day_and_night.name(), Some("Day & Night"));
+        assert_eq!("B1/S1".parse::<Rule>().unwrap().name(), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for input in ["", "B3", "B3/S23/", "X3/S23", "B3/B23", "B3/S2a", "B3 S23"] {
+            assert_eq!(input.parse::<Rule>(), Err(RuleError::Syntax), "{input}");
+        }
+        assert_eq!("B39/S23".parse::<Rule>(), Err(RuleError::Neighbors('9')));
+    }
+
+    #[test]
+    fn test_masks_round_trip() {
+        assert_eq!(Rule::conway().masks(), (0b1000, 0b1100));
+        for rule in Rule::presets() {
+            let (birth, survival) = rule.masks();
+            assert_eq!(Rule::from_masks(birth, survival), Some(rule));
+        }
+        assert_eq!(Rule::from_masks(1 << 8, 0).unwrap().to_string(), "B8/S");
+        assert_eq!(Rule::from_masks(1 << 9, 0), None);
+        assert_eq!(Rule::from_masks(0, u16::MAX), None);
+    }
+
+    #[test]
+    fn test_next_state() {
+        let conway = Rule::conway();
+        assert!(conway.next_state(false, 3));
+        assert!(!conway.next_state(false, 2));
+        assert!(conway.next_state(true, 2));
+        assert!(!conway.next_state(true, 4));
+
+        let seeds: Rule = "B2/S".parse().unwrap();
+        assert!(seeds.next_state(false, 2));
+        assert!(!seeds.next_state(true, 2));
+    }
+
+    #[test]
+    fn test_presets_are_valid() {
+        assert_eq!(Rule::presets().len(), PRESETS.len());
+        assert_eq!(Rule::presets()[0], Rule::conway());
+    }
+}
@@ -0,0 +1,163 @@
+use crate::buttons::STRIP_HEIGHT;
+use crate::camera::{Bounds, Camera};
+
+/// Height of the column ruler along the top of the window, in pixels
+pub const RULER_HEIGHT: f32 = 18.0;
+
+/// Width of the row ruler along the left of the window, in pixels, enough
+/// for the numbers of the largest boards
+pub const RULER_WIDTH: f32 = 44.0;
+
+/// Size of the numbers on the rulers
+pub const RULER_FONT_SIZE: f32 = 16.0;
+
+/// Fewest pixels between two labels, so neighbouring numbers never touch
+const MIN_LABEL_SPACING: f32 = 40.0;
+
+/// Strides the labels step by, from every cell to every thousandth
+const STRIDES: [usize; 7] = [1, 5, 10, 50, 100, 500, 1000];
+
+/// Space the HUD line and the buttons take at the bottom of the window
+const HUD_SPACE: f32 = 30.0 + STRIP_HEIGHT;
+
+/// How many cells apart the labels go at `cell_size` pixels per cell: every
+/// cell when zoomed in, then every 5, 10, 50 and so on as the view zooms out
+pub fn label_stride(cell_size: f32) -> usize {
+    STRIDES
+        .into_iter()
+        .find(|&stride| stride as f32 * cell_size >= MIN_LABEL_SPACING)
+        .unwrap_or(STRIDES[STRIDES.len() - 1])
+}
+
+/// The column ruler: the top edge, right of the row ruler's corner
+pub fn top_bounds(screen: (f32, f32)) -> Bounds {
+    Bounds {
+        x: RULER_WIDTH,
+        y: 0.0,
+        w: (screen.0 - RULER_WIDTH).max(0.0),
+        h: RULER_HEIGHT,
+    }
+}
+
+/// The row ruler: the left edge, from under the column ruler down to the HUD
+pub fn left_bounds(screen: (f32, f32)) -> Bounds {
+    Bounds {
+        x: 0.0,
+        y: RULER_HEIGHT,
+        w: RULER_WIDTH,
+        h: (screen.1 - HUD_SPACE - RULER_HEIGHT).max(0.0),
+    }
+}
+
+/// The labelled cells along one axis between `start` and `end` pixels
+///
+/// # Arguments
+/// * `origin` - The fractional cell at pixel 0 on this axis, from the camera
+/// * `cells` - How many cells the board has along the axis; none past it are labelled
+///
+/// # Returns
+/// Each labelled cell with the pixel its middle sits at
+pub fn ticks(
+    origin: f32,
+    cell_size: f32,
+    cells: usize,
+    (start, end): (f32, f32),
+) -> Vec<(usize, f32)> {
+    let stride = label_stride(cell_size);
+    let first = (origin + start / cell_size).max(0.0) as usize;
+    let last = ((origin + end / cell_size).ceil().max(0.0) as usize).min(cells);
+    // Rounded up to the stride, so labels land on multiples of it
+    let first = first.div_ceil(stride) * stride;
+    (first..last)
+        .step_by(stride)
+        .map(|cell| (cell, (cell as f32 + 0.5 - origin) * cell_size))
+        .filter(|&(_, pixel)| pixel >= start && pixel < end)
+        .collect()
+}
+
+/// The column and row ticks for `camera`, clipped to their rulers
+pub fn column_ticks(camera: &Camera, screen: (f32, f32), columns: usize) -> Vec<(usize, f32)> {
+    let bounds = top_bounds(screen);
+    let span = (bounds.x, bounds.x + bounds.w);
+    ticks(camera.origin.0, camera.cell_size, columns, span)
+}
+
+pub fn row_ticks(camera: &Camera, screen: (f32, f32), rows: usize) -> Vec<(usize, f32)> {
+    let bounds = left_bounds(screen);
+    let span = (bounds.y, bounds.y + bounds.h);
+    ticks(camera.origin.1, camera.cell_size, rows, span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stride_follows_zoom() {
+        assert_eq!(label_stride(64.0), 1);
+        assert_eq!(label_stride(40.0), 1);
+        assert_eq!(label_stride(20.0), 5);
+        assert_eq!(label_stride(8.0), 5);
+        assert_eq!(label_stride(4.0), 10);
+        assert_eq!(label_stride(2.0), 50);
+        assert_eq!(label_stride(1.0), 50);
+        // Zoomed out past what the widest stride fits, it sticks to that one
+        assert_eq!(label_stride(0.01), 1000);
+        // Zooming out never makes labels denser
+        let mut previous = 1;
+        for size in (1..=64).rev() {
+            let stride = label_stride(size as f32);
+            assert!(stride >= previous && stride as f32 * size as f32 >= MIN_LABEL_SPACING);
+            previous = stride;
+        }
+    }
+
+    #[test]
+    fn test_ticks_land_on_cell_centers() {
+        // Cells of 10 pixels from cell 3.5 on, labelled every 5
+        let ticks = ticks(3.5, 10.0, 100, (0.0, 200.0));
+        assert_eq!(ticks, vec![(5, 20.0), (10, 70.0), (15, 120.0), (20, 170.0)]);
+        let camera = Camera {
+            origin: (3.5, 3.5),
+            cell_size: 10.0,
+        };
+        let (x, y) = camera.cell_to_screen((10.0, 10.0));
+        assert_eq!((x + 5.0, y + 5.0), (70.0, 70.0));
+    }
+
+    #[test]
+    fn test_ticks_stay_on_the_board_and_ruler() {
+        // Panned left of the board, nothing before column 0
+        let ticks = ticks(-10.0, 20.0, 12, (0.0, 800.0));
+        assert_eq!(ticks.first().unwrap().0, 0);
+        // Nor past the last column
+        assert_eq!(ticks.last().unwrap().0, 10);
+
+        let screen = (800.0, 600.0);
+        let camera = Camera {
+            origin: (0.0, 0.0),
+            cell_size: 40.0,
+        };
+        let columns = column_ticks(&camera, screen, 1000);
+        // Column 0 hides under the corner of the row ruler
+        assert_eq!(columns.first().unwrap().0, 1);
+        assert!(columns.iter().all(|&(_, x)| x >= RULER_WIDTH));
+        let rows = row_ticks(&camera, screen, 1000);
+        let left = left_bounds(screen);
+        assert!(rows
+            .iter()
+            .all(|&(_, y)| y >= RULER_HEIGHT && y < left.y + left.h));
+    }
+
+    #[test]
+    fn test_rulers_keep_clear_of_the_hud() {
+        let screen = (800.0, 600.0);
+        let left = left_bounds(screen);
+        let top = top_bounds(screen);
+        // The HUD line's top and the strip below it
+        assert!(left.y + left.h <= screen.1 - STRIP_HEIGHT - 10.0 - 20.0);
+        assert!(top.x >= left.x + left.w && top.y + top.h <= left.y);
+        // A window smaller than the HUD leaves no ruler rather than a negative one
+        assert_eq!(left_bounds((100.0, 50.0)).h, 0.0);
+    }
+}
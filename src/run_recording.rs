@@ -0,0 +1,349 @@
+use crate::conways::{BoundaryCondition, Grid};
+use crate::error::ConwayError;
+use crate::formats::{rle, FormatError};
+use crate::rule::Rule;
+use crate::timeline::Timeline;
+use std::fs;
+use std::path::Path;
+
+/// First line of every run recording
+const MAGIC: &str = "game-of-life run";
+
+/// Version of the format written, bumped whenever it changes in a way older
+/// builds couldn't read
+pub const RUN_FORMAT_VERSION: u32 = 1;
+
+/// The change from one recorded board to the next
+#[derive(Clone, PartialEq, Debug)]
+struct Step {
+    /// Generation counter of the board after the change
+    generation: u64,
+    /// Row-major indices of every cell that flipped, in increasing order
+    flipped: Vec<usize>,
+}
+
+/// A run of the simulation as the boards it went through, to be watched again
+/// without simulating it
+///
+/// The starting board is kept whole, then every recorded generation only as
+/// the cells that changed since the one before, which also takes in any edit
+/// made in between. Playing it back goes through a `Timeline`, so stepping and
+/// scrubbing work in both directions.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RunRecording {
+    rule: Rule,
+    boundary: BoundaryCondition,
+    start: Grid,
+    steps: Vec<Step>,
+    /// The latest board recorded, the next one is compared with
+    last: Grid,
+}
+
+impl RunRecording {
+    /// A recording starting from `grid`, with its rule and edges
+    pub fn new(grid: &Grid) -> Self {
+        Self {
+            rule: grid.rule(),
+            boundary: grid.boundary(),
+            start: grid.clone(),
+            steps: Vec::new(),
+            last: grid.clone(),
+        }
+    }
+
+    /// Add `grid` as the next board of the run, when it changed
+    ///
+    /// # Returns
+    /// A `DimensionMismatch` error when `grid` has another size than the
+    /// recorded boards, which a step can't describe; it isn't recorded then
+    pub fn record(&mut self, grid: &Grid) -> Result<(), ConwayError> {
+        let (width, height) = (grid.width(), grid.height());
+        let expected = (self.last.width(), self.last.height());
+        if (width, height) != expected {
+            return Err(ConwayError::DimensionMismatch {
+                expected,
+                found: (width, height),
+            });
+        }
+        let flipped: Vec<usize> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.last.get(x, y) != grid.get(x, y))
+            .map(|(x, y)| y * width + x)
+            .collect();
+        if flipped.is_empty() && grid.generation() == self.last.generation() {
+            return Ok(());
+        }
+        self.steps.push(Step {
+            generation: grid.generation(),
+            flipped,
+        });
+        self.last.clone_from(grid);
+        Ok(())
+    }
+
+    /// How many steps were recorded after the starting board
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn boundary(&self) -> BoundaryCondition {
+        self.boundary
+    }
+
+    /// The last board of the run, with a timeline holding every step to it
+    /// and at least `capacity` generations, to step back to the start from
+    pub fn play(&self, capacity: usize) -> (Grid, Timeline) {
+        let mut grid = self.start.clone();
+        grid.set_rule(self.rule);
+        grid.set_boundary(self.boundary);
+        let mut timeline = Timeline::new(capacity.max(self.steps.len()));
+        let width = grid.width();
+        for step in &self.steps {
+            let from = grid.generation();
+            for &i in &step.flipped {
+                grid.toggle_born(i % width, i / width);
+            }
+            grid.set_generation(step.generation);
+            timeline.record_flips(from, step.generation, step.flipped.clone());
+        }
+        (grid, timeline)
+    }
+
+    /// The recording as text: a header with the format version, the size,
+    /// rule and edges, the starting board and a hash of the last one, then a
+    /// `step` line per recorded board with its generation and the flipped
+    /// cells, each as the distance from the one before
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "{MAGIC}\nversion {RUN_FORMAT_VERSION}\nsize {}x{}\nrule {}\nedges {}\ngeneration {}\nboard {}\nlast {:016x}\n",
+            self.start.width(),
+            self.start.height(),
+            self.rule,
+            self.boundary.label(),
+            self.start.generation(),
+            rle::encode_line(&self.start),
+            self.last.content_hash()
+        );
+        for step in &self.steps {
+            out.push_str(&format!("step {}", step.generation));
+            let mut previous = 0;
+            for &i in &step.flipped {
+                out.push_str(&format!(" {}", i - previous));
+                previous = i;
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn parse(text: &str) -> Result<RunRecording, FormatError> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let mut field = |name: &str| {
+            let (number, line) = lines
+                .next()
+                .ok_or_else(|| FormatError::new(0, format!("missing '{name}' line")))?;
+            let value = if name == MAGIC {
+                (line == MAGIC).then_some("")
+            } else {
+                line.strip_prefix(name)
+                    .and_then(|rest| rest.strip_prefix(' '))
+            };
+            value
+                .map(|value| (number, value.to_string()))
+                .ok_or_else(|| FormatError::new(number, format!("expected '{name}'")))
+        };
+        field(MAGIC)?;
+        let (number, version) = field("version")?;
+        if version != RUN_FORMAT_VERSION.to_string() {
+            return Err(FormatError::new(
+                number,
+                format!("written in format version {version}, this build reads version {RUN_FORMAT_VERSION}"),
+            ));
+        }
+        let (number, size) = field("size")?;
+        let (width, height) = size
+            .split_once('x')
+            .and_then(|(w, h)| Some((w.parse::<usize>().ok()?, h.parse::<usize>().ok()?)))
+            .ok_or_else(|| FormatError::new(number, format!("invalid size '{size}'")))?;
+        let (number, rule) = field("rule")?;
+        let rule = rule
+            .parse()
+            .map_err(|err| FormatError::new(number, format!("invalid rule '{rule}': {err}")))?;
+        let (number, edges) = field("edges")?;
+        let boundary = [BoundaryCondition::Dead, BoundaryCondition::Wrap]
+            .into_iter()
+            .find(|boundary| boundary.label() == edges)
+            .ok_or_else(|| FormatError::new(number, format!("unknown edges '{edges}'")))?;
+        let (number, generation) = field("generation")?;
+        let generation = generation
+            .parse()
+            .map_err(|_| FormatError::new(number, "invalid generation"))?;
+        let (number, board) = field("board")?;
+        let mut start = rle::decode_line::<Grid>(width, height, &board)
+            .map_err(|err| FormatError::new(number, format!("invalid board: {}", err.message)))?;
+        start.set_generation(generation);
+        start.set_rule(rule);
+        start.set_boundary(boundary);
+        let (last_number, last) = field("last")?;
+        let last = u64::from_str_radix(&last, 16)
+            .map_err(|_| FormatError::new(last_number, "invalid board hash"))?;
+
+        let mut recording = RunRecording::new(&start);
+        let cells = width * height;
+        for (number, line) in lines {
+            let mut words = line
+                .strip_prefix("step ")
+                .ok_or_else(|| FormatError::new(number, "expected 'step'"))?
+                .split_whitespace();
+            let generation = words
+                .next()
+                .and_then(|word| word.parse().ok())
+                .ok_or_else(|| FormatError::new(number, "invalid generation"))?;
+            let mut flipped = Vec::new();
+            let mut previous = 0;
+            for word in words {
+                let i = word
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&distance| distance > 0 || flipped.is_empty())
+                    .map(|distance| previous + distance)
+                    .filter(|&i| i < cells)
+                    .ok_or_else(|| FormatError::new(number, "invalid flipped cell"))?;
+                flipped.push(i);
+                previous = i;
+            }
+            recording.steps.push(Step {
+                generation,
+                flipped,
+            });
+        }
+        // Played through, the steps have to land on the board that was recorded last
+        let (end, _) = recording.play(0);
+        if end.content_hash() != last {
+            return Err(FormatError::new(
+                last_number,
+                "the steps don't lead to the last board recorded",
+            ));
+        }
+        recording.last = end;
+        Ok(recording)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ConwayError> {
+        fs::write(path, self.to_text())?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<RunRecording, ConwayError> {
+        Self::parse(&fs::read_to_string(path)?)
+            .map_err(|err| ConwayError::parse("run recording", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::CellState;
+
+    /// An R-pentomino on a wrapping board, recorded for `steps` generations
+    fn recorded(steps: usize) -> (Grid, RunRecording) {
+        let mut grid = Grid::new(24, 20);
+        grid.set_boundary(BoundaryCondition::Wrap);
+        for (x, y) in [(10, 9), (11, 9), (9, 10), (10, 10), (10, 11)] {
+            grid.set(x, y, CellState::Alive);
+        }
+        let mut recording = RunRecording::new(&grid);
+        for _ in 0..steps {
+            grid.next_cell_generation();
+            recording.record(&grid).unwrap();
+        }
+        (grid, recording)
+    }
+
+    #[test]
+    fn test_text_round_trip_reaches_the_final_board() {
+        let (end, recording) = recorded(60);
+        let text = recording.to_text();
+        assert!(
+            text.starts_with("game-of-life run\nversion 1\nsize 24x20\nrule B3/S23\nedges wrap\n")
+        );
+        let parsed = RunRecording::parse(&text).unwrap();
+        assert_eq!(parsed, recording);
+        assert_eq!(parsed.len(), 60);
+
+        let (played, _) = parsed.play(0);
+        assert_eq!(played.content_hash(), end.content_hash());
+        assert_eq!(played.generation(), 60);
+        assert_eq!(played.boundary(), BoundaryCondition::Wrap);
+    }
+
+    #[test]
+    fn test_playback_scrubs_both_ways() {
+        let (end, recording) = recorded(30);
+        let (mut grid, mut timeline) = recording.play(10);
+        assert_eq!(timeline.len(), 31);
+        timeline.seek(&mut grid, 0);
+        assert_eq!(grid.generation(), 0);
+        assert_eq!(grid.content_hash(), recorded(0).0.content_hash());
+
+        let (middle, _) = recorded(17);
+        timeline.seek(&mut grid, 17);
+        assert_eq!(grid, middle);
+        assert_eq!(grid.generation(), 17);
+        timeline.seek(&mut grid, 30);
+        assert_eq!(grid, end);
+    }
+
+    #[test]
+    fn test_edits_between_generations_are_kept() {
+        let (mut grid, mut recording) = recorded(5);
+        grid.set(0, 0, CellState::Alive);
+        grid.next_cell_generation();
+        recording.record(&grid).unwrap();
+        // Recording the same board again adds nothing
+        recording.record(&grid).unwrap();
+        assert_eq!(recording.len(), 6);
+        let (played, _) = recording.play(0);
+        assert_eq!(played, grid);
+
+        // A resized board can't be recorded
+        assert!(matches!(
+            recording.record(&Grid::new(5, 5)),
+            Err(ConwayError::DimensionMismatch {
+                expected: (24, 20),
+                found: (5, 5)
+            })
+        ));
+        assert_eq!(recording.len(), 6);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let (_, recording) = recorded(3);
+        let text = recording.to_text();
+
+        let newer = text.replace("version 1", "version 2");
+        let err = RunRecording::parse(&newer).unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.contains("format version 2"));
+
+        // A flipped cell past the board
+        let err = RunRecording::parse(&format!("{text}step 4 9999\n")).unwrap_err();
+        assert_eq!(
+            (err.line, err.message.as_str()),
+            (12, "invalid flipped cell")
+        );
+
+        // A step that was tampered with no longer lands on the last board
+        let mut lines: Vec<&str> = text.lines().collect();
+        lines[10] = "step 3";
+        let err = RunRecording::parse(&lines.join("\n")).unwrap_err();
+        assert_eq!(err.line, 8);
+
+        assert!(RunRecording::parse("game-of-life replay\n").is_err());
+    }
+}
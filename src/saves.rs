@@ -0,0 +1,138 @@
+use crate::conways::Grid;
+use crate::formats::{self, rle};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory where grids are saved, relative to the working directory
+pub const SAVE_DIR: &str = "saves";
+
+/// Write a grid to disk as RLE, creating the parent directory if needed
+/// The rulestring goes in the header, and a comment line records the
+/// generation number and, for randomized boards, the seed.
+///
+/// # Arguments
+/// * `grid` - The grid to save
+/// * `path` - The file to write
+///
+/// # Example
+/// ```
+/// let grid = Grid::new(10, 10);
+/// save_grid(&grid, Path::new("saves/empty.rle"))?;
+/// ```
+pub fn save_grid(grid: &Grid, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut comment = format!("generation {}", grid.generation());
+    if let Some(seed) = grid.seed() {
+        comment.push_str(&format!(", seed {seed:#x}"));
+    }
+
+    fs::write(
+        path,
+        rle::encode(grid, &grid.rule().to_string(), &[comment]),
+    )
+}
+
+/// Every loadable file in `SAVE_DIR`, newest first
+/// A missing directory simply means there are no saves yet
+pub fn list_saves() -> io::Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(SAVE_DIR) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+
+    let mut saves = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        let supported = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                formats::SUPPORTED_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str())
+            });
+        if supported {
+            saves.push((entry.metadata()?.modified()?, path));
+        }
+    }
+
+    // Newest first, falling back to the (timestamped) name for equal mtimes
+    saves.sort_by(|a, b| b.cmp(a));
+    Ok(saves.into_iter().map(|(_, path)| path).collect())
+}
+
+/// A fresh path in `SAVE_DIR` named after the current UTC time,
+/// e.g. `saves/grid-20240601-134502.rle`
+pub fn timestamped_path() -> PathBuf {
+    Path::new(SAVE_DIR).join(format!("grid-{}.rle", timestamp_now()))
+}
+
+/// The current UTC time as a `YYYYMMDD-HHMMSS` timestamp, for file names
+pub fn timestamp_now() -> String {
+    // miniquad's clock also works in the browser, unlike `SystemTime::now`
+    format_timestamp(macroquad::miniquad::date::now() as u64)
+}
+
+/// Format seconds since the Unix epoch as a `YYYYMMDD-HHMMSS` UTC timestamp
+fn format_timestamp(secs: u64) -> String {
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_from_days(days as i64);
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// Convert days since 1970-01-01 into a `(year, month, day)` civil date
+/// see more: <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::CellState;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "19700101-000000");
+        assert_eq!(format_timestamp(1_717_249_502), "20240601-134502");
+        // Leap day
+        assert_eq!(format_timestamp(951_782_400), "20000229-000000");
+    }
+
+    #[test]
+    fn test_save_grid_round_trip() {
+        let dir = std::env::temp_dir().join(format!("conways-saves-{}", std::process::id()));
+        let path = dir.join("nested").join("grid.rle");
+
+        let mut grid = Grid::new(16, 12);
+        grid.randomize(0.4, 0xbeef);
+        grid.next_cell_generation();
+        grid.set(15, 11, CellState::Alive);
+
+        save_grid(&grid, &path).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("#C generation 1, seed 0xbeef\n"));
+        assert!(text.contains("rule = B3/S23"));
+        assert_eq!(rle::decode::<Grid>(&text).unwrap(), grid);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
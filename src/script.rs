@@ -0,0 +1,904 @@
+//! Seeding a board from a script, for `--script seed.rhai` with the
+//! `scripting` feature: a small language in the manner of rhai, which isn't
+//! available to this build, run by an evaluator of its own
+//!
+//! A script is statements run top to bottom, separated by `;`:
+//! - `let x = 3;`, `x = x + 1;` and `x += 1;`, with `-=`, `*=`, `/=` and `%=`
+//! - `if .. { } else if .. { } else { }`, `while .. { }`, and
+//!   `for i in 0..n { }` over `0..n` or `0..=n`, with `break` and `continue`
+//! - integers, floats, `"strings"`, `true` and `false`, the arithmetic and
+//!   comparison operators, `&&`, `||` and `!`
+//! - `//` and `/* */` comments
+//!
+//! and the functions `set(x, y)`, bringing a cell to life, `width()`,
+//! `height()`, `rand()`, a float in `[0, 1)`, and `rule("B36/S23")`.
+//!
+//! Every statement and expression evaluated takes a step of the budget given
+//! to [`Script::run`], so a script that never ends is stopped rather than
+//! hanging the app. Errors say the line they were found on.
+//!
+//! ```
+//! use game_of_life::conways::Grid;
+//! use game_of_life::rng::SplitMix64;
+//! use game_of_life::script::{Script, DEFAULT_BUDGET};
+//!
+//! let script = Script::parse("for x in 0..width() { set(x, height() / 2); }").unwrap();
+//! let mut grid = Grid::new(10, 5);
+//! script.run(&mut grid, &mut SplitMix64::new(1), DEFAULT_BUDGET).unwrap();
+//! assert_eq!(grid.population(), 10);
+//! ```
+
+use crate::conways::{CellState, Grid};
+use crate::formats::FormatError;
+use crate::rng::Rng;
+use crate::rule::Rule;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Steps a script gets when nothing else is said, enough for two loops to set
+/// every cell of a 4096x4096 board, the largest `--width` and `--height` allow
+pub const DEFAULT_BUDGET: u64 = 100_000_000;
+
+/// Operators, longest first so that `..=` isn't read as `..` and `=`
+const PUNCTUATION: [&str; 28] = [
+    "..=", "..", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=", "*=", "/=", "%=", "+", "-", "*",
+    "/", "%", "<", ">", "=", "!", "(", ")", "{", "}", ",", ";",
+];
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Ident(String),
+    Punct(&'static str),
+}
+
+/// The tokens of `source` with the lines they're on
+fn lex(source: &str) -> Result<Vec<(Token, usize)>, FormatError> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        if c == '\n' {
+            line += 1;
+        }
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if rest.starts_with("//") {
+            rest = rest.find('\n').map_or("", |end| &rest[end..]);
+        } else if rest.starts_with("/*") {
+            let end = rest
+                .find("*/")
+                .ok_or_else(|| FormatError::new(line, "a comment that's never closed"))?;
+            line += rest[..end].matches('\n').count();
+            rest = &rest[end + 2..];
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '_')
+                .unwrap_or(rest.len());
+            // A float needs a digit after its point, so `0..3` stays a range
+            let fraction = rest[len..]
+                .strip_prefix('.')
+                .filter(|after| after.starts_with(|c: char| c.is_ascii_digit()))
+                .map(|after| {
+                    1 + after
+                        .find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(after.len())
+                });
+            let text = &rest[..len + fraction.unwrap_or(0)];
+            let digits = text.replace('_', "");
+            tokens.push((
+                match fraction {
+                    Some(_) => Token::Float(digits.parse().expect("digits, a point and digits")),
+                    None => Token::Int(
+                        digits
+                            .parse()
+                            .map_err(|_| FormatError::new(line, format!("{text} is too big")))?,
+                    ),
+                },
+                line,
+            ));
+            rest = &rest[text.len()..];
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push((Token::Ident(rest[..len].to_string()), line));
+            rest = &rest[len..];
+        } else if c == '"' {
+            let end = rest[1..]
+                .find(['"', '\n'])
+                .filter(|&end| rest[1 + end..].starts_with('"'))
+                .ok_or_else(|| FormatError::new(line, "a string that's never closed"))?;
+            tokens.push((Token::Str(rest[1..1 + end].to_string()), line));
+            rest = &rest[end + 2..];
+        } else if let Some(punct) = PUNCTUATION.iter().find(|punct| rest.starts_with(**punct)) {
+            tokens.push((Token::Punct(punct), line));
+            rest = &rest[punct.len()..];
+        } else {
+            return Err(FormatError::new(
+                line,
+                format!("unexpected character {c:?}"),
+            ));
+        }
+    }
+    Ok(tokens)
+}
+
+/// What an expression evaluates to
+#[derive(Clone, PartialEq, Debug)]
+enum Value {
+    /// What a statement or `set` gives back
+    Unit,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl Value {
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Unit => "nothing",
+            Value::Bool(_) => "a bool",
+            Value::Int(_) => "an integer",
+            Value::Float(_) => "a float",
+            Value::Str(_) => "a string",
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Int(n) => Some(n as f64),
+            Value::Float(n) => Some(n),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Int(n) => write!(f, "{n}"),
+            Value::Float(n) => write!(f, "{n}"),
+            Value::Str(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Expr {
+    kind: ExprKind,
+    line: usize,
+}
+
+#[derive(Debug)]
+enum ExprKind {
+    Literal(Value),
+    Var(String),
+    Call(String, Vec<Expr>),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug)]
+struct Stmt {
+    kind: StmtKind,
+    line: usize,
+}
+
+#[derive(Debug)]
+enum StmtKind {
+    Let(String, Expr),
+    /// `x = ..`, or `x += ..` and the like with the operator applied
+    Assign(String, Option<&'static str>, Expr),
+    /// Each condition with its block, then the `else` block
+    If(Vec<(Expr, Vec<Stmt>)>, Vec<Stmt>),
+    While(Expr, Vec<Stmt>),
+    /// The variable, the range's ends, whether it includes the last, and the body
+    For(String, Expr, Expr, bool, Vec<Stmt>),
+    Break,
+    Continue,
+    Expr(Expr),
+}
+
+/// Binary operators from the loosest binding to the tightest
+const PRECEDENCE: [&[&str]; 5] = [
+    &["||"],
+    &["&&"],
+    &["==", "!=", "<", "<=", ">", ">="],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    at: usize,
+}
+
+impl Parser {
+    /// The line of the next token, or of the last one at the end
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.at)
+            .or(self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.at).map(|(token, _)| token)
+    }
+
+    fn error(&self, message: impl Into<String>) -> FormatError {
+        FormatError::new(self.line(), message)
+    }
+
+    /// Take the next token if it's `punct`
+    fn eat(&mut self, punct: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Punct(p)) if *p == punct);
+        self.at += found as usize;
+        found
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Ident(name)) if name == keyword);
+        self.at += found as usize;
+        found
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), FormatError> {
+        match self.eat(punct) {
+            true => Ok(()),
+            false => Err(self.error(format!("expected `{punct}`"))),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, FormatError> {
+        match self.peek() {
+            Some(Token::Ident(name)) if !is_keyword(name) => {
+                let name = name.clone();
+                self.at += 1;
+                Ok(name)
+            }
+            _ => Err(self.error("expected a name")),
+        }
+    }
+
+    /// Statements up to the end of the script, or of the block when `block`
+    fn statements(&mut self, block: bool) -> Result<Vec<Stmt>, FormatError> {
+        let mut statements = Vec::new();
+        loop {
+            while self.eat(";") {}
+            match self.peek() {
+                None if block => return Err(self.error("expected `}`")),
+                None => return Ok(statements),
+                Some(Token::Punct("}")) if block => {
+                    self.at += 1;
+                    return Ok(statements);
+                }
+                _ => (),
+            }
+            let (statement, needs_end) = self.statement()?;
+            statements.push(statement);
+            // Statements end with `;`, or with the block or script they're last in
+            let ends = matches!(self.peek(), None | Some(Token::Punct(";" | "}")));
+            if needs_end && !ends {
+                return Err(self.error("expected `;`"));
+            }
+        }
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, FormatError> {
+        self.expect("{")?;
+        self.statements(true)
+    }
+
+    /// The next statement, and whether a `;` has to follow it
+    fn statement(&mut self) -> Result<(Stmt, bool), FormatError> {
+        let line = self.line();
+        let (kind, needs_end) = if self.eat_keyword("let") {
+            let name = self.ident()?;
+            self.expect("=")?;
+            (StmtKind::Let(name, self.expr()?), true)
+        } else if self.eat_keyword("if") {
+            let mut branches = Vec::from([(self.expr()?, self.block()?)]);
+            let mut otherwise = Vec::new();
+            while self.eat_keyword("else") {
+                if self.eat_keyword("if") {
+                    branches.push((self.expr()?, self.block()?));
+                } else {
+                    otherwise = self.block()?;
+                    break;
+                }
+            }
+            (StmtKind::If(branches, otherwise), false)
+        } else if self.eat_keyword("while") {
+            (StmtKind::While(self.expr()?, self.block()?), false)
+        } else if self.eat_keyword("for") {
+            let name = self.ident()?;
+            if !self.eat_keyword("in") {
+                return Err(self.error("expected `in`"));
+            }
+            let from = self.expr()?;
+            let inclusive = match () {
+                () if self.eat("..") => false,
+                () if self.eat("..=") => true,
+                () => return Err(self.error("expected `..` or `..=`")),
+            };
+            let to = self.expr()?;
+            (
+                StmtKind::For(name, from, to, inclusive, self.block()?),
+                false,
+            )
+        } else if self.eat_keyword("break") {
+            (StmtKind::Break, true)
+        } else if self.eat_keyword("continue") {
+            (StmtKind::Continue, true)
+        } else {
+            let assigned = match (self.peek(), self.tokens.get(self.at + 1)) {
+                (Some(Token::Ident(name)), Some((Token::Punct(op), _)))
+                    if !is_keyword(name) && ["=", "+=", "-=", "*=", "/=", "%="].contains(op) =>
+                {
+                    Some((name.clone(), *op))
+                }
+                _ => None,
+            };
+            match assigned {
+                Some((name, op)) => {
+                    self.at += 2;
+                    let op = PRECEDENCE[3..]
+                        .iter()
+                        .flat_map(|ops| ops.iter())
+                        .find(|binary| op.strip_suffix('=') == Some(**binary))
+                        .copied();
+                    (StmtKind::Assign(name, op, self.expr()?), true)
+                }
+                None => (StmtKind::Expr(self.expr()?), true),
+            }
+        };
+        Ok((Stmt { kind, line }, needs_end))
+    }
+
+    fn expr(&mut self) -> Result<Expr, FormatError> {
+        self.binary(0)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, FormatError> {
+        let Some(ops) = PRECEDENCE.get(level) else {
+            return self.unary();
+        };
+        let mut left = self.binary(level + 1)?;
+        loop {
+            let line = self.line();
+            let op = match self.peek() {
+                Some(Token::Punct(op)) if ops.contains(op) => *op,
+                _ => return Ok(left),
+            };
+            self.at += 1;
+            let right = self.binary(level + 1)?;
+            left = Expr {
+                kind: ExprKind::Binary(op, Box::new(left), Box::new(right)),
+                line,
+            };
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, FormatError> {
+        let line = self.line();
+        for op in ["-", "!"] {
+            if self.eat(op) {
+                let operand = self.unary()?;
+                return Ok(Expr {
+                    kind: ExprKind::Unary(op, Box::new(operand)),
+                    line,
+                });
+            }
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, FormatError> {
+        let line = self.line();
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error("expected a value"));
+        };
+        self.at += 1;
+        let kind = match token {
+            Token::Int(n) => ExprKind::Literal(Value::Int(n)),
+            Token::Float(n) => ExprKind::Literal(Value::Float(n)),
+            Token::Str(s) => ExprKind::Literal(Value::Str(s)),
+            Token::Ident(name) if name == "true" || name == "false" => {
+                ExprKind::Literal(Value::Bool(name == "true"))
+            }
+            Token::Ident(name) if !is_keyword(&name) => {
+                if !self.eat("(") {
+                    ExprKind::Var(name)
+                } else {
+                    let mut args = Vec::new();
+                    while !self.eat(")") {
+                        if !args.is_empty() {
+                            self.expect(",")?;
+                        }
+                        args.push(self.expr()?);
+                    }
+                    ExprKind::Call(name, args)
+                }
+            }
+            Token::Punct("(") => {
+                let inner = self.expr()?;
+                self.expect(")")?;
+                return Ok(inner);
+            }
+            _ => {
+                self.at -= 1;
+                return Err(self.error("expected a value"));
+            }
+        };
+        Ok(Expr { kind, line })
+    }
+}
+
+fn is_keyword(name: &str) -> bool {
+    [
+        "let", "if", "else", "while", "for", "in", "break", "continue", "true", "false",
+    ]
+    .contains(&name)
+}
+
+/// A script read and checked, to run onto as many boards as wanted, see the
+/// [module docs](self)
+#[derive(Debug)]
+pub struct Script {
+    statements: Vec<Stmt>,
+}
+
+/// How a statement let the ones after it run
+enum Flow {
+    Next,
+    Break,
+    Continue,
+}
+
+/// A script running onto a board
+struct Run<'a, R> {
+    grid: &'a mut Grid,
+    rng: &'a mut R,
+    budget: u64,
+    steps: u64,
+    /// Variables of every block the script is in, innermost last
+    scopes: Vec<Vec<(String, Value)>>,
+}
+
+impl<R: Rng> Run<'_, R> {
+    /// Take a step of the budget on `line`
+    fn step(&mut self, line: usize) -> Result<(), FormatError> {
+        self.steps += 1;
+        match self.steps > self.budget {
+            true => Err(FormatError::new(
+                line,
+                format!("stopped after {} steps, the script's budget", self.budget),
+            )),
+            false => Ok(()),
+        }
+    }
+
+    fn block(&mut self, statements: &[Stmt]) -> Result<Flow, FormatError> {
+        self.scopes.push(Vec::new());
+        let mut flow = Ok(Flow::Next);
+        for statement in statements {
+            flow = self.statement(statement);
+            if !matches!(flow, Ok(Flow::Next)) {
+                break;
+            }
+        }
+        self.scopes.pop();
+        flow
+    }
+
+    fn variable(&mut self, name: &str, line: usize) -> Result<&mut Value, FormatError> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .flat_map(|scope| scope.iter_mut().rev())
+            .find(|(defined, _)| defined == name)
+            .map(|(_, value)| value)
+            .ok_or_else(|| FormatError::new(line, format!("`{name}` isn't defined")))
+    }
+
+    fn statement(&mut self, statement: &Stmt) -> Result<Flow, FormatError> {
+        let line = statement.line;
+        self.step(line)?;
+        match &statement.kind {
+            StmtKind::Let(name, value) => {
+                let value = self.expr(value)?;
+                let scope = self.scopes.last_mut().expect("a block is being run");
+                scope.push((name.clone(), value));
+            }
+            StmtKind::Assign(name, op, value) => {
+                let mut value = self.expr(value)?;
+                if let Some(op) = op {
+                    let old = self.variable(name, line)?.clone();
+                    value = binary(op, old, value, line)?;
+                }
+                *self.variable(name, line)? = value;
+            }
+            StmtKind::If(branches, otherwise) => {
+                for (condition, body) in branches {
+                    if self.condition(condition)? {
+                        return self.block(body);
+                    }
+                }
+                return self.block(otherwise);
+            }
+            StmtKind::While(condition, body) => {
+                while self.condition(condition)? {
+                    if let Flow::Break = self.block(body)? {
+                        break;
+                    }
+                }
+            }
+            StmtKind::For(name, from, to, inclusive, body) => {
+                let (from, to) = (self.int(from)?, self.int(to)?);
+                let mut i = from;
+                while i < to || (*inclusive && i == to) {
+                    self.step(line)?;
+                    self.scopes.push(Vec::from([(name.clone(), Value::Int(i))]));
+                    let flow = self.block(body);
+                    self.scopes.pop();
+                    if let Flow::Break = flow? {
+                        break;
+                    }
+                    match i.checked_add(1) {
+                        Some(next) => i = next,
+                        None => break,
+                    }
+                }
+            }
+            StmtKind::Break => return Ok(Flow::Break),
+            StmtKind::Continue => return Ok(Flow::Continue),
+            StmtKind::Expr(expr) => {
+                self.expr(expr)?;
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn condition(&mut self, expr: &Expr) -> Result<bool, FormatError> {
+        match self.expr(expr)? {
+            Value::Bool(b) => Ok(b),
+            value => Err(FormatError::new(
+                expr.line,
+                format!("expected true or false, found {}", value.kind()),
+            )),
+        }
+    }
+
+    fn int(&mut self, expr: &Expr) -> Result<i64, FormatError> {
+        match self.expr(expr)? {
+            Value::Int(n) => Ok(n),
+            value => Err(FormatError::new(
+                expr.line,
+                format!("expected an integer, found {}", value.kind()),
+            )),
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Result<Value, FormatError> {
+        let line = expr.line;
+        self.step(line)?;
+        match &expr.kind {
+            ExprKind::Literal(value) => Ok(value.clone()),
+            ExprKind::Var(name) => self.variable(name, line).map(|value| value.clone()),
+            ExprKind::Unary(op, operand) => match (*op, self.expr(operand)?) {
+                ("-", Value::Int(n)) => n
+                    .checked_neg()
+                    .map(Value::Int)
+                    .ok_or_else(|| FormatError::new(line, "the integer overflowed")),
+                ("-", Value::Float(n)) => Ok(Value::Float(-n)),
+                ("!", Value::Bool(b)) => Ok(Value::Bool(!b)),
+                (op, value) => Err(FormatError::new(
+                    line,
+                    format!("`{op}` doesn't go with {}", value.kind()),
+                )),
+            },
+            ExprKind::Binary(op @ ("&&" | "||"), left, right) => {
+                // The right side is only looked at when it decides
+                let left = self.condition(left)?;
+                match (*op, left) {
+                    ("&&", false) | ("||", true) => Ok(Value::Bool(left)),
+                    _ => self.condition(right).map(Value::Bool),
+                }
+            }
+            ExprKind::Binary(op, left, right) => {
+                let left = self.expr(left)?;
+                let right = self.expr(right)?;
+                binary(op, left, right, line)
+            }
+            ExprKind::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.expr(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.call(name, args, line)
+            }
+        }
+    }
+
+    fn call(&mut self, name: &str, args: Vec<Value>, line: usize) -> Result<Value, FormatError> {
+        let arity = match name {
+            "set" => 2,
+            "rule" => 1,
+            "width" | "height" | "rand" => 0,
+            _ => return Err(FormatError::new(line, format!("no function `{name}`"))),
+        };
+        if args.len() != arity {
+            return Err(FormatError::new(
+                line,
+                format!("`{name}` takes {arity} arguments, not {}", args.len()),
+            ));
+        }
+        let (width, height) = (self.grid.width(), self.grid.height());
+        match (name, &args[..]) {
+            ("set", [Value::Int(x), Value::Int(y)]) => {
+                let cell = usize::try_from(*x)
+                    .ok()
+                    .zip(usize::try_from(*y).ok())
+                    .filter(|&(x, y)| x < width && y < height);
+                let Some((x, y)) = cell else {
+                    return Err(FormatError::new(
+                        line,
+                        format!("({x}, {y}) is off the {width}x{height} board"),
+                    ));
+                };
+                self.grid.set(x, y, CellState::Alive);
+                Ok(Value::Unit)
+            }
+            ("rule", [Value::Str(rule)]) => {
+                let rule: Rule = rule
+                    .parse()
+                    .map_err(|err| FormatError::new(line, format!("invalid rule: {err}")))?;
+                self.grid.set_rule(rule);
+                Ok(Value::Unit)
+            }
+            ("width", []) => Ok(Value::Int(width as i64)),
+            ("height", []) => Ok(Value::Int(height as i64)),
+            ("rand", []) => Ok(Value::Float(self.rng.next_f64())),
+            (_, args) => Err(FormatError::new(
+                line,
+                format!(
+                    "`{name}` doesn't take {}",
+                    args.iter()
+                        .map(Value::kind)
+                        .collect::<Vec<_>>()
+                        .join(" and ")
+                ),
+            )),
+        }
+    }
+}
+
+/// `left op right`, for every operator but `&&` and `||`
+fn binary(op: &str, left: Value, right: Value, line: usize) -> Result<Value, FormatError> {
+    let overflow = || FormatError::new(line, "the integer overflowed");
+    let value = match (op, &left, &right) {
+        ("/" | "%", Value::Int(_), Value::Int(0)) => {
+            return Err(FormatError::new(line, "division by zero"))
+        }
+        ("+", Value::Int(a), Value::Int(b)) => Value::Int(a.checked_add(*b).ok_or_else(overflow)?),
+        ("-", Value::Int(a), Value::Int(b)) => Value::Int(a.checked_sub(*b).ok_or_else(overflow)?),
+        ("*", Value::Int(a), Value::Int(b)) => Value::Int(a.checked_mul(*b).ok_or_else(overflow)?),
+        ("/", Value::Int(a), Value::Int(b)) => Value::Int(a.checked_div(*b).ok_or_else(overflow)?),
+        ("%", Value::Int(a), Value::Int(b)) => Value::Int(a.checked_rem(*b).ok_or_else(overflow)?),
+        ("+", Value::Str(_), _) | ("+", _, Value::Str(_)) => Value::Str(format!("{left}{right}")),
+        ("==", _, _) if left.kind() == right.kind() => Value::Bool(left == right),
+        ("!=", _, _) if left.kind() == right.kind() => Value::Bool(left != right),
+        ("<" | "<=" | ">" | ">=", Value::Str(a), Value::Str(b)) => Value::Bool(compare(op, a, b)),
+        _ => match (left.as_f64(), right.as_f64()) {
+            (Some(a), Some(b)) => match op {
+                "+" => Value::Float(a + b),
+                "-" => Value::Float(a - b),
+                "*" => Value::Float(a * b),
+                "/" => Value::Float(a / b),
+                "%" => Value::Float(a % b),
+                "==" => Value::Bool(a == b),
+                "!=" => Value::Bool(a != b),
+                _ => Value::Bool(compare(op, &a, &b)),
+            },
+            _ => {
+                return Err(FormatError::new(
+                    line,
+                    format!(
+                        "`{op}` doesn't go between {} and {}",
+                        left.kind(),
+                        right.kind()
+                    ),
+                ))
+            }
+        },
+    };
+    Ok(value)
+}
+
+fn compare<T: PartialOrd + ?Sized>(op: &str, a: &T, b: &T) -> bool {
+    match op {
+        "<" => a < b,
+        "<=" => a <= b,
+        ">" => a > b,
+        _ => a >= b,
+    }
+}
+
+impl Script {
+    /// Read a script, without running it
+    ///
+    /// # Returns
+    /// The script, or a `FormatError` on the line it stopped making sense
+    pub fn parse(source: &str) -> Result<Self, FormatError> {
+        let mut parser = Parser {
+            tokens: lex(source)?,
+            at: 0,
+        };
+        Ok(Self {
+            statements: parser.statements(false)?,
+        })
+    }
+
+    /// Run the script onto `grid`, drawing `rand()` from `rng` and taking at
+    /// most `budget` steps
+    ///
+    /// The cells it sets are brought to life on top of what's on the board
+    /// already, which is left as the script left it when it fails.
+    ///
+    /// # Returns
+    /// A `FormatError` on the line the script failed on, or ran out of
+    /// budget on
+    pub fn run(&self, grid: &mut Grid, rng: &mut impl Rng, budget: u64) -> Result<(), FormatError> {
+        let mut run = Run {
+            grid,
+            rng,
+            budget,
+            steps: 0,
+            scopes: Vec::new(),
+        };
+        match run.block(&self.statements)? {
+            Flow::Next => Ok(()),
+            // `break` and `continue` outside a loop end the script early
+            Flow::Break | Flow::Continue => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SplitMix64;
+
+    fn run(source: &str, width: usize, height: usize) -> Result<Grid, FormatError> {
+        let mut grid = Grid::new(width, height);
+        Script::parse(source)?.run(&mut grid, &mut SplitMix64::new(7), DEFAULT_BUDGET)?;
+        Ok(grid)
+    }
+
+    #[test]
+    fn test_the_bundled_example_seeds_its_board() {
+        let grid = run(include_str!("../scripts/seed.rhai"), 64, 48).unwrap();
+        // The ring and the diagonal are fixed, the noise in the middle drawn from the seed
+        assert_eq!(grid.population(), 374);
+        assert_eq!(grid.rule(), "B36/S23".parse().unwrap());
+        assert_eq!(grid.get(0, 0), CellState::Alive);
+    }
+
+    #[test]
+    fn test_control_flow_and_arithmetic() {
+        let source = r#"
+            let n = 0;
+            for i in 0..=9 {
+                if i % 2 == 0 { continue; }   // odd only
+                n += i;
+            }
+            /* 1 + 3 + 5 + 7 + 9 */
+            let x = 0;
+            while true {
+                x = x + 1;
+                if x * x > n { break }
+            }
+            let label = "cell " + x;
+            if label == "cell 6" && !(2.5 < 1) { set(x, n / 25); } else { set(0, 0); }
+        "#;
+        let grid = run(source, 8, 8).unwrap();
+        assert_eq!(grid.population(), 1);
+        assert_eq!(grid.get(6, 1), CellState::Alive);
+    }
+
+    #[test]
+    fn test_blocks_scope_their_variables() {
+        let source = "let a = 1; if true { let a = 2; set(a, 0); } set(a, 0);";
+        let grid = run(source, 3, 1).unwrap();
+        assert_eq!(grid.population(), 2);
+        let err = run("if true { let b = 1; } set(b, 0);", 3, 1).unwrap_err();
+        assert_eq!(err, FormatError::new(1, "`b` isn't defined"));
+    }
+
+    #[test]
+    fn test_errors_say_their_line() {
+        let cases = [
+            ("set(1, 2)\nset(9, 0);", 2, "expected `;`"),
+            ("let x = ;", 1, "expected a value"),
+            (
+                "\n\nfor i in 0..3 { set(i) }",
+                3,
+                "`set` takes 2 arguments, not 1",
+            ),
+            ("/* a\ncomment */ oops();", 2, "no function `oops`"),
+            ("\nset(-1, 0);", 2, "(-1, 0) is off the 4x4 board"),
+            ("let s = \"open", 1, "a string that's never closed"),
+            (
+                "rule(\"B9/S\");",
+                1,
+                "invalid rule: '9' isn't a neighbor count from 0 to 8",
+            ),
+            ("if 1 { }", 1, "expected true or false, found an integer"),
+            ("let y = 3 / (2 - 2);", 1, "division by zero"),
+            (
+                "let z = \"a\" * 2;",
+                1,
+                "`*` doesn't go between a string and an integer",
+            ),
+            (
+                "set(0.5, 1);",
+                1,
+                "`set` doesn't take a float and an integer",
+            ),
+            (
+                "let big = 99999999999999999999;",
+                1,
+                "99999999999999999999 is too big",
+            ),
+        ];
+        for (source, line, message) in cases {
+            assert_eq!(
+                run(source, 4, 4).map(|_| ()),
+                Err(FormatError::new(line, message)),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_endless_scripts_run_out_of_budget() {
+        let script = Script::parse("let i = 0;\nwhile true {\n  i += 1;\n}").unwrap();
+        let mut grid = Grid::new(4, 4);
+        let err = script
+            .run(&mut grid, &mut SplitMix64::new(1), 1000)
+            .unwrap_err();
+        assert!((2..=3).contains(&err.line), "{err:?}");
+        assert_eq!(err.message, "stopped after 1000 steps, the script's budget");
+        // An empty loop body still takes steps
+        let script = Script::parse("for i in 0..9223372036854775807 { }").unwrap();
+        assert!(script
+            .run(&mut grid, &mut SplitMix64::new(1), 1000)
+            .is_err());
+    }
+
+    #[test]
+    fn test_rand_follows_the_generator() {
+        let source =
+            "for y in 0..height() { for x in 0..width() { if rand() < 0.5 { set(x, y) } } }";
+        let script = Script::parse(source).unwrap();
+        let (mut a, mut b) = (Grid::new(16, 16), Grid::new(16, 16));
+        script
+            .run(&mut a, &mut SplitMix64::new(3), DEFAULT_BUDGET)
+            .unwrap();
+        script
+            .run(&mut b, &mut SplitMix64::new(3), DEFAULT_BUDGET)
+            .unwrap();
+        assert_eq!(a, b);
+        assert!((64..192).contains(&a.population()));
+    }
+}
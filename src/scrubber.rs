@@ -0,0 +1,107 @@
+use crate::buttons;
+use crate::camera::Bounds;
+
+/// Height of the timeline bar, in pixels; it fits in the gap above the buttons
+pub const BAR_HEIGHT: f32 = 5.0;
+
+/// The timeline bar along the top edge of the button strip, the full width
+/// of the screen, oldest kept generation on the left
+pub fn bar_bounds(screen: (f32, f32)) -> Bounds {
+    let strip = buttons::strip_bounds(screen);
+    Bounds {
+        x: strip.x,
+        y: strip.y,
+        w: strip.w,
+        h: BAR_HEIGHT,
+    }
+}
+
+/// Index of the kept generation under `x`, counting from the oldest one
+/// The bar is split into `count` equal slots, one per generation; anything
+/// left or right of the bar picks the first or last one.
+pub fn index_at(x: f32, bar: Bounds, count: usize) -> usize {
+    if count == 0 || bar.w <= 0.0 {
+        return 0;
+    }
+    let slot = ((x - bar.x) / bar.w * count as f32).floor();
+    (slot.max(0.0) as usize).min(count - 1)
+}
+
+/// Where the marker for the generation at `index` goes: the middle of its slot
+pub fn marker_x(index: usize, bar: Bounds, count: usize) -> f32 {
+    if count == 0 {
+        return bar.x;
+    }
+    bar.x + (index as f32 + 0.5) * bar.w / count as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::{CellState, Grid};
+    use crate::timeline::Timeline;
+
+    const SCREEN: (f32, f32) = (800.0, 600.0);
+
+    #[test]
+    fn test_index_at_splits_bar() {
+        let bar = bar_bounds(SCREEN);
+        assert_eq!(index_at(0.0, bar, 4), 0);
+        assert_eq!(index_at(199.9, bar, 4), 0);
+        assert_eq!(index_at(200.0, bar, 4), 1);
+        assert_eq!(index_at(799.9, bar, 4), 3);
+        // Dragging past either end sticks to it
+        assert_eq!(index_at(-50.0, bar, 4), 0);
+        assert_eq!(index_at(900.0, bar, 4), 3);
+        assert_eq!(index_at(400.0, bar, 1), 0);
+        assert_eq!(index_at(400.0, bar, 0), 0);
+    }
+
+    #[test]
+    fn test_marker_round_trips() {
+        let bar = bar_bounds(SCREEN);
+        for count in [1, 2, 7, 100, 1000] {
+            for index in 0..count {
+                assert_eq!(index_at(marker_x(index, bar, count), bar, count), index);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bar_clears_buttons() {
+        let bar = bar_bounds(SCREEN);
+        let strip = buttons::strip_bounds(SCREEN);
+        assert!(strip.contains((10.0, bar.y + 1.0)));
+        for rect in buttons::layout(SCREEN, 7) {
+            assert!(bar.y + bar.h < rect.y);
+        }
+    }
+
+    #[test]
+    fn test_seek_after_history_wrapped() {
+        let mut grid = Grid::new(20, 20);
+        for (x, y) in [(10, 9), (11, 9), (9, 10), (10, 10), (10, 11)] {
+            grid.set(x, y, CellState::Alive);
+        }
+        // Only the last 3 of 10 generations are kept, so the bar spans 7 to 10
+        let mut timeline = Timeline::new(3);
+        for _ in 0..10 {
+            let before = grid.clone();
+            grid.next_cell_generation();
+            timeline.record(&before, &grid);
+        }
+        let bar = bar_bounds(SCREEN);
+        let count = timeline.len();
+        assert_eq!(count, 4);
+        assert_eq!(
+            index_at(marker_x(timeline.position(), bar, count), bar, count),
+            3
+        );
+
+        timeline.seek(&mut grid, index_at(0.0, bar, count));
+        assert_eq!(grid.generation(), 7);
+        timeline.seek(&mut grid, index_at(500.0, bar, count));
+        assert_eq!(grid.generation(), 9);
+        assert_eq!(timeline.position(), 2);
+    }
+}
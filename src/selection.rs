@@ -0,0 +1,76 @@
+use crate::conways::Region;
+
+/// A rectangular selection made by dragging over the grid
+/// The selection is anchored at the cell where the drag started and follows
+/// the cell under the cursor, in any direction, until the drag is finished.
+pub struct Selection {
+    anchor: (usize, usize),
+    corner: (usize, usize),
+    dragging: bool,
+}
+
+impl Selection {
+    /// Start a new selection at the given cell
+    pub fn start(cell: (usize, usize)) -> Self {
+        Self {
+            anchor: cell,
+            corner: cell,
+            dragging: true,
+        }
+    }
+
+    /// Move the free corner of the selection while it is still being dragged
+    pub fn extend_to(&mut self, cell: (usize, usize)) {
+        if self.dragging {
+            self.corner = cell;
+        }
+    }
+
+    /// Stop dragging, freezing the selected region
+    pub fn finish(&mut self) {
+        self.dragging = false;
+    }
+
+    /// Whether the selection is still following the cursor
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// The selected cells, snapped to cell boundaries
+    pub fn region(&self) -> Region {
+        Region::from_corners(self.anchor, self.corner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drag_bottom_right_to_top_left() {
+        let mut selection = Selection::start((6, 5));
+        selection.extend_to((4, 4));
+        selection.extend_to((2, 1));
+
+        assert_eq!(
+            selection.region(),
+            Region {
+                x: 2,
+                y: 1,
+                width: 5,
+                height: 5
+            }
+        );
+    }
+
+    #[test]
+    fn test_finished_selection_stops_following() {
+        let mut selection = Selection::start((0, 0));
+        selection.extend_to((2, 2));
+        selection.finish();
+        selection.extend_to((5, 5));
+
+        assert!(!selection.is_dragging());
+        assert_eq!(selection.region(), Region::from_corners((0, 0), (2, 2)));
+    }
+}
@@ -0,0 +1,402 @@
+use crate::bookmarks::{Bookmarks, BOOKMARK_SLOTS};
+use crate::camera::Camera;
+use crate::conways::Grid;
+use crate::formats::{rle, FormatError};
+use game_of_life::log::{info, warn};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// First line of every session file
+const MAGIC: &str = "conways-session";
+
+/// Version of the session file layout, bumped on incompatible changes
+pub const SESSION_VERSION: u32 = 3;
+
+/// Oldest version still read; version 1 files have no bookmarks, and neither
+/// version 1 nor 2 keeps the view or when the session was saved
+const OLDEST_SESSION_VERSION: u32 = 1;
+
+/// Everything needed to pick up where the previous run left off
+pub struct Session {
+    pub grid: Grid,
+    /// Seconds between two generations
+    pub update_interval: f32,
+    /// Rulestring the board was being simulated with
+    pub rule: String,
+    pub bookmarks: Bookmarks,
+    /// The view of the board, when the session was saved from a window
+    pub camera: Option<Camera>,
+    /// When the session was saved, as a `YYYYMMDD-HHMMSS` UTC timestamp
+    pub saved: Option<String>,
+}
+
+/// Error produced when a session file cannot be restored
+/// - `Io`: The file couldn't be read
+/// - `VersionMismatch`: The file was written by an incompatible version
+/// - `Corrupt`: The file contents are malformed
+#[derive(Debug)]
+pub enum SessionError {
+    Io(io::Error),
+    VersionMismatch { found: String },
+    Corrupt(String),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Io(err) => write!(f, "{err}"),
+            SessionError::VersionMismatch { found } => write!(
+                f,
+                "unsupported session version '{found}', expected {SESSION_VERSION}"
+            ),
+            SessionError::Corrupt(msg) => write!(f, "corrupt session file: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+impl From<io::Error> for SessionError {
+    fn from(err: io::Error) -> Self {
+        SessionError::Io(err)
+    }
+}
+
+impl From<FormatError> for SessionError {
+    fn from(err: FormatError) -> Self {
+        SessionError::Corrupt(format!("grid {err}"))
+    }
+}
+
+impl Session {
+    /// Serialize the session as a small text file: a versioned header,
+    /// one `key value` line per setting and the grid as RLE
+    /// Every bookmark is a `bookmark <slot> <generation> <lines>` line followed
+    /// by that many lines of RLE.
+    pub fn encode(&self) -> String {
+        let mut optional = String::new();
+        if let Some(camera) = &self.camera {
+            optional.push_str(&format!(
+                "camera {} {} {}\n",
+                camera.origin.0, camera.origin.1, camera.cell_size
+            ));
+        }
+        if let Some(saved) = &self.saved {
+            optional.push_str(&format!("saved {saved}\n"));
+        }
+        let mut bookmarks = String::new();
+        for (slot, grid) in self.bookmarks.iter() {
+            let board = rle::encode(grid, &self.rule, &[]);
+            bookmarks.push_str(&format!(
+                "bookmark {slot} {} {}\n{board}",
+                grid.generation(),
+                board.lines().count()
+            ));
+        }
+        format!(
+            "{MAGIC} {SESSION_VERSION}\n\
+             update_interval {}\n\
+             rule {}\n\
+             generation {}\n\
+             {optional}\
+             {bookmarks}\
+             grid\n{}",
+            self.update_interval,
+            self.rule,
+            self.grid.generation(),
+            rle::encode(&self.grid, &self.rule, &[])
+        )
+    }
+
+    /// Parse a session previously written by `encode`
+    pub fn decode(input: &str) -> Result<Session, SessionError> {
+        let mut lines = input.lines();
+
+        let version = lines
+            .next()
+            .and_then(|line| line.strip_prefix(MAGIC))
+            .ok_or_else(|| SessionError::Corrupt("missing session header".to_string()))?
+            .trim();
+        let supported = version
+            .parse()
+            .is_ok_and(|version| (OLDEST_SESSION_VERSION..=SESSION_VERSION).contains(&version));
+        if !supported {
+            return Err(SessionError::VersionMismatch {
+                found: version.to_string(),
+            });
+        }
+
+        let (mut update_interval, mut rule, mut generation) = (None, None, None);
+        let (mut camera, mut saved) = (None, None);
+        let mut bookmarks = Bookmarks::new();
+        while let Some(line) = lines.next() {
+            if line == "grid" {
+                break;
+            }
+            let (key, value) = line
+                .split_once(' ')
+                .ok_or_else(|| SessionError::Corrupt(format!("malformed line '{line}'")))?;
+            let invalid = || SessionError::Corrupt(format!("invalid {key} '{value}'"));
+            match key {
+                "update_interval" => {
+                    update_interval = Some(
+                        value
+                            .parse::<f32>()
+                            .ok()
+                            .filter(|v| v.is_finite() && *v > 0.0)
+                            .ok_or_else(invalid)?,
+                    )
+                }
+                "rule" => rule = Some(value.to_string()),
+                "generation" => generation = Some(value.parse::<u64>().map_err(|_| invalid())?),
+                "camera" => {
+                    let numbers: Vec<f32> = value
+                        .split(' ')
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| invalid())?;
+                    let [x, y, cell_size] = numbers[..] else {
+                        return Err(invalid());
+                    };
+                    if !numbers.iter().all(|n| n.is_finite()) || cell_size <= 0.0 {
+                        return Err(invalid());
+                    }
+                    camera = Some(Camera {
+                        origin: (x, y),
+                        cell_size,
+                    });
+                }
+                "saved" => saved = Some(value.to_string()),
+                "bookmark" => {
+                    let numbers: Vec<usize> = value
+                        .split(' ')
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| invalid())?;
+                    let [slot, bookmark_generation, count] = numbers[..] else {
+                        return Err(invalid());
+                    };
+                    if !(1..=BOOKMARK_SLOTS).contains(&slot) {
+                        return Err(invalid());
+                    }
+                    let board: Vec<&str> = lines.by_ref().take(count).collect();
+                    if board.len() < count {
+                        return Err(SessionError::Corrupt(format!("truncated bookmark {slot}")));
+                    }
+                    let mut grid: Grid = rle::decode(&board.join("\n"))?;
+                    grid.set_generation(bookmark_generation as u64);
+                    if bookmarks.save(slot, &grid).is_some() {
+                        return Err(SessionError::Corrupt(format!("bookmark {slot} twice")));
+                    }
+                }
+                _ => return Err(SessionError::Corrupt(format!("unknown key '{key}'"))),
+            }
+        }
+
+        let missing = |key: &str| SessionError::Corrupt(format!("missing {key}"));
+        let update_interval = update_interval.ok_or_else(|| missing("update_interval"))?;
+        let rule = rule.ok_or_else(|| missing("rule"))?;
+        let generation = generation.ok_or_else(|| missing("generation"))?;
+
+        let mut grid: Grid = rle::decode(&lines.collect::<Vec<_>>().join("\n"))?;
+        grid.set_generation(generation);
+
+        Ok(Session {
+            grid,
+            update_interval,
+            rule,
+            bookmarks,
+            camera,
+            saved,
+        })
+    }
+
+    /// Write the session to `path`, creating the parent directory if needed
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.encode())
+    }
+
+    /// Read a session from `path`
+    pub fn load(path: &Path) -> Result<Session, SessionError> {
+        Session::decode(&fs::read_to_string(path)?)
+    }
+
+    /// The session the previous run left at `path`, or `None` when there is
+    /// none; one that can't be read is ignored with a warning, so a corrupt
+    /// autosave never keeps the game from starting
+    pub fn restore(path: &Path) -> Option<Session> {
+        match Session::load(path) {
+            Ok(session) => {
+                info!("found the previous session in {}", path.display());
+                Some(session)
+            }
+            Err(SessionError::Io(err)) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => {
+                warn!("ignoring previous session: {err}");
+                None
+            }
+        }
+    }
+}
+
+/// Location of the session file in the platform's data directory:
+/// - Linux and other unixes: `$XDG_DATA_HOME/conways`, or `~/.local/share/conways`
+/// - macOS: `~/Library/Application Support/conways`
+/// - Windows: `%APPDATA%\conways`
+///
+/// Returns `None` when the relevant environment variables are not set
+pub fn session_path() -> Option<PathBuf> {
+    let env = |key: &str| std::env::var_os(key).filter(|value| !value.is_empty());
+
+    let data_dir = if cfg!(target_os = "windows") {
+        PathBuf::from(env("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(env("HOME")?).join("Library/Application Support")
+    } else {
+        env("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env("HOME").map(|home| PathBuf::from(home).join(".local/share")))?
+    };
+    Some(data_dir.join("conways").join("session.txt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_of_life::log::Level;
+
+    fn sample() -> Session {
+        let mut grid = Grid::new(12, 8);
+        grid.randomize(0.5, 99);
+        grid.next_cell_generation();
+        grid.next_cell_generation();
+        let mut bookmarks = Bookmarks::new();
+        let mut early = Grid::new(6, 4);
+        early.toggle(2, 1);
+        early.set_generation(5);
+        bookmarks.save(4, &early);
+        bookmarks.save(1, &grid);
+        Session {
+            grid,
+            update_interval: 0.05,
+            rule: "B3/S23".to_string(),
+            bookmarks,
+            camera: Some(Camera {
+                origin: (-2.5, 4.0),
+                cell_size: 12.0,
+            }),
+            saved: Some("20240601-134502".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let session = sample();
+        let restored = Session::decode(&session.encode()).unwrap();
+
+        assert_eq!(restored.grid, session.grid);
+        assert_eq!(restored.grid.generation(), 2);
+        assert_eq!(restored.update_interval, 0.05);
+        assert_eq!(restored.rule, "B3/S23");
+        assert_eq!(restored.bookmarks, session.bookmarks);
+        assert_eq!(restored.bookmarks.restore(4).unwrap().generation(), 5);
+        assert_eq!(restored.bookmarks.restore(1).unwrap().generation(), 2);
+        assert_eq!(restored.camera, session.camera);
+        assert_eq!(restored.saved.as_deref(), Some("20240601-134502"));
+    }
+
+    #[test]
+    fn test_reads_version_1() {
+        let text = "conways-session 1\n\
+                    update_interval 0.1\n\
+                    rule B3/S23\n\
+                    generation 4\n\
+                    grid\n\
+                    x = 3, y = 1, rule = B3/S23\n\
+                    3o!\n";
+        let session = Session::decode(text).unwrap();
+        assert_eq!(session.grid.generation(), 4);
+        assert!(session.bookmarks.is_empty());
+        assert_eq!((session.camera, session.saved), (None, None));
+    }
+
+    #[test]
+    fn test_round_trip_through_file() {
+        let path = std::env::temp_dir()
+            .join(format!("conways-session-{}", std::process::id()))
+            .join("session.txt");
+        let session = sample();
+
+        session.save(&path).unwrap();
+        let restored = Session::load(&path).unwrap();
+        assert_eq!(restored.grid, session.grid);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_session_is_ignored_with_one_warning() {
+        let dir = std::env::temp_dir().join(format!("conways-corrupt-{}", std::process::id()));
+        let path = dir.join("session.txt");
+        let text = sample().encode().replace("generation ", "generation x");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, text).unwrap();
+
+        let (restored, records) = game_of_life::log::capture(|| Session::restore(&path));
+        assert!(restored.is_none());
+        let warnings: Vec<_> = records.iter().filter(|r| r.level == Level::Warn).collect();
+        assert_eq!(warnings.len(), 1, "{records:?}");
+        assert!(warnings[0]
+            .message
+            .starts_with("ignoring previous session: corrupt"));
+
+        // No session at all is the usual first run, not worth a warning
+        fs::remove_dir_all(&dir).unwrap();
+        let (restored, records) = game_of_life::log::capture(|| Session::restore(&path));
+        assert!(restored.is_none() && records.is_empty(), "{records:?}");
+    }
+
+    #[test]
+    fn test_version_mismatch() {
+        let text = sample()
+            .encode()
+            .replacen("conways-session 3", "conways-session 99", 1);
+        assert!(matches!(
+            Session::decode(&text),
+            Err(SessionError::VersionMismatch { found }) if found == "99"
+        ));
+    }
+
+    #[test]
+    fn test_corrupt_files_are_rejected() {
+        let valid = sample().encode();
+        let corrupt = [
+            String::new(),
+            "garbage\n".to_string(),
+            valid.replace("update_interval 0.05", "update_interval fast"),
+            valid.replace("update_interval 0.05", "update_interval -1"),
+            valid.replace("rule B3/S23\n", ""),
+            valid.replace("grid\n", "grid\nx = 1, y = 1\nzz!\n"),
+            valid[..valid.len() / 2].to_string(),
+            valid.replacen("bookmark 1 2", "bookmark 10 2", 1),
+            valid.replacen("bookmark 1 2", "bookmark 4 2", 1),
+            valid.replacen("bookmark 1 2 ", "bookmark 1 2 99", 1),
+            valid.replacen("bookmark 1 2", "bookmark 1", 1),
+            valid.replace("camera -2.5 4 12", "camera -2.5 4"),
+            valid.replace("camera -2.5 4 12", "camera -2.5 4 0"),
+            valid.replace("camera -2.5 4 12", "camera NaN 4 12"),
+        ];
+
+        for text in corrupt {
+            assert!(
+                matches!(Session::decode(&text), Err(SessionError::Corrupt(_))),
+                "accepted corrupt session:\n{text}"
+            );
+        }
+    }
+}
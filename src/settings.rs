@@ -0,0 +1,629 @@
+use crate::background::BackgroundStyle;
+use crate::bindings::strip_comment;
+use crate::camera::{MAX_CELL_SIZE, MIN_CELL_SIZE};
+use crate::cli::{Config, MAX_GRID_SIDE, MAX_SPEED, MIN_SPEED};
+use crate::conways::BoundaryCondition;
+use crate::formats::FormatError;
+use crate::phantom::MAX_PHANTOM_WIDTH;
+use crate::rule::Rule;
+use crate::shape::CellShape;
+use crate::speed_presets::PRESET_SLOTS;
+use crate::theme::Theme;
+
+/// File the preferences are loaded from, in the working directory
+pub const SETTINGS_FILE: &str = "conways.toml";
+
+/// Range accepted for the window size, in pixels
+pub const MIN_WINDOW_SIDE: i32 = 200;
+pub const MAX_WINDOW_SIDE: i32 = 8192;
+
+/// A value on the right hand side of a `key = value` line
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    String(String),
+}
+
+impl Value {
+    /// What kind of value this is, for error messages
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Value::Integer(_) => "an integer",
+            Value::Float(_) => "a number",
+            Value::Boolean(_) => "a boolean",
+            Value::String(_) => "a string",
+        }
+    }
+}
+
+/// Apply the preferences in `conways.toml` contents on top of `config`
+///
+/// The file is a small subset of TOML: one `key = value` pair per line, `#`
+/// comments, and integer, float, boolean or basic string values:
+/// ```toml
+/// width = 200
+/// speed = 20.5
+/// theme = "solarized"
+/// ```
+/// Keys missing from the file keep the values already in `config`.
+///
+/// # Returns
+/// A warning for every unknown key, which is skipped, or a `FormatError` for
+/// malformed lines and values of the wrong type or out of range
+pub fn apply_settings(input: &str, config: &mut Config) -> Result<Vec<String>, FormatError> {
+    let mut warnings = Vec::new();
+
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            return Err(FormatError::new(line_number, "tables are not supported"));
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| FormatError::new(line_number, "expected 'key = value'"))?;
+        let key = key.trim();
+        let value = parse_value(value.trim()).map_err(|msg| FormatError::new(line_number, msg))?;
+        let fail = |expected: &str| {
+            FormatError::new(
+                line_number,
+                format!("expected {expected} for '{key}', found {}", value.kind()),
+            )
+        };
+        let out_of_range =
+            |expected: String| FormatError::new(line_number, format!("'{key}' must be {expected}"));
+
+        match key {
+            "width" | "height" => {
+                let side = integer(&value)
+                    .ok_or_else(|| fail("an integer"))?
+                    .try_into()
+                    .ok()
+                    .filter(|side| (1..=MAX_GRID_SIDE).contains(side))
+                    .ok_or_else(|| out_of_range(format!("1 to {MAX_GRID_SIDE} cells")))?;
+                if key == "width" {
+                    config.width = side;
+                } else {
+                    config.height = side;
+                }
+            }
+            "window_width" | "window_height" => {
+                let side = integer(&value)
+                    .ok_or_else(|| fail("an integer"))?
+                    .try_into()
+                    .ok()
+                    .filter(|side| (MIN_WINDOW_SIDE..=MAX_WINDOW_SIDE).contains(side))
+                    .ok_or_else(|| {
+                        out_of_range(format!("{MIN_WINDOW_SIDE} to {MAX_WINDOW_SIDE} pixels"))
+                    })?;
+                if key == "window_width" {
+                    config.window_size.0 = side;
+                } else {
+                    config.window_size.1 = side;
+                }
+            }
+            "cell_size" => {
+                let size = number(&value).ok_or_else(|| fail("a number"))? as f32;
+                if !(MIN_CELL_SIZE..=MAX_CELL_SIZE).contains(&size) {
+                    return Err(out_of_range(format!(
+                        "{MIN_CELL_SIZE} to {MAX_CELL_SIZE} pixels"
+                    )));
+                }
+                config.cell_size = size;
+            }
+            "speed" => {
+                let speed = number(&value).ok_or_else(|| fail("a number"))? as f32;
+                if !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+                    return Err(out_of_range(format!(
+                        "{MIN_SPEED} to {MAX_SPEED} generations per second"
+                    )));
+                }
+                config.update_interval = 1.0 / speed;
+            }
+            "preset_base" => {
+                let speed = number(&value).ok_or_else(|| fail("a number"))? as f32;
+                if !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+                    return Err(out_of_range(format!(
+                        "{MIN_SPEED} to {MAX_SPEED} generations per second"
+                    )));
+                }
+                config.preset_base = speed;
+            }
+            "speed_presets" => {
+                let list = string(&value).ok_or_else(|| fail("a string"))?;
+                config.speed_presets = list
+                    .split(',')
+                    .map(|multiple| multiple.trim().trim_end_matches('x').parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .ok()
+                    .filter(|multiples| {
+                        (1..=PRESET_SLOTS).contains(&multiples.len())
+                            && multiples.iter().all(|m| *m > 0.0 && m.is_finite())
+                    })
+                    .ok_or_else(|| {
+                        out_of_range(format!(
+                            "1 to {PRESET_SLOTS} positive multiples separated by commas, e.g. \"1, 2, 4, 8\""
+                        ))
+                    })?;
+            }
+            "autosave_interval" => {
+                let seconds = number(&value).ok_or_else(|| fail("a number"))? as f32;
+                if !(seconds >= 0.0 && seconds.is_finite()) {
+                    return Err(out_of_range("0 (off) or more seconds".to_string()));
+                }
+                config.autosave_interval = seconds;
+            }
+            "theme" => {
+                let name = string(&value).ok_or_else(|| fail("a string"))?;
+                let presets = Theme::presets();
+                config.theme = presets
+                    .iter()
+                    .position(|theme| theme.name.eq_ignore_ascii_case(name))
+                    .ok_or_else(|| {
+                        let names: Vec<_> = presets.iter().map(|theme| theme.name).collect();
+                        out_of_range(format!("one of {}", names.join(", ")))
+                    })?;
+            }
+            "rule" => {
+                let rule = string(&value).ok_or_else(|| fail("a string"))?;
+                config.rule = rule
+                    .parse()
+                    .map_err(|err| out_of_range(format!("a rulestring, {err}")))?;
+            }
+            "rules" => {
+                let list = string(&value).ok_or_else(|| fail("a string"))?;
+                config.rules = list
+                    .split(',')
+                    .map(str::parse)
+                    .collect::<Result<Vec<Rule>, _>>()
+                    .map_err(|err| out_of_range(format!("a list of rulestrings, {err}")))?;
+            }
+            "background" => {
+                let name = string(&value).ok_or_else(|| fail("a string"))?;
+                config.background = BackgroundStyle::ALL
+                    .into_iter()
+                    .find(|style| style.label().eq_ignore_ascii_case(name))
+                    .ok_or_else(|| {
+                        out_of_range("\"off\", \"dots\" or \"checkerboard\"".to_string())
+                    })?;
+            }
+            "cell_shape" => {
+                let name = string(&value).ok_or_else(|| fail("a string"))?;
+                config.cell_shape = CellShape::ALL
+                    .into_iter()
+                    .find(|shape| shape.label().eq_ignore_ascii_case(name))
+                    .ok_or_else(|| {
+                        out_of_range("\"square\", \"circle\" or \"rounded\"".to_string())
+                    })?;
+            }
+            "phantom_width" => {
+                config.phantom_width = integer(&value)
+                    .ok_or_else(|| fail("an integer"))?
+                    .try_into()
+                    .ok()
+                    .filter(|width| *width <= MAX_PHANTOM_WIDTH)
+                    .ok_or_else(|| out_of_range(format!("0 (off) to {MAX_PHANTOM_WIDTH} cells")))?;
+            }
+            "tile_pitch" => {
+                let text = string(&value).ok_or_else(|| fail("a string"))?;
+                let pitch = text
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+                    .filter(|&(w, h): &(usize, usize)| w > 0 && h > 0 && w.max(h) <= MAX_GRID_SIDE);
+                config.tile_pitch = Some(
+                    pitch
+                        .ok_or_else(|| out_of_range("columns x rows, e.g. \"6x4\"".to_string()))?,
+                );
+            }
+            "boundary" => {
+                let name = string(&value).ok_or_else(|| fail("a string"))?;
+                config.boundary = [BoundaryCondition::Dead, BoundaryCondition::Wrap]
+                    .into_iter()
+                    .find(|boundary| boundary.label().eq_ignore_ascii_case(name))
+                    .ok_or_else(|| out_of_range("\"dead\" or \"wrap\"".to_string()))?;
+            }
+            "auto_pause" => {
+                let Value::Boolean(on) = value else {
+                    return Err(fail("true or false"));
+                };
+                config.auto_pause = on;
+            }
+            "pause_on_focus_loss" => {
+                let Value::Boolean(on) = value else {
+                    return Err(fail("true or false"));
+                };
+                config.pause_on_focus_loss = on;
+            }
+            _ => warnings.push(format!(
+                "{SETTINGS_FILE} line {line_number}: unknown key '{key}' ignored"
+            )),
+        }
+    }
+    Ok(warnings)
+}
+
+/// The contents of a `conways.toml` holding the built-in defaults, with a
+/// comment for every key
+pub fn default_file() -> String {
+    let defaults = Config::default();
+    let theme = &Theme::presets()[defaults.theme];
+    let themes: Vec<_> = Theme::presets().iter().map(|theme| theme.name).collect();
+    let rules: Vec<_> = defaults.rules.iter().map(Rule::to_string).collect();
+    let presets: Vec<_> = defaults.speed_presets.iter().map(f32::to_string).collect();
+    format!(
+        "\
+# Preferences for Conway's Game of Life, overridden by command line options
+# Remove a line or comment it out to keep the built-in default
+
+# Size of the window, in pixels
+window_width = {}
+window_height = {}
+
+# Size of the board, in cells (1 to {MAX_GRID_SIDE})
+width = {}
+height = {}
+
+# Initial size of a cell on screen, in pixels ({MIN_CELL_SIZE} to {MAX_CELL_SIZE})
+cell_size = {}
+
+# Generations per second ({MIN_SPEED} to {MAX_SPEED})
+speed = {}
+
+# Speed the Shift+1 to Shift+4 presets multiply, in generations per second
+preset_base = {}
+
+# Multiples of preset_base the Shift+1 to Shift+4 presets jump to, separated by commas
+speed_presets = \"{}\"
+
+# Color theme: {}
+theme = \"{}\"
+
+# Marks on dead cells: \"off\", \"dots\" or \"checkerboard\"
+background = \"{}\"
+
+# How live cells are drawn: \"square\", \"circle\" or \"rounded\"
+cell_shape = \"{}\"
+
+# Rulestring to simulate, in B/S notation
+rule = \"{}\"
+
+# Rules cycled through while playing, separated by commas
+rules = \"{}\"
+
+# What lies beyond the edges: \"dead\" or \"wrap\"
+boundary = \"{}\"
+
+# Rows and columns of a wrapping board shown ghosted past the opposite edge (0 to {MAX_PHANTOM_WIDTH})
+phantom_width = {}
+
+# Cells between copies of the tile tool, as columns x rows; the copied
+# pattern's size plus one when left out
+# tile_pitch = \"6x4\"
+
+# Pause once the board is stable or oscillating
+auto_pause = {}
+
+# Pause while the window is in the background, resuming when it's back
+pause_on_focus_loss = {}
+
+# Seconds between two autosaves of the session, 0 to only save on exit
+autosave_interval = {}
+",
+        defaults.window_size.0,
+        defaults.window_size.1,
+        defaults.width,
+        defaults.height,
+        defaults.cell_size,
+        1.0 / defaults.update_interval,
+        defaults.preset_base,
+        presets.join(", "),
+        themes.join(", "),
+        theme.name,
+        defaults.background.label(),
+        defaults.cell_shape.label(),
+        defaults.rule,
+        rules.join(", "),
+        defaults.boundary.label(),
+        defaults.phantom_width,
+        defaults.auto_pause,
+        defaults.pause_on_focus_loss,
+        defaults.autosave_interval,
+    )
+}
+
+/// `input` with `key` set to `value`, a TOML literal such as `"circle"` or `20`
+/// The first line setting `key` is replaced, keeping the rest of the file and
+/// its comments as they are; the line is appended when there is none.
+pub fn set_key(input: &str, key: &str, value: &str) -> String {
+    let line = format!("{key} = {value}");
+    let mut lines: Vec<&str> = input.lines().collect();
+    let existing = lines.iter().position(|line| {
+        strip_comment(line)
+            .split_once('=')
+            .is_some_and(|(name, _)| name.trim() == key)
+    });
+    match existing {
+        Some(i) => lines[i] = &line,
+        None => lines.push(&line),
+    }
+    let mut output = lines.join("\n");
+    output.push('\n');
+    output
+}
+
+pub(crate) fn integer(value: &Value) -> Option<i64> {
+    match value {
+        Value::Integer(n) => Some(*n),
+        _ => None,
+    }
+}
+
+/// Integers are accepted wherever a number is, so `speed = 20` works
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Integer(n) => Some(*n as f64),
+        Value::Float(x) => Some(*x),
+        _ => None,
+    }
+}
+
+pub(crate) fn string(value: &Value) -> Option<&str> {
+    match value {
+        Value::String(s) => Some(s),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_value(text: &str) -> Result<Value, String> {
+    if let Some(rest) = text.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.chars();
+        loop {
+            match chars.next() {
+                Some('"') => break,
+                Some('\\') => match chars.next() {
+                    Some('\\') => value.push('\\'),
+                    Some('"') => value.push('"'),
+                    Some(c) => return Err(format!("unsupported escape '\\{c}'")),
+                    None => return Err("unterminated string".to_string()),
+                },
+                Some(c) => value.push(c),
+                None => return Err("unterminated string".to_string()),
+            }
+        }
+        if !chars.as_str().trim().is_empty() {
+            return Err("unexpected text after the string".to_string());
+        }
+        return Ok(Value::String(value));
+    }
+
+    // TOML allows underscores between digits, e.g. 1_000
+    let number = text.replace('_', "");
+    match text {
+        "true" => Ok(Value::Boolean(true)),
+        "false" => Ok(Value::Boolean(false)),
+        "" => Err("missing value".to_string()),
+        _ => {
+            if let Ok(n) = number.parse() {
+                Ok(Value::Integer(n))
+            } else if let Ok(x) = number.parse::<f64>() {
+                Ok(Value::Float(x))
+            } else {
+                Err(format!("invalid value '{text}'"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(input: &str) -> Result<(Config, Vec<String>), FormatError> {
+        let mut config = Config::default();
+        let warnings = apply_settings(input, &mut config)?;
+        Ok((config, warnings))
+    }
+
+    fn error(input: &str) -> String {
+        apply(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_empty_file_keeps_defaults() {
+        assert_eq!(apply("").unwrap(), (Config::default(), vec![]));
+        assert_eq!(
+            apply("# nothing here\n\n   \n").unwrap(),
+            (Config::default(), vec![])
+        );
+    }
+
+    #[test]
+    fn test_partial_file() {
+        let (config, warnings) = apply(
+            "width = 200 # wide\n\
+             speed = 20\n\
+             theme = \"Solarized\"\n\
+             background = \"Dots\"\n\
+             cell_shape = \"Circle\"\n\
+             boundary = \"wrap\"\n",
+        )
+        .unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(config.width, 200);
+        assert_eq!(config.update_interval, 0.05);
+        assert_eq!(config.theme, 1);
+        assert_eq!(config.background, BackgroundStyle::Dots);
+        assert_eq!(config.cell_shape, CellShape::Circle);
+        assert_eq!(config.boundary, BoundaryCondition::Wrap);
+        // Everything else keeps its default
+        assert_eq!(config.height, Config::default().height);
+        assert_eq!(config.cell_size, Config::default().cell_size);
+    }
+
+    #[test]
+    fn test_every_key() {
+        let (config, _) = apply(
+            "window_width = 1_024\n\
+             window_height = 768\n\
+             height = 40\n\
+             cell_size = 2.5\n\
+             rule = \"b36/s23\"\n\
+             rules = \"B3/S23,B2/S\"\n\
+             auto_pause = false\n\
+             pause_on_focus_loss = false\n\
+             autosave_interval = 0\n\
+             phantom_width = 5\n\
+             tile_pitch = \"6 x 4\"\n\
+             preset_base = 12.5\n\
+             speed_presets = \"0.5, 1x, 3\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.preset_base, 12.5);
+        assert_eq!(config.speed_presets, vec![0.5, 1.0, 3.0]);
+        assert_eq!(config.window_size, (1024, 768));
+        assert_eq!(config.phantom_width, 5);
+        assert_eq!(config.tile_pitch, Some((6, 4)));
+        assert_eq!(config.height, 40);
+        assert_eq!(config.cell_size, 2.5);
+        assert_eq!(config.rule.to_string(), "B36/S23");
+        assert_eq!(config.rules, vec![Rule::conway(), "B2/S".parse().unwrap()]);
+        assert!(!config.auto_pause);
+        assert!(!config.pause_on_focus_loss);
+        assert_eq!(config.autosave_interval, 0.0);
+    }
+
+    #[test]
+    fn test_unknown_keys_warn() {
+        let (config, warnings) = apply("colour = \"red\"\nwidth = 90\n").unwrap();
+        assert_eq!(config.width, 90);
+        assert_eq!(
+            warnings,
+            vec!["conways.toml line 1: unknown key 'colour' ignored"]
+        );
+    }
+
+    #[test]
+    fn test_type_mismatches() {
+        assert_eq!(
+            error("width = \"wide\""),
+            "line 1: expected an integer for 'width', found a string"
+        );
+        assert_eq!(
+            error("\nwidth = 2.5"),
+            "line 2: expected an integer for 'width', found a number"
+        );
+        assert_eq!(
+            error("speed = true"),
+            "line 1: expected a number for 'speed', found a boolean"
+        );
+        assert_eq!(
+            error("theme = 3"),
+            "line 1: expected a string for 'theme', found an integer"
+        );
+        assert_eq!(
+            error("auto_pause = \"yes\""),
+            "line 1: expected true or false for 'auto_pause', found a string"
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_values() {
+        assert_eq!(
+            error("width = 0"),
+            format!("line 1: 'width' must be 1 to {MAX_GRID_SIDE} cells")
+        );
+        assert_eq!(
+            error("window_width = -5"),
+            "line 1: 'window_width' must be 200 to 8192 pixels"
+        );
+        assert_eq!(
+            error("speed = 1000"),
+            "line 1: 'speed' must be 0.5 to 100 generations per second"
+        );
+        assert_eq!(
+            error("theme = \"neon\""),
+            "line 1: 'theme' must be one of classic, solarized, high contrast"
+        );
+        assert_eq!(
+            error("rule = \"B36/S2x\""),
+            "line 1: 'rule' must be a rulestring, expected B<digits>/S<digits>, e.g. B3/S23"
+        );
+        assert_eq!(
+            error("rules = \"B3/S23, B9/S\""),
+            "line 1: 'rules' must be a list of rulestrings, '9' isn't a neighbor count from 0 to 8"
+        );
+        assert_eq!(
+            error("cell_shape = \"hexagon\""),
+            "line 1: 'cell_shape' must be \"square\", \"circle\" or \"rounded\""
+        );
+        assert_eq!(
+            error("tile_pitch = \"6x0\""),
+            "line 1: 'tile_pitch' must be columns x rows, e.g. \"6x4\""
+        );
+        assert_eq!(
+            error("speed_presets = \"1, 2, 4, 8, 16\""),
+            "line 1: 'speed_presets' must be 1 to 4 positive multiples separated by commas, e.g. \"1, 2, 4, 8\""
+        );
+        assert!(error("speed_presets = \"2, -1\"").contains("positive multiples"));
+        assert!(error("speed_presets = \"\"").contains("positive multiples"));
+        assert_eq!(
+            error("preset_base = 0"),
+            "line 1: 'preset_base' must be 0.5 to 100 generations per second"
+        );
+        assert_eq!(
+            error("phantom_width = 17"),
+            "line 1: 'phantom_width' must be 0 (off) to 16 cells"
+        );
+        assert_eq!(
+            error("autosave_interval = -1"),
+            "line 1: 'autosave_interval' must be 0 (off) or more seconds"
+        );
+    }
+
+    #[test]
+    fn test_malformed_lines() {
+        assert_eq!(error("width 200"), "line 1: expected 'key = value'");
+        assert_eq!(error("width ="), "line 1: missing value");
+        assert_eq!(error("theme = \"classic"), "line 1: unterminated string");
+        assert_eq!(error("width = 20O"), "line 1: invalid value '20O'");
+        assert_eq!(error("[window]"), "line 1: tables are not supported");
+    }
+
+    #[test]
+    fn test_default_file_round_trips() {
+        let (config, warnings) = apply(&default_file()).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_set_key() {
+        let input = "# Preferences\nwidth = 90\ncell_shape = \"square\" # drawn\nspeed = 5\n";
+        let output = set_key(input, "cell_shape", "\"circle\"");
+        assert_eq!(
+            output,
+            "# Preferences\nwidth = 90\ncell_shape = \"circle\"\nspeed = 5\n"
+        );
+        let (config, _) = apply(&output).unwrap();
+        assert_eq!(config.cell_shape, CellShape::Circle);
+
+        // Appended when missing, including to a file without a final newline
+        assert_eq!(
+            set_key("width = 90", "speed", "5"),
+            "width = 90\nspeed = 5\n"
+        );
+        assert_eq!(set_key("", "speed", "5"), "speed = 5\n");
+        // Commented out lines and keys sharing a prefix are left alone
+        assert_eq!(
+            set_key("# speed = 1\nspeedy = 2\n", "speed", "5"),
+            "# speed = 1\nspeedy = 2\nspeed = 5\n"
+        );
+    }
+}
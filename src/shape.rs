@@ -0,0 +1,154 @@
+use crate::input::Point;
+
+/// Gap kept around circles and rounded cells, as a share of the cell size,
+/// so neighboring cells don't merge into one blob
+pub const INSET: f32 = 0.1;
+
+/// Smallest cell size shapes other than squares are drawn at, in pixels;
+/// smaller cells are drawn as squares, which is all they can show anyway
+pub const MIN_SHAPED_CELL_SIZE: f32 = 3.0;
+
+/// Corner radius of rounded cells, as a share of their side
+const CORNER_RADIUS: f32 = 0.3;
+
+/// Triangles approximating each rounded corner
+const CORNER_SEGMENTS: usize = 4;
+
+/// How live cells are drawn on screen
+/// - `Square`: Full squares touching their neighbors, the classic look
+/// - `Circle`: Filled circles
+/// - `Rounded`: Squares with rounded corners
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CellShape {
+    #[default]
+    Square,
+    Circle,
+    Rounded,
+}
+
+impl CellShape {
+    pub const ALL: [CellShape; 3] = [CellShape::Square, CellShape::Circle, CellShape::Rounded];
+
+    /// The shape after this one, wrapping around
+    pub fn next(self) -> Self {
+        match self {
+            CellShape::Square => CellShape::Circle,
+            CellShape::Circle => CellShape::Rounded,
+            CellShape::Rounded => CellShape::Square,
+        }
+    }
+
+    /// Name used by the HUD and `conways.toml`
+    pub fn label(self) -> &'static str {
+        match self {
+            CellShape::Square => "square",
+            CellShape::Circle => "circle",
+            CellShape::Rounded => "rounded",
+        }
+    }
+
+    /// The shape actually drawn for cells of `cell_size` pixels
+    pub fn at_size(self, cell_size: f32) -> Self {
+        if cell_size < MIN_SHAPED_CELL_SIZE {
+            CellShape::Square
+        } else {
+            self
+        }
+    }
+
+    /// Where the shape goes within its cell: the offset from the cell's
+    /// top-left corner and the side of the square it fills, in pixels
+    pub fn footprint(self, cell_size: f32) -> (f32, f32) {
+        match self.at_size(cell_size) {
+            CellShape::Square => (0.0, cell_size),
+            CellShape::Circle | CellShape::Rounded => {
+                let inset = (cell_size * INSET).max(0.5);
+                (inset, cell_size - 2.0 * inset)
+            }
+        }
+    }
+}
+
+/// Non-overlapping triangles covering a square of `side` pixels at `(x, y)`
+/// with rounded corners, so translucent colors are drawn evenly
+pub fn rounded_square((x, y): Point, side: f32) -> Vec<[Point; 3]> {
+    let r = side * CORNER_RADIUS;
+    let mut triangles = Vec::with_capacity(6 + 4 * CORNER_SEGMENTS);
+    let mut rect = |x0: f32, y0: f32, x1: f32, y1: f32| {
+        triangles.push([(x0, y0), (x1, y0), (x1, y1)]);
+        triangles.push([(x0, y0), (x1, y1), (x0, y1)]);
+    };
+    // A full-width band in the middle, and the top and bottom bands between the corners
+    rect(x, y + r, x + side, y + side - r);
+    rect(x + r, y, x + side - r, y + r);
+    rect(x + r, y + side - r, x + side - r, y + side);
+
+    let corners = [
+        ((x + r, y + r), std::f32::consts::PI),
+        ((x + side - r, y + r), 1.5 * std::f32::consts::PI),
+        ((x + side - r, y + side - r), 0.0),
+        ((x + r, y + side - r), 0.5 * std::f32::consts::PI),
+    ];
+    for (center, start) in corners {
+        let point = |i: usize| {
+            let angle = start + i as f32 * std::f32::consts::FRAC_PI_2 / CORNER_SEGMENTS as f32;
+            (center.0 + r * angle.cos(), center.1 + r * angle.sin())
+        };
+        for i in 0..CORNER_SEGMENTS {
+            triangles.push([center, point(i), point(i + 1)]);
+        }
+    }
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(triangles: &[[Point; 3]]) -> f32 {
+        triangles
+            .iter()
+            .map(|[a, b, c]| ((b.0 - a.0) * (c.1 - a.1) - (c.0 - a.0) * (b.1 - a.1)).abs() / 2.0)
+            .sum()
+    }
+
+    #[test]
+    fn test_footprint_insets_shapes() {
+        assert_eq!(CellShape::Square.footprint(10.0), (0.0, 10.0));
+        assert_eq!(CellShape::Circle.footprint(10.0), (1.0, 8.0));
+        assert_eq!(CellShape::Rounded.footprint(20.0), (2.0, 16.0));
+        // Never less than half a pixel apart
+        assert_eq!(CellShape::Circle.footprint(4.0), (0.5, 3.0));
+        // Tiny cells stay squares
+        assert_eq!(CellShape::Circle.at_size(2.0), CellShape::Square);
+        assert_eq!(CellShape::Rounded.footprint(2.0), (0.0, 2.0));
+    }
+
+    #[test]
+    fn test_rounded_square_area() {
+        let side = 20.0;
+        let r = side * CORNER_RADIUS;
+        let triangles = rounded_square((5.0, 7.0), side);
+        // The square minus what the rounded corners cut off, give or take the
+        // polygons approximating the arcs, which fall slightly inside them
+        let expected = side * side - (4.0 - std::f32::consts::PI) * r * r;
+        assert!(area(&triangles) < expected);
+        assert!(area(&triangles) > expected - 0.1 * r * r);
+        assert!(area(&triangles) < side * side);
+
+        // Everything stays within the square
+        for &(px, py) in triangles.iter().flatten() {
+            assert!((5.0..=25.0 + 1e-4).contains(&px));
+            assert!((7.0..=27.0 + 1e-4).contains(&py));
+        }
+    }
+
+    #[test]
+    fn test_next_cycles_all() {
+        let mut shape = CellShape::Square;
+        for expected in CellShape::ALL.iter().cycle().skip(1).take(3) {
+            shape = shape.next();
+            assert_eq!(shape, *expected);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use crate::buttons;
+use crate::camera::Bounds;
+use crate::input::Point;
+
+/// Slowest and fastest speeds the slider reaches, in generations per second
+pub const MIN_SLIDER_SPEED: f32 = 0.5;
+pub const MAX_SLIDER_SPEED: f32 = 500.0;
+
+/// Length of the slider track, in pixels
+pub const TRACK_WIDTH: f32 = 160.0;
+
+/// Space between the track and the right edge of the screen, leaving room
+/// for the speed written under it
+const TRACK_MARGIN: f32 = 16.0;
+
+/// Size of the handle, in pixels
+pub const HANDLE_WIDTH: f32 = 8.0;
+pub const HANDLE_HEIGHT: f32 = 16.0;
+
+/// The area of the strip that grabs the slider: the track along the right
+/// end of the button strip, as tall as the strip below the timeline bar
+pub fn slider_bounds(screen: (f32, f32)) -> Bounds {
+    let strip = buttons::strip_bounds(screen);
+    Bounds {
+        x: screen.0 - TRACK_MARGIN - TRACK_WIDTH - HANDLE_WIDTH / 2.0,
+        y: strip.y + crate::scrubber::BAR_HEIGHT,
+        w: TRACK_WIDTH + HANDLE_WIDTH,
+        h: strip.h - crate::scrubber::BAR_HEIGHT,
+    }
+}
+
+/// The line the handle slides along, as its left end, length and height
+pub fn track(screen: (f32, f32)) -> (f32, f32, f32) {
+    let bounds = slider_bounds(screen);
+    (
+        bounds.x + HANDLE_WIDTH / 2.0,
+        TRACK_WIDTH,
+        bounds.y + bounds.h / 2.0,
+    )
+}
+
+/// Whether a press at `point` grabs the slider
+pub fn hit(point: Point, screen: (f32, f32)) -> bool {
+    slider_bounds(screen).contains(point)
+}
+
+/// The speed for the handle dragged to `x`
+/// The track is logarithmic, so every step along it multiplies the speed by
+/// the same factor, and past either end sticks to the slowest or fastest speed.
+pub fn speed_at(x: f32, screen: (f32, f32)) -> f32 {
+    let (left, width, _) = track(screen);
+    let t = ((x - left) / width).clamp(0.0, 1.0);
+    MIN_SLIDER_SPEED * (MAX_SLIDER_SPEED / MIN_SLIDER_SPEED).powf(t)
+}
+
+/// Where the handle sits for `speed`, the inverse of `speed_at`
+pub fn handle_x(speed: f32, screen: (f32, f32)) -> f32 {
+    let (left, width, _) = track(screen);
+    let speed = speed.clamp(MIN_SLIDER_SPEED, MAX_SLIDER_SPEED);
+    let t = (speed / MIN_SLIDER_SPEED).ln() / (MAX_SLIDER_SPEED / MIN_SLIDER_SPEED).ln();
+    left + t * width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: (f32, f32) = (800.0, 600.0);
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3 * b.max(1.0)
+    }
+
+    #[test]
+    fn test_track_is_logarithmic() {
+        let (left, width, _) = track(SCREEN);
+        assert!(close(speed_at(left, SCREEN), MIN_SLIDER_SPEED));
+        assert!(close(speed_at(left + width, SCREEN), MAX_SLIDER_SPEED));
+        // The middle is the geometric mean of both ends
+        assert!(close(speed_at(left + width / 2.0, SCREEN), 15.811));
+        // Dragging past either end sticks to it
+        assert!(close(speed_at(0.0, SCREEN), MIN_SLIDER_SPEED));
+        assert!(close(speed_at(10_000.0, SCREEN), MAX_SLIDER_SPEED));
+    }
+
+    #[test]
+    fn test_handle_round_trips() {
+        for speed in [0.5, 1.0, 10.0, 37.5, 100.0, 500.0] {
+            assert!(close(speed_at(handle_x(speed, SCREEN), SCREEN), speed));
+        }
+        // Speeds outside the slider's range pin the handle to an end
+        let (left, width, _) = track(SCREEN);
+        assert_eq!(handle_x(0.1, SCREEN), left);
+        assert_eq!(handle_x(1000.0, SCREEN), left + width);
+    }
+
+    #[test]
+    fn test_slider_sits_clear_of_buttons_and_bar() {
+        let slider = slider_bounds(SCREEN);
+        let strip = buttons::strip_bounds(SCREEN);
+        let bar = crate::scrubber::bar_bounds(SCREEN);
+        assert!(slider.x + slider.w <= SCREEN.0 && slider.y + slider.h <= SCREEN.1);
+        assert!(slider.y >= bar.y + bar.h && slider.y >= strip.y);
+        for rect in buttons::layout(SCREEN, buttons::buttons(true).len()) {
+            assert!(rect.x + rect.w < slider.x);
+        }
+        let (left, width, y) = track(SCREEN);
+        assert!(hit((left + width / 3.0, y), SCREEN));
+        assert!(!hit((left, bar.y + 1.0), SCREEN));
+        assert!(!hit((400.0, 300.0), SCREEN));
+    }
+}
@@ -0,0 +1,230 @@
+use crate::session::Session;
+use std::path::{Path, PathBuf};
+
+/// Number of quick save slots, one per function key from F1 to F9
+pub const SLOT_COUNT: usize = 9;
+
+/// The file slot `n` is kept in, next to the session file at `session`
+pub fn slot_path(session: &Path, n: usize) -> PathBuf {
+    session.with_file_name(format!("slot-{n}.txt"))
+}
+
+/// What the help overlay and the prompts tell about a filled slot
+#[derive(Clone, PartialEq, Debug)]
+pub struct SlotInfo {
+    /// When the slot was saved, as a `YYYYMMDD-HHMMSS` UTC timestamp
+    pub saved: Option<String>,
+    pub generation: u64,
+    pub population: usize,
+}
+
+impl SlotInfo {
+    pub fn of(session: &Session) -> Self {
+        Self {
+            saved: session.saved.clone(),
+            generation: session.grid.generation(),
+            population: session.grid.population(),
+        }
+    }
+
+    /// e.g. "gen 120, pop 45, saved 2024-06-01 13:45:02"
+    pub fn label(&self) -> String {
+        let mut label = format!("gen {}, pop {}", self.generation, self.population);
+        if let Some(saved) = &self.saved {
+            label.push_str(&format!(", saved {}", readable_timestamp(saved)));
+        }
+        label
+    }
+}
+
+/// A `YYYYMMDD-HHMMSS` timestamp as `YYYY-MM-DD HH:MM:SS`, or as it is when
+/// it has another shape
+fn readable_timestamp(timestamp: &str) -> String {
+    let digits = |range: std::ops::Range<usize>| {
+        timestamp
+            .get(range)
+            .filter(|part| part.bytes().all(|b| b.is_ascii_digit()))
+    };
+    let parts = (
+        digits(0..4),
+        digits(4..6),
+        digits(6..8),
+        digits(9..11),
+        digits(11..13),
+        digits(13..15),
+    );
+    match parts {
+        (Some(year), Some(month), Some(day), Some(hour), Some(minute), Some(second))
+            if timestamp.len() == 15 && &timestamp[8..9] == "-" =>
+        {
+            format!("{year}-{month}-{day} {hour}:{minute}:{second}")
+        }
+        _ => timestamp.to_string(),
+    }
+}
+
+/// Something asked of a slot, which may need confirming first
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SlotAction {
+    Save(usize),
+    Load(usize),
+}
+
+/// The warning to show before `action` goes through, or `None` when it can
+/// go ahead right away
+///
+/// # Arguments
+/// * `filled` - What the slot holds, if anything
+/// * `dirty` - Whether the board has edits that loading would lose
+pub fn warning(action: SlotAction, filled: Option<&SlotInfo>, dirty: bool) -> Option<String> {
+    match (action, filled) {
+        (SlotAction::Save(n), Some(info)) => Some(format!(
+            "Slot {n} holds {}, press again to overwrite",
+            info.label()
+        )),
+        (SlotAction::Load(n), Some(_)) if dirty => Some(format!(
+            "Unsaved changes, press again to load slot {n} over them"
+        )),
+        _ => None,
+    }
+}
+
+/// The metadata of every slot, read once and kept up to date as slots are saved
+pub struct Slots {
+    /// The session file the slots sit next to; without one there are no slots
+    session: Option<PathBuf>,
+    infos: Vec<Option<SlotInfo>>,
+}
+
+impl Slots {
+    /// The slots next to `session`, read from disk
+    pub fn new(session: Option<PathBuf>) -> Self {
+        let infos = (1..=SLOT_COUNT)
+            .map(|n| {
+                let path = slot_path(session.as_deref()?, n);
+                Session::load(&path)
+                    .ok()
+                    .map(|session| SlotInfo::of(&session))
+            })
+            .collect();
+        Self { session, infos }
+    }
+
+    /// The file of slot `n`, counting from 1
+    pub fn path(&self, n: usize) -> Option<PathBuf> {
+        Some(slot_path(self.session.as_deref()?, n))
+    }
+
+    pub fn info(&self, n: usize) -> Option<&SlotInfo> {
+        self.infos.get(n.checked_sub(1)?)?.as_ref()
+    }
+
+    /// Remember what slot `n` holds after saving it
+    pub fn saved(&mut self, n: usize, info: SlotInfo) {
+        if let Some(slot) = n.checked_sub(1).and_then(|i| self.infos.get_mut(i)) {
+            *slot = Some(info);
+        }
+    }
+
+    /// Every filled slot with its number, in order
+    pub fn filled(&self) -> impl Iterator<Item = (usize, &SlotInfo)> {
+        self.infos
+            .iter()
+            .enumerate()
+            .filter_map(|(i, info)| Some((i + 1, info.as_ref()?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bookmarks::Bookmarks;
+    use crate::conways::Grid;
+    use std::fs;
+
+    fn session(generation: u64) -> Session {
+        let mut grid = Grid::new(8, 8);
+        for x in 2..5 {
+            grid.toggle(x, 3);
+        }
+        grid.set_generation(generation);
+        Session {
+            grid,
+            update_interval: 0.1,
+            rule: "B3/S23".to_string(),
+            bookmarks: Bookmarks::new(),
+            camera: None,
+            saved: Some("20240601-134502".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_slots_sit_next_to_the_session() {
+        let session = Path::new("/data/conways/session.txt");
+        assert_eq!(
+            slot_path(session, 3),
+            PathBuf::from("/data/conways/slot-3.txt")
+        );
+        let slots = Slots::new(Some(session.to_path_buf()));
+        assert_eq!(
+            slots.path(9),
+            Some(PathBuf::from("/data/conways/slot-9.txt"))
+        );
+        // Without a data directory there is nowhere to keep them
+        let slots = Slots::new(None);
+        assert_eq!(slots.path(1), None);
+        assert_eq!(slots.filled().count(), 0);
+    }
+
+    #[test]
+    fn test_saving_a_filled_slot_or_loading_over_edits_asks_first() {
+        let info = SlotInfo::of(&session(120));
+        assert_eq!(warning(SlotAction::Save(2), None, true), None);
+        assert_eq!(
+            warning(SlotAction::Save(2), Some(&info), false).as_deref(),
+            Some(
+                "Slot 2 holds gen 120, pop 3, saved 2024-06-01 13:45:02, press again to overwrite"
+            )
+        );
+        assert_eq!(warning(SlotAction::Load(4), Some(&info), false), None);
+        assert!(warning(SlotAction::Load(4), Some(&info), true)
+            .unwrap()
+            .contains("load slot 4"));
+        // An empty slot has nothing to load, which the game says itself
+        assert_eq!(warning(SlotAction::Load(4), None, true), None);
+    }
+
+    #[test]
+    fn test_metadata_is_read_from_the_slot_files() {
+        let dir = std::env::temp_dir().join(format!("conways-slots-{}", std::process::id()));
+        let path = dir.join("session.txt");
+        session(7).save(&slot_path(&path, 1)).unwrap();
+        let mut older = session(30);
+        older.saved = None;
+        older.save(&slot_path(&path, 5)).unwrap();
+        fs::write(slot_path(&path, 6), "not a session").unwrap();
+
+        let mut slots = Slots::new(Some(path));
+        let filled: Vec<_> = slots.filled().map(|(n, info)| (n, info.label())).collect();
+        assert_eq!(
+            filled,
+            vec![
+                (1, "gen 7, pop 3, saved 2024-06-01 13:45:02".to_string()),
+                (5, "gen 30, pop 3".to_string()),
+            ]
+        );
+        slots.saved(6, SlotInfo::of(&session(1)));
+        assert_eq!(slots.info(6).map(|info| info.generation), Some(1));
+        assert_eq!(slots.info(2), None);
+        assert_eq!(slots.info(0), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_odd_timestamps_are_shown_as_they_are() {
+        assert_eq!(readable_timestamp("20240601-134502"), "2024-06-01 13:45:02");
+        assert_eq!(readable_timestamp("yesterday"), "yesterday");
+        assert_eq!(readable_timestamp("2024060l-134502"), "2024060l-134502");
+    }
+}
@@ -0,0 +1,272 @@
+use crate::observer::{GridObserver, Stats};
+
+/// Samples per second of the generated sounds
+pub const SAMPLE_RATE: u32 = 22_050;
+
+/// Number of tick sounds, from the lowest pitch for a nearly empty board to
+/// the highest for a crowded one
+pub const TICK_PITCHES: usize = 8;
+
+/// Pitch of the lowest tick, in Hz; the highest one is two octaves above
+const LOWEST_TICK: f32 = 330.0;
+
+/// Length of a tick and of a click, in seconds
+const TICK_LENGTH: f32 = 0.04;
+const CLICK_LENGTH: f32 = 0.012;
+
+/// Shortest time between two sounds, in seconds
+/// Fast speeds and turbo mode step far more often than this, and a sound per
+/// generation would blur into a buzz.
+pub const MIN_SOUND_GAP: f32 = 0.08;
+
+/// Cells dying in a single step that make a die-off worth a click
+pub const DIE_OFF_CELLS: u64 = 40;
+
+/// Which simulation events make a sound
+/// - `Off`: Silence, the default
+/// - `Ticks`: A soft tick per generation, higher for larger populations
+/// - `DieOffs`: A click whenever many cells die at once
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SoundMode {
+    #[default]
+    Off,
+    Ticks,
+    DieOffs,
+}
+
+impl SoundMode {
+    /// The mode after this one, wrapping around
+    pub fn next(self) -> Self {
+        match self {
+            SoundMode::Off => SoundMode::Ticks,
+            SoundMode::Ticks => SoundMode::DieOffs,
+            SoundMode::DieOffs => SoundMode::Off,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SoundMode::Off => "off",
+            SoundMode::Ticks => "ticks",
+            SoundMode::DieOffs => "die-offs",
+        }
+    }
+}
+
+/// A sound to play
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Cue {
+    /// The tick at this pitch, from 0 to `TICK_PITCHES - 1`
+    Tick(usize),
+    Click,
+}
+
+/// Decides which sound, if any, a frame of the simulation makes
+///
+/// Kept apart from playing sounds, so the choice can be tested without audio.
+pub struct Cues {
+    mode: SoundMode,
+    /// Seconds since the last sound
+    since: f32,
+}
+
+impl Cues {
+    pub fn new() -> Self {
+        Self {
+            mode: SoundMode::Off,
+            since: MIN_SOUND_GAP,
+        }
+    }
+
+    /// Switch to the next mode
+    pub fn cycle(&mut self) -> SoundMode {
+        self.mode = self.mode.next();
+        self.mode
+    }
+
+    /// The sound for a frame of `dt` seconds
+    ///
+    /// # Arguments
+    /// * `stepped` - Whether at least one generation ran during the frame
+    /// * `population` - Live cells at the end of the frame
+    /// * `die_off` - The most cells that died in a single step of the frame
+    pub fn frame(&mut self, dt: f32, stepped: bool, population: u64, die_off: u64) -> Option<Cue> {
+        self.since += dt;
+        let cue = match self.mode {
+            SoundMode::Off => None,
+            SoundMode::Ticks => stepped.then(|| Cue::Tick(tick_pitch(population))),
+            SoundMode::DieOffs => (die_off >= DIE_OFF_CELLS).then_some(Cue::Click),
+        };
+        if cue.is_some() && self.since >= MIN_SOUND_GAP {
+            self.since = 0.0;
+            cue
+        } else {
+            None
+        }
+    }
+}
+
+/// Pitch of the tick for a board of `population` live cells
+/// Every doubling of the population raises the pitch, up to 2^14 cells.
+pub fn tick_pitch(population: u64) -> usize {
+    let doublings = (u64::BITS - population.leading_zeros()) as usize;
+    (doublings * TICK_PITCHES / 15).min(TICK_PITCHES - 1)
+}
+
+/// Keeps the most cells that died in a single generation, as an observer of
+/// the board, until the frame's sound is picked
+#[derive(Default)]
+pub struct DieOffs(u64);
+
+impl DieOffs {
+    /// The most cells that died in a generation since the last call
+    pub fn take(&mut self) -> u64 {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl GridObserver for DieOffs {
+    fn on_generation(&mut self, _generation: u64, stats: &Stats) {
+        self.0 = self.0.max(stats.deaths as u64);
+    }
+}
+
+/// Every tick from the lowest pitch to the highest, as WAV files
+pub fn tick_sounds() -> Vec<Vec<u8>> {
+    (0..TICK_PITCHES)
+        .map(|pitch| {
+            let octaves = 2.0 * pitch as f32 / (TICK_PITCHES - 1) as f32;
+            wav(&tone(LOWEST_TICK * octaves.exp2(), TICK_LENGTH, 0.2))
+        })
+        .collect()
+}
+
+/// The die-off click, as a WAV file
+pub fn click_sound() -> Vec<u8> {
+    wav(&tone(1500.0, CLICK_LENGTH, 0.35))
+}
+
+/// A sine wave at `frequency` Hz fading out over `seconds`, as 16-bit samples
+fn tone(frequency: f32, seconds: f32, amplitude: f32) -> Vec<i16> {
+    let count = (seconds * SAMPLE_RATE as f32) as usize;
+    (0..count)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            // Fast linear attack against pops, then an exponential fade
+            let attack = (i as f32 / 32.0).min(1.0);
+            let fade = (-5.0 * t / seconds).exp();
+            let wave = (std::f32::consts::TAU * frequency * t).sin();
+            (wave * amplitude * attack * fade * i16::MAX as f32) as i16
+        })
+        .collect()
+}
+
+/// A mono 16-bit PCM WAV file holding `samples`
+fn wav(samples: &[i16]) -> Vec<u8> {
+    let data_size = (samples.len() * 2) as u32;
+    let mut bytes = Vec::with_capacity(44 + samples.len() * 2);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, one channel
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_mode(mode: SoundMode) -> Cues {
+        let mut cues = Cues::new();
+        while cues.mode != mode {
+            cues.cycle();
+        }
+        cues
+    }
+
+    #[test]
+    fn test_off_is_silent() {
+        let mut cues = Cues::new();
+        assert_eq!(cues.mode, SoundMode::Off);
+        assert_eq!(cues.frame(1.0, true, 100, 1000), None);
+
+        let labels: Vec<_> = (0..3).map(|_| cues.cycle().label()).collect();
+        assert_eq!(labels, ["ticks", "die-offs", "off"]);
+    }
+
+    #[test]
+    fn test_ticks_follow_generations() {
+        let mut cues = with_mode(SoundMode::Ticks);
+        assert_eq!(cues.frame(0.1, true, 1, 0), Some(Cue::Tick(0)));
+        // Paused frames are quiet
+        assert_eq!(cues.frame(0.1, false, 1, 0), None);
+        assert_eq!(
+            cues.frame(0.1, true, 1 << 20, 0),
+            Some(Cue::Tick(TICK_PITCHES - 1))
+        );
+    }
+
+    #[test]
+    fn test_throttled_at_high_speed() {
+        let mut cues = with_mode(SoundMode::Ticks);
+        // A second of turbo frames, every one of them stepping
+        let sounds = (0..60)
+            .filter_map(|_| cues.frame(1.0 / 60.0, true, 500, 0))
+            .count();
+        assert!(sounds <= (1.0 / MIN_SOUND_GAP).ceil() as usize);
+        assert!(sounds >= 5);
+    }
+
+    #[test]
+    fn test_clicks_on_die_offs_only() {
+        let mut cues = with_mode(SoundMode::DieOffs);
+        assert_eq!(cues.frame(0.1, true, 500, DIE_OFF_CELLS - 1), None);
+        assert_eq!(cues.frame(0.1, true, 500, DIE_OFF_CELLS), Some(Cue::Click));
+    }
+
+    #[test]
+    fn test_tick_pitch_rises_with_population() {
+        let pitches: Vec<_> = (0..20).map(|bits| tick_pitch(1 << bits)).collect();
+        assert!(pitches.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(tick_pitch(0), 0);
+        assert_eq!(tick_pitch(u64::MAX), TICK_PITCHES - 1);
+    }
+
+    #[test]
+    fn test_die_offs_keep_the_worst_generation() {
+        let mut die_offs = DieOffs::default();
+        for deaths in [3, 12, 5] {
+            let stats = Stats {
+                deaths,
+                ..Stats::default()
+            };
+            die_offs.on_generation(1, &stats);
+        }
+        assert_eq!(die_offs.take(), 12);
+        assert_eq!(die_offs.take(), 0);
+    }
+
+    #[test]
+    fn test_wav_layout() {
+        let bytes = click_sound();
+        let samples = (CLICK_LENGTH * SAMPLE_RATE as f32) as usize;
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(bytes.len(), 44 + samples * 2);
+        let data_size = u32::from_le_bytes(bytes[40..44].try_into().unwrap());
+        assert_eq!(data_size as usize, samples * 2);
+        assert_eq!(tick_sounds().len(), TICK_PITCHES);
+    }
+}
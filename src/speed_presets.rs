@@ -0,0 +1,128 @@
+/// Number of presets that get a key, Shift+1 to Shift+4
+pub const PRESET_SLOTS: usize = 4;
+
+/// Speed the presets multiply when `preset_base` isn't set, in generations per second
+pub const DEFAULT_PRESET_BASE: f32 = 10.0;
+
+/// Multiples of the base the presets jump to when `speed_presets` isn't set
+pub const DEFAULT_PRESET_MULTIPLES: [f32; PRESET_SLOTS] = [1.0, 2.0, 4.0, 8.0];
+
+/// Jumps straight to multiples of a base speed, remembering the speed before
+/// the jump so pressing the same preset again goes back to it
+pub struct SpeedPresets {
+    base: f32,
+    multiples: Vec<f32>,
+    /// The speed before the latest jump, until a preset goes back to it
+    previous: Option<f32>,
+}
+
+impl SpeedPresets {
+    pub fn new(base: f32, multiples: Vec<f32>) -> Self {
+        Self {
+            base,
+            multiples,
+            previous: None,
+        }
+    }
+
+    /// Speed of preset `n`, counting from 1, if there is one
+    pub fn speed(&self, n: usize) -> Option<f32> {
+        let multiple = self.multiples.get(n.checked_sub(1)?)?;
+        Some(self.base * multiple)
+    }
+
+    /// The preset running at `speed`, counting from 1
+    /// Speeds are compared loosely, since they go through an update interval.
+    pub fn active(&self, speed: f32) -> Option<usize> {
+        (1..=self.multiples.len()).find(|&n| {
+            self.speed(n)
+                .is_some_and(|preset| (preset - speed).abs() <= preset * 1e-3)
+        })
+    }
+
+    /// Press preset `n` while running at `current`
+    ///
+    /// # Returns
+    /// The speed to switch to: the preset's, or the one before it when it is
+    /// already running; `None` when there is no such preset or nowhere to go back to
+    pub fn press(&mut self, n: usize, current: f32) -> Option<f32> {
+        let speed = self.speed(n)?;
+        if self.active(current) == Some(n) {
+            return self.previous.take();
+        }
+        self.previous = Some(current);
+        Some(speed)
+    }
+
+    /// The HUD's name for the preset running at `speed`, e.g. "2x (20 g/s)"
+    pub fn label(&self, speed: f32) -> Option<String> {
+        let n = self.active(speed)?;
+        let preset = self.speed(n)?;
+        Some(format!("{}x ({} g/s)", self.multiples[n - 1], trim(preset)))
+    }
+}
+
+/// A speed with one decimal, dropped when it is whole
+fn trim(speed: f32) -> String {
+    let text = format!("{speed:.1}");
+    text.strip_suffix(".0").unwrap_or(&text).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn presets() -> SpeedPresets {
+        SpeedPresets::new(DEFAULT_PRESET_BASE, DEFAULT_PRESET_MULTIPLES.to_vec())
+    }
+
+    #[test]
+    fn test_presets_multiply_the_base() {
+        let presets = presets();
+        assert_eq!(presets.speed(1), Some(10.0));
+        assert_eq!(presets.speed(4), Some(80.0));
+        assert_eq!(presets.speed(0), None);
+        assert_eq!(presets.speed(5), None);
+        // Speeds that went through an interval still count as the preset
+        assert_eq!(presets.active(1.0 / (1.0 / 20.0)), Some(2));
+        assert_eq!(presets.active(15.0), None);
+        assert_eq!(presets.label(20.0).as_deref(), Some("2x (20 g/s)"));
+        assert_eq!(presets.label(15.0), None);
+
+        let odd = SpeedPresets::new(2.5, vec![0.5, 3.0]);
+        assert_eq!(odd.label(1.25).as_deref(), Some("0.5x (1.2 g/s)"));
+        assert_eq!(odd.label(7.5).as_deref(), Some("3x (7.5 g/s)"));
+    }
+
+    #[test]
+    fn test_pressing_the_running_preset_goes_back() {
+        let mut presets = presets();
+        assert_eq!(presets.press(3, 3.0), Some(40.0));
+        // Again, back to where it was
+        assert_eq!(presets.press(3, 40.0), Some(3.0));
+        // And forth again
+        assert_eq!(presets.press(1, 3.0), Some(10.0));
+        assert_eq!(presets.press(1, 10.0), Some(3.0));
+        assert_eq!(presets.press(1, 3.0), Some(10.0));
+        // From preset to preset, the previous one is the one gone back to
+        assert_eq!(presets.press(2, 10.0), Some(20.0));
+        assert_eq!(presets.press(2, 20.0), Some(10.0));
+        assert_eq!(presets.press(2, 10.0), Some(20.0));
+        assert_eq!(presets.press(2, 20.0), Some(10.0));
+        // With nothing to go back to, the running preset stays
+        assert_eq!(presets.press(1, 10.0), None);
+        assert_eq!(presets.press(9, 10.0), None);
+    }
+
+    #[test]
+    fn test_speed_changed_elsewhere_leaves_the_preset() {
+        let mut presets = presets();
+        presets.press(2, 5.0);
+        // Sped up with `=` from 20 to 40, which is preset 3's speed
+        assert_eq!(presets.active(40.0), Some(3));
+        assert_eq!(presets.press(3, 40.0), Some(5.0));
+        // Dragged to some other speed, pressing a preset jumps to it again
+        assert_eq!(presets.press(2, 33.0), Some(20.0));
+        assert_eq!(presets.press(2, 20.0), Some(33.0));
+    }
+}
@@ -0,0 +1,68 @@
+/// Most generations of population history kept
+pub const STATS_CAPACITY: usize = 1000;
+
+/// Population of the board after every generation, most recent last
+pub struct Stats {
+    population: Vec<usize>,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self {
+            population: Vec::with_capacity(STATS_CAPACITY * 2),
+        }
+    }
+
+    /// Add the population after a generation
+    /// Past `STATS_CAPACITY` the oldest entries are dropped, in batches so
+    /// recording stays cheap.
+    pub fn record(&mut self, population: usize) {
+        if self.population.len() == STATS_CAPACITY * 2 {
+            self.population.drain(..STATS_CAPACITY);
+        }
+        self.population.push(population);
+    }
+
+    /// The populations of the latest `count` generations, or fewer if the
+    /// history is shorter, oldest first
+    pub fn recent(&self, count: usize) -> &[usize] {
+        let count = count.min(STATS_CAPACITY);
+        &self.population[self.population.len().saturating_sub(count)..]
+    }
+
+    /// Forget the history, e.g. when a different board is loaded
+    pub fn clear(&mut self) {
+        self.population.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_history() {
+        let mut stats = Stats::new();
+        assert!(stats.recent(10).is_empty());
+
+        for population in 0..5 {
+            stats.record(population);
+        }
+        assert_eq!(stats.recent(3), &[2, 3, 4]);
+        assert_eq!(stats.recent(10), &[0, 1, 2, 3, 4]);
+
+        stats.clear();
+        assert!(stats.recent(10).is_empty());
+    }
+
+    #[test]
+    fn test_old_history_is_dropped() {
+        let mut stats = Stats::new();
+        for population in 0..STATS_CAPACITY * 5 {
+            stats.record(population);
+        }
+        let recent = stats.recent(usize::MAX);
+        assert_eq!(recent.len(), STATS_CAPACITY);
+        assert_eq!(recent.last(), Some(&(STATS_CAPACITY * 5 - 1)));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::observer::{GridObserver, Stats};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// First row of every statistics CSV
+pub const CSV_HEADER: &str = "generation,population,births,deaths,step_ms";
+
+/// Rows kept in the buffer between two flushes, so a crash loses at most this many
+pub const FLUSH_EVERY: usize = 100;
+
+/// A CSV file getting a row of statistics for every generation of a run, for
+/// spreadsheets and scripts
+///
+/// It writes its rows as an observer of the board, so births and deaths are
+/// those of the generation, edits in between not counting. Rows go through a
+/// buffer, flushed every `FLUSH_EVERY` of them, so logging costs the
+/// simulation next to nothing.
+pub struct StatsCsv {
+    out: BufWriter<File>,
+    unflushed: usize,
+    /// Seconds on some clock, for timing the steps
+    clock: fn() -> f64,
+    /// When the generation being stepped started
+    started: f64,
+    /// Why writing stopped, not yet reported by `take_error`
+    failed: Option<io::Error>,
+    /// Whether a write failed, after which no more rows are written
+    stopped: bool,
+}
+
+impl StatsCsv {
+    /// Create the file at `path`, replacing any, and write the header
+    ///
+    /// # Arguments
+    /// * `clock` - Seconds on a clock, read before and after every step
+    pub fn create(path: &Path, clock: fn() -> f64) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "{CSV_HEADER}")?;
+        out.flush()?;
+        Ok(Self {
+            out,
+            unflushed: 0,
+            clock,
+            started: clock(),
+            failed: None,
+            stopped: false,
+        })
+    }
+
+    /// Add the row for `generation`
+    ///
+    /// # Arguments
+    /// * `step` - Wall-clock seconds the step took
+    fn record(&mut self, generation: u64, stats: &Stats, step: f64) -> io::Result<()> {
+        writeln!(
+            self.out,
+            "{generation},{},{},{},{:.3}",
+            stats.population,
+            stats.births,
+            stats.deaths,
+            step * 1000.0
+        )?;
+        self.unflushed += 1;
+        if self.unflushed >= FLUSH_EVERY {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Write out the rows still in the buffer
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.unflushed = 0;
+        self.out.flush()
+    }
+
+    /// The error that stopped the rows, once
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.failed.take()
+    }
+}
+
+impl GridObserver for StatsCsv {
+    fn before_generation(&mut self, _generation: u64) {
+        self.started = (self.clock)();
+    }
+
+    fn on_generation(&mut self, generation: u64, stats: &Stats) {
+        if self.stopped {
+            return;
+        }
+        let step = (self.clock)() - self.started;
+        if let Err(err) = self.record(generation, stats, step) {
+            self.failed = Some(err);
+            self.stopped = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::{CellState, Grid};
+    use std::cell::Cell;
+    use std::fs;
+
+    fn path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("conways-{name}-{}.csv", std::process::id()))
+    }
+
+    thread_local! {
+        /// What `clock` reads, moved on by the tests
+        static NOW: Cell<f64> = const { Cell::new(0.0) };
+    }
+
+    fn clock() -> f64 {
+        NOW.with(Cell::get)
+    }
+
+    #[test]
+    fn test_rows_follow_the_generations() {
+        let path = path("stats-blinker");
+        let mut grid = Grid::new(7, 7);
+        for x in 1..4 {
+            grid.set(x, 2, CellState::Alive);
+        }
+        grid.set_observer(Box::new(StatsCsv::create(&path, clock).unwrap()));
+        grid.step_n(2);
+        // An edit between generations isn't a birth
+        grid.set(6, 6, CellState::Alive);
+        grid.next_cell_generation();
+        // Dropping the file flushes what is left in its buffer
+        drop(grid.take_observer());
+
+        let text = fs::read_to_string(&path).unwrap();
+        // A blinker turns: two ends die, two cells are born
+        assert_eq!(
+            text.lines().collect::<Vec<_>>(),
+            vec![
+                CSV_HEADER,
+                "1,3,2,2,0.000",
+                "2,3,2,2,0.000",
+                "3,3,2,3,0.000"
+            ]
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_rows_are_flushed_as_they_pile_up() {
+        let path = path("stats-flush");
+        let mut csv = StatsCsv::create(&path, clock).unwrap();
+        // The header is on disk right away
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("{CSV_HEADER}\n")
+        );
+
+        let stats = Stats {
+            population: 1,
+            births: 1,
+            deaths: 0,
+        };
+        csv.before_generation(1);
+        NOW.with(|now| now.set(now.get() + 0.0025));
+        csv.on_generation(1, &stats);
+        // A single row waits in the buffer
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+        let stats = Stats { births: 0, ..stats };
+        for generation in 2..=FLUSH_EVERY as u64 {
+            csv.before_generation(generation);
+            csv.on_generation(generation, &stats);
+        }
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), FLUSH_EVERY + 1);
+        assert_eq!(text.lines().nth(1), Some("1,1,1,0,2.500"));
+        assert_eq!(text.lines().nth(2), Some("2,1,0,0,0.000"));
+        assert!(csv.take_error().is_none());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_unwritable_path_fails_up_front() {
+        let path = std::env::temp_dir()
+            .join(format!("conways-missing-{}", std::process::id()))
+            .join("run.csv");
+        assert!(StatsCsv::create(&path, clock).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use crate::buttons::STRIP_HEIGHT;
+use crate::camera::Bounds;
+
+/// Height of the status bar along the bottom of the window, above the buttons
+pub const BAR_HEIGHT: f32 = 26.0;
+
+/// Space from the left edge to the first segment, and kept clear at the right
+pub const MARGIN: f32 = 10.0;
+
+/// Drawn between two segments
+pub const SEPARATOR: &str = " | ";
+
+/// Ends a segment cut short to fit
+pub const ELLIPSIS: &str = "…";
+
+/// Fewest characters of a segment worth showing before the ellipsis; a
+/// segment that can't keep this many is dropped instead
+const MIN_KEPT: usize = 3;
+
+/// The status bar: the full width of the window, just above the buttons
+pub fn bar_bounds(screen: (f32, f32)) -> Bounds {
+    Bounds {
+        x: 0.0,
+        y: screen.1 - STRIP_HEIGHT - BAR_HEIGHT,
+        w: screen.0,
+        h: BAR_HEIGHT,
+    }
+}
+
+/// Where the segments and separators go along the bar
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Layout {
+    /// Each shown segment with the x its text starts at, cut short if need be
+    pub segments: Vec<(f32, String)>,
+    /// The x each separator starts at
+    pub separators: Vec<f32>,
+}
+
+/// Lay `segments` out left to right in `width` pixels, from `MARGIN` on
+///
+/// Segments keep their order, with a separator between each two. The first
+/// one that runs past the right margin is cut short with an ellipsis, and it
+/// and everything after are dropped once too little of it would be left.
+///
+/// # Arguments
+/// * `measure` - How many pixels a text takes, so layouts can be worked out without a window
+pub fn layout(segments: &[String], width: f32, measure: impl Fn(&str) -> f32) -> Layout {
+    let right = width - MARGIN;
+    let separator = measure(SEPARATOR);
+    let mut layout = Layout::default();
+    let mut x = MARGIN;
+    for segment in segments {
+        let start = if layout.segments.is_empty() {
+            x
+        } else {
+            x + separator
+        };
+        let text_width = measure(segment);
+        if start + text_width <= right {
+            if !layout.segments.is_empty() {
+                layout.separators.push(x);
+            }
+            layout.segments.push((start, segment.clone()));
+            x = start + text_width;
+            continue;
+        }
+        if let Some(cut) = truncate(segment, right - start, &measure) {
+            if !layout.segments.is_empty() {
+                layout.separators.push(x);
+            }
+            layout.segments.push((start, cut));
+        }
+        break;
+    }
+    layout
+}
+
+/// The longest start of `text` that fits in `room` pixels with an ellipsis
+/// after it, if it keeps at least `MIN_KEPT` characters
+fn truncate(text: &str, room: f32, measure: impl Fn(&str) -> f32) -> Option<String> {
+    let ends: Vec<usize> = text.char_indices().map(|(i, _)| i).skip(MIN_KEPT).collect();
+    ends.into_iter().rev().find_map(|end| {
+        let cut = format!("{}{ELLIPSIS}", text[..end].trim_end());
+        (measure(&cut) <= room).then_some(cut)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ten pixels a character, like a monospaced font
+    fn measure(text: &str) -> f32 {
+        text.chars().count() as f32 * 10.0
+    }
+
+    fn segments(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|text| text.to_string()).collect()
+    }
+
+    fn texts(layout: &Layout) -> Vec<&str> {
+        layout
+            .segments
+            .iter()
+            .map(|(_, text)| text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_wide_bar_shows_every_segment() {
+        let bar = layout(&segments(&["Paused", "Gen 12", "Pop 5"]), 800.0, measure);
+        assert_eq!(texts(&bar), vec!["Paused", "Gen 12", "Pop 5"]);
+        // Each segment starts after the separator before it
+        assert_eq!(bar.segments[0].0, MARGIN);
+        assert_eq!(bar.separators, vec![70.0, 160.0]);
+        assert_eq!(bar.segments[1].0, 70.0 + 30.0);
+        assert_eq!(bar.segments[2].0, 160.0 + 30.0);
+    }
+
+    #[test]
+    fn test_narrow_bar_cuts_the_last_segment_that_fits() {
+        // 10 px margin each side leaves 180 px: "Paused" then " | " reach 100,
+        // so the next segment has room for 8 characters and the ellipsis
+        let bar = layout(
+            &segments(&["Paused", "Rule: B36/S23", "Pop 5"]),
+            200.0,
+            measure,
+        );
+        assert_eq!(texts(&bar), vec!["Paused", "Rule: B3…"]);
+        assert_eq!(bar.separators.len(), 1);
+        let (x, text) = bar.segments.last().unwrap();
+        assert!(x + measure(text) <= 200.0 - MARGIN);
+    }
+
+    #[test]
+    fn test_too_narrow_drops_segments_and_separators() {
+        // Room for "Paused", a separator and two characters: not worth cutting
+        let bar = layout(&segments(&["Paused", "Gen 12", "Pop 5"]), 140.0, measure);
+        assert_eq!(texts(&bar), vec!["Paused"]);
+        assert!(bar.separators.is_empty());
+        // Narrower than the first segment, it is cut on its own
+        let bar = layout(&segments(&["Running"]), 80.0, measure);
+        assert_eq!(texts(&bar), vec!["Runni…"]);
+        // Nothing fits at all
+        assert_eq!(
+            layout(&segments(&["Paused"]), 20.0, measure),
+            Layout::default()
+        );
+        assert_eq!(layout(&[], 800.0, measure), Layout::default());
+    }
+
+    #[test]
+    fn test_cut_never_ends_on_a_space() {
+        let cut = truncate("Tool: paint", 70.0, measure).unwrap();
+        assert_eq!(cut, "Tool:…");
+        // Multi-byte characters are cut on their boundaries
+        let cut = truncate("180° rotational", 60.0, measure).unwrap();
+        assert_eq!(cut, "180°…");
+    }
+
+    #[test]
+    fn test_bar_sits_on_the_buttons() {
+        let screen = (800.0, 600.0);
+        let bar = bar_bounds(screen);
+        assert_eq!(bar.y + bar.h, screen.1 - STRIP_HEIGHT);
+        assert_eq!(bar.w, screen.0);
+    }
+}
@@ -0,0 +1,404 @@
+//! A board's generations as an asynchronous stream, one frame per tick, for
+//! feeding async pipelines on any runtime
+//!
+//! The stream sleeps through a `Timer`, which is all it needs of the runtime;
+//! `sleep_fn` makes one from a runtime's sleep function, e.g.
+//! `sleep_fn(tokio::time::sleep)`. The first frame is a snapshot of the board
+//! and every later one a `GridDelta` from the frame before.
+//!
+//! Ticks are due every `interval` from the first frame on, whether or not the
+//! consumer keeps up. One that lags finds several generations due at once;
+//! `Backpressure` says whether it gets all of them, one frame each, or only
+//! the latest board.
+//!
+//! `GenerationStream::poll_next` has the signature of `futures::Stream`,
+//! which isn't implemented here while the futures crate isn't available to
+//! this build; `next_frame` awaits a frame without it.
+
+use crate::conways::delta::GridDelta;
+use crate::conways::Grid;
+use alloc::boxed::Box;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use core::time::Duration;
+
+/// What an async runtime provides the stream with
+pub trait Timer {
+    type Sleep: Future<Output = ()>;
+
+    /// Time since some fixed moment, which never goes backwards
+    fn now(&mut self) -> Duration;
+
+    /// A future done once `duration` has passed
+    fn sleep(&mut self, duration: Duration) -> Self::Sleep;
+}
+
+/// A `Timer` made of a runtime's sleep function and the system clock, see
+/// `sleep_fn`
+#[cfg(feature = "std")]
+pub struct SleepFn<F> {
+    sleep: F,
+    start: std::time::Instant,
+}
+
+/// The `Timer` of a runtime's sleep function, e.g. `tokio::time::sleep` or
+/// `async_io::Timer::after`, telling time by the system clock
+#[cfg(feature = "std")]
+pub fn sleep_fn<F, S>(sleep: F) -> SleepFn<F>
+where
+    F: FnMut(Duration) -> S,
+    S: Future,
+{
+    SleepFn {
+        sleep,
+        start: std::time::Instant::now(),
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F, S> Timer for SleepFn<F>
+where
+    F: FnMut(Duration) -> S,
+    S: Future,
+{
+    type Sleep = Ignore<S>;
+
+    fn now(&mut self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) -> Self::Sleep {
+        Ignore(Box::pin((self.sleep)(duration)))
+    }
+}
+
+/// A future with its output dropped, for sleeps that return something
+#[cfg(feature = "std")]
+pub struct Ignore<S>(Pin<Box<S>>);
+
+#[cfg(feature = "std")]
+impl<S: Future> Future for Ignore<S> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        self.0.as_mut().poll(cx).map(|_| ())
+    }
+}
+
+/// What a consumer that fell behind gets
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Backpressure {
+    /// Only the latest board, the generations in between in one delta
+    Skip,
+    /// Every generation due, each its own frame, straight after each other
+    /// until it caught up
+    Queue,
+}
+
+/// One item of the stream
+#[derive(Clone, Debug, PartialEq)]
+pub enum Frame {
+    /// The board as the stream started
+    Snapshot(Grid),
+    /// What changed since the frame before
+    Delta(GridDelta),
+}
+
+/// A board stepping a generation every tick, see the [module docs](self)
+pub struct GenerationStream<T: Timer> {
+    grid: Grid,
+    /// The board of the last frame, which the next delta starts from
+    sent: Option<Grid>,
+    interval: Duration,
+    timer: T,
+    backpressure: Backpressure,
+    /// When the first frame went, from which ticks are counted
+    start: Duration,
+    ticks: u64,
+    sleeping: Option<Pin<Box<T::Sleep>>>,
+}
+
+impl<T: Timer> GenerationStream<T> {
+    /// A stream stepping `grid` every `interval`
+    ///
+    /// # Panics
+    /// When `interval` is zero
+    ///
+    /// # Example
+    /// ```
+    /// use game_of_life::conways::Grid;
+    /// use game_of_life::stream::{sleep_fn, Backpressure, GenerationStream};
+    /// use std::time::Duration;
+    ///
+    /// let timer = sleep_fn(|duration| async move { std::thread::sleep(duration) });
+    /// let mut grid = Grid::new(20, 20);
+    /// grid.randomize(0.3, 1);
+    /// let stream = GenerationStream::new(grid, Duration::from_millis(5), timer, Backpressure::Skip);
+    /// // e.g. `while let Some(frame) = stream.next_frame().await { ... }` in an async task
+    /// assert_eq!(stream.grid().generation(), 0);
+    /// ```
+    pub fn new(grid: Grid, interval: Duration, timer: T, backpressure: Backpressure) -> Self {
+        assert!(!interval.is_zero(), "a zero interval would never sleep");
+        Self {
+            grid,
+            sent: None,
+            interval,
+            timer,
+            backpressure,
+            start: Duration::ZERO,
+            ticks: 0,
+            sleeping: None,
+        }
+    }
+
+    /// The board as of the latest frame
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Ticks due by now that no frame has covered yet, and how long until
+    /// the next one is
+    fn due(&mut self) -> (u64, Duration) {
+        let elapsed = self.timer.now().saturating_sub(self.start).as_nanos();
+        let interval = self.interval.as_nanos();
+        let due = u64::try_from(elapsed / interval).unwrap_or(u64::MAX);
+        let next = (self.ticks as u128 + 1) * interval;
+        let wait = u64::try_from(next.saturating_sub(elapsed)).unwrap_or(u64::MAX);
+        (due.saturating_sub(self.ticks), Duration::from_nanos(wait))
+    }
+
+    /// The next frame, as `futures::Stream::poll_next` would give it; the
+    /// stream never ends, so this is never `Ready(None)`
+    pub fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Frame>>
+    where
+        T: Unpin,
+    {
+        let this = self.get_mut();
+        if this.sent.is_none() {
+            this.start = this.timer.now();
+            this.sent = Some(this.grid.clone());
+            return Poll::Ready(Some(Frame::Snapshot(this.grid.clone())));
+        }
+        loop {
+            if let Some(sleep) = &mut this.sleeping {
+                if sleep.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                this.sleeping = None;
+            }
+            let (due, wait) = this.due();
+            if due == 0 {
+                this.sleeping = Some(Box::pin(this.timer.sleep(wait)));
+                continue;
+            }
+            let steps = match this.backpressure {
+                Backpressure::Skip => due,
+                Backpressure::Queue => 1,
+            };
+            this.grid.step_n(steps);
+            this.ticks += steps;
+            let sent = this.sent.as_mut().expect("set by the first frame");
+            let delta = GridDelta::between(sent, &this.grid).expect("stepping keeps the size");
+            sent.clone_from(&this.grid);
+            return Poll::Ready(Some(Frame::Delta(delta)));
+        }
+    }
+
+    /// A future of the next frame, for `stream.next_frame().await`
+    pub fn next_frame(&mut self) -> NextFrame<'_, T>
+    where
+        T: Unpin,
+    {
+        NextFrame(self)
+    }
+}
+
+/// The future of `GenerationStream::next_frame`
+pub struct NextFrame<'a, T: Timer>(&'a mut GenerationStream<T>);
+
+impl<T: Timer + Unpin> Future for NextFrame<'_, T> {
+    type Output = Option<Frame>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Frame>> {
+        Pin::new(&mut *self.0).poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns;
+    use alloc::rc::Rc;
+    use alloc::sync::Arc;
+    use alloc::vec::Vec;
+    use core::cell::{Cell, RefCell};
+    use core::task::Waker;
+    use std::task::Wake;
+
+    /// A clock only the test moves, keeping every sleep asked for
+    #[derive(Clone, Default)]
+    struct MockTimer {
+        now: Rc<Cell<Duration>>,
+        sleeps: Rc<RefCell<Vec<Duration>>>,
+    }
+
+    impl MockTimer {
+        fn advance(&self, by: Duration) {
+            self.now.set(self.now.get() + by);
+        }
+    }
+
+    /// Done once the mock clock reaches `until`
+    struct MockSleep {
+        now: Rc<Cell<Duration>>,
+        until: Duration,
+    }
+
+    impl Future for MockSleep {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+            if self.now.get() >= self.until {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+
+    impl Timer for MockTimer {
+        type Sleep = MockSleep;
+
+        fn now(&mut self) -> Duration {
+            self.now.get()
+        }
+
+        fn sleep(&mut self, duration: Duration) -> MockSleep {
+            self.sleeps.borrow_mut().push(duration);
+            MockSleep {
+                now: Rc::clone(&self.now),
+                until: self.now.get() + duration,
+            }
+        }
+    }
+
+    /// A waker that does nothing, the tests poll by hand
+    struct Noop;
+
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    fn stream(backpressure: Backpressure) -> (GenerationStream<MockTimer>, MockTimer) {
+        let gun = patterns::find("gosper-glider-gun").unwrap().to_grid();
+        let timer = MockTimer::default();
+        let grid = Grid::centered(&gun, 60, 40);
+        (
+            GenerationStream::new(grid, ms(100), timer.clone(), backpressure),
+            timer,
+        )
+    }
+
+    fn poll(stream: &mut GenerationStream<MockTimer>) -> Poll<Option<Frame>> {
+        let waker = Waker::from(Arc::new(Noop));
+        Pin::new(stream).poll_next(&mut Context::from_waker(&waker))
+    }
+
+    /// The generation of the next frame, which has to be a delta, or `None`
+    /// while the stream waits
+    fn next_generation(stream: &mut GenerationStream<MockTimer>) -> Option<u64> {
+        match poll(stream) {
+            Poll::Ready(Some(Frame::Delta(delta))) => Some(delta.generation),
+            Poll::Pending => None,
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_a_frame_every_interval() {
+        let (mut stream, timer) = stream(Backpressure::Skip);
+        let start = stream.grid().clone();
+        let Poll::Ready(Some(Frame::Snapshot(mut copy))) = poll(&mut stream) else {
+            panic!("no snapshot first");
+        };
+        assert_eq!(copy, start);
+        assert_eq!(next_generation(&mut stream), None);
+        timer.advance(ms(60));
+        assert_eq!(next_generation(&mut stream), None);
+        timer.advance(ms(40));
+        for generation in 1..=5 {
+            let Poll::Ready(Some(Frame::Delta(delta))) = poll(&mut stream) else {
+                panic!("no delta at {generation}");
+            };
+            assert_eq!(delta.generation, generation);
+            delta.apply(&mut copy).unwrap();
+            assert_eq!(next_generation(&mut stream), None);
+            timer.advance(ms(100));
+        }
+        assert_eq!(copy, *stream.grid());
+        // One sleep a tick, however often polled while it lasts
+        assert_eq!(*timer.sleeps.borrow(), [ms(100); 6]);
+    }
+
+    #[test]
+    fn test_a_lagging_consumer_skips_to_the_latest_board() {
+        let (mut stream, timer) = stream(Backpressure::Skip);
+        let mut copy = stream.grid().clone();
+        assert!(poll(&mut stream).is_ready());
+        timer.advance(ms(350));
+        let Poll::Ready(Some(Frame::Delta(delta))) = poll(&mut stream) else {
+            panic!("no delta");
+        };
+        assert_eq!(delta.generation, 3);
+        delta.apply(&mut copy).unwrap();
+        assert_eq!(copy, *stream.grid());
+        assert_eq!(stream.grid().generation(), 3);
+        // Back on the schedule, the next tick at 400ms
+        assert_eq!(next_generation(&mut stream), None);
+        timer.advance(ms(50));
+        assert_eq!(next_generation(&mut stream), Some(4));
+    }
+
+    #[test]
+    fn test_a_lagging_consumer_gets_every_generation_queued() {
+        let (mut stream, timer) = stream(Backpressure::Queue);
+        assert!(poll(&mut stream).is_ready());
+        timer.advance(ms(350));
+        assert_eq!(next_generation(&mut stream), Some(1));
+        assert_eq!(next_generation(&mut stream), Some(2));
+        assert_eq!(next_generation(&mut stream), Some(3));
+        assert_eq!(next_generation(&mut stream), None);
+        timer.advance(ms(50));
+        assert_eq!(next_generation(&mut stream), Some(4));
+        assert_eq!(*timer.sleeps.borrow(), [ms(50)]);
+    }
+
+    #[test]
+    fn test_frames_can_be_awaited() {
+        let (mut stream, timer) = stream(Backpressure::Skip);
+        let waker = Waker::from(Arc::new(Noop));
+        let mut cx = Context::from_waker(&waker);
+        let mut generations = Vec::new();
+        for _ in 0..3 {
+            // A runtime of one task, whose clock jumps to the next sleep's end
+            let mut next = core::pin::pin!(stream.next_frame());
+            let frame = loop {
+                match next.as_mut().poll(&mut cx) {
+                    Poll::Ready(frame) => break frame,
+                    Poll::Pending => timer.advance(*timer.sleeps.borrow().last().unwrap()),
+                }
+            };
+            generations.push(match frame {
+                Some(Frame::Snapshot(grid)) => grid.generation(),
+                Some(Frame::Delta(delta)) => delta.generation,
+                None => panic!("the stream ended"),
+            });
+        }
+        assert_eq!(generations, [0, 1, 2]);
+    }
+}
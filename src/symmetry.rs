@@ -0,0 +1,159 @@
+/// Symmetry applied while painting, so hand-drawn soups stay symmetric
+/// - `None`: Only the cell under the cursor is affected
+/// - `Horizontal`: Mirrored left to right, about the vertical center line
+/// - `Vertical`: Mirrored top to bottom, about the horizontal center line
+/// - `FourFold`: Mirrored about both center lines
+/// - `Rotational`: Rotated 180° about the grid center
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Symmetry {
+    None,
+    Horizontal,
+    Vertical,
+    FourFold,
+    Rotational,
+}
+
+impl Symmetry {
+    /// Returns the next mode in the cycle, wrapping back to `None`
+    pub fn next(self) -> Self {
+        match self {
+            Symmetry::None => Symmetry::Horizontal,
+            Symmetry::Horizontal => Symmetry::Vertical,
+            Symmetry::Vertical => Symmetry::FourFold,
+            Symmetry::FourFold => Symmetry::Rotational,
+            Symmetry::Rotational => Symmetry::None,
+        }
+    }
+
+    /// Short human readable name, used by the HUD
+    pub fn label(self) -> &'static str {
+        match self {
+            Symmetry::None => "none",
+            Symmetry::Horizontal => "horizontal",
+            Symmetry::Vertical => "vertical",
+            Symmetry::FourFold => "4-fold",
+            Symmetry::Rotational => "180° rotational",
+        }
+    }
+}
+
+/// Computes every cell affected by painting `(x, y)` under the given symmetry mode
+///
+/// Mirroring is done about the grid center, so on a grid with an odd dimension
+/// the middle row/column maps onto itself. Cells lying exactly on an axis are
+/// only returned once.
+///
+/// # Arguments
+/// * `x` - The x-coordinate of the painted cell
+/// * `y` - The y-coordinate of the painted cell
+/// * `mode` - The active symmetry mode
+/// * `width` - The width of the grid
+/// * `height` - The height of the grid
+///
+/// # Returns
+/// The painted cell followed by its distinct mirrored counterparts
+///
+/// # Example
+/// ```
+/// let cells = mirror_coords(1, 2, Symmetry::Horizontal, 10, 10);
+/// assert_eq!(cells, vec![(1, 2), (8, 2)]);
+/// ```
+pub fn mirror_coords(
+    x: usize,
+    y: usize,
+    mode: Symmetry,
+    width: usize,
+    height: usize,
+) -> Vec<(usize, usize)> {
+    let mx = width - 1 - x;
+    let my = height - 1 - y;
+
+    let candidates = match mode {
+        Symmetry::None => vec![(x, y)],
+        Symmetry::Horizontal => vec![(x, y), (mx, y)],
+        Symmetry::Vertical => vec![(x, y), (x, my)],
+        Symmetry::FourFold => vec![(x, y), (mx, y), (x, my), (mx, my)],
+        Symmetry::Rotational => vec![(x, y), (mx, my)],
+    };
+
+    let mut cells = Vec::with_capacity(candidates.len());
+    for cell in candidates {
+        if !cells.contains(&cell) {
+            cells.push(cell);
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_returns_to_none() {
+        let mut mode = Symmetry::None;
+        for _ in 0..5 {
+            mode = mode.next();
+        }
+        assert_eq!(mode, Symmetry::None);
+    }
+
+    #[test]
+    fn test_no_symmetry() {
+        assert_eq!(mirror_coords(2, 3, Symmetry::None, 10, 10), vec![(2, 3)]);
+    }
+
+    #[test]
+    fn test_even_dimensions() {
+        assert_eq!(
+            mirror_coords(1, 2, Symmetry::Horizontal, 10, 8),
+            vec![(1, 2), (8, 2)]
+        );
+        assert_eq!(
+            mirror_coords(1, 2, Symmetry::Vertical, 10, 8),
+            vec![(1, 2), (1, 5)]
+        );
+        assert_eq!(
+            mirror_coords(1, 2, Symmetry::FourFold, 10, 8),
+            vec![(1, 2), (8, 2), (1, 5), (8, 5)]
+        );
+        assert_eq!(
+            mirror_coords(1, 2, Symmetry::Rotational, 10, 8),
+            vec![(1, 2), (8, 5)]
+        );
+    }
+
+    #[test]
+    fn test_odd_dimensions() {
+        assert_eq!(
+            mirror_coords(0, 0, Symmetry::FourFold, 5, 7),
+            vec![(0, 0), (4, 0), (0, 6), (4, 6)]
+        );
+        assert_eq!(
+            mirror_coords(1, 1, Symmetry::Rotational, 5, 7),
+            vec![(1, 1), (3, 5)]
+        );
+    }
+
+    #[test]
+    fn test_cells_on_the_axis() {
+        // The middle column of an odd-width grid mirrors onto itself
+        assert_eq!(
+            mirror_coords(2, 1, Symmetry::Horizontal, 5, 5),
+            vec![(2, 1)]
+        );
+        // The middle row of an odd-height grid mirrors onto itself
+        assert_eq!(mirror_coords(1, 2, Symmetry::Vertical, 5, 5), vec![(1, 2)]);
+        // On the vertical axis only the vertical mirror remains distinct
+        assert_eq!(
+            mirror_coords(2, 0, Symmetry::FourFold, 5, 5),
+            vec![(2, 0), (2, 4)]
+        );
+        // The exact center of an odd grid is its own counterpart in every mode
+        assert_eq!(mirror_coords(2, 2, Symmetry::FourFold, 5, 5), vec![(2, 2)]);
+        assert_eq!(
+            mirror_coords(2, 2, Symmetry::Rotational, 5, 5),
+            vec![(2, 2)]
+        );
+    }
+}
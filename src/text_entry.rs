@@ -0,0 +1,161 @@
+/// Longest text a `TextEntry` takes, in characters
+pub const MAX_ENTRY_LENGTH: usize = 40;
+
+/// A key press while a `TextEntry` captures the keyboard
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EntryKey {
+    Char(char),
+    Backspace,
+    Enter,
+    Escape,
+}
+
+/// What a key press did to a `TextEntry`
+#[derive(Clone, PartialEq, Debug)]
+pub enum EntryOutcome<T> {
+    /// Still typing, possibly with an error from the last `Enter` to show
+    Editing,
+    /// `Escape` closed the entry without a value
+    Cancelled,
+    /// `Enter` produced a valid value
+    Submitted(T),
+}
+
+/// A single line the player types into, e.g. a rulestring
+///
+/// Every key press goes to the entry while it is open. `Enter` only closes it
+/// once the text parses; otherwise the parse error is kept for display until
+/// the text is edited, so it can be fixed in place.
+pub struct TextEntry {
+    prompt: &'static str,
+    text: String,
+    error: Option<String>,
+}
+
+impl TextEntry {
+    pub fn new(prompt: &'static str) -> Self {
+        Self {
+            prompt,
+            text: String::new(),
+            error: None,
+        }
+    }
+
+    pub fn prompt(&self) -> &'static str {
+        self.prompt
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Why the text was rejected the last time `Enter` was pressed
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Handle a key press, parsing the text with `parse` on `Enter`
+    /// Control characters and text beyond `MAX_ENTRY_LENGTH` are ignored.
+    pub fn key<T, E: ToString>(
+        &mut self,
+        key: EntryKey,
+        parse: impl Fn(&str) -> Result<T, E>,
+    ) -> EntryOutcome<T> {
+        match key {
+            EntryKey::Char(c) => {
+                if !c.is_control() && self.text.chars().count() < MAX_ENTRY_LENGTH {
+                    self.text.push(c);
+                    self.error = None;
+                }
+            }
+            EntryKey::Backspace => {
+                self.text.pop();
+                self.error = None;
+            }
+            EntryKey::Escape => return EntryOutcome::Cancelled,
+            EntryKey::Enter => match parse(self.text.trim()) {
+                Ok(value) => return EntryOutcome::Submitted(value),
+                Err(err) => self.error = Some(err.to_string()),
+            },
+        }
+        EntryOutcome::Editing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_number(text: &str) -> Result<u32, String> {
+        text.parse().map_err(|_| format!("'{text}' isn't a number"))
+    }
+
+    fn type_text(entry: &mut TextEntry, text: &str) {
+        for c in text.chars() {
+            assert_eq!(
+                entry.key(EntryKey::Char(c), parse_number),
+                EntryOutcome::Editing
+            );
+        }
+    }
+
+    #[test]
+    fn test_typing_and_backspace() {
+        let mut entry = TextEntry::new("Number");
+        type_text(&mut entry, "12x");
+        assert_eq!(entry.text(), "12x");
+        entry.key(EntryKey::Backspace, parse_number);
+        assert_eq!(entry.text(), "12");
+        type_text(&mut entry, "3");
+        assert_eq!(
+            entry.key(EntryKey::Enter, parse_number),
+            EntryOutcome::Submitted(123)
+        );
+
+        // Backspace on an empty entry does nothing
+        let mut entry = TextEntry::new("Number");
+        entry.key(EntryKey::Backspace, parse_number);
+        assert_eq!(entry.text(), "");
+    }
+
+    #[test]
+    fn test_invalid_input_stays_open() {
+        let mut entry = TextEntry::new("Number");
+        type_text(&mut entry, "4o");
+        assert_eq!(
+            entry.key(EntryKey::Enter, parse_number),
+            EntryOutcome::Editing
+        );
+        assert_eq!(entry.error(), Some("'4o' isn't a number"));
+        assert_eq!(entry.text(), "4o");
+
+        // Fixing the text clears the error
+        entry.key(EntryKey::Backspace, parse_number);
+        assert_eq!(entry.error(), None);
+        type_text(&mut entry, "2");
+        assert_eq!(
+            entry.key(EntryKey::Enter, parse_number),
+            EntryOutcome::Submitted(42)
+        );
+    }
+
+    #[test]
+    fn test_escape_cancels() {
+        let mut entry = TextEntry::new("Number");
+        type_text(&mut entry, "7");
+        assert_eq!(
+            entry.key(EntryKey::Escape, parse_number),
+            EntryOutcome::Cancelled
+        );
+    }
+
+    #[test]
+    fn test_ignores_control_chars_and_overflow() {
+        let mut entry = TextEntry::new("Number");
+        type_text(&mut entry, "1\u{8}\t\r");
+        assert_eq!(entry.text(), "1");
+
+        type_text(&mut entry, &"9".repeat(MAX_ENTRY_LENGTH + 5));
+        assert_eq!(entry.text().len(), MAX_ENTRY_LENGTH);
+    }
+}
@@ -0,0 +1,253 @@
+use crate::immigration::Player;
+use macroquad::color::Color;
+
+/// Every color used to draw the game, so the whole look can be swapped at once
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub background: Color,
+    pub alive: Color,
+    pub grid_lines: Color,
+    pub hud_text: Color,
+    pub trail: Color,
+    pub ghost: Color,
+    pub selection: Color,
+    /// Transient notices and prompts
+    pub highlight: Color,
+    /// Indicators that need attention, e.g. an active recording
+    pub warning: Color,
+}
+
+impl Theme {
+    /// White cells on black, the original look
+    pub fn classic() -> Self {
+        Self {
+            name: "classic",
+            background: Color::from_rgba(0, 0, 0, 255),
+            alive: Color::from_rgba(255, 255, 255, 255),
+            grid_lines: Color::from_rgba(40, 40, 40, 255),
+            hud_text: Color::from_rgba(130, 130, 130, 255),
+            trail: Color::from_rgba(70, 110, 200, 255),
+            ghost: Color::from_rgba(100, 255, 150, 115),
+            selection: Color::from_rgba(100, 180, 255, 255),
+            highlight: Color::from_rgba(253, 249, 0, 255),
+            warning: Color::from_rgba(230, 41, 55, 255),
+        }
+    }
+
+    /// Muted colors loosely based on the Solarized palette
+    pub fn solarized() -> Self {
+        Self {
+            name: "solarized",
+            background: Color::from_rgba(0, 43, 54, 255),
+            alive: Color::from_rgba(238, 232, 213, 255),
+            grid_lines: Color::from_rgba(7, 54, 66, 255),
+            hud_text: Color::from_rgba(147, 161, 161, 255),
+            trail: Color::from_rgba(38, 139, 210, 255),
+            ghost: Color::from_rgba(133, 153, 0, 140),
+            selection: Color::from_rgba(42, 161, 152, 255),
+            highlight: Color::from_rgba(181, 137, 0, 255),
+            warning: Color::from_rgba(220, 50, 47, 255),
+        }
+    }
+
+    /// Maximum contrast for accessibility and projectors
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "high contrast",
+            background: Color::from_rgba(0, 0, 0, 255),
+            alive: Color::from_rgba(255, 255, 0, 255),
+            grid_lines: Color::from_rgba(90, 90, 90, 255),
+            hud_text: Color::from_rgba(255, 255, 255, 255),
+            trail: Color::from_rgba(0, 160, 255, 255),
+            ghost: Color::from_rgba(0, 255, 255, 160),
+            selection: Color::from_rgba(255, 0, 255, 255),
+            highlight: Color::from_rgba(0, 255, 0, 255),
+            warning: Color::from_rgba(255, 0, 0, 255),
+        }
+    }
+
+    /// The built-in presets, in the order they are cycled through
+    pub fn presets() -> [Theme; 3] {
+        [Self::classic(), Self::solarized(), Self::high_contrast()]
+    }
+
+    /// The color with the given field name, e.g. `"grid_lines"`, so it can be overridden
+    pub fn color_mut(&mut self, name: &str) -> Option<&mut Color> {
+        match name {
+            "background" => Some(&mut self.background),
+            "alive" => Some(&mut self.alive),
+            "grid_lines" => Some(&mut self.grid_lines),
+            "hud_text" => Some(&mut self.hud_text),
+            "trail" => Some(&mut self.trail),
+            "ghost" => Some(&mut self.ghost),
+            "selection" => Some(&mut self.selection),
+            "highlight" => Some(&mut self.highlight),
+            "warning" => Some(&mut self.warning),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a hex color string such as `#1e293b` or `#1e293b80` into a `Color`
+/// The leading `#` is optional; without an alpha component the color is opaque
+///
+/// # Example
+/// ```
+/// let slate = parse_hex_color("#1e293b").unwrap();
+/// ```
+pub fn parse_hex_color(hex: &str) -> Option<Color> {
+    let hex = hex.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Some(Color::from_rgba(
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+        alpha,
+    ))
+}
+
+/// `color` as `#rrggbb`, or `#rrggbbaa` when it isn't opaque, the way
+/// `parse_hex_color` reads it
+pub fn to_hex_color(color: Color) -> String {
+    let [r, g, b, a]: [u8; 4] = color.into();
+    if a == 255 {
+        format!("#{r:02x}{g:02x}{b:02x}")
+    } else {
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+}
+
+/// Color of a live cell with `neighbors` live neighbors, for the neighbor coloring mode
+/// Cold blues for the lonely cells that die of underpopulation, greens for the
+/// 2 or 3 that survive and hot oranges to reds for the crowded ones. The same
+/// in every theme, so the colors keep their meaning.
+pub fn neighbor_color(neighbors: u8) -> Color {
+    match neighbors {
+        0 => Color::from_rgba(40, 70, 220, 255),
+        1 => Color::from_rgba(90, 160, 255, 255),
+        2 => Color::from_rgba(60, 200, 90, 255),
+        3 => Color::from_rgba(150, 240, 80, 255),
+        4 => Color::from_rgba(255, 190, 40, 255),
+        5 => Color::from_rgba(255, 120, 30, 255),
+        _ => Color::from_rgba(230, 30, 30, 255),
+    }
+}
+
+/// Color of a live cell that was placed rather than born, when cell origins
+/// are shown: the theme's live color pulled towards amber, keeping its
+/// opacity, so the two kinds stay close but tell apart in every theme
+pub fn painted_color(alive: Color) -> Color {
+    const AMBER: Color = Color::new(1.0, 0.7, 0.15, 1.0);
+    const PULL: f32 = 0.45;
+    Color::new(
+        alive.r + (AMBER.r - alive.r) * PULL,
+        alive.g + (AMBER.g - alive.g) * PULL,
+        alive.b + (AMBER.b - alive.b) * PULL,
+        alive.a,
+    )
+}
+
+/// Color of the cells of an Immigration player, the same in every theme
+pub fn player_color(player: Player) -> Color {
+    match player {
+        Player::One => Color::from_rgba(235, 80, 60, 255),
+        Player::Two => Color::from_rgba(60, 140, 240, 255),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(
+            parse_hex_color("#1e293b"),
+            Some(Color::from_rgba(0x1e, 0x29, 0x3b, 255))
+        );
+        assert_eq!(
+            parse_hex_color("FFFFFF"),
+            Some(Color::from_rgba(255, 255, 255, 255))
+        );
+        assert_eq!(
+            parse_hex_color("#00000080"),
+            Some(Color::from_rgba(0, 0, 0, 0x80))
+        );
+    }
+
+    #[test]
+    fn test_hex_colors_round_trip() {
+        assert_eq!(
+            to_hex_color(Color::from_rgba(0x1e, 0x29, 0x3b, 255)),
+            "#1e293b"
+        );
+        assert_eq!(to_hex_color(Color::from_rgba(0, 0, 0, 0x80)), "#00000080");
+        for theme in Theme::presets() {
+            let hex = to_hex_color(theme.alive);
+            assert_eq!(to_hex_color(parse_hex_color(&hex).unwrap()), hex);
+        }
+    }
+
+    #[test]
+    fn test_parse_hex_color_rejects_malformed_input() {
+        assert_eq!(parse_hex_color(""), None);
+        assert_eq!(parse_hex_color("#12345"), None);
+        assert_eq!(parse_hex_color("#1234567"), None);
+        assert_eq!(parse_hex_color("#gggggg"), None);
+        assert_eq!(parse_hex_color("#é2345"), None);
+    }
+
+    #[test]
+    fn test_color_mut() {
+        let mut theme = Theme::classic();
+        *theme.color_mut("alive").unwrap() = parse_hex_color("#ff0000").unwrap();
+        assert_eq!(theme.alive, Color::from_rgba(255, 0, 0, 255));
+        assert!(theme.color_mut("name").is_none());
+    }
+
+    #[test]
+    fn test_neighbor_colors() {
+        for n in 0..=1 {
+            let color = neighbor_color(n);
+            assert!(color.b > color.r && color.b > color.g, "{n} is cold");
+        }
+        for n in 2..=3 {
+            let color = neighbor_color(n);
+            assert!(color.g > color.r && color.g > color.b, "{n} is green");
+        }
+        for n in 4..=8 {
+            let color = neighbor_color(n);
+            assert!(color.r > color.g && color.r > color.b, "{n} is hot");
+        }
+        // Every count up to 6 gets a color of its own
+        for a in 0..=6 {
+            for b in a + 1..=6 {
+                assert_ne!(neighbor_color(a), neighbor_color(b));
+            }
+        }
+        assert_eq!(neighbor_color(8), neighbor_color(6));
+    }
+
+    #[test]
+    fn test_painted_color_differs_from_alive() {
+        for theme in Theme::presets() {
+            let painted = painted_color(theme.alive);
+            assert_ne!(painted, theme.alive, "{}", theme.name);
+            assert_eq!(painted.a, theme.alive.a);
+        }
+    }
+
+    #[test]
+    fn test_presets_have_distinct_names() {
+        let presets = Theme::presets();
+        assert_ne!(presets[0].name, presets[1].name);
+        assert_ne!(presets[1].name, presets[2].name);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::conways::{Grid, InsertMode, Region};
+use std::fmt;
+
+/// Dead cells left between two copies when no pitch is configured
+pub const DEFAULT_TILE_GAP: usize = 1;
+
+/// A pitch tighter than the pattern it repeats
+///
+/// Overlapping copies would merge into some other pattern than the one
+/// repeated, so they are rejected instead of stamped.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PitchTooSmall {
+    pub pitch: (usize, usize),
+    pub pattern: (usize, usize),
+}
+
+impl fmt::Display for PitchTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a tile pitch of {}x{} would overlap the {}x{} pattern",
+            self.pitch.0, self.pitch.1, self.pattern.0, self.pattern.1
+        )
+    }
+}
+
+impl std::error::Error for PitchTooSmall {}
+
+/// The default pitch for `pattern`: its size plus `DEFAULT_TILE_GAP`
+pub fn default_pitch(pattern: &Grid) -> (usize, usize) {
+    (
+        pattern.width() + DEFAULT_TILE_GAP,
+        pattern.height() + DEFAULT_TILE_GAP,
+    )
+}
+
+/// Top-left cells of the copies of a `pattern`-sized pattern repeated every
+/// `pitch` cells across `region`, starting at its top-left corner
+///
+/// Every copy starts inside the region; the last ones in a row or column may
+/// not fit whole and are clipped by `tile`.
+///
+/// # Returns
+/// The origins row by row, or `PitchTooSmall` when copies would overlap
+pub fn tile_origins(
+    region: Region,
+    pattern: (usize, usize),
+    pitch: (usize, usize),
+) -> Result<Vec<(usize, usize)>, PitchTooSmall> {
+    if pitch.0 < pattern.0 || pitch.1 < pattern.1 || pitch.0 == 0 || pitch.1 == 0 {
+        return Err(PitchTooSmall { pitch, pattern });
+    }
+    let xs = (region.x..region.x + region.width).step_by(pitch.0);
+    let ys = (region.y..region.y + region.height).step_by(pitch.1);
+    Ok(ys.flat_map(|y| xs.clone().map(move |x| (x, y))).collect())
+}
+
+/// Stamp copies of `pattern` across `region` of `grid`, every `pitch` cells
+/// Copies are clipped to the region, which is clipped to the grid, and
+/// combined with the cells already there like a paste.
+///
+/// # Returns
+/// The number of copies, whole or clipped
+pub fn tile(
+    grid: &mut Grid,
+    pattern: &Grid,
+    region: Region,
+    pitch: (usize, usize),
+) -> Result<usize, PitchTooSmall> {
+    let size = (pattern.width(), pattern.height());
+    let origins = tile_origins(region, size, pitch)?;
+    // Laid out on a board the size of the region, which clips the last copies
+    let mut tiles = Grid::new(region.width, region.height);
+    for &(x, y) in &origins {
+        let (dx, dy) = ((x - region.x) as isize, (y - region.y) as isize);
+        tiles.insert_pattern(pattern, dx, dy, InsertMode::Overwrite);
+    }
+    grid.insert_pattern(&tiles, region.x as isize, region.y as isize, InsertMode::Or);
+    Ok(origins.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::CellState;
+
+    fn blinker() -> Grid {
+        let mut pattern = Grid::new(3, 1);
+        for x in 0..3 {
+            pattern.set(x, 0, CellState::Alive);
+        }
+        pattern
+    }
+
+    #[test]
+    fn test_origins_every_pitch() {
+        let region = Region::from_corners((2, 1), (11, 4));
+        let origins = tile_origins(region, (3, 1), (4, 2)).unwrap();
+        assert_eq!(
+            origins,
+            vec![(2, 1), (6, 1), (10, 1), (2, 3), (6, 3), (10, 3)]
+        );
+        // A region smaller than the pattern still starts a copy
+        let small = Region::from_corners((0, 0), (1, 0));
+        assert_eq!(tile_origins(small, (3, 1), (4, 2)).unwrap(), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn test_overlapping_pitch_is_rejected() {
+        let region = Region::from_corners((0, 0), (9, 9));
+        let err = tile_origins(region, (3, 3), (2, 4)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "a tile pitch of 2x4 would overlap the 3x3 pattern"
+        );
+        assert!(tile_origins(region, (0, 0), (0, 1)).is_err());
+        // Copies may touch
+        assert!(tile_origins(region, (3, 3), (3, 3)).is_ok());
+    }
+
+    #[test]
+    fn test_edge_copies_are_clipped_to_region() {
+        let mut grid = Grid::new(12, 4);
+        let pattern = blinker();
+        let region = Region::from_corners((0, 0), (9, 2));
+        let copies = tile(&mut grid, &pattern, region, default_pitch(&pattern)).unwrap();
+        // Three copies across two rows, the last one of each cut after two cells
+        assert_eq!(copies, 6);
+        assert_eq!(grid.population(), 2 * (3 + 3 + 2));
+        assert_eq!(grid.get(9, 0), CellState::Alive);
+        assert_eq!(grid.get(10, 0), CellState::Dead);
+        assert_eq!(grid.get(0, 2), CellState::Alive);
+        assert_eq!(grid.get(0, 1), CellState::Dead);
+    }
+}
@@ -0,0 +1,285 @@
+use crate::conways::Grid;
+use std::collections::VecDeque;
+
+/// Generations kept for stepping backwards when `--rewind` isn't given
+pub const DEFAULT_REWIND: usize = 100;
+
+/// The cells that changed between two boards, enough to go either way
+struct Delta {
+    /// Generation counter before the change
+    from: u64,
+    /// Generation counter after the change
+    to: u64,
+    /// Row-major indices of every cell that flipped
+    flipped: Vec<usize>,
+}
+
+impl Delta {
+    fn between(before: &Grid, after: &Grid) -> Self {
+        let width = before.width();
+        let flipped = (0..before.height())
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| before.get(x, y) != after.get(x, y))
+            .map(|(x, y)| y * width + x)
+            .collect();
+        Self {
+            from: before.generation(),
+            to: after.generation(),
+            flipped,
+        }
+    }
+
+    /// Flip the cells back or forth, setting the generation to `generation`
+    /// Cells the flips bring back to life count as born, like a generation's.
+    fn apply(&self, grid: &mut Grid, generation: u64) {
+        let width = grid.width();
+        for &i in &self.flipped {
+            grid.toggle_born(i % width, i / width);
+        }
+        grid.set_generation(generation);
+    }
+}
+
+/// The latest generations, for stepping backwards and forwards through them
+///
+/// Only the cells that changed are kept for every generation, so long
+/// histories of mostly still boards stay small. Stepping back moves
+/// generations to the "future", which stepping forward replays; any other
+/// change to the board while rewound discards that future.
+pub struct Timeline {
+    past: VecDeque<Delta>,
+    future: Vec<Delta>,
+    capacity: usize,
+}
+
+impl Timeline {
+    /// A timeline keeping the latest `capacity` generations
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            past: VecDeque::with_capacity(capacity),
+            future: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Remember how the simulation changed the board from `before` to `after`
+    /// Discards the future, since the simulation has moved on from the
+    /// rewound position.
+    pub fn record(&mut self, before: &Grid, after: &Grid) {
+        self.future.clear();
+        if self.capacity == 0 {
+            return;
+        }
+        if (before.width(), before.height()) != (after.width(), after.height()) {
+            self.past.clear();
+            return;
+        }
+        if self.past.len() == self.capacity {
+            self.past.pop_front();
+        }
+        self.past.push_back(Delta::between(before, after));
+    }
+
+    /// Remember a change already worked out as the row-major indices of the
+    /// cells that flipped, taking generation `from` to `to`
+    /// Like `record`, it discards the future.
+    pub fn record_flips(&mut self, from: u64, to: u64, flipped: Vec<usize>) {
+        self.future.clear();
+        if self.capacity == 0 {
+            return;
+        }
+        if self.past.len() == self.capacity {
+            self.past.pop_front();
+        }
+        self.past.push_back(Delta { from, to, flipped });
+    }
+
+    /// How many generations back it keeps at most
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Go back one generation
+    ///
+    /// # Returns
+    /// `false` when the oldest kept generation is already shown
+    pub fn back(&mut self, grid: &mut Grid) -> bool {
+        let Some(delta) = self.past.pop_back() else {
+            return false;
+        };
+        delta.apply(grid, delta.from);
+        self.future.push(delta);
+        true
+    }
+
+    /// Go forward one generation, after stepping back
+    ///
+    /// # Returns
+    /// `false` when the latest generation is already shown
+    pub fn forward(&mut self, grid: &mut Grid) -> bool {
+        let Some(delta) = self.future.pop() else {
+            return false;
+        };
+        delta.apply(grid, delta.to);
+        self.past.push_back(delta);
+        true
+    }
+
+    /// How many generations back from the latest the board is
+    pub fn rewound(&self) -> usize {
+        self.future.len()
+    }
+
+    /// How many generations are kept, including the shown one
+    pub fn len(&self) -> usize {
+        self.past.len() + self.future.len() + 1
+    }
+
+    /// Index of the shown generation among the kept ones, the oldest being 0
+    pub fn position(&self) -> usize {
+        self.past.len()
+    }
+
+    /// Index of the kept generation numbered `generation`, if it is kept
+    /// `shown` is the generation of the board on screen.
+    pub fn index_of(&self, generation: u64, shown: u64) -> Option<usize> {
+        self.past
+            .iter()
+            .map(|delta| delta.from)
+            .chain(std::iter::once(shown))
+            .chain(self.future.iter().rev().map(|delta| delta.to))
+            .position(|kept| kept == generation)
+    }
+
+    /// Step back or forward until the kept generation at `index` is shown
+    /// Indices past the latest generation show the latest one.
+    pub fn seek(&mut self, grid: &mut Grid, index: usize) {
+        while self.position() > index && self.back(grid) {}
+        while self.position() < index && self.forward(grid) {}
+    }
+
+    /// Drop the generations after the shown one, e.g. because the board was edited
+    pub fn truncate(&mut self) {
+        self.future.clear();
+    }
+
+    /// Forget everything, e.g. when the board is replaced by a different one
+    pub fn clear(&mut self) {
+        self.past.clear();
+        self.future.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conways::CellState;
+
+    /// An R-pentomino and a timeline of its first `steps` generations
+    fn run(steps: usize, capacity: usize) -> (Grid, Timeline) {
+        let mut grid = Grid::new(20, 20);
+        for (x, y) in [(10, 9), (11, 9), (9, 10), (10, 10), (10, 11)] {
+            grid.set(x, y, CellState::Alive);
+        }
+        let mut timeline = Timeline::new(capacity);
+        for _ in 0..steps {
+            let before = grid.clone();
+            grid.next_cell_generation();
+            timeline.record(&before, &grid);
+        }
+        (grid, timeline)
+    }
+
+    #[test]
+    fn test_back_then_forward_reproduces_grids() {
+        let (latest, mut timeline) = run(10, 100);
+        let (earlier, _) = run(6, 100);
+        let mut grid = latest.clone();
+
+        for _ in 0..4 {
+            assert!(timeline.back(&mut grid));
+        }
+        assert_eq!(grid, earlier);
+        assert_eq!(grid.generation(), 6);
+        assert_eq!(timeline.rewound(), 4);
+
+        for _ in 0..4 {
+            assert!(timeline.forward(&mut grid));
+        }
+        assert_eq!(grid, latest);
+        assert_eq!(grid.generation(), 10);
+        assert!(!timeline.forward(&mut grid));
+        assert_eq!(timeline.rewound(), 0);
+    }
+
+    #[test]
+    fn test_capacity_limits_rewinding() {
+        let (mut grid, mut timeline) = run(10, 3);
+        let (earliest, _) = run(7, 3);
+        while timeline.back(&mut grid) {}
+        assert_eq!(grid, earliest);
+
+        // Nothing is kept with no capacity
+        let (mut grid, mut timeline) = run(5, 0);
+        assert!(!timeline.back(&mut grid));
+    }
+
+    #[test]
+    fn test_edit_while_rewound_truncates() {
+        let (mut grid, mut timeline) = run(10, 100);
+        timeline.back(&mut grid);
+        timeline.back(&mut grid);
+
+        grid.toggle(0, 0);
+        timeline.truncate();
+        assert!(!timeline.forward(&mut grid));
+
+        // The older generations are still there
+        assert!(timeline.back(&mut grid));
+        assert_eq!(grid.generation(), 7);
+    }
+
+    #[test]
+    fn test_resuming_while_rewound_truncates() {
+        let (mut grid, mut timeline) = run(10, 100);
+        timeline.back(&mut grid);
+        timeline.back(&mut grid);
+
+        let before = grid.clone();
+        grid.next_cell_generation();
+        timeline.record(&before, &grid);
+        assert_eq!(grid.generation(), 9);
+        assert_eq!(timeline.rewound(), 0);
+        assert!(!timeline.forward(&mut grid));
+    }
+
+    #[test]
+    fn test_seek_both_ways() {
+        let (latest, mut timeline) = run(10, 100);
+        let (earlier, _) = run(3, 100);
+        let mut grid = latest.clone();
+        assert_eq!((timeline.len(), timeline.position()), (11, 10));
+
+        timeline.seek(&mut grid, 3);
+        assert_eq!(grid, earlier);
+        assert_eq!(timeline.position(), 3);
+        assert_eq!(timeline.rewound(), 7);
+        timeline.seek(&mut grid, 50);
+        assert_eq!(grid, latest);
+        assert_eq!(timeline.len(), 11);
+    }
+
+    #[test]
+    fn test_index_of_generation() {
+        let (mut grid, mut timeline) = run(10, 5);
+        assert_eq!(timeline.index_of(10, grid.generation()), Some(5));
+        assert_eq!(timeline.index_of(5, grid.generation()), Some(0));
+        // Too old to be kept, or not run yet
+        assert_eq!(timeline.index_of(4, grid.generation()), None);
+        assert_eq!(timeline.index_of(11, grid.generation()), None);
+
+        // Rewound generations are found in the future
+        timeline.seek(&mut grid, 1);
+        assert_eq!(timeline.index_of(9, grid.generation()), Some(4));
+    }
+}
@@ -0,0 +1,250 @@
+/// Longest time turbo mode spends stepping in a single frame, in seconds
+pub const TURBO_BUDGET: f64 = 0.012;
+
+/// Shortest time turbo mode keeps stepping per frame when frames run long
+pub const MIN_TURBO_BUDGET: f64 = 0.002;
+
+/// Frames slower than this make turbo mode step less, so input stays responsive
+pub const TARGET_FRAME_TIME: f64 = 1.0 / 30.0;
+
+/// Length of the window the achieved speed is averaged over, in seconds
+pub const RATE_WINDOW: f32 = 1.0;
+
+/// Most generations run in one frame at a fixed speed
+/// After a hitch the missed steps beyond this are dropped rather than caught up
+/// on, which would make the next frame slow too and fall further behind.
+pub const MAX_STEPS_PER_FRAME: u32 = 16;
+
+/// Turns frame times into a whole number of fixed-length simulation steps
+///
+/// Time left over after the last step is carried to the next frame, so the
+/// simulation runs at the requested speed whatever the frame rate.
+pub struct Accumulator {
+    carried: f32,
+}
+
+impl Accumulator {
+    pub fn new() -> Self {
+        Self { carried: 0.0 }
+    }
+
+    /// The number of steps due after a frame of `dt` seconds
+    ///
+    /// # Arguments
+    /// * `dt` - Duration of the frame, in seconds
+    /// * `interval` - Time between two generations, in seconds
+    pub fn advance(&mut self, dt: f32, interval: f32) -> u32 {
+        self.carried += dt;
+        let due = (self.carried / interval).floor();
+        if due > MAX_STEPS_PER_FRAME as f32 {
+            self.carried %= interval;
+            return MAX_STEPS_PER_FRAME;
+        }
+        self.carried -= due * interval;
+        due as u32
+    }
+
+    /// Forget the carried time, e.g. when switching modes
+    pub fn reset(&mut self) {
+        self.carried = 0.0;
+    }
+}
+
+/// Runs as many generations per frame as fit in a time budget
+///
+/// The budget starts at `TURBO_BUDGET` and shrinks whenever a whole frame,
+/// drawing included, takes longer than `TARGET_FRAME_TIME`, growing back once
+/// frames are fast again.
+pub struct Turbo {
+    budget: f64,
+}
+
+impl Turbo {
+    pub fn new() -> Self {
+        Self {
+            budget: TURBO_BUDGET,
+        }
+    }
+
+    /// Call `step` until the budget is spent, always at least once
+    ///
+    /// # Arguments
+    /// * `now` - The current time in seconds, read between steps
+    /// * `step` - Advances the simulation by one generation
+    ///
+    /// # Returns
+    /// The number of steps taken
+    pub fn run(&self, now: impl FnMut() -> f64, mut step: impl FnMut()) -> u64 {
+        self.run_while(now, || {
+            step();
+            true
+        })
+    }
+
+    /// Call `step` until the budget is spent or it returns `false`, always at
+    /// least once
+    ///
+    /// # Returns
+    /// The number of steps taken
+    pub fn run_while(&self, mut now: impl FnMut() -> f64, mut step: impl FnMut() -> bool) -> u64 {
+        let start = now();
+        let mut steps = 0;
+        loop {
+            let more = step();
+            steps += 1;
+            if !more || now() - start >= self.budget {
+                return steps;
+            }
+        }
+    }
+
+    /// Adjust the budget to the time the last frame took, in seconds
+    pub fn adapt(&mut self, frame_time: f64) {
+        let factor = if frame_time > TARGET_FRAME_TIME {
+            0.75
+        } else {
+            1.1
+        };
+        self.budget = (self.budget * factor).clamp(MIN_TURBO_BUDGET, TURBO_BUDGET);
+    }
+}
+
+/// Measures the generations per second actually run
+/// The rate is averaged over windows of `RATE_WINDOW` seconds, so it doesn't flicker.
+pub struct RateMeter {
+    generations: u64,
+    elapsed: f32,
+    rate: f32,
+}
+
+impl RateMeter {
+    pub fn new() -> Self {
+        Self {
+            generations: 0,
+            elapsed: 0.0,
+            rate: 0.0,
+        }
+    }
+
+    /// Count the generations run during a frame that took `dt` seconds
+    pub fn add(&mut self, generations: u64, dt: f32) {
+        self.generations += generations;
+        self.elapsed += dt;
+        if self.elapsed >= RATE_WINDOW {
+            self.rate = self.generations as f32 / self.elapsed;
+            self.generations = 0;
+            self.elapsed = 0.0;
+        }
+    }
+
+    /// Generations per second over the last complete window
+    pub fn rate(&self) -> f32 {
+        self.rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Run a turbo frame against a fake clock where every step takes `cost` seconds
+    fn run_with_cost(turbo: &Turbo, cost: f64) -> u64 {
+        let clock = Cell::new(0.0);
+        turbo.run(|| clock.get(), || clock.set(clock.get() + cost))
+    }
+
+    #[test]
+    fn test_turbo_fills_the_budget() {
+        let turbo = Turbo { budget: 0.5 };
+        assert_eq!(run_with_cost(&turbo, 0.125), 4);
+        assert_eq!(run_with_cost(&turbo, 0.0625), 8);
+        // A step slower than the whole budget still runs once
+        assert_eq!(run_with_cost(&turbo, 2.0), 1);
+    }
+
+    #[test]
+    fn test_turbo_stops_when_told() {
+        let turbo = Turbo { budget: 0.5 };
+        let mut left = 3;
+        let steps = turbo.run_while(
+            || 0.0,
+            || {
+                left -= 1;
+                left > 0
+            },
+        );
+        assert_eq!(steps, 3);
+    }
+
+    #[test]
+    fn test_turbo_budget_adapts() {
+        let mut turbo = Turbo::new();
+        turbo.adapt(0.1);
+        assert!(turbo.budget < TURBO_BUDGET);
+
+        // Slow frames never take the budget below the minimum
+        for _ in 0..100 {
+            turbo.adapt(0.1);
+        }
+        assert_eq!(turbo.budget, MIN_TURBO_BUDGET);
+
+        // Fast frames grow it back, up to the maximum
+        for _ in 0..100 {
+            turbo.adapt(0.01);
+        }
+        assert_eq!(turbo.budget, TURBO_BUDGET);
+    }
+
+    #[test]
+    fn test_accumulator_carries_remainder() {
+        let mut accumulator = Accumulator::new();
+        // Frames shorter than the interval add up
+        assert_eq!(accumulator.advance(0.125, 0.25), 0);
+        assert_eq!(accumulator.advance(0.125, 0.25), 1);
+        // Frames longer than the interval run several steps, keeping the rest
+        assert_eq!(accumulator.advance(0.625, 0.25), 2);
+        assert_eq!(accumulator.advance(0.125, 0.25), 1);
+    }
+
+    #[test]
+    fn test_accumulator_is_frame_rate_independent() {
+        // 0.0625s steps for two seconds, at 30 and at 128 frames per second
+        let steps = |frames: u32| {
+            let mut accumulator = Accumulator::new();
+            (0..frames)
+                .map(|_| accumulator.advance(2.0 / frames as f32, 0.0625))
+                .sum::<u32>()
+        };
+        assert!((31..=32).contains(&steps(30)));
+        assert_eq!(steps(128), 32);
+    }
+
+    #[test]
+    fn test_accumulator_caps_after_stall() {
+        let mut accumulator = Accumulator::new();
+        assert_eq!(accumulator.advance(0.125, 0.25), 0);
+        // A five second hitch runs the maximum, without a backlog afterwards
+        assert_eq!(accumulator.advance(5.0, 0.25), MAX_STEPS_PER_FRAME);
+        assert_eq!(accumulator.advance(0.125, 0.25), 1);
+        assert_eq!(accumulator.advance(0.125, 0.25), 0);
+
+        accumulator.reset();
+        assert_eq!(accumulator.advance(0.125, 0.25), 0);
+    }
+
+    #[test]
+    fn test_rate_meter() {
+        let mut meter = RateMeter::new();
+        // Nothing is reported before the first window completes
+        meter.add(100, 0.5);
+        assert_eq!(meter.rate(), 0.0);
+        meter.add(100, 0.5);
+        assert_eq!(meter.rate(), 200.0);
+
+        // The next window starts from scratch
+        meter.add(10, 0.5);
+        meter.add(0, 0.5);
+        assert_eq!(meter.rate(), 10.0);
+    }
+}
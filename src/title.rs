@@ -0,0 +1,118 @@
+use std::io::{self, IsTerminal, Write};
+
+/// Name of the game, the window title before any generation ran
+pub const APP_NAME: &str = "Conway's Game of Life";
+
+/// Seconds between two updates of the title
+/// Often enough to follow the board, seldom enough not to rebuild and send a
+/// string every frame.
+pub const TITLE_INTERVAL: f32 = 0.25;
+
+/// The generation, population and pause state, e.g. `gen 1523, pop 348 [paused]`
+/// Shared by the terminal's status line and the title, so both read the same.
+pub fn summary(generation: u64, population: usize, paused: bool) -> String {
+    let mut text = format!("gen {generation}, pop {population}");
+    if paused {
+        text.push_str(" [paused]");
+    }
+    text
+}
+
+/// The full title, e.g. `Conway's Game of Life — gen 1523, pop 348 [paused]`
+pub fn title(generation: u64, population: usize, paused: bool) -> String {
+    format!("{APP_NAME} — {}", summary(generation, population, paused))
+}
+
+/// Decides when the title needs changing
+///
+/// Kept apart from setting it, so the throttling can be tested without a window.
+pub struct TitleUpdater {
+    /// Seconds since the title was last looked at
+    since: f32,
+    /// What the title shows, `None` before the first update
+    shown: Option<(u64, usize, bool)>,
+}
+
+impl TitleUpdater {
+    pub fn new() -> Self {
+        Self {
+            since: TITLE_INTERVAL,
+            shown: None,
+        }
+    }
+
+    /// The new title after a frame of `dt` seconds, if it is time for one
+    /// Only builds a string once `TITLE_INTERVAL` seconds have passed and
+    /// something it shows changed.
+    pub fn poll(
+        &mut self,
+        dt: f32,
+        generation: u64,
+        population: usize,
+        paused: bool,
+    ) -> Option<String> {
+        self.since += dt;
+        if self.since < TITLE_INTERVAL {
+            return None;
+        }
+        self.since = 0.0;
+        let state = (generation, population, paused);
+        if self.shown == Some(state) {
+            return None;
+        }
+        self.shown = Some(state);
+        Some(title(generation, population, paused))
+    }
+}
+
+/// Show `title` as the title of the terminal the game runs in
+///
+/// miniquad only takes a title when the window opens and has no way to change
+/// it afterwards, so the terminal's title, which most terminal emulators show
+/// in their own window title or tab, is where it goes instead. Does nothing
+/// when the output isn't a terminal.
+pub fn set_terminal_title(title: &str) {
+    let mut out = io::stdout();
+    if out.is_terminal() {
+        // OSC 2, the xterm sequence for the window title
+        let _ = write!(out, "\x1b]2;{title}\x07");
+        let _ = out.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_title_text() {
+        assert_eq!(
+            title(1523, 348, true),
+            "Conway's Game of Life — gen 1523, pop 348 [paused]"
+        );
+        assert_eq!(summary(0, 5, false), "gen 0, pop 5");
+    }
+
+    #[test]
+    fn test_updates_are_throttled() {
+        let mut updater = TitleUpdater::new();
+        assert!(updater.poll(0.0, 1, 5, false).is_some());
+        // A second of frames at 60 fps, each one a new generation
+        let updates = (2..62)
+            .filter_map(|generation| updater.poll(1.0 / 60.0, generation, 5, false))
+            .count();
+        assert!(updates <= (1.0 / TITLE_INTERVAL).ceil() as usize);
+        assert!(updates >= 3);
+    }
+
+    #[test]
+    fn test_unchanged_state_keeps_title() {
+        let mut updater = TitleUpdater::new();
+        assert!(updater.poll(0.0, 7, 5, true).is_some());
+        assert_eq!(updater.poll(TITLE_INTERVAL, 7, 5, true), None);
+        assert_eq!(
+            updater.poll(TITLE_INTERVAL, 7, 5, false).as_deref(),
+            Some("Conway's Game of Life — gen 7, pop 5")
+        );
+    }
+}
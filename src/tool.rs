@@ -0,0 +1,36 @@
+/// What the left mouse button does on the board
+/// - `Paint`: Clicking or dragging makes cells alive
+/// - `Erase`: Clicking or dragging kills cells
+/// - `Toggle`: Clicking flips a cell, dragging paints the opposite of the cell the drag started on
+/// - `FloodFill`: Clicking flips the connected region of same-state cells under the cursor
+/// - `Select`: Dragging draws a selection rectangle
+/// - `Tile`: Dragging a rectangle fills it with copies of the clipboard
+/// - `Paste`: Clicking stamps the pending paste
+///
+/// The right button erases, or paints while erasing, whatever the tool.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Tool {
+    Paint,
+    Erase,
+    #[default]
+    Toggle,
+    FloodFill,
+    Select,
+    Tile,
+    Paste,
+}
+
+impl Tool {
+    /// Short human readable name, used by the HUD
+    pub fn label(self) -> &'static str {
+        match self {
+            Tool::Paint => "paint",
+            Tool::Erase => "erase",
+            Tool::Toggle => "toggle",
+            Tool::FloodFill => "fill",
+            Tool::Select => "select",
+            Tool::Tile => "tile",
+            Tool::Paste => "paste",
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use crate::input::{distance, midpoint, two_finger_motion, Point};
+
+/// Distance in pixels a finger has to travel before a touch becomes a drag,
+/// wider than the mouse's since fingers wobble
+pub const TAP_SLOP: f32 = 10.0;
+
+/// Time in seconds a finger can rest in place before its touch becomes a drag
+pub const HOLD_TIMEOUT: f32 = 0.3;
+
+/// What the fingers on the screen did during the current frame
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TouchGesture {
+    /// Nothing worth acting on happened
+    None,
+    /// One finger lifted before its touch turned into a drag
+    Tap(Point),
+    /// One finger is being dragged from `from` to `to`, starting where it
+    /// first touched on the first drag frame, like `Gesture::Drag`
+    Drag { from: Point, to: Point },
+    /// Two fingers moved: the view pans by `pan` and zooms by `zoom` around
+    /// the point halfway between them
+    Pinch {
+        pan: Point,
+        zoom: f32,
+        around: Point,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum State {
+    Idle,
+    /// One finger down that may still turn out to be a tap, a drag or the
+    /// first of two
+    Touched {
+        id: u64,
+        start: Point,
+        held: f32,
+    },
+    Dragging {
+        id: u64,
+        last: Point,
+    },
+    Pinching {
+        fingers: [(u64, Point); 2],
+    },
+    /// A gesture ended with fingers still down; they do nothing until all are lifted
+    Lifting,
+}
+
+/// State machine telling a tap, a one-finger drag and two fingers apart
+///
+/// A single touch stays pending until its finger moves further than
+/// `TAP_SLOP` or rests longer than `HOLD_TIMEOUT`, at which point it turns
+/// into a drag; lifting it sooner is a tap. A second finger landing turns
+/// what is going on into a pinch, after which nothing paints until every
+/// finger is lifted, so letting go of one of the two never paints with the other.
+pub struct TouchRecognizer {
+    state: State,
+}
+
+impl TouchRecognizer {
+    pub fn new() -> Self {
+        Self { state: State::Idle }
+    }
+
+    /// Feed the recognizer with the fingers down this frame
+    ///
+    /// # Arguments
+    /// * `touches` - Each finger still on the screen, by its id, in any order
+    /// * `dt` - Time elapsed since the previous frame, in seconds
+    ///
+    /// # Returns
+    /// The `TouchGesture` the game should react to this frame
+    pub fn update(&mut self, touches: &[(u64, Point)], dt: f32) -> TouchGesture {
+        let mut fingers = touches.to_vec();
+        fingers.sort_by_key(|&(id, _)| id);
+        let (state, gesture) = match (self.state, fingers.as_slice()) {
+            (_, []) => {
+                let gesture = match self.state {
+                    State::Touched { start, .. } => TouchGesture::Tap(start),
+                    _ => TouchGesture::None,
+                };
+                (State::Idle, gesture)
+            }
+            (State::Idle, &[(id, pos)]) => (
+                State::Touched {
+                    id,
+                    start: pos,
+                    held: 0.0,
+                },
+                TouchGesture::None,
+            ),
+            (State::Touched { id, start, held }, &[(now, pos)]) if now == id => {
+                let held = held + dt;
+                if distance(start, pos) >= TAP_SLOP || held >= HOLD_TIMEOUT {
+                    (
+                        State::Dragging { id, last: pos },
+                        TouchGesture::Drag {
+                            from: start,
+                            to: pos,
+                        },
+                    )
+                } else {
+                    (State::Touched { id, start, held }, TouchGesture::None)
+                }
+            }
+            (State::Dragging { id, last }, &[(now, pos)]) if now == id => (
+                State::Dragging { id, last: pos },
+                TouchGesture::Drag {
+                    from: last,
+                    to: pos,
+                },
+            ),
+            (State::Pinching { fingers: before }, &[a, b]) if [a.0, b.0] == before.map(|f| f.0) => {
+                let (pan, zoom) = two_finger_motion(before.map(|f| f.1), [a.1, b.1]);
+                (
+                    State::Pinching { fingers: [a, b] },
+                    TouchGesture::Pinch {
+                        pan,
+                        zoom,
+                        around: midpoint([a.1, b.1]),
+                    },
+                )
+            }
+            // A second finger joins whatever one finger was doing, or two
+            // different ones land at once
+            (State::Idle | State::Touched { .. } | State::Dragging { .. }, &[a, b]) => {
+                (State::Pinching { fingers: [a, b] }, TouchGesture::None)
+            }
+            // A finger lifted from a pinch, one swapped for another, or more
+            // than two down
+            _ => (State::Lifting, TouchGesture::None),
+        };
+
+        self.state = state;
+        gesture
+    }
+
+    /// Whether fingers are down, so the board follows them and not the
+    /// mouse that the platform fakes from them
+    pub fn is_touching(&self) -> bool {
+        self.state != State::Idle
+    }
+
+    /// Whether the fingers down move the view rather than edit: two of them,
+    /// or what is left of them until all are lifted
+    pub fn is_panning(&self) -> bool {
+        matches!(self.state, State::Pinching { .. } | State::Lifting)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: f32 = 1.0 / 60.0;
+
+    /// Feed a sequence of frames, collecting every gesture that isn't `None`
+    fn play(recognizer: &mut TouchRecognizer, frames: &[&[(u64, Point)]]) -> Vec<TouchGesture> {
+        frames
+            .iter()
+            .map(|touches| recognizer.update(touches, FRAME))
+            .filter(|gesture| *gesture != TouchGesture::None)
+            .collect()
+    }
+
+    #[test]
+    fn test_short_touch_is_a_tap() {
+        let mut recognizer = TouchRecognizer::new();
+        let gestures = play(
+            &mut recognizer,
+            &[&[(0, (10.0, 10.0))], &[(0, (14.0, 12.0))], &[]],
+        );
+        // Taps land where the finger first touched
+        assert_eq!(gestures, vec![TouchGesture::Tap((10.0, 10.0))]);
+        assert!(!recognizer.is_touching());
+        assert_eq!(recognizer.update(&[], FRAME), TouchGesture::None);
+    }
+
+    #[test]
+    fn test_moving_or_resting_is_a_drag() {
+        let mut recognizer = TouchRecognizer::new();
+        let gestures = play(
+            &mut recognizer,
+            &[
+                &[(3, (10.0, 10.0))],
+                &[(3, (30.0, 10.0))],
+                &[(3, (40.0, 10.0))],
+                &[],
+            ],
+        );
+        // No tap after the drag
+        assert_eq!(
+            gestures,
+            vec![
+                TouchGesture::Drag {
+                    from: (10.0, 10.0),
+                    to: (30.0, 10.0)
+                },
+                TouchGesture::Drag {
+                    from: (30.0, 10.0),
+                    to: (40.0, 10.0)
+                },
+            ]
+        );
+
+        // Resting in place past the timeout paints the cell under the finger
+        recognizer.update(&[(4, (50.0, 50.0))], FRAME);
+        assert_eq!(
+            recognizer.update(&[(4, (50.0, 50.0))], HOLD_TIMEOUT),
+            TouchGesture::Drag {
+                from: (50.0, 50.0),
+                to: (50.0, 50.0)
+            }
+        );
+        assert!(recognizer.is_touching() && !recognizer.is_panning());
+    }
+
+    #[test]
+    fn test_second_finger_turns_a_touch_into_a_pinch() {
+        let mut recognizer = TouchRecognizer::new();
+        // The second finger lands before the first moved: no tap, no paint
+        let gestures = play(
+            &mut recognizer,
+            &[
+                &[(0, (40.0, 50.0))],
+                &[(1, (60.0, 50.0)), (0, (40.0, 50.0))],
+                &[(0, (30.0, 60.0)), (1, (70.0, 60.0))],
+            ],
+        );
+        assert_eq!(
+            gestures,
+            vec![TouchGesture::Pinch {
+                pan: (0.0, 10.0),
+                zoom: 2.0,
+                around: (50.0, 60.0)
+            }]
+        );
+        assert!(recognizer.is_panning());
+
+        // Lifting one finger leaves the other doing nothing, even when it moves
+        let gestures = play(
+            &mut recognizer,
+            &[&[(1, (70.0, 60.0))], &[(1, (120.0, 90.0))], &[]],
+        );
+        assert!(gestures.is_empty());
+        assert!(!recognizer.is_touching());
+    }
+
+    #[test]
+    fn test_stray_fingers_do_nothing() {
+        let mut recognizer = TouchRecognizer::new();
+        // A third finger stops the pinch, and nothing comes of the rest
+        let gestures = play(
+            &mut recognizer,
+            &[
+                &[(0, (0.0, 0.0)), (1, (100.0, 0.0))],
+                &[(0, (0.0, 0.0)), (1, (100.0, 0.0)), (2, (50.0, 50.0))],
+                &[(0, (0.0, 0.0)), (1, (100.0, 0.0))],
+                &[],
+            ],
+        );
+        assert!(gestures.is_empty());
+
+        // One finger swapped for another in a single frame isn't a tap
+        let gestures = play(
+            &mut recognizer,
+            &[&[(5, (10.0, 10.0))], &[(6, (80.0, 80.0))], &[]],
+        );
+        assert!(gestures.is_empty());
+
+        // Two different fingers landing together start a new pinch
+        let gestures = play(
+            &mut recognizer,
+            &[
+                &[(7, (0.0, 0.0)), (8, (10.0, 0.0))],
+                &[(7, (5.0, 0.0)), (8, (15.0, 0.0))],
+            ],
+        );
+        assert_eq!(
+            gestures,
+            vec![TouchGesture::Pinch {
+                pan: (5.0, 0.0),
+                zoom: 1.0,
+                around: (10.0, 0.0)
+            }]
+        );
+    }
+}
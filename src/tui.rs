@@ -0,0 +1,287 @@
+use crate::conways::{CellState, Grid};
+use macroquad::input::KeyCode;
+use std::io::{self, Read, Write};
+use std::sync::Mutex;
+
+/// Cells scrolled by one press of an arrow key
+pub const PAN_STEP: isize = 4;
+
+/// Seconds between two frames drawn to the terminal
+pub const FRAME_TIME: f32 = 1.0 / 30.0;
+
+/// A key read from the terminal
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TermKey {
+    Char(char),
+    /// A letter typed while holding Ctrl, in lowercase
+    Ctrl(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Tab,
+    Escape,
+    Backspace,
+    /// The terminal window got the focus back
+    FocusIn,
+    /// The terminal window lost the focus
+    FocusOut,
+}
+
+/// Split raw terminal input into keys
+/// Escape sequences that aren't understood are dropped.
+pub fn parse_keys(input: &[u8]) -> Vec<TermKey> {
+    let mut keys = Vec::new();
+    let text = String::from_utf8_lossy(input);
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\x1b' if chars.peek() == Some(&'[') => {
+                chars.next();
+                match chars.next() {
+                    Some('A') => TermKey::Up,
+                    Some('B') => TermKey::Down,
+                    Some('C') => TermKey::Right,
+                    Some('D') => TermKey::Left,
+                    Some('I') => TermKey::FocusIn,
+                    Some('O') => TermKey::FocusOut,
+                    _ => {
+                        // Skip the rest of an unknown sequence, up to its final byte
+                        while chars
+                            .next_if(|c| !c.is_ascii_alphabetic() && *c != '~')
+                            .is_some()
+                        {}
+                        chars.next();
+                        continue;
+                    }
+                }
+            }
+            '\x1b' => TermKey::Escape,
+            '\r' | '\n' => TermKey::Enter,
+            '\t' => TermKey::Tab,
+            '\x7f' | '\x08' => TermKey::Backspace,
+            '\x01'..='\x1a' => TermKey::Ctrl((b'a' + c as u8 - 1) as char),
+            c => TermKey::Char(c),
+        };
+        keys.push(key);
+    }
+    keys
+}
+
+/// The key and modifiers a terminal key corresponds to, so it can be looked up
+/// in the same binding table as the graphical frontend
+///
+/// # Returns
+/// `(key, ctrl, shift)`, or `None` for keys with no equivalent
+pub fn key_code(key: TermKey) -> Option<(KeyCode, bool, bool)> {
+    let named = |c: char| {
+        crate::bindings::KEY_NAMES
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(&c.to_string()))
+            .map(|(code, _)| *code)
+    };
+    match key {
+        TermKey::Char('?') => Some((KeyCode::Slash, false, true)),
+        TermKey::Char(' ') => Some((KeyCode::Space, false, false)),
+        TermKey::Char(c) => Some((named(c)?, false, c.is_ascii_uppercase())),
+        TermKey::Ctrl(c) => Some((named(c)?, true, false)),
+        TermKey::Up => Some((KeyCode::Up, false, false)),
+        TermKey::Down => Some((KeyCode::Down, false, false)),
+        TermKey::Left => Some((KeyCode::Left, false, false)),
+        TermKey::Right => Some((KeyCode::Right, false, false)),
+        TermKey::Enter => Some((KeyCode::Enter, false, false)),
+        TermKey::Tab => Some((KeyCode::Tab, false, false)),
+        TermKey::Escape => Some((KeyCode::Escape, false, false)),
+        TermKey::Backspace => Some((KeyCode::Backspace, false, false)),
+        TermKey::FocusIn | TermKey::FocusOut => None,
+    }
+}
+
+/// Draw the part of the grid visible in a terminal of `columns` x `rows` characters
+///
+/// Every character shows two cells stacked vertically with half block
+/// characters, so cells come out roughly square. Parts of the view outside the
+/// grid are drawn as `·` to show where the board ends.
+///
+/// # Arguments
+/// * `grid` - The board to draw
+/// * `origin` - The cell shown in the top-left corner, may lie outside the grid
+/// * `columns`, `rows` - Size of the area to draw into, in characters
+pub fn render(grid: &Grid, origin: (isize, isize), columns: usize, rows: usize) -> Vec<String> {
+    let cell = |x: isize, y: isize| -> Option<bool> {
+        let inside =
+            x >= 0 && y >= 0 && (x as usize) < grid.width() && (y as usize) < grid.height();
+        inside.then(|| grid.get(x as usize, y as usize) == CellState::Alive)
+    };
+
+    (0..rows as isize)
+        .map(|row| {
+            let y = origin.1 + row * 2;
+            (0..columns as isize)
+                .map(|column| {
+                    let x = origin.0 + column;
+                    match (cell(x, y), cell(x, y + 1)) {
+                        (None, None) => '·',
+                        (top, bottom) => match (top == Some(true), bottom == Some(true)) {
+                            (true, true) => '█',
+                            (true, false) => '▀',
+                            (false, true) => '▄',
+                            (false, false) => ' ',
+                        },
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Terminal settings from before raw mode, restored on exit or panic
+static SAVED: Mutex<Option<libc::termios>> = Mutex::new(None);
+
+/// Puts the terminal in raw, non-blocking mode on the alternate screen for as
+/// long as it lives, restoring it when dropped or when the program panics
+pub struct RawTerminal;
+
+impl RawTerminal {
+    pub fn enter() -> io::Result<Self> {
+        // SAFETY: termios is plain data, filled in by tcgetattr before use
+        let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        *SAVED.lock().unwrap_or_else(|e| e.into_inner()) = Some(termios);
+
+        let mut raw = termios;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        // Return from reads right away, with whatever input is available
+        raw.c_cc[libc::VMIN] = 0;
+        raw.c_cc[libc::VTIME] = 0;
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            restore();
+            previous(info);
+        }));
+
+        // Alternate screen, hidden cursor, focus changes reported as input
+        print!("\x1b[?1049h\x1b[?25l\x1b[?1004h");
+        io::stdout().flush()?;
+        Ok(Self)
+    }
+
+    /// Size of the terminal in `(columns, rows)`
+    pub fn size(&self) -> Option<(usize, usize)> {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        let ok = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+        (ok && size.ws_col > 0 && size.ws_row > 0)
+            .then_some((size.ws_col as usize, size.ws_row as usize))
+    }
+
+    /// Every key pressed since the previous call, without waiting
+    pub fn read_keys(&self) -> io::Result<Vec<TermKey>> {
+        let mut buffer = [0; 64];
+        let read = io::stdin().read(&mut buffer)?;
+        Ok(parse_keys(&buffer[..read]))
+    }
+
+    /// Replace the screen contents with `lines`, followed by `status` in reverse video
+    pub fn draw(&self, lines: &[String], status: &str) -> io::Result<()> {
+        let mut out = io::stdout().lock();
+        write!(out, "\x1b[H")?;
+        for line in lines {
+            write!(out, "{line}\x1b[K\r\n")?;
+        }
+        write!(out, "\x1b[7m{status}\x1b[K\x1b[0m")?;
+        out.flush()
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Leave raw mode and the alternate screen, if still in them
+fn restore() {
+    if let Some(termios) = SAVED.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) };
+        print!("\x1b[?1004l\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_packs_two_rows_per_line() {
+        let mut grid = Grid::new(3, 4);
+        grid.set(0, 0, CellState::Alive);
+        grid.set(1, 1, CellState::Alive);
+        grid.set(2, 0, CellState::Alive);
+        grid.set(2, 1, CellState::Alive);
+
+        assert_eq!(render(&grid, (0, 0), 3, 2), vec!["▀▄█", "   "]);
+    }
+
+    #[test]
+    fn test_render_viewport_outside_grid() {
+        let mut grid = Grid::new(2, 2);
+        grid.set(1, 1, CellState::Alive);
+
+        // Shifted up and left by one cell: the first column, the last column
+        // and the top half of the first line fall outside the board
+        assert_eq!(render(&grid, (-1, -1), 4, 2), vec!["·  ·", "· ▀·"]);
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(
+            parse_keys(b" q\x1b[A\x1b[D\x13\x1bX\r"),
+            vec![
+                TermKey::Char(' '),
+                TermKey::Char('q'),
+                TermKey::Up,
+                TermKey::Left,
+                TermKey::Ctrl('s'),
+                TermKey::Escape,
+                TermKey::Char('X'),
+                TermKey::Enter,
+            ]
+        );
+        // Unknown sequences such as F5 are skipped whole
+        assert_eq!(parse_keys(b"\x1b[15~a"), vec![TermKey::Char('a')]);
+        assert_eq!(
+            parse_keys(b"\x1b[O\x1b[I"),
+            vec![TermKey::FocusOut, TermKey::FocusIn]
+        );
+    }
+
+    #[test]
+    fn test_key_codes() {
+        assert_eq!(
+            key_code(TermKey::Char('-')),
+            Some((KeyCode::Minus, false, false))
+        );
+        assert_eq!(
+            key_code(TermKey::Char('N')),
+            Some((KeyCode::N, false, true))
+        );
+        assert_eq!(
+            key_code(TermKey::Ctrl('s')),
+            Some((KeyCode::S, true, false))
+        );
+        assert_eq!(
+            key_code(TermKey::Char('?')),
+            Some((KeyCode::Slash, false, true))
+        );
+        assert_eq!(key_code(TermKey::Char('é')), None);
+    }
+}
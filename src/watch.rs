@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Seconds between two looks at the watched file
+pub const POLL_INTERVAL: f32 = 1.0;
+
+/// Notices when a file is changed by another program, by polling its
+/// modification time every `POLL_INTERVAL` seconds
+///
+/// A file that goes missing, e.g. while an editor replaces it, isn't a change;
+/// it counts as one once it is back with a different time.
+pub struct FileWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    since: f32,
+}
+
+impl FileWatcher {
+    /// Watch `path`, taking its current state as unchanged
+    pub fn new(path: PathBuf) -> Self {
+        let modified = modified(&path);
+        Self {
+            path,
+            modified,
+            since: 0.0,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file changed since the last change was reported
+    /// Only looks at the file once `POLL_INTERVAL` seconds have passed, counting
+    /// the `dt` of every call.
+    pub fn poll(&mut self, dt: f32) -> bool {
+        self.since += dt;
+        if self.since < POLL_INTERVAL {
+            return false;
+        }
+        self.since = 0.0;
+        match modified(&self.path) {
+            Some(time) if self.modified != Some(time) => {
+                self.modified = Some(time);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::time::Duration;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("conways-watch-{}-{name}", std::process::id()));
+        std::fs::write(&path, "x = 1, y = 1\no!\n").unwrap();
+        path
+    }
+
+    /// Move the modification time forward, as saving from an editor would
+    fn touch(path: &Path, seconds: u64) {
+        let time = SystemTime::now() + Duration::from_secs(seconds);
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_reports_each_change_once() {
+        let path = temp_file("once");
+        let mut watcher = FileWatcher::new(path.clone());
+        assert!(!watcher.poll(POLL_INTERVAL));
+
+        touch(&path, 10);
+        // Not looked at before the interval is over
+        assert!(!watcher.poll(POLL_INTERVAL / 2.0));
+        assert!(watcher.poll(POLL_INTERVAL / 2.0));
+        assert!(!watcher.poll(POLL_INTERVAL));
+
+        touch(&path, 20);
+        assert!(watcher.poll(POLL_INTERVAL));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_missing_file_is_not_a_change() {
+        let path = temp_file("missing");
+        let mut watcher = FileWatcher::new(path.clone());
+        std::fs::remove_file(&path).unwrap();
+        assert!(!watcher.poll(POLL_INTERVAL));
+
+        // Written again, as editors saving through a new file do
+        std::fs::write(&path, "x = 1, y = 1\no!\n").unwrap();
+        touch(&path, 10);
+        assert!(watcher.poll(POLL_INTERVAL));
+        assert_eq!(watcher.path(), path);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,683 @@
+//! The running board served to read-only viewers over WebSocket, e.g. a web
+//! dashboard watching a long simulation, with `--websocket <PORT>`
+//!
+//! A viewer connecting to `ws://<host>:<PORT>/` is sent, as binary messages
+//! whose first byte says what the rest is:
+//! - 0: the whole board as a `.lifesnap` snapshot, first thing and whenever
+//!   the board changes size or most of it at once
+//! - 1: what changed, see `GridDelta`, every frame the board changed
+//!
+//! and once a second a text message of statistics,
+//! `{"generation":120,"population":48,"width":80,"height":60,"rule":"B3/S23"}`.
+//! Viewers are answered when they ping or close the connection, and anything
+//! else they send is ignored.
+//!
+//! Accepting viewers and writing to them happens on threads of their own,
+//! talking to the game through channels, so a slow viewer never holds up a
+//! frame. A viewer with `QUEUE_LEN` messages waiting is skipped until it
+//! makes room for a fresh snapshot, and one that takes longer than
+//! `WRITE_TIMEOUT` to take a message is dropped.
+
+use crate::conways::delta::GridDelta;
+use crate::conways::Grid;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Seconds between two statistics messages
+pub const STATS_INTERVAL: f32 = 1.0;
+
+/// Longest opening request read from a viewer
+const MAX_REQUEST_LEN: usize = 8192;
+
+/// How long a viewer has to send its opening request
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Messages waiting for a viewer before it's skipped until it catches up
+const QUEUE_LEN: usize = 64;
+
+/// How long a viewer has to take a message before it's dropped
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Appended to a viewer's key to prove the server speaks WebSocket, RFC 6455
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const SNAPSHOT: u8 = 0;
+const DELTA: u8 = 1;
+
+/// The SHA-1 digest of `bytes`, which the handshake needs and nothing else
+fn sha1(bytes: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [
+        0x6745_2301,
+        0xefcd_ab89,
+        0x98ba_dcfe,
+        0x1032_5476,
+        0xc3d2_e1f0,
+    ];
+    let mut message = bytes.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(bytes.len() as u64 * 8).to_be_bytes());
+    for block in message.chunks_exact(64) {
+        let mut words = [0u32; 80];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            words[i] = u32::from_be_bytes(word.try_into().expect("4 bytes"));
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, word) in words.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let next = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, next);
+        }
+        for (value, add) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+    let mut digest = [0; 20];
+    for (bytes, value) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+/// `bytes` in padded standard base64
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let word = chunk.iter().enumerate().fold(0u32, |word, (i, byte)| {
+            word | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(ALPHABET[(word >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+/// What the server answers a viewer's key with
+fn accept_key(key: &str) -> String {
+    base64(&sha1(format!("{key}{HANDSHAKE_GUID}").as_bytes()))
+}
+
+/// A WebSocket frame from the server, which is never masked: `opcode` 1
+/// for text, 2 for binary, 8 to close and 10 for a pong
+fn ws_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        len @ 0..=125 => frame.push(len as u8),
+        len @ 126..=0xffff => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+fn binary(kind: u8, payload: &[u8]) -> Arc<[u8]> {
+    ws_frame(2, &[&[kind][..], payload].concat()).into()
+}
+
+fn snapshot(grid: &Grid) -> Arc<[u8]> {
+    let mut snapshot = Vec::new();
+    grid.write_snapshot(&mut snapshot)
+        .expect("writing to memory can't fail");
+    binary(SNAPSHOT, &snapshot)
+}
+
+/// The statistics message for `grid`
+fn stats(grid: &Grid) -> Arc<[u8]> {
+    let json = format!(
+        "{{\"generation\":{},\"population\":{},\"width\":{},\"height\":{},\"rule\":\"{}\"}}",
+        grid.generation(),
+        grid.population(),
+        grid.width(),
+        grid.height(),
+        grid.rule()
+    );
+    ws_frame(1, json.as_bytes()).into()
+}
+
+/// Read a viewer's opening request and agree to switch to WebSocket
+fn handshake(stream: &mut TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    let mut request = Vec::new();
+    let mut byte = [0];
+    while !request.ends_with(b"\r\n\r\n") {
+        if request.len() == MAX_REQUEST_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request too long",
+            ));
+        }
+        stream.read_exact(&mut byte)?;
+        request.push(byte[0]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let key = request.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case("Sec-WebSocket-Key")
+            .then(|| value.trim())
+    });
+    let Some(key) = key else {
+        stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n")?;
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a WebSocket request",
+        ));
+    };
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    )
+}
+
+/// Read the frames a viewer sends, answering pings with pongs, until it
+/// closes the connection, which is answered in kind
+fn answer(mut reader: TcpStream, writer: &Mutex<TcpStream>) -> io::Result<()> {
+    let reply = |frame: Vec<u8>| match writer.lock() {
+        Ok(mut writer) => writer.write_all(&frame),
+        Err(_) => Err(io::ErrorKind::BrokenPipe.into()),
+    };
+    loop {
+        let mut head = [0; 2];
+        reader.read_exact(&mut head)?;
+        let len = match head[1] & 0x7f {
+            126 => {
+                let mut len = [0; 2];
+                reader.read_exact(&mut len)?;
+                u16::from_be_bytes(len) as u64
+            }
+            127 => {
+                let mut len = [0; 8];
+                reader.read_exact(&mut len)?;
+                u64::from_be_bytes(len)
+            }
+            len => len as u64,
+        };
+        let mut mask = [0; 4];
+        if head[1] & 0x80 != 0 {
+            reader.read_exact(&mut mask)?;
+        }
+        let opcode = head[0] & 0x0f;
+        if opcode < 8 {
+            // Text and binary messages are skipped without being kept
+            let skipped = io::copy(&mut (&mut reader).take(len), &mut io::sink())?;
+            if skipped < len {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            continue;
+        }
+        // Control frames carry at most 125 bytes
+        if len > 125 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "control frame too long",
+            ));
+        }
+        let mut payload = vec![0; len as usize];
+        reader.read_exact(&mut payload)?;
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+        match opcode {
+            // Echoing the status code, if there is one
+            8 => return reply(ws_frame(8, &payload[..payload.len().min(2)])),
+            9 => reply(ws_frame(10, &payload))?,
+            _ => (),
+        }
+    }
+}
+
+/// A viewer past the handshake
+struct Viewer {
+    /// Messages for its writer thread to send
+    frames: SyncSender<Arc<[u8]>>,
+    /// Cleared by its reader thread once the connection is gone
+    connected: Arc<AtomicBool>,
+    /// Set while it's missing messages, from joining or from its queue
+    /// filling up, until a snapshot catches it up
+    behind: bool,
+}
+
+impl Viewer {
+    /// Queue `frame`, falling behind if there's no room for it
+    fn send(&mut self, frame: &Arc<[u8]>) {
+        // A viewer that's gone is dropped on the next sync
+        if !self.behind {
+            self.behind = self.frames.try_send(Arc::clone(frame)).is_err();
+        }
+    }
+
+    /// Queue `snapshot` for a viewer that's behind, once there's room for it
+    fn catch_up(&mut self, snapshot: &Arc<[u8]>) {
+        if self.behind {
+            self.behind = self.frames.try_send(Arc::clone(snapshot)).is_err();
+        }
+    }
+}
+
+/// Shake hands with a viewer, then write it every message queued for it
+/// while answering what it sends on a thread of its own
+fn serve(mut stream: TcpStream, joiner: Sender<Viewer>) {
+    let reader = handshake(&mut stream)
+        .and_then(|()| stream.set_read_timeout(None))
+        .and_then(|()| stream.set_write_timeout(Some(WRITE_TIMEOUT)))
+        .and_then(|()| stream.try_clone());
+    let Ok(reader) = reader else {
+        let _ = stream.shutdown(Shutdown::Both);
+        return;
+    };
+    let (frames, queued) = mpsc::sync_channel::<Arc<[u8]>>(QUEUE_LEN);
+    let connected = Arc::new(AtomicBool::new(true));
+    let viewer = Viewer {
+        frames,
+        connected: Arc::clone(&connected),
+        behind: true,
+    };
+    if joiner.send(viewer).is_err() {
+        return;
+    }
+    let writer = Arc::new(Mutex::new(stream));
+    let answering = Arc::clone(&writer);
+    thread::spawn(move || {
+        let _ = answer(reader, &answering);
+        connected.store(false, Ordering::Relaxed);
+        if let Ok(stream) = answering.lock() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    });
+    for frame in queued {
+        let written = match writer.lock() {
+            Ok(mut stream) => stream.write_all(&frame),
+            Err(_) => break,
+        };
+        if written.is_err() {
+            break;
+        }
+    }
+    // Also wakes the reader, for the broadcaster dropping the viewer
+    if let Ok(stream) = writer.lock() {
+        let _ = stream.shutdown(Shutdown::Both);
+    };
+}
+
+/// Sends the board to WebSocket viewers, see the [module docs](self)
+pub struct Broadcaster {
+    address: SocketAddr,
+    joined: Receiver<Viewer>,
+    viewers: Vec<Viewer>,
+    /// The board every viewer has with its `content_hash`, `None` before
+    /// the first sync
+    sent: Option<(Grid, u64)>,
+    /// Seconds since the last statistics message
+    since_stats: f32,
+}
+
+impl Broadcaster {
+    /// Listen for viewers on `address`, on a thread of its own
+    pub fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let (joiner, joined) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let joiner = joiner.clone();
+                thread::spawn(move || serve(stream, joiner));
+            }
+        });
+        Ok(Self {
+            address,
+            joined,
+            viewers: Vec::new(),
+            sent: None,
+            since_stats: 0.0,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Viewers connected at the last sync
+    pub fn viewers(&self) -> usize {
+        self.viewers.len()
+    }
+
+    /// Send the viewers how `grid` changed since the last sync, a snapshot
+    /// to viewers that connected or fell behind since, and the statistics
+    /// once `STATS_INTERVAL` seconds have passed, counting the `dt` of every
+    /// call
+    pub fn sync(&mut self, grid: &Grid, dt: f32) {
+        self.viewers
+            .retain(|viewer| viewer.connected.load(Ordering::Relaxed));
+        let mut frames = Vec::new();
+        // As for `collab::Host`, an edit whose hash collides goes out with the next change
+        let hash = grid.content_hash();
+        match &self.sent {
+            Some((sent, was)) if sent.generation() == grid.generation() && *was == hash => (),
+            Some((sent, _)) => {
+                // Whichever is smaller, most of the board changing at once
+                let delta = GridDelta::between(sent, grid).map(|delta| delta.to_bytes());
+                frames.push(match delta {
+                    Some(delta) if delta.len() < grid.width() * grid.height() / 4 => {
+                        binary(DELTA, &delta)
+                    }
+                    _ => snapshot(grid),
+                });
+            }
+            None => (),
+        }
+        if !frames.is_empty() || self.sent.is_none() {
+            self.sent = Some((grid.clone(), hash));
+        }
+        self.since_stats += dt;
+        if self.since_stats >= STATS_INTERVAL {
+            self.since_stats = 0.0;
+            frames.push(stats(grid));
+        }
+        for frame in &frames {
+            for viewer in &mut self.viewers {
+                viewer.send(frame);
+            }
+        }
+
+        self.viewers.extend(self.joined.try_iter());
+        if self.viewers.iter().any(|viewer| viewer.behind) {
+            let snapshot = snapshot(grid);
+            for viewer in &mut self.viewers {
+                viewer.catch_up(&snapshot);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns;
+    use std::time::Instant;
+
+    /// Call `done` until it's true, failing after a few seconds
+    fn wait_until(mut done: impl FnMut() -> bool) {
+        let start = Instant::now();
+        while !done() {
+            assert!(start.elapsed() < Duration::from_secs(5), "timed out");
+            thread::sleep(Duration::from_millis(2));
+        }
+    }
+
+    /// A WebSocket client in a few lines: connects and checks the handshake
+    struct TestViewer(TcpStream);
+
+    impl TestViewer {
+        fn connect(address: SocketAddr) -> Self {
+            let mut stream = TcpStream::connect(address).unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            write!(
+                stream,
+                "GET / HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+                 Connection: Upgrade\r\nsec-websocket-key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                 Sec-WebSocket-Version: 13\r\n\r\n"
+            )
+            .unwrap();
+            let mut response = Vec::new();
+            let mut byte = [0];
+            while !response.ends_with(b"\r\n\r\n") {
+                stream.read_exact(&mut byte).unwrap();
+                response.push(byte[0]);
+            }
+            let response = String::from_utf8(response).unwrap();
+            assert!(response.starts_with("HTTP/1.1 101 "), "{response}");
+            assert!(response.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+            Self(stream)
+        }
+
+        /// Send a frame masked as clients have to
+        fn send(&mut self, opcode: u8, payload: &[u8]) {
+            let mask = [0x37, 0xfa, 0x21, 0x3d];
+            let mut frame = vec![0x80 | opcode, 0x80 | payload.len() as u8];
+            frame.extend_from_slice(&mask);
+            frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+            self.0.write_all(&frame).unwrap();
+        }
+
+        /// The opcode and payload of the next message
+        fn read(&mut self) -> (u8, Vec<u8>) {
+            let mut head = [0; 2];
+            self.0.read_exact(&mut head).unwrap();
+            assert_eq!(head[0] & 0x80, 0x80, "fragmented");
+            assert_eq!(head[1] & 0x80, 0, "masked");
+            let len = match head[1] {
+                126 => {
+                    let mut len = [0; 2];
+                    self.0.read_exact(&mut len).unwrap();
+                    u16::from_be_bytes(len) as usize
+                }
+                127 => {
+                    let mut len = [0; 8];
+                    self.0.read_exact(&mut len).unwrap();
+                    u64::from_be_bytes(len) as usize
+                }
+                len => len as usize,
+            };
+            let mut payload = vec![0; len];
+            self.0.read_exact(&mut payload).unwrap();
+            (head[0] & 0x0f, payload)
+        }
+    }
+
+    #[test]
+    fn test_handshake_digests() {
+        let hex = |digest: [u8; 20]| {
+            digest
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect::<String>()
+        };
+        assert_eq!(
+            hex(sha1(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(hex(sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        let long = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+        assert_eq!(hex(sha1(long)), "84983e441c3bd26ebaae4aa1f95129e5e54670f1");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        // The example of RFC 6455
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn test_frame_lengths() {
+        for len in [0, 125, 126, 65535, 65536] {
+            let frame = ws_frame(2, &vec![7; len]);
+            let header = match len {
+                0..=125 => 2,
+                126..=65535 => 4,
+                _ => 10,
+            };
+            assert_eq!((frame[0], frame.len()), (0x82, header + len));
+        }
+    }
+
+    #[test]
+    fn test_a_viewer_rebuilds_the_board() {
+        let mut broadcaster = Broadcaster::bind("127.0.0.1:0").unwrap();
+        // Few enough changes a generation that they go as deltas
+        let gun = patterns::find("gosper-glider-gun").unwrap().to_grid();
+        let mut grid = Grid::centered(&gun, 48, 32);
+        broadcaster.sync(&grid, 0.0);
+        let mut viewer = TestViewer::connect(broadcaster.local_addr());
+        wait_until(|| {
+            broadcaster.sync(&grid, 0.0);
+            broadcaster.viewers() == 1
+        });
+
+        let (opcode, message) = viewer.read();
+        assert_eq!((opcode, message[0]), (2, SNAPSHOT));
+        let mut copy = Grid::read_snapshot(&message[1..]).unwrap();
+        assert_eq!(copy, grid);
+        for _ in 0..3 {
+            grid.next_cell_generation();
+            broadcaster.sync(&grid, 0.0);
+            let (opcode, message) = viewer.read();
+            assert_eq!((opcode, message[0]), (2, DELTA));
+            GridDelta::from_bytes(&message[1..])
+                .unwrap()
+                .apply(&mut copy)
+                .unwrap();
+        }
+        assert_eq!((copy.generation(), &copy), (3, &grid));
+
+        broadcaster.sync(&grid, STATS_INTERVAL);
+        let (opcode, message) = viewer.read();
+        assert_eq!(opcode, 1);
+        assert_eq!(
+            String::from_utf8(message).unwrap(),
+            format!(
+                "{{\"generation\":3,\"population\":{},\"width\":48,\"height\":32,\"rule\":\"B3/S23\"}}",
+                grid.population()
+            )
+        );
+    }
+
+    #[test]
+    fn test_pings_and_closes_are_answered() {
+        let mut broadcaster = Broadcaster::bind("127.0.0.1:0").unwrap();
+        let grid = Grid::new(8, 8);
+        let mut viewer = TestViewer::connect(broadcaster.local_addr());
+        wait_until(|| {
+            broadcaster.sync(&grid, 0.0);
+            broadcaster.viewers() == 1
+        });
+        assert_eq!(viewer.read().0, 2);
+
+        // Messages are skipped over, however long
+        viewer.send(1, b"hello");
+        viewer.send(9, b"still there?");
+        assert_eq!(viewer.read(), (10, b"still there?".to_vec()));
+        // 1000, a normal closure, and a reason, echoed without the reason
+        viewer.send(8, b"\x03\xe8bye");
+        assert_eq!(viewer.read(), (8, b"\x03\xe8".to_vec()));
+        let mut rest = Vec::new();
+        viewer.0.read_to_end(&mut rest).unwrap();
+        assert!(rest.is_empty());
+        wait_until(|| {
+            broadcaster.sync(&grid, 0.0);
+            broadcaster.viewers() == 0
+        });
+    }
+
+    #[test]
+    fn test_a_viewer_falling_behind_gets_a_snapshot() {
+        let mut broadcaster = Broadcaster::bind("127.0.0.1:0").unwrap();
+        let (frames, queued) = mpsc::sync_channel(QUEUE_LEN);
+        broadcaster.viewers.push(Viewer {
+            frames,
+            connected: Arc::new(AtomicBool::new(true)),
+            behind: true,
+        });
+        let mut grid = Grid::new(16, 16);
+        grid.randomize(0.3, 2);
+        broadcaster.sync(&grid, 0.0);
+        // The snapshot and then a delta for every toggle, with nothing taken
+        // off the queue, so the last ones don't fit
+        for _ in 0..QUEUE_LEN + 10 {
+            grid.toggle(3, 4);
+            broadcaster.sync(&grid, 0.0);
+        }
+        assert!(broadcaster.viewers[0].behind);
+        let mut copy = Grid::new(0, 0);
+        let mut rebuild = |frame: Arc<[u8]>| {
+            let header = match frame[1] {
+                126 => 4,
+                127 => 10,
+                _ => 2,
+            };
+            let (kind, payload) = (frame[header], &frame[header + 1..]);
+            match kind {
+                SNAPSHOT => copy = Grid::read_snapshot(payload).unwrap(),
+                _ => {
+                    let delta = GridDelta::from_bytes(payload).unwrap();
+                    delta.apply(&mut copy).unwrap();
+                }
+            }
+            kind
+        };
+        let kinds: Vec<u8> = queued.try_iter().map(&mut rebuild).collect();
+        assert_eq!(kinds.len(), QUEUE_LEN);
+        assert_eq!(kinds[0], SNAPSHOT);
+
+        // Once there's room again it's caught up with the board as it is
+        broadcaster.sync(&grid, 0.0);
+        assert!(!broadcaster.viewers[0].behind);
+        let kinds: Vec<u8> = queued.try_iter().map(&mut rebuild).collect();
+        assert_eq!(kinds, [SNAPSHOT]);
+        assert_eq!(copy, grid);
+    }
+
+    #[test]
+    fn test_viewers_leaving_dont_stop_the_broadcast() {
+        let mut broadcaster = Broadcaster::bind("127.0.0.1:0").unwrap();
+        let mut grid = Grid::new(20, 20);
+        grid.randomize(0.3, 1);
+        let viewer = TestViewer::connect(broadcaster.local_addr());
+        wait_until(|| {
+            broadcaster.sync(&grid, 0.0);
+            broadcaster.viewers() == 1
+        });
+        drop(viewer);
+        // Not WebSocket at all, turned away
+        let mut browser = TcpStream::connect(broadcaster.local_addr()).unwrap();
+        browser
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        browser.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 400 "));
+
+        wait_until(|| {
+            grid.next_cell_generation();
+            broadcaster.sync(&grid, 0.1);
+            broadcaster.viewers() == 0
+        });
+    }
+}
@@ -0,0 +1,144 @@
+use crate::camera::{Bounds, Camera};
+use std::ops::Range;
+
+/// The cell of a `grid`-sized wrapping board that `cell` lands on, for cells
+/// of the copies around it, however far out and on whichever side
+pub fn canonical_cell((x, y): (isize, isize), grid: (usize, usize)) -> (usize, usize) {
+    (
+        x.rem_euclid(grid.0.max(1) as isize) as usize,
+        y.rem_euclid(grid.1.max(1) as isize) as usize,
+    )
+}
+
+/// `camera` moved by whole boards so its top-left corner lies on the board
+/// itself, which shows the same view of a wrapping board and keeps panning
+/// forever from losing precision
+pub fn canonical_camera(camera: Camera, grid: (usize, usize)) -> Camera {
+    let wrap = |origin: f32, len: usize| origin.rem_euclid(len.max(1) as f32);
+    Camera {
+        origin: (wrap(camera.origin.0, grid.0), wrap(camera.origin.1, grid.1)),
+        ..camera
+    }
+}
+
+/// Indices of the copies that `len` cells from `start` overlap, along an axis
+/// of boards `size` cells long; copy `i` covers cells `i * size..(i + 1) * size`
+fn copies(start: f32, len: f32, size: usize) -> Range<isize> {
+    let size = size.max(1) as f32;
+    let first = (start / size).floor() as isize;
+    let last = ((start + len) / size).ceil() as isize;
+    first..last.max(first + 1)
+}
+
+/// The copies of a `grid`-sized wrapping board to draw for `view`, as the
+/// number of boards each lies right of and below the board itself
+///
+/// Every copy at least partly in view is listed; that is up to four while the
+/// board is bigger than the view, and more once the view zooms out past it.
+pub fn tiles(view: Bounds, grid: (usize, usize)) -> Vec<(isize, isize)> {
+    let xs = copies(view.x, view.w, grid.0);
+    copies(view.y, view.h, grid.1)
+        .flat_map(|j| xs.clone().map(move |i| (i, j)))
+        .collect()
+}
+
+/// The camera that draws copy `(i, j)` of the board where `camera` sees it,
+/// so the board can be drawn once through each
+pub fn tile_camera(camera: &Camera, (i, j): (isize, isize), grid: (usize, usize)) -> Camera {
+    Camera {
+        origin: (
+            camera.origin.0 - (i * grid.0 as isize) as f32,
+            camera.origin.1 - (j * grid.1 as isize) as f32,
+        ),
+        ..*camera
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_cell_wraps_both_ways() {
+        let grid = (10, 8);
+        assert_eq!(canonical_cell((3, 4), grid), (3, 4));
+        assert_eq!(canonical_cell((10, 8), grid), (0, 0));
+        assert_eq!(canonical_cell((23, 17), grid), (3, 1));
+        // Negative cells count back from the far edge, not towards zero
+        assert_eq!(canonical_cell((-1, -1), grid), (9, 7));
+        assert_eq!(canonical_cell((-10, -8), grid), (0, 0));
+        assert_eq!(canonical_cell((-11, -9), grid), (9, 7));
+        assert_eq!(canonical_cell((-25, -100), grid), (5, 4));
+    }
+
+    #[test]
+    fn test_canonical_camera_keeps_the_view() {
+        let grid = (10, 8);
+        let camera = Camera {
+            origin: (-2.5, 17.25),
+            cell_size: 12.0,
+        };
+        let canonical = canonical_camera(camera, grid);
+        assert_eq!(canonical.origin, (7.5, 1.25));
+        assert_eq!(canonical.cell_size, 12.0);
+        // The same screen point lands on the same cell of the board
+        for point in [(0.0, 0.0), (37.0, 91.0), (400.0, 300.0)] {
+            let (a, b) = (
+                camera.screen_to_cell(point),
+                canonical.screen_to_cell(point),
+            );
+            let cell = |(x, y): (f32, f32)| (x.floor() as isize, y.floor() as isize);
+            assert_eq!(canonical_cell(cell(a), grid), canonical_cell(cell(b), grid));
+        }
+        // Whole boards away, on either side, land on the same camera
+        let far = Camera {
+            origin: (7.5 - 30.0, 1.25 + 80.0),
+            cell_size: 12.0,
+        };
+        assert_eq!(canonical_camera(far, grid).origin, (7.5, 1.25));
+        assert_eq!(canonical_camera(canonical, grid), canonical);
+    }
+
+    #[test]
+    fn test_tiles_cover_the_view() {
+        let grid = (10, 10);
+        let view = |x: f32, y: f32, w: f32, h: f32| Bounds { x, y, w, h };
+        // Inside the board, just the board
+        assert_eq!(tiles(view(1.0, 1.0, 5.0, 5.0), grid), vec![(0, 0)]);
+        // Across the bottom-right corner, the copies right, below and diagonal
+        assert_eq!(
+            tiles(view(7.0, 8.0, 5.0, 5.0), grid),
+            vec![(0, 0), (1, 0), (0, 1), (1, 1)]
+        );
+        // Across the top-left corner from negative offsets
+        assert_eq!(
+            tiles(view(-3.0, -0.5, 5.0, 5.0), grid),
+            vec![(-1, -1), (0, -1), (-1, 0), (0, 0)]
+        );
+        // Ending exactly on an edge doesn't reach into the next copy
+        assert_eq!(tiles(view(0.0, 0.0, 10.0, 10.0), grid), vec![(0, 0)]);
+        // Zoomed out past the board, every copy in view
+        assert_eq!(tiles(view(-5.0, 0.0, 30.0, 5.0), grid).len(), 4);
+    }
+
+    #[test]
+    fn test_tile_camera_draws_the_copy_in_place() {
+        let grid = (10, 8);
+        let camera = Camera {
+            origin: (7.0, -3.0),
+            cell_size: 4.0,
+        };
+        // Cell 0 of the copy right of the board sits where world cell 10 would
+        let right = tile_camera(&camera, (1, 0), grid);
+        assert_eq!(
+            right.cell_to_screen((0.0, 2.0)),
+            camera.cell_to_screen((10.0, 2.0))
+        );
+        // And the last row of the copy above sits at world row -1
+        let above = tile_camera(&camera, (0, -1), grid);
+        assert_eq!(
+            above.cell_to_screen((3.0, 7.0)),
+            camera.cell_to_screen((3.0, -1.0))
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Famous patterns run a set number of generations and compared against the
+//! boards they're known to reach, stored as plaintext next to their RLE in
+//! `tests/fixtures/golden`
+//!
+//! Every board is big enough that nothing reaches its dead edges, so the
+//! expected boards are those of the unbounded plane and hold for any way of
+//! stepping it: whatever replaces `next_cell_generation` has to keep these
+//! passing, and so does every `GridBackend`, which each case is run in.
+//!
+//! A mismatch prints both boards side by side with the rows that differ
+//! marked. After a change that's meant to alter a result,
+//! `GOLDEN_UPDATE=1 cargo test --test golden` rewrites the expected boards.
+
+use game_of_life::backend::{self, bits::BitGrid, sparse::SparseGrid, GridBackend};
+use game_of_life::conways::{CellState, Grid};
+use game_of_life::formats::{plaintext, rle};
+use std::fmt::Write;
+
+/// One pattern, how far it runs and on how big a board
+struct Case {
+    name: &'static str,
+    rle: &'static str,
+    expected: &'static str,
+    generations: u64,
+    width: usize,
+    height: usize,
+}
+
+/// A case whose fixtures are `tests/fixtures/golden/<name>.rle` and `<name>.cells`
+macro_rules! case {
+    ($name:literal, $generations:expr, $width:expr, $height:expr) => {
+        Case {
+            name: $name,
+            rle: include_str!(concat!("fixtures/golden/", $name, ".rle")),
+            expected: include_str!(concat!("fixtures/golden/", $name, ".cells")),
+            generations: $generations,
+            width: $width,
+            height: $height,
+        }
+    };
+}
+
+/// The pattern of `case`, centered on its board and run in backend `B`
+///
+/// # Panics
+/// When a live cell reaches the outermost row or column in any generation,
+/// as then the board's edges may have changed what happened
+fn run<B: GridBackend>(case: &Case) -> B {
+    let pattern = rle::decode(case.rle).unwrap_or_else(|err| panic!("{}: {err}", case.name));
+    let mut grid: B = backend::convert(&Grid::centered(&pattern, case.width, case.height));
+    for _ in 0..case.generations {
+        grid.step();
+        assert!(
+            !touches_edge(&grid),
+            "{} reached the edge of its {}x{} board at generation {}",
+            case.name,
+            case.width,
+            case.height,
+            grid.generation()
+        );
+    }
+    grid
+}
+
+/// Whether a live cell is on the outermost row or column
+fn touches_edge(grid: &impl GridBackend) -> bool {
+    let (width, height) = (grid.width(), grid.height());
+    (0..height).any(|y| {
+        (0..width).any(|x| {
+            (x == 0 || y == 0 || x == width - 1 || y == height - 1)
+                && grid.get(x, y) == CellState::Alive
+        })
+    })
+}
+
+/// The rows of a plaintext board, without its comments
+fn rows(cells: &str) -> Vec<&str> {
+    cells
+        .lines()
+        .filter(|line| !line.starts_with('!'))
+        .collect()
+}
+
+/// Both boards side by side, every row padded to `width` and the ones that
+/// differ marked with `>`
+fn diff(expected: &[&str], actual: &[&str], width: usize) -> String {
+    let height = expected.len().max(actual.len());
+    let column = width.max("expected".len());
+    let pad = |row: Option<&&str>| format!("{:.<width$}", row.copied().unwrap_or(""));
+    let mut out = format!("     {:<column$}  actual\n", "expected");
+    let mut differing = Vec::new();
+    for y in 0..height {
+        let (want, got) = (pad(expected.get(y)), pad(actual.get(y)));
+        let marker = if want == got { ' ' } else { '>' };
+        if want != got {
+            differing.push(y);
+        }
+        writeln!(out, "{marker}{y:>3} {want:<column$}  {got}").unwrap();
+    }
+    match differing.as_slice() {
+        [] => out.push_str("no row differs\n"),
+        [y] => writeln!(out, "row {y} differs").unwrap(),
+        [first, ..] => writeln!(out, "{} rows differ, the first {first}", differing.len()).unwrap(),
+    }
+    out
+}
+
+/// The expected board of `case`, with a header saying where it came from
+fn fixture(case: &Case, grid: &impl GridBackend) -> String {
+    let name = case
+        .rle
+        .lines()
+        .find_map(|line| line.strip_prefix("#N "))
+        .unwrap_or(case.name);
+    format!(
+        "!Name: {name} after {} generations\n!On a {}x{} board with dead edges, {} cells alive\n{}",
+        case.generations,
+        case.width,
+        case.height,
+        grid.population(),
+        plaintext::encode(grid)
+    )
+}
+
+const CASES: &[Case] = &[
+    case!("glider", 8, 10, 10),
+    case!("lwss", 8, 16, 10),
+    case!("r-pentomino", 64, 64, 48),
+    case!("pulsar", 2, 17, 17),
+    case!("gosper-gun", 120, 96, 72),
+    case!("acorn", 100, 96, 96),
+];
+
+#[test]
+fn test_famous_patterns_reach_their_golden_boards() {
+    let update = std::env::var_os("GOLDEN_UPDATE").is_some();
+    let mut failures = String::new();
+    for case in CASES {
+        if update {
+            let path = format!(
+                "{}/tests/fixtures/golden/{}.cells",
+                env!("CARGO_MANIFEST_DIR"),
+                case.name
+            );
+            std::fs::write(&path, fixture(case, &run::<Grid>(case))).unwrap();
+            continue;
+        }
+        let boards: [(&str, Box<dyn GridBackend>); 3] = [
+            ("dense", Box::new(run::<Grid>(case))),
+            ("bit-packed", Box::new(run::<BitGrid>(case))),
+            ("sparse", Box::new(run::<SparseGrid>(case))),
+        ];
+        for (backend, grid) in boards {
+            let actual = plaintext::encode(&*grid);
+            let (expected, actual) = (rows(case.expected), rows(&actual));
+            if expected != actual {
+                writeln!(
+                    failures,
+                    "{} after {} generations on the {backend} grid isn't its golden board:\n{}",
+                    case.name,
+                    case.generations,
+                    diff(&expected, &actual, grid.width())
+                )
+                .unwrap();
+            }
+        }
+    }
+    assert!(failures.is_empty(), "\n{failures}");
+}
+
+#[test]
+fn test_the_patterns_are_read_as_their_size() {
+    let sizes: Vec<_> = CASES
+        .iter()
+        .map(|case| {
+            let pattern: Grid = rle::decode(case.rle).unwrap();
+            (
+                case.name,
+                pattern.width(),
+                pattern.height(),
+                pattern.population(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        sizes,
+        [
+            ("glider", 3, 3, 5),
+            ("lwss", 5, 4, 9),
+            ("r-pentomino", 3, 3, 5),
+            ("pulsar", 13, 13, 48),
+            ("gosper-gun", 36, 9, 36),
+            ("acorn", 7, 3, 7),
+        ]
+    );
+}
+
+#[test]
+fn test_mismatches_show_both_boards() {
+    let expected = rows("!Name: blinker\n\n.O\n.O\n.O\n");
+    let actual = rows("\n\nOOO\n");
+    assert_eq!(
+        diff(&expected, &actual, 3),
+        "     expected  actual\n\
+         \x20  0 ...       ...\n\
+         >  1 .O.       ...\n\
+         >  2 .O.       OOO\n\
+         >  3 .O.       ...\n\
+         3 rows differ, the first 1\n"
+    );
+}
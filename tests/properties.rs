@@ -0,0 +1,177 @@
+//! Invariants of the simulation checked on many random boards
+//!
+//! Boards come from the library's own seeded generator, so a failure names
+//! the case's seed and can be replayed with `case(seed)`; proptest isn't
+//! available to this build, which also leaves out shrinking.
+
+use game_of_life::conways::{BoundaryCondition, CellState, Grid, InsertMode};
+use game_of_life::formats::{life106, plaintext, rle};
+use game_of_life::rng::SplitMix64;
+use game_of_life::rule::Rule;
+
+/// Random boards tried for every property
+const CASES: u64 = 256;
+
+/// Longest side of the random boards
+const MAX_SIDE: u64 = 16;
+
+/// The random board of case `seed`: its size, density, edges and rule all vary
+fn case(seed: u64) -> Grid {
+    let mut rng = SplitMix64::new(seed);
+    let width = 1 + (rng.next_u64() % MAX_SIDE) as usize;
+    let height = 1 + (rng.next_u64() % MAX_SIDE) as usize;
+    let mut grid = Grid::new(width, height);
+    grid.randomize(rng.next_f64(), rng.next_u64());
+    if rng.next_u64().is_multiple_of(2) {
+        grid.set_boundary(BoundaryCondition::Wrap);
+    }
+    let presets = Rule::presets();
+    grid.set_rule(presets[(rng.next_u64() % presets.len() as u64) as usize]);
+    grid
+}
+
+/// Check `property` on every case, naming the seed of the first one it fails
+fn check(property: impl Fn(&Grid) -> Result<(), String>) {
+    for seed in 0..CASES {
+        if let Err(message) = property(&case(seed)) {
+            panic!("case {seed} failed: {message}");
+        }
+    }
+}
+
+fn ensure(holds: bool, message: impl FnOnce() -> String) -> Result<(), String> {
+    if holds {
+        Ok(())
+    } else {
+        Err(message())
+    }
+}
+
+/// `grid` moved by `(dx, dy)`, cells leaving one edge coming back at the other
+fn shifted(grid: &Grid, dx: usize, dy: usize) -> Grid {
+    let (width, height) = (grid.width(), grid.height());
+    let mut moved = grid.clone();
+    for y in 0..height {
+        for x in 0..width {
+            moved.set((x + dx) % width, (y + dy) % height, grid.get(x, y));
+        }
+    }
+    moved
+}
+
+#[test]
+fn test_step_n_equals_single_steps() {
+    check(|grid| {
+        let mut twice = grid.clone();
+        twice.step_n(2);
+        let mut stepped = grid.clone();
+        stepped.next_cell_generation();
+        stepped.next_cell_generation();
+        ensure(twice == stepped && twice.generation() == 2, || {
+            format!("step_n(2) gave generation {}", twice.generation())
+        })
+    });
+}
+
+#[test]
+fn test_pure_and_in_place_steppers_agree() {
+    check(|grid| {
+        let pure = grid.next_generation();
+        let mut scratch = Grid::new(3, 1);
+        grid.next_generation_into(&mut scratch);
+        let mut in_place = grid.clone();
+        in_place.next_cell_generation();
+        ensure(pure == in_place && scratch == in_place, || {
+            "the steppers disagree".to_string()
+        })
+    });
+}
+
+#[test]
+fn test_rle_round_trips() {
+    check(|grid| {
+        let text = rle::encode(grid, &grid.rule().to_string(), &[]);
+        let decoded: Grid = rle::decode(&text).map_err(|err| format!("{err} in {text:?}"))?;
+        ensure(decoded == *grid, || format!("{text:?} decoded differently"))
+    });
+}
+
+#[test]
+fn test_plaintext_round_trips() {
+    check(|grid| {
+        let text = plaintext::encode(grid);
+        let decoded = plaintext::decode(&text).map_err(|err| format!("{err} in {text:?}"))?;
+        // The format leaves out dead cells at the end of rows, which can
+        // narrow the board but never drops a live cell
+        let mut padded = Grid::new(grid.width(), grid.height());
+        padded.insert_pattern(&decoded, 0, 0, InsertMode::Overwrite);
+        ensure(padded == *grid && decoded.width() <= grid.width(), || {
+            format!("{text:?} decoded differently")
+        })
+    });
+}
+
+#[test]
+fn test_mangled_patterns_never_panic() {
+    // A stand-in for the fuzz targets where they can't run: every case
+    // encoded, then cut short and with bytes swapped for ones the parsers
+    // treat specially
+    const SPECIAL: &[u8] = b"0123456789bo$!xy=,-#O. \n";
+    check(|grid| {
+        let mut rng = SplitMix64::new(grid.population() as u64);
+        let texts = [
+            rle::encode(grid, "B3/S23", &[]),
+            plaintext::encode(grid),
+            life106::encode(grid),
+        ];
+        for text in texts {
+            let mut bytes = text.into_bytes();
+            for _ in 0..8 {
+                let i = (rng.next_u64() % bytes.len() as u64) as usize;
+                bytes[i] = SPECIAL[(rng.next_u64() % SPECIAL.len() as u64) as usize];
+            }
+            let cut = (rng.next_u64() % (bytes.len() as u64 + 1)) as usize;
+            let text = String::from_utf8_lossy(&bytes[..cut]);
+            let _ = (rle::decode::<Grid>(&text), plaintext::decode::<Grid>(&text));
+            let _ = life106::decode::<Grid>(&text);
+        }
+        Ok(())
+    });
+}
+
+#[test]
+fn test_births_need_live_neighbors() {
+    check(|grid| {
+        // Cells with a live cell somewhere in their 3x3 neighborhood; none
+        // of the presets brings a cell to life with 0 neighbors
+        let mut reachable = 0;
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                let neighbors = grid.count_neighbors(x, y);
+                if neighbors > 0 || grid.get(x, y) == CellState::Alive {
+                    reachable += 1;
+                }
+            }
+        }
+        let population = grid.next_generation().population();
+        ensure(population <= reachable, || {
+            format!("{population} cells alive, only {reachable} could be")
+        })
+    });
+}
+
+#[test]
+fn test_wrapping_is_translation_invariant() {
+    check(|grid| {
+        let mut grid = grid.clone();
+        grid.set_boundary(BoundaryCondition::Wrap);
+        let mut rng = SplitMix64::new(grid.population() as u64);
+        let dx = (rng.next_u64() % grid.width() as u64) as usize;
+        let dy = (rng.next_u64() % grid.height() as u64) as usize;
+        let moved_then_stepped = shifted(&grid, dx, dy).next_generation();
+        let stepped_then_moved = shifted(&grid.next_generation(), dx, dy);
+        ensure(moved_then_stepped == stepped_then_moved, || {
+            format!("shifting by {dx},{dy} changed the outcome")
+        })
+    });
+}
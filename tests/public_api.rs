@@ -0,0 +1,103 @@
+//! Uses the library only through its public API, the way a crate depending on it would
+
+use game_of_life::conways::{BoundaryCondition, CellState, Grid, InsertMode};
+use game_of_life::cycle::{CycleDetector, Stabilization};
+use game_of_life::formats::rle;
+use game_of_life::patterns;
+use game_of_life::rule::Rule;
+
+#[test]
+fn test_glider_crosses_a_wrapping_board() {
+    let glider = patterns::find("glider").unwrap().to_grid();
+    let mut grid = Grid::new(8, 8);
+    grid.set_boundary(BoundaryCondition::Wrap);
+    grid.insert_pattern(&glider, 0, 0, InsertMode::Or);
+    let start = grid.clone();
+    // A glider moves one cell diagonally every 4 generations
+    for _ in 0..32 {
+        grid.next_cell_generation();
+    }
+    assert_eq!(grid.generation(), 32);
+    assert_eq!(grid.population(), 5);
+    for y in 0..8 {
+        for x in 0..8 {
+            assert_eq!(grid.get(x, y), start.get(x, y));
+        }
+    }
+}
+
+#[test]
+fn test_rules_change_what_is_born() {
+    let highlife: Rule = "B36/S23".parse().unwrap();
+    assert_eq!(highlife.to_string(), "B36/S23");
+    assert!(highlife.next_state(false, 6));
+    assert!(!Rule::default().next_state(false, 6));
+    assert!("B9/S23".parse::<Rule>().is_err());
+
+    // Under B2/S nothing survives and every cell with two live neighbors is born
+    let mut grid = Grid::new(6, 6);
+    grid.set_rule("B2/S".parse().unwrap());
+    grid.set(2, 2, CellState::Alive);
+    grid.set(3, 2, CellState::Alive);
+    grid.next_cell_generation();
+    assert_eq!(grid.get(2, 2), CellState::Dead);
+    assert_eq!(grid.population(), 4);
+}
+
+#[test]
+fn test_rle_round_trip() {
+    let acorn = patterns::find("acorn").unwrap().to_grid();
+    let text = rle::encode(&acorn, "B3/S23", &["acorn".to_string()]);
+    assert!(text.starts_with("#C acorn\nx = 7, y = 3"));
+    let decoded: Grid = rle::decode(&text).unwrap();
+    assert_eq!(decoded, acorn);
+
+    let err = rle::decode::<Grid>("x = 3, y = 1\n3o").unwrap_err();
+    assert!(err.line > 0);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_file_errors() {
+    use game_of_life::{formats, ConwayError};
+
+    let missing = std::env::temp_dir().join("conways-public-api-missing.rle");
+    assert!(matches!(
+        formats::load_file(&missing),
+        Err(ConwayError::Io(_))
+    ));
+    assert!(matches!(
+        formats::load_file(std::path::Path::new("board.png")),
+        Err(ConwayError::InvalidArgument(_))
+    ));
+}
+
+#[test]
+fn test_settling_is_detected() {
+    let mut grid = Grid::new(10, 10);
+    for x in 3..6 {
+        grid.set(x, 4, CellState::Alive);
+    }
+    let mut detector = CycleDetector::new(30);
+    let mut found = None;
+    for _ in 0..5 {
+        found = found.or(detector.observe(&grid));
+        grid.next_cell_generation();
+    }
+    assert_eq!(
+        found,
+        Some(Stabilization {
+            generation: 0,
+            period: 2
+        })
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_objects_are_named() {
+    assert_eq!(
+        patterns::identify(&[(4, 1), (4, 2), (4, 3)]),
+        Some("blinker")
+    );
+}